@@ -0,0 +1,198 @@
+use criterion::{criterion_group, criterion_main, BenchmarkId, Criterion};
+use wasm2spirv::{config::Config, Compilation};
+
+/// Number of functions of the large fixture
+const LARGE_FUNCTIONS: usize = 256;
+
+fn fixture(config: &str, wat: &[u8]) -> (Config, Vec<u8>) {
+    let config = serde_json::from_str(config).unwrap();
+    let wasm = wat::parse_bytes(wat).unwrap().into_owned();
+    return (config, wasm);
+}
+
+/// A compute shader that passes a value of its storage buffer through a chain of `functions` functions, each with
+/// a branch and a loop
+fn large_fixture(functions: usize) -> (Config, Vec<u8>) {
+    let mut wat = String::from(
+        r#"(module
+  (func (export "main") (param i32)
+    local.get 0
+    local.get 0
+    f32.load
+"#,
+    );
+    for i in 0..functions {
+        wat.push_str(&format!("    call $f{i}\n"));
+    }
+    wat.push_str("    f32.store)\n");
+
+    for i in 0..functions {
+        wat.push_str(&format!(
+            r#"  (func $f{i} (param f32) (result f32)
+    (local i32)
+    local.get 0
+    f32.const 0
+    f32.gt
+    if (result f32)
+      local.get 0
+      f32.const {i}
+      f32.mul
+    else
+      local.get 0
+      f32.const {i}
+      f32.sub
+    end
+    local.set 0
+    loop
+      local.get 0
+      f32.const 0.5
+      f32.mul
+      local.set 0
+      local.get 1
+      i32.const 1
+      i32.add
+      local.tee 1
+      i32.const 4
+      i32.lt_u
+      br_if 0
+    end
+    local.get 0)
+"#
+        ));
+    }
+    wat.push_str("  (memory 1))\n");
+
+    let config = serde_json::json!({
+        "platform": { "vulkan": "1.1" },
+        "addressing_model": "logical",
+        "memory_model": "GLSL450",
+        "capabilities": { "dynamic": [] },
+        "extensions": [],
+        "functions": {
+            "0": {
+                "execution_model": "GLCompute",
+                "execution_modes": [{ "local_size": [1, 1, 1] }],
+                "params": {
+                    "0": {
+                        "type": {
+                            "size": "fat",
+                            "storage_class": "StorageBuffer",
+                            "pointee": "f32"
+                        },
+                        "kind": {
+                            "descriptor_set": {
+                                "storage_class": "StorageBuffer",
+                                "set": 0,
+                                "binding": 0
+                            }
+                        }
+                    }
+                }
+            }
+        }
+    });
+
+    let config = serde_json::from_value(config).unwrap();
+    let wasm = wat::parse_str(wat).unwrap();
+    return (config, wasm);
+}
+
+fn fixtures() -> [(&'static str, (Config, Vec<u8>)); 3] {
+    return [
+        (
+            "small",
+            fixture(
+                include_str!("../examples/square/square.json"),
+                include_bytes!("../examples/square/square.wat"),
+            ),
+        ),
+        (
+            "medium",
+            fixture(
+                include_str!("../examples/saxpy/saxpy.json"),
+                include_bytes!("../examples/saxpy/saxpy.wat"),
+            ),
+        ),
+        ("large", large_fixture(LARGE_FUNCTIONS)),
+    ];
+}
+
+fn saxpy(c: &mut Criterion) {
+    let config: Config =
+        serde_json::from_str(include_str!("../examples/saxpy/saxpy.json")).unwrap();
+    let wasm = wat::parse_bytes(include_bytes!("../examples/saxpy/saxpy.wat")).unwrap();
+
+    let mut group = c.benchmark_group("saxpy");
+    group.bench_function("owned", |b| {
+        b.iter(|| {
+            let compilation = Compilation::new(config.clone(), &wasm).unwrap();
+            compilation.into_words().unwrap()
+        })
+    });
+    group.bench_function("borrowed", |b| {
+        b.iter(|| {
+            let compilation = Compilation::new_borrowed(config.clone(), &wasm).unwrap();
+            compilation.words().unwrap().len()
+        })
+    });
+    group.bench_function("borrowed_untranslated", |b| {
+        b.iter(|| Compilation::new_borrowed(config.clone(), &wasm).unwrap())
+    });
+    group.finish();
+}
+
+/// Parsing and translation of fixtures of increasing size
+fn translation(c: &mut Criterion) {
+    let mut group = c.benchmark_group("translation");
+    for (name, (config, wasm)) in fixtures() {
+        group.bench_with_input(BenchmarkId::from_parameter(name), &wasm, |b, wasm| {
+            b.iter(|| {
+                let compilation = Compilation::new(config.clone(), wasm).unwrap();
+                compilation.into_words().unwrap()
+            })
+        });
+    }
+    group.finish();
+}
+
+/// Cross-compilation of the already translated fixtures, with every enabled backend
+#[allow(unused_variables, unused_mut)]
+fn cross_compilation(c: &mut Criterion) {
+    let mut group = c.benchmark_group("cross_compilation");
+    for (name, (config, wasm)) in fixtures() {
+        let compilation = Compilation::new(config, &wasm).unwrap();
+
+        #[cfg(feature = "naga-wgsl")]
+        group.bench_function(BenchmarkId::new("naga_wgsl", name), |b| {
+            b.iter(|| compilation.naga_wgsl().unwrap())
+        });
+        #[cfg(feature = "naga-glsl")]
+        group.bench_function(BenchmarkId::new("naga_glsl", name), |b| {
+            b.iter(|| compilation.naga_glsl().unwrap())
+        });
+        #[cfg(feature = "naga-hlsl")]
+        group.bench_function(BenchmarkId::new("naga_hlsl", name), |b| {
+            b.iter(|| compilation.naga_hlsl().unwrap())
+        });
+        #[cfg(feature = "naga-msl")]
+        group.bench_function(BenchmarkId::new("naga_msl", name), |b| {
+            b.iter(|| compilation.naga_msl().unwrap())
+        });
+        #[cfg(feature = "spvc-glsl")]
+        group.bench_function(BenchmarkId::new("spvc_glsl", name), |b| {
+            b.iter(|| compilation.spvc_glsl().unwrap())
+        });
+        #[cfg(feature = "spvc-hlsl")]
+        group.bench_function(BenchmarkId::new("spvc_hlsl", name), |b| {
+            b.iter(|| compilation.spvc_hlsl().unwrap())
+        });
+        #[cfg(feature = "spvc-msl")]
+        group.bench_function(BenchmarkId::new("spvc_msl", name), |b| {
+            b.iter(|| compilation.spvc_msl().unwrap())
+        });
+    }
+    group.finish();
+}
+
+criterion_group!(benches, saxpy, translation, cross_compilation);
+criterion_main!(benches);
@@ -0,0 +1,46 @@
+use wasm2spirv::{compilers::GlslOptions, config::Config};
+use wasm_bindgen::prelude::*;
+
+/// Compiles a WebAssembly module into SPIR-V, returning the resulting bytes
+#[wasm_bindgen]
+pub fn compile(config: &str, bytes: &[u8]) -> Result<Vec<u8>, JsError> {
+    return Compilation::new(config, bytes)?.bytes();
+}
+
+#[wasm_bindgen]
+pub struct Compilation {
+    inner: wasm2spirv::Compilation,
+}
+
+#[wasm_bindgen]
+impl Compilation {
+    #[wasm_bindgen(constructor)]
+    pub fn new(config: &str, bytes: &[u8]) -> Result<Compilation, JsError> {
+        let config: Config = serde_json::from_str(config)?;
+        let inner = wasm2spirv::Compilation::new(config, bytes)?;
+        return Ok(Self { inner });
+    }
+
+    pub fn bytes(&self) -> Result<Vec<u8>, JsError> {
+        return Ok(self.inner.bytes()?.to_vec());
+    }
+
+    pub fn assembly(&self) -> Result<String, JsError> {
+        return Ok(self.inner.assembly()?.to_string());
+    }
+
+    pub fn glsl(&self) -> Result<String, JsError> {
+        return Ok(self.inner.glsl()?);
+    }
+
+    /// Compiles into GLSL with the given options, as a JSON-encoded `GlslOptions`
+    #[wasm_bindgen(js_name = glslWith)]
+    pub fn glsl_with(&self, options: &str) -> Result<String, JsError> {
+        let options: GlslOptions = serde_json::from_str(options)?;
+        return Ok(self.inner.glsl_with(&options)?);
+    }
+
+    pub fn wgsl(&self) -> Result<String, JsError> {
+        return Ok(self.inner.wgsl()?);
+    }
+}
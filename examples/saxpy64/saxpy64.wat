@@ -0,0 +1,37 @@
+(module
+  (type (;0;) (func (param i32) (result i32)))
+  (type (;1;) (func (param i32 f32 i64 i64)))
+  (import "spir_global" "gl_GlobalInvocationID" (func (;0;) (type 0)))
+  (func (;1;) (type 1) (param i32 f32 i64 i64)
+    (local i64)
+    block  ;; label = @1
+      i32.const 0
+      call 0
+      local.get 0
+      i32.ge_u
+      br_if 0 (;@1;)
+      i32.const 0
+      call 0
+      i64.extend_i32_u
+      i64.const 2
+      i64.shl
+      local.set 4
+      local.get 3
+      local.get 4
+      i64.add
+      local.get 2
+      local.get 4
+      i64.add
+      f32.load
+      local.get 1
+      f32.mul
+      local.get 3
+      local.get 4
+      i64.add
+      f32.load
+      f32.add
+      f32.store
+    end)
+  (memory (;0;) i64 16)
+  (export "memory" (memory 0))
+  (export "main" (func 1)))
@@ -0,0 +1,15 @@
+#include <stddef.h>
+
+__attribute__((import_module("spir_global"), import_name("gl_GlobalInvocationID")))
+size_t gl_GlobalInvocationID(unsigned int);
+
+__attribute__((import_module("spir_global"), import_name("gl_NumWorkGroups")))
+size_t gl_NumWorkGroups(unsigned int);
+
+__attribute__((export_name("main")))
+void saxpy(size_t n, float alpha, const float *x, float *y) {
+    size_t size = gl_NumWorkGroups(0);
+    for (size_t i = gl_GlobalInvocationID(0); i < n; i += size) {
+        y[i] += alpha * x[i];
+    }
+}
@@ -1,13 +1,16 @@
 use crate::{
-    compiler::{rust::RustCompiler, zig::ZigCompiler, Compiler},
+    compiler::{c::CCompiler, rust::RustCompiler, zig::ZigCompiler, Compiler},
     rate_limit::{LimitHandler, LimitInfo, RateLimit},
-    Result,
+    share, Error, Result,
 };
-use axum::{routing::post, Json, Router};
+use axum::{extract::Multipart, routing::post, Json, Router};
 use color_eyre::Report;
-use serde::{Deserialize, Serialize};
+use serde::{de::DeserializeOwned, Deserialize, Serialize};
 use std::{borrow::Cow, panic::catch_unwind, time::Duration};
-use wasm2spirv::config::Config;
+use wasm2spirv::{
+    compilers::spvt::{OptimizationPreset, OptimizationStats},
+    config::Config,
+};
 
 #[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Deserialize, Serialize)]
 #[serde(rename_all = "snake_case")]
@@ -15,6 +18,7 @@ pub enum Language {
     Wasm,
     Rust,
     Zig,
+    C,
 }
 
 #[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Deserialize, Serialize)]
@@ -27,24 +31,146 @@ pub enum CompilationLanguage {
     Wgsl,
 }
 
-#[derive(Debug, Clone, Deserialize)]
+#[derive(Debug, Clone, Deserialize, Serialize)]
 pub struct CompileBody {
     source: String,
     lang: Language,
     compile_lang: CompilationLanguage,
     config: Config,
     optimization_runs: u8,
+    #[serde(default)]
+    optimization_preset: OptimizationPreset,
 }
 
 #[derive(Debug, Clone, PartialEq, Serialize)]
 pub struct CompileResponse {
     wat: String,
     result: Result<String, Cow<'static, str>>,
+    optimization_stats: Option<OptimizationStats>,
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub struct RawCompileResponse {
+    result: Result<String, Diagnostic>,
+    optimization_stats: Option<OptimizationStats>,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Serialize)]
+#[serde(rename_all = "snake_case")]
+pub enum CompilationStage {
+    Parsing,
+    Translation,
+    Validation,
+    Optimization,
+    CrossCompilation,
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub struct Diagnostic {
+    stage: CompilationStage,
+    message: Cow<'static, str>,
 }
 
 async fn compile(Json(body): Json<CompileBody>) -> Result<Json<CompileResponse>> {
+    return compile_body(body).await.map(Json);
+}
+
+pub(crate) async fn compile_body(body: CompileBody) -> Result<CompileResponse> {
+    let wasm = match body.lang {
+        Language::Wasm => wat::parse_str(&body.source)?,
+        Language::Rust => RustCompiler.compile(&body.source).await?,
+        Language::Zig => ZigCompiler.compile(&body.source).await?,
+        Language::C => CCompiler.compile(&body.source).await?,
+    };
+
+    let wat = match body.lang {
+        Language::Wasm => body.source,
+        _ => wasmprinter::print_bytes(&wasm).map_err(Report::msg)?,
+    };
+
+    let (result, optimization_stats) = run_compilation(
+        &wasm,
+        body.config,
+        body.compile_lang,
+        body.optimization_runs,
+        body.optimization_preset,
+    );
+
+    return Ok(CompileResponse {
+        wat,
+        result: result.map_err(|e| e.message),
+        optimization_stats,
+    });
+}
+
+/// Compiles an uploaded WebAssembly binary (or text) module.
+///
+/// Expects a multipart body with a `module` file, a `config` JSON field and a `compile_lang` field,
+/// optionally followed by `optimization_runs` and `optimization_preset`.
+async fn compile_raw(mut multipart: Multipart) -> Result<Json<RawCompileResponse>> {
+    let mut module = None;
+    let mut config = None;
+    let mut compile_lang = CompilationLanguage::Spirv;
+    let mut optimization_runs = 0;
+    let mut optimization_preset = OptimizationPreset::default();
+
+    while let Some(field) = multipart.next_field().await? {
+        match field.name() {
+            Some("module") => module = Some(field.bytes().await?),
+            Some("config") => config = Some(serde_json::from_str(&field.text().await?)?),
+            Some("compile_lang") => compile_lang = from_text(field.text().await?)?,
+            Some("optimization_runs") => optimization_runs = field.text().await?.parse()?,
+            Some("optimization_preset") => optimization_preset = from_text(field.text().await?)?,
+            _ => continue,
+        }
+    }
+
+    let module = module.ok_or_else(|| Error::msg("Missing 'module' field"))?;
+    let config: Config = config.ok_or_else(|| Error::msg("Missing 'config' field"))?;
+
+    let wasm = match wat::parse_bytes(&module) {
+        Ok(wasm) => wasm,
+        Err(e) => {
+            return Ok(RawCompileResponse {
+                result: Err(Diagnostic {
+                    stage: CompilationStage::Parsing,
+                    message: Cow::Owned(e.to_string()),
+                }),
+                optimization_stats: None,
+            }
+            .into())
+        }
+    };
+
+    let (result, optimization_stats) = run_compilation(
+        &wasm,
+        config,
+        compile_lang,
+        optimization_runs,
+        optimization_preset,
+    );
+
+    return Ok(RawCompileResponse {
+        result,
+        optimization_stats,
+    }
+    .into());
+}
+
+/// Deserializes a unit enum variant from its plain-text name
+fn from_text<T: DeserializeOwned>(text: String) -> Result<T> {
+    return Ok(serde_json::from_value(serde_json::Value::String(text))?);
+}
+
+fn run_compilation(
+    wasm: &[u8],
+    config: Config,
+    compile_lang: CompilationLanguage,
+    optimization_runs: u8,
+    optimization_preset: OptimizationPreset,
+) -> (Result<String, Diagnostic>, Option<OptimizationStats>) {
     macro_rules! tri {
-        ($e:expr) => {
+        ($stage:ident: $e:expr) => {
             match catch_unwind(std::panic::AssertUnwindSafe(|| $e)) {
                 Ok(Ok(x)) => Ok(x),
                 Ok(Err(e)) => Err(Cow::Owned(e.to_string())),
@@ -58,45 +184,55 @@ async fn compile(Json(body): Json<CompileBody>) -> Result<Json<CompileResponse>>
                     }
                 }
             }
+            .map_err(|message| Diagnostic {
+                stage: CompilationStage::$stage,
+                message,
+            })
         };
     }
 
-    let wasm = match body.lang {
-        Language::Wasm => wat::parse_str(&body.source)?,
-        Language::Rust => RustCompiler.compile(&body.source).await?,
-        Language::Zig => ZigCompiler.compile(&body.source).await?,
-    };
-
-    let wat = match body.lang {
-        Language::Wasm => body.source,
-        _ => wasmprinter::print_bytes(&wasm).map_err(Report::msg)?,
-    };
+    let mut result = tri!(Translation: wasm2spirv::Compilation::new(config, wasm))
+        .and_then(|result| tri!(Validation: result.validate()).map(|_| result));
 
-    let mut result = tri!(wasm2spirv::Compilation::new(body.config, &wasm))
-        .and_then(|result| tri!(result.validate()).map(|_| result));
-
-    for _ in 0..u8::min(body.optimization_runs, 3) {
-        result = result.and_then(|result| tri!(result.into_optimized()));
+    let mut optimization_stats = None::<OptimizationStats>;
+    for _ in 0..u8::min(optimization_runs, 3) {
+        result = result.and_then(|result| {
+            let (result, stats) = tri!(Optimization: result.optimized_with(optimization_preset))?;
+            optimization_stats = Some(match optimization_stats {
+                Some(prev) => OptimizationStats {
+                    instructions_before: prev.instructions_before,
+                    words_before: prev.words_before,
+                    ..stats
+                },
+                None => stats,
+            });
+            Ok(result)
+        });
     }
 
-    let result = result.and_then(|result| {
-        tri!(match body.compile_lang {
-            CompilationLanguage::Spirv => result.into_assembly(),
-            CompilationLanguage::Glsl => result.glsl(),
-            CompilationLanguage::Hlsl => result.hlsl(),
-            CompilationLanguage::Msl => result.msl(),
-            CompilationLanguage::Wgsl => result.wgsl(),
-        })
+    let result = result.and_then(|result| match compile_lang {
+        CompilationLanguage::Spirv => tri!(CrossCompilation: result.into_assembly()),
+        CompilationLanguage::Glsl => tri!(CrossCompilation: result.glsl()),
+        CompilationLanguage::Hlsl => tri!(CrossCompilation: result.hlsl()),
+        CompilationLanguage::Msl => tri!(CrossCompilation: result.msl()),
+        CompilationLanguage::Wgsl => tri!(CrossCompilation: result.wgsl()),
     });
 
-    return Ok(CompileResponse { wat, result }.into());
+    return (result, optimization_stats);
 }
 
 pub fn router() -> Router {
     return Router::new()
         .route("/compile", post(compile))
-        .layer(RateLimit::new(
-            None,
-            LimitInfo::new(1, Duration::SECOND, LimitHandler::Wait),
-        ));
+        .route("/compile/raw", post(compile_raw))
+        .route("/share", post(share::share))
+        .layer(compilation_limit());
+}
+
+/// Rate limit of the routes that compile code, shared snippets included
+pub fn compilation_limit() -> RateLimit {
+    return RateLimit::new(
+        None,
+        LimitInfo::new(1, Duration::SECOND, LimitHandler::Wait),
+    );
 }
@@ -0,0 +1,43 @@
+use super::Compiler;
+use crate::tmp::{TmpFile, TmpPath};
+use std::process::Stdio;
+use tokio::io::AsyncWriteExt;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash, Default)]
+pub struct CCompiler;
+
+impl Compiler for CCompiler {
+    async fn compile(&self, source: &str) -> Result<Vec<u8>, crate::Error> {
+        let mut tmp_file = TmpFile::new("c").await?;
+        tmp_file.write_all(source.as_bytes()).await?;
+
+        let target_path = tmp_file.drop_handle().await?;
+        let target_wasm_path = TmpPath::from(target_path.with_extension("wasm"));
+
+        // clang examples/{{TEST}}/{{TEST}}.c --target=wasm32-unknown-unknown -nostdlib -Os -Wl,--no-entry -Wl,--export-dynamic -o examples/out/{{TEST}}.wasm
+        let output = tokio::process::Command::new("clang")
+            .arg(&target_path)
+            .args([
+                "--target=wasm32-unknown-unknown",
+                "-nostdlib",
+                "-Os",
+                "-Wl,--no-entry",
+                "-Wl,--export-dynamic",
+                "-o",
+            ])
+            .arg(&target_wasm_path)
+            .kill_on_drop(true)
+            .stderr(Stdio::piped())
+            .output()
+            .await?;
+
+        if !output.status.success() {
+            let message = String::from_utf8_lossy(&output.stderr);
+            return Err(color_eyre::Report::msg(message.into_owned()).into());
+        }
+
+        let content = tokio::fs::read(&target_wasm_path).await?;
+        drop(target_wasm_path);
+        return Ok(content);
+    }
+}
@@ -1,3 +1,4 @@
+pub mod c;
 pub mod rust;
 pub mod zig;
 
@@ -9,7 +9,8 @@
 
 use axum::http::StatusCode;
 use axum::response::IntoResponse;
-use axum::Router;
+use axum::routing::get;
+use axum::{Extension, Router};
 use color_eyre::Report;
 use std::fmt::{Debug, Display};
 use std::net::SocketAddr;
@@ -24,6 +25,7 @@ use crate::rate_limit::{LimitHandler, LimitInfo, RateLimit};
 pub mod api;
 pub mod compiler;
 pub mod rate_limit;
+pub mod share;
 pub mod tmp;
 
 pub type Result<T, E = Error> = ::std::result::Result<T, E>;
@@ -42,13 +44,17 @@ async fn main() -> color_eyre::Result<()> {
 
     info!("Path of the HTML file: {}", html_path.display());
 
+    let db = sled::open(".share")?;
+
     // build our application with a single route
     let app = Router::new()
         .nest("/api", api::router())
+        .route("/s/:id", get(share::load).layer(api::compilation_limit()))
         .nest_service(
             "/",
             ServeDir::new(html_path).append_index_html_on_directories(false),
         )
+        .layer(Extension(db))
         .layer(
             TraceLayer::new_for_http()
                 .make_span_with(DefaultMakeSpan::new().level(Level::INFO))
@@ -0,0 +1,52 @@
+use crate::{
+    api::{compile_body, CompileBody, CompileResponse},
+    Error, Result,
+};
+use axum::{extract::Path, Extension, Json};
+use serde::Serialize;
+
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+pub struct ShareResponse {
+    id: String,
+}
+
+#[derive(Debug, Clone, Serialize)]
+pub struct SharedSnippet {
+    snippet: CompileBody,
+    response: CompileResponse,
+}
+
+/// Persists the snippet, returning the id it can be retrieved with.
+pub async fn share(
+    Extension(db): Extension<sled::Db>,
+    Json(body): Json<CompileBody>,
+) -> Result<Json<ShareResponse>> {
+    let value = serde_json::to_vec(&body)?;
+
+    let id = loop {
+        let id = format!("{:016x}", rand::random::<u64>());
+        if db
+            .compare_and_swap(id.as_bytes(), None::<&[u8]>, Some(value.as_slice()))?
+            .is_ok()
+        {
+            break id;
+        }
+    };
+
+    db.flush_async().await?;
+    return Ok(Json(ShareResponse { id }));
+}
+
+/// Retrieves a shared snippet and reproduces its compilation.
+pub async fn load(
+    Extension(db): Extension<sled::Db>,
+    Path(id): Path<String>,
+) -> Result<Json<SharedSnippet>> {
+    let value = db
+        .get(id.as_bytes())?
+        .ok_or_else(|| Error::msg(format!("Snippet '{id}' not found")))?;
+
+    let snippet: CompileBody = serde_json::from_slice(&value)?;
+    let response = compile_body(snippet.clone()).await?;
+    return Ok(Json(SharedSnippet { snippet, response }));
+}
@@ -1,10 +1,16 @@
+use super::{FORMAT_VERSION, MAGIC};
 use crate::{
     config::{
-        AddressingModel, CapabilityModel, Config, ExtensionModel, MemoryGrowErrorKind, WasmFeatures,
+        AddressingModel, AtomicCounter, CapabilityModel, Config, DataSegmentMode, Float64Mode,
+        GlobalConfig, MemoryBinding, MemoryGrowErrorKind, Profile, ProfilingBuffer, SelectionHint,
+        TrapMode, Verbosity, WasmFeatures, CONFIG_SCHEMA_VERSION,
     },
     error::{Error, Result},
-    fg::function::{ExecutionMode, FunctionConfig, Parameter, ParameterKind},
-    r#type::{CompositeType, ScalarType, Type},
+    fg::function::{
+        BufferAccess, BufferKind, ExecutionMode, FastMath, FunctionConfig, LoopHints, Parameter,
+        ParameterKind, RelaxedPrecision,
+    },
+    r#type::{CompositeType, Layout, PointerSize, ScalarType, StructMember, Type},
     version::{TargetPlatform, Version},
     Str,
 };
@@ -12,6 +18,7 @@ use num_traits::cast::FromPrimitive;
 use spirv::{Capability, ExecutionModel, MemoryModel, StorageClass};
 use std::hash::Hash;
 use std::{
+    cell::RefCell,
     collections::{BTreeMap, HashMap},
     mem::{size_of, MaybeUninit},
     rc::Rc,
@@ -61,10 +68,16 @@ impl BinaryDeserialize for u32 {
 
 impl BinaryDeserialize for WasmFeatures {
     fn deserialize_from<R: ?Sized + std::io::Read>(reader: &mut R) -> Result<Self> {
-        reader
-            .read_u64()
-            .map(Self::from_integer)
-            .map_err(Into::into)
+        let bits = reader.read_u64()?;
+        if bits >> WasmFeatures::PROPOSALS.len() != 0 {
+            return Err(Error::msg("Unknown WebAssembly proposal"));
+        }
+
+        let mut result = WasmFeatures::default();
+        for (i, proposal) in WasmFeatures::PROPOSALS.iter().enumerate() {
+            result.set_enabled(proposal, bits & (1 << i) != 0);
+        }
+        return Ok(result);
     }
 }
 
@@ -82,6 +95,8 @@ impl BinaryDeserialize for TargetPlatform {
         return match kind {
             0 => Ok(Version::deserialize_from(reader).map(Self::Vulkan)?),
             1 => Ok(Version::deserialize_from(reader).map(Self::Universal)?),
+            2 => Ok(Version::deserialize_from(reader).map(Self::OpenGL)?),
+            3 => Ok(Version::deserialize_from(reader).map(Self::OpenCL)?),
             _ => Err(Error::msg("Unknown kind")),
         };
     }
@@ -124,21 +139,117 @@ impl BinaryDeserialize for CapabilityModel {
 
         return Ok(match kind {
             0 => CapabilityModel::Static(capabilities.into_boxed_slice()),
-            1 => CapabilityModel::Dynamic(capabilities),
+            1 => CapabilityModel::Dynamic(RefCell::new(capabilities)),
             _ => return Err(Error::msg("Unkown capability model")),
         });
     }
 }
 
-impl BinaryDeserialize for ExtensionModel {
+impl BinaryDeserialize for MemoryGrowErrorKind {
     fn deserialize_from<R: ?Sized + std::io::Read>(reader: &mut R) -> Result<Self> {
-        let kind = reader.read_u8()?;
-        let extensions = Vec::<Str>::deserialize_from(reader)?;
+        Self::try_from(reader.read_u8()?).map_err(Error::custom)
+    }
+}
 
-        return Ok(match kind {
-            0 => ExtensionModel::Static(extensions.into_boxed_slice()),
-            1 => ExtensionModel::Dynamic(extensions),
-            _ => return Err(Error::msg("Unkown extension model")),
+impl BinaryDeserialize for Verbosity {
+    fn deserialize_from<R: ?Sized + std::io::Read>(reader: &mut R) -> Result<Self> {
+        Self::try_from(reader.read_u8()?).map_err(Error::custom)
+    }
+}
+
+impl BinaryDeserialize for Float64Mode {
+    fn deserialize_from<R: ?Sized + std::io::Read>(reader: &mut R) -> Result<Self> {
+        Self::try_from(reader.read_u8()?).map_err(Error::custom)
+    }
+}
+
+impl BinaryDeserialize for TrapMode {
+    fn deserialize_from<R: ?Sized + std::io::Read>(reader: &mut R) -> Result<Self> {
+        return Ok(match reader.read_u8()? {
+            0 => Self::Unreachable,
+            1 => Self::InfiniteLoop,
+            2 => Self::Flag {
+                set: reader.read_u32()?,
+                binding: reader.read_u32()?,
+            },
+            3 => Self::TerminateInvocation,
+            4 => Self::DemoteToHelperInvocation,
+            _ => return Err(Error::msg("Unknown trap mode")),
+        });
+    }
+}
+
+impl BinaryDeserialize for SelectionHint {
+    fn deserialize_from<R: ?Sized + std::io::Read>(reader: &mut R) -> Result<Self> {
+        return Ok(match reader.read_u8()? {
+            0 => Self::Flatten,
+            1 => Self::DontFlatten,
+            _ => return Err(Error::msg("Unknown selection hint")),
+        });
+    }
+}
+
+impl BinaryDeserialize for MemoryBinding {
+    fn deserialize_from<R: ?Sized + std::io::Read>(reader: &mut R) -> Result<Self> {
+        return Ok(Self {
+            set: reader.read_u32()?,
+            binding: reader.read_u32()?,
+        });
+    }
+}
+
+impl BinaryDeserialize for ProfilingBuffer {
+    fn deserialize_from<R: ?Sized + std::io::Read>(reader: &mut R) -> Result<Self> {
+        return Ok(Self {
+            set: reader.read_u32()?,
+            binding: reader.read_u32()?,
+        });
+    }
+}
+
+impl BinaryDeserialize for Profile {
+    fn deserialize_from<R: ?Sized + std::io::Read>(reader: &mut R) -> Result<Self> {
+        return Ok(Self {
+            platform: BinaryDeserialize::deserialize_from(reader)?,
+            capabilities: BinaryDeserialize::deserialize_from(reader)?,
+            extensions: BinaryDeserialize::deserialize_from(reader)?,
+        });
+    }
+}
+
+impl BinaryDeserialize for AtomicCounter {
+    fn deserialize_from<R: ?Sized + std::io::Read>(reader: &mut R) -> Result<Self> {
+        return Ok(Self {
+            set: reader.read_u32()?,
+            binding: reader.read_u32()?,
+        });
+    }
+}
+
+impl BinaryDeserialize for GlobalConfig {
+    fn deserialize_from<R: ?Sized + std::io::Read>(reader: &mut R) -> Result<Self> {
+        return Ok(match reader.read_u8()? {
+            0 => Self::DescriptorSet {
+                set: reader.read_u32()?,
+                binding: reader.read_u32()?,
+                buffer: BinaryDeserialize::deserialize_from(reader)?,
+            },
+            1 => Self::PushConstant,
+            _ => return Err(Error::msg("Unknown global config")),
+        });
+    }
+}
+
+impl BinaryDeserialize for DataSegmentMode {
+    fn deserialize_from<R: ?Sized + std::io::Read>(reader: &mut R) -> Result<Self> {
+        return Ok(match reader.read_u8()? {
+            0 => Self::Ignore,
+            1 => Self::Constant,
+            2 => Self::Buffer {
+                set: reader.read_u32()?,
+                binding: reader.read_u32()?,
+            },
+            _ => return Err(Error::msg("Unknown data segment mode")),
         });
     }
 }
@@ -158,6 +269,36 @@ impl BinaryDeserialize for ExecutionMode {
                 reader.read_u32()?,
                 reader.read_u32()?,
             ),
+            6 => ExecutionMode::DepthReplacing,
+            7 => ExecutionMode::DepthGreater,
+            8 => ExecutionMode::DepthLess,
+            9 => ExecutionMode::DepthUnchanged,
+            10 => ExecutionMode::EarlyFragmentTests,
+            11 => ExecutionMode::InputPoints,
+            12 => ExecutionMode::InputLines,
+            13 => ExecutionMode::InputLinesAdjacency,
+            14 => ExecutionMode::Triangles,
+            15 => ExecutionMode::InputTrianglesAdjacency,
+            16 => ExecutionMode::Quads,
+            17 => ExecutionMode::Isolines,
+            18 => reader.read_u32().map(ExecutionMode::OutputVertices)?,
+            19 => ExecutionMode::OutputPoints,
+            20 => ExecutionMode::OutputLineStrip,
+            21 => ExecutionMode::OutputTriangleStrip,
+            22 => ExecutionMode::SpacingEqual,
+            23 => ExecutionMode::SpacingFractionalEven,
+            24 => ExecutionMode::SpacingFractionalOdd,
+            25 => ExecutionMode::VertexOrderCw,
+            26 => ExecutionMode::VertexOrderCcw,
+            27 => ExecutionMode::PointMode,
+            28 => reader.read_u32().map(ExecutionMode::OutputPrimitives)?,
+            29 => ExecutionMode::OutputLines,
+            30 => ExecutionMode::OutputTriangles,
+            31 => ExecutionMode::LocalSizeId(
+                reader.read_u32()?,
+                reader.read_u32()?,
+                reader.read_u32()?,
+            ),
             _ => return Err(Error::msg("Unknown execution mode")),
         });
     }
@@ -172,25 +313,50 @@ impl BinaryDeserialize for ScalarType {
 impl BinaryDeserialize for CompositeType {
     fn deserialize_from<R: ?Sized + std::io::Read>(reader: &mut R) -> Result<Self> {
         return match reader.read_u16()? {
-            0 => ScalarType::deserialize_from(reader).map(CompositeType::Structured),
-            1 => ScalarType::deserialize_from(reader).map(CompositeType::StructuredArray),
-            2 => {
+            0 => {
                 let elem = ScalarType::deserialize_from(reader)?;
                 let count = reader.read_u32()?;
                 Ok(CompositeType::Vector(elem, count))
             }
+            1 => {
+                let columns = reader.read_u32()?;
+                let rows = reader.read_u32()?;
+                let elem = ScalarType::deserialize_from(reader)?;
+                Ok(CompositeType::Matrix(columns, rows, elem))
+            }
+            2 => Box::<[StructMember]>::deserialize_from(reader).map(CompositeType::Struct),
             _ => return Err(Error::msg("Unknown composite type")),
         };
     }
 }
 
+impl BinaryDeserialize for StructMember {
+    fn deserialize_from<R: ?Sized + std::io::Read>(reader: &mut R) -> Result<Self> {
+        let name = Box::<str>::deserialize_from(reader)?;
+        let ty = Type::deserialize_from(reader)?;
+        let offset = reader.read_u32()?;
+        return Ok(StructMember { name, ty, offset });
+    }
+}
+
+impl BinaryDeserialize for PointerSize {
+    fn deserialize_from<R: ?Sized + std::io::Read>(reader: &mut R) -> Result<Self> {
+        return Ok(match reader.read_u8()? {
+            0 => Self::Skinny,
+            1 => Self::Fat,
+            _ => return Err(Error::msg("Unknown pointer size")),
+        });
+    }
+}
+
 impl BinaryDeserialize for Type {
     fn deserialize_from<R: ?Sized + std::io::Read>(reader: &mut R) -> Result<Self> {
         return match reader.read_u16()? {
             0 => {
+                let size = PointerSize::deserialize_from(reader)?;
                 let storage_class = StorageClass::deserialize_from(reader)?;
                 let pointee = Type::deserialize_from(reader)?;
-                Ok(Type::Pointer(storage_class, Box::new(pointee)))
+                Ok(Type::pointer(size, storage_class, pointee))
             }
             1 => ScalarType::deserialize_from(reader).map(Self::Scalar),
             2 => CompositeType::deserialize_from(reader).map(Self::Composite),
@@ -199,9 +365,36 @@ impl BinaryDeserialize for Type {
     }
 }
 
-impl BinaryDeserialize for MemoryGrowErrorKind {
+impl BinaryDeserialize for Layout {
     fn deserialize_from<R: ?Sized + std::io::Read>(reader: &mut R) -> Result<Self> {
-        Self::try_from(reader.read_u8()?).map_err(Error::custom)
+        return Ok(match reader.read_u8()? {
+            0 => Self::Std140,
+            1 => Self::Std430,
+            _ => return Err(Error::msg("Unknown layout")),
+        });
+    }
+}
+
+impl BinaryDeserialize for BufferKind {
+    fn deserialize_from<R: ?Sized + std::io::Read>(reader: &mut R) -> Result<Self> {
+        return Ok(match reader.read_u8()? {
+            0 => Self::Storage,
+            1 => Self::Uniform,
+            _ => return Err(Error::msg("Unknown buffer kind")),
+        });
+    }
+}
+
+impl BinaryDeserialize for BufferAccess {
+    fn deserialize_from<R: ?Sized + std::io::Read>(reader: &mut R) -> Result<Self> {
+        return Ok(Self {
+            read_only: BinaryDeserialize::deserialize_from(reader)?,
+            write_only: BinaryDeserialize::deserialize_from(reader)?,
+            restrict: BinaryDeserialize::deserialize_from(reader)?,
+            aliased: BinaryDeserialize::deserialize_from(reader)?,
+            volatile: BinaryDeserialize::deserialize_from(reader)?,
+            coherent: BinaryDeserialize::deserialize_from(reader)?,
+        });
     }
 }
 
@@ -209,13 +402,19 @@ impl BinaryDeserialize for ParameterKind {
     fn deserialize_from<R: ?Sized + std::io::Read>(reader: &mut R) -> Result<Self> {
         return Ok(match reader.read_u16()? {
             0 => Self::FunctionParameter,
-            1 => Self::Input,
-            2 => Self::Output,
+            1 => Self::Input(reader.read_u32()?),
+            2 => Self::Output(reader.read_u32()?),
             3 => Self::DescriptorSet {
                 storage_class: StorageClass::deserialize_from(reader)?,
                 set: reader.read_u32()?,
                 binding: reader.read_u32()?,
+                access: BinaryDeserialize::deserialize_from(reader)?,
+                buffer: BinaryDeserialize::deserialize_from(reader)?,
+                layout: BinaryDeserialize::deserialize_from(reader)?,
             },
+            4 => Self::ShaderRecordBuffer,
+            5 => Self::RayPayload(reader.read_u32()?),
+            6 => Self::IncomingRayPayload,
             _ => return Err(Error::msg("Unknown parameter kind")),
         });
     }
@@ -226,32 +425,188 @@ impl BinaryDeserialize for Parameter {
         return Ok(Self {
             ty: BinaryDeserialize::deserialize_from(reader)?,
             kind: BinaryDeserialize::deserialize_from(reader)?,
-            is_extern_pointer: BinaryDeserialize::deserialize_from(reader)?,
+            relaxed_precision: BinaryDeserialize::deserialize_from(reader)?,
         });
     }
 }
 
-impl BinaryDeserialize for FunctionConfig {
+impl BinaryDeserialize for LoopHints {
+    fn deserialize_from<R: ?Sized + std::io::Read>(reader: &mut R) -> Result<Self> {
+        return Ok(Self {
+            unroll: BinaryDeserialize::deserialize_from(reader)?,
+            dependency_length: BinaryDeserialize::deserialize_from(reader)?,
+        });
+    }
+}
+
+impl BinaryDeserialize for RelaxedPrecision {
+    fn deserialize_from<R: ?Sized + std::io::Read>(reader: &mut R) -> Result<Self> {
+        return Ok(Self {
+            return_value: BinaryDeserialize::deserialize_from(reader)?,
+            arithmetic: BinaryDeserialize::deserialize_from(reader)?,
+        });
+    }
+}
+
+impl BinaryDeserialize for FastMath {
     fn deserialize_from<R: ?Sized + std::io::Read>(reader: &mut R) -> Result<Self> {
         return Ok(Self {
-            execution_model: BinaryDeserialize::deserialize_from(reader)?,
-            execution_mode: BinaryDeserialize::deserialize_from(reader)?,
-            params: BinaryDeserialize::deserialize_from(reader)?,
+            no_contraction: BinaryDeserialize::deserialize_from(reader)?,
+            not_nan: BinaryDeserialize::deserialize_from(reader)?,
+            not_inf: BinaryDeserialize::deserialize_from(reader)?,
+            no_signed_zeros: BinaryDeserialize::deserialize_from(reader)?,
+            allow_recip: BinaryDeserialize::deserialize_from(reader)?,
+            fast: BinaryDeserialize::deserialize_from(reader)?,
         });
     }
 }
 
+impl BinaryDeserialize for FunctionConfig {
+    fn deserialize_from<R: ?Sized + std::io::Read>(reader: &mut R) -> Result<Self> {
+        return deserialize_function(reader, FORMAT_VERSION);
+    }
+}
+
+fn deserialize_function<R: ?Sized + std::io::Read>(
+    reader: &mut R,
+    format: u32,
+) -> Result<FunctionConfig> {
+    return Ok(FunctionConfig {
+        execution_model: BinaryDeserialize::deserialize_from(reader)?,
+        execution_modes: BinaryDeserialize::deserialize_from(reader)?,
+        params: BinaryDeserialize::deserialize_from(reader)?,
+        loops: BinaryDeserialize::deserialize_from(reader)?,
+        selection_control: BinaryDeserialize::deserialize_from(reader)?,
+        relaxed_precision: BinaryDeserialize::deserialize_from(reader)?,
+        // Added in the tenth version of the format
+        fast_math: match format {
+            1..=9 => FastMath::default(),
+            _ => BinaryDeserialize::deserialize_from(reader)?,
+        },
+    });
+}
+
+fn deserialize_functions<R: ?Sized + std::io::Read>(
+    reader: &mut R,
+    format: u32,
+) -> Result<VecMap<u32, FunctionConfig>> {
+    let len = reader.read_u32()? as usize;
+
+    let mut result = VecMap::with_capacity(len);
+    for _ in 0..len {
+        let key = u32::deserialize_from(reader)?;
+        let value = deserialize_function(reader, format)?;
+        result.insert(key, value);
+    }
+
+    return Ok(result);
+}
+
 impl BinaryDeserialize for Config {
     fn deserialize_from<R: ?Sized + std::io::Read>(reader: &mut R) -> Result<Self> {
+        let mut header = [0; 4];
+        reader.read_exact(&mut header)?;
+
+        let (format, schema) = match header {
+            MAGIC => match reader.read_u32()? {
+                version @ 2..=FORMAT_VERSION => (version, reader.read_u32()?),
+                version if version > FORMAT_VERSION => {
+                    return Err(Error::msg(format!(
+                        "The config was produced by a newer wasm2spirv (binary format version {version}, but only versions up to {FORMAT_VERSION} are supported)"
+                    )))
+                }
+                version => {
+                    return Err(Error::msg(format!(
+                        "Unknown binary config format version {version}"
+                    )))
+                }
+            },
+            // The first version of the format had no header, and started with the schema version
+            _ => match u32::from_le_bytes(header) {
+                1 => (1, 1),
+                _ => return Err(Error::msg("Not a wasm2spirv binary config")),
+            },
+        };
+
+        if schema > CONFIG_SCHEMA_VERSION {
+            return Err(Error::msg(format!(
+                "The config was produced by a newer wasm2spirv (schema version {schema}, but only versions up to {CONFIG_SCHEMA_VERSION} are supported)"
+            )));
+        }
+
         return Ok(Self {
+            schema,
             platform: BinaryDeserialize::deserialize_from(reader)?,
             features: BinaryDeserialize::deserialize_from(reader)?,
             addressing_model: BinaryDeserialize::deserialize_from(reader)?,
             memory_model: BinaryDeserialize::deserialize_from(reader)?,
             capabilities: BinaryDeserialize::deserialize_from(reader)?,
             extensions: BinaryDeserialize::deserialize_from(reader)?,
-            functions: BinaryDeserialize::deserialize_from(reader)?,
             memory_grow_error: BinaryDeserialize::deserialize_from(reader)?,
+            trap_mode: BinaryDeserialize::deserialize_from(reader)?,
+            bounds_checks: BinaryDeserialize::deserialize_from(reader)?,
+            selection_control: BinaryDeserialize::deserialize_from(reader)?,
+            inline_threshold: BinaryDeserialize::deserialize_from(reader)?,
+            memories: BinaryDeserialize::deserialize_from(reader)?,
+            globals: BinaryDeserialize::deserialize_from(reader)?,
+            data_segments: BinaryDeserialize::deserialize_from(reader)?,
+            sort_globals: BinaryDeserialize::deserialize_from(reader)?,
+            phi_merges: BinaryDeserialize::deserialize_from(reader)?,
+            // Added in the third version of the format
+            profiling: match format {
+                1 | 2 => None,
+                _ => BinaryDeserialize::deserialize_from(reader)?,
+            },
+            // Added in the fourth version of the format
+            atomic_counters: match format {
+                1..=3 => VecMap::new(),
+                _ => BinaryDeserialize::deserialize_from(reader)?,
+            },
+            // Added in the fifth version of the format
+            profiles: match format {
+                1..=4 => BTreeMap::new(),
+                _ => BinaryDeserialize::deserialize_from(reader)?,
+            },
+            // Added in the sixth version of the format
+            entry_point_names: match format {
+                1..=5 => BTreeMap::new(),
+                _ => BinaryDeserialize::deserialize_from(reader)?,
+            },
+            internal_exports: match format {
+                1..=5 => Box::default(),
+                _ => BinaryDeserialize::deserialize_from(reader)?,
+            },
+            // Added in the seventh version of the format
+            verbosity: match format {
+                1..=6 => Verbosity::default(),
+                _ => BinaryDeserialize::deserialize_from(reader)?,
+            },
+            // Added in the eighth version of the format
+            forbidden_operators: match format {
+                1..=7 => Box::default(),
+                _ => BinaryDeserialize::deserialize_from(reader)?,
+            },
+            // Added in the ninth version of the format
+            division_traps: match format {
+                1..=8 => false,
+                _ => BinaryDeserialize::deserialize_from(reader)?,
+            },
+            // Added in the eleventh version of the format
+            float64: match format {
+                1..=10 => Float64Mode::default(),
+                _ => BinaryDeserialize::deserialize_from(reader)?,
+            },
+            // Added in the twelfth version of the format
+            max_value_depth: match format {
+                1..=11 => None,
+                _ => BinaryDeserialize::deserialize_from(reader)?,
+            },
+            // Added in the thirteenth version of the format
+            linkage: match format {
+                1..=12 => false,
+                _ => BinaryDeserialize::deserialize_from(reader)?,
+            },
+            functions: deserialize_functions(reader, format)?,
         });
     }
 }
@@ -348,7 +703,7 @@ impl BinaryDeserialize for Box<str> {
     }
 }
 
-impl<T: ?Sized + BinaryDeserialize> BinaryDeserialize for Rc<T> {
+impl<T: BinaryDeserialize> BinaryDeserialize for Rc<T> {
     fn deserialize_from<R: ?Sized + std::io::Read>(reader: &mut R) -> Result<Self> {
         T::deserialize_from(reader).map(Rc::new)
     }
@@ -366,6 +721,7 @@ impl BinaryDeserialize for Rc<str> {
     }
 }
 
+#[allow(dead_code)]
 pub(crate) trait ReadLe: std::io::Read {
     fn read_i8(&mut self) -> std::io::Result<i8> {
         let mut res: MaybeUninit<[u8; size_of::<i8>()]> = MaybeUninit::uninit();
@@ -1,2 +1,11 @@
+//! Compact binary encoding of [`Config`](crate::config::Config). Configurations written by the first version of the
+//! format, which had no header, can still be decoded
+
 pub mod deserialize;
 pub mod serialize;
+
+/// Bytes every encoded configuration starts with
+pub const MAGIC: [u8; 4] = *b"W2SC";
+
+/// Version of the binary format written by [`BinarySerialize`](serialize::BinarySerialize)
+pub const FORMAT_VERSION: u32 = 13;
@@ -1,8 +1,16 @@
+use super::{FORMAT_VERSION, MAGIC};
 use crate::{
-    config::{AddressingModel, CapabilityModel, Config, ExtensionModel, WasmFeatures},
+    config::{
+        AddressingModel, AtomicCounter, CapabilityModel, Config, DataSegmentMode, Float64Mode,
+        GlobalConfig, MemoryBinding, MemoryGrowErrorKind, Profile, ProfilingBuffer, SelectionHint,
+        TrapMode, Verbosity, WasmFeatures,
+    },
     error::{Error, Result},
-    fg::function::{ExecutionMode, FunctionConfig, Parameter, ParameterKind},
-    r#type::{CompositeType, ScalarType, Type},
+    fg::function::{
+        BufferAccess, BufferKind, ExecutionMode, FastMath, FunctionConfig, LoopHints, Parameter,
+        ParameterKind, RelaxedPrecision,
+    },
+    r#type::{CompositeType, Layout, PointerSize, ScalarType, StructMember, Type},
     version::{TargetPlatform, Version},
     Str,
 };
@@ -61,7 +69,14 @@ impl BinarySerialize for u32 {
 
 impl BinarySerialize for WasmFeatures {
     fn serialize_into<W: ?Sized + std::io::Write>(&self, writer: &mut W) -> Result<()> {
-        writer.write_u64(self.into_integer())?;
+        // One bit for every proposal, in the order of `WasmFeatures::PROPOSALS`
+        let bits = WasmFeatures::PROPOSALS
+            .iter()
+            .enumerate()
+            .filter(|(_, x)| self.is_enabled(x) == Some(true))
+            .fold(0u64, |bits, (i, _)| bits | (1 << i));
+
+        writer.write_u64(bits)?;
         Ok(())
     }
 }
@@ -76,17 +91,15 @@ impl BinarySerialize for Version {
 
 impl BinarySerialize for TargetPlatform {
     fn serialize_into<W: ?Sized + std::io::Write>(&self, writer: &mut W) -> Result<()> {
-        match self {
-            TargetPlatform::Vulkan(version) => {
-                writer.write_u16(0)?;
-                version.serialize_into(writer)?;
-            }
-            TargetPlatform::Universal(version) => {
-                writer.write_u16(1)?;
-                version.serialize_into(writer)?;
-            }
+        let (kind, version) = match self {
+            TargetPlatform::Vulkan(version) => (0, version),
+            TargetPlatform::Universal(version) => (1, version),
+            TargetPlatform::OpenGL(version) => (2, version),
+            TargetPlatform::OpenCL(version) => (3, version),
         };
 
+        writer.write_u16(kind)?;
+        version.serialize_into(writer)?;
         return Ok(());
     }
 }
@@ -135,27 +148,124 @@ impl BinarySerialize for CapabilityModel {
             }
             CapabilityModel::Dynamic(data) => {
                 writer.write_u8(1)?;
-                data.serialize_into(writer)?;
+                data.borrow().serialize_into(writer)?;
             }
         };
+        Ok(())
+    }
+}
+
+impl BinarySerialize for MemoryGrowErrorKind {
+    fn serialize_into<W: ?Sized + std::io::Write>(&self, writer: &mut W) -> Result<()> {
+        writer.write_u8(*self as u8)?;
+        Ok(())
+    }
+}
+
+impl BinarySerialize for Verbosity {
+    fn serialize_into<W: ?Sized + std::io::Write>(&self, writer: &mut W) -> Result<()> {
+        writer.write_u8(*self as u8)?;
+        Ok(())
+    }
+}
 
+impl BinarySerialize for Float64Mode {
+    fn serialize_into<W: ?Sized + std::io::Write>(&self, writer: &mut W) -> Result<()> {
+        writer.write_u8(*self as u8)?;
         Ok(())
     }
 }
 
-impl BinarySerialize for ExtensionModel {
+impl BinarySerialize for TrapMode {
     fn serialize_into<W: ?Sized + std::io::Write>(&self, writer: &mut W) -> Result<()> {
         match self {
-            ExtensionModel::Static(data) => {
-                writer.write_u8(0)?;
-                data.serialize_into(writer)?;
+            TrapMode::Unreachable => writer.write_u8(0)?,
+            TrapMode::InfiniteLoop => writer.write_u8(1)?,
+            TrapMode::Flag { set, binding } => {
+                writer.write_u8(2)?;
+                writer.write_u32(*set)?;
+                writer.write_u32(*binding)?;
             }
-            ExtensionModel::Dynamic(data) => {
-                writer.write_u8(1)?;
-                data.serialize_into(writer)?;
+            TrapMode::TerminateInvocation => writer.write_u8(3)?,
+            TrapMode::DemoteToHelperInvocation => writer.write_u8(4)?,
+        };
+        Ok(())
+    }
+}
+
+impl BinarySerialize for SelectionHint {
+    fn serialize_into<W: ?Sized + std::io::Write>(&self, writer: &mut W) -> Result<()> {
+        match self {
+            SelectionHint::Flatten => writer.write_u8(0)?,
+            SelectionHint::DontFlatten => writer.write_u8(1)?,
+        };
+        Ok(())
+    }
+}
+
+impl BinarySerialize for MemoryBinding {
+    fn serialize_into<W: ?Sized + std::io::Write>(&self, writer: &mut W) -> Result<()> {
+        writer.write_u32(self.set)?;
+        writer.write_u32(self.binding)?;
+        Ok(())
+    }
+}
+
+impl BinarySerialize for ProfilingBuffer {
+    fn serialize_into<W: ?Sized + std::io::Write>(&self, writer: &mut W) -> Result<()> {
+        writer.write_u32(self.set)?;
+        writer.write_u32(self.binding)?;
+        Ok(())
+    }
+}
+
+impl BinarySerialize for Profile {
+    fn serialize_into<W: ?Sized + std::io::Write>(&self, writer: &mut W) -> Result<()> {
+        self.platform.serialize_into(writer)?;
+        self.capabilities.serialize_into(writer)?;
+        self.extensions.serialize_into(writer)?;
+        Ok(())
+    }
+}
+
+impl BinarySerialize for AtomicCounter {
+    fn serialize_into<W: ?Sized + std::io::Write>(&self, writer: &mut W) -> Result<()> {
+        writer.write_u32(self.set)?;
+        writer.write_u32(self.binding)?;
+        Ok(())
+    }
+}
+
+impl BinarySerialize for GlobalConfig {
+    fn serialize_into<W: ?Sized + std::io::Write>(&self, writer: &mut W) -> Result<()> {
+        match self {
+            GlobalConfig::DescriptorSet {
+                set,
+                binding,
+                buffer,
+            } => {
+                writer.write_u8(0)?;
+                writer.write_u32(*set)?;
+                writer.write_u32(*binding)?;
+                buffer.serialize_into(writer)?;
             }
+            GlobalConfig::PushConstant => writer.write_u8(1)?,
         };
+        Ok(())
+    }
+}
 
+impl BinarySerialize for DataSegmentMode {
+    fn serialize_into<W: ?Sized + std::io::Write>(&self, writer: &mut W) -> Result<()> {
+        match self {
+            DataSegmentMode::Ignore => writer.write_u8(0)?,
+            DataSegmentMode::Constant => writer.write_u8(1)?,
+            DataSegmentMode::Buffer { set, binding } => {
+                writer.write_u8(2)?;
+                writer.write_u32(*set)?;
+                writer.write_u32(*binding)?;
+            }
+        };
         Ok(())
     }
 }
@@ -182,8 +292,44 @@ impl BinarySerialize for ExecutionMode {
                 writer.write_u32(*y)?;
                 writer.write_u32(*z)?;
             }
+            ExecutionMode::DepthReplacing => writer.write_u16(6)?,
+            ExecutionMode::DepthGreater => writer.write_u16(7)?,
+            ExecutionMode::DepthLess => writer.write_u16(8)?,
+            ExecutionMode::DepthUnchanged => writer.write_u16(9)?,
+            ExecutionMode::EarlyFragmentTests => writer.write_u16(10)?,
+            ExecutionMode::InputPoints => writer.write_u16(11)?,
+            ExecutionMode::InputLines => writer.write_u16(12)?,
+            ExecutionMode::InputLinesAdjacency => writer.write_u16(13)?,
+            ExecutionMode::Triangles => writer.write_u16(14)?,
+            ExecutionMode::InputTrianglesAdjacency => writer.write_u16(15)?,
+            ExecutionMode::Quads => writer.write_u16(16)?,
+            ExecutionMode::Isolines => writer.write_u16(17)?,
+            ExecutionMode::OutputVertices(x) => {
+                writer.write_u16(18)?;
+                writer.write_u32(*x)?;
+            }
+            ExecutionMode::OutputPoints => writer.write_u16(19)?,
+            ExecutionMode::OutputLineStrip => writer.write_u16(20)?,
+            ExecutionMode::OutputTriangleStrip => writer.write_u16(21)?,
+            ExecutionMode::SpacingEqual => writer.write_u16(22)?,
+            ExecutionMode::SpacingFractionalEven => writer.write_u16(23)?,
+            ExecutionMode::SpacingFractionalOdd => writer.write_u16(24)?,
+            ExecutionMode::VertexOrderCw => writer.write_u16(25)?,
+            ExecutionMode::VertexOrderCcw => writer.write_u16(26)?,
+            ExecutionMode::PointMode => writer.write_u16(27)?,
+            ExecutionMode::OutputPrimitives(x) => {
+                writer.write_u16(28)?;
+                writer.write_u32(*x)?;
+            }
+            ExecutionMode::OutputLines => writer.write_u16(29)?,
+            ExecutionMode::OutputTriangles => writer.write_u16(30)?,
+            ExecutionMode::LocalSizeId(x, y, z) => {
+                writer.write_u16(31)?;
+                writer.write_u32(*x)?;
+                writer.write_u32(*y)?;
+                writer.write_u32(*z)?;
+            }
         }
-
         Ok(())
     }
 }
@@ -198,21 +344,41 @@ impl BinarySerialize for ScalarType {
 impl BinarySerialize for CompositeType {
     fn serialize_into<W: ?Sized + std::io::Write>(&self, writer: &mut W) -> Result<()> {
         match self {
-            CompositeType::Structured(elem) => {
+            CompositeType::Vector(elem, count) => {
                 writer.write_u16(0)?;
                 elem.serialize_into(writer)?;
+                writer.write_u32(*count)?;
             }
-            CompositeType::StructuredArray(elem) => {
+            CompositeType::Matrix(columns, rows, elem) => {
                 writer.write_u16(1)?;
+                writer.write_u32(*columns)?;
+                writer.write_u32(*rows)?;
                 elem.serialize_into(writer)?;
             }
-            CompositeType::Vector(elem, count) => {
+            CompositeType::Struct(members) => {
                 writer.write_u16(2)?;
-                elem.serialize_into(writer)?;
-                writer.write_u32(*count)?;
+                members.serialize_into(writer)?;
             }
         };
+        Ok(())
+    }
+}
 
+impl BinarySerialize for StructMember {
+    fn serialize_into<W: ?Sized + std::io::Write>(&self, writer: &mut W) -> Result<()> {
+        self.name.serialize_into(writer)?;
+        self.ty.serialize_into(writer)?;
+        writer.write_u32(self.offset)?;
+        Ok(())
+    }
+}
+
+impl BinarySerialize for PointerSize {
+    fn serialize_into<W: ?Sized + std::io::Write>(&self, writer: &mut W) -> Result<()> {
+        match self {
+            PointerSize::Skinny => writer.write_u8(0)?,
+            PointerSize::Fat => writer.write_u8(1)?,
+        };
         Ok(())
     }
 }
@@ -220,9 +386,14 @@ impl BinarySerialize for CompositeType {
 impl BinarySerialize for Type {
     fn serialize_into<W: ?Sized + std::io::Write>(&self, writer: &mut W) -> Result<()> {
         match self {
-            Type::Pointer(sc, pointee) => {
+            Type::Pointer {
+                size,
+                storage_class,
+                pointee,
+            } => {
                 writer.write_u16(0)?;
-                sc.serialize_into(writer)?;
+                size.serialize_into(writer)?;
+                storage_class.serialize_into(writer)?;
                 pointee.serialize_into(writer)?;
             }
             Type::Scalar(x) => {
@@ -234,7 +405,38 @@ impl BinarySerialize for Type {
                 x.serialize_into(writer)?;
             }
         };
+        Ok(())
+    }
+}
+
+impl BinarySerialize for Layout {
+    fn serialize_into<W: ?Sized + std::io::Write>(&self, writer: &mut W) -> Result<()> {
+        match self {
+            Layout::Std140 => writer.write_u8(0)?,
+            Layout::Std430 => writer.write_u8(1)?,
+        };
+        Ok(())
+    }
+}
 
+impl BinarySerialize for BufferKind {
+    fn serialize_into<W: ?Sized + std::io::Write>(&self, writer: &mut W) -> Result<()> {
+        match self {
+            BufferKind::Storage => writer.write_u8(0)?,
+            BufferKind::Uniform => writer.write_u8(1)?,
+        };
+        Ok(())
+    }
+}
+
+impl BinarySerialize for BufferAccess {
+    fn serialize_into<W: ?Sized + std::io::Write>(&self, writer: &mut W) -> Result<()> {
+        self.read_only.serialize_into(writer)?;
+        self.write_only.serialize_into(writer)?;
+        self.restrict.serialize_into(writer)?;
+        self.aliased.serialize_into(writer)?;
+        self.volatile.serialize_into(writer)?;
+        self.coherent.serialize_into(writer)?;
         Ok(())
     }
 }
@@ -243,20 +445,37 @@ impl BinarySerialize for ParameterKind {
     fn serialize_into<W: ?Sized + std::io::Write>(&self, writer: &mut W) -> Result<()> {
         match self {
             ParameterKind::FunctionParameter => writer.write_u16(0)?,
-            ParameterKind::Input => writer.write_u16(1)?,
-            ParameterKind::Output => writer.write_u16(2)?,
+            ParameterKind::Input(location) => {
+                writer.write_u16(1)?;
+                writer.write_u32(*location)?;
+            }
+            ParameterKind::Output(location) => {
+                writer.write_u16(2)?;
+                writer.write_u32(*location)?;
+            }
             ParameterKind::DescriptorSet {
                 storage_class,
                 set,
                 binding,
+                access,
+                buffer,
+                layout,
             } => {
                 writer.write_u16(3)?;
                 storage_class.serialize_into(writer)?;
                 writer.write_u32(*set)?;
                 writer.write_u32(*binding)?;
+                access.serialize_into(writer)?;
+                buffer.serialize_into(writer)?;
+                layout.serialize_into(writer)?;
             }
+            ParameterKind::ShaderRecordBuffer => writer.write_u16(4)?,
+            ParameterKind::RayPayload(location) => {
+                writer.write_u16(5)?;
+                writer.write_u32(*location)?;
+            }
+            ParameterKind::IncomingRayPayload => writer.write_u16(6)?,
         };
-
         Ok(())
     }
 }
@@ -265,7 +484,23 @@ impl BinarySerialize for Parameter {
     fn serialize_into<W: ?Sized + std::io::Write>(&self, writer: &mut W) -> Result<()> {
         self.ty.serialize_into(writer)?;
         self.kind.serialize_into(writer)?;
-        self.is_extern_pointer.serialize_into(writer)?;
+        self.relaxed_precision.serialize_into(writer)?;
+        Ok(())
+    }
+}
+
+impl BinarySerialize for LoopHints {
+    fn serialize_into<W: ?Sized + std::io::Write>(&self, writer: &mut W) -> Result<()> {
+        self.unroll.serialize_into(writer)?;
+        self.dependency_length.serialize_into(writer)?;
+        Ok(())
+    }
+}
+
+impl BinarySerialize for RelaxedPrecision {
+    fn serialize_into<W: ?Sized + std::io::Write>(&self, writer: &mut W) -> Result<()> {
+        self.return_value.serialize_into(writer)?;
+        self.arithmetic.serialize_into(writer)?;
         Ok(())
     }
 }
@@ -273,22 +508,62 @@ impl BinarySerialize for Parameter {
 impl BinarySerialize for FunctionConfig {
     fn serialize_into<W: ?Sized + std::io::Write>(&self, writer: &mut W) -> Result<()> {
         self.execution_model.serialize_into(writer)?;
-        self.execution_mode.serialize_into(writer)?;
+        self.execution_modes.serialize_into(writer)?;
         self.params.serialize_into(writer)?;
+        self.loops.serialize_into(writer)?;
+        self.selection_control.serialize_into(writer)?;
+        self.relaxed_precision.serialize_into(writer)?;
+        self.fast_math.serialize_into(writer)?;
+        Ok(())
+    }
+}
+
+impl BinarySerialize for FastMath {
+    fn serialize_into<W: ?Sized + std::io::Write>(&self, writer: &mut W) -> Result<()> {
+        self.no_contraction.serialize_into(writer)?;
+        self.not_nan.serialize_into(writer)?;
+        self.not_inf.serialize_into(writer)?;
+        self.no_signed_zeros.serialize_into(writer)?;
+        self.allow_recip.serialize_into(writer)?;
+        self.fast.serialize_into(writer)?;
         Ok(())
     }
 }
 
 impl BinarySerialize for Config {
     fn serialize_into<W: ?Sized + std::io::Write>(&self, writer: &mut W) -> Result<()> {
+        writer.write_all(&MAGIC)?;
+        writer.write_u32(FORMAT_VERSION)?;
+        writer.write_u32(self.schema)?;
         self.platform.serialize_into(writer)?;
         self.features.serialize_into(writer)?;
         self.addressing_model.serialize_into(writer)?;
         self.memory_model.serialize_into(writer)?;
         self.capabilities.serialize_into(writer)?;
         self.extensions.serialize_into(writer)?;
+        self.memory_grow_error.serialize_into(writer)?;
+        self.trap_mode.serialize_into(writer)?;
+        self.bounds_checks.serialize_into(writer)?;
+        self.selection_control.serialize_into(writer)?;
+        self.inline_threshold.serialize_into(writer)?;
+        self.memories.serialize_into(writer)?;
+        self.globals.serialize_into(writer)?;
+        self.data_segments.serialize_into(writer)?;
+        self.sort_globals.serialize_into(writer)?;
+        self.phi_merges.serialize_into(writer)?;
+        self.profiling.serialize_into(writer)?;
+        self.atomic_counters.serialize_into(writer)?;
+        self.profiles.serialize_into(writer)?;
+        self.entry_point_names.serialize_into(writer)?;
+        self.internal_exports.serialize_into(writer)?;
+        self.verbosity.serialize_into(writer)?;
+        self.forbidden_operators.serialize_into(writer)?;
+        self.division_traps.serialize_into(writer)?;
+        self.float64.serialize_into(writer)?;
+        self.max_value_depth.serialize_into(writer)?;
+        self.linkage.serialize_into(writer)?;
         self.functions.serialize_into(writer)?;
-        self.memory_grow_error.Ok(())
+        Ok(())
     }
 }
 
@@ -383,6 +658,7 @@ impl<T: ?Sized + BinarySerialize> BinarySerialize for Rc<T> {
     }
 }
 
+#[allow(dead_code)]
 pub(crate) trait WriteLe: std::io::Write {
     fn write_i8(&mut self, value: i8) -> std::io::Result<()> {
         self.write_all(&i8::to_le_bytes(value))
@@ -0,0 +1,150 @@
+use crate::{binary::serialize::BinarySerialize, config::Config, error::Result, Compilation};
+use sha2::{Digest, Sha256};
+use std::{
+    collections::VecDeque,
+    path::PathBuf,
+    sync::{Arc, Mutex},
+};
+use tracing::warn;
+
+/// SHA-256 digest of the crate version, binary configuration and WebAssembly bytes of a module
+type Key = [u8; 32];
+
+/// Memoizes the compiled SPIR-V of modules, keyed by their configuration and WebAssembly bytes
+#[derive(Debug)]
+pub struct CompilationCache {
+    capacity: usize,
+    entries: Mutex<VecDeque<(Key, Arc<[u32]>)>>,
+    directory: Option<PathBuf>,
+}
+
+impl CompilationCache {
+    /// Creates an in-memory cache holding up to `capacity` modules
+    pub fn new(capacity: usize) -> Self {
+        return Self {
+            capacity,
+            entries: Mutex::new(VecDeque::with_capacity(capacity)),
+            directory: None,
+        };
+    }
+
+    /// Also persists compiled modules inside `directory`, creating it if required
+    pub fn with_directory(mut self, directory: impl Into<PathBuf>) -> Result<Self> {
+        let directory = directory.into();
+        std::fs::create_dir_all(&directory)?;
+        self.directory = Some(directory);
+        return Ok(self);
+    }
+
+    /// Returns the compiled words of the module, compiling it only if it isn't cached
+    pub fn words(&self, config: &Config, bytes: &[u8]) -> Result<Arc<[u32]>> {
+        let key = Self::key(config, bytes)?;
+
+        if let Some(words) = self.get(key) {
+            return Ok(words);
+        }
+
+        if let Some(words) = self.read_file(key) {
+            self.insert(key, words.clone());
+            return Ok(words);
+        }
+
+        let words = Arc::<[u32]>::from(Compilation::new(config.clone(), bytes)?.into_words()?);
+        self.write_file(key, &words);
+        self.insert(key, words.clone());
+        return Ok(words);
+    }
+
+    /// Number of modules currently held in memory
+    pub fn len(&self) -> usize {
+        return self.lock().len();
+    }
+
+    pub fn is_empty(&self) -> bool {
+        return self.lock().is_empty();
+    }
+
+    /// Removes every in-memory entry. Entries persisted on disk are kept
+    pub fn clear(&self) {
+        self.lock().clear()
+    }
+
+    fn key(config: &Config, bytes: &[u8]) -> Result<Key> {
+        let mut config_bytes = Vec::new();
+        config.serialize_into(&mut config_bytes)?;
+
+        let mut hasher = Sha256::new();
+        for part in [env!("CARGO_PKG_VERSION").as_bytes(), &config_bytes, bytes] {
+            hasher.update((part.len() as u64).to_le_bytes());
+            hasher.update(part);
+        }
+        return Ok(hasher.finalize().into());
+    }
+
+    fn lock(&self) -> std::sync::MutexGuard<'_, VecDeque<(Key, Arc<[u32]>)>> {
+        return match self.entries.lock() {
+            Ok(entries) => entries,
+            Err(e) => e.into_inner(),
+        };
+    }
+
+    fn get(&self, key: Key) -> Option<Arc<[u32]>> {
+        let mut entries = self.lock();
+        let idx = entries.iter().position(|(x, _)| *x == key)?;
+        let entry = entries.remove(idx)?;
+        let words = entry.1.clone();
+        entries.push_back(entry);
+        return Some(words);
+    }
+
+    fn insert(&self, key: Key, words: Arc<[u32]>) {
+        if self.capacity == 0 {
+            return;
+        }
+
+        let mut entries = self.lock();
+        if entries.len() >= self.capacity {
+            let _ = entries.pop_front();
+        }
+        entries.push_back((key, words));
+    }
+
+    fn path(&self, key: Key) -> Option<PathBuf> {
+        let name = key.iter().map(|x| format!("{x:02x}")).collect::<String>();
+        return self
+            .directory
+            .as_ref()
+            .map(|dir| dir.join(format!("{name}.spv")));
+    }
+
+    fn read_file(&self, key: Key) -> Option<Arc<[u32]>> {
+        let bytes = std::fs::read(self.path(key)?).ok()?;
+        let words = bytes.strip_prefix(&key)?;
+        if words.len() % 4 != 0 {
+            return None;
+        }
+
+        return Some(
+            words
+                .chunks_exact(4)
+                .map(|x| u32::from_le_bytes([x[0], x[1], x[2], x[3]]))
+                .collect(),
+        );
+    }
+
+    fn write_file(&self, key: Key, words: &[u32]) {
+        let path = match self.path(key) {
+            Some(path) => path,
+            None => return,
+        };
+
+        let bytes = key
+            .into_iter()
+            .chain(words.iter().flat_map(|x| x.to_le_bytes()))
+            .collect::<Vec<_>>();
+
+        if let Err(e) = std::fs::write(&path, bytes) {
+            warn!("Unable to persist cached module at {}: {e}", path.display());
+        }
+    }
+}
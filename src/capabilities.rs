@@ -1,19 +1,181 @@
-use rspirv::dr::{Instruction, Operand};
+use crate::{
+    error::{Error, Result},
+    version::Version,
+};
+use rspirv::dr::{Instruction, Module, Operand};
 use spirv::{
-    AddressingModel, BuiltIn, Capability, ExecutionMode, ExecutionModel, FunctionControl,
-    MemoryAccess, MemoryModel, StorageClass,
+    AddressingModel, BuiltIn, Capability, Decoration, ExecutionMode, ExecutionModel,
+    FunctionControl, MemoryAccess, MemoryModel, Op, StorageClass,
 };
 use tracing::warn;
 
 pub fn instruction_capabilities<'a>(
     instr: &'a Instruction,
 ) -> impl 'a + Iterator<Item = Capability> {
-    let opcode = instr.class.capabilities.iter().copied();
+    // Acceleration structures can be used by any ray tracing capability, not only by all of them
+    let opcode = match instr.class.opcode {
+        Op::TypeAccelerationStructureKHR => &[Capability::RayTracingKHR],
+        _ => instr.class.capabilities,
+    }
+    .iter()
+    .copied();
     let operands = instr.operands.iter().flat_map(operand_capabilities);
-    opcode.chain(operands)
+    opcode.chain(operands).chain(width_capability(instr))
+}
+
+fn width_capability(instr: &Instruction) -> Option<Capability> {
+    return match (instr.class.opcode, instr.operands.first()?) {
+        (Op::TypeInt, Operand::LiteralInt32(8)) => Some(Capability::Int8),
+        (Op::TypeInt, Operand::LiteralInt32(16)) => Some(Capability::Int16),
+        (Op::TypeInt, Operand::LiteralInt32(64)) => Some(Capability::Int64),
+        (Op::TypeFloat, Operand::LiteralInt32(16)) => Some(Capability::Float16),
+        (Op::TypeFloat, Operand::LiteralInt32(64)) => Some(Capability::Float64),
+        _ => None,
+    };
+}
+
+/// Extension that (besides the capability itself) must be enabled to use the capability, if any
+pub fn capability_extension(capability: Capability) -> Option<&'static str> {
+    use Capability::*;
+
+    return Some(match capability {
+        VariablePointers | VariablePointersStorageBuffer => "SPV_KHR_variable_pointers",
+        PhysicalStorageBufferAddresses => "SPV_KHR_physical_storage_buffer",
+        StorageBuffer16BitAccess | UniformAndStorageBuffer16BitAccess | StoragePushConstant16 => {
+            "SPV_KHR_16bit_storage"
+        }
+        StorageBuffer8BitAccess | UniformAndStorageBuffer8BitAccess | StoragePushConstant8 => {
+            "SPV_KHR_8bit_storage"
+        }
+        VulkanMemoryModel => "SPV_KHR_vulkan_memory_model",
+        IntegerFunctions2INTEL => "SPV_INTEL_shader_integer_functions2",
+        RayTracingKHR => "SPV_KHR_ray_tracing",
+        RayQueryKHR => "SPV_KHR_ray_query",
+        MeshShadingNV => "SPV_NV_mesh_shader",
+        DrawParameters => "SPV_KHR_shader_draw_parameters",
+        MultiView => "SPV_KHR_multiview",
+        DeviceGroup => "SPV_KHR_device_group",
+        ShaderClockKHR => "SPV_KHR_shader_clock",
+        AtomicFloat32AddEXT | AtomicFloat64AddEXT => "SPV_EXT_shader_atomic_float_add",
+        _ => return None,
+    });
+}
+
+/// Extension that must be enabled to use the instruction itself (regardless of its capabilities), if any
+pub fn instruction_extension(instr: &Instruction) -> Option<&'static str> {
+    return match instr.class.opcode {
+        Op::TerminateInvocation => Some("SPV_KHR_terminate_invocation"),
+        Op::DemoteToHelperInvocationEXT | Op::IsHelperInvocationEXT => {
+            Some("SPV_EXT_demote_to_helper_invocation")
+        }
+        Op::ExtInstImport => match instr.operands.first() {
+            Some(Operand::LiteralString(name)) if name.starts_with("NonSemantic.") => {
+                Some("SPV_KHR_non_semantic_info")
+            }
+            _ => None,
+        },
+        _ => instr.operands.iter().find_map(|op| match op {
+            Operand::StorageClass(StorageClass::StorageBuffer) => {
+                Some("SPV_KHR_storage_buffer_storage_class")
+            }
+            _ => None,
+        }),
+    };
+}
+
+/// SPIR-V version whose core specification includes the extension, if any
+pub fn extension_core_version(extension: &str) -> Option<Version> {
+    return Some(match extension {
+        "SPV_KHR_storage_buffer_storage_class"
+        | "SPV_KHR_variable_pointers"
+        | "SPV_KHR_16bit_storage"
+        | "SPV_KHR_shader_draw_parameters"
+        | "SPV_KHR_multiview"
+        | "SPV_KHR_device_group" => Version::V1_3,
+        "SPV_KHR_physical_storage_buffer"
+        | "SPV_KHR_8bit_storage"
+        | "SPV_KHR_vulkan_memory_model" => Version::V1_5,
+        "SPV_KHR_non_semantic_info"
+        | "SPV_KHR_terminate_invocation"
+        | "SPV_EXT_demote_to_helper_invocation" => Version::V1_6,
+        _ => return None,
+    });
+}
+
+/// Checks that the module declares every capability and extension its instructions require, listing every missing one
+pub fn verify_module(module: &Module) -> Result<()> {
+    let declared_capabilities = module
+        .capabilities
+        .iter()
+        .filter_map(|instr| match instr.operands.first() {
+            Some(Operand::Capability(capability)) => Some(*capability),
+            _ => None,
+        })
+        .collect::<Vec<_>>();
+
+    let declared_extensions = module
+        .extensions
+        .iter()
+        .filter_map(|instr| match instr.operands.first() {
+            Some(Operand::LiteralString(extension)) => Some(extension.as_str()),
+            _ => None,
+        })
+        .collect::<Vec<_>>();
+
+    // Extensions aren't required once they're core
+    let version = module
+        .header
+        .as_ref()
+        .map_or(Version::V1_0, |header| Version::from(header.version()));
+    let required = |extension: &str| {
+        !declared_extensions.contains(&extension)
+            && !extension_core_version(extension).is_some_and(|since| version >= since)
+    };
+
+    let mut missing_capabilities = Vec::new();
+    let mut missing_extensions = Vec::new();
+
+    for instr in module.all_inst_iter() {
+        for capability in instruction_capabilities(instr) {
+            if !declared_capabilities.contains(&capability)
+                && !missing_capabilities.contains(&capability)
+            {
+                missing_capabilities.push(capability)
+            }
+        }
+    }
+
+    for capability in declared_capabilities.iter().chain(&missing_capabilities) {
+        if let Some(extension) = capability_extension(*capability) {
+            if required(extension) && !missing_extensions.contains(&extension) {
+                missing_extensions.push(extension)
+            }
+        }
+    }
+
+    for extension in module.all_inst_iter().filter_map(instruction_extension) {
+        if required(extension) && !missing_extensions.contains(&extension) {
+            missing_extensions.push(extension)
+        }
+    }
+
+    if missing_capabilities.is_empty() && missing_extensions.is_empty() {
+        return Ok(());
+    }
+
+    let mut msg =
+        String::from("Declared capabilities and extensions don't match the module's instructions");
+    if !missing_capabilities.is_empty() {
+        msg.push_str(&format!(". Missing capabilities: {missing_capabilities:?}"));
+    }
+    if !missing_extensions.is_empty() {
+        msg.push_str(&format!(". Missing extensions: {missing_extensions:?}"));
+    }
+
+    return Err(Error::msg(msg));
 }
 
-fn operand_capabilities(op: &Operand) -> Vec<Capability> {
+pub(crate) fn operand_capabilities(op: &Operand) -> Vec<Capability> {
     use Operand::*;
 
     match op {
@@ -25,7 +187,8 @@ fn operand_capabilities(op: &Operand) -> Vec<Capability> {
         ExecutionMode(execution_mode) => execution_mode_capabilities(*execution_mode),
         MemoryAccess(memory_access) => memory_access_capabilities(*memory_access),
         FunctionControl(control) => function_control_capabilities(*control),
-        Decoration(_)
+        Decoration(decoration) => decoration_capabilities(*decoration),
+        FPFastMathMode(_)
         | IdRef(_)
         | LiteralInt32(_)
         | LiteralInt64(_)
@@ -47,9 +210,12 @@ fn storage_class_capabilities(storage_class: StorageClass) -> Vec<Capability> {
     return match storage_class {
         Uniform | Output | Private | PushConstant | StorageBuffer => vec![Capability::Shader],
         PhysicalStorageBuffer => vec![Capability::PhysicalStorageBufferAddresses],
+        StorageClass::RayPayloadKHR
+        | StorageClass::IncomingRayPayloadKHR
+        | StorageClass::ShaderRecordBufferKHR => vec![Capability::RayTracingKHR],
         AtomicCounter => vec![Capability::AtomicStorage],
         Generic => vec![Capability::GenericPointer],
-        Input | Function => Vec::new(),
+        Input | Function | UniformConstant => Vec::new(),
         other => {
             warn!("Not yet implemented storage class: {other:?}");
             return Vec::new();
@@ -84,11 +250,27 @@ fn execution_mode_capabilities(execution_mode: ExecutionMode) -> Vec<Capability>
 
     match execution_mode {
         Invocations => vec![Capability::Geometry],
-        PixelCenterInteger | DepthReplacing | OriginUpperLeft | OriginLowerLeft => {
-            vec![Capability::Shader]
-        }
+        PixelCenterInteger | DepthReplacing | DepthGreater | DepthLess | DepthUnchanged
+        | EarlyFragmentTests | OriginUpperLeft | OriginLowerLeft => vec![Capability::Shader],
+        InputPoints
+        | InputLines
+        | InputLinesAdjacency
+        | InputTrianglesAdjacency
+        | OutputLineStrip
+        | OutputTriangleStrip => vec![Capability::Geometry],
+        Quads
+        | Isolines
+        | SpacingEqual
+        | SpacingFractionalEven
+        | SpacingFractionalOdd
+        | VertexOrderCw
+        | VertexOrderCcw
+        | PointMode => vec![Capability::Tessellation],
+        OutputPrimitivesNV | OutputLinesNV | OutputTrianglesNV => vec![Capability::MeshShadingNV],
+        // Either geometry, tessellation or mesh shading, which is already required by the execution model
+        Triangles | OutputVertices | OutputPoints => Vec::new(),
         LocalSizeHint => vec![Capability::Kernel],
-        LocalSize => Vec::new(),
+        LocalSize | LocalSizeId => Vec::new(),
         other => {
             warn!("Not yet implemented execution mode: {other:?}");
             return Vec::new();
@@ -108,7 +290,13 @@ fn execution_model_capabilities(execution_model: ExecutionModel) -> Vec<Capabili
         }
         Geometry => vec![Capability::Geometry],
         Kernel => vec![Capability::Kernel],
-        _ => Vec::new(),
+        TaskNV | MeshNV => vec![Capability::MeshShadingNV],
+        ExecutionModel::RayGenerationKHR
+        | ExecutionModel::IntersectionKHR
+        | ExecutionModel::AnyHitKHR
+        | ExecutionModel::ClosestHitKHR
+        | ExecutionModel::MissKHR
+        | ExecutionModel::CallableKHR => vec![Capability::RayTracingKHR],
     }
 }
 
@@ -119,6 +307,7 @@ fn builtin_capabilities(builtin: BuiltIn) -> Vec<Capability> {
         Position | PointSize | VertexId | InstanceId | FragCoord | PointCoord | SampleMask
         | FragDepth | HelperInvocation => vec![Capability::Shader],
         NumWorkgroups | GlobalInvocationId => Vec::new(),
+        TaskCountNV | PrimitiveCountNV => vec![Capability::MeshShadingNV],
         other => {
             warn!("Not yet implemented built-in: {other:?}");
             Vec::new()
@@ -141,3 +330,12 @@ fn memory_access_capabilities(memory_access: MemoryAccess) -> Vec<Capability> {
 fn function_control_capabilities(_: FunctionControl) -> Vec<Capability> {
     Vec::new()
 }
+
+fn decoration_capabilities(decoration: Decoration) -> Vec<Capability> {
+    return match decoration {
+        Decoration::NoContraction => vec![Capability::Shader],
+        Decoration::FPFastMathMode => vec![Capability::Kernel],
+        Decoration::LinkageAttributes => vec![Capability::Linkage],
+        _ => Vec::new(),
+    };
+}
@@ -1,18 +1,23 @@
-use clap::Parser;
+use clap::{Parser, ValueEnum};
 use color_eyre::{Report, Result};
 #[cfg(feature = "tree-sitter")]
 use colored::{Color, Colorize};
 use std::{fs::File, io::BufReader, path::PathBuf};
-use tracing::info;
+use tracing::{info, warn};
 #[cfg(feature = "tree-sitter")]
 use tree_sitter_highlight::{Highlight, HighlightConfiguration, HighlightEvent, Highlighter};
-use wasm2spirv::{config::Config, Compilation};
+use wasm2spirv::{
+    config::{Config, Verbosity},
+    version::{TargetPlatform, Version},
+    Compilation,
+};
 
 /// Simple program to greet a person
 #[derive(Parser, Debug)]
 #[command(author, version, about, long_about = None)]
 struct Cli {
-    /// File to be converted. Has to be a WebAssembly text or binary file
+    /// File to be converted. Has to be a WebAssembly text or binary file, or a SPIR-V binary (`.spv`) to only
+    /// validate, optimize or cross-compile
     source: PathBuf,
 
     /// Import compilation configuration from a custom section on the WebAssemly program itself
@@ -23,6 +28,10 @@ struct Cli {
     #[arg(long)]
     from_json: Option<PathBuf>,
 
+    /// Compiles with the settings of the named profile of the configuration
+    #[arg(long)]
+    profile: Option<String>,
+
     /// Path to write the compiled spv file
     #[arg(long, short)]
     output: Option<PathBuf>,
@@ -31,6 +40,10 @@ struct Cli {
     #[arg(long, short, default_value_t = false)]
     quiet: bool,
 
+    /// Logs the translation at the debug level, overriding the verbosity of the configuration
+    #[arg(long, value_enum)]
+    verbosity: Option<Verbosity>,
+
     /// When printing to the standard output, syntax highlights will be added.
     /// (Currently, it only works for assembly and GLSL outputs)
     #[arg(long)]
@@ -47,6 +60,10 @@ struct Cli {
     #[arg(long, default_value_t = false)]
     validate: bool,
 
+    /// Checks the capabilities and extensions declared by the resulting SPIR-V
+    #[arg(long, default_value_t = false)]
+    verify_roundtrip: bool,
+
     /// Print OpenGL Shading Language (GLSL) translation to standard output
     #[cfg(any(feature = "spvc-glsl", feature = "naga-glsl"))]
     #[arg(long, default_value_t = false)]
@@ -70,6 +87,20 @@ struct Cli {
     /// Print text assembly on standard output
     #[arg(long, default_value_t = false)]
     show_asm: bool,
+
+    /// Additional outputs to print on standard output
+    #[arg(long, value_enum)]
+    emit: Vec<Emit>,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, ValueEnum)]
+enum Emit {
+    /// JSON description of the module's entry points, bindings and interface variables
+    ReflectJson,
+    /// JSON list of the Rust panics recognized before traps, in the order of their trap codes
+    Panics,
+    /// Instruction counts, capabilities and bindings of the module
+    Stats,
 }
 
 pub fn main() -> color_eyre::Result<()> {
@@ -79,15 +110,19 @@ pub fn main() -> color_eyre::Result<()> {
         source,
         from_wasm,
         from_json,
+        profile,
         output,
         quiet,
+        verbosity,
         #[cfg(feature = "tree-sitter")]
         highlight,
         #[cfg(feature = "spirv-tools")]
         optimize,
         #[cfg(any(feature = "naga-validate", feature = "spvt-validate"))]
         validate,
+        verify_roundtrip,
         show_asm,
+        emit,
         #[cfg(any(feature = "spvc-glsl", feature = "naga-glsl"))]
         show_glsl,
         #[cfg(any(feature = "spvc-hlsl", feature = "naga-hlsl"))]
@@ -102,20 +137,24 @@ pub fn main() -> color_eyre::Result<()> {
     let optimize = false;
 
     if !quiet {
-        tracing_subscriber::fmt::try_init().map_err(Report::msg)?;
+        let level = match verbosity {
+            Some(Verbosity::Quiet) | None => tracing::Level::INFO,
+            Some(_) => tracing::Level::DEBUG,
+        };
+        tracing_subscriber::fmt()
+            .with_max_level(level)
+            .try_init()
+            .map_err(Report::msg)?;
     }
 
-    let mut config: Config = match (from_wasm, from_json) {
+    let is_spirv = source.extension().is_some_and(|x| x == "spv");
+    let config: Option<Config> = match (from_wasm, from_json) {
         (true, None) => todo!(),
         (false, Some(json)) => {
             let mut file = BufReader::new(File::open(json)?);
-            serde_json::from_reader(&mut file)?
-        }
-        (false, None) => {
-            return Err(Report::msg(
-                "One of 'from-wasm', 'from-binary' or 'from-json' must be enabled",
-            ));
+            Some(serde_json::from_reader(&mut file)?)
         }
+        (false, None) => None,
         _ => {
             return Err(Report::msg(
                 "Only one of 'from-wasm', 'from-binary' or 'from-json' must be enabled",
@@ -123,8 +162,47 @@ pub fn main() -> color_eyre::Result<()> {
         }
     };
 
-    let bytes = wat::parse_file(source)?;
-    let mut compilation = Compilation::new(config, &bytes)?;
+    let config = match (config, profile) {
+        (Some(config), Some(profile)) => Some(config.profile(&profile).ok_or_else(|| {
+            Report::msg(format!(
+                "The configuration has no profile named '{profile}'"
+            ))
+        })?),
+        (None, Some(_)) => return Err(Report::msg("Profiles require a configuration")),
+        (config, None) => config,
+    };
+
+    let config = config.map(|mut config| {
+        if let Some(verbosity) = verbosity {
+            config.verbosity = verbosity;
+        }
+        config
+    });
+
+    if let Some(config) = &config {
+        for warning in config.validate().into_result()? {
+            warn!("{warning}");
+        }
+    }
+
+    let mut compilation = if is_spirv {
+        let words = spirv_words(std::fs::read(source)?)?;
+        // Without a configuration, target the SPIR-V version the module declares
+        let platform = match config {
+            Some(config) => config.platform,
+            None => TargetPlatform::Universal(Version::new(
+                (words[1] >> 16) as u8,
+                (words[1] >> 8) as u8,
+            )),
+        };
+        Compilation::from_spirv(platform, words)?
+    } else {
+        let config = config.ok_or_else(|| {
+            Report::msg("One of 'from-wasm', 'from-binary' or 'from-json' must be enabled")
+        })?;
+        let bytes = wat::parse_file(source)?;
+        Compilation::new(config, &bytes)?
+    };
 
     if show_asm && !optimize {
         cfg_if::cfg_if! {
@@ -147,6 +225,10 @@ pub fn main() -> color_eyre::Result<()> {
         compilation.validate()?;
     }
 
+    if verify_roundtrip {
+        compilation.verify_roundtrip()?;
+    }
+
     #[cfg(feature = "spirv-tools")]
     if optimize {
         compilation = compilation.into_optimized()?;
@@ -228,9 +310,42 @@ pub fn main() -> color_eyre::Result<()> {
         println!("{}", compilation.wgsl()?);
     }
 
+    for emit in emit {
+        match emit {
+            Emit::ReflectJson => {
+                println!("{}", serde_json::to_string_pretty(&compilation.reflect()?)?)
+            }
+            Emit::Panics => {
+                println!(
+                    "{}",
+                    serde_json::to_string_pretty(compilation.panic_messages())?
+                )
+            }
+            Emit::Stats => print!("{}", compilation.stats()?),
+        }
+    }
+
     return Ok(());
 }
 
+fn spirv_words(bytes: Vec<u8>) -> Result<Vec<u32>> {
+    if bytes.len() % 4 != 0 || bytes.len() < 20 {
+        return Err(Report::msg("Not a SPIR-V binary"));
+    }
+
+    let magic = u32::from_le_bytes([bytes[0], bytes[1], bytes[2], bytes[3]]);
+    let from_bytes = match magic {
+        rspirv::spirv::MAGIC_NUMBER => u32::from_le_bytes,
+        x if x.swap_bytes() == rspirv::spirv::MAGIC_NUMBER => u32::from_be_bytes,
+        _ => return Err(Report::msg("Not a SPIR-V binary")),
+    };
+
+    return Ok(bytes
+        .chunks_exact(4)
+        .map(|x| from_bytes([x[0], x[1], x[2], x[3]]))
+        .collect());
+}
+
 #[cfg(feature = "tree-sitter")]
 fn print_to_stdout(
     language: impl FnOnce() -> tree_sitter::Language,
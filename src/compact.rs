@@ -0,0 +1,130 @@
+use crate::{
+    diagnostics::Origin,
+    error::{Error, Result},
+};
+use rspirv::{
+    dr::{Instruction, Module, Operand},
+    spirv::{Op, Word},
+};
+use std::collections::HashMap;
+
+pub(crate) fn compact_ids(module: &mut Module, origins: &mut HashMap<Word, Origin>) {
+    let mut replacements = HashMap::new();
+    for instr in module.all_inst_iter() {
+        if let Some(id) = instr.result_id {
+            let next = replacements.len() as Word + 1;
+            replacements.entry(id).or_insert(next);
+        }
+    }
+
+    // Ids that are referenced but never defined keep a place of their own, after the defined ones
+    for instr in module.all_inst_iter() {
+        for id in instr
+            .result_type
+            .into_iter()
+            .chain(instr.operands.iter().filter_map(Operand::id_ref_any))
+        {
+            let next = replacements.len() as Word + 1;
+            replacements.entry(id).or_insert(next);
+        }
+    }
+
+    for instr in module.all_inst_iter_mut() {
+        for id in instr
+            .result_id
+            .iter_mut()
+            .chain(instr.result_type.iter_mut())
+            .chain(
+                instr
+                    .operands
+                    .iter_mut()
+                    .filter_map(Operand::id_ref_any_mut),
+            )
+        {
+            *id = replacements[id];
+        }
+    }
+
+    *origins = std::mem::take(origins)
+        .into_iter()
+        .filter_map(|(id, origin)| Some((*replacements.get(&id)?, origin)))
+        .collect();
+
+    if let Some(ref mut header) = module.header {
+        header.bound = replacements.len() as Word + 1;
+    }
+}
+
+pub(crate) fn sort_globals(module: &mut Module) {
+    let mut depths = HashMap::<Word, u32>::new();
+    let mut keys = Vec::with_capacity(module.types_global_values.len());
+
+    for instr in module.types_global_values.iter() {
+        let depth = instr
+            .result_type
+            .into_iter()
+            .chain(instr.operands.iter().filter_map(Operand::id_ref_any))
+            .filter_map(|id| depths.get(&id))
+            .map(|x| x + 1)
+            .max()
+            .unwrap_or(0);
+
+        if let Some(id) = instr.result_id {
+            depths.insert(id, depth);
+        }
+        keys.push((depth, global_rank(instr)));
+    }
+
+    let mut globals = std::mem::take(&mut module.types_global_values)
+        .into_iter()
+        .zip(keys)
+        .collect::<Vec<_>>();
+
+    globals.sort_by_key(|(_, key)| *key);
+    module.types_global_values = globals.into_iter().map(|(instr, _)| instr).collect();
+}
+
+pub(crate) fn check_duplicates(module: &Module) -> Result<()> {
+    let mut definitions = HashMap::<(Op, Option<Word>, String), Word>::new();
+    for instr in module
+        .ext_inst_imports
+        .iter()
+        .chain(module.types_global_values.iter())
+    {
+        let unique = match instr.class.opcode {
+            Op::TypeStruct | Op::TypeArray | Op::TypeRuntimeArray => false,
+            Op::ExtInstImport
+            | Op::ConstantTrue
+            | Op::ConstantFalse
+            | Op::Constant
+            | Op::ConstantComposite
+            | Op::ConstantNull => true,
+            _ => instr.class.opname.starts_with("Type"),
+        };
+        let (true, Some(id)) = (unique, instr.result_id) else {
+            continue;
+        };
+
+        let key = (
+            instr.class.opcode,
+            instr.result_type,
+            format!("{:?}", instr.operands),
+        );
+        if let Some(other) = definitions.insert(key, id) {
+            return Err(Error::msg(format!(
+                "`{}` is defined twice, as %{other} and %{id}",
+                instr.disassemble()
+            )));
+        }
+    }
+
+    return Ok(());
+}
+
+fn global_rank(instr: &Instruction) -> u8 {
+    return match instr.class.opcode {
+        Op::Variable => 2,
+        _ if instr.class.opname.starts_with("Type") => 0,
+        _ => 1,
+    };
+}
@@ -1,6 +1,26 @@
 use docfg::docfg;
+use rspirv::spirv::ExecutionModel;
+use serde::{Deserialize, Serialize};
+use vector_mapp::vec::VecMap;
 
 use crate::error::Error;
+use crate::Compilation;
+#[cfg(any(
+    feature = "spvc-glsl",
+    feature = "spvc-hlsl",
+    feature = "spvc-msl",
+    feature = "naga-hlsl",
+    feature = "naga-msl"
+))]
+use {
+    crate::error::Result,
+    rspirv::{
+        binary::Assemble,
+        dr::Operand,
+        spirv::{Decoration, Op},
+    },
+    std::borrow::Cow,
+};
 
 #[cfg(feature = "naga")]
 pub mod naga;
@@ -11,6 +31,355 @@ pub mod spvc;
 #[cfg(feature = "spirv-tools")]
 pub mod spvt;
 
+impl Compilation {
+    /// Returns the module's words, keeping only the specified entry point (if any)
+    #[cfg(any(
+        feature = "spvc-glsl",
+        feature = "spvc-hlsl",
+        feature = "spvc-msl",
+        feature = "naga-hlsl",
+        feature = "naga-msl"
+    ))]
+    pub(crate) fn patched_words(
+        &self,
+        entry_point: Option<&str>,
+        stage: Option<ExecutionModel>,
+        register_spaces: Option<&VecMap<u32, u32>>,
+    ) -> Result<Cow<'_, [u32]>> {
+        let register_spaces = register_spaces.filter(|x| !x.is_empty());
+        if entry_point.is_none() && register_spaces.is_none() {
+            return Ok(Cow::Borrowed(self.words()?));
+        }
+
+        let mut module = self.module()?.clone();
+        if let Some(entry_point) = entry_point {
+            module
+                .entry_points
+                .retain(|instr| match &instr.operands[..] {
+                    [Operand::ExecutionModel(model), _, Operand::LiteralString(name), ..] => {
+                        name == entry_point && stage.map_or(true, |x| x == *model)
+                    }
+                    _ => false,
+                });
+
+            let target = match module.entry_points.first() {
+                Some(instr) => instr.operands[1].unwrap_id_ref(),
+                None => return Err(Error::element_not_found()),
+            };
+
+            module.execution_modes.retain(
+                |instr| matches!(instr.operands.first(), Some(Operand::IdRef(id)) if *id == target),
+            );
+        }
+
+        if let Some(register_spaces) = register_spaces {
+            for instr in module.annotations.iter_mut() {
+                if instr.class.opcode != Op::Decorate {
+                    continue;
+                }
+
+                if let [_, Operand::Decoration(Decoration::DescriptorSet), Operand::LiteralInt32(set)] =
+                    &mut instr.operands[..]
+                {
+                    if let Some(space) = register_spaces.get(set) {
+                        *set = *space;
+                    }
+                }
+            }
+        }
+
+        return Ok(Cow::Owned(module.assemble()));
+    }
+}
+
+/// Options for GLSL cross-compilation
+#[derive(Debug, Clone, PartialEq, Eq, Hash, Serialize, Deserialize)]
+#[serde(default)]
+pub struct GlslOptions {
+    /// GLSL version to target (e.g. `450`, or `310` for OpenGL ES 3.1)
+    pub version: u16,
+    /// Targets OpenGL ES (`#version 310 es`) instead of desktop OpenGL
+    pub es: bool,
+    /// Entry point to compile. Required if the module has more than one entry point
+    pub entry_point: Option<String>,
+    /// Execution model of the entry point to compile
+    pub stage: Option<ExecutionModel>,
+    /// Emits Vulkan-flavored GLSL. Defaults to whether the target platform is Vulkan
+    pub vulkan_semantics: Option<bool>,
+}
+
+impl Default for GlslOptions {
+    #[inline]
+    fn default() -> Self {
+        Self {
+            version: 450,
+            es: false,
+            entry_point: None,
+            stage: None,
+            vulkan_semantics: None,
+        }
+    }
+}
+
+/// Options for HLSL cross-compilation
+#[derive(Debug, Clone, PartialEq, Eq, Default, Serialize, Deserialize)]
+#[serde(default)]
+pub struct HlslOptions {
+    pub shader_model: ShaderModel,
+    /// Entry point to compile. Required if the module has more than one entry point
+    pub entry_point: Option<String>,
+    /// Execution model of the entry point to compile
+    pub stage: Option<ExecutionModel>,
+    /// Register space assigned to each descriptor set. Sets not present in the map keep their number as their space
+    pub register_spaces: VecMap<u32, u32>,
+}
+
+/// HLSL shader model
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Default, Serialize, Deserialize)]
+pub enum ShaderModel {
+    #[serde(rename = "5.0")]
+    V5_0,
+    #[default]
+    #[serde(rename = "5.1")]
+    V5_1,
+    #[serde(rename = "6.0")]
+    V6_0,
+}
+
+impl ShaderModel {
+    pub fn major(self) -> u32 {
+        return match self {
+            ShaderModel::V5_0 | ShaderModel::V5_1 => 5,
+            ShaderModel::V6_0 => 6,
+        };
+    }
+
+    pub fn minor(self) -> u32 {
+        return match self {
+            ShaderModel::V5_0 | ShaderModel::V6_0 => 0,
+            ShaderModel::V5_1 => 1,
+        };
+    }
+}
+
+/// Options for MSL cross-compilation
+#[derive(Debug, Clone, PartialEq, Eq, Default, Serialize, Deserialize)]
+#[serde(default)]
+pub struct MslOptions {
+    /// MSL version to target, as `(major, minor)`. Defaults to the backend's default version
+    pub version: Option<(u8, u8)>,
+    /// Ignored by naga
+    pub platform: MslPlatform,
+    /// Places resources inside argument buffers, one per descriptor set. Not supported by naga
+    pub argument_buffers: bool,
+    /// Entry point to compile. Required if the module has more than one entry point
+    pub entry_point: Option<String>,
+    /// Execution model of the entry point to compile
+    pub stage: Option<ExecutionModel>,
+    /// Explicit Metal indices for resources. Resources not present keep the indices assigned by the backend
+    pub bindings: Vec<MslBinding>,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Default, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum MslPlatform {
+    #[default]
+    MacOS,
+    IOS,
+}
+
+/// Metal indices assigned to the resource bound at `set` and `binding`
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Serialize, Deserialize)]
+pub struct MslBinding {
+    pub set: u32,
+    pub binding: u32,
+    #[serde(default)]
+    pub buffer: Option<u32>,
+    #[serde(default)]
+    pub texture: Option<u32>,
+    #[serde(default)]
+    pub sampler: Option<u32>,
+}
+
+/// Options for WGSL cross-compilation
+#[derive(Debug, Clone, PartialEq, Eq, Hash, Serialize, Deserialize)]
+#[serde(default)]
+pub struct WgslOptions {
+    /// Runs the spirv-tools legalization passes before translating the module. Requires the `spirv-tools` feature
+    pub legalize: bool,
+    /// Annotates every declaration with its type, for older `wgpu` releases
+    pub explicit_types: bool,
+}
+
+impl Default for WgslOptions {
+    #[inline]
+    fn default() -> Self {
+        Self {
+            legalize: false,
+            explicit_types: true,
+        }
+    }
+}
+
+/// Library used to cross-compile the SPIR-V module into other shading languages
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Serialize, Deserialize)]
+#[serde(rename_all = "kebab-case")]
+pub enum CrossBackend {
+    /// [naga](https://github.com/gfx-rs/naga), enabled by the `naga-*` features
+    Naga,
+    /// [SPIRV-Cross](https://github.com/KhronosGroup/SPIRV-Cross), enabled by the `spvc-*` features
+    SpirvCross,
+}
+
+impl CrossBackend {
+    /// Every backend, in the order the fallback chains try them
+    pub const ALL: [CrossBackend; 2] = [CrossBackend::SpirvCross, CrossBackend::Naga];
+
+    fn disabled(self, language: &str) -> Error {
+        let feature = match self {
+            CrossBackend::Naga => "naga",
+            CrossBackend::SpirvCross => "spvc",
+        };
+        return Error::msg(format!(
+            "The {self:?} backend can't compile into {language} without the `{feature}-{}` feature",
+            language.to_lowercase()
+        ));
+    }
+}
+
+/// Cross-compiles a [`Compilation`] with the chosen backend
+#[derive(Clone, Copy)]
+pub struct CrossCompiler<'a> {
+    compilation: &'a Compilation,
+    backend: CrossBackend,
+}
+
+impl Compilation {
+    /// Returns a cross-compiler that uses the specified backend. Fails on every call if the backend's feature for the
+    /// language isn't enabled
+    pub fn cross_compiler(&self, backend: CrossBackend) -> CrossCompiler<'_> {
+        return CrossCompiler {
+            compilation: self,
+            backend,
+        };
+    }
+
+    /// Compiles the module into GLSL with the first backend that succeeds
+    pub fn glsl_fallback(&self, options: &GlslOptions) -> crate::error::Result<CrossOutput> {
+        return self.fallback(|compiler| compiler.glsl_with(options));
+    }
+
+    /// Compiles the module into HLSL with the first backend that succeeds
+    pub fn hlsl_fallback(&self, options: &HlslOptions) -> crate::error::Result<CrossOutput> {
+        return self.fallback(|compiler| compiler.hlsl_with(options));
+    }
+
+    /// Compiles the module into MSL with the first backend that succeeds
+    pub fn msl_fallback(&self, options: &MslOptions) -> crate::error::Result<CrossOutput> {
+        return self.fallback(|compiler| compiler.msl_with(options));
+    }
+
+    fn fallback(
+        &self,
+        compile: impl Fn(CrossCompiler<'_>) -> crate::error::Result<String>,
+    ) -> crate::error::Result<CrossOutput> {
+        let mut errors = Vec::new();
+        for backend in CrossBackend::ALL {
+            match compile(self.cross_compiler(backend)) {
+                Ok(source) => return Ok(CrossOutput { backend, source }),
+                Err(e) => errors.push((backend, e)),
+            }
+        }
+
+        return Err(Error::Fallback(Box::new(FallbackError { errors })));
+    }
+}
+
+/// Source produced by a cross-compilation fallback chain
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct CrossOutput {
+    /// Backend that produced the source
+    pub backend: CrossBackend,
+    pub source: String,
+}
+
+/// Errors of every backend tried by a cross-compilation fallback chain, in the order they were tried
+#[derive(Debug)]
+pub struct FallbackError {
+    pub errors: Vec<(CrossBackend, Error)>,
+}
+
+impl std::fmt::Display for FallbackError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "every backend failed")?;
+        for (backend, error) in self.errors.iter() {
+            write!(f, "\n{backend:?}: {error}")?;
+        }
+        Ok(())
+    }
+}
+
+impl std::error::Error for FallbackError {}
+
+#[allow(unused_variables)]
+impl<'a> CrossCompiler<'a> {
+    pub fn compilation(&self) -> &'a Compilation {
+        return self.compilation;
+    }
+
+    pub fn backend(&self) -> CrossBackend {
+        return self.backend;
+    }
+
+    #[inline]
+    pub fn glsl(&self) -> crate::error::Result<String> {
+        return self.glsl_with(&GlslOptions::default());
+    }
+
+    pub fn glsl_with(&self, options: &GlslOptions) -> crate::error::Result<String> {
+        return match self.backend {
+            #[cfg(feature = "naga-glsl")]
+            CrossBackend::Naga => self.compilation.naga_glsl_with(options),
+            #[cfg(feature = "spvc-glsl")]
+            CrossBackend::SpirvCross => self.compilation.spvc_glsl_with(options),
+            #[allow(unreachable_patterns)]
+            backend => Err(backend.disabled("GLSL")),
+        };
+    }
+
+    #[inline]
+    pub fn hlsl(&self) -> crate::error::Result<String> {
+        return self.hlsl_with(&HlslOptions::default());
+    }
+
+    pub fn hlsl_with(&self, options: &HlslOptions) -> crate::error::Result<String> {
+        return match self.backend {
+            #[cfg(feature = "naga-hlsl")]
+            CrossBackend::Naga => self.compilation.naga_hlsl_with(options),
+            #[cfg(feature = "spvc-hlsl")]
+            CrossBackend::SpirvCross => self.compilation.spvc_hlsl_with(options),
+            #[allow(unreachable_patterns)]
+            backend => Err(backend.disabled("HLSL")),
+        };
+    }
+
+    #[inline]
+    pub fn msl(&self) -> crate::error::Result<String> {
+        return self.msl_with(&MslOptions::default());
+    }
+
+    pub fn msl_with(&self, options: &MslOptions) -> crate::error::Result<String> {
+        return match self.backend {
+            #[cfg(feature = "naga-msl")]
+            CrossBackend::Naga => self.compilation.naga_msl_with(options),
+            #[cfg(feature = "spvc-msl")]
+            CrossBackend::SpirvCross => self.compilation.spvc_msl_with(options),
+            #[allow(unreachable_patterns)]
+            backend => Err(backend.disabled("MSL")),
+        };
+    }
+}
+
 #[derive(Debug, Clone, thiserror::Error)]
 pub enum CompilerError {
     #[cfg(feature = "spirvcross")]
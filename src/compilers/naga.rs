@@ -1,3 +1,7 @@
+#[cfg(feature = "naga-glsl")]
+use super::GlslOptions;
+#[cfg(feature = "naga-wgsl")]
+use super::WgslOptions;
 use crate::{
     error::{Error, Result},
     Compilation,
@@ -15,31 +19,48 @@ impl Compilation {
     }
 
     #[docfg(feature = "naga-glsl")]
+    #[inline]
     pub fn naga_glsl(&self) -> Result<String> {
+        return self.naga_glsl_with(&GlslOptions::default());
+    }
+
+    /// Compiles the module into GLSL, ignoring [`GlslOptions::vulkan_semantics`]
+    #[docfg(feature = "naga-glsl")]
+    pub fn naga_glsl_with(&self, options: &GlslOptions) -> Result<String> {
         use naga::back::glsl;
 
         tracing::warn!("GLSL is currently on secondary support for naga.");
-        let (exec_model, name) = self.naga_info()?;
         let (module, info) = self.naga_module()?;
 
-        let pipeline_options = glsl::PipelineOptions {
-            shader_stage: match exec_model {
-                ExecutionModel::Vertex => naga::ShaderStage::Vertex,
-                ExecutionModel::Fragment => naga::ShaderStage::Fragment,
-                ExecutionModel::GLCompute => naga::ShaderStage::Compute,
-                other => {
-                    return Err(Error::msg(format!(
-                        "Unsupported execution model '{other:?}'"
-                    )))
+        let pipeline_options = match options.entry_point {
+            Some(ref name) => {
+                let stage = options.stage.map(shader_stage).transpose()?;
+                let entry_point = module
+                    .entry_points
+                    .iter()
+                    .find(|x| x.name == *name && stage.map_or(true, |stage| stage == x.stage))
+                    .ok_or_else(Error::element_not_found)?;
+
+                glsl::PipelineOptions {
+                    shader_stage: entry_point.stage,
+                    entry_point: name.clone(),
+                    multiview: None,
                 }
-            },
-            entry_point: name.into(),
-            multiview: None,
+            }
+
+            None => {
+                let (exec_model, name) = self.naga_info()?;
+                glsl::PipelineOptions {
+                    shader_stage: shader_stage(exec_model)?,
+                    entry_point: name.into(),
+                    multiview: None,
+                }
+            }
         };
 
-        let version = match 0 {
-            // TODO
-            _ => glsl::Version::Desktop(450),
+        let version = match options.es {
+            true => glsl::Version::new_gles(options.version),
+            false => glsl::Version::Desktop(options.version),
         };
 
         let options = glsl::Options {
@@ -62,61 +83,194 @@ impl Compilation {
     }
 
     #[docfg(feature = "naga-hlsl")]
+    #[inline]
     pub fn naga_hlsl(&self) -> Result<String> {
+        return self.naga_hlsl_with(&super::HlslOptions::default());
+    }
+
+    #[docfg(feature = "naga-hlsl")]
+    pub fn naga_hlsl_with(&self, options: &super::HlslOptions) -> Result<String> {
+        use super::ShaderModel;
         use naga::back::hlsl;
 
-        let (module, info) = self.naga_module()?;
-        let options = hlsl::Options::default();
+        let patched = self.naga_patched_module(
+            options.entry_point.as_deref(),
+            options.stage,
+            Some(&options.register_spaces),
+        )?;
+
+        let (module, info) = match patched {
+            Some((ref module, ref info)) => (module, info),
+            None => {
+                let (module, info) = self.naga_module()?;
+                (module, info)
+            }
+        };
+
+        let options = hlsl::Options {
+            shader_model: match options.shader_model {
+                ShaderModel::V5_0 => hlsl::ShaderModel::V5_0,
+                ShaderModel::V5_1 => hlsl::ShaderModel::V5_1,
+                ShaderModel::V6_0 => hlsl::ShaderModel::V6_0,
+            },
+            ..Default::default()
+        };
 
         let mut result = String::new();
         let mut writer = hlsl::Writer::new(&mut result, &options);
 
-        writer.write(&module, &info)?;
+        writer.write(module, info)?;
         return Ok(result);
     }
 
     #[docfg(feature = "naga-msl")]
+    #[inline]
     pub fn naga_msl(&self) -> Result<String> {
+        return self.naga_msl_with(&super::MslOptions::default());
+    }
+
+    /// Compiles the module into MSL, ignoring [`MslOptions::platform`](super::MslOptions::platform)
+    #[docfg(feature = "naga-msl")]
+    pub fn naga_msl_with(&self, options: &super::MslOptions) -> Result<String> {
         use naga::back::msl;
 
-        let (module, info) = self.naga_module()?;
+        if options.argument_buffers {
+            return Err(Error::msg("Argument buffers aren't supported by naga"));
+        }
+
+        let patched =
+            self.naga_patched_module(options.entry_point.as_deref(), options.stage, None)?;
+        let (module, info) = match patched {
+            Some((ref module, ref info)) => (module, info),
+            None => {
+                let (module, info) = self.naga_module()?;
+                (module, info)
+            }
+        };
+
+        let slot = |x: Option<u32>| {
+            x.map(|x| {
+                msl::Slot::try_from(x)
+                    .map_err(|_| Error::msg(format!("Metal index {x} is too large")))
+            })
+            .transpose()
+        };
+
+        let mut resources = msl::EntryPointResources::default();
+        for binding in options.bindings.iter() {
+            resources.resources.insert(
+                naga::ResourceBinding {
+                    group: binding.set,
+                    binding: binding.binding,
+                },
+                msl::BindTarget {
+                    buffer: slot(binding.buffer)?,
+                    texture: slot(binding.texture)?,
+                    sampler: slot(binding.sampler)?.map(msl::BindSamplerTarget::Resource),
+                    binding_array_size: None,
+                    mutable: false,
+                },
+            );
+        }
+
         let pipeline_options = msl::PipelineOptions::default();
-        let options = msl::Options::default();
+        let mut msl_options = msl::Options {
+            per_entry_point_map: module
+                .entry_points
+                .iter()
+                .map(|x| (x.name.clone(), resources.clone()))
+                .collect(),
+            ..Default::default()
+        };
+
+        if let Some(version) = options.version {
+            msl_options.lang_version = version;
+        }
 
         let mut writer = msl::Writer::new(String::new());
-        writer.write(&module, &info, &options, &pipeline_options)?;
+        writer.write(module, info, &msl_options, &pipeline_options)?;
         return Ok(writer.finish());
     }
 
     #[docfg(feature = "naga-wgsl")]
+    #[inline]
     pub fn naga_wgsl(&self) -> Result<String> {
+        return self.naga_wgsl_with(&WgslOptions::default());
+    }
+
+    #[docfg(feature = "naga-wgsl")]
+    pub fn naga_wgsl_with(&self, options: &WgslOptions) -> Result<String> {
         use naga::back::wgsl;
 
         tracing::warn!("WGSL is currently on secondary support for naga.");
-        let (module, info) = self.naga_module()?;
+        let legalized;
+        let (module, info) = match options.legalize {
+            true => {
+                legalized = self.naga_legalized_module()?;
+                &legalized
+            }
+            false => self.naga_module()?,
+        };
 
-        let mut writer = wgsl::Writer::new(String::new(), wgsl::WriterFlags::EXPLICIT_TYPES);
-        writer.write(&module, &info)?;
+        let mut flags = wgsl::WriterFlags::empty();
+        flags.set(wgsl::WriterFlags::EXPLICIT_TYPES, options.explicit_types);
+
+        let mut writer = wgsl::Writer::new(String::new(), flags);
+        writer.write(module, info)?;
         return Ok(writer.finish());
     }
 
-    fn naga_module(&self) -> Result<&(naga::Module, naga::valid::ModuleInfo)> {
-        match self.naga_module.get_or_try_init(|| {
-            let options = &naga::front::spv::Options::default();
-            let module =
-                naga::front::spv::Frontend::new(self.words()?.iter().copied(), options).parse()?;
-
-            let info =
-                valid::Validator::new(valid::ValidationFlags::all(), valid::Capabilities::all())
-                    .validate(&module)?;
-
-            return Ok::<_, Error>(Ok((module, info)));
-        })? {
+    /// Returns the module in naga's IR, alongside its validation info
+    #[docfg(feature = "naga")]
+    pub fn naga_module(&self) -> Result<&(naga::Module, naga::valid::ModuleInfo)> {
+        match self
+            .naga_module
+            .get_or_try_init(|| Ok::<_, Error>(Self::parse_naga_module(self.words()?)))?
+        {
             Ok(str) => Ok(str),
             Err(e) => Err(Error::from(e.clone())),
         }
     }
 
+    #[cfg(any(feature = "naga-hlsl", feature = "naga-msl"))]
+    fn naga_patched_module(
+        &self,
+        entry_point: Option<&str>,
+        stage: Option<ExecutionModel>,
+        register_spaces: Option<&vector_mapp::vec::VecMap<u32, u32>>,
+    ) -> Result<Option<(naga::Module, valid::ModuleInfo)>> {
+        if entry_point.is_none() && register_spaces.map_or(true, |x| x.is_empty()) {
+            return Ok(None);
+        }
+
+        let words = self.patched_words(entry_point, stage, register_spaces)?;
+        return Ok(Some(Self::parse_naga_module(&words)?));
+    }
+
+    #[cfg(feature = "naga-wgsl")]
+    fn naga_legalized_module(&self) -> Result<(naga::Module, valid::ModuleInfo)> {
+        cfg_if::cfg_if! {
+            if #[cfg(feature = "spirv-tools")] {
+                let words = self.legalized_words()?;
+                return Ok(Self::parse_naga_module(&words)?);
+            } else {
+                return Err(Error::msg("Legalizing the module requires the `spirv-tools` feature"));
+            }
+        }
+    }
+
+    fn parse_naga_module(
+        words: &[u32],
+    ) -> Result<(naga::Module, valid::ModuleInfo), super::CompilerError> {
+        let options = &naga::front::spv::Options::default();
+        let module = naga::front::spv::Frontend::new(words.iter().copied(), options).parse()?;
+
+        let info = valid::Validator::new(valid::ValidationFlags::all(), valid::Capabilities::all())
+            .validate(&module)?;
+
+        return Ok((module, info));
+    }
+
     fn naga_info(&self) -> Result<(ExecutionModel, &str)> {
         let module = self.module()?;
         if module.entry_points.len() != 1 {
@@ -139,3 +293,15 @@ impl Compilation {
         Ok((*execution_model, name))
     }
 }
+
+#[cfg(feature = "naga-glsl")]
+fn shader_stage(exec_model: ExecutionModel) -> Result<naga::ShaderStage> {
+    return match exec_model {
+        ExecutionModel::Vertex => Ok(naga::ShaderStage::Vertex),
+        ExecutionModel::Fragment => Ok(naga::ShaderStage::Fragment),
+        ExecutionModel::GLCompute => Ok(naga::ShaderStage::Compute),
+        other => Err(Error::msg(format!(
+            "Unsupported execution model '{other:?}'"
+        ))),
+    };
+}
@@ -5,13 +5,38 @@ use spirvcross::Context;
 use std::cell::UnsafeCell;
 
 impl Compilation {
-    #[cfg(feature = "spvc-glsl")]
+    #[docfg(feature = "spvc-glsl")]
+    #[inline]
     pub fn spvc_glsl(&self) -> Result<String> {
-        use spirvcross::{compiler::GlslCompiler, Compiler};
+        return self.spvc_glsl_with(&super::GlslOptions::default());
+    }
+
+    #[docfg(feature = "spvc-glsl")]
+    pub fn spvc_glsl_with(&self, options: &super::GlslOptions) -> Result<String> {
+        use spirvcross::{
+            compiler::{glsl::EsOptions, GlslCompiler},
+            Compiler,
+        };
+
+        if options.entry_point.is_none() && self.module()?.entry_points.len() > 1 {
+            return Err(crate::error::Error::msg(
+                "GLSL can only hold one entry point, but the module has many. Choose one with `GlslOptions::entry_point`",
+            ));
+        }
 
         let ctx = self.spvc_context()?;
-        let res = GlslCompiler::new(ctx, self.words()?)?
-            .vulkan_semantics(self.platform.is_vulkan())?
+        let words = self.patched_words(options.entry_point.as_deref(), options.stage, None)?;
+        let res = GlslCompiler::new(ctx, &words)?
+            .version(
+                options.version as u32 / 100,
+                (options.version as u32 % 100) / 10,
+            )?
+            .es(options.es.then(EsOptions::default))?
+            .vulkan_semantics(
+                options
+                    .vulkan_semantics
+                    .unwrap_or_else(|| self.platform.is_vulkan()),
+            )?
             .compile()?;
 
         ctx.release_allocations();
@@ -19,24 +44,113 @@ impl Compilation {
     }
 
     #[docfg(feature = "spvc-hlsl")]
+    #[inline]
     pub fn spvc_hlsl(&self) -> Result<String> {
+        return self.spvc_hlsl_with(&super::HlslOptions::default());
+    }
+
+    #[docfg(feature = "spvc-hlsl")]
+    pub fn spvc_hlsl_with(&self, options: &super::HlslOptions) -> Result<String> {
         use spirvcross::{compiler::HlslCompiler, Compiler};
 
         let ctx = self.spvc_context()?;
-        let res = HlslCompiler::new(ctx, self.words()?)?.compile()?;
+        let words = self.patched_words(
+            options.entry_point.as_deref(),
+            options.stage,
+            Some(&options.register_spaces),
+        )?;
+
+        let res = HlslCompiler::new(ctx, &words)?
+            .shader_model(options.shader_model.major(), options.shader_model.minor())?
+            .compile()?;
+
         ctx.release_allocations();
         return Ok(res);
     }
 
     #[docfg(feature = "spvc-msl")]
+    #[inline]
     pub fn spvc_msl(&self) -> Result<String> {
-        use spirvcross::{compiler::MslCompiler, Compiler};
+        return self.spvc_msl_with(&super::MslOptions::default());
+    }
+
+    #[docfg(feature = "spvc-msl")]
+    pub fn spvc_msl_with(&self, options: &super::MslOptions) -> Result<String> {
+        use super::MslPlatform;
+        use crate::error::Error;
+        use rspirv::dr::Operand;
+        use spirvcross::{compiler::MslCompiler, sys, Compiler};
 
         let ctx = self.spvc_context()?;
-        let res = MslCompiler::new(ctx, self.words()?)?
+        let words = self.patched_words(options.entry_point.as_deref(), options.stage, None)?;
+
+        let mut compiler = MslCompiler::new(ctx, &words)?
             .enable_point_size_builtin(true)?
-            .compile()?;
+            .platform(match options.platform {
+                MslPlatform::MacOS => sys::spvc_msl_platform::SPVC_MSL_PLATFORM_MACOS,
+                MslPlatform::IOS => sys::spvc_msl_platform::SPVC_MSL_PLATFORM_IOS,
+            })?
+            .argument_buffers(options.argument_buffers)?;
+
+        if let Some((major, minor)) = options.version {
+            compiler = compiler.version(major as u32, minor as u32)?;
+        }
+
+        let compiler = compiler.into_generic();
+        if !options.bindings.is_empty() {
+            let stages = self
+                .module()?
+                .entry_points
+                .iter()
+                .filter_map(|x| match &x.operands[..] {
+                    [Operand::ExecutionModel(model), _, Operand::LiteralString(name), ..] => {
+                        match &options.entry_point {
+                            Some(entry_point) if entry_point != name => None,
+                            _ if options.stage.is_some_and(|x| x != *model) => None,
+                            _ => Some(*model),
+                        }
+                    }
+                    _ => None,
+                })
+                .collect::<Vec<_>>();
+
+            for binding in options.bindings.iter() {
+                for stage in stages.iter() {
+                    unsafe {
+                        let mut resource = std::mem::MaybeUninit::uninit();
+                        sys::spvc_msl_resource_binding_init(resource.as_mut_ptr());
+
+                        let mut resource: sys::spvc_msl_resource_binding = resource.assume_init();
+                        // Both are generated from `spirv.h`, and share their discriminants
+                        resource.stage = std::mem::transmute(*stage as u32);
+                        resource.desc_set = binding.set;
+                        resource.binding = binding.binding;
+                        if let Some(buffer) = binding.buffer {
+                            resource.msl_buffer = buffer;
+                        }
+                        if let Some(texture) = binding.texture {
+                            resource.msl_texture = texture;
+                        }
+                        if let Some(sampler) = binding.sampler {
+                            resource.msl_sampler = sampler;
+                        }
+
+                        let result = sys::spvc_compiler_msl_add_resource_binding(
+                            compiler.compiler,
+                            &resource,
+                        );
+                        if result != sys::spvc_result::SPVC_SUCCESS {
+                            return Err(Error::msg(format!(
+                                "Unable to bind set {} binding {}: {result:?}",
+                                binding.set, binding.binding
+                            )));
+                        }
+                    }
+                }
+            }
+        }
 
+        let res = compiler.compile()?;
         ctx.release_allocations();
         return Ok(res);
     }
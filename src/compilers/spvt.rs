@@ -2,6 +2,7 @@ use crate::error::{Error, Result};
 use crate::Compilation;
 use docfg::docfg;
 use once_cell::unsync::OnceCell;
+use serde::{Deserialize, Serialize};
 use std::mem::ManuallyDrop;
 
 impl Compilation {
@@ -21,40 +22,53 @@ impl Compilation {
     }
 
     #[docfg(feature = "spirv-tools")]
+    #[inline]
     pub fn into_optimized(self) -> Result<Self> {
+        self.optimize(OptimizationPreset::Performance)
+    }
+
+    /// Optimizes the compiled module for performance, along with its statistics
+    #[docfg(feature = "spirv-tools")]
+    #[inline]
+    pub fn optimized(&self) -> Result<(Self, OptimizationStats)> {
+        self.optimized_with(OptimizationPreset::Performance)
+    }
+
+    /// Optimizes the compiled module for size, returning the optimized module along with its statistics
+    #[docfg(feature = "spirv-tools")]
+    #[inline]
+    pub fn optimized_for_size(&self) -> Result<(Self, OptimizationStats)> {
+        self.optimized_with(OptimizationPreset::Size)
+    }
+
+    #[docfg(feature = "spirv-tools")]
+    pub fn optimized_with(&self, preset: OptimizationPreset) -> Result<(Self, OptimizationStats)> {
+        let result = self.optimize(preset)?;
+        let stats = OptimizationStats {
+            preset,
+            instructions_before: self.module()?.all_inst_iter().count(),
+            instructions_after: result.module()?.all_inst_iter().count(),
+            words_before: self.words()?.len(),
+            words_after: result.words()?.len(),
+        };
+
+        return Ok((result, stats));
+    }
+
+    fn optimize(&self, preset: OptimizationPreset) -> Result<Self> {
         use spirv_tools::opt::Optimizer;
 
         let mut optimizer = spirv_tools::opt::create(Some(self.target_env));
-        let optimizer = optimizer
-            .register_hlsl_legalization_passes()
-            .register_performance_passes();
-
-        let words = match optimizer.optimize(self.words()?, &mut spirv_tools_message, None)? {
-            spirv_tools::binary::Binary::External(words) => AsRef::<[u32]>::as_ref(&words).into(),
-            spirv_tools::binary::Binary::OwnedU32(words) => words,
-            spirv_tools::binary::Binary::OwnedU8(bytes) => {
-                match bytes.as_ptr().align_offset(core::mem::align_of::<u32>()) {
-                    0 if bytes.len() % 4 == 0 && bytes.capacity() % 4 == 0 => unsafe {
-                        let mut bytes = ManuallyDrop::new(bytes);
-                        Vec::from_raw_parts(
-                            bytes.as_mut_ptr().cast(),
-                            bytes.len() / 4,
-                            bytes.capacity() / 4,
-                        )
-                    },
-                    _ => {
-                        let mut result = Vec::with_capacity(bytes.len() / 4);
-                        for chunk in bytes.chunks_exact(4) {
-                            let chunk = unsafe { TryFrom::try_from(chunk).unwrap_unchecked() };
-                            result.push(u32::from_ne_bytes(chunk));
-                        }
-                        result
-                    }
-                }
-            }
+        let optimizer = optimizer.register_hlsl_legalization_passes();
+        let optimizer = match preset {
+            OptimizationPreset::Performance => optimizer.register_performance_passes(),
+            OptimizationPreset::Size => optimizer.register_size_passes(),
         };
 
-        return Ok(Self {
+        let words =
+            binary_words(optimizer.optimize(self.words()?, &mut spirv_tools_message, None)?);
+
+        let result = Self {
             platform: self.platform,
             module: OnceCell::new(),
             #[cfg(feature = "naga")]
@@ -67,8 +81,73 @@ impl Compilation {
             assembly: OnceCell::new(),
             #[cfg(feature = "spirv-tools")]
             validate: OnceCell::new(),
-        });
+            // Optimization renumbers ids, so the original origins no longer apply
+            origins: Default::default(),
+            wasm_features: self.wasm_features,
+            profile_counters: self.profile_counters.clone(),
+            panics: self.panics.clone(),
+        };
+
+        return Ok(result);
     }
+
+    pub(crate) fn legalized_words(&self) -> Result<Vec<u32>> {
+        use spirv_tools::opt::Optimizer;
+
+        let mut optimizer = spirv_tools::opt::create(Some(self.target_env));
+        let optimizer = optimizer.register_hlsl_legalization_passes();
+        return Ok(binary_words(optimizer.optimize(
+            self.words()?,
+            &mut spirv_tools_message,
+            None,
+        )?));
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Default, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum OptimizationPreset {
+    /// Optimizes for execution speed
+    #[default]
+    Performance,
+    /// Optimizes for binary size
+    Size,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Serialize, Deserialize)]
+pub struct OptimizationStats {
+    pub preset: OptimizationPreset,
+    pub instructions_before: usize,
+    pub instructions_after: usize,
+    pub words_before: usize,
+    pub words_after: usize,
+}
+
+fn binary_words(binary: spirv_tools::binary::Binary) -> Vec<u32> {
+    return match binary {
+        spirv_tools::binary::Binary::External(words) => AsRef::<[u32]>::as_ref(&words).into(),
+        spirv_tools::binary::Binary::OwnedU32(words) => words,
+        spirv_tools::binary::Binary::OwnedU8(bytes) => {
+            match bytes.as_ptr().align_offset(core::mem::align_of::<u32>()) {
+                0 if bytes.len() % 4 == 0 && bytes.capacity() % 4 == 0 => unsafe {
+                    let mut bytes = ManuallyDrop::new(bytes);
+                    Vec::from_raw_parts(
+                        bytes.as_mut_ptr().cast(),
+                        bytes.len() / 4,
+                        bytes.capacity() / 4,
+                    )
+                },
+                _ => {
+                    let mut result = Vec::with_capacity(bytes.len() / 4);
+                    for chunk in bytes.chunks_exact(4) {
+                        let chunk = unsafe { TryFrom::try_from(chunk).unwrap_unchecked() };
+                        result.push(u32::from_ne_bytes(chunk));
+                    }
+                    result
+                }
+            }
+        }
+    };
 }
 
 fn clone_diagnostics(diag: &spirv_tools::error::Diagnostic) -> spirv_tools::error::Diagnostic {
@@ -1,16 +1,22 @@
 #![allow(non_upper_case_globals)]
 
 use crate::{
+    capabilities::{capability_extension, extension_core_version, operand_capabilities},
     error::{Error, Result},
-    fg::function::{FunctionConfig, FunctionConfigBuilder},
-    version::TargetPlatform,
+    fg::function::{
+        BufferKind, ExecutionMode, FunctionConfig, FunctionConfigBuilder, ParameterKind,
+    },
+    version::{TargetPlatform, Version},
     Str,
 };
 use docfg::docfg;
 use num_enum::TryFromPrimitive;
-use rspirv::spirv::{Capability, MemoryModel};
+use rspirv::{
+    dr::Operand,
+    spirv::{Capability, ExecutionModel, MemoryModel, SelectionControl},
+};
 use serde::{Deserialize, Serialize};
-use std::cell::RefCell;
+use std::{cell::RefCell, collections::BTreeMap};
 use vector_mapp::vec::VecMap;
 
 #[derive(Debug, Clone)]
@@ -18,9 +24,19 @@ pub struct ConfigBuilder {
     pub(crate) inner: Config,
 }
 
+/// Groups of operators that can be [forbidden](Config::forbidden_operators) together
+pub const OPERATOR_GROUPS: &[&str] = &["float", "f32", "f64", "i64", "atomic", "simd"];
+
+/// Version of the configuration schema, shared by its serde and [binary](crate::binary) representations.
+/// Configurations with a newer schema are rejected
+pub const CONFIG_SCHEMA_VERSION: u32 = 1;
+
 #[derive(Debug, Clone, Serialize, Deserialize)]
 #[non_exhaustive]
 pub struct Config {
+    /// Schema version the configuration was written with. Configurations without one use the first version
+    #[serde(default = "first_schema", deserialize_with = "deserialize_schema")]
+    pub schema: u32,
     pub platform: TargetPlatform,
     #[serde(default)]
     pub features: WasmFeatures,
@@ -31,9 +47,84 @@ pub struct Config {
     #[serde(default)]
     pub memory_grow_error: MemoryGrowErrorKind,
     #[serde(default)]
+    pub trap_mode: TrapMode,
+    /// Clamps every indexed access into a storage buffer to the buffer's runtime length, trapping on empty buffers
+    #[serde(default)]
+    pub bounds_checks: bool,
+    /// Default hint for the selections of every function
+    #[serde(default)]
+    pub selection_control: Option<SelectionHint>,
+    /// Calls to functions with at most this many WebAssembly operators are inlined into the caller
+    #[serde(default)]
+    pub inline_threshold: Option<u32>,
+    /// Storage buffers backing the WebAssembly memories, by memory index
+    #[serde(default)]
+    pub memories: VecMap<u32, MemoryBinding>,
+    /// External variables backing the WebAssembly globals, by global index
+    #[serde(default)]
+    pub globals: VecMap<u32, GlobalConfig>,
+    /// How the contents of the active data segments are made available to loads
+    #[serde(default)]
+    pub data_segments: DataSegmentMode,
+    /// Sorts the types, constants and global variables of the output into a canonical order
+    #[serde(default)]
+    pub sort_globals: bool,
+    /// Merges the values carried into a block by its branches with `OpPhi`, instead of function variables
+    #[serde(default)]
+    pub phi_merges: bool,
+    /// Counts the calls of every function, and the iterations of every loop, into a storage buffer
+    #[serde(default)]
+    pub profiling: Option<ProfilingBuffer>,
+    /// Storage buffers of the counters incremented by `atomic_counter_increment`, by counter index
+    #[serde(default)]
+    pub atomic_counters: VecMap<u32, AtomicCounter>,
+    /// Named variations of the configuration
+    #[serde(default)]
+    pub profiles: BTreeMap<Box<str>, Profile>,
+    /// Names of the entry points, by the name of the WebAssembly export they're created from
+    #[serde(default)]
+    pub entry_point_names: BTreeMap<Box<str>, Box<str>>,
+    /// Exports that are compiled, but never exposed as entry points
+    #[serde(default)]
+    pub internal_exports: Box<[Box<str>]>,
+    /// How much of the translation is logged through `tracing`
+    #[serde(default)]
+    pub verbosity: Verbosity,
+    /// WebAssembly operators the module isn't allowed to use, by name or by [group](OPERATOR_GROUPS). The compilation
+    /// fails at the first forbidden operator
+    #[serde(default)]
+    pub forbidden_operators: Box<[Box<str>]>,
+    /// Integer divisions and remainders by zero, and signed divisions of the minimum integer by minus one, trap as
+    /// they do in WebAssembly (following the [`trap_mode`](Config::trap_mode)), instead of being undefined
+    #[serde(default)]
+    pub division_traps: bool,
+    /// How 64-bit floats are handled, for targets where the `Float64` capability can't be enabled
+    #[serde(default)]
+    pub float64: Float64Mode,
+    /// Values depending on a longer chain of operations fail to compile
+    #[serde(default)]
+    pub max_value_depth: Option<u32>,
+    /// Emits linkage attributes, so that the module can be merged by a [`Linker`](crate::link::Linker)
+    #[serde(default)]
+    pub linkage: bool,
+    #[serde(default)]
     pub functions: VecMap<u32, FunctionConfig>,
 }
 
+fn first_schema() -> u32 {
+    return 1;
+}
+
+fn deserialize_schema<'de, D: serde::Deserializer<'de>>(deserializer: D) -> Result<u32, D::Error> {
+    let schema = u32::deserialize(deserializer)?;
+    if schema > CONFIG_SCHEMA_VERSION {
+        return Err(serde::de::Error::custom(format!(
+            "the configuration uses schema version {schema}, but only versions up to {CONFIG_SCHEMA_VERSION} are supported"
+        )));
+    }
+    return Ok(schema);
+}
+
 #[derive(
     Debug, Clone, Copy, PartialEq, Eq, Hash, Default, TryFromPrimitive, Serialize, Deserialize,
 )]
@@ -47,6 +138,155 @@ pub enum MemoryGrowErrorKind {
     Soft,
 }
 
+/// How WebAssembly traps (like `unreachable`) are translated
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Default, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum TrapMode {
+    /// Traps are translated into `OpUnreachable`, whose behavior is undefined
+    #[default]
+    Unreachable,
+    /// Traps hang the invocation in an infinite loop
+    InfiniteLoop,
+    /// Traps write a `1` into the storage buffer at the specified descriptor set and binding, and then return.
+    /// Traps after a recognized Rust panic write its [code](crate::diagnostics::PanicMessage) instead
+    Flag { set: u32, binding: u32 },
+    /// Traps are translated into `OpTerminateInvocation`. Traps outside of fragment shaders use `OpUnreachable`
+    TerminateInvocation,
+    /// Traps demote the invocation into a helper invocation, and then return. Traps outside of fragment shaders use
+    /// `OpUnreachable`
+    DemoteToHelperInvocation,
+}
+
+/// How the active data segments of a WebAssembly module are translated
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Default, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum DataSegmentMode {
+    /// Data segments are ignored, so the memory must be initialized by the host
+    #[default]
+    Ignore,
+    /// Loads from compile-time addresses inside a data segment are replaced by constants. The segments must be
+    /// read-only: stores to compile-time addresses inside them fail to compile, and stores to runtime addresses
+    /// are never seen by the replaced loads
+    Constant,
+    /// Accesses to the data segments are redirected to the storage buffer at the specified binding, which must be
+    /// initialized with [`ModuleBuilder::data_image`](crate::fg::module::ModuleBuilder::data_image)
+    Buffer { set: u32, binding: u32 },
+}
+
+/// Descriptor set and binding of the storage buffer backing a WebAssembly memory. Addresses are byte offsets into it
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Serialize, Deserialize)]
+pub struct MemoryBinding {
+    pub set: u32,
+    pub binding: u32,
+}
+
+/// Descriptor set and binding of the storage buffer the profiling counters are added into
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Serialize, Deserialize)]
+pub struct ProfilingBuffer {
+    pub set: u32,
+    pub binding: u32,
+}
+
+/// Descriptor set and binding of the storage buffer holding an atomic counter
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Serialize, Deserialize)]
+pub struct AtomicCounter {
+    pub set: u32,
+    pub binding: u32,
+}
+
+/// Settings a [profile](Config::profiles) overrides
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub struct Profile {
+    #[serde(default)]
+    pub platform: Option<TargetPlatform>,
+    #[serde(default)]
+    pub capabilities: Option<CapabilityModel>,
+    #[serde(default)]
+    pub extensions: Option<Box<[Str<'static>]>>,
+}
+
+/// Where the value of a WebAssembly global comes from, instead of being a private global
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum GlobalConfig {
+    /// The global is stored in its own buffer at the specified descriptor set and binding. Mutable globals must be
+    /// bound to storage buffers
+    DescriptorSet {
+        set: u32,
+        binding: u32,
+        #[serde(default)]
+        buffer: BufferKind,
+    },
+    /// The global is a member of the push constant block, shared by every global configured this way. Push constants
+    /// are read-only
+    PushConstant,
+}
+
+/// How much of the translation is logged through `tracing`, at the debug level
+#[derive(
+    Debug,
+    Clone,
+    Copy,
+    PartialEq,
+    Eq,
+    PartialOrd,
+    Ord,
+    Hash,
+    Default,
+    TryFromPrimitive,
+    Serialize,
+    Deserialize,
+)]
+#[cfg_attr(feature = "clap", derive(clap::ValueEnum))]
+#[serde(rename_all = "snake_case")]
+#[repr(u8)]
+pub enum Verbosity {
+    /// Nothing is logged
+    #[default]
+    Quiet,
+    /// Every function is built and translated inside of its own span
+    Functions,
+    /// Every WebAssembly block is translated inside of its own span
+    Blocks,
+    /// Decisions taken while handling operators are logged as events
+    Operators,
+}
+
+/// How modules using 64-bit floats are compiled
+#[derive(
+    Debug, Clone, Copy, PartialEq, Eq, Hash, Default, TryFromPrimitive, Serialize, Deserialize,
+)]
+#[serde(rename_all = "snake_case")]
+#[repr(u8)]
+pub enum Float64Mode {
+    /// 64-bit floats are translated as such, requiring the `Float64` capability
+    #[default]
+    Native,
+    /// The compilation fails, listing every function that uses 64-bit floats
+    Error,
+    /// 64-bit floats are translated as 32-bit ones, losing precision and range. Reinterpreting them from or into
+    /// 64-bit integers fails
+    Demote,
+}
+
+/// Whether the branches of a selection should be flattened (both executed) or kept as actual branches
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum SelectionHint {
+    Flatten,
+    DontFlatten,
+}
+
+impl SelectionHint {
+    pub fn selection_control(hint: Option<Self>, default: SelectionControl) -> SelectionControl {
+        return match hint {
+            Some(SelectionHint::Flatten) => SelectionControl::FLATTEN,
+            Some(SelectionHint::DontFlatten) => SelectionControl::DONT_FLATTEN,
+            None => default,
+        };
+    }
+}
+
 #[derive(
     Debug, Clone, Copy, PartialEq, Eq, Hash, Default, TryFromPrimitive, Serialize, Deserialize,
 )]
@@ -92,11 +332,32 @@ impl CapabilityModel {
         }
     }
 
+    /// Returns whether the capability is (or can be) enabled
+    pub fn can_enable(&self, capability: Capability) -> bool {
+        return match self {
+            CapabilityModel::Static(x) => x.contains(&capability),
+            CapabilityModel::Dynamic(_) => true,
+        };
+    }
+
     pub fn require(&self, capability: Capability) -> Result<()> {
+        return self.require_by(capability, || None);
+    }
+
+    pub fn require_mut(&mut self, capability: Capability) -> Result<()> {
+        return self.require_mut_by(capability, || None);
+    }
+
+    /// Requires the capability, explaining what needed it (`reason`) if it can't be enabled
+    pub fn require_by(
+        &self,
+        capability: Capability,
+        reason: impl FnOnce() -> Option<String>,
+    ) -> Result<()> {
         match self {
             CapabilityModel::Static(x) => {
                 if !x.contains(&capability) {
-                    return Err(Error::msg(format!("Unable to enable {capability:?}")));
+                    return Err(missing_capability(capability, reason()));
                 }
             }
             CapabilityModel::Dynamic(x) => {
@@ -109,11 +370,16 @@ impl CapabilityModel {
         Ok(())
     }
 
-    pub fn require_mut(&mut self, capability: Capability) -> Result<()> {
+    /// Like [`require_by`](Self::require_by), enabling the capability if required
+    pub fn require_mut_by(
+        &mut self,
+        capability: Capability,
+        reason: impl FnOnce() -> Option<String>,
+    ) -> Result<()> {
         match self {
             CapabilityModel::Static(x) => {
                 if !x.contains(&capability) {
-                    return Err(Error::msg(format!("Unable to enable {capability:?}")));
+                    return Err(missing_capability(capability, reason()));
                 }
             }
             CapabilityModel::Dynamic(x) => {
@@ -127,6 +393,24 @@ impl CapabilityModel {
     }
 }
 
+fn missing_capability(capability: Capability, reason: Option<String>) -> Error {
+    let mut msg = format!("Unable to enable {capability:?}");
+    if let Some(reason) = reason {
+        msg.push_str(&format!(", required by {reason}"));
+    }
+
+    msg.push_str(&format!(
+        ". Add \"{capability:?}\" to the static capabilities, or use a dynamic capability model"
+    ));
+    if let Some(extension) = crate::capabilities::capability_extension(capability) {
+        msg.push_str(&format!(
+            " (the capability also requires the \"{extension}\" extension)"
+        ));
+    }
+
+    return Error::msg(msg);
+}
+
 impl IntoIterator for CapabilityModel {
     type Item = Capability;
     type IntoIter = std::vec::IntoIter<Capability>;
@@ -148,6 +432,7 @@ impl Config {
         memory_model: MemoryModel,
     ) -> Result<ConfigBuilder> {
         let inner = Config {
+            schema: CONFIG_SCHEMA_VERSION,
             platform,
             features: WasmFeatures::default(),
             addressing_model,
@@ -156,10 +441,731 @@ impl Config {
             capabilities,
             extensions: extensions.into_iter().map(Into::into).collect(),
             memory_grow_error: Default::default(),
+            trap_mode: Default::default(),
+            bounds_checks: false,
+            selection_control: None,
+            inline_threshold: None,
+            memories: VecMap::new(),
+            globals: VecMap::new(),
+            data_segments: DataSegmentMode::default(),
+            sort_globals: false,
+            phi_merges: false,
+            profiling: None,
+            atomic_counters: VecMap::new(),
+            profiles: BTreeMap::new(),
+            entry_point_names: BTreeMap::new(),
+            internal_exports: Box::default(),
+            verbosity: Verbosity::default(),
+            forbidden_operators: Box::default(),
+            division_traps: false,
+            float64: Float64Mode::default(),
+            max_value_depth: None,
+            linkage: false,
         };
 
         return Ok(ConfigBuilder { inner });
     }
+
+    /// Creates a configuration with the platform's default models and core capabilities
+    pub fn for_platform(platform: TargetPlatform) -> Result<ConfigBuilder> {
+        let mut result = Self::builder(
+            platform,
+            CapabilityModel::dynamic(platform.core_capabilities()),
+            None::<&'static str>,
+            AddressingModel::Logical,
+            platform.default_memory_model(),
+        )?;
+
+        result.set_addressing_model(platform.default_addressing_model())?;
+        return Ok(result);
+    }
+
+    /// Creates a configuration for Vulkan compute shaders
+    ///
+    /// ```
+    /// use wasm2spirv::{config::Config, version::Version, Compilation};
+    ///
+    /// let wasm = wat::parse_str(r#"(module (func (export "main")))"#).unwrap();
+    /// let config = Config::vulkan_compute(Version::V1_1)?
+    ///     .compute_entry_point(0, [64, 1, 1])?
+    ///     .set_bounds_checks(true)
+    ///     .build()?;
+    /// assert!(config.validate().is_ok());
+    ///
+    /// let compilation = Compilation::new(config, &wasm)?;
+    /// assert!(compilation.assembly()?.contains("OpEntryPoint GLCompute"));
+    /// # Ok::<_, wasm2spirv::error::Error>(())
+    /// ```
+    pub fn vulkan_compute(version: Version) -> Result<ConfigBuilder> {
+        return Self::builder(
+            TargetPlatform::Vulkan(version),
+            CapabilityModel::dynamic(vec![Capability::Shader]),
+            None::<&'static str>,
+            AddressingModel::Logical,
+            MemoryModel::GLSL450,
+        );
+    }
+
+    /// Creates a configuration for Vulkan graphics shaders
+    ///
+    /// ```
+    /// use wasm2spirv::{
+    ///     config::{Config, TrapMode},
+    ///     version::Version,
+    /// };
+    ///
+    /// let config = Config::vulkan_graphics(Version::V1_2)?
+    ///     .vertex_entry_point(0)?
+    ///     .fragment_entry_point(1)?
+    ///     .build()?;
+    /// assert!(config.validate().is_ok());
+    ///
+    /// // Traps may only terminate fragment shaders
+    /// let config = Config::vulkan_graphics(Version::V1_2)?
+    ///     .fragment_entry_point(0)?
+    ///     .set_trap_mode(TrapMode::TerminateInvocation)
+    ///     .build()?;
+    /// assert!(config.validate().is_ok());
+    /// # Ok::<_, wasm2spirv::error::Error>(())
+    /// ```
+    pub fn vulkan_graphics(version: Version) -> Result<ConfigBuilder> {
+        return Self::builder(
+            TargetPlatform::Vulkan(version),
+            CapabilityModel::dynamic(vec![Capability::Shader, Capability::Matrix]),
+            None::<&'static str>,
+            AddressingModel::Logical,
+            MemoryModel::GLSL450,
+        );
+    }
+
+    /// Creates a configuration for OpenCL 2.1 kernels
+    ///
+    /// ```
+    /// use wasm2spirv::{config::Config, Compilation};
+    ///
+    /// let wasm = wat::parse_str(r#"(module (func (export "main")))"#).unwrap();
+    /// let config = Config::opencl_kernel()?.kernel_entry_point(0)?.build()?;
+    /// assert!(config.validate().is_ok());
+    ///
+    /// let compilation = Compilation::new(config, &wasm)?;
+    /// let assembly = compilation.assembly()?;
+    /// assert!(assembly.contains("OpMemoryModel Physical32 OpenCL"));
+    /// assert!(assembly.contains("OpEntryPoint Kernel"));
+    /// # Ok::<_, wasm2spirv::error::Error>(())
+    /// ```
+    pub fn opencl_kernel() -> Result<ConfigBuilder> {
+        return Self::for_platform(TargetPlatform::CL_2_1);
+    }
+}
+
+/// Problems found by [`Config::validate`]
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub struct ConfigReport {
+    /// Settings that will make the compilation fail, or produce an invalid module
+    pub errors: Vec<String>,
+    /// Settings that are likely a mistake, but don't prevent the compilation
+    pub warnings: Vec<String>,
+}
+
+impl ConfigReport {
+    /// Returns `true` if no errors were found
+    pub fn is_ok(&self) -> bool {
+        return self.errors.is_empty();
+    }
+
+    /// Returns the warnings, or an error listing every error that was found
+    pub fn into_result(self) -> Result<Vec<String>> {
+        if self.errors.is_empty() {
+            return Ok(self.warnings);
+        }
+        return Err(Error::msg(format!(
+            "Invalid configuration: {}",
+            self.errors.join(". ")
+        )));
+    }
+}
+
+impl Config {
+    /// Checks the configuration for inconsistencies, without compiling anything
+    pub fn validate(&self) -> ConfigReport {
+        let mut report = ConfigReport::default();
+        if let Err(e) = self.platform.check() {
+            report.errors.push(e.to_string());
+        }
+        let version = self.platform.spirv_version();
+        let is_shader = matches!(
+            self.platform,
+            TargetPlatform::Vulkan(_) | TargetPlatform::OpenGL(_)
+        );
+        let is_kernel = matches!(self.platform, TargetPlatform::OpenCL(_));
+
+        // Capabilities
+        let mut required = self
+            .addressing_model
+            .required_capabilities()
+            .into_iter()
+            .map(|x| {
+                (
+                    x,
+                    format!("the {:?} addressing model", self.addressing_model),
+                )
+            })
+            .chain(
+                operand_capabilities(&Operand::MemoryModel(self.memory_model))
+                    .into_iter()
+                    .map(|x| (x, format!("the {:?} memory model", self.memory_model))),
+            )
+            .collect::<Vec<_>>();
+
+        if self.features.memory64 {
+            required.push((Capability::Int64, String::from("the memory64 proposal")));
+        }
+        for (idx, execution_model) in self.entry_points() {
+            required.extend(
+                operand_capabilities(&Operand::ExecutionModel(execution_model))
+                    .into_iter()
+                    .map(|x| {
+                        (
+                            x,
+                            format!("the {execution_model:?} execution model of function {idx}"),
+                        )
+                    }),
+            );
+        }
+
+        for (capability, reason) in required.iter() {
+            if !self.capabilities.can_enable(*capability) {
+                report.errors.push(format!(
+                    "{capability:?} is required by {reason}, but isn't one of the static capabilities"
+                ));
+            }
+        }
+
+        if self.platform.is_vulkan() && self.features.memory64 {
+            report.warnings.push(String::from(
+                "The memory64 proposal requires 64-bit integers, which are an optional feature (`shaderInt64`) of Vulkan devices",
+            ));
+        }
+
+        // Extensions
+        if let CapabilityModel::Static(_) = self.capabilities {
+            for (capability, reason) in required.iter() {
+                let Some(extension) = capability_extension(*capability) else {
+                    continue;
+                };
+                if !self.has_extension(extension) && !core_since(extension, version) {
+                    report.errors.push(format!(
+                        "{capability:?} (required by {reason}) requires the \"{extension}\" extension, which isn't enabled"
+                    ));
+                }
+            }
+        }
+
+        for extension in self.extensions.iter() {
+            if self.platform.core_extensions().contains(&&**extension) {
+                report.warnings.push(format!(
+                    "The \"{}\" extension is already part of {:?}",
+                    &**extension, self.platform
+                ));
+            }
+        }
+
+        // Addressing and memory models
+        match self.addressing_model {
+            AddressingModel::Physical if is_shader => report.errors.push(format!(
+                "The physical addressing model isn't supported by {:?}",
+                self.platform
+            )),
+            AddressingModel::Logical if is_kernel => report.errors.push(String::from(
+                "OpenCL kernels require a physical addressing model",
+            )),
+            AddressingModel::PhysicalStorageBuffer => {
+                if !self.features.memory64 {
+                    report.errors.push(String::from(
+                        "The physical storage buffer addressing model requires a 64-bit (memory64) WebAssembly memory",
+                    ));
+                }
+                if matches!(self.platform, TargetPlatform::Vulkan(x) if x < Version::V1_2) {
+                    report.warnings.push(format!(
+                        "The physical storage buffer addressing model requires the `VK_KHR_buffer_device_address` device extension on {:?}",
+                        self.platform
+                    ));
+                }
+            }
+            _ => {}
+        }
+
+        match self.memory_model {
+            MemoryModel::OpenCL if is_shader => report.errors.push(format!(
+                "The OpenCL memory model isn't supported by {:?}",
+                self.platform
+            )),
+            MemoryModel::Simple | MemoryModel::GLSL450 | MemoryModel::Vulkan if is_kernel => {
+                report.errors.push(format!(
+                    "OpenCL kernels require the OpenCL memory model, not {:?}",
+                    self.memory_model
+                ))
+            }
+            _ => {}
+        }
+
+        // Entry points
+        let entry_points = self.entry_points().collect::<Vec<_>>();
+        if entry_points.is_empty() {
+            report
+                .warnings
+                .push(String::from("No function is configured as an entry point"));
+        }
+
+        for (idx, execution_model) in entry_points.iter().copied() {
+            let function = &self.functions[&idx];
+            if is_shader && execution_model == ExecutionModel::Kernel {
+                report.errors.push(format!(
+                    "Function {idx} is a kernel, which isn't supported by {:?}",
+                    self.platform
+                ));
+            }
+            if is_kernel && execution_model != ExecutionModel::Kernel {
+                report.errors.push(format!(
+                    "Function {idx} uses the {execution_model:?} execution model, but OpenCL only supports kernels"
+                ));
+            }
+
+            if execution_model == ExecutionModel::GLCompute
+                && !function.execution_modes.iter().any(|x| {
+                    matches!(
+                        x,
+                        ExecutionMode::LocalSize(..) | ExecutionMode::LocalSizeId(..)
+                    )
+                })
+            {
+                report.warnings.push(format!(
+                    "Compute shader {idx} doesn't specify its local size"
+                ));
+            }
+
+            // Execution modes
+            for mode in function.execution_modes.iter() {
+                let models: &[ExecutionModel] = match mode {
+                    ExecutionMode::LocalSize(..)
+                    | ExecutionMode::LocalSizeHint(..)
+                    | ExecutionMode::LocalSizeId(..) => &[
+                        ExecutionModel::GLCompute,
+                        ExecutionModel::Kernel,
+                        ExecutionModel::TaskNV,
+                        ExecutionModel::MeshNV,
+                    ],
+                    ExecutionMode::Invocations(_) => &[ExecutionModel::Geometry],
+                    ExecutionMode::PixelCenterInteger
+                    | ExecutionMode::OriginUpperLeft
+                    | ExecutionMode::OriginLowerLeft
+                    | ExecutionMode::DepthReplacing
+                    | ExecutionMode::DepthGreater
+                    | ExecutionMode::DepthLess
+                    | ExecutionMode::DepthUnchanged
+                    | ExecutionMode::EarlyFragmentTests => &[ExecutionModel::Fragment],
+                    ExecutionMode::InputPoints
+                    | ExecutionMode::InputLines
+                    | ExecutionMode::InputLinesAdjacency
+                    | ExecutionMode::InputTrianglesAdjacency
+                    | ExecutionMode::OutputLineStrip
+                    | ExecutionMode::OutputTriangleStrip => &[ExecutionModel::Geometry],
+                    ExecutionMode::Triangles => &[
+                        ExecutionModel::Geometry,
+                        ExecutionModel::TessellationControl,
+                        ExecutionModel::TessellationEvaluation,
+                    ],
+                    ExecutionMode::OutputVertices(_) => &[
+                        ExecutionModel::Geometry,
+                        ExecutionModel::TessellationControl,
+                        ExecutionModel::TessellationEvaluation,
+                        ExecutionModel::MeshNV,
+                    ],
+                    ExecutionMode::OutputPoints => {
+                        &[ExecutionModel::Geometry, ExecutionModel::MeshNV]
+                    }
+                    ExecutionMode::OutputPrimitives(_)
+                    | ExecutionMode::OutputLines
+                    | ExecutionMode::OutputTriangles => &[ExecutionModel::MeshNV],
+                    ExecutionMode::Quads
+                    | ExecutionMode::Isolines
+                    | ExecutionMode::SpacingEqual
+                    | ExecutionMode::SpacingFractionalEven
+                    | ExecutionMode::SpacingFractionalOdd
+                    | ExecutionMode::VertexOrderCw
+                    | ExecutionMode::VertexOrderCcw
+                    | ExecutionMode::PointMode => &[
+                        ExecutionModel::TessellationControl,
+                        ExecutionModel::TessellationEvaluation,
+                    ],
+                };
+                if !models.contains(&execution_model) {
+                    report.errors.push(format!(
+                        "The {mode:?} execution mode of function {idx} is only valid for {models:?} entry points, not {execution_model:?}"
+                    ));
+                }
+                if matches!(mode, ExecutionMode::LocalSizeId(..)) && version < Version::V1_2 {
+                    report.errors.push(format!(
+                        "The {mode:?} execution mode of function {idx} requires SPIR-V 1.2, but {:?} uses an earlier version",
+                        self.platform
+                    ));
+                }
+            }
+
+            let has_mode = |f: fn(&ExecutionMode) -> bool| function.execution_modes.iter().any(f);
+            let mut missing = Vec::new();
+            if matches!(
+                execution_model,
+                ExecutionModel::Geometry
+                    | ExecutionModel::TessellationControl
+                    | ExecutionModel::MeshNV
+            ) && !has_mode(|x| matches!(x, ExecutionMode::OutputVertices(_)))
+            {
+                missing.push("its number of output vertices (OutputVertices)");
+            }
+            if execution_model == ExecutionModel::MeshNV {
+                if !has_mode(|x| matches!(x, ExecutionMode::OutputPrimitives(_))) {
+                    missing.push("its number of output primitives (OutputPrimitives)");
+                }
+                if !has_mode(|x| {
+                    matches!(
+                        x,
+                        ExecutionMode::OutputPoints
+                            | ExecutionMode::OutputLines
+                            | ExecutionMode::OutputTriangles
+                    )
+                }) {
+                    missing.push("its output primitive (like OutputTriangles)");
+                }
+            }
+            if matches!(
+                execution_model,
+                ExecutionModel::TaskNV | ExecutionModel::MeshNV
+            ) && !has_mode(|x| {
+                matches!(
+                    x,
+                    ExecutionMode::LocalSize(..) | ExecutionMode::LocalSizeId(..)
+                )
+            }) {
+                missing.push("its local size (LocalSize)");
+            }
+            if execution_model == ExecutionModel::Geometry {
+                if !has_mode(|x| {
+                    matches!(
+                        x,
+                        ExecutionMode::InputPoints
+                            | ExecutionMode::InputLines
+                            | ExecutionMode::InputLinesAdjacency
+                            | ExecutionMode::Triangles
+                            | ExecutionMode::InputTrianglesAdjacency
+                    )
+                }) {
+                    missing.push("its input primitive (like InputPoints or Triangles)");
+                }
+                if !has_mode(|x| {
+                    matches!(
+                        x,
+                        ExecutionMode::OutputPoints
+                            | ExecutionMode::OutputLineStrip
+                            | ExecutionMode::OutputTriangleStrip
+                    )
+                }) {
+                    missing.push("its output primitive (like OutputTriangleStrip)");
+                }
+            }
+            for mode in missing {
+                report.errors.push(format!(
+                    "{execution_model:?} shader {idx} doesn't specify {mode}"
+                ));
+            }
+
+            let depth_modes = function
+                .execution_modes
+                .iter()
+                .filter(|x| {
+                    matches!(
+                        x,
+                        ExecutionMode::DepthGreater
+                            | ExecutionMode::DepthLess
+                            | ExecutionMode::DepthUnchanged
+                    )
+                })
+                .collect::<Vec<_>>();
+            if depth_modes.len() > 1 {
+                report.errors.push(format!(
+                    "Fragment shader {idx} can only declare one of DepthGreater, DepthLess and DepthUnchanged, but declares {depth_modes:?}"
+                ));
+            }
+
+            if execution_model == ExecutionModel::Fragment && self.platform.is_vulkan() {
+                if function
+                    .execution_modes
+                    .iter()
+                    .any(|x| matches!(x, ExecutionMode::OriginLowerLeft))
+                {
+                    report.errors.push(format!(
+                        "Vulkan doesn't support the OriginLowerLeft execution mode of fragment shader {idx}, use OriginUpperLeft instead"
+                    ));
+                } else if !function
+                    .execution_modes
+                    .iter()
+                    .any(|x| matches!(x, ExecutionMode::OriginUpperLeft))
+                {
+                    report.errors.push(format!(
+                        "Vulkan requires fragment shader {idx} to use the OriginUpperLeft execution mode"
+                    ));
+                }
+            }
+
+            // Ray tracing
+            const RAY_TRACING: [ExecutionModel; 6] = [
+                ExecutionModel::RayGenerationKHR,
+                ExecutionModel::IntersectionKHR,
+                ExecutionModel::AnyHitKHR,
+                ExecutionModel::ClosestHitKHR,
+                ExecutionModel::MissKHR,
+                ExecutionModel::CallableKHR,
+            ];
+            if RAY_TRACING.contains(&execution_model) && version < Version::V1_4 {
+                report.errors.push(format!(
+                    "Function {idx} is a {execution_model:?} entry point, which requires SPIR-V 1.4, but {:?} uses an earlier version",
+                    self.platform
+                ));
+            }
+
+            // Parameter kinds
+            let mut locations = Vec::new();
+            for (param, config) in function.params.iter() {
+                let models: &[ExecutionModel] = match config.kind {
+                    ParameterKind::ShaderRecordBuffer => &RAY_TRACING,
+                    ParameterKind::RayPayload(_) => &[
+                        ExecutionModel::RayGenerationKHR,
+                        ExecutionModel::ClosestHitKHR,
+                        ExecutionModel::MissKHR,
+                    ],
+                    ParameterKind::IncomingRayPayload => &[
+                        ExecutionModel::AnyHitKHR,
+                        ExecutionModel::ClosestHitKHR,
+                        ExecutionModel::MissKHR,
+                    ],
+                    _ => &[],
+                };
+                if !models.is_empty() && !models.contains(&execution_model) {
+                    report.errors.push(format!(
+                        "Parameter {param} of function {idx} is a {:?} parameter, which is only valid for {models:?} entry points, not {execution_model:?}",
+                        config.kind
+                    ));
+                }
+
+                let (direction, location) = match config.kind {
+                    ParameterKind::Input(location) => ("input", location),
+                    ParameterKind::Output(location) => ("output", location),
+                    ParameterKind::RayPayload(location) => ("ray payload", location),
+                    _ => continue,
+                };
+
+                if direction != "ray payload"
+                    && matches!(
+                        execution_model,
+                        ExecutionModel::GLCompute | ExecutionModel::Kernel
+                    )
+                {
+                    report.errors.push(format!(
+                        "Parameter {param} of function {idx} uses {direction} location {location}, but {execution_model:?} entry points have no {direction} locations. Bind it to a descriptor set instead"
+                    ));
+                }
+
+                match locations
+                    .iter()
+                    .find(|(_, d, l)| *d == direction && *l == location)
+                {
+                    Some((other, _, _)) => report.errors.push(format!(
+                        "Parameters {other} and {param} of function {idx} both use {direction} location {location}"
+                    )),
+                    None => locations.push((*param, direction, location)),
+                }
+            }
+
+            if execution_model != ExecutionModel::Fragment
+                && self.trap_mode == TrapMode::TerminateInvocation
+            {
+                report.errors.push(format!(
+                    "Traps can only terminate the invocation of fragment shaders, but function {idx} is a {execution_model:?} entry point"
+                ));
+            }
+            if execution_model != ExecutionModel::Fragment
+                && self.trap_mode == TrapMode::DemoteToHelperInvocation
+            {
+                report.errors.push(format!(
+                    "Traps can only demote fragment shader invocations, but function {idx} is a {execution_model:?} entry point"
+                ));
+            }
+        }
+
+        // Floating point arithmetic
+        for (idx, function) in self.functions.iter() {
+            if function.fast_math.no_contraction && is_kernel {
+                report.errors.push(format!(
+                    "Function {idx} forbids contraction, which isn't supported by OpenCL kernels"
+                ));
+            }
+            if !function.fast_math.mode().is_empty() && is_shader {
+                report.errors.push(format!(
+                    "Function {idx} uses fast math modes, which aren't supported by {:?}",
+                    self.platform
+                ));
+            }
+        }
+
+        // Descriptor sets
+        let mut bindings = Vec::new();
+        for (idx, function) in self.functions.iter() {
+            for (param, config) in function.params.iter() {
+                if let ParameterKind::DescriptorSet { set, binding, .. } = config.kind {
+                    bindings.push((set, binding, format!("parameter {param} of function {idx}")));
+                }
+            }
+        }
+        for (memory, MemoryBinding { set, binding }) in self.memories.iter() {
+            bindings.push((*set, *binding, format!("memory {memory}")));
+        }
+        for (global, config) in self.globals.iter() {
+            if let GlobalConfig::DescriptorSet { set, binding, .. } = config {
+                bindings.push((*set, *binding, format!("global {global}")));
+            }
+        }
+        if let TrapMode::Flag { set, binding } = self.trap_mode {
+            bindings.push((set, binding, String::from("the trap flag")));
+        }
+        if let DataSegmentMode::Buffer { set, binding } = self.data_segments {
+            bindings.push((set, binding, String::from("the data segments")));
+        }
+        if let Some(ProfilingBuffer { set, binding }) = self.profiling {
+            bindings.push((set, binding, String::from("the profiling counters")));
+        }
+        for (counter, AtomicCounter { set, binding }) in self.atomic_counters.iter() {
+            bindings.push((*set, *binding, format!("atomic counter {counter}")));
+        }
+
+        if is_kernel
+            || entry_points
+                .iter()
+                .any(|(_, x)| *x == ExecutionModel::Kernel)
+        {
+            for (_, _, user) in bindings.iter() {
+                report.errors.push(format!(
+                    "OpenCL kernels don't have descriptor sets, but {user} is bound to one"
+                ));
+            }
+        }
+
+        for (i, (set, binding, user)) in bindings.iter().enumerate() {
+            if let Some((_, _, other)) = bindings[..i]
+                .iter()
+                .find(|(s, b, _)| s == set && b == binding)
+            {
+                report.warnings.push(format!(
+                    "Both {other} and {user} are bound to descriptor set {set}, binding {binding}"
+                ));
+            }
+        }
+
+        // Entry points
+        for name in self.entry_point_names.keys() {
+            if self.internal_exports.contains(name) {
+                report.warnings.push(format!(
+                    "Export `{name}` is renamed, but it's internal, so it won't be an entry point"
+                ));
+            }
+        }
+
+        // Forbidden operators
+        for pattern in self.forbidden_operators.iter() {
+            if pattern.starts_with(char::is_lowercase) && !OPERATOR_GROUPS.contains(&&**pattern) {
+                report.errors.push(format!(
+                    "Unknown operator group `{pattern}` is forbidden. Groups are {OPERATOR_GROUPS:?}, and operators are named like `F32Add`"
+                ));
+            }
+        }
+
+        // Profiles only report the problems their overrides introduce
+        let shared = report.clone();
+        for (name, _) in self.profiles.iter() {
+            let Some(profile) = self.profile(name) else {
+                continue;
+            };
+
+            let ConfigReport { errors, warnings } = profile.validate();
+            for error in errors.into_iter().filter(|x| !shared.errors.contains(x)) {
+                report.errors.push(format!("Profile `{name}`: {error}"));
+            }
+            for warning in warnings
+                .into_iter()
+                .filter(|x| !shared.warnings.contains(x))
+            {
+                report.warnings.push(format!("Profile `{name}`: {warning}"));
+            }
+        }
+
+        return report;
+    }
+
+    /// Returns the configuration of the profile, applying its overrides to the shared settings, or `None` if there's no
+    /// such profile
+    pub fn profile(&self, name: &str) -> Option<Config> {
+        let profile = self.profiles.get(name)?;
+        return Some(Config {
+            platform: profile.platform.unwrap_or(self.platform),
+            capabilities: profile
+                .capabilities
+                .clone()
+                .unwrap_or_else(|| self.capabilities.clone()),
+            extensions: profile
+                .extensions
+                .clone()
+                .unwrap_or_else(|| self.extensions.clone()),
+            profiles: BTreeMap::new(),
+            ..self.clone()
+        });
+    }
+
+    /// Returns the entry of [`forbidden_operators`](Config::forbidden_operators) forbidding the operator, if any
+    pub fn forbidding(&self, operator: &str) -> Option<&str> {
+        return self
+            .forbidden_operators
+            .iter()
+            .map(|x| &**x)
+            .find(|pattern| match *pattern {
+                "float" => operator.contains("F32") || operator.contains("F64"),
+                "f32" => operator.contains("F32"),
+                "f64" => operator.contains("F64"),
+                "i64" => operator.contains("I64"),
+                "atomic" => operator.contains("Atomic"),
+                "simd" => {
+                    operator.starts_with("V128")
+                        || ["x2", "x4", "x8", "x16"]
+                            .iter()
+                            .any(|lanes| operator.contains(lanes))
+                }
+                name => name == operator,
+            });
+    }
+
+    fn entry_points(&self) -> impl '_ + Iterator<Item = (u32, ExecutionModel)> {
+        return self
+            .functions
+            .iter()
+            .filter_map(|(idx, x)| Some((*idx, x.execution_model?)));
+    }
+
+    fn has_extension(&self, extension: &str) -> bool {
+        return self.extensions.iter().any(|x| &**x == extension)
+            || self.platform.core_extensions().contains(&extension);
+    }
+}
+
+fn core_since(extension: &str, version: Version) -> bool {
+    return extension_core_version(extension).is_some_and(|since| version >= since);
 }
 
 impl ConfigBuilder {
@@ -168,13 +1174,26 @@ impl ConfigBuilder {
         self.inner.capabilities.require_mut(capability)
     }
 
+    /// Like [`require_capability`](Self::require_capability), explaining what needed the capability
+    pub fn require_capability_by(
+        &mut self,
+        capability: Capability,
+        reason: impl FnOnce() -> Option<String>,
+    ) -> Result<()> {
+        self.inner.capabilities.require_mut_by(capability, reason)
+    }
+
     pub fn set_addressing_model(&mut self, addressing_model: AddressingModel) -> Result<&mut Self> {
         match addressing_model {
             AddressingModel::Logical => {}
-            AddressingModel::Physical => self.require_capability(Capability::Addresses)?,
-            AddressingModel::PhysicalStorageBuffer => {
-                self.require_capability(Capability::PhysicalStorageBufferAddresses)?
-            }
+            AddressingModel::Physical => self
+                .require_capability_by(Capability::Addresses, || {
+                    Some(String::from("the physical addressing model"))
+                })?,
+            AddressingModel::PhysicalStorageBuffer => self
+                .require_capability_by(Capability::PhysicalStorageBufferAddresses, || {
+                    Some(String::from("the physical storage buffer addressing model"))
+                })?,
         }
 
         self.inner.addressing_model = addressing_model;
@@ -182,13 +1201,15 @@ impl ConfigBuilder {
     }
 
     pub fn set_memory_model(&mut self, memory_model: MemoryModel) -> Result<&mut Self> {
-        match memory_model {
-            MemoryModel::Simple | MemoryModel::GLSL450 => {
-                self.require_capability(Capability::Shader)?
-            }
-            MemoryModel::OpenCL => self.require_capability(Capability::Kernel)?,
-            MemoryModel::Vulkan => self.require_capability(Capability::VulkanMemoryModel)?,
-        }
+        let capability = match memory_model {
+            MemoryModel::Simple | MemoryModel::GLSL450 => Capability::Shader,
+            MemoryModel::OpenCL => Capability::Kernel,
+            MemoryModel::Vulkan => Capability::VulkanMemoryModel,
+        };
+
+        self.require_capability_by(capability, || {
+            Some(format!("the {memory_model:?} memory model"))
+        })?;
 
         self.inner.memory_model = memory_model;
         Ok(self)
@@ -199,11 +1220,171 @@ impl ConfigBuilder {
         self
     }
 
+    pub fn set_trap_mode(&mut self, trap_mode: TrapMode) -> &mut Self {
+        self.inner.trap_mode = trap_mode;
+        self
+    }
+
+    pub fn set_bounds_checks(&mut self, bounds_checks: bool) -> &mut Self {
+        self.inner.bounds_checks = bounds_checks;
+        self
+    }
+
+    pub fn set_selection_control(&mut self, selection_control: Option<SelectionHint>) -> &mut Self {
+        self.inner.selection_control = selection_control;
+        self
+    }
+
+    pub fn set_inline_threshold(&mut self, inline_threshold: Option<u32>) -> &mut Self {
+        self.inner.inline_threshold = inline_threshold;
+        self
+    }
+
+    pub fn set_global(&mut self, global: u32, config: GlobalConfig) -> &mut Self {
+        self.inner.globals.insert(global, config);
+        self
+    }
+
+    pub fn set_data_segments(&mut self, data_segments: DataSegmentMode) -> &mut Self {
+        self.inner.data_segments = data_segments;
+        self
+    }
+
+    pub fn set_sort_globals(&mut self, sort_globals: bool) -> &mut Self {
+        self.inner.sort_globals = sort_globals;
+        self
+    }
+
+    pub fn set_phi_merges(&mut self, phi_merges: bool) -> &mut Self {
+        self.inner.phi_merges = phi_merges;
+        self
+    }
+
+    pub fn set_verbosity(&mut self, verbosity: Verbosity) -> &mut Self {
+        self.inner.verbosity = verbosity;
+        self
+    }
+
+    pub fn set_division_traps(&mut self, division_traps: bool) -> &mut Self {
+        self.inner.division_traps = division_traps;
+        self
+    }
+
+    pub fn set_float64(&mut self, float64: Float64Mode) -> &mut Self {
+        self.inner.float64 = float64;
+        self
+    }
+
+    pub fn set_max_value_depth(&mut self, max_value_depth: Option<u32>) -> &mut Self {
+        self.inner.max_value_depth = max_value_depth;
+        self
+    }
+
+    pub fn set_linkage(&mut self, linkage: bool) -> &mut Self {
+        self.inner.linkage = linkage;
+        self
+    }
+
+    /// Forbids the operator, or group of operators (see [`Config::forbidden_operators`])
+    pub fn forbid_operator(&mut self, operator: impl Into<Box<str>>) -> &mut Self {
+        let mut forbidden = std::mem::take(&mut self.inner.forbidden_operators).into_vec();
+        forbidden.push(operator.into());
+        self.inner.forbidden_operators = forbidden.into_boxed_slice();
+        self
+    }
+
+    /// Enables profiling into the storage buffer at the specified descriptor set and binding
+    pub fn set_profiling(&mut self, set: u32, binding: u32) -> &mut Self {
+        self.inner.profiling = Some(ProfilingBuffer { set, binding });
+        self
+    }
+
+    pub fn set_memory_binding(&mut self, memory: u32, set: u32, binding: u32) -> &mut Self {
+        self.inner
+            .memories
+            .insert(memory, MemoryBinding { set, binding });
+        self
+    }
+
+    /// Backs the atomic counter with the storage buffer at the specified descriptor set and binding
+    pub fn set_atomic_counter(&mut self, counter: u32, set: u32, binding: u32) -> &mut Self {
+        self.inner
+            .atomic_counters
+            .insert(counter, AtomicCounter { set, binding });
+        self
+    }
+
     pub fn set_features(&mut self, features: WasmFeatures) -> &mut Self {
         self.inner.features = features;
         self
     }
 
+    /// Makes the function a compute shader entry point, with the specified local (workgroup) size
+    pub fn compute_entry_point(&mut self, f_idx: u32, local_size: [u32; 3]) -> Result<&mut Self> {
+        let [x, y, z] = local_size;
+        return self.entry_point(
+            f_idx,
+            ExecutionModel::GLCompute,
+            [ExecutionMode::LocalSize(x, y, z)],
+        );
+    }
+
+    /// Makes the function a vertex shader entry point
+    pub fn vertex_entry_point(&mut self, f_idx: u32) -> Result<&mut Self> {
+        return self.entry_point(f_idx, ExecutionModel::Vertex, []);
+    }
+
+    /// Makes the function a fragment shader entry point, with its origin on the upper left (as required by Vulkan)
+    pub fn fragment_entry_point(&mut self, f_idx: u32) -> Result<&mut Self> {
+        return self.entry_point(
+            f_idx,
+            ExecutionModel::Fragment,
+            [ExecutionMode::OriginUpperLeft],
+        );
+    }
+
+    /// Makes the function an OpenCL kernel
+    pub fn kernel_entry_point(&mut self, f_idx: u32) -> Result<&mut Self> {
+        return self.entry_point(f_idx, ExecutionModel::Kernel, []);
+    }
+
+    fn entry_point(
+        &mut self,
+        f_idx: u32,
+        execution_model: ExecutionModel,
+        execution_modes: impl IntoIterator<Item = ExecutionMode>,
+    ) -> Result<&mut Self> {
+        for capability in operand_capabilities(&Operand::ExecutionModel(execution_model)) {
+            self.require_capability_by(capability, || {
+                Some(format!(
+                    "the {execution_model:?} execution model of function {f_idx}"
+                ))
+            })?;
+        }
+
+        if self.inner.functions.get(&f_idx).is_none() {
+            self.inner
+                .functions
+                .insert(f_idx, FunctionConfig::default());
+        }
+        let function = self
+            .inner
+            .functions
+            .get_mut(&f_idx)
+            .ok_or_else(Error::unexpected)?;
+
+        function.execution_model = Some(execution_model);
+        for mode in execution_modes {
+            // Replaces the previous mode of the same kind (like another local size)
+            function
+                .execution_modes
+                .retain(|x| std::mem::discriminant(x) != std::mem::discriminant(&mode));
+            function.execution_modes.push(mode);
+        }
+
+        return Ok(self);
+    }
+
     pub fn function<'a>(&'a mut self, f_idx: u32) -> FunctionConfigBuilder<'a> {
         return FunctionConfigBuilder {
             inner: Default::default(),
@@ -231,6 +1412,67 @@ impl ConfigBuilder {
 pub struct WasmFeatures {
     pub memory64: bool,
     pub saturating_float_to_int: bool,
+    #[serde(default)]
+    pub multi_memory: bool,
+    #[serde(default)]
+    pub tail_call: bool,
+    /// Thrown exceptions are translated as traps, and `catch` clauses are never reached
+    #[serde(default)]
+    pub exceptions: bool,
+}
+
+impl WasmFeatures {
+    /// Names of the WebAssembly proposals that can be enabled
+    pub const PROPOSALS: [&'static str; 5] = [
+        "memory64",
+        "saturating_float_to_int",
+        "multi_memory",
+        "tail_call",
+        "exceptions",
+    ];
+
+    /// Returns whether the proposal is enabled, or `None` if there isn't a proposal with that name
+    pub fn is_enabled(&self, proposal: &str) -> Option<bool> {
+        let mut features = *self;
+        return features.flag(proposal).map(|x| *x);
+    }
+
+    /// Enables or disables the proposal. Returns `false` if there isn't a proposal with that name
+    pub fn set_enabled(&mut self, proposal: &str, enabled: bool) -> bool {
+        match self.flag(proposal) {
+            Some(flag) => {
+                *flag = enabled;
+                return true;
+            }
+            None => return false,
+        }
+    }
+
+    /// Names of the enabled proposals
+    pub fn enabled(&self) -> impl '_ + Iterator<Item = &'static str> {
+        return Self::PROPOSALS
+            .into_iter()
+            .filter(|x| self.is_enabled(x) == Some(true));
+    }
+
+    /// Returns the proposals enabled by either set of features
+    pub fn union(mut self, other: Self) -> Self {
+        for proposal in other.enabled() {
+            self.set_enabled(proposal, true);
+        }
+        return self;
+    }
+
+    fn flag(&mut self, proposal: &str) -> Option<&mut bool> {
+        return Some(match proposal {
+            "memory64" => &mut self.memory64,
+            "saturating_float_to_int" => &mut self.saturating_float_to_int,
+            "multi_memory" => &mut self.multi_memory,
+            "tail_call" => &mut self.tail_call,
+            "exceptions" => &mut self.exceptions,
+            _ => return None,
+        });
+    }
 }
 
 impl Into<wasmparser::WasmFeatures> for WasmFeatures {
@@ -238,6 +1480,9 @@ impl Into<wasmparser::WasmFeatures> for WasmFeatures {
         return wasmparser::WasmFeatures {
             memory64: self.memory64,
             saturating_float_to_int: self.saturating_float_to_int,
+            multi_memory: self.multi_memory,
+            tail_call: self.tail_call,
+            exceptions: self.exceptions,
             ..Default::default()
         };
     }
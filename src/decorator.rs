@@ -10,6 +10,13 @@ pub enum VariableDecorator {
     Binding(u32),
     Location(u32),
     Flat,
+    RelaxedPrecision,
+    NonWritable,
+    NonReadable,
+    Restrict,
+    Aliased,
+    Volatile,
+    Coherent,
 }
 
 #[derive(Debug, Clone, PartialEq)]
@@ -23,6 +30,8 @@ pub enum TypeDecorator {
 pub enum TypeMemberDecorator {
     Offset(u32),
     NonWriteable,
+    ColMajor,
+    MatrixStride(u32),
 }
 
 impl VariableDecorator {
@@ -43,6 +52,19 @@ impl VariableDecorator {
                 builder.decorate(target, Decoration::Location, [Operand::LiteralInt32(*x)])
             }
             VariableDecorator::Flat => builder.decorate(target, Decoration::Flat, None),
+            VariableDecorator::RelaxedPrecision => {
+                builder.decorate(target, Decoration::RelaxedPrecision, None)
+            }
+            VariableDecorator::NonWritable => {
+                builder.decorate(target, Decoration::NonWritable, None)
+            }
+            VariableDecorator::NonReadable => {
+                builder.decorate(target, Decoration::NonReadable, None)
+            }
+            VariableDecorator::Restrict => builder.decorate(target, Decoration::Restrict, None),
+            VariableDecorator::Aliased => builder.decorate(target, Decoration::Aliased, None),
+            VariableDecorator::Volatile => builder.decorate(target, Decoration::Volatile, None),
+            VariableDecorator::Coherent => builder.decorate(target, Decoration::Coherent, None),
         }
     }
 }
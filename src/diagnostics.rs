@@ -0,0 +1,376 @@
+use crate::{
+    error::{Error, Result},
+    fg::{
+        module::ModuleBuilder,
+        values::{integer::ConstantSource, Value},
+    },
+    Compilation,
+};
+use docfg::docfg;
+use rspirv::{
+    binary::Disassemble,
+    dr::{Instruction, Module, Operand},
+    spirv::Word,
+};
+use serde::{Deserialize, Serialize};
+use std::{collections::HashMap, fmt::Display};
+
+/// Location in the WebAssembly module that produced a SPIR-V instruction
+#[derive(Debug, Clone, PartialEq, Eq, Hash, Serialize, Deserialize)]
+pub struct Origin {
+    /// Index of the WebAssembly function
+    pub function: u32,
+    /// WebAssembly operator, or `None` for function prologue instructions
+    pub operator: Option<Box<str>>,
+    /// Flow graph operation the instruction was translated from
+    pub operation: Box<str>,
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct ValidationDiagnostic {
+    pub message: String,
+    /// Id of the SPIR-V instruction the validator complained about, if it could be determined
+    pub id: Option<Word>,
+    pub origin: Option<Origin>,
+}
+
+/// Message and source location of a Rust panic, recovered from the call that precedes a trap. With
+/// [`TrapMode::Flag`](crate::config::TrapMode::Flag), the flag holds `2 + i` after the `i`-th panic fires
+#[derive(Debug, Clone, PartialEq, Eq, Hash, Serialize, Deserialize)]
+pub struct PanicMessage {
+    /// Index of the WebAssembly function that traps
+    pub function: u32,
+    pub message: Option<String>,
+    /// Location of the panic in the Rust source, as `file:line:column`
+    pub location: Option<String>,
+}
+
+impl Compilation {
+    /// Panics recognized in the WebAssembly module, in the order of their trap codes
+    #[inline]
+    pub fn panic_messages(&self) -> &[PanicMessage] {
+        return &self.panics;
+    }
+
+    /// Returns the origin of the SPIR-V instruction with the specified result id, if known
+    #[inline]
+    pub fn origin(&self, id: Word) -> Option<&Origin> {
+        return self.origins.get(&id);
+    }
+
+    /// Disassembles the module with named ids, annotated with the WebAssembly operators they come from
+    pub fn annotated_assembly(&self) -> Result<String> {
+        let module = self.module()?;
+        let names = friendly_names(module);
+        let text = module.disassemble();
+        let mut lines = text.lines();
+        let mut result = String::with_capacity(text.len());
+
+        // Every instruction is disassembled into a line of its own, after the lines of the header
+        let header = match module.header {
+            Some(_) => 4,
+            None => 0,
+        };
+        for line in lines.by_ref().take(header) {
+            result.push_str(line);
+            result.push('\n');
+        }
+
+        let mut next_line = |instr: &Instruction| -> Result<String> {
+            let line = lines.next().ok_or_else(Error::unexpected)?;
+            if !line.contains(&format!("Op{}", instr.class.opname)) {
+                return Err(Error::unexpected());
+            }
+            return Ok(rename_ids(line, &names));
+        };
+
+        for instr in module.global_inst_iter() {
+            result.push_str(&next_line(instr)?);
+            result.push('\n');
+        }
+
+        for function in module.functions.iter() {
+            let mut current = None;
+            let instructions = function
+                .def
+                .iter()
+                .chain(function.parameters.iter())
+                .chain(
+                    function
+                        .blocks
+                        .iter()
+                        .flat_map(|block| block.label.iter().chain(block.instructions.iter())),
+                )
+                .chain(function.end.iter());
+
+            for instr in instructions {
+                let origin = instr.result_id.and_then(|id| self.origin(id));
+                if let Some(origin) = origin.filter(|x| current != Some(*x)) {
+                    result.push_str(&format!("; {origin}\n"));
+                    current = Some(origin);
+                }
+
+                result.push_str(&next_line(instr)?);
+                result.push('\n');
+            }
+        }
+
+        return Ok(result);
+    }
+
+    /// Validates the module, mapping errors back to the WebAssembly operator that produced them
+    #[docfg(feature = "spvt-validate")]
+    pub fn validate_detailed(&self) -> crate::error::Result<()> {
+        use crate::error::Error;
+
+        let err = match self.spvt_validate() {
+            Ok(()) => return Ok(()),
+            Err(Error::SpirvTools(err)) => err,
+            Err(e) => return Err(e),
+        };
+
+        let message = match err.diagnostic {
+            Some(ref diag) => diag.message.clone(),
+            None => err.to_string(),
+        };
+
+        let ids = referenced_ids(self.module().ok(), &message);
+        let (id, origin) = match ids
+            .iter()
+            .find_map(|id| Some((*id, self.origin(*id)?.clone())))
+        {
+            Some((id, origin)) => (Some(id), Some(origin)),
+            None => (ids.first().copied(), None),
+        };
+
+        return Err(Error::Validation(Box::new(ValidationDiagnostic {
+            message,
+            id,
+            origin,
+        })));
+    }
+}
+
+impl Display for Origin {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "function {}", self.function)?;
+        if let Some(ref operator) = self.operator {
+            write!(f, ", operator {operator}")?;
+        }
+        write!(f, " ({})", self.operation)
+    }
+}
+
+impl Display for PanicMessage {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "function {} panicked", self.function)?;
+        if let Some(ref location) = self.location {
+            write!(f, " at {location}")?;
+        }
+        if let Some(ref message) = self.message {
+            write!(f, ": {message}")?;
+        }
+        return Ok(());
+    }
+}
+
+impl Display for ValidationDiagnostic {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.write_str(&self.message)?;
+        if let Some(ref origin) = self.origin {
+            write!(f, "\nproduced by {origin}")?;
+        }
+        return Ok(());
+    }
+}
+
+pub(crate) fn panic_message(
+    function: u32,
+    args: &[Value],
+    module: &ModuleBuilder,
+) -> Option<PanicMessage> {
+    const MAX_LEN: u64 = 4096;
+    let usize_len = match module.wasm_memory64 {
+        true => 8,
+        false => 4,
+    };
+
+    let constants = args
+        .iter()
+        .map(|x| match x {
+            Value::Integer(x) => match x.get_constant_value().ok()?? {
+                ConstantSource::Short(x) => Some(x as u64),
+                ConstantSource::Long(x) => Some(x),
+            },
+            _ => None,
+        })
+        .collect::<Vec<_>>();
+
+    let read_uint = |address: u64, len: u32| -> Option<u64> {
+        let mut bytes = [0; 8];
+        bytes[..len as usize].copy_from_slice(module.data_at(0, address, len)?);
+        Some(u64::from_le_bytes(bytes))
+    };
+
+    let read_str = |address: u64, len: u64| -> Option<&str> {
+        if len == 0 || len > MAX_LEN {
+            return None;
+        }
+        let str = std::str::from_utf8(module.data_at(0, address, len as u32)?).ok()?;
+        match str.chars().all(|x| !x.is_control() || x.is_whitespace()) {
+            true => Some(str),
+            false => None,
+        }
+    };
+
+    let message = constants
+        .windows(2)
+        .find_map(|x| read_str(x[0]?, x[1]?))
+        .map(String::from);
+
+    let location = constants.iter().flatten().find_map(|&address| {
+        let file = read_str(
+            read_uint(address, usize_len)?,
+            read_uint(address + usize_len as u64, usize_len)?,
+        )?;
+        if !file.ends_with(".rs") {
+            return None;
+        }
+
+        let line = read_uint(address + 2 * usize_len as u64, 4)?;
+        let column = read_uint(address + 2 * usize_len as u64 + 4, 4)?;
+        Some(format!("{file}:{line}:{column}"))
+    });
+
+    if message.is_none() && location.is_none() {
+        return None;
+    }
+
+    return Some(PanicMessage {
+        function,
+        message,
+        location,
+    });
+}
+
+fn friendly_names(module: &Module) -> HashMap<Word, String> {
+    let named = module
+        .debug_names
+        .iter()
+        .filter_map(|instr| match &instr.operands[..] {
+            [Operand::IdRef(target), Operand::LiteralString(name)] => Some((*target, name)),
+            _ => None,
+        });
+    let entry_points = module
+        .entry_points
+        .iter()
+        .filter_map(|instr| match &instr.operands[..] {
+            [_, Operand::IdRef(function), Operand::LiteralString(name), ..] => {
+                Some((*function, name))
+            }
+            _ => None,
+        });
+
+    let mut result = HashMap::<Word, String>::new();
+    let mut counts = HashMap::<String, usize>::new();
+    for (id, name) in named.chain(entry_points) {
+        if result.contains_key(&id) {
+            continue;
+        }
+
+        let mut name = name
+            .chars()
+            .map(|c| match c.is_ascii_alphanumeric() {
+                true => c,
+                false => '_',
+            })
+            .collect::<String>();
+        if name.is_empty() {
+            continue;
+        }
+        if name.starts_with(|c: char| c.is_ascii_digit()) {
+            name.insert(0, '_');
+        }
+
+        *counts.entry(name.clone()).or_default() += 1;
+        result.insert(id, name);
+    }
+
+    // Ambiguous names are suffixed with their id
+    for (id, name) in result.iter_mut() {
+        if counts[name.as_str()] > 1 {
+            name.push_str(&format!("_{id}"));
+        }
+    }
+
+    return result;
+}
+
+fn rename_ids(line: &str, names: &HashMap<Word, String>) -> String {
+    let mut result = String::with_capacity(line.len());
+    let mut in_string = false;
+    let mut chars = line.char_indices().peekable();
+
+    while let Some((i, c)) = chars.next() {
+        result.push(c);
+        match c {
+            '\\' if in_string => result.extend(chars.next().map(|(_, c)| c)),
+            '"' => in_string = !in_string,
+            '%' if !in_string => {
+                let digits = line[i + 1..]
+                    .find(|c: char| !c.is_ascii_digit())
+                    .unwrap_or(line.len() - i - 1);
+
+                let name = line[i + 1..i + 1 + digits]
+                    .parse::<Word>()
+                    .ok()
+                    .and_then(|id| names.get(&id));
+                if let Some(name) = name {
+                    result.push_str(name);
+                    for _ in 0..digits {
+                        chars.next();
+                    }
+                }
+            }
+            _ => {}
+        }
+    }
+
+    return result;
+}
+
+#[cfg(feature = "spvt-validate")]
+fn referenced_ids(module: Option<&rspirv::dr::Module>, message: &str) -> Vec<Word> {
+    use rspirv::dr::Operand;
+
+    let mut result = Vec::new();
+
+    for (i, _) in message.match_indices('%') {
+        let token = &message[i + 1..];
+        let len = token
+            .find(|c: char| !c.is_ascii_alphanumeric() && c != '_')
+            .unwrap_or(token.len());
+        let token = &token[..len];
+
+        let id = match token.parse::<Word>() {
+            Ok(id) => Some(id),
+            Err(_) => module.and_then(|module| {
+                module
+                    .debug_names
+                    .iter()
+                    .find_map(|instr| match &instr.operands[..] {
+                        [Operand::IdRef(target), Operand::LiteralString(name)] if name == token => {
+                            Some(*target)
+                        }
+                        _ => None,
+                    })
+            }),
+        };
+
+        if let Some(id) = id.filter(|id| !result.contains(id)) {
+            result.push(id);
+        }
+    }
+
+    return result;
+}
@@ -44,6 +44,17 @@ pub enum Error {
     #[error("SPIR-V Tools error: {0}")]
     SpirvTools(#[from] spirv_tools::error::Error),
 
+    #[cfg(feature = "testing")]
+    #[cfg_attr(docsrs, doc(cfg(feature = "testing")))]
+    #[error("Vulkan error: {0}")]
+    Vulkan(#[from] ash::vk::Result),
+
+    #[error("Validation error: {0}")]
+    Validation(Box<crate::diagnostics::ValidationDiagnostic>),
+
+    #[error("Cross-compilation error: {0}")]
+    Fallback(Box<compilers::FallbackError>),
+
     #[error("Custom error: {0}")]
     Custom(#[from] Box<dyn 'static + Send + Sync + StdError>),
 }
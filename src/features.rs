@@ -0,0 +1,127 @@
+use crate::{
+    config::{Config, WasmFeatures},
+    error::{Error, Result},
+};
+use wasmparser::{
+    types::Types, BinaryReaderError, Chunk, FuncType, FunctionBody, Parser, ValType, ValidPayload,
+    Validator,
+};
+
+pub(crate) fn validate(features: WasmFeatures, bytes: &[u8]) -> Result<Types> {
+    let mut validator = Validator::new_with_features(features.into());
+    return validator
+        .validate_all(bytes)
+        .map_err(|e| explain(e, features, bytes, true));
+}
+
+pub(crate) fn explain(
+    error: BinaryReaderError,
+    features: WasmFeatures,
+    bytes: &[u8],
+    eof: bool,
+) -> Error {
+    for proposal in WasmFeatures::PROPOSALS {
+        if features.is_enabled(proposal) == Some(true) {
+            continue;
+        }
+
+        let mut with_proposal = features;
+        with_proposal.set_enabled(proposal, true);
+        if validates(with_proposal, bytes, eof) {
+            return Error::msg(format!(
+                "The module uses the `{proposal}` proposal, which isn't enabled by the configuration's features: {error}"
+            ));
+        }
+    }
+
+    return Error::Wasm(error);
+}
+
+pub(crate) fn used_features(features: WasmFeatures, bytes: &[u8]) -> WasmFeatures {
+    let mut result = WasmFeatures::default();
+    for proposal in features.enabled() {
+        let mut without_proposal = features;
+        without_proposal.set_enabled(proposal, false);
+        if !validates(without_proposal, bytes, true) {
+            result.set_enabled(proposal, true);
+        }
+    }
+
+    return result;
+}
+
+pub(crate) fn check_operators(config: &Config, function: u32, body: &FunctionBody) -> Result<()> {
+    if config.forbidden_operators.is_empty() {
+        return Ok(());
+    }
+
+    for op in body.get_operators_reader()?.into_iter_with_offsets() {
+        let (op, offset) = op?;
+        let debug = format!("{op:?}");
+        let name = debug.split([' ', '{']).next().unwrap_or_default();
+
+        if let Some(pattern) = config.forbidding(name) {
+            return Err(Error::msg(format!(
+                "Function {function} uses `{name}` at offset {offset:#x}, which is forbidden by `{pattern}`"
+            )));
+        }
+    }
+
+    return Ok(());
+}
+
+pub(crate) fn uses_f64(ty: &FuncType, body: &FunctionBody) -> Result<bool> {
+    if ty
+        .params()
+        .iter()
+        .chain(ty.results())
+        .any(|x| *x == ValType::F64)
+    {
+        return Ok(true);
+    }
+
+    for local in body.get_locals_reader()? {
+        let (_, ty) = local?;
+        if ty == ValType::F64 {
+            return Ok(true);
+        }
+    }
+
+    for op in body.get_operators_reader()? {
+        let debug = format!("{:?}", op?);
+        let name = debug.split([' ', '{']).next().unwrap_or_default();
+        if name.contains("F64") {
+            return Ok(true);
+        }
+    }
+
+    return Ok(false);
+}
+
+fn validates(features: WasmFeatures, bytes: &[u8], eof: bool) -> bool {
+    let mut validator = Validator::new_with_features(features.into());
+    let mut parser = Parser::new(0);
+    let mut consumed = 0;
+
+    loop {
+        let (size, payload) = match parser.parse(&bytes[consumed..], eof) {
+            Ok(Chunk::NeedMoreData(_)) => return !eof,
+            Ok(Chunk::Parsed { consumed, payload }) => (consumed, payload),
+            Err(_) => return false,
+        };
+
+        match validator.payload(&payload) {
+            Ok(ValidPayload::Ok | ValidPayload::Parser(_)) => {}
+            Ok(ValidPayload::Func(func, body)) => {
+                let mut validator = func.into_validator(Default::default());
+                if validator.validate(&body).is_err() {
+                    return false;
+                }
+            }
+            Ok(ValidPayload::End(_)) => return true,
+            Err(_) => return false,
+        }
+
+        consumed += size;
+    }
+}
@@ -0,0 +1,126 @@
+use std::{
+    fmt::Debug,
+    hash::Hash,
+    marker::PhantomData,
+    ops::{Index, IndexMut},
+};
+
+/// Typed arena owning the nodes of a function's flow graph
+#[derive(Debug, Clone)]
+pub struct Arena<T> {
+    items: Vec<T>,
+}
+
+/// Reference to a node of an [`Arena`]. Handles are only meaningful for the arena that created them
+pub struct Handle<T> {
+    index: u32,
+    _phtm: PhantomData<fn() -> T>,
+}
+
+impl<T> Arena<T> {
+    pub const fn new() -> Self {
+        return Self { items: Vec::new() };
+    }
+
+    /// Moves the node into the arena, returning its handle
+    pub fn alloc(&mut self, item: T) -> Handle<T> {
+        let index = u32::try_from(self.items.len()).expect("Too many nodes inside the arena");
+        self.items.push(item);
+        return Handle {
+            index,
+            _phtm: PhantomData,
+        };
+    }
+
+    pub fn get(&self, handle: Handle<T>) -> Option<&T> {
+        return self.items.get(handle.index());
+    }
+
+    pub fn get_mut(&mut self, handle: Handle<T>) -> Option<&mut T> {
+        return self.items.get_mut(handle.index());
+    }
+
+    #[inline]
+    pub fn len(&self) -> usize {
+        return self.items.len();
+    }
+
+    #[inline]
+    pub fn is_empty(&self) -> bool {
+        return self.items.is_empty();
+    }
+
+    /// Iterates over every node of the arena, in allocation order
+    pub fn iter(&self) -> impl ExactSizeIterator<Item = (Handle<T>, &T)> {
+        return self.items.iter().enumerate().map(|(i, item)| {
+            let handle = Handle {
+                index: i as u32,
+                _phtm: PhantomData,
+            };
+            (handle, item)
+        });
+    }
+}
+
+impl<T> Handle<T> {
+    /// Position of the node inside its arena
+    #[inline]
+    pub fn index(self) -> usize {
+        return self.index as usize;
+    }
+}
+
+impl<T> Default for Arena<T> {
+    #[inline]
+    fn default() -> Self {
+        return Self::new();
+    }
+}
+
+impl<T> Index<Handle<T>> for Arena<T> {
+    type Output = T;
+
+    #[inline]
+    fn index(&self, handle: Handle<T>) -> &T {
+        return &self.items[handle.index()];
+    }
+}
+
+impl<T> IndexMut<Handle<T>> for Arena<T> {
+    #[inline]
+    fn index_mut(&mut self, handle: Handle<T>) -> &mut T {
+        return &mut self.items[handle.index()];
+    }
+}
+
+// Manual implementations, to avoid requiring them on `T`
+impl<T> Clone for Handle<T> {
+    #[inline]
+    fn clone(&self) -> Self {
+        *self
+    }
+}
+
+impl<T> Copy for Handle<T> {}
+
+impl<T> PartialEq for Handle<T> {
+    #[inline]
+    fn eq(&self, other: &Self) -> bool {
+        self.index == other.index
+    }
+}
+
+impl<T> Eq for Handle<T> {}
+
+impl<T> Hash for Handle<T> {
+    #[inline]
+    fn hash<H: std::hash::Hasher>(&self, state: &mut H) {
+        self.index.hash(state)
+    }
+}
+
+impl<T> Debug for Handle<T> {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "Handle({})", self.index)
+    }
+}
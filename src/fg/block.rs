@@ -1,10 +1,11 @@
 use super::module::CallableFunction;
 use super::values::pointer::Pointer;
 use super::{function::FunctionBuilder, module::ModuleBuilder, values::Value, Operation};
-use super::{End, Label};
+use super::{End, Handle, Label};
 use crate::fg::block::mvp::TranslationResult;
 use crate::r#type::PointerSize;
 use crate::{
+    config::Verbosity,
     error::{Error, Result},
     fg::values::{
         float::{Float, FloatKind, FloatSource},
@@ -13,8 +14,8 @@ use crate::{
     r#type::{ScalarType, Type},
 };
 use std::rc::Rc;
-use std::{collections::VecDeque, fmt::Debug};
-use wasmparser::{BinaryReaderError, Operator, OperatorsReader};
+use std::{cell::Cell, collections::VecDeque, fmt::Debug};
+use wasmparser::{BinaryReaderError, FuncType, FunctionBody, Operator, OperatorsReader};
 
 macro_rules! tri {
     ($e:expr) => {
@@ -23,14 +24,6 @@ macro_rules! tri {
             _ => {}
         }
     };
-
-    (continue $e:expr) => {
-        match $e? {
-            TranslationResult::Eof => break,
-            TranslationResult::Found => continue,
-            TranslationResult::NotFound => {}
-        }
-    };
 }
 
 pub mod mvp;
@@ -101,30 +94,87 @@ impl StackValue {
     }
 }
 
+/// Label a branch can jump to, alongside the variables that carry the values the branch takes from the stack
+#[derive(Debug, Clone)]
+pub struct BranchTarget {
+    pub label: Handle<Label>,
+    /// Results of blocks, or parameters of loops
+    pub values: Rc<[Rc<Pointer>]>,
+}
+
+impl BranchTarget {
+    pub fn new(label: Handle<Label>) -> Self {
+        return Self {
+            label,
+            values: Rc::new([]),
+        };
+    }
+}
+
 #[derive(Debug, Clone)]
 pub struct BlockBuilder<'a> {
     pub reader: BlockReader<'a>,
     pub stack: Vec<StackValue>,
     pub end: End,
-    pub outer_labels: VecDeque<Rc<Label>>,
+    pub outer_labels: VecDeque<BranchTarget>,
 }
 
 pub fn translate_block<'a>(
     reader: BlockReader<'a>,
-    labels: VecDeque<Rc<Label>>,
+    labels: VecDeque<BranchTarget>,
+    end: End,
+    function: &mut FunctionBuilder,
+    module: &mut ModuleBuilder,
+) -> Result<BlockBuilder<'a>> {
+    return translate_block_with_stack(reader, labels, Vec::new(), end, function, module);
+}
+
+/// Translates a block that starts with `stack` as its parameters
+pub fn translate_block_with_stack<'a>(
+    reader: BlockReader<'a>,
+    labels: VecDeque<BranchTarget>,
+    stack: Vec<StackValue>,
     end: End,
     function: &mut FunctionBuilder,
     module: &mut ModuleBuilder,
 ) -> Result<BlockBuilder<'a>> {
     let mut result = BlockBuilder {
-        stack: Vec::new(),
+        stack,
         reader,
         end,
         outer_labels: labels,
     };
 
+    let _span = (module.verbosity >= Verbosity::Blocks).then(|| {
+        tracing::debug_span!(
+            "block",
+            function = function.index,
+            depth = result.outer_labels.len(),
+            end = ?result.end
+        )
+        .entered()
+    });
+
     while let Some(op) = result.reader.next().transpose()? {
-        tri!(continue mvp::translate_all(&op, &mut result, function, module));
+        let translated = mvp::translate_all(&op, &mut result, function, module);
+        function.record_operator(&op);
+        match translated? {
+            TranslationResult::Eof => break,
+            // The rest of the block can't be reached after a branch
+            TranslationResult::Found
+                if function
+                    .anchors
+                    .last()
+                    .is_some_and(Operation::is_block_terminating) =>
+            {
+                if module.verbosity >= Verbosity::Operators {
+                    tracing::debug!("Skipping the rest of the block, unreachable after {op:?}");
+                }
+                break;
+            }
+            TranslationResult::Found => continue,
+            TranslationResult::NotFound => {}
+        }
         return Err(Error::msg(format!("Unknown instruction: {op:?}")));
     }
 
@@ -182,56 +232,93 @@ impl<'a> BlockBuilder<'a> {
     ) -> Result<()> {
         match f {
             CallableFunction::Callback(f) => f(self, function, module),
-            CallableFunction::Defined { function_id, ty: f } => {
-                let mut args = Vec::with_capacity(f.params().len());
-                for ty in f.params().iter().rev() {
-                    let raw_arg = self.stack_pop(Type::from(*ty), module)?;
-                    args.push(raw_arg);
-                }
-
-                args.reverse();
-                let args = args.into_boxed_slice();
-
-                assert!(f.results().len() <= 1);
-                match f.results().get(0) {
-                    Some(wasmparser::ValType::I32) => {
-                        self.stack_push(Integer::new(IntegerSource::FunctionCall {
-                            function_id: function_id.clone(),
-                            args,
-                            kind: IntegerKind::Short,
-                        }))
-                    }
-                    Some(wasmparser::ValType::I64) => {
-                        self.stack_push(Integer::new(IntegerSource::FunctionCall {
-                            function_id: function_id.clone(),
-                            args,
-                            kind: IntegerKind::Long,
-                        }))
-                    }
-                    Some(wasmparser::ValType::F32) => {
-                        self.stack_push(Float::new(FloatSource::FunctionCall {
-                            function_id: function_id.clone(),
-                            args,
-                            kind: FloatKind::Single,
-                        }))
-                    }
-                    Some(wasmparser::ValType::F64) => {
-                        self.stack_push(Float::new(FloatSource::FunctionCall {
-                            function_id: function_id.clone(),
-                            args,
-                            kind: FloatKind::Double,
-                        }))
-                    }
-                    None => function.anchors.push(Operation::FunctionCall {
-                        function_id: function_id.clone(),
-                        args,
-                    }),
-                    _ => return Err(Error::unexpected()),
-                };
-                Ok(())
+            CallableFunction::Defined { function_id, ty } => {
+                let args = self.pop_arguments(ty, module)?;
+                self.push_call(function_id, ty, args, function)
             }
         }
     }
+
+    /// Inlines the function into its caller, if possible
+    pub fn inline_function(
+        &mut self,
+        index: u32,
+        f: &CallableFunction,
+        body: FunctionBody<'_>,
+        function: &mut FunctionBuilder,
+        module: &mut ModuleBuilder,
+    ) -> Result<()> {
+        let (function_id, ty) = match f {
+            CallableFunction::Defined { function_id, ty } => (function_id, ty),
+            CallableFunction::Callback(_) => return self.call_function(f, function, module),
+        };
+
+        let args = self.pop_arguments(ty, module)?;
+        match function.inline(index, ty, &args, body, module)? {
+            Some(Some(value)) => self.stack_push(value),
+            Some(None) => {}
+            None => self.push_call(function_id, ty, args, function)?,
+        }
+
+        return Ok(());
+    }
+
+    fn pop_arguments(&mut self, f: &FuncType, module: &mut ModuleBuilder) -> Result<Box<[Value]>> {
+        let mut args = Vec::with_capacity(f.params().len());
+        for ty in f.params().iter().rev() {
+            let raw_arg = self.stack_pop(Type::from(*ty), module)?;
+            args.push(raw_arg);
+        }
+
+        args.reverse();
+        return Ok(args.into_boxed_slice());
+    }
+
+    fn push_call(
+        &mut self,
+        function_id: &Rc<Cell<Option<rspirv::spirv::Word>>>,
+        f: &FuncType,
+        args: Box<[Value]>,
+        function: &mut FunctionBuilder,
+    ) -> Result<()> {
+        assert!(f.results().len() <= 1);
+        match f.results().get(0) {
+            Some(wasmparser::ValType::I32) => {
+                self.stack_push(Integer::new(IntegerSource::FunctionCall {
+                    function_id: function_id.clone(),
+                    args,
+                    kind: IntegerKind::Short,
+                }))
+            }
+            Some(wasmparser::ValType::I64) => {
+                self.stack_push(Integer::new(IntegerSource::FunctionCall {
+                    function_id: function_id.clone(),
+                    args,
+                    kind: IntegerKind::Long,
+                }))
+            }
+            Some(wasmparser::ValType::F32) => {
+                self.stack_push(Float::new(FloatSource::FunctionCall {
+                    function_id: function_id.clone(),
+                    args,
+                    kind: FloatKind::Single,
+                }))
+            }
+            Some(wasmparser::ValType::F64) => {
+                self.stack_push(Float::new(FloatSource::FunctionCall {
+                    function_id: function_id.clone(),
+                    args,
+                    kind: FloatKind::Double,
+                }))
+            }
+            None => function.anchors.push(Operation::FunctionCall {
+                function_id: function_id.clone(),
+                args,
+            }),
+            _ => return Err(Error::unexpected()),
+        };
+        Ok(())
+    }
 }
 
 #[derive(Clone)]
@@ -254,8 +341,12 @@ impl<'a> BlockReader<'a> {
 
         for (i, op) in self.cache.iter().enumerate() {
             match op {
-                Operator::Loop { .. } | Operator::Block { .. } => inner_branches += 1,
-                Operator::End => match inner_branches.checked_sub(1) {
+                Operator::Loop { .. }
+                | Operator::Block { .. }
+                | Operator::If { .. }
+                | Operator::Try { .. } => inner_branches += 1,
+                // `try ... delegate` blocks have no `end`
+                Operator::End | Operator::Delegate { .. } => match inner_branches.checked_sub(1) {
                     Some(x) => inner_branches = x,
                     None => {
                         let mut cache = self.cache.split_off(i + 1);
@@ -277,11 +368,16 @@ impl<'a> BlockReader<'a> {
                 cache.push_back(op.clone());
 
                 match op {
-                    Operator::Loop { .. } | Operator::Block { .. } => inner_branches += 1,
-                    Operator::End => match inner_branches.checked_sub(1) {
-                        Some(x) => inner_branches = x,
-                        None => break,
-                    },
+                    Operator::Loop { .. }
+                    | Operator::Block { .. }
+                    | Operator::If { .. }
+                    | Operator::Try { .. } => inner_branches += 1,
+                    Operator::End | Operator::Delegate { .. } => {
+                        match inner_branches.checked_sub(1) {
+                            Some(x) => inner_branches = x,
+                            None => break,
+                        }
+                    }
                     _ => continue,
                 }
             }
@@ -294,6 +390,62 @@ impl<'a> BlockReader<'a> {
     }
 }
 
+impl<'a> BlockReader<'a> {
+    /// Splits the reader of an `if` branch at its `else`, returning the reader of the `else` branch
+    pub fn split_else(&mut self) -> Option<BlockReader<'a>> {
+        let mut inner_branches = 0u32;
+
+        for (i, op) in self.cache.iter().enumerate() {
+            match op {
+                Operator::Loop { .. }
+                | Operator::Block { .. }
+                | Operator::If { .. }
+                | Operator::Try { .. } => inner_branches += 1,
+                Operator::End | Operator::Delegate { .. } => {
+                    inner_branches = inner_branches.checked_sub(1)?
+                }
+                Operator::Else if inner_branches == 0 => {
+                    let cache = self.cache.split_off(i + 1);
+                    self.cache.pop_back();
+                    self.cache.push_back(Operator::End);
+                    return Some(BlockReader {
+                        reader: None,
+                        cache,
+                    });
+                }
+                _ => continue,
+            }
+        }
+
+        return None;
+    }
+
+    /// Cuts the reader of a `try` block at its first `catch`, `catch_all` or `delegate`, discarding the handlers
+    pub fn split_catch(&mut self) {
+        let mut inner_branches = 0u32;
+
+        for (i, op) in self.cache.iter().enumerate() {
+            match op {
+                Operator::Loop { .. }
+                | Operator::Block { .. }
+                | Operator::If { .. }
+                | Operator::Try { .. } => inner_branches += 1,
+                Operator::End | Operator::Delegate { .. } if inner_branches > 0 => {
+                    inner_branches -= 1
+                }
+                Operator::Catch { .. } | Operator::CatchAll | Operator::Delegate { .. }
+                    if inner_branches == 0 =>
+                {
+                    self.cache.truncate(i);
+                    self.cache.push_back(Operator::End);
+                    return;
+                }
+                _ => continue,
+            }
+        }
+    }
+}
+
 impl<'a> Iterator for BlockReader<'a> {
     type Item = Result<Operator<'a>, BinaryReaderError>;
 
@@ -1,6 +1,7 @@
-use super::{translate_block, BlockBuilder, StackValue};
+use super::{translate_block_with_stack, BlockBuilder, BlockReader, BranchTarget, StackValue};
 use crate::{
-    config::MemoryGrowErrorKind,
+    config::{DataSegmentMode, MemoryGrowErrorKind},
+    diagnostics::panic_message,
     error::{Error, Result},
     fg::{
         function::{FunctionBuilder, Storeable},
@@ -9,18 +10,19 @@ use crate::{
             bool::{Bool, BoolSource, Comparison, Equality},
             float::{ConversionSource, Float, FloatKind, FloatSource},
             integer::{
-                ConversionSource as IntegerConversionSource, Integer, IntegerKind, IntegerSource,
+                ConstantSource, ConversionSource as IntegerConversionSource, Integer, IntegerKind,
+                IntegerSource,
             },
-            pointer::{Pointer, PointerSource},
+            pointer::{Pointer, PointerKind, PointerSource},
             Value,
         },
-        End, Label, Operation,
+        End, Operation,
     },
-    r#type::{PointerSize, ScalarType, Type},
+    r#type::{PointerSize, ScalarType, Type, NULL_REFERENCE},
 };
-use std::rc::Rc;
-use tracing::debug;
-use wasmparser::{MemArg, Operator};
+use rspirv::spirv::StorageClass;
+use std::{collections::VecDeque, rc::Rc};
+use wasmparser::{BlockType, MemArg, Operator};
 use Operator::*;
 
 #[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
@@ -41,7 +43,7 @@ pub fn translate_all<'a>(
     tri!(translate_conversion(op, block, module));
     tri!(translate_variables(op, block, function, module));
     tri!(translate_memory(op, block, function, module));
-    tri!(translate_arith(op, block, module));
+    tri!(translate_arith(op, block, function, module));
     tri!(translate_logic(op, block, module));
     tri!(translate_comparison(op, block, module));
     return Ok(TranslationResult::NotFound);
@@ -56,6 +58,8 @@ pub fn translate_constants<'a>(
         I64Const { value } => Integer::new_constant_i64(*value).into(),
         F32Const { value } => Float::new_constant_f32(f32::from_bits(value.bits())).into(),
         F64Const { value } => Float::new_constant_f64(f64::from_bits(value.bits())).into(),
+        RefNull { .. } => Integer::new_constant_u32(NULL_REFERENCE).into(),
+        RefFunc { function_index } => Integer::new_constant_u32(function_index + 1).into(),
         _ => return Ok(TranslationResult::NotFound),
     };
 
@@ -71,113 +75,228 @@ pub fn translate_control_flow<'a>(
 ) -> Result<TranslationResult> {
     match op {
         Loop { blockty } => {
-            let start_label = Rc::new(Label::default());
+            let (params, results) = block_type(*blockty, module)?;
+            let start_label = function.new_label();
+            let target = BranchTarget {
+                label: start_label,
+                values: block_variables(&params),
+            };
 
-            function.anchors.push(Operation::Branch {
-                label: start_label.clone(),
-            });
-            function.anchors.push(Operation::Label(start_label.clone()));
+            // Back edges carry the loop's parameters through its variables
+            store_branch_values(&target, false, block, function, module)?;
+            function
+                .anchors
+                .push(Operation::Branch { label: start_label });
+            function.anchors.push(Operation::Label(start_label));
+
+            let stack = target
+                .values
+                .iter()
+                .map(|x| Ok(StackValue::from(x.clone().load(None, block, module)?)))
+                .collect::<Result<Vec<_>>>()?;
 
             let mut outer_labels = block.outer_labels.clone();
-            outer_labels.push_front(start_label);
+            outer_labels.push_front(target);
 
             let inner_block = block.reader.split_branch()?;
-            translate_block(
+            let mut inner_block = translate_block_with_stack(
                 inner_block,
                 outer_labels,
+                stack,
                 End::Unreachable,
                 function,
                 module,
             )?;
+
+            // The loop's results fall through into the following operators
+            if !function
+                .anchors
+                .last()
+                .is_some_and(Operation::is_block_terminating)
+            {
+                let at = inner_block
+                    .stack
+                    .len()
+                    .checked_sub(results.len())
+                    .ok_or_else(|| Error::msg("Empty stack"))?;
+                block.stack.extend(inner_block.stack.drain(at..));
+            }
         }
 
-        Block { blockty } => {
-            let start_label = Rc::new(Label::default());
-            let end_label = Rc::new(Label::default());
+        // Exceptions are never caught, since throwing one traps
+        Block { blockty } | Try { blockty } => {
+            let (params, results) = block_type(*blockty, module)?;
+            let start_label = function.new_label();
+            let target = BranchTarget {
+                label: function.new_label(),
+                values: block_variables(&results),
+            };
+            let stack = pop_params(params.len(), block)?;
 
-            function.anchors.push(Operation::Branch {
-                label: start_label.clone(),
-            });
+            function
+                .anchors
+                .push(Operation::Branch { label: start_label });
             function.anchors.push(Operation::Label(start_label));
 
             let mut outer_labels = block.outer_labels.clone();
-            outer_labels.push_front(end_label.clone());
+            outer_labels.push_front(target.clone());
 
-            let inner_block = block.reader.split_branch()?;
-            translate_block(
+            let mut inner_block = block.reader.split_branch()?;
+            if let Try { .. } = op {
+                inner_block.split_catch();
+            }
+
+            let inner_block = translate_block_with_stack(
                 inner_block,
                 outer_labels,
+                stack,
                 End::Unreachable,
                 function,
                 module,
             )?;
 
-            if !function
-                .anchors
-                .last()
-                .is_some_and(Operation::is_block_terminating)
-            {
-                function.anchors.push(Operation::Branch {
-                    label: end_label.clone(),
-                });
-            } else {
-                debug!("{:?}", function.anchors.last());
-            }
-
-            function.anchors.push(Operation::Label(end_label));
+            end_branch(inner_block, &target, function, module)?;
+            push_results(&target, block, function, module)?;
         }
 
-        Br { relative_depth } => {
-            let label = block
-                .outer_labels
-                .get(*relative_depth as usize)
-                .ok_or_else(Error::element_not_found)?;
+        If { blockty } => {
+            let (params, results) = block_type(*blockty, module)?;
+            let condition = block.stack_pop(ScalarType::Bool, module)?.into_bool()?;
+            let stack = pop_params(params.len(), block)?;
 
-            function.anchors.push(Operation::Branch {
-                label: label.clone(),
-            })
+            let true_label = function.new_label();
+            let false_label = function.new_label();
+            let target = BranchTarget {
+                label: function.new_label(),
+                values: block_variables(&results),
+            };
+
+            let mut outer_labels = block.outer_labels.clone();
+            outer_labels.push_front(target.clone());
+
+            let mut true_block = block.reader.split_branch()?;
+            // Without an `else`, the parameters are the results of the false branch
+            let false_block = true_block.split_else().unwrap_or_else(|| BlockReader {
+                reader: None,
+                cache: VecDeque::from([End]),
+            });
+
+            function.anchors.push(Operation::BranchConditional {
+                condition,
+                true_label: true_label,
+                false_label: false_label,
+            });
+
+            for (label, reader) in [(true_label, true_block), (false_label, false_block)] {
+                function.anchors.push(Operation::Label(label));
+                let inner_block = translate_block_with_stack(
+                    reader,
+                    outer_labels.clone(),
+                    stack.clone(),
+                    End::Unreachable,
+                    function,
+                    module,
+                )?;
+                end_branch(inner_block, &target, function, module)?;
+            }
+
+            push_results(&target, block, function, module)?;
         }
 
+        Br { relative_depth } => match branch_target(*relative_depth, block)? {
+            Some(target) => {
+                store_branch_values(&target, false, block, function, module)?;
+                function.anchors.push(Operation::Branch {
+                    label: target.label,
+                })
+            }
+            // Branching out of the function's body returns from it
+            None => push_return(function.return_type.clone(), false, block, function, module)?,
+        },
+
         BrIf { relative_depth } => {
-            let false_label = Rc::new(Label::default());
-            let true_label = block
-                .outer_labels
-                .get(*relative_depth as usize)
-                .cloned()
-                .ok_or_else(Error::element_not_found)?;
+            let false_label = function.new_label();
+            let target = branch_target(*relative_depth, block)?;
 
             let condition = block.stack_pop(ScalarType::Bool, module)?.into_bool()?;
+            let true_label = match &target {
+                Some(target) if target.values.is_empty() => target.label,
+                // Only the taken branch stores the values it carries
+                _ => function.new_label(),
+            };
+
             function.anchors.push(Operation::BranchConditional {
                 condition,
-                true_label: true_label.clone(),
-                false_label: false_label.clone(),
+                true_label: true_label,
+                false_label: false_label,
             });
+
+            match target {
+                Some(target) if !target.values.is_empty() => {
+                    function.anchors.push(Operation::Label(true_label));
+                    store_branch_values(&target, true, block, function, module)?;
+                    function.anchors.push(Operation::Branch {
+                        label: target.label,
+                    });
+                }
+                None => {
+                    function.anchors.push(Operation::Label(true_label));
+                    push_return(function.return_type.clone(), true, block, function, module)?;
+                }
+                _ => {}
+            }
             function.anchors.push(Operation::Label(false_label))
         }
 
-        End | Return => {
-            let value = match &block.end {
-                End::Return(Some(ty)) => Some(block.stack_pop(ty.clone(), module)?),
-                End::Return(None) => None,
-                _ => return Ok(TranslationResult::Eof),
+        End => {
+            if let End::Return(ty) = &block.end {
+                push_return(ty.clone(), false, block, function, module)?;
+            }
+            return Ok(TranslationResult::Eof);
+        }
+
+        Return => {
+            push_return(function.return_type.clone(), false, block, function, module)?;
+            return Ok(TranslationResult::Eof);
+        }
+
+        Unreachable => {
+            // Rust panics call a diverging function with the message and location of the panic, and then trap
+            let panic = match function.anchors.last() {
+                Some(Operation::FunctionCall { args, .. }) => {
+                    panic_message(function.index, args, module).map(Rc::new)
+                }
+                _ => None,
             };
+            if let Some(ref panic) = panic {
+                module.panics.push(panic.clone());
+            }
+            function.anchors.push(Operation::Unreachable { panic });
+            return Ok(TranslationResult::Eof);
+        }
 
-            function.anchors.push(Operation::Return { value });
+        Throw { .. } | Rethrow { .. } => {
+            function
+                .anchors
+                .push(Operation::Unreachable { panic: None });
             return Ok(TranslationResult::Eof);
         }
 
-        Call { function_index } => {
-            let f = module
-                .functions
-                .get(*function_index as usize)
-                .cloned()
-                .ok_or_else(Error::element_not_found)?;
+        Call { function_index } => call(*function_index, block, function, module)?,
+
+        // SPIR-V has no tail calls, so the callee's result is returned after a regular call
+        ReturnCall { function_index } => {
+            call(*function_index, block, function, module)?;
+            push_return(function.return_type.clone(), false, block, function, module)?;
+            return Ok(TranslationResult::Eof);
+        }
 
-            block.call_function(&f, function, module)?;
+        ReturnCallIndirect { .. } => {
+            return Err(Error::msg("Indirect calls aren't supported"));
         }
 
         // May need rework
-        Select => {
+        Select | TypedSelect { .. } => {
             let selector = block.stack_pop(ScalarType::Bool, module)?.into_bool()?;
             let false_operand = block.stack_pop_any()?;
             let true_operand = block.stack_pop_any()?;
@@ -301,6 +420,15 @@ pub fn translate_variables<'a>(
                     block.stack.extend(value)
                 }
             }
+
+            // Remember the addresses of the frame, so that its loads and stores use its variables
+            if let Some(shadow_stack) = function.shadow_stack.as_mut() {
+                if let (true, Some(StackValue::Value(Value::Integer(address)))) =
+                    (shadow_stack.local == *local_index, block.stack.last())
+                {
+                    shadow_stack.addresses.push(address.clone());
+                }
+            }
         }
 
         LocalSet { local_index } => local_set(*local_index, false, block, function, module)?,
@@ -313,6 +441,18 @@ pub fn translate_variables<'a>(
                 .ok_or_else(Error::element_not_found)?;
 
             let var = match var {
+                // Block members need a fresh access chain on every function
+                GlobalVariable::Variable(var)
+                    if matches!(var.source, PointerSource::Member { .. }) =>
+                {
+                    Rc::new(Pointer::new(
+                        PointerKind::Skinny,
+                        var.storage_class,
+                        var.pointee.clone(),
+                        var.source.clone(),
+                    ))
+                    .load(None, block, module)?
+                }
                 GlobalVariable::Variable(var) => var.clone().load(None, block, module)?,
                 GlobalVariable::Constant(c) => c.clone(),
             };
@@ -365,14 +505,14 @@ pub fn translate_memory<'a>(
                 _ => return Err(Error::unexpected()),
             };
 
-            let offset = Integer::new_constant_usize(memarg.offset as u32, module);
-            let pointer = block
-                .stack_pop_any()?
-                .to_pointer(PointerSize::Skinny, pointee, module)?
-                .access(offset, module)
-                .map(Rc::new)?;
+            if let Some(slot) = frame_slot(memarg, block.stack.last(), function) {
+                block.stack_pop_any()?;
+                let value = slot.load(None, block, module)?;
+                block.stack_push(value);
+                return Ok(TranslationResult::Found);
+            }
 
-            let value = pointer.load(Some(memarg.align as u32), block, module)?;
+            let value = load(memarg, pointee, block, function, module)?;
             block.stack_push(value);
         }
 
@@ -386,32 +526,53 @@ pub fn translate_memory<'a>(
             };
 
             let value = block.stack_pop(pointee, module)?;
-            let offset = Integer::new_constant_usize(memarg.offset as u32, module);
-            let pointer = block
-                .stack_pop_any()?
-                .to_pointer(PointerSize::Skinny, pointee, module)?
-                .access(offset, module)
-                .map(Rc::new)?;
+            if let Some(slot) = frame_slot(memarg, block.stack.last(), function) {
+                block.stack_pop_any()?;
+                function
+                    .anchors
+                    .push(slot.store(value, None, block, module)?);
+                return Ok(TranslationResult::Found);
+            }
 
-            function.anchors.push(pointer.store(
-                value,
-                Some(memarg.align as u32),
-                block,
-                module,
-            )?);
+            store(memarg, pointee, value, block, function, module)?;
         }
 
-        I32Load8U { memarg } => load_byte(IntegerKind::Short, memarg, block, module)?,
-        I64Load8U { memarg } => load_byte(IntegerKind::Long, memarg, block, module)?,
+        I32Load8U { memarg } => load_byte(IntegerKind::Short, memarg, block, function, module)?,
+        I64Load8U { memarg } => load_byte(IntegerKind::Long, memarg, block, function, module)?,
 
         I32Load16U { memarg } => {
             todo!()
         }
 
-        MemorySize { .. } => {
-            let zero = Integer::new_constant_usize(0, module);
-            block.stack_push(zero)
-        }
+        MemorySize { mem, .. } => match module.memory_variable(*mem, ScalarType::I32) {
+            Some(memory) => {
+                // The buffer's length (in 4-byte words) is converted into 64 KiB pages
+                let words = Rc::new(Integer::new(IntegerSource::ArrayLength {
+                    structured_array: memory,
+                }));
+                let pages = words.u_shr(Rc::new(Integer::new_constant_u32(14)), false, module)?;
+                let pages = match module.isize_integer_kind() {
+                    IntegerKind::Short => pages,
+                    IntegerKind::Long => Rc::new(Integer::new(IntegerSource::Conversion(
+                        IntegerConversionSource::FromShort {
+                            signed: false,
+                            value: pages,
+                        },
+                    ))),
+                };
+                block.stack_push(pages)
+            }
+            None if *mem != 0 => return Err(unbound_memory(*mem)),
+            None => {
+                let zero = Integer::new_constant_usize(0, module);
+                block.stack_push(zero)
+            }
+        },
+
+        MemoryInit { data_index, mem } => memory_init(*data_index, *mem, block, function, module)?,
+
+        // Segments are translated into constants, so there's nothing to free
+        DataDrop { .. } => {}
 
         MemoryGrow { .. } => match module.memory_grow_error {
             MemoryGrowErrorKind::Hard => return Err(Error::msg("SPIR-V cannot allocate memory")),
@@ -570,6 +731,7 @@ pub fn translate_conversion<'a>(
 pub fn translate_arith<'a>(
     op: &Operator<'a>,
     block: &mut BlockBuilder<'a>,
+    function: &mut FunctionBuilder,
     module: &mut ModuleBuilder,
 ) -> Result<TranslationResult> {
     let instr: Value = match op {
@@ -698,6 +860,9 @@ pub fn translate_arith<'a>(
 
             let op2 = block.stack_pop(ty, module)?.into_integer()?;
             let op1 = block.stack_pop(ty, module)?.into_integer()?;
+            if module.division_traps && !division_guard(&op1, &op2, true, function, module)? {
+                return Ok(TranslationResult::Eof);
+            }
             op1.s_div(op2, module)?.into()
         }
 
@@ -710,6 +875,9 @@ pub fn translate_arith<'a>(
 
             let op2 = block.stack_pop(ty, module)?.into_integer()?;
             let op1 = block.stack_pop(ty, module)?.into_integer()?;
+            if module.division_traps && !division_guard(&op1, &op2, false, function, module)? {
+                return Ok(TranslationResult::Eof);
+            }
             op1.u_div(op2, false, module)?.into()
         }
 
@@ -722,6 +890,14 @@ pub fn translate_arith<'a>(
 
             let op2 = block.stack_pop(ty, module)?.into_integer()?;
             let op1 = block.stack_pop(ty, module)?.into_integer()?;
+            if module.division_traps && !division_guard(&op1, &op2, false, function, module)? {
+                return Ok(TranslationResult::Eof);
+            }
+            let op2 = match module.division_traps {
+                // `INT_MIN % -1` is zero in WebAssembly, but overflows in SPIR-V
+                true => remainder_divisor(op2, module)?,
+                false => op2,
+            };
             op1.s_rem(op2, module)?.into()
         }
 
@@ -734,6 +910,9 @@ pub fn translate_arith<'a>(
 
             let op2 = block.stack_pop(ty, module)?.into_integer()?;
             let op1 = block.stack_pop(ty, module)?.into_integer()?;
+            if module.division_traps && !division_guard(&op1, &op2, false, function, module)? {
+                return Ok(TranslationResult::Eof);
+            }
             op1.u_rem(op2, module)?.into()
         }
 
@@ -1035,6 +1214,18 @@ pub fn translate_logic<'a>(
             op1.max(op2)?.into()
         }
 
+        F32Copysign | F64Copysign => {
+            let ty: ScalarType = match op {
+                F32Copysign => ScalarType::F32,
+                F64Copysign => ScalarType::F64,
+                _ => return Err(Error::unexpected()),
+            };
+
+            let op2 = block.stack_pop(ty, module)?.into_float()?;
+            let op1 = block.stack_pop(ty, module)?.into_float()?;
+            op1.copysign(op2)?.into()
+        }
+
         _ => return Ok(TranslationResult::NotFound),
     };
 
@@ -1101,10 +1292,14 @@ pub fn translate_comparison<'a>(
             .into()
         }
 
-        I32Eqz | I64Eqz => {
+        I32Eqz | I64Eqz | RefIsNull => {
             let (ty, op2) = match op {
                 I32Eqz => (ScalarType::I32, Rc::new(Integer::new_constant_i32(0))),
                 I64Eqz => (ScalarType::I64, Rc::new(Integer::new_constant_i64(0))),
+                RefIsNull => (
+                    ScalarType::I32,
+                    Rc::new(Integer::new_constant_u32(NULL_REFERENCE)),
+                ),
                 _ => return Err(Error::unexpected()),
             };
 
@@ -1279,53 +1474,745 @@ pub fn translate_comparison<'a>(
     return Ok(TranslationResult::Found);
 }
 
+fn division_guard(
+    op1: &Rc<Integer>,
+    op2: &Rc<Integer>,
+    signed: bool,
+    function: &mut FunctionBuilder,
+    module: &mut ModuleBuilder,
+) -> Result<bool> {
+    let (zero, minus_one, min) = match op2.kind(module)? {
+        IntegerKind::Short => (
+            Integer::new_constant_u32(0),
+            Integer::new_constant_i32(-1),
+            Integer::new_constant_i32(i32::MIN),
+        ),
+        IntegerKind::Long => (
+            Integer::new_constant_u64(0),
+            Integer::new_constant_i64(-1),
+            Integer::new_constant_i64(i64::MIN),
+        ),
+    };
+
+    let overflows = Bool::new(BoolSource::IntEquality {
+        kind: Equality::Eq,
+        op1: op1.clone(),
+        op2: Rc::new(min),
+    });
+
+    let condition = match (op1.get_constant_value()?, op2.get_constant_value()?) {
+        (_, Some(ConstantSource::Short(0) | ConstantSource::Long(0))) => {
+            function
+                .anchors
+                .push(Operation::Unreachable { panic: None });
+            return Ok(false);
+        }
+
+        (
+            Some(ConstantSource::Short(0x8000_0000) | ConstantSource::Long(0x8000_0000_0000_0000)),
+            Some(ConstantSource::Short(u32::MAX) | ConstantSource::Long(u64::MAX)),
+        ) if signed => {
+            function
+                .anchors
+                .push(Operation::Unreachable { panic: None });
+            return Ok(false);
+        }
+
+        (Some(_), Some(_)) => return Ok(true),
+
+        (_, Some(ConstantSource::Short(u32::MAX) | ConstantSource::Long(u64::MAX))) if signed => {
+            overflows
+        }
+
+        (_, Some(_)) => return Ok(true),
+
+        (_, None) => {
+            let is_zero = Bool::new(BoolSource::IntEquality {
+                kind: Equality::Eq,
+                op1: op2.clone(),
+                op2: Rc::new(zero),
+            });
+
+            match signed {
+                true => Bool::new(BoolSource::Select {
+                    selector: Rc::new(is_zero),
+                    true_value: Rc::new(Bool::new(BoolSource::Constant(true))),
+                    false_value: Rc::new(Bool::new(BoolSource::Select {
+                        selector: Rc::new(Bool::new(BoolSource::IntEquality {
+                            kind: Equality::Eq,
+                            op1: op2.clone(),
+                            op2: Rc::new(minus_one),
+                        })),
+                        true_value: Rc::new(overflows),
+                        false_value: Rc::new(Bool::new(BoolSource::Constant(false))),
+                    })),
+                }),
+                false => is_zero,
+            }
+        }
+    };
+
+    let trap = function.new_label();
+    let next = function.new_label();
+    function.anchors.push(Operation::BranchConditional {
+        condition: Rc::new(condition),
+        true_label: trap,
+        false_label: next,
+    });
+    function.anchors.push(Operation::Label(trap));
+    function
+        .anchors
+        .push(Operation::Unreachable { panic: None });
+    function.anchors.push(Operation::Label(next));
+    return Ok(true);
+}
+
+fn remainder_divisor(op2: Rc<Integer>, module: &ModuleBuilder) -> Result<Rc<Integer>> {
+    if op2.get_constant_value()?.is_some() {
+        return Ok(op2);
+    }
+
+    let (one, minus_one) = match op2.kind(module)? {
+        IntegerKind::Short => (Integer::new_constant_i32(1), Integer::new_constant_i32(-1)),
+        IntegerKind::Long => (Integer::new_constant_i64(1), Integer::new_constant_i64(-1)),
+    };
+
+    return Ok(Rc::new(Integer::new(IntegerSource::Select {
+        selector: Rc::new(Bool::new(BoolSource::IntEquality {
+            kind: Equality::Eq,
+            op1: op2.clone(),
+            op2: Rc::new(minus_one),
+        })),
+        true_value: Rc::new(one),
+        false_value: op2,
+    })));
+}
+
+fn frame_slot(
+    memarg: &MemArg,
+    address: Option<&StackValue>,
+    function: &FunctionBuilder,
+) -> Option<Rc<Pointer>> {
+    let Some(StackValue::Value(Value::Integer(address))) = address else {
+        return None;
+    };
+    return function.shadow_stack.as_ref()?.slot(address, memarg.offset);
+}
+
+fn pop_address(
+    memarg: &MemArg,
+    pointee: impl Into<Type>,
+    block: &mut BlockBuilder,
+    function: &mut FunctionBuilder,
+    module: &mut ModuleBuilder,
+) -> Result<Rc<Pointer>> {
+    let pointee = pointee.into();
+    return match block.stack_pop_any()? {
+        StackValue::Value(Value::Integer(address)) => {
+            memory_pointer(memarg, address, pointee, function, module)
+        }
+        address => {
+            let pointer = address.to_pointer(PointerSize::Skinny, pointee, module)?;
+            bounds_guard(&pointer, function, module);
+            Ok(pointer)
+        }
+    };
+}
+
+fn memory_pointer(
+    memarg: &MemArg,
+    address: Rc<Integer>,
+    pointee: impl Into<Type>,
+    function: &mut FunctionBuilder,
+    module: &mut ModuleBuilder,
+) -> Result<Rc<Pointer>> {
+    let pointee = pointee.into();
+    return match module.memory_variable(memarg.memory, pointee.clone()) {
+        Some(memory) => {
+            bounds_guard(&memory, function, module);
+            memory.access(address, module).map(Rc::new)
+        }
+        None if memarg.memory != 0 => Err(unbound_memory(memarg.memory)),
+        None => Value::Integer(address).to_pointer(PointerSize::Skinny, pointee, module),
+    };
+}
+
+/// Traps when the runtime array behind `pointer` is empty, since bounds checks have no element to clamp its
+/// accesses to
+pub fn bounds_guard(pointer: &Rc<Pointer>, function: &mut FunctionBuilder, module: &ModuleBuilder) {
+    let mut array = pointer;
+    while let PointerSource::Casted { prev } = &array.source {
+        array = prev;
+    }
+
+    if !module.bounds_checks || !array.is_fat() || !array.is_structured() {
+        return;
+    }
+
+    let is_empty = Bool::new(BoolSource::IntEquality {
+        kind: Equality::Eq,
+        op1: Rc::new(Integer::new(IntegerSource::ArrayLength {
+            structured_array: array.clone(),
+        })),
+        op2: Rc::new(Integer::new_constant_u32(0)),
+    });
+
+    let trap = function.new_label();
+    let next = function.new_label();
+    function.anchors.push(Operation::BranchConditional {
+        condition: Rc::new(is_empty),
+        true_label: trap,
+        false_label: next,
+    });
+    function.anchors.push(Operation::Label(trap));
+    function
+        .anchors
+        .push(Operation::Unreachable { panic: None });
+    function.anchors.push(Operation::Label(next));
+}
+
+fn load(
+    memarg: &MemArg,
+    pointee: ScalarType,
+    block: &mut BlockBuilder,
+    function: &mut FunctionBuilder,
+    module: &mut ModuleBuilder,
+) -> Result<Value> {
+    let offset = Rc::new(Integer::try_new_constant_usize(memarg.offset, module)?);
+    let address = match block.stack_peek_any()? {
+        StackValue::Value(Value::Integer(address))
+            if module.data_mode != DataSegmentMode::Ignore =>
+        {
+            block.stack_pop_any()?;
+            address.add(offset, module)?
+        }
+        _ => {
+            return pop_address(memarg, pointee, block, function, module)?
+                .access(offset, module)
+                .map(Rc::new)?
+                .load(Some(memarg.align as u32), block, module)
+        }
+    };
+
+    let size = pointee.byte_size().ok_or_else(Error::unexpected)?;
+    if let Some(bytes) = constant_data(memarg, &address, size, module)? {
+        let value: Value = match pointee {
+            ScalarType::I32 => Integer::new_constant_u32(u32::from_le_bytes(
+                bytes.try_into().map_err(Error::custom)?,
+            ))
+            .into(),
+            ScalarType::I64 => Integer::new_constant_u64(u64::from_le_bytes(
+                bytes.try_into().map_err(Error::custom)?,
+            ))
+            .into(),
+            ScalarType::F32 => Float::new_constant_f32(f32::from_le_bytes(
+                bytes.try_into().map_err(Error::custom)?,
+            ))
+            .into(),
+            ScalarType::F64 => Float::new_constant_f64(f64::from_le_bytes(
+                bytes.try_into().map_err(Error::custom)?,
+            ))
+            .into(),
+            _ => return Err(Error::unexpected()),
+        };
+        return Ok(value);
+    }
+
+    return load_data(memarg, address, size, pointee, block, function, module);
+}
+
+fn constant_data<'a>(
+    memarg: &MemArg,
+    address: &Integer,
+    len: u32,
+    module: &ModuleBuilder<'a>,
+) -> Result<Option<&'a [u8]>> {
+    if module.data_mode != DataSegmentMode::Constant {
+        return Ok(None);
+    }
+
+    return Ok(match constant_address(address)? {
+        Some(address) => module.data_at(memarg.memory, address, len),
+        None => None,
+    });
+}
+
+fn constant_address(address: &Integer) -> Result<Option<u64>> {
+    return Ok(match address.get_constant_value()? {
+        Some(ConstantSource::Short(x)) => Some(x as u64),
+        Some(ConstantSource::Long(x)) => Some(x),
+        None => None,
+    });
+}
+
+fn in_data_segments(address: &Rc<Integer>, len: u32, module: &ModuleBuilder) -> Result<Rc<Bool>> {
+    let ranges = module.data_ranges();
+    if let Some(address) = constant_address(address)? {
+        let inside = ranges
+            .iter()
+            .any(|x| address >= x.start && address + len as u64 <= x.end);
+        return Ok(Rc::new(Bool::new(BoolSource::Constant(inside))));
+    }
+
+    let mut result = None;
+    for range in ranges.into_iter().filter(|x| x.end - x.start >= len as u64) {
+        let lower = Rc::new(Bool::new(BoolSource::IntComparison {
+            kind: Comparison::Ge,
+            signed: false,
+            op1: address.clone(),
+            op2: Rc::new(Integer::try_new_constant_usize(range.start, module)?),
+        }));
+        let upper = Rc::new(Bool::new(BoolSource::IntComparison {
+            kind: Comparison::Le,
+            signed: false,
+            op1: address.clone(),
+            op2: Rc::new(Integer::try_new_constant_usize(
+                range.end - len as u64,
+                module,
+            )?),
+        }));
+        let inside = Rc::new(Bool::new(BoolSource::Select {
+            selector: lower,
+            true_value: upper,
+            false_value: Rc::new(Bool::new(BoolSource::Constant(false))),
+        }));
+
+        result = Some(match result {
+            Some(prev) => Rc::new(Bool::new(BoolSource::Select {
+                selector: inside,
+                true_value: Rc::new(Bool::new(BoolSource::Constant(true))),
+                false_value: prev,
+            })),
+            None => inside,
+        });
+    }
+
+    return Ok(result.unwrap_or_else(|| Rc::new(Bool::new(BoolSource::Constant(false)))));
+}
+
+fn data_address(
+    address: Rc<Integer>,
+    len: u32,
+    module: &ModuleBuilder,
+) -> Result<(Rc<Bool>, Option<Rc<Integer>>)> {
+    let selector = in_data_segments(&address, len, module)?;
+    let data_address = match selector.get_constant_value()? {
+        Some(true) => Some(address),
+        Some(false) => None,
+        None => Some(Rc::new(Integer::new(IntegerSource::Select {
+            selector: selector.clone(),
+            true_value: address,
+            false_value: Rc::new(Integer::try_new_constant_usize(
+                module.data_scratch_offset(),
+                module,
+            )?),
+        }))),
+    };
+    return Ok((selector, data_address));
+}
+
+fn load_data(
+    memarg: &MemArg,
+    address: Rc<Integer>,
+    len: u32,
+    pointee: ScalarType,
+    block: &mut BlockBuilder,
+    function: &mut FunctionBuilder,
+    module: &mut ModuleBuilder,
+) -> Result<Value> {
+    let log2_alignment = Some(memarg.align as u32);
+    let data = match module.data_variable(pointee) {
+        Some(data) if memarg.memory == 0 => data,
+        _ => {
+            return memory_pointer(memarg, address, pointee, function, module)?.load(
+                log2_alignment,
+                block,
+                module,
+            )
+        }
+    };
+
+    let (selector, data_address) = data_address(address.clone(), len, module)?;
+    let data_value = match data_address {
+        Some(data_address) => {
+            data.access(data_address, module)
+                .map(Rc::new)?
+                .load(log2_alignment, block, module)?
+        }
+        None => {
+            return memory_pointer(memarg, address, pointee, function, module)?.load(
+                log2_alignment,
+                block,
+                module,
+            )
+        }
+    };
+
+    if selector.get_constant_value()? == Some(true) {
+        return Ok(data_value);
+    }
+
+    let memory_value = memory_pointer(memarg, address, pointee, function, module)?.load(
+        log2_alignment,
+        block,
+        module,
+    )?;
+
+    return Ok(match (data_value, memory_value) {
+        (Value::Integer(true_value), Value::Integer(false_value)) => {
+            Integer::new(IntegerSource::Select {
+                selector,
+                true_value,
+                false_value,
+            })
+            .into()
+        }
+        (Value::Float(true_value), Value::Float(false_value)) => Float::new(FloatSource::Select {
+            selector,
+            true_value,
+            false_value,
+        })
+        .into(),
+        _ => return Err(Error::unexpected()),
+    });
+}
+
+fn store(
+    memarg: &MemArg,
+    pointee: ScalarType,
+    value: Value,
+    block: &mut BlockBuilder,
+    function: &mut FunctionBuilder,
+    module: &mut ModuleBuilder,
+) -> Result<()> {
+    let log2_alignment = Some(memarg.align as u32);
+    let offset = Rc::new(Integer::try_new_constant_usize(memarg.offset, module)?);
+    let address = match block.stack_peek_any()? {
+        StackValue::Value(Value::Integer(address))
+            if module.data_mode != DataSegmentMode::Ignore =>
+        {
+            block.stack_pop_any()?;
+            address.add(offset, module)?
+        }
+        _ => {
+            let pointer = pop_address(memarg, pointee, block, function, module)?
+                .access(offset, module)
+                .map(Rc::new)?;
+            function
+                .anchors
+                .push(pointer.store(value, log2_alignment, block, module)?);
+            return Ok(());
+        }
+    };
+
+    let len = pointee.byte_size().ok_or_else(Error::unexpected)?;
+    if module.data_mode == DataSegmentMode::Constant {
+        if let Some(constant) = constant_address(&address)? {
+            if module.data_overlaps(memarg.memory, constant, len) {
+                return Err(Error::msg(format!(
+                    "Stores to address {constant} overwrite a data segment translated into constants"
+                )));
+            }
+        }
+    }
+
+    let data = match module.data_variable(pointee) {
+        Some(data) if memarg.memory == 0 => Some(data),
+        _ => None,
+    };
+    if let Some(data) = data {
+        if let (_, Some(data_address)) = data_address(address.clone(), len, module)? {
+            let pointer = data.access(data_address, module).map(Rc::new)?;
+            function
+                .anchors
+                .push(pointer.store(value.clone(), log2_alignment, block, module)?);
+        }
+    }
+
+    let pointer = memory_pointer(memarg, address, pointee, function, module)?;
+    function
+        .anchors
+        .push(pointer.store(value, log2_alignment, block, module)?);
+    return Ok(());
+}
+
+fn memory_init(
+    data_index: u32,
+    mem: u32,
+    block: &mut BlockBuilder,
+    function: &mut FunctionBuilder,
+    module: &mut ModuleBuilder,
+) -> Result<()> {
+    let len = block.stack_pop(ScalarType::I32, module)?.into_integer()?;
+    let offset = block.stack_pop(ScalarType::I32, module)?.into_integer()?;
+    let address = block
+        .stack_pop(ScalarType::Isize(module), module)?
+        .into_integer()?;
+
+    let bytes = *module
+        .passive_data
+        .get(&data_index)
+        .ok_or_else(Error::element_not_found)?;
+    let segment_len = u32::try_from(bytes.len()).map_err(Error::custom)?;
+
+    match (offset.get_constant_value()?, len.get_constant_value()?) {
+        (Some(ConstantSource::Short(offset)), Some(ConstantSource::Short(len))) => {
+            if offset as u64 + len as u64 > segment_len as u64 {
+                return Err(Error::msg(format!(
+                    "memory.init reads past the end of data segment {data_index}"
+                )));
+            }
+        }
+
+        // Copies past the end of the segment trap
+        _ => {
+            let segment_len = Rc::new(Integer::new_constant_u32(segment_len));
+            let condition = Bool::new(BoolSource::Select {
+                selector: Rc::new(Bool::new(BoolSource::IntComparison {
+                    kind: Comparison::Gt,
+                    signed: false,
+                    op1: offset.clone(),
+                    op2: segment_len.clone(),
+                })),
+                true_value: Rc::new(Bool::new(BoolSource::Constant(true))),
+                false_value: Rc::new(Bool::new(BoolSource::IntComparison {
+                    kind: Comparison::Gt,
+                    signed: false,
+                    op1: len.clone(),
+                    op2: Rc::new(segment_len.sub(offset.clone(), module)?),
+                })),
+            });
+
+            let trap = function.new_label();
+            let next = function.new_label();
+            function.anchors.push(Operation::BranchConditional {
+                condition: Rc::new(condition),
+                true_label: trap,
+                false_label: next,
+            });
+            function.anchors.push(Operation::Label(trap));
+            function
+                .anchors
+                .push(Operation::Unreachable { panic: None });
+            function.anchors.push(Operation::Label(next));
+        }
+    }
+
+    // The array has at least one element, even if the segment is empty
+    let mut words = bytes
+        .chunks(4)
+        .map(|x| {
+            let mut word = [0; 4];
+            word[..x.len()].copy_from_slice(x);
+            u32::from_le_bytes(word)
+        })
+        .collect::<Vec<_>>();
+    if words.is_empty() {
+        words.push(0);
+    }
+    let words = Rc::<[u32]>::from(words);
+
+    let counter = Rc::new(Pointer::new_variable(
+        PointerSize::Skinny,
+        StorageClass::Function,
+        ScalarType::I32,
+        None,
+        [],
+    ));
+    function.anchors.push(counter.clone().store(
+        Integer::new_constant_u32(0),
+        None,
+        block,
+        module,
+    )?);
+
+    let header = function.new_label();
+    let body = function.new_label();
+    let end = function.new_label();
+    function.anchors.push(Operation::Branch { label: header });
+    function.anchors.push(Operation::Label(header));
+
+    let i = counter.clone().load(None, block, module)?.into_integer()?;
+    function.anchors.push(Operation::BranchConditional {
+        condition: Rc::new(Bool::new(BoolSource::IntComparison {
+            kind: Comparison::Lt,
+            signed: false,
+            op1: i.clone(),
+            op2: len,
+        })),
+        true_label: body,
+        false_label: end,
+    });
+    function.anchors.push(Operation::Label(body));
+
+    // Reads the byte out of the segment's words
+    let source = offset.add(i.clone(), module)?;
+    let word = Rc::new(Integer::new(IntegerSource::ConstantArrayElement {
+        words,
+        index: source
+            .clone()
+            .u_shr(Rc::new(Integer::new_constant_u32(2)), false, module)?,
+    }));
+    let byte = word
+        .u_shr(byte_shift(source, module)?, false, module)?
+        .and(Rc::new(Integer::new_constant_u32(0xff)), module)?;
+
+    // Replaces the byte inside the word of the memory that contains it
+    let target = match module.isize_integer_kind() {
+        IntegerKind::Short => i.clone(),
+        IntegerKind::Long => Rc::new(Integer::new(IntegerSource::Conversion(
+            IntegerConversionSource::FromShort {
+                signed: false,
+                value: i.clone(),
+            },
+        ))),
+    };
+    let target = address.add(target, module)?;
+    let shift = byte_shift(target.clone(), module)?;
+    let target = target.and(Rc::new(Integer::new_constant_isize(-4, module)), module)?;
+
+    let memarg = MemArg {
+        align: 2,
+        max_align: 2,
+        offset: 0,
+        memory: mem,
+    };
+    block.stack_push(target.clone());
+    let value = load(&memarg, ScalarType::I32, block, function, module)?.into_integer()?;
+
+    let mask = Rc::new(Integer::new_constant_u32(0xff))
+        .shl(shift.clone(), module)?
+        .xor(Rc::new(Integer::new_constant_u32(u32::MAX)), module)?;
+    let value = value
+        .and(mask, module)?
+        .or(byte.shl(shift, module)?, module)?;
+
+    block.stack_push(target);
+    store(
+        &memarg,
+        ScalarType::I32,
+        Value::Integer(value),
+        block,
+        function,
+        module,
+    )?;
+
+    let next = i.add(Rc::new(Integer::new_constant_u32(1)), module)?;
+    function
+        .anchors
+        .push(counter.store(next, None, block, module)?);
+    function.anchors.push(Operation::Branch { label: header });
+    function.anchors.push(Operation::Label(end));
+    return Ok(());
+}
+
+fn byte_shift(address: Rc<Integer>, module: &ModuleBuilder) -> Result<Rc<Integer>> {
+    let byte = match address.kind(module)? {
+        IntegerKind::Short => address.and(Rc::new(Integer::new_constant_u32(3)), module)?,
+        IntegerKind::Long => Rc::new(Integer::new(IntegerSource::Conversion(
+            IntegerConversionSource::FromLong(
+                address.and(Rc::new(Integer::new_constant_u64(3)), module)?,
+            ),
+        ))),
+    };
+    return byte.shl(Rc::new(Integer::new_constant_u32(3)), module);
+}
+
+fn unbound_memory(memory: u32) -> Error {
+    return Error::msg(format!("Memory {memory} isn't bound to any storage buffer"));
+}
+
 fn load_byte<'a>(
     kind: IntegerKind,
     memarg: &MemArg,
     block: &mut BlockBuilder<'a>,
+    function: &mut FunctionBuilder,
     module: &mut ModuleBuilder,
 ) -> Result<()> {
-    let zero = Rc::new(Integer::new_constant_usize(0, module));
     let eight = Rc::new(Integer::new_constant_usize(8, module));
 
+    // Offsets are computed with pointer-sized integers, whilst the mask matches the loaded value
     let (shift_offset, stride, mask) = match kind {
         IntegerKind::Short => (
             Rc::new(Integer::new_constant_usize(3, &module)),
-            Rc::new(Integer::new_constant_u32(4)),
+            Rc::new(Integer::new_constant_usize(4, &module)),
             Rc::new(Integer::new_constant_u32(0xff)),
         ),
         IntegerKind::Long => (
             Rc::new(Integer::new_constant_usize(7, &module)),
-            Rc::new(Integer::new_constant_u64(8)),
+            Rc::new(Integer::new_constant_usize(8, &module)),
             Rc::new(Integer::new_constant_u64(0xff)),
         ),
     };
 
-    // Take pointer by parts
-    let pointer = block
-        .stack_pop_any()?
-        .to_pointer(PointerSize::Skinny, kind, module)?;
-    let byte_offset = pointer.byte_offset();
-
-    // Calculate true offset
-    let constant_offset = Rc::new(Integer::new_constant_usize(memarg.offset as u32, module));
-    let byte_offset = match byte_offset {
-        Some(byte_offset) => byte_offset.add(constant_offset, module)?,
-        None => constant_offset,
-    };
+    let constant_offset = Rc::new(Integer::try_new_constant_usize(memarg.offset, module)?);
+    let (value, byte_offset) = match block.stack_peek_any()? {
+        // Integer addresses may point into the data segments
+        StackValue::Value(Value::Integer(address))
+            if module.data_mode != DataSegmentMode::Ignore =>
+        {
+            block.stack_pop_any()?;
+            let address = address.add(constant_offset, module)?;
+
+            if let Some(&[byte]) = constant_data(memarg, &address, 1, module)? {
+                match kind {
+                    IntegerKind::Short => block.stack_push(Integer::new_constant_u32(byte as u32)),
+                    IntegerKind::Long => block.stack_push(Integer::new_constant_u64(byte as u64)),
+                }
+                return Ok(());
+            }
 
-    // Get value of unadapted integer
-    let value = pointer
-        .access(byte_offset.clone(), module)
-        .map(Rc::new)?
-        .load(Some(memarg.align as u32), block, module)?
-        .into_integer()?;
+            let value = load_data(
+                memarg,
+                address.clone(),
+                1,
+                kind.into(),
+                block,
+                function,
+                module,
+            )?;
+            (value.into_integer()?, address)
+        }
+
+        _ => {
+            // Take pointer by parts
+            let pointer = pop_address(memarg, kind, block, function, module)?;
+            let byte_offset = pointer.byte_offset();
+
+            // Calculate true offset
+            let byte_offset = match byte_offset {
+                Some(byte_offset) => byte_offset.add(constant_offset, module)?,
+                None => constant_offset,
+            };
+
+            // Get value of unadapted integer
+            let value = pointer
+                .access(byte_offset.clone(), module)
+                .map(Rc::new)?
+                .load(Some(memarg.align as u32), block, module)?
+                .into_integer()?;
+            (value, byte_offset)
+        }
+    };
 
     let shift = shift_offset
         .sub(byte_offset.u_rem(stride, module)?, module)
         .map(Rc::new)?
         .mul(eight, module)?;
 
+    let shift = match (module.isize_integer_kind(), kind) {
+        (IntegerKind::Long, IntegerKind::Short) => Rc::new(Integer::new(
+            IntegerSource::Conversion(IntegerConversionSource::FromLong(shift)),
+        )),
+        (IntegerKind::Short, IntegerKind::Long) => Rc::new(Integer::new(
+            IntegerSource::Conversion(IntegerConversionSource::FromShort {
+                signed: false,
+                value: shift,
+            }),
+        )),
+        _ => shift,
+    };
+
     let result = value.u_shr(shift, false, module)?.and(mask, module)?;
     block.stack_push(result);
     Ok(())
@@ -1420,6 +2307,16 @@ fn local_set<'a>(
                     ops
                 }
 
+                // The local only ever holds pointers, so the integer view isn't needed
+                StackValue::Schrodinger {
+                    pointer_variable, ..
+                } if !sch.integer_shadow => {
+                    let (op1, op2) = sch.store_pointer(pointer_variable, block, module)?;
+                    let mut ops = vec![op1];
+                    ops.extend(op2);
+                    ops
+                }
+
                 StackValue::Schrodinger {
                     pointer_variable,
                     loaded_integer,
@@ -1441,3 +2338,155 @@ fn local_set<'a>(
 
     return Ok(());
 }
+
+fn block_type(blockty: BlockType, module: &ModuleBuilder) -> Result<(Vec<Type>, Vec<Type>)> {
+    return Ok(match blockty {
+        BlockType::Empty => (Vec::new(), Vec::new()),
+        BlockType::Type(ty) => (Vec::new(), vec![Type::from(ty)]),
+        BlockType::FuncType(i) => {
+            let ty = module
+                .func_types
+                .get(i as usize)
+                .ok_or_else(Error::element_not_found)?;
+
+            (
+                ty.params().iter().copied().map(Type::from).collect(),
+                ty.results().iter().copied().map(Type::from).collect(),
+            )
+        }
+    });
+}
+
+fn block_variables(types: &[Type]) -> Rc<[Rc<Pointer>]> {
+    return types
+        .iter()
+        .map(|ty| {
+            Rc::new(Pointer::new_variable(
+                PointerSize::Skinny,
+                StorageClass::Function,
+                ty.clone(),
+                None,
+                [],
+            ))
+        })
+        .collect();
+}
+
+fn pop_params(count: usize, block: &mut BlockBuilder) -> Result<Vec<StackValue>> {
+    let at = block
+        .stack
+        .len()
+        .checked_sub(count)
+        .ok_or_else(|| Error::msg("Empty stack"))?;
+    return Ok(block.stack.split_off(at));
+}
+
+fn call(
+    function_index: u32,
+    block: &mut BlockBuilder,
+    function: &mut FunctionBuilder,
+    module: &mut ModuleBuilder,
+) -> Result<()> {
+    let f = module
+        .functions
+        .get(function_index as usize)
+        .cloned()
+        .ok_or_else(Error::element_not_found)?;
+
+    match module.inline_candidates.get(&function_index).cloned() {
+        Some(body) if !module.inlining.contains(&function_index) => {
+            block.inline_function(function_index, &f, body, function, module)
+        }
+        _ => block.call_function(&f, function, module),
+    }
+}
+
+fn branch_target(relative_depth: u32, block: &BlockBuilder) -> Result<Option<BranchTarget>> {
+    let depth = relative_depth as usize;
+    if depth == block.outer_labels.len() {
+        return Ok(None);
+    }
+
+    return block
+        .outer_labels
+        .get(depth)
+        .cloned()
+        .map(Some)
+        .ok_or_else(Error::element_not_found);
+}
+
+fn push_return(
+    ty: Option<Type>,
+    peek: bool,
+    block: &mut BlockBuilder,
+    function: &mut FunctionBuilder,
+    module: &mut ModuleBuilder,
+) -> Result<()> {
+    let value = match (ty, peek) {
+        (Some(ty), true) => Some(block.stack_peek(ty, module)?),
+        (Some(ty), false) => Some(block.stack_pop(ty, module)?),
+        (None, _) => None,
+    };
+
+    function.anchors.push(Operation::Return { value });
+    return Ok(());
+}
+
+fn store_branch_values(
+    target: &BranchTarget,
+    peek: bool,
+    block: &mut BlockBuilder,
+    function: &mut FunctionBuilder,
+    module: &mut ModuleBuilder,
+) -> Result<()> {
+    let mut values = Vec::with_capacity(target.values.len());
+    for variable in target.values.iter().rev() {
+        values.push(block.stack_pop(variable.pointee.clone(), module)?);
+    }
+    values.reverse();
+
+    for (variable, value) in target.values.iter().zip(values) {
+        if peek {
+            block.stack_push(value.clone());
+        }
+        function
+            .anchors
+            .push(variable.clone().store(value, None, block, module)?);
+    }
+    return Ok(());
+}
+
+fn end_branch(
+    mut inner_block: BlockBuilder,
+    target: &BranchTarget,
+    function: &mut FunctionBuilder,
+    module: &mut ModuleBuilder,
+) -> Result<()> {
+    if function
+        .anchors
+        .last()
+        .is_some_and(Operation::is_block_terminating)
+    {
+        return Ok(());
+    }
+
+    store_branch_values(target, false, &mut inner_block, function, module)?;
+    function.anchors.push(Operation::Branch {
+        label: target.label,
+    });
+    return Ok(());
+}
+
+fn push_results(
+    target: &BranchTarget,
+    block: &mut BlockBuilder,
+    function: &mut FunctionBuilder,
+    module: &mut ModuleBuilder,
+) -> Result<()> {
+    function.anchors.push(Operation::Label(target.label));
+    for variable in target.values.iter() {
+        let value = variable.clone().load(None, block, module)?;
+        block.stack_push(value);
+    }
+    return Ok(());
+}
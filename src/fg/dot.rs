@@ -0,0 +1,109 @@
+use super::{function::FunctionBuilder, Handle, Label, Operation};
+use std::fmt::Write;
+
+impl<'a> FunctionBuilder<'a> {
+    /// Renders the blocks of the function, and the branches between them, as a Graphviz graph
+    pub fn dump_dot(&self) -> String {
+        let mut labels = Vec::<Handle<Label>>::new();
+        let mut block_id = |label: &Handle<Label>| -> usize {
+            match labels.iter().position(|x| x == label) {
+                Some(i) => i + 1,
+                None => {
+                    labels.push(*label);
+                    labels.len()
+                }
+            }
+        };
+
+        let mut nodes = String::new();
+        let mut edges = String::new();
+        let mut current = Some(0);
+        let mut rows = String::new();
+
+        for (op, i) in self.anchors.iter().zip(0..) {
+            if let Operation::Label(label) = op {
+                let next = block_id(label);
+                if let Some(prev) = current {
+                    let _ = writeln!(edges, "    b{prev} -> b{next} [style=dashed];");
+                    write_node(&mut nodes, prev, &rows);
+                }
+
+                rows.clear();
+                current = Some(next);
+                continue;
+            }
+
+            let Some(block) = current else { continue };
+            let operator = self
+                .operators
+                .get(i)
+                .and_then(Option::as_deref)
+                .unwrap_or("");
+            let _ = write!(
+                rows,
+                "<TR><TD ALIGN=\"LEFT\">{}</TD><TD ALIGN=\"LEFT\"><FONT COLOR=\"gray40\">{}</FONT></TD></TR>",
+                op.name(),
+                escape(operator)
+            );
+
+            match op {
+                Operation::Branch { label } => {
+                    let _ = writeln!(edges, "    b{block} -> b{};", block_id(label));
+                }
+                Operation::BranchConditional {
+                    true_label,
+                    false_label,
+                    ..
+                } => {
+                    let _ = writeln!(
+                        edges,
+                        "    b{block} -> b{} [label=\"true\"];",
+                        block_id(true_label)
+                    );
+                    let _ = writeln!(
+                        edges,
+                        "    b{block} -> b{} [label=\"false\"];",
+                        block_id(false_label)
+                    );
+                }
+                _ => {}
+            }
+
+            if op.is_block_terminating() {
+                write_node(&mut nodes, block, &rows);
+                current = None;
+            }
+        }
+
+        if let Some(block) = current {
+            write_node(&mut nodes, block, &rows);
+        }
+
+        let mut result = format!("digraph \"function {}\" {{\n", self.index);
+        result.push_str("    node [shape=plaintext, fontname=\"monospace\"];\n");
+        result.push_str(&nodes);
+        result.push_str(&edges);
+        result.push_str("}\n");
+        return result;
+    }
+}
+
+fn write_node(nodes: &mut String, block: usize, rows: &str) {
+    let name = match block {
+        0 => String::from("entry"),
+        other => format!("L{other}"),
+    };
+
+    let _ = writeln!(
+        nodes,
+        "    b{block} [label=<<TABLE BORDER=\"0\" CELLBORDER=\"1\" CELLSPACING=\"0\"><TR><TD COLSPAN=\"2\"><B>{name}</B></TD></TR>{rows}</TABLE>>];"
+    );
+}
+
+fn escape(s: &str) -> String {
+    return s
+        .replace('&', "&amp;")
+        .replace('<', "&lt;")
+        .replace('>', "&gt;")
+        .replace('"', "&quot;");
+}
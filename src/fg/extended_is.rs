@@ -9,6 +9,8 @@ pub enum ExtendedSet {
     GLSL450,
     // https://registry.khronos.org/SPIR-V/specs/unified1/OpenCL.ExtendedInstructionSet.100.html
     OpenCL,
+    // https://github.com/KhronosGroup/SPIRV-Registry/blob/main/nonsemantic/NonSemantic.DebugPrintf.asciidoc
+    DebugPrintf,
 }
 
 #[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
@@ -22,6 +24,21 @@ pub enum GLSLInstr {
     RoundEven = 2,
     Fmin = 37,
     Fmax = 40,
+    Sin = 13,
+    Cos = 14,
+    Tan = 15,
+    Asin = 16,
+    Acos = 17,
+    Atan = 18,
+    Sinh = 19,
+    Cosh = 20,
+    Tanh = 21,
+    Atan2 = 25,
+    Pow = 26,
+    Exp = 27,
+    Log = 28,
+    Exp2 = 29,
+    Log2 = 30,
 }
 
 #[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
@@ -41,6 +58,27 @@ pub enum OpenCLInstr {
     Copysign = 13,
     Fmin = 28,
     Fmax = 27,
+    Acos = 0,
+    Asin = 3,
+    Atan = 6,
+    Atan2 = 7,
+    Cos = 14,
+    Cosh = 15,
+    Exp = 19,
+    Exp2 = 20,
+    Log = 37,
+    Log2 = 38,
+    Pow = 48,
+    Sin = 57,
+    Sinh = 59,
+    Tan = 62,
+    Tanh = 63,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[repr(u32)]
+pub enum DebugPrintfInstr {
+    DebugPrintf = 1,
 }
 
 impl Display for ExtendedSet {
@@ -48,6 +86,7 @@ impl Display for ExtendedSet {
         match self {
             ExtendedSet::GLSL450 => write!(f, "GLSL.std.450"),
             ExtendedSet::OpenCL => write!(f, "OpenCL.std"),
+            ExtendedSet::DebugPrintf => write!(f, "NonSemantic.DebugPrintf"),
         }
     }
 }
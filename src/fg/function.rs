@@ -1,22 +1,29 @@
 use super::{
     block::{translate_block, BlockBuilder, BlockReader, StackValue},
-    module::ModuleBuilder,
+    module::{CallableFunction, ModuleBuilder},
+    provenance::{self, Provenance},
+    shadow_stack::{self, ShadowStack},
+    structurize::Construct,
     values::{integer::Integer, pointer::Pointer, Value},
-    End, Label, Operation,
+    Arena, End, Handle, Label, Operation,
 };
 use crate::{
-    config::ConfigBuilder,
+    config::{ConfigBuilder, SelectionHint, Verbosity},
     decorator::VariableDecorator,
+    diagnostics::Origin,
     error::{Error, Result},
-    r#type::{PointerSize, ScalarType, Type},
-    version::Version,
+    r#type::{Layout, PointerSize, ScalarType, Type, NULL_REFERENCE},
+    Str,
 };
 use once_cell::unsync::OnceCell;
-use rspirv::spirv::{Capability, ExecutionModel, StorageClass};
+use rspirv::{
+    dr::Operand,
+    spirv::{Capability, ExecutionModel, FPFastMathMode, LoopControl, MemoryModel, StorageClass},
+};
 use serde::{Deserialize, Serialize};
 use std::{borrow::Cow, cell::Cell, collections::VecDeque, rc::Rc};
 use vector_mapp::vec::VecMap;
-use wasmparser::{Export, FuncType, FunctionBody, ValType};
+use wasmparser::{Export, FuncType, FunctionBody, Operator, ValType};
 
 /// May be a pointer or an integer, but you won't know until you try to store into it.
 #[derive(Debug, Clone)]
@@ -24,6 +31,8 @@ pub struct Schrodinger {
     pub pointer: OnceCell<Rc<Pointer>>,
     pub offset: OnceCell<Rc<Pointer>>,
     pub integer: OnceCell<Rc<Pointer>>,
+    /// Whether the integer view of the values stored into it is kept
+    pub integer_shadow: bool,
 }
 
 impl Schrodinger {
@@ -148,26 +157,42 @@ pub enum Storeable {
 pub struct EntryPoint<'a> {
     pub execution_model: ExecutionModel,
     pub execution_modes: Box<[ExecutionMode]>,
-    pub name: &'a str,
-    pub interface: Vec<Rc<Pointer>>,
+    pub name: Str<'a>,
 }
 
 #[derive(Debug, Default)]
 pub struct FunctionBuilder<'a> {
     pub(crate) function_id: Rc<Cell<Option<rspirv::spirv::Word>>>,
+    /// Index of the function inside the WebAssembly module
+    pub(crate) index: u32,
     pub entry_point: Option<EntryPoint<'a>>,
     pub parameters: Box<[Value]>,
     pub local_variables: Box<[Storeable]>,
     pub return_type: Option<Type>,
     /// Instructions who's order **must** be followed
     pub anchors: Vec<Operation>,
+    /// Labels of the blocks of the function, referenced by its branches
+    pub labels: Arena<Label>,
+    /// WebAssembly operator that produced each anchor
+    pub(crate) operators: Vec<Option<Box<str>>>,
+    /// Structured construct headed by the block each anchor terminates
+    pub(crate) constructs: Vec<Option<Construct>>,
     pub variable_initializers: Box<[Operation]>,
     pub outside_vars: Box<[Rc<Pointer>]>,
+    pub loops: LoopHints,
+    pub selection_control: Option<SelectionHint>,
+    pub relaxed_precision: RelaxedPrecision,
+    pub fast_math: FastMath,
+    /// Verbosity of the module the function is built for
+    pub(crate) verbosity: Verbosity,
+    /// Frame of the function in the shadow stack, if its slots are kept in variables
+    pub(crate) shadow_stack: Option<ShadowStack>,
 }
 
 impl<'a> FunctionBuilder<'a> {
     pub fn new(
         function_id: Rc<Cell<Option<rspirv::spirv::Word>>>,
+        index: u32,
         export: Option<Export<'a>>,
         config: &FunctionConfig,
         ty: &FuncType,
@@ -178,7 +203,6 @@ impl<'a> FunctionBuilder<'a> {
             return Err(Error::msg("Function can only have a single result value"));
         }
 
-        let mut interface = Vec::new();
         let mut params = Vec::new();
         let mut locals = Vec::new();
         let mut outside_vars = Vec::new();
@@ -213,6 +237,11 @@ impl<'a> FunctionBuilder<'a> {
                     ty => (ty, PointerSize::Skinny, param.kind.storage_class(), None),
                 };
 
+            let relaxed_precision = match param.relaxed_precision {
+                true => vec![VariableDecorator::RelaxedPrecision],
+                false => Vec::new(),
+            };
+
             let variable = match param.kind {
                 ParameterKind::FunctionParameter => {
                     let param = Value::function_parameter(ty.clone());
@@ -221,7 +250,7 @@ impl<'a> FunctionBuilder<'a> {
                         StorageClass::Function,
                         ty,
                         None,
-                        Vec::new(),
+                        relaxed_precision,
                     ));
 
                     variable_initializers.push(Operation::Store {
@@ -239,6 +268,7 @@ impl<'a> FunctionBuilder<'a> {
                         Type::Scalar(_) => decorators.push(VariableDecorator::Flat),
                         _ => {}
                     };
+                    decorators.extend(relaxed_precision.clone());
 
                     let param = Rc::new(Pointer::new_variable(
                         pointer_size,
@@ -248,14 +278,13 @@ impl<'a> FunctionBuilder<'a> {
                         decorators,
                     ));
                     outside_vars.push(param.clone());
-                    interface.push(param.clone());
 
                     let variable = Rc::new(Pointer::new_variable(
                         pointer_size,
                         StorageClass::Function,
                         ty,
                         None,
-                        Vec::new(),
+                        relaxed_precision,
                     ));
 
                     variable_initializers.push(Operation::Copy {
@@ -269,7 +298,8 @@ impl<'a> FunctionBuilder<'a> {
                 }
 
                 ParameterKind::Output(location) => {
-                    let decorators = vec![VariableDecorator::Location(location)];
+                    let mut decorators = vec![VariableDecorator::Location(location)];
+                    decorators.extend(relaxed_precision);
                     let param = Rc::new(Pointer::new_variable(
                         pointer_size,
                         storage_class,
@@ -280,28 +310,76 @@ impl<'a> FunctionBuilder<'a> {
                     param
                 }
 
-                ParameterKind::DescriptorSet { set, binding, .. } => {
-                    let param = Rc::new(Pointer::new_variable(
+                ParameterKind::DescriptorSet {
+                    set,
+                    binding,
+                    access,
+                    buffer,
+                    layout,
+                    ..
+                } => {
+                    if param.relaxed_precision {
+                        return Err(Error::msg(format!(
+                            "Descriptor set parameter {i} can't have relaxed precision"
+                        )));
+                    }
+
+                    let mut decorators = vec![
+                        VariableDecorator::DesctiptorSet(set),
+                        VariableDecorator::Binding(binding),
+                    ];
+                    decorators.extend(
+                        access.decorators(module.memory_model).map_err(|e| {
+                            Error::msg(format!("Descriptor set parameter {i}: {e}"))
+                        })?,
+                    );
+
+                    let param = match buffer {
+                        BufferKind::Storage => {
+                            if let Some(layout) = layout {
+                                layout.validate(&ty, &format!("parameter {i}"))?;
+                            }
+                            Rc::new(Pointer::new_variable(
+                                pointer_size,
+                                storage_class,
+                                ty,
+                                None,
+                                decorators,
+                            ))
+                        }
+                        BufferKind::Uniform => {
+                            let layout = layout.unwrap_or(Layout::Std140);
+                            check_uniform_buffer(&ty, pointer_size, storage_class, access, layout)
+                                .map_err(|e| {
+                                    Error::msg(format!("Uniform buffer parameter {i}: {e}"))
+                                })?;
+                            layout.validate(&ty, &format!("parameter {i}"))?;
+                            Rc::new(Pointer::new_uniform_buffer(ty, decorators))
+                        }
+                    };
+                    param
+                }
+
+                // Ray tracing variables are accessed in place, so that the shaders invoked by `trace_ray` see the changes
+                ParameterKind::ShaderRecordBuffer
+                | ParameterKind::RayPayload(_)
+                | ParameterKind::IncomingRayPayload => {
+                    let variable = Rc::new(Pointer::new_variable(
                         pointer_size,
-                        storage_class,
+                        param.kind.storage_class(),
                         ty,
                         None,
-                        vec![
-                            VariableDecorator::DesctiptorSet(set),
-                            VariableDecorator::Binding(binding),
-                        ],
+                        relaxed_precision,
                     ));
-                    param
+                    if let ParameterKind::RayPayload(location) = param.kind {
+                        module.ray_payloads.push((location, variable.clone()));
+                    }
+                    variable
                 }
             };
 
             if variable.storage_class != StorageClass::Function {
                 outside_vars.push(variable.clone());
-                if module.version >= Version::V1_4
-                    || matches!(storage_class, StorageClass::Input | StorageClass::Output)
-                {
-                    interface.push(variable.clone())
-                }
             }
 
             locals.push(Storeable::Pointer {
@@ -311,47 +389,16 @@ impl<'a> FunctionBuilder<'a> {
         }
 
         // Create local variables
-        let mut locals_reader = body.get_locals_reader()?;
-        for _ in 0..locals_reader.get_count() {
-            let (count, ty) = locals_reader.read()?;
-            locals.reserve(count as usize);
-
-            if matches!(ty, ValType::I32 if !module.wasm_memory64)
-                || matches!(ty, ValType::I64 if module.wasm_memory64)
-            {
-                for _ in 0..count {
-                    let storeable = Storeable::Schrodinger(Rc::new(Schrodinger {
-                        pointer: OnceCell::new(),
-                        offset: OnceCell::new(),
-                        integer: OnceCell::new(),
-                    }));
-                    locals.push(storeable);
-                }
-            } else {
-                let ty = Type::from(ty);
-                for _ in 0..count {
-                    let pointer = Rc::new(Pointer::new_variable(
-                        PointerSize::Skinny,
-                        StorageClass::Function,
-                        ty.clone(),
-                        None,
-                        [],
-                    ));
-
-                    locals.push(Storeable::Pointer {
-                        variable: pointer,
-                        integer_variable: None,
-                    });
-                }
-            }
-        }
+        declare_locals(&body, &mut locals, module)?;
 
         let entry_point = match (export, config.execution_model) {
             (Some(export), Some(execution_model)) => Some(EntryPoint {
                 execution_model,
                 execution_modes: config.execution_modes.clone().into_boxed_slice(),
-                name: export.name,
-                interface, // TODO
+                name: match module.entry_point_names.get(export.name) {
+                    Some(name) => Str::Owned(name.clone()),
+                    None => Str::Borrowed(export.name),
+                },
             }),
             (None, Some(_)) => todo!(),
             _ => None,
@@ -359,15 +406,47 @@ impl<'a> FunctionBuilder<'a> {
 
         let mut result = Self {
             anchors: Vec::new(),
+            labels: Arena::new(),
+            operators: Vec::new(),
+            constructs: Vec::new(),
             parameters: params.into_boxed_slice(),
             local_variables: locals.into_boxed_slice(),
             outside_vars: outside_vars.into_boxed_slice(),
             variable_initializers: variable_initializers.into_boxed_slice(),
             function_id,
+            index,
             entry_point,
             return_type,
+            loops: config.loops,
+            selection_control: config.selection_control.or(module.selection_control),
+            relaxed_precision: config.relaxed_precision,
+            fast_math: config.fast_math,
+            verbosity: module.verbosity,
+            shadow_stack: shadow_stack::analyze(&body, module)?,
         };
 
+        // Run the start function before the body of every entry point
+        if let (Some(_), Some(start)) = (&result.entry_point, module.start_function) {
+            if start != index {
+                match module
+                    .functions
+                    .get(start as usize)
+                    .ok_or_else(Error::element_not_found)?
+                {
+                    CallableFunction::Defined { function_id, .. } => {
+                        result.anchors.push(Operation::FunctionCall {
+                            function_id: function_id.clone(),
+                            args: Box::default(),
+                        });
+                        result.operators.push(Some(Box::from("Start")));
+                    }
+                    CallableFunction::Callback(_) => {
+                        return Err(Error::msg("Imported start functions aren't supported"))
+                    }
+                }
+            }
+        }
+
         let reader = BlockReader::new(body.get_operators_reader()?);
         translate_block(
             reader,
@@ -380,7 +459,137 @@ impl<'a> FunctionBuilder<'a> {
         return Ok(result);
     }
 
-    pub fn block_of(&self, op: &Operation) -> Option<&Rc<Label>> {
+    /// Creates a function without a body, for frontends other than WebAssembly. The body must end with a terminating
+    /// operation
+    pub fn with_signature(
+        parameters: impl IntoIterator<Item = Type>,
+        return_type: Option<Type>,
+    ) -> Self {
+        return Self {
+            parameters: parameters
+                .into_iter()
+                .map(Value::function_parameter)
+                .collect(),
+            return_type,
+            ..Default::default()
+        };
+    }
+
+    /// Marks the function as an entry point of the module
+    pub fn set_entry_point(
+        &mut self,
+        name: impl Into<Str<'a>>,
+        execution_model: ExecutionModel,
+        execution_modes: impl IntoIterator<Item = ExecutionMode>,
+    ) -> &mut Self {
+        self.entry_point = Some(EntryPoint {
+            execution_model,
+            execution_modes: execution_modes.into_iter().collect(),
+            name: name.into(),
+        });
+        self
+    }
+
+    /// Appends an operation to the body of the function
+    pub fn push(&mut self, operation: Operation) -> &mut Self {
+        self.anchors.push(operation);
+        self
+    }
+
+    /// Creates the label of a new block of the function
+    pub fn new_label(&mut self) -> Handle<Label> {
+        return self.labels.alloc(Label::default());
+    }
+
+    pub(crate) fn inline(
+        &mut self,
+        index: u32,
+        ty: &FuncType,
+        args: &[Value],
+        body: FunctionBody<'_>,
+        module: &mut ModuleBuilder,
+    ) -> Result<Option<Option<Value>>> {
+        let mut locals = Vec::new();
+        let mut callee = FunctionBuilder {
+            index,
+            entry_point: self.entry_point.take(),
+            // The callee's blocks end up in this function
+            labels: std::mem::take(&mut self.labels),
+            return_type: ty.results().first().cloned().map(Type::from),
+            verbosity: self.verbosity,
+            ..Default::default()
+        };
+
+        for (arg, ty) in args.iter().zip(ty.params()) {
+            let variable = Rc::new(Pointer::new_variable(
+                PointerSize::Skinny,
+                StorageClass::Function,
+                Type::from(*ty),
+                None,
+                [],
+            ));
+
+            callee.anchors.push(Operation::Store {
+                target: variable.clone(),
+                value: arg.clone(),
+                log2_alignment: None,
+            });
+            locals.push(Storeable::Pointer {
+                variable,
+                integer_variable: None,
+            });
+        }
+
+        declare_locals(&body, &mut locals, module)?;
+        callee.local_variables = locals.into_boxed_slice();
+        callee.shadow_stack = shadow_stack::analyze(&body, module)?;
+
+        module.inlining.push(index);
+        let block = translate_block(
+            BlockReader::new(body.get_operators_reader()?),
+            VecDeque::new(),
+            End::Return(callee.return_type.clone()),
+            &mut callee,
+            module,
+        );
+        module.inlining.pop();
+        self.entry_point = callee.entry_point.take();
+        self.labels = std::mem::take(&mut callee.labels);
+        block?;
+
+        let returns = callee
+            .anchors
+            .iter()
+            .filter(|x| matches!(x, Operation::Return { .. }))
+            .count();
+
+        let value = match callee.anchors.pop() {
+            Some(Operation::Return { value }) if returns == 1 => value,
+            _ => return Ok(None),
+        };
+
+        callee.operators.truncate(callee.anchors.len());
+        self.anchors.append(&mut callee.anchors);
+        self.operators.append(&mut callee.operators);
+        return Ok(Some(value));
+    }
+
+    pub(crate) fn record_operator(&mut self, op: &Operator) {
+        if self.operators.len() < self.anchors.len() {
+            let op = Box::<str>::from(format!("{op:?}"));
+            self.operators.resize(self.anchors.len(), Some(op));
+        }
+    }
+
+    pub(crate) fn origin(&self, op: &Operation, operator: Option<&Box<str>>) -> Origin {
+        return Origin {
+            function: self.index,
+            operator: operator.cloned(),
+            operation: Box::from(op.name()),
+        };
+    }
+
+    pub fn block_of(&self, op: &Operation) -> Option<Handle<Label>> {
         let mut current_blocks = Vec::new();
 
         for anchor in self.anchors.iter() {
@@ -391,7 +600,7 @@ impl<'a> FunctionBuilder<'a> {
                     Some(current_blocks.remove(current_blocks.len() - 1))
                 };
             } else if let Operation::Label(label) = anchor {
-                current_blocks.push(label);
+                current_blocks.push(*label);
             } else if anchor.is_block_terminating() {
                 if current_blocks.is_empty() {
                     continue;
@@ -403,10 +612,10 @@ impl<'a> FunctionBuilder<'a> {
         return None;
     }
 
-    pub fn block(&self, label: &Rc<Label>) -> impl Iterator<Item = &Operation> {
+    pub fn block(&self, label: Handle<Label>) -> impl Iterator<Item = &Operation> {
         let mut start_idx = None;
         for (i, anchor) in self.anchors.iter().enumerate() {
-            if anchor == label {
+            if *anchor == label {
                 start_idx = Some(i + 1);
                 break;
             }
@@ -450,6 +659,26 @@ impl<'a> FunctionConfigBuilder<'a> {
         };
     }
 
+    pub fn set_loop_hints(mut self, loops: LoopHints) -> Self {
+        self.inner.loops = loops;
+        self
+    }
+
+    pub fn set_selection_control(mut self, selection_control: Option<SelectionHint>) -> Self {
+        self.inner.selection_control = selection_control;
+        self
+    }
+
+    pub fn set_relaxed_precision(mut self, relaxed_precision: RelaxedPrecision) -> Self {
+        self.inner.relaxed_precision = relaxed_precision;
+        self
+    }
+
+    pub fn set_fast_math(mut self, fast_math: FastMath) -> Self {
+        self.inner.fast_math = fast_math;
+        self
+    }
+
     pub fn add_exec_mode(mut self, exec_mode: ExecutionMode) -> Result<Self> {
         self.inner.execution_modes.push(exec_mode);
         Ok(self)
@@ -465,10 +694,21 @@ impl<'a> FunctionConfigBuilder<'a> {
             }
             ExecutionModel::Geometry => Capability::Geometry,
             ExecutionModel::Kernel => Capability::Kernel,
-            _ => todo!(),
+            ExecutionModel::TaskNV | ExecutionModel::MeshNV => Capability::MeshShadingNV,
+            ExecutionModel::RayGenerationKHR
+            | ExecutionModel::IntersectionKHR
+            | ExecutionModel::AnyHitKHR
+            | ExecutionModel::ClosestHitKHR
+            | ExecutionModel::MissKHR
+            | ExecutionModel::CallableKHR => Capability::RayTracingKHR,
         };
 
-        self.config.require_capability(capability)?;
+        let idx = self.idx;
+        self.config.require_capability_by(capability, || {
+            Some(format!(
+                "the {exec_model:?} execution model of function {idx}"
+            ))
+        })?;
         self.inner.execution_model = Some(exec_model);
         Ok(self)
     }
@@ -487,6 +727,97 @@ pub struct FunctionConfig {
     pub execution_modes: Vec<ExecutionMode>,
     #[serde(default)]
     pub params: VecMap<u32, Parameter>,
+    #[serde(default)]
+    pub loops: LoopHints,
+    /// Overrides the module's default selection hint
+    #[serde(default)]
+    pub selection_control: Option<SelectionHint>,
+    #[serde(default)]
+    pub relaxed_precision: RelaxedPrecision,
+    #[serde(default)]
+    pub fast_math: FastMath,
+}
+
+/// Values of a function decorated as `RelaxedPrecision`
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Default, Serialize, Deserialize)]
+pub struct RelaxedPrecision {
+    /// The function's return value
+    #[serde(default)]
+    pub return_value: bool,
+    /// The results of every 32-bit floating point arithmetic operation
+    #[serde(default)]
+    pub arithmetic: bool,
+}
+
+/// Floating point optimizations allowed on the arithmetic of a function. Contraction is a shader decoration, and
+/// fast math modes are a kernel decoration, so each platform can only use one of them
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Default, Serialize, Deserialize)]
+pub struct FastMath {
+    /// Decorates the arithmetic as `NoContraction`
+    #[serde(default)]
+    pub no_contraction: bool,
+    /// Assumes the operands and results are never NaN
+    #[serde(default)]
+    pub not_nan: bool,
+    /// Assumes the operands and results are never infinite
+    #[serde(default)]
+    pub not_inf: bool,
+    /// Treats the sign of zero as insignificant
+    #[serde(default)]
+    pub no_signed_zeros: bool,
+    /// Allows divisions to be replaced by a multiplication with the reciprocal
+    #[serde(default)]
+    pub allow_recip: bool,
+    /// Allows any algebraically equivalent transformation
+    #[serde(default)]
+    pub fast: bool,
+}
+
+impl FastMath {
+    /// Returns the modes of the `FPFastMathMode` decoration of the arithmetic
+    pub fn mode(&self) -> FPFastMathMode {
+        let mut mode = FPFastMathMode::NONE;
+        for (enabled, flag) in [
+            (self.not_nan, FPFastMathMode::NOT_NAN),
+            (self.not_inf, FPFastMathMode::NOT_INF),
+            (self.no_signed_zeros, FPFastMathMode::NSZ),
+            (self.allow_recip, FPFastMathMode::ALLOW_RECIP),
+            (self.fast, FPFastMathMode::FAST),
+        ] {
+            if enabled {
+                mode |= flag;
+            }
+        }
+        return mode;
+    }
+}
+
+/// Hints attached to every loop of a function
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Default, Serialize, Deserialize)]
+pub struct LoopHints {
+    /// Whether the loops should (`true`) or shouldn't (`false`) be unrolled
+    #[serde(default)]
+    pub unroll: Option<bool>,
+    /// Minimum number of iterations between two iterations that depend on each other
+    #[serde(default)]
+    pub dependency_length: Option<u32>,
+}
+
+impl LoopHints {
+    pub fn loop_control(&self) -> (LoopControl, Option<Operand>) {
+        let mut control = match self.unroll {
+            Some(true) => LoopControl::UNROLL,
+            Some(false) => LoopControl::DONT_UNROLL,
+            None => LoopControl::NONE,
+        };
+
+        let dependency_length = self.dependency_length.map(|x| {
+            control |= LoopControl::DEPENDENCY_LENGTH;
+            Operand::LiteralInt32(x)
+        });
+
+        return (control, dependency_length);
+    }
 }
 
 #[derive(Debug, Clone, Serialize, Deserialize)]
@@ -498,7 +829,33 @@ pub enum ExecutionMode {
     OriginLowerLeft,
     LocalSize(u32, u32, u32),
     LocalSizeHint(u32, u32, u32),
+    /// Same as [`LocalSize`](ExecutionMode::LocalSize), but declared with `OpExecutionModeId`. Requires SPIR-V 1.2
+    LocalSizeId(u32, u32, u32),
     DepthReplacing,
+    DepthGreater,
+    DepthLess,
+    DepthUnchanged,
+    EarlyFragmentTests,
+    InputPoints,
+    InputLines,
+    InputLinesAdjacency,
+    Triangles,
+    InputTrianglesAdjacency,
+    Quads,
+    Isolines,
+    OutputVertices(u32),
+    OutputPoints,
+    OutputLineStrip,
+    OutputTriangleStrip,
+    SpacingEqual,
+    SpacingFractionalEven,
+    SpacingFractionalOdd,
+    VertexOrderCw,
+    VertexOrderCcw,
+    PointMode,
+    OutputPrimitives(u32),
+    OutputLines,
+    OutputTriangles,
 }
 
 #[must_use]
@@ -520,6 +877,11 @@ impl<'a> ParameterBuilder<'a> {
         Ok(self)
     }
 
+    pub fn set_relaxed_precision(mut self, relaxed_precision: bool) -> Self {
+        self.inner.relaxed_precision = relaxed_precision;
+        self
+    }
+
     pub fn build(mut self) -> FunctionConfigBuilder<'a> {
         self.function.inner.params.insert(self.idx, self.inner);
         self.function
@@ -531,6 +893,9 @@ pub struct Parameter {
     #[serde(rename = "type", default)]
     pub ty: Option<Type>,
     pub kind: ParameterKind,
+    /// Decorates the parameter's variable as `RelaxedPrecision`
+    #[serde(default)]
+    pub relaxed_precision: bool,
 }
 
 impl Parameter {
@@ -538,6 +903,7 @@ impl Parameter {
         return Self {
             ty: ty.into(),
             kind,
+            relaxed_precision: false,
         };
     }
 }
@@ -553,7 +919,91 @@ pub enum ParameterKind {
         storage_class: StorageClass,
         set: u32,
         binding: u32,
+        #[serde(default)]
+        access: BufferAccess,
+        #[serde(default)]
+        buffer: BufferKind,
+        /// Layout rules the buffer's contents are checked against. Uniform buffers default to std140
+        #[serde(default)]
+        layout: Option<Layout>,
     },
+    /// Buffer of the shader's record in the shader binding table of a ray tracing pipeline
+    ShaderRecordBuffer,
+    /// Payload of the rays traced by the shader
+    RayPayload(u32),
+    /// Payload of the ray that invoked a hit or miss shader
+    IncomingRayPayload,
+}
+
+/// Kind of buffer a descriptor set parameter is bound to
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Default, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum BufferKind {
+    /// Storage buffer (SSBO)
+    #[default]
+    Storage,
+    /// Read-only uniform buffer (UBO), laid out following the std140 rules
+    Uniform,
+}
+
+/// Access qualifiers of a descriptor set buffer
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Default, Serialize, Deserialize)]
+pub struct BufferAccess {
+    /// The buffer is never written to (`NonWritable`)
+    #[serde(default)]
+    pub read_only: bool,
+    /// The buffer is never read from (`NonReadable`)
+    #[serde(default)]
+    pub write_only: bool,
+    /// The buffer isn't aliased by any other buffer (`Restrict`)
+    #[serde(default)]
+    pub restrict: bool,
+    /// The buffer may be aliased by other buffers (`Aliased`)
+    #[serde(default)]
+    pub aliased: bool,
+    /// Every access to the buffer is volatile (`Volatile`)
+    #[serde(default)]
+    pub volatile: bool,
+    /// Writes to the buffer are visible to other invocations (`Coherent`)
+    #[serde(default)]
+    pub coherent: bool,
+}
+
+impl BufferAccess {
+    pub fn decorators(&self, memory_model: MemoryModel) -> Result<Vec<VariableDecorator>> {
+        if self.read_only && self.write_only {
+            return Err(Error::msg(
+                "A buffer can't be both read-only and write-only",
+            ));
+        } else if self.restrict && self.aliased {
+            return Err(Error::msg("A buffer can't be both restrict and aliased"));
+        } else if (self.volatile || self.coherent) && memory_model == MemoryModel::Vulkan {
+            return Err(Error::msg(
+                "Volatile and coherent buffers aren't supported by the Vulkan memory model",
+            ));
+        }
+
+        let mut result = Vec::new();
+        if self.read_only {
+            result.push(VariableDecorator::NonWritable);
+        }
+        if self.write_only {
+            result.push(VariableDecorator::NonReadable);
+        }
+        if self.restrict {
+            result.push(VariableDecorator::Restrict);
+        }
+        if self.aliased {
+            result.push(VariableDecorator::Aliased);
+        }
+        if self.volatile {
+            result.push(VariableDecorator::Volatile);
+        }
+        if self.coherent {
+            result.push(VariableDecorator::Coherent);
+        }
+        return Ok(result);
+    }
 }
 
 impl ParameterKind {
@@ -563,15 +1013,118 @@ impl ParameterKind {
             ParameterKind::Input(_) => StorageClass::Input,
             ParameterKind::Output(_) => StorageClass::Output,
             ParameterKind::DescriptorSet { storage_class, .. } => *storage_class,
+            ParameterKind::ShaderRecordBuffer => StorageClass::ShaderRecordBufferKHR,
+            ParameterKind::RayPayload(_) => StorageClass::RayPayloadKHR,
+            ParameterKind::IncomingRayPayload => StorageClass::IncomingRayPayloadKHR,
         };
     }
 }
 
+/// Minimum uniform buffer range every Vulkan implementation is required to support
+pub const MAX_UNIFORM_BUFFER_SIZE: u32 = 16384;
+
+fn check_uniform_buffer(
+    ty: &Type,
+    pointer_size: PointerSize,
+    storage_class: StorageClass,
+    access: BufferAccess,
+    layout: Layout,
+) -> Result<()> {
+    if storage_class != StorageClass::Uniform {
+        return Err(Error::msg(format!(
+            "Uniform buffers must be in the Uniform storage class, found {storage_class:?}"
+        )));
+    } else if pointer_size == PointerSize::Fat {
+        return Err(Error::msg(
+            "Uniform buffers can't contain runtime arrays (fat pointers)",
+        ));
+    } else if access.write_only {
+        return Err(Error::msg("Uniform buffers can't be written to"));
+    }
+
+    let size = layout.size(ty)?;
+    if size > MAX_UNIFORM_BUFFER_SIZE {
+        return Err(Error::msg(format!(
+            "Uniform buffer of {size} bytes exceeds the maximum of {MAX_UNIFORM_BUFFER_SIZE} bytes"
+        )));
+    }
+
+    return Ok(());
+}
+
 impl Default for Parameter {
     fn default() -> Self {
         Self {
             ty: Default::default(),
             kind: Default::default(),
+            relaxed_precision: false,
+        }
+    }
+}
+
+fn declare_locals(
+    body: &FunctionBody,
+    locals: &mut Vec<Storeable>,
+    module: &ModuleBuilder,
+) -> Result<()> {
+    let mut locals_reader = body.get_locals_reader()?;
+    let declarations = (0..locals_reader.get_count())
+        .map(|_| locals_reader.read())
+        .collect::<Result<Vec<_>, _>>()?;
+
+    let local_count = declarations.iter().map(|(count, _)| *count as usize).sum();
+    let provenances = provenance::analyze(body, locals, local_count, module)?;
+
+    for (count, ty) in declarations {
+        locals.reserve(count as usize);
+
+        if matches!(ty, ValType::I32 if !module.wasm_memory64)
+            || matches!(ty, ValType::I64 if module.wasm_memory64)
+        {
+            for _ in 0..count {
+                let provenance = provenances.as_ref().map(|x| x[locals.len()]);
+                let storeable = match provenance {
+                    Some(provenance) if provenance.is_integer() => Storeable::Pointer {
+                        variable: Rc::new(Pointer::new_variable(
+                            PointerSize::Skinny,
+                            StorageClass::Function,
+                            module.isize_type(),
+                            None,
+                            [],
+                        )),
+                        integer_variable: None,
+                    },
+                    _ => Storeable::Schrodinger(Rc::new(Schrodinger {
+                        pointer: OnceCell::new(),
+                        offset: OnceCell::new(),
+                        integer: OnceCell::new(),
+                        integer_shadow: !provenance.is_some_and(Provenance::is_pointer),
+                    })),
+                };
+                locals.push(storeable);
+            }
+        } else {
+            // References start out as null
+            let init = matches!(ty, ValType::Ref(_))
+                .then(|| Value::Integer(Rc::new(Integer::new_constant_u32(NULL_REFERENCE))));
+
+            let ty = Type::from(ty);
+            for _ in 0..count {
+                let pointer = Rc::new(Pointer::new_variable(
+                    PointerSize::Skinny,
+                    StorageClass::Function,
+                    ty.clone(),
+                    init.clone(),
+                    [],
+                ));
+
+                locals.push(Storeable::Pointer {
+                    variable: pointer,
+                    integer_variable: None,
+                });
+            }
         }
     }
+
+    return Ok(());
 }
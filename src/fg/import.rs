@@ -1,5 +1,17 @@
 use super::{
+    block::{
+        mvp::{bounds_guard, translate_all, TranslationResult},
+        BlockBuilder, StackValue,
+    },
+    extended_is::{ExtendedIs, ExtendedSet},
+    function::FunctionBuilder,
     module::{GlobalVariable, ModuleBuilder},
+    values::{
+        bool::{Bool, BoolSource, Comparison, Equality},
+        float::{BinarySource, Float, FloatSource, UnarySource},
+        integer::{ConstantSource, Integer, IntegerSource},
+        Value,
+    },
     Operation,
 };
 use crate::{
@@ -10,7 +22,7 @@ use crate::{
 };
 use rspirv::spirv::{BuiltIn, StorageClass};
 use std::rc::Rc;
-use wasmparser::TypeRef;
+use wasmparser::{FuncType, Operator, TypeRef, ValType};
 
 pub enum ImportResult {
     Global(GlobalVariable),
@@ -27,12 +39,27 @@ pub fn translate_spir_global<'a>(
         // Fragment shaders
         "gl_FragDepth" => import_output(BuiltIn::FragDepth, ScalarType::F32, ty, module),
 
+        // Task and mesh shaders
+        "gl_TaskCountNV" => import_output(BuiltIn::TaskCountNV, ScalarType::I32, ty, module),
+        "gl_PrimitiveCountNV" => {
+            import_output(BuiltIn::PrimitiveCountNV, ScalarType::I32, ty, module)
+        }
+
         // Compute Shaders
         "gl_NumWorkGroups" => import_uint3_input(BuiltIn::NumWorkgroups, ty, module),
         "gl_WorkGroupID" => import_uint3_input(BuiltIn::WorkgroupId, ty, module),
         "gl_WorkGroupSize" => import_uint3_input(BuiltIn::WorkgroupSize, ty, module),
         "gl_LocalInvocationID" => import_uint3_input(BuiltIn::LocalInvocationId, ty, module),
         "gl_GlobalInvocationID" => import_uint3_input(BuiltIn::GlobalInvocationId, ty, module),
+
+        // Ray tracing
+        "trace_ray" => import_trace_ray(ty),
+
+        // Atomics
+        "atomic_counter_increment" => import_atomic_counter_increment(ty),
+
+        // Debugging
+        "debug_printf" => import_debug_printf(ty),
         _ => return Ok(None),
     };
 
@@ -60,12 +87,6 @@ fn import_output(
             module.hidden_global_variables.push(var.clone());
             ImportResult::Func(CallableFunction::callback(
                 move |block, function, module| {
-                    if let Some(ref mut entry_point) = function.entry_point {
-                        if !entry_point.interface.iter().any(|x| Rc::ptr_eq(x, &var)) {
-                            entry_point.interface.push(var.clone());
-                        }
-                    }
-
                     let value = block.stack_pop(output_type.clone(), module)?;
                     function.anchors.push(Operation::Store {
                         target: var.clone(),
@@ -97,21 +118,691 @@ fn import_uint3_input(
     return Ok(match ty {
         TypeRef::Func(_) => {
             module.hidden_global_variables.push(var.clone());
-            ImportResult::Func(CallableFunction::callback(
-                move |block, function, module| {
-                    if let Some(ref mut entry_point) = function.entry_point {
-                        if !entry_point.interface.iter().any(|x| Rc::ptr_eq(x, &var)) {
-                            entry_point.interface.push(var.clone());
-                        }
+            ImportResult::Func(CallableFunction::callback(move |block, _, module| {
+                let index = block.stack_pop(ScalarType::I32, module)?.into_integer()?;
+                let vector = var.clone().load(None, block, module)?.into_vector()?;
+                block.stack_push(vector.extract(index));
+                Ok(())
+            }))
+        }
+        _ => return Err(Error::unexpected()),
+    });
+}
+
+/// `debug_printf(format, args...)`, where `format` is the constant address of a nul-terminated string inside the
+/// data segments
+fn import_debug_printf(ty: TypeRef) -> Result<ImportResult> {
+    let TypeRef::Func(type_index) = ty else {
+        return Err(Error::unexpected());
+    };
+    let set = Rc::new(ExtendedIs::new(ExtendedSet::DebugPrintf));
+
+    return Ok(ImportResult::Func(CallableFunction::callback(
+        move |block, function, module| {
+            let ty = module
+                .func_types
+                .get(type_index as usize)
+                .ok_or_else(Error::unexpected)?
+                .clone();
+
+            let format_type = match ty.params().first() {
+                Some(x @ (ValType::I32 | ValType::I64)) if ty.results().is_empty() => *x,
+                _ => {
+                    return Err(Error::msg(
+                        "`debug_printf` must take the address of its format string first, and return nothing",
+                    ))
+                }
+            };
+
+            let mut args = Vec::with_capacity(ty.params().len() - 1);
+            for param in ty.params()[1..].iter().rev() {
+                args.push(block.stack_pop(Type::from(*param), module)?);
+            }
+            args.reverse();
+
+            let format = block
+                .stack_pop(Type::from(format_type), module)?
+                .into_integer()?;
+            let address = match format.get_constant_value()? {
+                Some(ConstantSource::Short(x)) => x as u64,
+                Some(ConstantSource::Long(x)) => x,
+                None => {
+                    return Err(Error::msg(
+                        "The format string of `debug_printf` must be at a constant address",
+                    ))
+                }
+            };
+
+            let format = module
+                .c_str_at(0, address)
+                .and_then(|x| std::str::from_utf8(x).ok())
+                .ok_or_else(|| {
+                    Error::msg(format!(
+                        "No format string of `debug_printf` found at address {address} of the data segments"
+                    ))
+                })?;
+
+            function.anchors.push(Operation::DebugPrintf {
+                set: set.clone(),
+                format: Box::from(format),
+                args: args.into_boxed_slice(),
+            });
+
+            Ok(())
+        },
+    )));
+}
+
+/// `trace_ray(set, binding, flags, cull_mask, sbt_offset, sbt_stride, miss_index, origin_x, origin_y, origin_z, tmin,
+/// direction_x, direction_y, direction_z, tmax, payload)`, where `set`, `binding` and `payload` are constants
+fn import_trace_ray(ty: TypeRef) -> Result<ImportResult> {
+    const INTEGERS: usize = 5;
+    const FLOATS: usize = 8;
+
+    let TypeRef::Func(type_index) = ty else {
+        return Err(Error::unexpected());
+    };
+
+    return Ok(ImportResult::Func(CallableFunction::callback(
+        move |block, function, module| {
+            let ty = module
+                .func_types
+                .get(type_index as usize)
+                .ok_or_else(Error::unexpected)?;
+
+            let params = [ValType::I32; 2 + INTEGERS]
+                .into_iter()
+                .chain([ValType::F32; FLOATS])
+                .chain([ValType::I32]);
+            if !ty.params().iter().copied().eq(params) || !ty.results().is_empty() {
+                return Err(Error::msg(
+                    "`trace_ray` must take the set and binding of the acceleration structure, 5 integers, 8 floats and the location of the payload, and return nothing",
+                ));
+            }
+
+            let payload = pop_constant("trace_ray", "payload location", block, module)?;
+
+            let mut args = Vec::with_capacity(INTEGERS + FLOATS);
+            for _ in 0..FLOATS {
+                args.push(block.stack_pop(ScalarType::F32, module)?);
+            }
+            for _ in 0..INTEGERS {
+                args.push(block.stack_pop(ScalarType::I32, module)?);
+            }
+            args.reverse();
+
+            let binding = pop_constant("trace_ray", "binding", block, module)?;
+            let set = pop_constant("trace_ray", "descriptor set", block, module)?;
+
+            function.anchors.push(Operation::TraceRay {
+                set,
+                binding,
+                args: args.into_boxed_slice(),
+                payload,
+            });
+            Ok(())
+        },
+    )));
+}
+
+/// `atomic_counter_increment(counter) -> i32`, returning the value of the constant counter before the increment
+fn import_atomic_counter_increment(ty: TypeRef) -> Result<ImportResult> {
+    let TypeRef::Func(type_index) = ty else {
+        return Err(Error::unexpected());
+    };
+
+    return Ok(ImportResult::Func(CallableFunction::callback(
+        move |block, function, module| {
+            let ty = module
+                .func_types
+                .get(type_index as usize)
+                .ok_or_else(Error::unexpected)?;
+
+            if ty.params() != [ValType::I32] || ty.results() != [ValType::I32] {
+                return Err(Error::msg(
+                    "`atomic_counter_increment` must take the index of the counter, and return an integer",
+                ));
+            }
+
+            let counter = pop_constant("atomic_counter_increment", "counter", block, module)?;
+            let counter = module.atomic_counter_variable(counter).ok_or_else(|| {
+                Error::msg(format!(
+                    "Atomic counter {counter} isn't bound to any buffer"
+                ))
+            })?;
+
+            // The increment must happen even if its result is never used
+            let value = Rc::new(Integer::new(IntegerSource::AtomicIncrement { counter }));
+            function
+                .anchors
+                .push(Operation::Value(value.clone().into()));
+            block.stack_push(value);
+            Ok(())
+        },
+    )));
+}
+
+fn pop_constant(
+    intrinsic: &str,
+    name: &str,
+    block: &mut BlockBuilder,
+    module: &mut ModuleBuilder,
+) -> Result<u32> {
+    let value = block.stack_pop(ScalarType::I32, module)?.into_integer()?;
+    return match value.get_constant_value()? {
+        Some(ConstantSource::Short(x)) => Ok(x),
+        _ => Err(Error::msg(format!(
+            "The {name} of `{intrinsic}` must be a constant"
+        ))),
+    };
+}
+
+/// Returns the native replacement of a C library function, if it's a known one
+pub fn translate_intrinsic(
+    name: &str,
+    ty: &FuncType,
+    module: &ModuleBuilder,
+) -> Result<Option<CallableFunction>> {
+    if let Some(f) = translate_memory_intrinsic(name, ty)? {
+        return Ok(Some(f));
+    }
+    return translate_math_intrinsic(name, ty, module);
+}
+
+/// Replaces `memcpy`, `memmove` and `memset` with loops over the buffers they access. `memmove` copies backwards
+/// when the destination is above the source
+pub fn translate_memory_intrinsic(name: &str, ty: &FuncType) -> Result<Option<CallableFunction>> {
+    let (copy, overlaps) = match name {
+        "memcpy" => (true, false),
+        "memmove" => (true, true),
+        "memset" => (false, false),
+        _ => return Ok(None),
+    };
+
+    let address = ty.params().first().copied();
+    if ty.params().len() != 3
+        || !matches!(address, Some(ValType::I32 | ValType::I64))
+        || ty.results().iter().any(|x| Some(*x) != address)
+        || ty.results().len() > 1
+    {
+        return Err(Error::msg(format!(
+            "`{name}` must take a destination address, a {} and a length, returning nothing or the destination",
+            match copy {
+                true => "source address",
+                false => "byte",
+            }
+        )));
+    }
+
+    let name = Box::<str>::from(name);
+    let returns_destination = !ty.results().is_empty();
+
+    return Ok(Some(CallableFunction::callback(
+        move |block, function, module| {
+            let len = block
+                .stack_pop(module.isize_type(), module)?
+                .into_integer()?;
+            let (src, value) = match copy {
+                true => (Some(block.stack_pop_any()?), None),
+                false => (
+                    None,
+                    Some(block.stack_pop(ScalarType::I32, module)?.into_integer()?),
+                ),
+            };
+            let dst = block.stack_pop_any()?;
+
+            let element = [Some(&dst), src.as_ref()]
+                .into_iter()
+                .flatten()
+                .find_map(|x| match x {
+                    StackValue::Value(Value::Pointer(pointer))
+                    | StackValue::Schrodinger {
+                        pointer_variable: pointer,
+                        ..
+                    } => match pointer.pointee {
+                        Type::Scalar(ty) if ty.byte_size().is_some() => Some(ty),
+                        _ => None,
+                    },
+                    _ => None,
+                })
+                .unwrap_or(ScalarType::I32);
+
+            let size = element.byte_size().ok_or_else(Error::unexpected)?;
+            let size_constant = Rc::new(Integer::new_constant_usize(size, module));
+            match len.get_constant_value()? {
+                Some(ConstantSource::Short(x)) if x % size != 0 => {
+                    return Err(Error::msg(format!(
+                        "`{name}` of {x} bytes isn't a multiple of its {size}-byte elements"
+                    )))
+                }
+                Some(ConstantSource::Long(x)) if x % size as u64 != 0 => {
+                    return Err(Error::msg(format!(
+                        "`{name}` of {x} bytes isn't a multiple of its {size}-byte elements"
+                    )))
+                }
+                None if size > 1 => {
+                    // Trailing bytes that don't fill an element can't be accessed, so they trap instead
+                    let tail = len.clone().u_rem(size_constant.clone(), module)?;
+                    let trap = function.new_label();
+                    let next = function.new_label();
+                    function.anchors.push(Operation::BranchConditional {
+                        condition: Rc::new(Bool::new(BoolSource::IntEquality {
+                            kind: Equality::Ne,
+                            op1: tail,
+                            op2: Rc::new(Integer::new_constant_usize(0, module)),
+                        })),
+                        true_label: trap,
+                        false_label: next,
+                    });
+                    function.anchors.push(Operation::Label(trap));
+                    function
+                        .anchors
+                        .push(Operation::Unreachable { panic: None });
+                    function.anchors.push(Operation::Label(next));
+                }
+                _ => {}
+            }
+
+            let backwards = match (overlaps, &src) {
+                (true, Some(src)) => move_backwards(&name, &dst, src, module)?,
+                _ => None,
+            };
+
+            let dst_pointer = intrinsic_pointer(&name, dst.clone(), element, module)?;
+            let src_pointer = match src {
+                Some(src) => Some(intrinsic_pointer(&name, src, element, module)?),
+                None => None,
+            };
+            let value = match value {
+                Some(value) => Some(fill_value(&name, value, element, module)?),
+                None => None,
+            };
+
+            let count = len.u_div(size_constant, false, module)?;
+
+            element_loop(
+                count,
+                size,
+                backwards,
+                block,
+                function,
+                module,
+                |offset, block, function, module| {
+                    bounds_guard(&dst_pointer, function, module);
+                    if let Some(src) = &src_pointer {
+                        bounds_guard(src, function, module);
                     }
 
-                    let index = block.stack_pop(ScalarType::I32, module)?.into_integer()?;
-                    let vector = var.clone().load(None, block, module)?.into_vector()?;
-                    block.stack_push(vector.extract(index));
+                    let target = dst_pointer
+                        .clone()
+                        .access(offset.clone(), module)
+                        .map(Rc::new)?;
+                    let op = match (&src_pointer, &value) {
+                        (Some(src), _) => Operation::Copy {
+                            src: src.clone().access(offset, module).map(Rc::new)?,
+                            src_log2_alignment: None,
+                            dst: target,
+                            dst_log2_alignment: None,
+                        },
+                        (None, Some(value)) => target.store(value.clone(), None, block, module)?,
+                        (None, None) => return Err(Error::unexpected()),
+                    };
+                    function.anchors.push(op);
                     Ok(())
                 },
-            ))
+            )?;
+
+            if returns_destination {
+                block.stack.push(dst);
+            }
+            Ok(())
+        },
+    )));
+}
+
+/// How a math function is computed
+enum MathLowering {
+    /// WebAssembly operators applied to the arguments
+    Operators(Vec<Operator<'static>>),
+    Unary(UnarySource),
+    Binary(BinarySource),
+}
+
+/// Replaces soft-float routines and libm functions with the native instructions computing them. Transcendental
+/// functions are kept unless an extended instruction set of the platform computes them
+pub fn translate_math_intrinsic(
+    name: &str,
+    ty: &FuncType,
+    module: &ModuleBuilder,
+) -> Result<Option<CallableFunction>> {
+    use MathLowering::*;
+    use ValType::{F32, F64, I32, I64};
+
+    let (params, result, lowering): (&[ValType], ValType, MathLowering) = match name {
+        // Arithmetic
+        "__addsf3" => (&[F32, F32], F32, Operators(vec![Operator::F32Add])),
+        "__adddf3" => (&[F64, F64], F64, Operators(vec![Operator::F64Add])),
+        "__subsf3" => (&[F32, F32], F32, Operators(vec![Operator::F32Sub])),
+        "__subdf3" => (&[F64, F64], F64, Operators(vec![Operator::F64Sub])),
+        "__mulsf3" => (&[F32, F32], F32, Operators(vec![Operator::F32Mul])),
+        "__muldf3" => (&[F64, F64], F64, Operators(vec![Operator::F64Mul])),
+        "__divsf3" => (&[F32, F32], F32, Operators(vec![Operator::F32Div])),
+        "__divdf3" => (&[F64, F64], F64, Operators(vec![Operator::F64Div])),
+        "__negsf2" => (&[F32], F32, Operators(vec![Operator::F32Neg])),
+        "__negdf2" => (&[F64], F64, Operators(vec![Operator::F64Neg])),
+
+        // Conversions
+        "__extendsfdf2" => (&[F32], F64, Operators(vec![Operator::F64PromoteF32])),
+        "__truncdfsf2" => (&[F64], F32, Operators(vec![Operator::F32DemoteF64])),
+        "__floatsisf" => (&[I32], F32, Operators(vec![Operator::F32ConvertI32S])),
+        "__floatunsisf" => (&[I32], F32, Operators(vec![Operator::F32ConvertI32U])),
+        "__floatdisf" => (&[I64], F32, Operators(vec![Operator::F32ConvertI64S])),
+        "__floatundisf" => (&[I64], F32, Operators(vec![Operator::F32ConvertI64U])),
+        "__floatsidf" => (&[I32], F64, Operators(vec![Operator::F64ConvertI32S])),
+        "__floatunsidf" => (&[I32], F64, Operators(vec![Operator::F64ConvertI32U])),
+        "__floatdidf" => (&[I64], F64, Operators(vec![Operator::F64ConvertI64S])),
+        "__floatundidf" => (&[I64], F64, Operators(vec![Operator::F64ConvertI64U])),
+        "__fixsfsi" => (&[F32], I32, Operators(vec![Operator::I32TruncF32S])),
+        "__fixunssfsi" => (&[F32], I32, Operators(vec![Operator::I32TruncF32U])),
+        "__fixsfdi" => (&[F32], I64, Operators(vec![Operator::I64TruncF32S])),
+        "__fixunssfdi" => (&[F32], I64, Operators(vec![Operator::I64TruncF32U])),
+        "__fixdfsi" => (&[F64], I32, Operators(vec![Operator::I32TruncF64S])),
+        "__fixunsdfsi" => (&[F64], I32, Operators(vec![Operator::I32TruncF64U])),
+        "__fixdfdi" => (&[F64], I64, Operators(vec![Operator::I64TruncF64S])),
+        "__fixunsdfdi" => (&[F64], I64, Operators(vec![Operator::I64TruncF64U])),
+
+        // Comparisons only define the sign of their result, which is zero for equal operands
+        "__eqsf2" | "__nesf2" => (&[F32, F32], I32, Operators(vec![Operator::F32Ne])),
+        "__eqdf2" | "__nedf2" => (&[F64, F64], I32, Operators(vec![Operator::F64Ne])),
+        "__ltsf2" => (&[F32, F32], I32, less_than(Operator::F32Lt)),
+        "__ltdf2" => (&[F64, F64], I32, less_than(Operator::F64Lt)),
+        "__lesf2" => (&[F32, F32], I32, less_equal(Operator::F32Le)),
+        "__ledf2" => (&[F64, F64], I32, less_equal(Operator::F64Le)),
+        "__gtsf2" => (&[F32, F32], I32, Operators(vec![Operator::F32Gt])),
+        "__gtdf2" => (&[F64, F64], I32, Operators(vec![Operator::F64Gt])),
+        "__gesf2" => (&[F32, F32], I32, greater_equal(Operator::F32Ge)),
+        "__gedf2" => (&[F64, F64], I32, greater_equal(Operator::F64Ge)),
+
+        // libm
+        "sqrtf" => (&[F32], F32, Operators(vec![Operator::F32Sqrt])),
+        "sqrt" => (&[F64], F64, Operators(vec![Operator::F64Sqrt])),
+        "fabsf" => (&[F32], F32, Operators(vec![Operator::F32Abs])),
+        "fabs" => (&[F64], F64, Operators(vec![Operator::F64Abs])),
+        "ceilf" => (&[F32], F32, Operators(vec![Operator::F32Ceil])),
+        "ceil" => (&[F64], F64, Operators(vec![Operator::F64Ceil])),
+        "floorf" => (&[F32], F32, Operators(vec![Operator::F32Floor])),
+        "floor" => (&[F64], F64, Operators(vec![Operator::F64Floor])),
+        "truncf" => (&[F32], F32, Operators(vec![Operator::F32Trunc])),
+        "trunc" => (&[F64], F64, Operators(vec![Operator::F64Trunc])),
+        "rintf" | "nearbyintf" => (&[F32], F32, Operators(vec![Operator::F32Nearest])),
+        "rint" | "nearbyint" => (&[F64], F64, Operators(vec![Operator::F64Nearest])),
+        "copysignf" => (&[F32, F32], F32, Operators(vec![Operator::F32Copysign])),
+        "copysign" => (&[F64, F64], F64, Operators(vec![Operator::F64Copysign])),
+
+        "sinf" => (&[F32], F32, Unary(UnarySource::Sin)),
+        "sin" => (&[F64], F64, Unary(UnarySource::Sin)),
+        "cosf" => (&[F32], F32, Unary(UnarySource::Cos)),
+        "cos" => (&[F64], F64, Unary(UnarySource::Cos)),
+        "tanf" => (&[F32], F32, Unary(UnarySource::Tan)),
+        "tan" => (&[F64], F64, Unary(UnarySource::Tan)),
+        "asinf" => (&[F32], F32, Unary(UnarySource::Asin)),
+        "asin" => (&[F64], F64, Unary(UnarySource::Asin)),
+        "acosf" => (&[F32], F32, Unary(UnarySource::Acos)),
+        "acos" => (&[F64], F64, Unary(UnarySource::Acos)),
+        "atanf" => (&[F32], F32, Unary(UnarySource::Atan)),
+        "atan" => (&[F64], F64, Unary(UnarySource::Atan)),
+        "sinhf" => (&[F32], F32, Unary(UnarySource::Sinh)),
+        "sinh" => (&[F64], F64, Unary(UnarySource::Sinh)),
+        "coshf" => (&[F32], F32, Unary(UnarySource::Cosh)),
+        "cosh" => (&[F64], F64, Unary(UnarySource::Cosh)),
+        "tanhf" => (&[F32], F32, Unary(UnarySource::Tanh)),
+        "tanh" => (&[F64], F64, Unary(UnarySource::Tanh)),
+        "expf" => (&[F32], F32, Unary(UnarySource::Exp)),
+        "exp" => (&[F64], F64, Unary(UnarySource::Exp)),
+        "exp2f" => (&[F32], F32, Unary(UnarySource::Exp2)),
+        "exp2" => (&[F64], F64, Unary(UnarySource::Exp2)),
+        "logf" => (&[F32], F32, Unary(UnarySource::Log)),
+        "log" => (&[F64], F64, Unary(UnarySource::Log)),
+        "log2f" => (&[F32], F32, Unary(UnarySource::Log2)),
+        "log2" => (&[F64], F64, Unary(UnarySource::Log2)),
+        "powf" => (&[F32, F32], F32, Binary(BinarySource::Pow)),
+        "pow" => (&[F64, F64], F64, Binary(BinarySource::Pow)),
+        "atan2f" => (&[F32, F32], F32, Binary(BinarySource::Atan2)),
+        "atan2" => (&[F64, F64], F64, Binary(BinarySource::Atan2)),
+        _ => return Ok(None),
+    };
+
+    // Names like `log` may as well be the module's own functions
+    if ty.params() != params || ty.results() != [result] {
+        return Ok(None);
+    }
+
+    // Only GLSL450 (for 32-bit floats) and OpenCL compute transcendental functions
+    if matches!(lowering, Unary(_) | Binary(_))
+        && !module.extended_is.iter().any(|x| match x.kind {
+            ExtendedSet::GLSL450 => result == F32,
+            ExtendedSet::OpenCL => true,
+            _ => false,
+        })
+    {
+        return Ok(None);
+    }
+
+    let result = Type::from(result);
+    return Ok(Some(CallableFunction::callback(
+        move |block, function, module| {
+            match &lowering {
+                Operators(ops) => {
+                    for op in ops.iter() {
+                        if translate_all(op, block, function, module)? != TranslationResult::Found {
+                            return Err(Error::unexpected());
+                        }
+                    }
+                }
+                Unary(source) => {
+                    let op1 = block.stack_pop(result.clone(), module)?.into_float()?;
+                    block.stack_push(Float::new(FloatSource::Unary {
+                        source: *source,
+                        op1,
+                    }));
+                }
+                Binary(source) => {
+                    let op2 = block.stack_pop(result.clone(), module)?.into_float()?;
+                    let op1 = block.stack_pop(result.clone(), module)?.into_float()?;
+                    block.stack_push(Float::new(FloatSource::Binary {
+                        source: *source,
+                        op1,
+                        op2,
+                    }));
+                }
+            }
+            Ok(())
+        },
+    )));
+}
+
+fn less_than(comparison: Operator<'static>) -> MathLowering {
+    return MathLowering::Operators(vec![
+        comparison,
+        Operator::I32Const { value: -1 },
+        Operator::I32Mul,
+    ]);
+}
+
+fn less_equal(comparison: Operator<'static>) -> MathLowering {
+    return MathLowering::Operators(vec![
+        comparison,
+        Operator::I32Const { value: 1 },
+        Operator::I32Xor,
+    ]);
+}
+
+fn greater_equal(comparison: Operator<'static>) -> MathLowering {
+    return MathLowering::Operators(vec![
+        comparison,
+        Operator::I32Const { value: -1 },
+        Operator::I32Add,
+    ]);
+}
+
+fn intrinsic_pointer(
+    intrinsic: &str,
+    address: StackValue,
+    element: ScalarType,
+    module: &mut ModuleBuilder,
+) -> Result<Rc<Pointer>> {
+    return match address {
+        StackValue::Value(Value::Integer(address)) => {
+            let memory = module.memory_variable(0, element).ok_or_else(|| {
+                Error::msg(format!(
+                    "`{intrinsic}` into integer addresses needs the memory to be bound to a buffer"
+                ))
+            })?;
+            memory.access(address, module).map(Rc::new)
         }
-        _ => return Err(Error::unexpected()),
+        address => address.to_pointer(PointerSize::Skinny, element, module),
+    };
+}
+
+fn fill_value(
+    intrinsic: &str,
+    value: Rc<Integer>,
+    element: ScalarType,
+    module: &ModuleBuilder,
+) -> Result<Value> {
+    if let Some(ConstantSource::Short(x)) = value.get_constant_value()? {
+        let bytes = u64::from_ne_bytes([x as u8; 8]);
+        return Ok(match element {
+            ScalarType::I32 => Integer::new_constant_u32(bytes as u32).into(),
+            ScalarType::I64 => Integer::new_constant_u64(bytes).into(),
+            ScalarType::F32 => Float::new_constant_f32(f32::from_bits(bytes as u32)).into(),
+            ScalarType::F64 => Float::new_constant_f64(f64::from_bits(bytes)).into(),
+            _ => return Err(Error::unexpected()),
+        });
+    }
+
+    return match element {
+        ScalarType::I32 => Ok(value
+            .and(Rc::new(Integer::new_constant_u32(0xff)), module)?
+            .mul(Rc::new(Integer::new_constant_u32(0x01010101)), module)?
+            .into()),
+        _ => Err(Error::msg(format!(
+            "`{intrinsic}` into {element:?} elements must set a constant byte"
+        ))),
+    };
+}
+
+fn move_backwards(
+    intrinsic: &str,
+    dst: &StackValue,
+    src: &StackValue,
+    module: &ModuleBuilder,
+) -> Result<Option<Rc<Bool>>> {
+    let address = |value: &StackValue| match value {
+        StackValue::Value(Value::Integer(address)) => Some(address.clone()),
+        StackValue::Schrodinger { loaded_integer, .. } => Some(loaded_integer.clone()),
+        StackValue::Value(Value::Pointer(pointer)) if pointer.is_fat() => Some(
+            pointer
+                .byte_offset()
+                .unwrap_or_else(|| Rc::new(Integer::new_constant_usize(0, module))),
+        ),
+        _ => None,
+    };
+
+    let (Some(dst), Some(src)) = (address(dst), address(src)) else {
+        return Err(Error::msg(format!(
+            "`{intrinsic}` needs the offsets of its addresses, to tell whether they overlap"
+        )));
+    };
+
+    let constant = |x: ConstantSource| match x {
+        ConstantSource::Short(x) => x as u64,
+        ConstantSource::Long(x) => x,
+    };
+    // Copying from the last element to the first is only required when the destination is above the source
+    return Ok(
+        match (dst.get_constant_value()?, src.get_constant_value()?) {
+            (Some(dst), Some(src)) if constant(dst) <= constant(src) => None,
+            (Some(_), Some(_)) => Some(Rc::new(Bool::new(BoolSource::Constant(true)))),
+            _ => Some(Rc::new(Bool::new(BoolSource::IntComparison {
+                kind: Comparison::Gt,
+                signed: false,
+                op1: dst,
+                op2: src,
+            }))),
+        },
+    );
+}
+
+fn element_loop(
+    count: Rc<Integer>,
+    size: u32,
+    backwards: Option<Rc<Bool>>,
+    block: &mut BlockBuilder,
+    function: &mut FunctionBuilder,
+    module: &mut ModuleBuilder,
+    f: impl FnOnce(
+        Rc<Integer>,
+        &mut BlockBuilder,
+        &mut FunctionBuilder,
+        &mut ModuleBuilder,
+    ) -> Result<()>,
+) -> Result<()> {
+    let counter = Rc::new(Pointer::new_variable(
+        PointerSize::Skinny,
+        StorageClass::Function,
+        module.isize_type(),
+        None,
+        [],
+    ));
+    let header = function.new_label();
+    let body = function.new_label();
+    let merge = function.new_label();
+
+    let zero = Integer::new_constant_usize(0, module);
+    function
+        .anchors
+        .push(counter.clone().store(zero, None, block, module)?);
+    function.anchors.push(Operation::Branch { label: header });
+    function.anchors.push(Operation::Label(header));
+
+    let index = counter.clone().load(None, block, module)?.into_integer()?;
+    function.anchors.push(Operation::BranchConditional {
+        condition: Rc::new(Bool::new(BoolSource::IntComparison {
+            kind: Comparison::Lt,
+            signed: false,
+            op1: index.clone(),
+            op2: count.clone(),
+        })),
+        true_label: body,
+        false_label: merge,
     });
+    function.anchors.push(Operation::Label(body));
+
+    let element = match backwards {
+        Some(backwards) => {
+            let last = count.sub(Rc::new(Integer::new_constant_usize(1, module)), module)?;
+            let mirrored = Rc::new(Rc::new(last).sub(index.clone(), module)?);
+            match backwards.source {
+                BoolSource::Constant(true) => mirrored,
+                _ => Rc::new(Integer::new(IntegerSource::Select {
+                    selector: backwards,
+                    true_value: mirrored,
+                    false_value: index.clone(),
+                })),
+            }
+        }
+        None => index.clone(),
+    };
+
+    let offset = element.mul(Rc::new(Integer::new_constant_usize(size, module)), module)?;
+    f(offset, block, function, module)?;
+
+    let next = index.add(Rc::new(Integer::new_constant_usize(1, module)), module)?;
+    function
+        .anchors
+        .push(counter.store(next, None, block, module)?);
+    function.anchors.push(Operation::Branch { label: header });
+    function.anchors.push(Operation::Label(merge));
+    return Ok(());
 }
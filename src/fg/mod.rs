@@ -1,18 +1,29 @@
-use self::values::{bool::Bool, pointer::Pointer, Value};
-use crate::r#type::Type;
+use self::{
+    extended_is::ExtendedIs,
+    values::{bool::Bool, pointer::Pointer, Value},
+};
+use crate::{diagnostics::PanicMessage, r#type::Type};
 use std::{cell::Cell, rc::Rc};
 
+pub use arena::{Arena, Handle};
+
+pub mod arena;
 pub mod block;
+mod dot;
 pub mod extended_is;
 pub mod function;
 pub mod import;
 pub mod module;
+pub mod provenance;
+pub mod shadow_stack;
+pub mod structurize;
 pub mod values;
+pub mod visit;
 
 #[derive(Debug, PartialEq)]
 pub enum MergeBlock {
     This,
-    Label(Rc<Label>),
+    Label(Handle<Label>),
 }
 
 #[derive(Debug, PartialEq, Default)]
@@ -29,14 +40,14 @@ pub enum End {
 #[derive(Debug, Clone)]
 pub enum Operation {
     Value(Value),
-    Label(Rc<Label>),
+    Label(Handle<Label>),
     Branch {
-        label: Rc<Label>,
+        label: Handle<Label>,
     },
     BranchConditional {
         condition: Rc<Bool>,
-        true_label: Rc<Label>,
-        false_label: Rc<Label>,
+        true_label: Handle<Label>,
+        false_label: Handle<Label>,
     },
     Store {
         target: Rc<Pointer>,
@@ -53,8 +64,25 @@ pub enum Operation {
         function_id: Rc<Cell<Option<rspirv::spirv::Word>>>,
         args: Box<[Value]>,
     },
+    DebugPrintf {
+        set: Rc<ExtendedIs>,
+        format: Box<str>,
+        args: Box<[Value]>,
+    },
+    /// `OpTraceRayKHR` into the acceleration structure bound to the descriptor set and binding
+    TraceRay {
+        set: u32,
+        binding: u32,
+        /// Operands of `OpTraceRayKHR`, excluding the acceleration structure and payload
+        args: Box<[Value]>,
+        /// Location of the ray payload
+        payload: u32,
+    },
     Nop,
-    Unreachable,
+    Unreachable {
+        /// Rust panic that leads to the trap, if known
+        panic: Option<Rc<PanicMessage>>,
+    },
     Return {
         value: Option<Value>,
     },
@@ -65,7 +93,7 @@ impl Operation {
         match (self, other) {
             (Operation::Value(x), Operation::Value(y)) => x.ptr_eq(y),
             (Operation::Label(x), Operation::Label(y))
-            | (Operation::Branch { label: x }, Operation::Branch { label: y }) => Rc::ptr_eq(x, y),
+            | (Operation::Branch { label: x }, Operation::Branch { label: y }) => x == y,
             (
                 Operation::BranchConditional {
                     condition,
@@ -79,16 +107,40 @@ impl Operation {
                 },
             ) => {
                 Rc::ptr_eq(condition, other_condition)
-                    && Rc::ptr_eq(true_label, other_true_label)
-                    && Rc::ptr_eq(false_label, other_false_label)
+                    && true_label == other_true_label
+                    && false_label == other_false_label
             }
             // TODO are ops without values equal?
             _ => false,
         }
     }
 
+    pub fn name(&self) -> &'static str {
+        return match self {
+            Operation::Value(Value::Integer(_)) => "Integer",
+            Operation::Value(Value::Float(_)) => "Float",
+            Operation::Value(Value::Pointer(_)) => "Pointer",
+            Operation::Value(Value::Vector(_)) => "Vector",
+            Operation::Value(Value::Bool(_)) => "Bool",
+            Operation::Label(_) => "Label",
+            Operation::Branch { .. } => "Branch",
+            Operation::BranchConditional { .. } => "BranchConditional",
+            Operation::Store { .. } => "Store",
+            Operation::Copy { .. } => "Copy",
+            Operation::FunctionCall { .. } => "FunctionCall",
+            Operation::DebugPrintf { .. } => "DebugPrintf",
+            Operation::TraceRay { .. } => "TraceRay",
+            Operation::Nop => "Nop",
+            Operation::Unreachable { .. } => "Unreachable",
+            Operation::Return { .. } => "Return",
+        };
+    }
+
     pub fn is_function_terminating(&self) -> bool {
-        return matches!(self, Operation::Return { .. } | Operation::Unreachable);
+        return matches!(
+            self,
+            Operation::Return { .. } | Operation::Unreachable { .. }
+        );
     }
 
     pub fn is_branch_instruction(&self) -> bool {
@@ -103,16 +155,16 @@ impl Operation {
     }
 }
 
-impl PartialEq<Rc<Label>> for Operation {
-    fn eq(&self, other: &Rc<Label>) -> bool {
+impl PartialEq<Handle<Label>> for Operation {
+    fn eq(&self, other: &Handle<Label>) -> bool {
         match self {
-            Operation::Label(x) => Rc::ptr_eq(x, other),
+            Operation::Label(x) => x == other,
             _ => false,
         }
     }
 }
 
-impl PartialEq<Operation> for Rc<Label> {
+impl PartialEq<Operation> for Handle<Label> {
     #[inline]
     fn eq(&self, other: &Operation) -> bool {
         other == self
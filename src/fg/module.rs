@@ -1,22 +1,41 @@
 use super::{
     block::{mvp::translate_constants, translate_block, BlockBuilder, BlockReader},
     extended_is::ExtendedIs,
-    function::FunctionBuilder,
-    import::{translate_spir_global, ImportResult},
-    values::{integer::IntegerKind, pointer::Pointer, Value},
+    function::{BufferKind, FunctionBuilder, ParameterKind},
+    import::{translate_intrinsic, translate_spir_global, ImportResult},
+    values::{
+        integer::IntegerKind,
+        pointer::{Pointer, PointerKind, PointerSource},
+        Value,
+    },
     End,
 };
 use crate::{
-    config::{CapabilityModel, Config, MemoryGrowErrorKind},
+    config::{
+        AtomicCounter, CapabilityModel, Config, DataSegmentMode, Float64Mode, GlobalConfig,
+        MemoryBinding, MemoryGrowErrorKind, ProfilingBuffer, SelectionHint, TrapMode, Verbosity,
+        WasmFeatures,
+    },
+    decorator::VariableDecorator,
+    diagnostics::PanicMessage,
     error::{Error, Result},
-    r#type::{PointerSize, ScalarType, Type},
+    features,
+    r#type::{Layout, PointerSize, ScalarType, Type},
     version::{TargetPlatform, Version},
     Str,
 };
-use rspirv::spirv::{AddressingModel, MemoryModel, StorageClass};
-use std::{borrow::Cow, cell::Cell, collections::VecDeque, rc::Rc};
-use tracing::warn;
-use wasmparser::{ExternalKind, FuncType, Payload, Validator};
+use rspirv::spirv::{AddressingModel, MemoryModel, StorageClass, Word};
+use std::{
+    borrow::Cow,
+    cell::Cell,
+    collections::{BTreeMap, HashMap, HashSet, VecDeque},
+    ops::Range,
+    rc::Rc,
+};
+use vector_mapp::vec::VecMap;
+use wasmparser::{
+    types::Types, DataKind, Export, ExternalKind, FuncType, FunctionBody, Operator, Payload,
+};
 
 #[derive(Debug, Clone)]
 pub enum GlobalVariable {
@@ -24,6 +43,14 @@ pub enum GlobalVariable {
     Constant(Value),
 }
 
+/// Contents of an active data segment, placed at a constant offset of its memory
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct DataSegment<'a> {
+    pub memory: u32,
+    pub offset: u64,
+    pub bytes: &'a [u8],
+}
+
 #[derive(Clone)]
 pub enum CallableFunction {
     Callback(
@@ -63,22 +90,111 @@ pub struct ModuleBuilder<'a> {
     pub addressing_model: AddressingModel,
     pub memory_model: MemoryModel,
     pub memory_grow_error: MemoryGrowErrorKind,
+    pub trap_mode: TrapMode,
+    /// Storage buffer written to by traps when using [`TrapMode::Flag`]
+    pub trap_flag: Option<Rc<Pointer>>,
+    pub bounds_checks: bool,
+    pub division_traps: bool,
+    pub float64: Float64Mode,
+    pub max_value_depth: Option<u32>,
+    pub selection_control: Option<SelectionHint>,
+    pub sort_globals: bool,
+    pub phi_merges: bool,
+    pub profiling: Option<ProfilingBuffer>,
+    pub verbosity: Verbosity,
+    /// Rust panics found before traps, in the order of their trap codes
+    pub panics: Vec<Rc<PanicMessage>>,
+    /// Ray payload parameters of the entry points, by location
+    pub ray_payloads: Vec<(u32, Rc<Pointer>)>,
+    /// Proposals the WebAssembly module uses
+    pub wasm_features: WasmFeatures,
+    pub memories: VecMap<u32, MemoryBinding>,
+    /// Variables of the memories' storage buffers, one for every type they're accessed as
+    pub memory_variables: Vec<(u32, Type, Rc<Pointer>)>,
+    pub atomic_counters: VecMap<u32, AtomicCounter>,
+    pub entry_point_names: BTreeMap<Box<str>, Box<str>>,
+    /// Variables of the atomic counters' storage buffers, by counter index
+    pub atomic_counter_variables: Vec<(u32, Rc<Pointer>)>,
     pub wasm_memory64: bool,
+    pub data_mode: DataSegmentMode,
+    /// Active data segments, in the order they initialize their memory
+    pub data_segments: Vec<DataSegment<'a>>,
+    /// Contents of the passive data segments, by data index
+    pub passive_data: HashMap<u32, &'a [u8]>,
+    /// Variables of the data segments' storage buffer, one for every type it's accessed as
+    pub data_variables: Vec<(Type, Rc<Pointer>)>,
     pub functions: Box<[CallableFunction]>,
+    /// Whether imports and exports are declared with linkage attributes
+    pub linkage: bool,
+    /// Unresolved function imports, declared with an `Import` linkage
+    pub imported_functions: Vec<(&'a str, Rc<Cell<Option<Word>>>, FuncType)>,
+    /// Signatures of the type section, by type index
+    pub func_types: Box<[FuncType]>,
+    /// Index of the module's start function, called at the beginning of every entry point
+    pub start_function: Option<u32>,
     pub global_variables: Box<[GlobalVariable]>,
     pub hidden_global_variables: Vec<Rc<Pointer>>,
     pub built_functions: Box<[FunctionBuilder<'a>]>,
+    pub exports: Box<[Export<'a>]>,
+    /// Bodies of the functions whose calls are inlined, by function index
+    pub inline_candidates: HashMap<u32, FunctionBody<'a>>,
+    /// Functions currently being inlined, used to avoid inlining recursive calls
+    pub inlining: Vec<u32>,
 }
 
 impl<'a> ModuleBuilder<'a> {
     pub fn new(config: Config, bytes: &'a [u8]) -> Result<Self> {
-        let mut validator = Validator::new_with_features(config.features.into());
-        let types = validator.validate_all(&bytes)?;
+        let types = features::validate(config.features, bytes)?;
+        return Self::from_validated(config, bytes, &types);
+    }
 
-        let wasm_memory64 = match types.memory_count() {
-            0 => false,
-            _ => types.memory_at(0).memory64,
-        };
+    /// Builds the module, resolving its imports with the exports of the library modules. Library functions are
+    /// translated as if they were defined by the module
+    pub fn with_libraries(
+        config: Config,
+        bytes: &'a [u8],
+        libraries: &[(&'a str, &'a [u8])],
+    ) -> Result<Self> {
+        let libraries = libraries
+            .iter()
+            .map(|(name, bytes)| {
+                let config = Config {
+                    functions: Default::default(),
+                    ..config.clone()
+                };
+                Ok((*name, Self::new(config, bytes)?))
+            })
+            .collect::<Result<Vec<_>>>()?;
+
+        let types = features::validate(config.features, bytes)?;
+        return Self::build(config, bytes, &types, libraries);
+    }
+
+    pub(crate) fn from_validated(config: Config, bytes: &'a [u8], types: &Types) -> Result<Self> {
+        return Self::build(config, bytes, types, Vec::new());
+    }
+
+    /// Creates a module without any functions or globals, for frontends other than WebAssembly
+    pub fn empty(config: Config) -> Result<Self> {
+        return Self::with_config(&config, false);
+    }
+
+    /// Adds a function to the module, returning the id it will be translated with
+    pub fn add_function(&mut self, function: FunctionBuilder<'a>) -> Rc<Cell<Option<Word>>> {
+        let function_id = function.function_id.clone();
+        let mut built_functions = std::mem::take(&mut self.built_functions).into_vec();
+        built_functions.push(function);
+        self.built_functions = built_functions.into_boxed_slice();
+        return function_id;
+    }
+
+    /// Adds a global variable to the module, even if it isn't used by any function
+    pub fn add_global(&mut self, variable: Rc<Pointer>) {
+        self.hidden_global_variables.push(variable);
+    }
+
+    fn with_config(config: &Config, wasm_memory64: bool) -> Result<Self> {
+        config.platform.check()?;
         let addressing_model = match (config.addressing_model, wasm_memory64) {
             (crate::config::AddressingModel::Logical, _) => AddressingModel::Logical,
             (crate::config::AddressingModel::Physical, false) => AddressingModel::Physical32,
@@ -86,7 +202,11 @@ impl<'a> ModuleBuilder<'a> {
             (crate::config::AddressingModel::PhysicalStorageBuffer, true) => {
                 AddressingModel::PhysicalStorageBuffer64
             }
-            _ => return Err(Error::msg("Invalid addressing model")),
+            (crate::config::AddressingModel::PhysicalStorageBuffer, false) => {
+                return Err(Error::msg(
+                    "The physical storage buffer addressing model requires a 64-bit (memory64) WebAssembly memory",
+                ))
+            }
         };
 
         let mut result = Self {
@@ -96,22 +216,90 @@ impl<'a> ModuleBuilder<'a> {
                 .extended_is()
                 .map_or_else(Default::default, |x| Box::from([Rc::new(x)])),
             version: config.platform.spirv_version(),
-            capabilities: config.capabilities,
-            extensions: config.extensions,
+            capabilities: config.capabilities.clone(),
+            extensions: config.extensions.clone(),
             memory_model: config.memory_model,
             memory_grow_error: config.memory_grow_error,
+            trap_mode: config.trap_mode,
+            trap_flag: None,
+            bounds_checks: config.bounds_checks,
+            division_traps: config.division_traps,
+            float64: config.float64,
+            max_value_depth: config.max_value_depth,
+            selection_control: config.selection_control,
+            sort_globals: config.sort_globals,
+            phi_merges: config.phi_merges,
+            profiling: config.profiling,
+            verbosity: config.verbosity,
+            panics: Vec::new(),
+            ray_payloads: Vec::new(),
+            wasm_features: WasmFeatures::default(),
+            memories: config.memories.clone(),
+            memory_variables: Vec::new(),
+            atomic_counters: config.atomic_counters.clone(),
+            entry_point_names: config.entry_point_names.clone(),
+            atomic_counter_variables: Vec::new(),
             wasm_memory64,
+            data_mode: config.data_segments,
+            data_segments: Vec::new(),
+            passive_data: HashMap::new(),
+            data_variables: Vec::new(),
             addressing_model,
             functions: Box::default(),
+            linkage: config.linkage,
+            imported_functions: Vec::new(),
+            func_types: Box::default(),
+            start_function: None,
             global_variables: Box::default(),
             built_functions: Box::default(),
+            exports: Box::default(),
+            inline_candidates: HashMap::default(),
+            inlining: Vec::default(),
             hidden_global_variables: Vec::default(),
         };
 
+        if let TrapMode::Flag { set, binding } = result.trap_mode {
+            let storage_class = match result.version >= Version::V1_3 {
+                true => StorageClass::StorageBuffer,
+                false => StorageClass::Uniform,
+            };
+
+            let var = Rc::new(Pointer::new_variable(
+                PointerSize::Skinny,
+                storage_class,
+                ScalarType::I32,
+                None,
+                [
+                    VariableDecorator::DesctiptorSet(set),
+                    VariableDecorator::Binding(binding),
+                ],
+            ));
+
+            result.hidden_global_variables.push(var.clone());
+            result.trap_flag = Some(var);
+        }
+
+        return Ok(result);
+    }
+
+    fn build(
+        config: Config,
+        bytes: &'a [u8],
+        types: &Types,
+        libraries: Vec<(&'a str, ModuleBuilder<'a>)>,
+    ) -> Result<Self> {
+        let wasm_memory64 = match types.memory_count() {
+            0 => false,
+            _ => types.memory_at(0).memory64,
+        };
+        let mut result = Self::with_config(&config, wasm_memory64)?;
+        result.wasm_features = features::used_features(config.features, bytes);
+
         let mut functions = Vec::with_capacity(types.function_count() as usize);
         let mut global_variables = Vec::with_capacity(types.global_count() as usize);
 
         let mut globals = Vec::new();
+        let mut datas = Vec::new();
         let mut code_sections = Vec::new();
         let mut imports = Vec::new();
         let mut exports = Vec::new();
@@ -137,12 +325,73 @@ impl<'a> ModuleBuilder<'a> {
                         globals.push(global?);
                     }
                 }
+                Payload::DataSection(d) => {
+                    datas.reserve(d.count() as usize);
+                    for data in d.into_iter() {
+                        datas.push(data?);
+                    }
+                }
+                Payload::StartSection { func, .. } => result.start_function = Some(func),
                 Payload::CodeSectionEntry(body) => code_sections.push(body),
                 Payload::End(_) => break,
                 _ => continue,
             }
         }
 
+        // Data segments
+        for (i, data) in (0..).zip(datas) {
+            let (memory_index, offset_expr) = match data.kind {
+                DataKind::Passive => {
+                    result.passive_data.insert(i, data.data);
+                    continue;
+                }
+                DataKind::Active {
+                    memory_index,
+                    offset_expr,
+                } => (memory_index, offset_expr),
+            };
+
+            let offset = match offset_expr.get_operators_reader().read()? {
+                Operator::I32Const { value } => value as u32 as u64,
+                Operator::I64Const { value } => value as u64,
+                _ if result.data_mode == DataSegmentMode::Ignore => continue,
+                _ => {
+                    return Err(Error::msg(
+                        "Data segments can only be placed at constant offsets",
+                    ))
+                }
+            };
+
+            // Ignored segments are still kept, so that the format strings of `debug_printf` can be read from them
+            if memory_index != 0 && matches!(result.data_mode, DataSegmentMode::Buffer { .. }) {
+                return Err(Error::msg(format!(
+                    "Data segments of memory {memory_index} can't be placed in the data buffer"
+                )));
+            }
+
+            result.data_segments.push(DataSegment {
+                memory: memory_index,
+                offset,
+                bytes: data.data,
+            });
+        }
+
+        // Type section
+        let mut func_types = Vec::with_capacity(types.type_count());
+        for i in 0..types.type_count() as u32 {
+            match types
+                .get(types.core_type_at(i))
+                .ok_or_else(Error::unexpected)?
+            {
+                wasmparser::types::Type::Sub(ty) => match &ty.structural_type {
+                    wasmparser::StructuralType::Func(f) => func_types.push(f.clone()),
+                    _ => return Err(Error::unexpected()),
+                },
+                _ => return Err(Error::unexpected()),
+            }
+        }
+        result.func_types = func_types.into_boxed_slice();
+
         // Imports
         let mut imported_function_count = 0u32;
         let mut imported_global_count = 0u32;
@@ -162,7 +411,80 @@ impl<'a> ModuleBuilder<'a> {
                         None => todo!(),
                     }
                 }
-                _ => todo!(),
+                module => {
+                    let library = libraries
+                        .iter()
+                        .filter(|(name, _)| *name == module)
+                        .find_map(|(_, library)| {
+                            library
+                                .exports
+                                .iter()
+                                .find(|x| x.name == import.name)
+                                .map(|export| (library, export))
+                        });
+
+                    let Some((library, export)) = library else {
+                        // The C library's memory and math functions are replaced by native code
+                        if let wasmparser::TypeRef::Func(type_index) = import.ty {
+                            let ty = result
+                                .func_types
+                                .get(type_index as usize)
+                                .ok_or_else(Error::unexpected)?;
+                            if let Some(f) = translate_intrinsic(import.name, ty, &result)? {
+                                functions.push(f);
+                                imported_function_count += 1;
+                                continue;
+                            }
+
+                            // Left for the linker to resolve
+                            if result.linkage {
+                                let ty = ty.clone();
+                                let function_id = Rc::new(Cell::new(None));
+                                result.imported_functions.push((
+                                    import.name,
+                                    function_id.clone(),
+                                    ty.clone(),
+                                ));
+                                functions.push(CallableFunction::Defined { function_id, ty });
+                                imported_function_count += 1;
+                                continue;
+                            }
+                        }
+                        return Err(Error::msg(format!(
+                            "Unresolved import `{module}::{}`",
+                            import.name
+                        )));
+                    };
+
+                    match (import.ty, export.kind) {
+                        (wasmparser::TypeRef::Func(_), ExternalKind::Func) => {
+                            functions.push(
+                                library
+                                    .functions
+                                    .get(export.index as usize)
+                                    .ok_or_else(Error::unexpected)?
+                                    .clone(),
+                            );
+                            imported_function_count += 1
+                        }
+                        (wasmparser::TypeRef::Global(_), ExternalKind::Global) => {
+                            global_variables.push(
+                                library
+                                    .global_variables
+                                    .get(export.index as usize)
+                                    .ok_or_else(Error::unexpected)?
+                                    .clone(),
+                            );
+                            imported_global_count += 1
+                        }
+                        _ => {
+                            return Err(Error::msg(format!(
+                                "Import `{module}::{}` doesn't match the kind of its export",
+                                import.name
+                            )))
+                        }
+                    }
+                }
             }
         }
 
@@ -173,10 +495,23 @@ impl<'a> ModuleBuilder<'a> {
                 .ok_or_else(Error::unexpected)?
             {
                 wasmparser::types::Type::Sub(ty) => match &ty.structural_type {
-                    wasmparser::StructuralType::Func(f) => CallableFunction::Defined {
-                        function_id: Rc::new(Cell::new(None)),
-                        ty: f.clone(),
-                    },
+                    wasmparser::StructuralType::Func(f) => {
+                        // The C library's memory and math functions are replaced by native code, instead of translating
+                        // their bodies
+                        let intrinsic = match config.functions.get(&i) {
+                            Some(x) if x.execution_model.is_some() => None,
+                            _ => exports
+                                .iter()
+                                .filter(|x| x.kind == ExternalKind::Func && x.index == i)
+                                .find_map(|x| translate_intrinsic(x.name, f, &result).transpose())
+                                .transpose()?,
+                        };
+
+                        intrinsic.unwrap_or_else(|| CallableFunction::Defined {
+                            function_id: Rc::new(Cell::new(None)),
+                            ty: f.clone(),
+                        })
+                    }
                     _ => return Err(Error::unexpected()),
                 },
                 _ => return Err(Error::unexpected()),
@@ -185,7 +520,40 @@ impl<'a> ModuleBuilder<'a> {
         }
         result.functions = functions.into_boxed_slice();
 
+        // Push constants
+        let push_constants = (imported_global_count..types.global_count())
+            .filter(|i| matches!(config.globals.get(i), Some(GlobalConfig::PushConstant)))
+            .collect::<Vec<_>>();
+
+        let push_constant_block = match push_constants.is_empty() {
+            true => None,
+            false => {
+                let members = push_constants.iter().map(|i| {
+                    let name = exports
+                        .iter()
+                        .find(|x| x.kind == ExternalKind::Global && x.index == *i)
+                        .map_or_else(|| format!("global_{i}"), |x| x.name.to_string());
+                    (name, Type::from(types.global_at(*i).content_type))
+                });
+
+                let var = Rc::new(Pointer::new_variable(
+                    PointerSize::Skinny,
+                    StorageClass::PushConstant,
+                    Layout::Std430.struct_type(members)?,
+                    None,
+                    [],
+                ));
+                result.hidden_global_variables.push(var.clone());
+                Some(var)
+            }
+        };
+
         // Global variables
+        let written_globals = written_globals(&code_sections)?;
+        let start_globals = match result.start_function {
+            Some(start) => start_globals(start, imported_function_count, &code_sections)?,
+            None => HashSet::new(),
+        };
         for i in imported_global_count..types.global_count() {
             let global = types.global_at(i);
             let init_expr = globals
@@ -193,6 +561,17 @@ impl<'a> ModuleBuilder<'a> {
                 .ok_or_else(Error::unexpected)?
                 .init_expr;
 
+            // Initializers are evaluated before the start function runs
+            for op in init_expr.get_operators_reader() {
+                if let Operator::GlobalGet { global_index } = op? {
+                    if start_globals.contains(&global_index) {
+                        return Err(Error::msg(format!(
+                            "The initializer of global {i} reads global {global_index}, which is set by the start function"
+                        )));
+                    }
+                }
+            }
+
             let ty = Type::from(global.content_type);
             let mut init_expr_reader = BlockReader::new(init_expr.get_operators_reader());
 
@@ -212,25 +591,155 @@ impl<'a> ModuleBuilder<'a> {
             translate_constants(&op, &mut block)?;
 
             let init_value = block.stack_pop(ty.clone(), &mut result)?;
-            global_variables.push(match global.mutable {
-                true => match result.platform {
-                    TargetPlatform::Vulkan { .. } => {
-                        warn!("Vulkan doesn't have mutable global variables. Using a constant instead.");
-                        GlobalVariable::Constant(init_value)
+
+            match config.globals.get(&i) {
+                Some(GlobalConfig::DescriptorSet {
+                    set,
+                    binding,
+                    buffer,
+                }) => {
+                    let decorators = [
+                        VariableDecorator::DesctiptorSet(*set),
+                        VariableDecorator::Binding(*binding),
+                    ];
+
+                    let var = match buffer {
+                        BufferKind::Storage => {
+                            let storage_class = match result.version >= Version::V1_3 {
+                                true => StorageClass::StorageBuffer,
+                                false => StorageClass::Uniform,
+                            };
+                            Pointer::new_variable(
+                                PointerSize::Skinny,
+                                storage_class,
+                                ty,
+                                None,
+                                decorators,
+                            )
+                        }
+                        BufferKind::Uniform if global.mutable => {
+                            return Err(Error::msg(format!(
+                                "Mutable global {i} can't be bound to a uniform buffer"
+                            )))
+                        }
+                        BufferKind::Uniform => Pointer::new_uniform_buffer(ty, decorators),
+                    };
+
+                    global_variables.push(GlobalVariable::Variable(Rc::new(var)));
+                    continue;
+                }
+
+                Some(GlobalConfig::PushConstant) => {
+                    if global.mutable {
+                        return Err(Error::msg(format!(
+                            "Mutable global {i} can't be a push constant"
+                        )));
                     }
-                    _ => GlobalVariable::Variable(Rc::new(Pointer::new_variable(
+
+                    let parent = push_constant_block.clone().ok_or_else(Error::unexpected)?;
+                    let index = push_constants
+                        .iter()
+                        .position(|x| *x == i)
+                        .ok_or_else(Error::unexpected)?;
+
+                    global_variables.push(GlobalVariable::Variable(Rc::new(Pointer::new(
+                        PointerKind::Skinny,
+                        StorageClass::PushConstant,
+                        ty,
+                        PointerSource::Member {
+                            parent,
+                            index: index as u32,
+                        },
+                    ))));
+                    continue;
+                }
+
+                None => {}
+            }
+
+            // Mutable globals that no function writes to are folded like immutable ones.
+            // Exported globals may still be written by the modules that import them.
+            let written = written_globals.contains(&i)
+                || exports
+                    .iter()
+                    .any(|x| x.kind == ExternalKind::Global && x.index == i);
+
+            global_variables.push(match global.mutable && written {
+                true => {
+                    // Shader globals are private to each invocation
+                    let storage_class = match result.platform {
+                        TargetPlatform::Vulkan { .. } | TargetPlatform::OpenGL { .. } => {
+                            StorageClass::Private
+                        }
+                        _ => StorageClass::CrossWorkgroup,
+                    };
+                    GlobalVariable::Variable(Rc::new(Pointer::new_variable(
                         PointerSize::Skinny,
-                        StorageClass::CrossWorkgroup,
+                        storage_class,
                         ty,
                         Some(init_value),
                         [],
-                    ))),
-                },
+                    )))
+                }
                 false => GlobalVariable::Constant(init_value),
             })
         }
         result.global_variables = global_variables.into_boxed_slice();
 
+        // Forbidden operators
+        for (i, body) in (imported_function_count..).zip(code_sections.iter()) {
+            features::check_operators(&config, i, body)?;
+        }
+
+        // 64-bit floats
+        if config.float64 != Float64Mode::Native {
+            let mut offending = Vec::new();
+            for (i, body) in (imported_function_count..).zip(code_sections.iter()) {
+                if let Some(CallableFunction::Defined { ty, .. }) = result.functions.get(i as usize)
+                {
+                    if features::uses_f64(ty, body)? {
+                        offending.push(i.to_string());
+                    }
+                }
+            }
+
+            if !offending.is_empty() {
+                let offending = offending.join(", ");
+                match config.float64 {
+                    Float64Mode::Error => {
+                        return Err(Error::msg(format!(
+                            "The module uses 64-bit floats, which aren't allowed by the configuration, in functions {offending}"
+                        )))
+                    }
+                    _ => tracing::warn!(
+                        "64-bit floats are demoted to 32-bit ones in functions {offending}"
+                    ),
+                }
+            }
+        }
+
+        // Inline candidates
+        if let Some(threshold) = config.inline_threshold {
+            for (i, body) in (imported_function_count..).zip(code_sections.iter()) {
+                if let Some(CallableFunction::Callback(_)) = result.functions.get(i as usize) {
+                    continue;
+                }
+                if config.functions.get(&i).is_some_and(|x| {
+                    x.execution_model.is_some()
+                        || x.params.iter().any(|(_, x)| {
+                            x.ty.is_some() || !matches!(x.kind, ParameterKind::FunctionParameter)
+                        })
+                }) {
+                    continue;
+                }
+
+                let operators = body.get_operators_reader()?.into_iter().count();
+                if operators <= threshold as usize {
+                    result.inline_candidates.insert(i, body.clone());
+                }
+            }
+        }
+
         // Function bodies
         let mut built_functions = Vec::with_capacity(code_sections.len());
         for (i, body) in (imported_function_count..types.function_count()).zip(code_sections) {
@@ -240,20 +749,38 @@ impl<'a> ModuleBuilder<'a> {
                 .ok_or_else(Error::unexpected)?
             {
                 CallableFunction::Defined { function_id, ty } => (function_id.clone(), ty.clone()),
-                _ => return Err(Error::unexpected()),
+                // Memory and math intrinsics
+                CallableFunction::Callback(_) => continue,
             };
 
-            let config = config
+            let export = exports.iter().find(|x| {
+                x.kind == ExternalKind::Func
+                    && x.index == i
+                    && !config.internal_exports.iter().any(|y| **y == *x.name)
+            });
+
+            let mut config = config
                 .functions
                 .get(&i)
                 .map_or_else(Cow::default, Cow::Borrowed);
 
-            let export = exports
-                .iter()
-                .find(|x| x.kind == ExternalKind::Func && x.index == i);
+            // Functions only exported internally aren't entry points
+            if export.is_none()
+                && config.execution_model.is_some()
+                && exports
+                    .iter()
+                    .any(|x| x.kind == ExternalKind::Func && x.index == i)
+            {
+                config.to_mut().execution_model = None;
+            }
 
+            let _span = (result.verbosity >= Verbosity::Functions).then(|| {
+                tracing::debug_span!("build_function", index = i, export = export.map(|x| x.name))
+                    .entered()
+            });
             built_functions.push(FunctionBuilder::new(
                 function_id,
+                i,
                 export.cloned(),
                 &config,
                 &ty,
@@ -262,10 +789,222 @@ impl<'a> ModuleBuilder<'a> {
             )?);
         }
 
+        // Library functions and globals
+        for (_, library) in libraries {
+            let trap_flag = library.trap_flag;
+            result.hidden_global_variables.extend(
+                library
+                    .hidden_global_variables
+                    .into_iter()
+                    .filter(|x| !trap_flag.as_ref().is_some_and(|flag| Rc::ptr_eq(x, flag))),
+            );
+            result
+                .hidden_global_variables
+                .extend(library.global_variables.iter().filter_map(|x| match x {
+                    GlobalVariable::Variable(var) => Some(var.clone()),
+                    GlobalVariable::Constant(_) => None,
+                }));
+            built_functions.extend(library.built_functions.into_vec());
+            result.panics.extend(library.panics);
+            result.ray_payloads.extend(library.ray_payloads);
+        }
+
         result.built_functions = built_functions.into_boxed_slice();
+        result.exports = exports.into_boxed_slice();
         return Ok(result);
     }
 
+    /// Returns the `len` bytes at `address` if they're inside a single data segment
+    pub fn data_at(&self, memory: u32, address: u64, len: u32) -> Option<&'a [u8]> {
+        // Later segments overwrite the previous ones
+        for segment in self
+            .data_segments
+            .iter()
+            .rev()
+            .filter(|x| x.memory == memory)
+        {
+            let end = segment.offset + segment.bytes.len() as u64;
+            if address >= end || address + len as u64 <= segment.offset {
+                continue;
+            }
+
+            let start = address.checked_sub(segment.offset)? as usize;
+            return segment.bytes.get(start..start + len as usize);
+        }
+
+        return None;
+    }
+
+    /// Returns the nul-terminated string at `address`, without its terminator
+    pub fn c_str_at(&self, memory: u32, address: u64) -> Option<&'a [u8]> {
+        let segment = self
+            .data_segments
+            .iter()
+            .rev()
+            .filter(|x| x.memory == memory)
+            .find(|x| address >= x.offset && address < x.offset + x.bytes.len() as u64)?;
+
+        let bytes = &segment.bytes[(address - segment.offset) as usize..];
+        let len = bytes.iter().position(|x| *x == 0)?;
+        return Some(&bytes[..len]);
+    }
+
+    /// Checks whether any of the `len` bytes starting at `address` is initialized by a data segment of the memory
+    pub fn data_overlaps(&self, memory: u32, address: u64, len: u32) -> bool {
+        return self.data_segments.iter().any(|x| {
+            x.memory == memory
+                && address < x.offset + x.bytes.len() as u64
+                && x.offset < address + len as u64
+        });
+    }
+
+    /// Address ranges of the first memory initialized by its data segments, sorted and merged
+    pub fn data_ranges(&self) -> Vec<Range<u64>> {
+        let mut ranges = self
+            .data_segments
+            .iter()
+            .filter(|x| x.memory == 0 && !x.bytes.is_empty())
+            .map(|x| x.offset..x.offset + x.bytes.len() as u64)
+            .collect::<Vec<_>>();
+        ranges.sort_by_key(|x| x.start);
+
+        let mut result = Vec::<Range<u64>>::with_capacity(ranges.len());
+        for range in ranges {
+            match result.last_mut() {
+                Some(last) if range.start <= last.end => last.end = last.end.max(range.end),
+                _ => result.push(range),
+            }
+        }
+        return result;
+    }
+
+    /// Offset of the scratch slot of the [`data_image`](ModuleBuilder::data_image)
+    pub fn data_scratch_offset(&self) -> u64 {
+        return self
+            .data_ranges()
+            .last()
+            .map_or(0, |x| x.end)
+            .next_multiple_of(8);
+    }
+
+    /// Length of [`data_image`](ModuleBuilder::data_image)
+    pub fn data_image_len(&self) -> u64 {
+        return self.data_scratch_offset() + 8;
+    }
+
+    /// Returns the initial contents of the first memory, followed by a scratch slot, padded to a multiple of 8 bytes
+    pub fn data_image(&self) -> Vec<u8> {
+        let mut result = vec![0; self.data_image_len() as usize];
+        for segment in self.data_segments.iter().filter(|x| x.memory == 0) {
+            let start = segment.offset as usize;
+            result[start..start + segment.bytes.len()].copy_from_slice(segment.bytes);
+        }
+        return result;
+    }
+
+    /// Returns a (fat) pointer to the data segments' storage buffer, or `None` if they aren't placed in one
+    pub fn data_variable(&mut self, pointee: impl Into<Type>) -> Option<Rc<Pointer>> {
+        let pointee = pointee.into();
+        let DataSegmentMode::Buffer { set, binding } = self.data_mode else {
+            return None;
+        };
+
+        if let Some((_, var)) = self.data_variables.iter().find(|(ty, _)| *ty == pointee) {
+            return Some(var.clone());
+        }
+
+        let storage_class = match self.version >= Version::V1_3 {
+            true => StorageClass::StorageBuffer,
+            false => StorageClass::Uniform,
+        };
+
+        let var = Rc::new(Pointer::new_variable(
+            PointerSize::Fat,
+            storage_class,
+            pointee.clone(),
+            None,
+            [
+                VariableDecorator::DesctiptorSet(set),
+                VariableDecorator::Binding(binding),
+            ],
+        ));
+
+        self.hidden_global_variables.push(var.clone());
+        self.data_variables.push((pointee, var.clone()));
+        return Some(var);
+    }
+
+    /// Returns a (fat) pointer to the storage buffer backing the memory, or `None` if it isn't bound to one
+    pub fn memory_variable(
+        &mut self,
+        memory: u32,
+        pointee: impl Into<Type>,
+    ) -> Option<Rc<Pointer>> {
+        let pointee = pointee.into();
+        let MemoryBinding { set, binding } = *self.memories.get(&memory)?;
+
+        if let Some((_, _, var)) = self
+            .memory_variables
+            .iter()
+            .find(|(idx, ty, _)| *idx == memory && *ty == pointee)
+        {
+            return Some(var.clone());
+        }
+
+        let storage_class = match self.version >= Version::V1_3 {
+            true => StorageClass::StorageBuffer,
+            false => StorageClass::Uniform,
+        };
+
+        let var = Rc::new(Pointer::new_variable(
+            PointerSize::Fat,
+            storage_class,
+            pointee.clone(),
+            None,
+            [
+                VariableDecorator::DesctiptorSet(set),
+                VariableDecorator::Binding(binding),
+            ],
+        ));
+
+        self.hidden_global_variables.push(var.clone());
+        self.memory_variables.push((memory, pointee, var.clone()));
+        return Some(var);
+    }
+
+    /// Returns a pointer to the atomic counter, or `None` if the counter isn't bound to any buffer
+    pub fn atomic_counter_variable(&mut self, counter: u32) -> Option<Rc<Pointer>> {
+        let AtomicCounter { set, binding } = *self.atomic_counters.get(&counter)?;
+
+        if let Some((_, var)) = self
+            .atomic_counter_variables
+            .iter()
+            .find(|(idx, _)| *idx == counter)
+        {
+            return Some(var.clone());
+        }
+
+        let storage_class = match self.version >= Version::V1_3 {
+            true => StorageClass::StorageBuffer,
+            false => StorageClass::Uniform,
+        };
+
+        let var = Rc::new(Pointer::new_variable(
+            PointerSize::Skinny,
+            storage_class,
+            ScalarType::I32,
+            None,
+            [
+                VariableDecorator::DesctiptorSet(set),
+                VariableDecorator::Binding(binding),
+            ],
+        ));
+
+        self.hidden_global_variables.push(var.clone());
+        self.atomic_counter_variables.push((counter, var.clone()));
+        return Some(var);
+    }
+
     pub fn isize_type(&self) -> ScalarType {
         match self.wasm_memory64 {
             true => ScalarType::I64,
@@ -306,3 +1045,52 @@ impl<'a> ModuleBuilder<'a> {
         self.wasm_address_bits() / 8
     }
 }
+
+fn written_globals(code_sections: &[FunctionBody]) -> Result<HashSet<u32>> {
+    let mut result = HashSet::new();
+    for body in code_sections {
+        for op in body.get_operators_reader()? {
+            if let Operator::GlobalSet { global_index } = op? {
+                result.insert(global_index);
+            }
+        }
+    }
+    return Ok(result);
+}
+
+fn start_globals(
+    start: u32,
+    imported_function_count: u32,
+    code_sections: &[FunctionBody],
+) -> Result<HashSet<u32>> {
+    let mut result = HashSet::new();
+    let mut visited = HashSet::new();
+    let mut pending = vec![start];
+
+    while let Some(f) = pending.pop() {
+        if !visited.insert(f) {
+            continue;
+        }
+
+        let Some(body) = f
+            .checked_sub(imported_function_count)
+            .and_then(|i| code_sections.get(i as usize))
+        else {
+            continue;
+        };
+
+        for op in body.get_operators_reader()? {
+            match op? {
+                Operator::GlobalSet { global_index } => {
+                    result.insert(global_index);
+                }
+                Operator::Call { function_index } | Operator::ReturnCall { function_index } => {
+                    pending.push(function_index)
+                }
+                _ => {}
+            }
+        }
+    }
+
+    return Ok(result);
+}
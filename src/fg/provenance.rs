@@ -0,0 +1,540 @@
+//! Pointer provenance of the integer locals of a function
+
+use super::{
+    function::Storeable,
+    module::{CallableFunction, GlobalVariable, ModuleBuilder},
+    values::Value,
+};
+use crate::{error::Result, r#type::Type};
+use wasmparser::{BlockType, FunctionBody, Operator};
+
+/// Kinds of values a local (or an operand) may hold
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Default)]
+pub struct Provenance {
+    pub integer: bool,
+    pub pointer: bool,
+}
+
+impl Provenance {
+    pub const INTEGER: Self = Self {
+        integer: true,
+        pointer: false,
+    };
+
+    pub const POINTER: Self = Self {
+        integer: false,
+        pointer: true,
+    };
+
+    pub fn join(self, other: Self) -> Self {
+        return Self {
+            integer: self.integer || other.integer,
+            pointer: self.pointer || other.pointer,
+        };
+    }
+
+    /// Only ever holds integers (or is never set at all)
+    pub fn is_integer(self) -> bool {
+        return !self.pointer;
+    }
+
+    /// Only ever holds pointers
+    pub fn is_pointer(self) -> bool {
+        return self.pointer && !self.integer;
+    }
+}
+
+#[derive(Debug)]
+struct Frame {
+    /// Height of the stack when the frame was entered, without its parameters
+    height: usize,
+    is_loop: bool,
+    /// Parameters of the frame, restored by `else`
+    params: Vec<Provenance>,
+    /// Results of the frame, joined from every branch into it
+    results: Vec<Provenance>,
+    /// `if` frames without an `else` forward their parameters as their results
+    missing_else: bool,
+}
+
+struct Analysis<'a, 'b> {
+    module: &'b ModuleBuilder<'a>,
+    locals: Vec<Provenance>,
+    stack: Vec<Provenance>,
+    frames: Vec<Frame>,
+    changed: bool,
+}
+
+/// Returns the provenance of every local of the function, if the body can be analyzed
+pub fn analyze(
+    body: &FunctionBody,
+    params: &[Storeable],
+    local_count: usize,
+    module: &ModuleBuilder,
+) -> Result<Option<Vec<Provenance>>> {
+    let mut locals = params
+        .iter()
+        .map(|param| match param {
+            // Pointer parameters push both views of themselves, but their integer is only a byproduct of the pointer
+            Storeable::Pointer {
+                integer_variable: Some(_),
+                ..
+            } => Provenance::POINTER,
+            _ => Provenance::INTEGER,
+        })
+        .collect::<Vec<_>>();
+    locals.resize(params.len() + local_count, Provenance::default());
+
+    let mut analysis = Analysis {
+        module,
+        locals,
+        stack: Vec::new(),
+        frames: Vec::new(),
+        changed: true,
+    };
+
+    // Locals may be set after they're read (like in loops), so the body is interpreted until a fixed point is found
+    while analysis.changed {
+        analysis.changed = false;
+        analysis.stack.clear();
+        analysis.frames.clear();
+        analysis.frames.push(Frame {
+            height: 0,
+            is_loop: false,
+            params: Vec::new(),
+            results: Vec::new(),
+            missing_else: false,
+        });
+
+        for op in body.get_operators_reader()? {
+            if analysis.interpret(&op?).is_none() {
+                return Ok(None);
+            }
+        }
+    }
+
+    return Ok(Some(analysis.locals));
+}
+
+impl Analysis<'_, '_> {
+    fn pop(&mut self) -> Provenance {
+        // Operands of unreachable code are polymorphic
+        let height = self.frames.last().map_or(0, |x| x.height);
+        if self.stack.len() <= height {
+            return Provenance::default();
+        }
+        return self.stack.pop().unwrap_or_default();
+    }
+
+    fn pop_n(&mut self, n: usize) -> Vec<Provenance> {
+        let mut result = (0..n).map(|_| self.pop()).collect::<Vec<_>>();
+        result.reverse();
+        return result;
+    }
+
+    fn pop_integers(&mut self, pops: usize, pushes: usize) {
+        for _ in 0..pops {
+            self.pop();
+        }
+        self.stack
+            .extend(std::iter::repeat(Provenance::INTEGER).take(pushes));
+    }
+
+    fn set_local(&mut self, local_index: u32, value: Provenance) -> Option<()> {
+        let local = self.locals.get_mut(local_index as usize)?;
+        let joined = local.join(value);
+        if joined != *local {
+            *local = joined;
+            self.changed = true;
+        }
+        return Some(());
+    }
+
+    fn branch(&mut self, relative_depth: u32) -> Option<()> {
+        let depth = self.frames.len().checked_sub(relative_depth as usize + 1)?;
+        let frame = &self.frames[depth];
+        let count = match frame.is_loop {
+            true => frame.params.len(),
+            false => frame.results.len(),
+        };
+
+        let values = self.stack[self.stack.len().saturating_sub(count)..].to_vec();
+        let frame = &mut self.frames[depth];
+        let target = match frame.is_loop {
+            true => &mut frame.params,
+            false => &mut frame.results,
+        };
+
+        for (target, value) in target.iter_mut().rev().zip(values.into_iter().rev()) {
+            *target = target.join(value);
+        }
+        return Some(());
+    }
+
+    fn unreachable(&mut self) {
+        let height = self.frames.last().map_or(0, |x| x.height);
+        self.stack.truncate(height);
+    }
+
+    fn enter(&mut self, blockty: BlockType, is_loop: bool) -> Option<()> {
+        let (params, results) = match blockty {
+            BlockType::Empty => (0, 0),
+            BlockType::Type(_) => (0, 1),
+            BlockType::FuncType(i) => {
+                let ty = self.module.func_types.get(i as usize)?;
+                (ty.params().len(), ty.results().len())
+            }
+        };
+
+        // Branches back into a loop with parameters would need another fixed point of their own
+        if is_loop && params > 0 {
+            return None;
+        }
+
+        let params = self.pop_n(params);
+        self.frames.push(Frame {
+            height: self.stack.len(),
+            is_loop,
+            params: params.clone(),
+            results: vec![Provenance::default(); results],
+            missing_else: false,
+        });
+        self.stack.extend(params);
+        return Some(());
+    }
+
+    fn interpret(&mut self, op: &Operator) -> Option<()> {
+        use Operator::*;
+
+        match op {
+            LocalGet { local_index } => {
+                let local = *self.locals.get(*local_index as usize)?;
+                self.stack.push(local);
+            }
+            LocalSet { local_index } => {
+                let value = self.pop();
+                self.set_local(*local_index, value)?;
+            }
+            LocalTee { local_index } => {
+                let value = self.pop();
+                self.set_local(*local_index, value)?;
+                self.stack.push(value);
+            }
+
+            GlobalGet { global_index } => {
+                let value = match self.module.global_variables.get(*global_index as usize)? {
+                    GlobalVariable::Variable(var)
+                        if matches!(var.pointee, Type::Pointer { .. }) =>
+                    {
+                        Provenance::POINTER
+                    }
+                    GlobalVariable::Constant(Value::Pointer(_)) => Provenance::POINTER,
+                    _ => Provenance::INTEGER,
+                };
+                self.stack.push(value);
+            }
+
+            // Pointer arithmetic keeps the pointer
+            I32Add | I64Add => {
+                let op2 = self.pop();
+                let op1 = self.pop();
+                self.stack.push(Provenance {
+                    integer: op1.integer && op2.integer,
+                    pointer: op1.pointer || op2.pointer,
+                });
+            }
+            I32Sub | I64Sub => {
+                self.pop();
+                let op1 = self.pop();
+                self.stack.push(op1);
+            }
+            Select | TypedSelect { .. } => {
+                self.pop();
+                let op2 = self.pop();
+                let op1 = self.pop();
+                self.stack.push(op1.join(op2));
+            }
+
+            Drop => {
+                self.pop();
+            }
+            Nop | DataDrop { .. } | ElemDrop { .. } => {}
+
+            Block { blockty } => self.enter(*blockty, false)?,
+            Loop { blockty } => self.enter(*blockty, true)?,
+            If { blockty } => {
+                self.pop();
+                self.enter(*blockty, false)?;
+                self.frames.last_mut()?.missing_else = true;
+            }
+            Else => {
+                let count = self.frames.last()?.results.len();
+                let values = self.pop_n(count);
+
+                let frame = self.frames.last_mut()?;
+                frame.missing_else = false;
+                for (result, value) in frame.results.iter_mut().zip(values) {
+                    *result = result.join(value);
+                }
+
+                let (height, params) = (frame.height, frame.params.clone());
+                self.stack.truncate(height);
+                self.stack.extend(params);
+            }
+            End => {
+                let count = self.frames.last()?.results.len();
+                let values = self.pop_n(count);
+
+                let frame = self.frames.pop()?;
+                let mut results = frame.results;
+                for (result, value) in results.iter_mut().zip(values) {
+                    *result = result.join(value);
+                }
+                if frame.missing_else {
+                    for (result, param) in results.iter_mut().zip(frame.params) {
+                        *result = result.join(param);
+                    }
+                }
+
+                self.stack.truncate(frame.height);
+                self.stack.extend(results);
+            }
+
+            Br { relative_depth } => {
+                self.branch(*relative_depth)?;
+                self.unreachable();
+            }
+            BrIf { relative_depth } => {
+                self.pop();
+                self.branch(*relative_depth)?;
+            }
+            BrTable { targets } => {
+                self.pop();
+                for target in targets.targets() {
+                    self.branch(target.ok()?)?;
+                }
+                self.branch(targets.default())?;
+                self.unreachable();
+            }
+            Return | Unreachable => self.unreachable(),
+
+            Call { function_index } | ReturnCall { function_index } => {
+                let CallableFunction::Defined { ty, .. } =
+                    self.module.functions.get(*function_index as usize)?
+                else {
+                    return None;
+                };
+
+                let (params, results) = (ty.params().len(), ty.results().len());
+                self.pop_integers(params, results);
+                if let ReturnCall { .. } = op {
+                    self.unreachable();
+                }
+            }
+
+            _ => {
+                let (pops, pushes) = arity(op)?;
+                self.pop_integers(pops, pushes);
+            }
+        }
+
+        return Some(());
+    }
+}
+
+/// Returns how many operands the operator pops from the stack, and how many results it pushes into it
+pub fn arity(op: &Operator) -> Option<(usize, usize)> {
+    use Operator::*;
+
+    return Some(match op {
+        Nop | DataDrop { .. } | ElemDrop { .. } => (0, 0),
+        LocalGet { .. } | GlobalGet { .. } => (0, 1),
+        LocalSet { .. } | Drop => (1, 0),
+        LocalTee { .. } => (1, 1),
+        I32Add | I64Add | I32Sub | I64Sub => (2, 1),
+        Select | TypedSelect { .. } => (3, 1),
+
+        I32Const { .. }
+        | I64Const { .. }
+        | F32Const { .. }
+        | F64Const { .. }
+        | RefNull { .. }
+        | RefFunc { .. }
+        | MemorySize { .. }
+        | TableSize { .. } => (0, 1),
+
+        GlobalSet { .. } => (1, 0),
+
+        I32Load { .. }
+        | I64Load { .. }
+        | F32Load { .. }
+        | F64Load { .. }
+        | I32Load8S { .. }
+        | I32Load8U { .. }
+        | I32Load16S { .. }
+        | I32Load16U { .. }
+        | I64Load8S { .. }
+        | I64Load8U { .. }
+        | I64Load16S { .. }
+        | I64Load16U { .. }
+        | I64Load32S { .. }
+        | I64Load32U { .. }
+        | MemoryGrow { .. }
+        | TableGet { .. }
+        | RefIsNull
+        | I32Eqz
+        | I64Eqz
+        | I32Clz
+        | I32Ctz
+        | I32Popcnt
+        | I64Clz
+        | I64Ctz
+        | I64Popcnt
+        | F32Abs
+        | F32Neg
+        | F32Ceil
+        | F32Floor
+        | F32Trunc
+        | F32Nearest
+        | F32Sqrt
+        | F64Abs
+        | F64Neg
+        | F64Ceil
+        | F64Floor
+        | F64Trunc
+        | F64Nearest
+        | F64Sqrt
+        | I32WrapI64
+        | I32TruncF32S
+        | I32TruncF32U
+        | I32TruncF64S
+        | I32TruncF64U
+        | I64ExtendI32S
+        | I64ExtendI32U
+        | I64TruncF32S
+        | I64TruncF32U
+        | I64TruncF64S
+        | I64TruncF64U
+        | F32ConvertI32S
+        | F32ConvertI32U
+        | F32ConvertI64S
+        | F32ConvertI64U
+        | F32DemoteF64
+        | F64ConvertI32S
+        | F64ConvertI32U
+        | F64ConvertI64S
+        | F64ConvertI64U
+        | F64PromoteF32
+        | I32ReinterpretF32
+        | I64ReinterpretF64
+        | F32ReinterpretI32
+        | F64ReinterpretI64
+        | I32Extend8S
+        | I32Extend16S
+        | I64Extend8S
+        | I64Extend16S
+        | I64Extend32S
+        | I32TruncSatF32S
+        | I32TruncSatF32U
+        | I32TruncSatF64S
+        | I32TruncSatF64U
+        | I64TruncSatF32S
+        | I64TruncSatF32U
+        | I64TruncSatF64S
+        | I64TruncSatF64U => (1, 1),
+
+        I32Store { .. }
+        | I64Store { .. }
+        | F32Store { .. }
+        | F64Store { .. }
+        | I32Store8 { .. }
+        | I32Store16 { .. }
+        | I64Store8 { .. }
+        | I64Store16 { .. }
+        | I64Store32 { .. }
+        | TableSet { .. } => (2, 0),
+
+        I32Eq
+        | I32Ne
+        | I32LtS
+        | I32LtU
+        | I32GtS
+        | I32GtU
+        | I32LeS
+        | I32LeU
+        | I32GeS
+        | I32GeU
+        | I64Eq
+        | I64Ne
+        | I64LtS
+        | I64LtU
+        | I64GtS
+        | I64GtU
+        | I64LeS
+        | I64LeU
+        | I64GeS
+        | I64GeU
+        | F32Eq
+        | F32Ne
+        | F32Lt
+        | F32Gt
+        | F32Le
+        | F32Ge
+        | F64Eq
+        | F64Ne
+        | F64Lt
+        | F64Gt
+        | F64Le
+        | F64Ge
+        | I32Mul
+        | I32DivS
+        | I32DivU
+        | I32RemS
+        | I32RemU
+        | I32And
+        | I32Or
+        | I32Xor
+        | I32Shl
+        | I32ShrS
+        | I32ShrU
+        | I32Rotl
+        | I32Rotr
+        | I64Mul
+        | I64DivS
+        | I64DivU
+        | I64RemS
+        | I64RemU
+        | I64And
+        | I64Or
+        | I64Xor
+        | I64Shl
+        | I64ShrS
+        | I64ShrU
+        | I64Rotl
+        | I64Rotr
+        | F32Add
+        | F32Sub
+        | F32Mul
+        | F32Div
+        | F32Min
+        | F32Max
+        | F32Copysign
+        | F64Add
+        | F64Sub
+        | F64Mul
+        | F64Div
+        | F64Min
+        | F64Max
+        | F64Copysign
+        | TableGrow { .. } => (2, 1),
+
+        MemoryCopy { .. }
+        | MemoryFill { .. }
+        | MemoryInit { .. }
+        | TableCopy { .. }
+        | TableInit { .. }
+        | TableFill { .. } => (3, 0),
+
+        _ => return None,
+    });
+}
@@ -0,0 +1,176 @@
+//! Elimination of the shadow stack of LLVM-produced modules
+
+use super::{
+    module::{CallableFunction, ModuleBuilder},
+    provenance::arity,
+    values::{integer::Integer, pointer::Pointer},
+};
+use crate::{
+    error::Result,
+    r#type::{PointerSize, ScalarType},
+};
+use rspirv::spirv::StorageClass;
+use std::{collections::BTreeMap, rc::Rc};
+use wasmparser::{FunctionBody, Operator};
+
+/// Frame of a function in the shadow stack, with a variable for every slot of it
+#[derive(Debug, Clone)]
+pub struct ShadowStack {
+    /// Local holding the address of the frame
+    pub local: u32,
+    pub slots: BTreeMap<u64, Rc<Pointer>>,
+    /// Values read from the local, which are only ever used as the address of a slot
+    pub addresses: Vec<Rc<Integer>>,
+}
+
+impl ShadowStack {
+    /// Returns the variable of the slot at `offset`, if `address` is the address of the frame
+    pub fn slot(&self, address: &Rc<Integer>, offset: u64) -> Option<Rc<Pointer>> {
+        if !self.addresses.iter().any(|x| Rc::ptr_eq(x, address)) {
+            return None;
+        }
+        return self.slots.get(&offset).cloned();
+    }
+}
+
+/// Accesses to a slot of the frame
+enum Access {
+    Slot(u64, ScalarType),
+    /// Restores the stack pointer, releasing the frame
+    Release,
+}
+
+/// Recognizes the frame of the function in the shadow stack, if it doesn't escape
+pub fn analyze(body: &FunctionBody, module: &ModuleBuilder) -> Result<Option<ShadowStack>> {
+    if module.wasm_memory64 {
+        return Ok(None);
+    }
+
+    let ops = body
+        .get_operators_reader()?
+        .into_iter()
+        .collect::<Result<Vec<_>, _>>()?;
+
+    // Prologue
+    let (global, size) = match &ops[..] {
+        [Operator::GlobalGet { global_index }, Operator::I32Const { value }, Operator::I32Sub, ..] => {
+            (*global_index, *value)
+        }
+        _ => return Ok(None),
+    };
+
+    let (local, prologue) = match &ops[3..] {
+        [Operator::LocalTee { local_index }, Operator::GlobalSet { global_index }, ..]
+            if *global_index == global =>
+        {
+            (*local_index, 5)
+        }
+        [Operator::LocalSet { local_index }, ..] => (*local_index, 4),
+        _ => return Ok(None),
+    };
+
+    if size <= 0 {
+        return Ok(None);
+    }
+
+    let mut slots = BTreeMap::<u64, ScalarType>::new();
+    for (i, op) in ops.iter().enumerate().skip(prologue) {
+        match op {
+            Operator::LocalSet { local_index } | Operator::LocalTee { local_index }
+                if *local_index == local =>
+            {
+                return Ok(None)
+            }
+            Operator::LocalGet { local_index } if *local_index == local => {}
+            _ => continue,
+        }
+
+        match access(&ops[i + 1..], global, size, module) {
+            Some(Access::Slot(offset, ty)) => match slots.get(&offset) {
+                Some(slot) if *slot != ty => return Ok(None),
+                _ => {
+                    slots.insert(offset, ty);
+                }
+            },
+            Some(Access::Release) => {}
+            None => return Ok(None),
+        }
+    }
+
+    // Slots can't overlap each other, nor go outside of the frame
+    let mut end = 0;
+    for (offset, ty) in slots.iter() {
+        let size_of = ty.byte_size().unwrap_or(u32::MAX) as u64;
+        if *offset < end || offset + size_of > size as u64 {
+            return Ok(None);
+        }
+        end = offset + size_of;
+    }
+
+    return Ok(Some(ShadowStack {
+        local,
+        slots: slots
+            .into_iter()
+            .map(|(offset, ty)| {
+                let variable = Pointer::new_variable(
+                    PointerSize::Skinny,
+                    StorageClass::Function,
+                    ty,
+                    None,
+                    [],
+                );
+                (offset, Rc::new(variable))
+            })
+            .collect(),
+        addresses: Vec::new(),
+    }));
+}
+
+fn access(ops: &[Operator], global: u32, size: i32, module: &ModuleBuilder) -> Option<Access> {
+    if let [Operator::I32Const { value }, Operator::I32Add, Operator::GlobalSet { global_index }, ..] =
+        ops
+    {
+        if *value == size && *global_index == global {
+            return Some(Access::Release);
+        }
+    }
+
+    // Values pushed since the address, itself included
+    let mut height = 1;
+    for op in ops {
+        let (pops, pushes) = match op {
+            Operator::Call { function_index } => {
+                match module.functions.get(*function_index as usize)? {
+                    CallableFunction::Defined { ty, .. } => (ty.params().len(), ty.results().len()),
+                    CallableFunction::Callback(_) => return None,
+                }
+            }
+            op => arity(op)?,
+        };
+
+        if pops < height {
+            height = height - pops + pushes;
+            continue;
+        }
+
+        // The address is consumed by this operator, which must access the frame
+        let (memarg, ty) = match op {
+            Operator::I32Load { memarg } if height == 1 => (memarg, ScalarType::I32),
+            Operator::I64Load { memarg } if height == 1 => (memarg, ScalarType::I64),
+            Operator::F32Load { memarg } if height == 1 => (memarg, ScalarType::F32),
+            Operator::F64Load { memarg } if height == 1 => (memarg, ScalarType::F64),
+            Operator::I32Store { memarg } if height == 2 => (memarg, ScalarType::I32),
+            Operator::I64Store { memarg } if height == 2 => (memarg, ScalarType::I64),
+            Operator::F32Store { memarg } if height == 2 => (memarg, ScalarType::F32),
+            Operator::F64Store { memarg } if height == 2 => (memarg, ScalarType::F64),
+            _ => return None,
+        };
+
+        return match memarg.memory {
+            0 => Some(Access::Slot(memarg.offset, ty)),
+            _ => None,
+        };
+    }
+
+    return None;
+}
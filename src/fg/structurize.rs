@@ -0,0 +1,757 @@
+//! Structurization of the control flow of a function
+
+use super::{
+    function::FunctionBuilder,
+    values::{
+        bool::{Bool, BoolSource, Equality},
+        integer::{Integer, IntegerSource},
+        pointer::Pointer,
+        Value,
+    },
+    Handle, Label, Operation,
+};
+use crate::{
+    config::Verbosity,
+    error::{Error, Result},
+    r#type::{PointerSize, ScalarType},
+};
+use rspirv::spirv::StorageClass;
+use std::{collections::HashMap, fmt::Arguments, rc::Rc};
+
+/// Structured control flow construct headed by a block, declared right before the block's terminating branch
+#[derive(Debug, Clone)]
+pub enum Construct {
+    Selection {
+        merge: Handle<Label>,
+    },
+    Loop {
+        merge: Handle<Label>,
+        continue_target: Handle<Label>,
+    },
+}
+
+/// Blocks of the function, delimited by labels and block terminating operations
+struct Cfg {
+    /// Label of every block, `None` for the entry block
+    labels: Vec<Option<Handle<Label>>>,
+    blocks: HashMap<Handle<Label>, usize>,
+    /// Index of the anchor terminating every block
+    terminators: Vec<Option<usize>>,
+    succs: Vec<Vec<usize>>,
+    preds: Vec<Vec<usize>>,
+    reachable: Vec<bool>,
+    idoms: Vec<Option<usize>>,
+    /// Immediate post-dominators
+    ipdoms: Vec<Option<usize>>,
+    /// Number of anchors already split into blocks
+    scanned: usize,
+}
+
+/// Unreachable merge blocks created for constructs, by the label of their header (`None` for the entry block)
+type UnreachableMerges = Vec<(Option<Handle<Label>>, Handle<Label>)>;
+
+enum Step {
+    Changed,
+    Done(Vec<Option<Construct>>),
+}
+
+impl<'a> FunctionBuilder<'a> {
+    /// Finds the structured constructs of the function, restructuring its control flow if needed
+    pub fn structurize(&mut self) -> Result<()> {
+        self.explicit_fallthroughs();
+
+        let mut unreachable_merges = Vec::new();
+        let limit = 16 + 4 * self.anchors.len();
+        let mut cfg = Cfg::new(&self.anchors);
+
+        for step in 0..limit {
+            if step > 0 {
+                cfg.update(&self.anchors);
+            }
+
+            self.log(format_args!("Step {step}: {} blocks", cfg.len()));
+            if let Step::Done(constructs) = self.structurize_step(&cfg, &mut unreachable_merges)? {
+                self.constructs = constructs;
+                self.reorder_blocks(&cfg);
+                return Ok(());
+            }
+        }
+
+        return Err(Error::msg(
+            "Unable to structurize the control flow of the function",
+        ));
+    }
+
+    fn log(&self, args: Arguments) {
+        if self.verbosity >= Verbosity::Operators {
+            tracing::debug!(function = self.index, "{args}");
+        }
+    }
+
+    pub(crate) fn construct_of(&self, op: &Operation) -> Option<&Construct> {
+        let offset =
+            (op as *const Operation as usize).checked_sub(self.anchors.as_ptr() as usize)?;
+        let i = offset / std::mem::size_of::<Operation>();
+        if !std::ptr::eq(self.anchors.get(i)?, op) {
+            return None;
+        }
+        return self.constructs.get(i)?.as_ref();
+    }
+
+    fn reorder_blocks(&mut self, cfg: &Cfg) {
+        let mut starts = vec![0; cfg.len()];
+        for (i, op) in self.anchors.iter().enumerate() {
+            if let Operation::Label(label) = op {
+                if let Some(block) = cfg.block(label) {
+                    starts[block] = i;
+                }
+            }
+        }
+
+        let mut order = postorder(0, cfg.len(), |x| cfg.succs[x].as_slice());
+        order.reverse();
+        order.extend((0..cfg.len()).filter(|&x| !cfg.reachable[x]));
+
+        let mut bounds = starts.iter().copied().enumerate().collect::<Vec<_>>();
+        bounds.sort_by_key(|&(_, start)| start);
+        let mut ends = vec![self.anchors.len(); cfg.len()];
+        for pair in bounds.windows(2) {
+            ends[pair[0].0] = pair[1].1;
+        }
+
+        let mut anchors = std::mem::take(&mut self.anchors)
+            .into_iter()
+            .map(Some)
+            .collect::<Vec<_>>();
+
+        let mut operators = Vec::with_capacity(anchors.len());
+        let mut constructs = Vec::with_capacity(anchors.len());
+        for block in order {
+            for (op, i) in anchors[starts[block]..ends[block]]
+                .iter_mut()
+                .zip(starts[block]..)
+            {
+                self.anchors.extend(op.take());
+                operators.push(self.operators.get(i).cloned().flatten());
+                constructs.push(self.constructs.get(i).cloned().flatten());
+            }
+        }
+
+        self.operators = operators;
+        self.constructs = constructs;
+    }
+
+    fn explicit_fallthroughs(&mut self) {
+        let anchors = std::mem::take(&mut self.anchors);
+        let operators = std::mem::take(&mut self.operators);
+        let mut terminated = true;
+
+        for (op, i) in anchors.into_iter().zip(0..) {
+            let operator = operators.get(i).cloned().flatten();
+            if let Operation::Label(label) = &op {
+                if !terminated {
+                    self.anchors.push(Operation::Branch { label: *label });
+                    self.operators.push(operator.clone());
+                }
+            }
+
+            terminated = op.is_block_terminating();
+            self.anchors.push(op);
+            self.operators.push(operator);
+        }
+    }
+
+    fn structurize_step(
+        &mut self,
+        cfg: &Cfg,
+        unreachable_merges: &mut UnreachableMerges,
+    ) -> Result<Step> {
+        let mut constructs = vec![None; self.anchors.len()];
+        let mut claimed = Vec::new();
+        let mut loops = Vec::<(usize, usize, usize)>::new();
+
+        // Loops, outermost first
+        let mut headers = (0..cfg.len())
+            .filter(|&h| cfg.reachable[h])
+            .filter(|&h| cfg.preds[h].iter().any(|&u| cfg.is_back_edge(u, h)))
+            .collect::<Vec<_>>();
+        headers.sort_by_key(|&h| (cfg.depth(h), h));
+
+        for h in headers {
+            let header = cfg.labels[h].ok_or_else(Error::unexpected)?;
+            let latches = cfg.preds[h]
+                .iter()
+                .copied()
+                .filter(|&u| cfg.is_back_edge(u, h))
+                .collect::<Vec<_>>();
+
+            // Single, unconditional back edge
+            if latches.len() > 1 || (latches[0] != h && self.is_conditional(cfg, latches[0])) {
+                self.log(format_args!(
+                    "Loop {h} has back edges from {latches:?}, adding a continue block"
+                ));
+                let continue_target = self.push_block([Operation::Branch { label: header }]);
+                for u in latches {
+                    self.retarget(cfg, u, &header, &continue_target);
+                }
+                return Ok(Step::Changed);
+            }
+            let continue_target = latches[0];
+
+            let body = cfg.natural_loop(h, continue_target);
+            let mut exits = body
+                .iter()
+                .flat_map(|&u| cfg.succs[u].iter().copied())
+                .filter(|v| !body.contains(v))
+                .collect::<Vec<_>>();
+            exits.sort_unstable();
+            exits.dedup();
+
+            // Early returns can stay inside of the loop
+            let non_terminal = exits
+                .iter()
+                .copied()
+                .filter(|&v| {
+                    !(cfg.succs[v].is_empty() && cfg.preds[v].iter().all(|u| body.contains(u)))
+                })
+                .collect::<Vec<_>>();
+            if !non_terminal.is_empty() {
+                exits = non_terminal;
+            }
+
+            let merge = match exits.iter().copied().reduce(|x, y| cfg.common_ipdom(x, y)) {
+                None => match self.unreachable_merge(cfg, Some(&header), unreachable_merges) {
+                    Some(merge) => merge,
+                    None => return Ok(Step::Changed),
+                },
+                Some(m) if m == cfg.len() => {
+                    self.log(format_args!(
+                        "Exits {exits:?} of loop {h} never meet, dispatching them from a new merge block"
+                    ));
+                    self.dispatch(cfg, &body, &exits)?;
+                    return Ok(Step::Changed);
+                }
+                Some(m) => {
+                    let shared = cfg.has_outside_preds(m, h) || claimed.contains(&m);
+                    if shared && exits.len() > 1 {
+                        self.log(format_args!(
+                            "Merge block {m} of loop {h} is shared, dispatching exits {exits:?} from a new merge block"
+                        ));
+                        self.dispatch(cfg, &body, &exits)?;
+                        return Ok(Step::Changed);
+                    } else if shared || m == continue_target {
+                        self.log(format_args!(
+                            "Merge block {m} of loop {h} is shared, forwarding the loop into it"
+                        ));
+                        self.forward(cfg, m, |u| body.contains(&u))?;
+                        return Ok(Step::Changed);
+                    }
+                    m
+                }
+            };
+
+            // The header branches either to the merge or the continue target, or unconditionally
+            if let Some((t, f)) = self.conditional_targets(cfg, h) {
+                if ![t, f].iter().any(|&x| x == merge || x == continue_target) {
+                    self.log(format_args!(
+                        "Header of loop {h} branches inside of the loop, moving the branch into a new block"
+                    ));
+                    self.split_terminator(cfg, h)?;
+                    return Ok(Step::Changed);
+                }
+            }
+
+            self.log(format_args!(
+                "Loop {h} merges into {merge}, continuing at {continue_target}"
+            ));
+            claimed.extend([merge, continue_target]);
+            loops.push((h, merge, continue_target));
+            constructs[cfg.terminators[h].ok_or_else(Error::unexpected)?] = Some(Construct::Loop {
+                merge: cfg.labels[merge].ok_or_else(Error::unexpected)?,
+                continue_target: cfg.labels[continue_target].ok_or_else(Error::unexpected)?,
+            });
+        }
+
+        // Selections, outermost first
+        let mut headers = (0..cfg.len())
+            .filter(|&x| cfg.reachable[x])
+            .filter(|&x| self.is_conditional(cfg, x))
+            .filter(|x| !loops.iter().any(|(h, ..)| h == x))
+            .collect::<Vec<_>>();
+        headers.sort_by_key(|&x| (cfg.depth(x), x));
+
+        for x in headers {
+            let (t, f) = self
+                .conditional_targets(cfg, x)
+                .ok_or_else(Error::unexpected)?;
+
+            // Breaking out of, or continuing, the innermost loop doesn't need a selection
+            let innermost = loops
+                .iter()
+                .filter(|(h, merge, _)| cfg.dominates(*h, x) && !cfg.dominates(*merge, x))
+                .max_by_key(|(h, ..)| cfg.depth(*h));
+            if let Some((_, merge, continue_target)) = innermost {
+                if [t, f].iter().any(|x| x == merge || x == continue_target) {
+                    self.log(format_args!(
+                        "Branch of block {x} breaks out of, or continues, its loop without a selection"
+                    ));
+                    continue;
+                }
+            }
+
+            // Branches that only meet again when leaving the innermost loop never merge inside of it
+            let leaves_loop = |m: usize| {
+                innermost
+                    .is_some_and(|(_, merge, continue_target)| m == *merge || m == *continue_target)
+            };
+
+            let merge = match cfg.ipdoms[x] {
+                Some(m) if m < cfg.len() && !leaves_loop(m) => {
+                    if claimed.contains(&m) || cfg.has_outside_preds(m, x) {
+                        self.log(format_args!(
+                            "Merge block {m} of selection {x} is shared, forwarding the selection into it"
+                        ));
+                        self.forward(cfg, m, |u| cfg.dominates(x, u))?;
+                        return Ok(Step::Changed);
+                    }
+                    m
+                }
+                _ => {
+                    match self.unreachable_merge(cfg, cfg.labels[x].as_ref(), unreachable_merges) {
+                        Some(merge) => merge,
+                        None => return Ok(Step::Changed),
+                    }
+                }
+            };
+
+            self.log(format_args!("Selection {x} merges into {merge}"));
+            claimed.push(merge);
+            constructs[cfg.terminators[x].ok_or_else(Error::unexpected)?] =
+                Some(Construct::Selection {
+                    merge: cfg.labels[merge].ok_or_else(Error::unexpected)?,
+                });
+        }
+
+        return Ok(Step::Done(constructs));
+    }
+
+    fn is_conditional(&self, cfg: &Cfg, block: usize) -> bool {
+        return self.conditional_targets(cfg, block).is_some();
+    }
+
+    fn conditional_targets(&self, cfg: &Cfg, block: usize) -> Option<(usize, usize)> {
+        match &self.anchors[cfg.terminators[block]?] {
+            Operation::BranchConditional {
+                true_label,
+                false_label,
+                ..
+            } => Some((cfg.block(true_label)?, cfg.block(false_label)?)),
+            _ => None,
+        }
+    }
+
+    fn push_block(&mut self, ops: impl IntoIterator<Item = Operation>) -> Handle<Label> {
+        let label = self.new_label();
+        self.anchors.push(Operation::Label(label));
+        self.anchors.extend(ops);
+        return label;
+    }
+
+    fn retarget(&mut self, cfg: &Cfg, block: usize, from: &Handle<Label>, to: &Handle<Label>) {
+        let Some(i) = cfg.terminators[block] else {
+            return;
+        };
+
+        match &mut self.anchors[i] {
+            Operation::Branch { label } if label == from => *label = *to,
+            Operation::BranchConditional {
+                true_label,
+                false_label,
+                ..
+            } => {
+                for label in [true_label, false_label] {
+                    if label == from {
+                        *label = *to
+                    }
+                }
+            }
+            _ => {}
+        }
+    }
+
+    fn forward(&mut self, cfg: &Cfg, merge: usize, sources: impl Fn(usize) -> bool) -> Result<()> {
+        let target = cfg.labels[merge].ok_or_else(Error::unexpected)?;
+        let forward = self.push_block([Operation::Branch { label: target }]);
+
+        for &u in cfg.preds[merge].iter() {
+            if cfg.reachable[u] && sources(u) && !cfg.is_back_edge(u, merge) {
+                self.retarget(cfg, u, &target, &forward);
+            }
+        }
+        return Ok(());
+    }
+
+    fn dispatch(&mut self, cfg: &Cfg, body: &[usize], exits: &[usize]) -> Result<()> {
+        let selector = Rc::new(Pointer::new_variable(
+            PointerSize::Skinny,
+            StorageClass::Function,
+            ScalarType::I32,
+            None,
+            [],
+        ));
+
+        // Declare the variable at the start of the function
+        let mut initializers = std::mem::take(&mut self.variable_initializers).into_vec();
+        initializers.push(Operation::Store {
+            target: selector.clone(),
+            value: Value::Integer(Rc::new(Integer::new_constant_u32(0))),
+            log2_alignment: None,
+        });
+        self.variable_initializers = initializers.into_boxed_slice();
+
+        let targets = exits
+            .iter()
+            .map(|&v| cfg.labels[v].ok_or_else(Error::unexpected))
+            .collect::<Result<Vec<_>>>()?;
+
+        let dispatch = self.new_label();
+        for (&v, i) in exits.iter().zip(0..) {
+            let exit = self.push_block([
+                Operation::Store {
+                    target: selector.clone(),
+                    value: Value::Integer(Rc::new(Integer::new_constant_u32(i))),
+                    log2_alignment: None,
+                },
+                Operation::Branch { label: dispatch },
+            ]);
+
+            for &u in cfg.preds[v].iter().filter(|u| body.contains(u)) {
+                self.retarget(cfg, u, &targets[i as usize], &exit);
+            }
+        }
+
+        self.anchors.push(Operation::Label(dispatch));
+        for (&label, i) in targets.iter().zip(0..) {
+            if i + 1 == targets.len() {
+                self.anchors.push(Operation::Branch { label });
+                break;
+            }
+
+            let loaded = Integer::new(IntegerSource::Loaded {
+                pointer: selector.clone(),
+                log2_alignment: None,
+            });
+            let condition = Bool::new(BoolSource::IntEquality {
+                kind: Equality::Eq,
+                op1: Rc::new(loaded),
+                op2: Rc::new(Integer::new_constant_u32(i as u32)),
+            });
+
+            let next = self.new_label();
+            self.anchors.push(Operation::BranchConditional {
+                condition: Rc::new(condition),
+                true_label: label,
+                false_label: next,
+            });
+            self.anchors.push(Operation::Label(next));
+        }
+
+        return Ok(());
+    }
+
+    fn split_terminator(&mut self, cfg: &Cfg, block: usize) -> Result<()> {
+        let i = cfg.terminators[block].ok_or_else(Error::unexpected)?;
+        let operator = self.operators.get(i).cloned().flatten();
+        let terminator =
+            std::mem::replace(&mut self.anchors[i], Operation::Unreachable { panic: None });
+
+        let label = self.push_block([terminator]);
+        self.anchors[i] = Operation::Branch { label };
+
+        self.operators.resize(self.anchors.len() - 1, None);
+        self.operators.push(operator);
+        return Ok(());
+    }
+
+    fn unreachable_merge(
+        &mut self,
+        cfg: &Cfg,
+        header: Option<&Handle<Label>>,
+        unreachable_merges: &mut UnreachableMerges,
+    ) -> Option<usize> {
+        let existing = unreachable_merges.iter().find(|(x, _)| match (x, header) {
+            (Some(x), Some(header)) => x == header,
+            (None, None) => true,
+            _ => false,
+        });
+
+        if let Some((_, merge)) = existing {
+            return cfg.block(merge);
+        }
+
+        self.log(format_args!(
+            "Construct at block {} has no merge block, adding an unreachable one",
+            header.and_then(|x| cfg.block(x)).unwrap_or_default()
+        ));
+        let merge = self.push_block([Operation::Unreachable { panic: None }]);
+        unreachable_merges.push((header.copied(), merge));
+        return None;
+    }
+}
+
+impl Cfg {
+    fn new(anchors: &[Operation]) -> Self {
+        let mut result = Self {
+            labels: vec![None],
+            blocks: HashMap::new(),
+            terminators: vec![None],
+            succs: Vec::new(),
+            preds: Vec::new(),
+            reachable: Vec::new(),
+            idoms: Vec::new(),
+            ipdoms: Vec::new(),
+            scanned: 0,
+        };
+
+        result.update(anchors);
+        return result;
+    }
+
+    fn update(&mut self, anchors: &[Operation]) {
+        let mut open = self.terminators.last().is_some_and(|x| x.is_none());
+        for (op, i) in anchors[self.scanned..].iter().zip(self.scanned..) {
+            if let Operation::Label(label) = op {
+                self.blocks.insert(*label, self.labels.len());
+                self.labels.push(Some(*label));
+                self.terminators.push(None);
+                open = true;
+            } else if open && op.is_block_terminating() {
+                *self.terminators.last_mut().unwrap() = Some(i);
+                open = false;
+            }
+        }
+        self.scanned = anchors.len();
+
+        self.succs = vec![Vec::new(); self.len()];
+        for (block, terminator) in self.terminators.iter().enumerate() {
+            let targets = match terminator.map(|i| &anchors[i]) {
+                Some(Operation::Branch { label }) => vec![label],
+                Some(Operation::BranchConditional {
+                    true_label,
+                    false_label,
+                    ..
+                }) => vec![true_label, false_label],
+                _ => Vec::new(),
+            };
+
+            for label in targets {
+                if let Some(&target) = self.blocks.get(label) {
+                    if !self.succs[block].contains(&target) {
+                        self.succs[block].push(target)
+                    }
+                }
+            }
+        }
+
+        self.preds = vec![Vec::new(); self.len()];
+        for (block, succs) in self.succs.iter().enumerate() {
+            for &succ in succs {
+                self.preds[succ].push(block);
+            }
+        }
+
+        self.analyze();
+    }
+
+    fn len(&self) -> usize {
+        return self.labels.len();
+    }
+
+    fn block(&self, label: &Handle<Label>) -> Option<usize> {
+        return self.blocks.get(label).copied();
+    }
+
+    fn analyze(&mut self) {
+        let n = self.len();
+
+        let order = postorder(0, n + 1, |x| self.succs[x].as_slice());
+        self.reachable = vec![false; n];
+        for &x in order.iter() {
+            self.reachable[x] = true;
+        }
+        self.idoms = idoms(0, &order, |x| self.preds[x].as_slice(), n);
+
+        // Post-dominators are the dominators of the reversed graph, starting from a virtual exit node
+        // that every exiting block branches into. Blocks that never exit branch into it too.
+        let exit = n;
+        let mut reverse_succs = vec![Vec::new(); n + 1];
+        let mut reverse_preds = vec![Vec::new(); n + 1];
+        for x in (0..n).filter(|&x| self.reachable[x]) {
+            for &y in self.succs[x].iter() {
+                reverse_succs[y].push(x);
+                reverse_preds[x].push(y);
+            }
+            if self.succs[x].is_empty() {
+                reverse_succs[exit].push(x);
+                reverse_preds[x].push(exit);
+            }
+        }
+
+        let mut reverse_order = postorder(exit, n + 1, |x| reverse_succs[x].as_slice());
+        for x in (0..n).filter(|&x| self.reachable[x]) {
+            if !reverse_order.contains(&x) {
+                reverse_succs[exit].push(x);
+                reverse_preds[x].push(exit);
+                reverse_order = postorder(exit, n + 1, |x| reverse_succs[x].as_slice());
+            }
+        }
+
+        self.ipdoms = idoms(exit, &reverse_order, |x| reverse_preds[x].as_slice(), n + 1);
+        self.ipdoms.truncate(n);
+    }
+
+    fn dominates(&self, a: usize, mut b: usize) -> bool {
+        loop {
+            if a == b {
+                return true;
+            }
+            match self.idoms.get(b).copied().flatten() {
+                Some(idom) if idom != b => b = idom,
+                _ => return false,
+            }
+        }
+    }
+
+    fn depth(&self, mut x: usize) -> usize {
+        let mut depth = 0;
+        while let Some(idom) = self.idoms[x].filter(|&idom| idom != x) {
+            depth += 1;
+            x = idom;
+        }
+        return depth;
+    }
+
+    fn is_back_edge(&self, from: usize, to: usize) -> bool {
+        return self.reachable[from] && self.dominates(to, from);
+    }
+
+    fn has_outside_preds(&self, block: usize, header: usize) -> bool {
+        return self.preds[block].iter().any(|&u| {
+            self.reachable[u] && !self.dominates(header, u) && !self.is_back_edge(u, block)
+        });
+    }
+
+    fn natural_loop(&self, header: usize, latch: usize) -> Vec<usize> {
+        let mut body = vec![header];
+        let mut pending = vec![latch];
+        while let Some(x) = pending.pop() {
+            if body.contains(&x) {
+                continue;
+            }
+            body.push(x);
+            pending.extend(self.preds[x].iter().filter(|&&u| self.reachable[u]));
+        }
+        return body;
+    }
+
+    fn common_ipdom(&self, mut x: usize, mut y: usize) -> usize {
+        let depth = |mut x: usize| {
+            let mut depth = 0;
+            while let Some(ipdom) = self.ipdoms.get(x).copied().flatten() {
+                depth += 1;
+                x = ipdom;
+            }
+            depth
+        };
+
+        let (mut dx, mut dy) = (depth(x), depth(y));
+        while x != y {
+            if dx >= dy {
+                match self.ipdoms.get(x).copied().flatten() {
+                    Some(ipdom) => x = ipdom,
+                    None => return self.len(),
+                }
+                dx -= 1;
+            } else {
+                match self.ipdoms.get(y).copied().flatten() {
+                    Some(ipdom) => y = ipdom,
+                    None => return self.len(),
+                }
+                dy -= 1;
+            }
+        }
+        return x;
+    }
+}
+
+fn postorder<'a>(root: usize, n: usize, succs: impl Fn(usize) -> &'a [usize]) -> Vec<usize> {
+    let mut visited = vec![false; n];
+    let mut result = Vec::new();
+    let mut stack = vec![(root, 0)];
+    visited[root] = true;
+
+    while let Some((x, i)) = stack.last_mut() {
+        match succs(*x).get(*i) {
+            Some(&y) => {
+                *i += 1;
+                if !visited[y] {
+                    visited[y] = true;
+                    stack.push((y, 0));
+                }
+            }
+            None => {
+                result.push(*x);
+                stack.pop();
+            }
+        }
+    }
+
+    return result;
+}
+
+fn idoms<'a>(
+    root: usize,
+    postorder: &[usize],
+    preds: impl Fn(usize) -> &'a [usize],
+    n: usize,
+) -> Vec<Option<usize>> {
+    let mut index = vec![usize::MAX; n];
+    for (i, &x) in postorder.iter().enumerate() {
+        index[x] = i;
+    }
+
+    let mut result = vec![None::<usize>; n];
+    result[root] = Some(root);
+
+    let mut changed = true;
+    while changed {
+        changed = false;
+        for &x in postorder.iter().rev().filter(|&&x| x != root) {
+            let mut new_idom: Option<usize> = None;
+            for &p in preds(x).iter().filter(|&&p| result[p].is_some()) {
+                new_idom = Some(match new_idom {
+                    None => p,
+                    Some(mut a) => {
+                        let mut b = p;
+                        while a != b {
+                            while index[a] < index[b] {
+                                a = result[a].unwrap();
+                            }
+                            while index[b] < index[a] {
+                                b = result[b].unwrap();
+                            }
+                        }
+                        a
+                    }
+                });
+            }
+
+            if new_idom.is_some() && result[x] != new_idom {
+                result[x] = new_idom;
+                changed = true;
+            }
+        }
+    }
+
+    return result;
+}
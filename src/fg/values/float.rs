@@ -71,6 +71,20 @@ pub enum UnarySource {
     Trunc,
     Nearest,
     Sqrt,
+    // Transcendental functions, only available through the extended instruction sets
+    Sin,
+    Cos,
+    Tan,
+    Asin,
+    Acos,
+    Atan,
+    Sinh,
+    Cosh,
+    Tanh,
+    Exp,
+    Exp2,
+    Log,
+    Log2,
 }
 
 #[derive(Debug, Clone, Copy, PartialEq, Eq)]
@@ -84,6 +98,9 @@ pub enum BinarySource {
     // If any is NaN, return NaN
     Min,
     Max,
+    // Transcendental functions, only available through the extended instruction sets
+    Pow,
+    Atan2,
 }
 
 #[derive(Debug, Clone)]
@@ -124,20 +141,22 @@ impl Float {
     }
 
     pub fn kind(&self) -> Result<FloatKind> {
-        return Ok(match &self.source {
+        // Chains of operations can be arbitrarily long, so they are followed iteratively
+        let mut float = self;
+        loop {
+            float = match &float.source {
+                FloatSource::Select { true_value, .. } => true_value,
+                FloatSource::Unary { op1, .. } | FloatSource::Binary { op1, .. } => op1,
+                _ => break,
+            };
+        }
+
+        return Ok(match &float.source {
             FloatSource::Loaded { pointer, .. } => match &pointer.pointee {
                 Type::Scalar(ScalarType::F32) => FloatKind::Single,
                 Type::Scalar(ScalarType::F64) => FloatKind::Double,
                 _ => return Err(Error::unexpected()),
             },
-            FloatSource::Select {
-                true_value,
-                false_value,
-                ..
-            } => {
-                debug_assert_eq!(true_value.kind()?, false_value.kind()?);
-                return true_value.kind();
-            }
             FloatSource::Extracted { vector, .. } => match vector.element_type {
                 ScalarType::F32 => FloatKind::Single,
                 ScalarType::F64 => FloatKind::Double,
@@ -156,11 +175,8 @@ impl Float {
             }
             FloatSource::Conversion(ConversionSource::FromInteger { kind, .. })
             | FloatSource::Conversion(ConversionSource::Bitcast { kind, .. }) => *kind,
-            FloatSource::Unary { op1, .. } => op1.kind()?,
-            FloatSource::Binary { op1, op2, .. } => {
-                let res = op1.kind()?;
-                debug_assert_eq!(res, op2.kind()?);
-                res
+            FloatSource::Select { .. } | FloatSource::Unary { .. } | FloatSource::Binary { .. } => {
+                return Err(Error::unexpected())
             }
         });
     }
@@ -233,7 +249,7 @@ impl Float {
             _ => Self {
                 translation: Cell::new(None),
                 source: FloatSource::Unary {
-                    source: UnarySource::Ceil,
+                    source: UnarySource::Floor,
                     op1: self,
                 },
             },
@@ -247,7 +263,7 @@ impl Float {
             _ => Self {
                 translation: Cell::new(None),
                 source: FloatSource::Unary {
-                    source: UnarySource::Ceil,
+                    source: UnarySource::Trunc,
                     op1: self,
                 },
             },
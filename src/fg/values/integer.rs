@@ -41,6 +41,10 @@ pub enum IntegerSource {
         pointer: Rc<Pointer>,
         log2_alignment: Option<u32>,
     },
+    /// Adds one to the counter atomically, returning its previous value
+    AtomicIncrement {
+        counter: Rc<Pointer>,
+    },
     Select {
         selector: Rc<Bool>,
         true_value: Rc<Integer>,
@@ -50,6 +54,11 @@ pub enum IntegerSource {
         vector: Rc<Vector>,
         index: Rc<Integer>,
     },
+    /// Element of a constant array of 32-bit words, which can be indexed at runtime
+    ConstantArrayElement {
+        words: Rc<[u32]>,
+        index: Rc<Integer>,
+    },
     FunctionCall {
         function_id: Rc<Cell<Option<rspirv::spirv::Word>>>,
         args: Box<[Value]>,
@@ -158,6 +167,16 @@ impl Integer {
         };
     }
 
+    /// Creates a pointer-sized constant, failing if the value doesn't fit in the module's address space
+    pub fn try_new_constant_usize(value: u64, module: &ModuleBuilder) -> Result<Self> {
+        return match module.wasm_memory64 {
+            true => Ok(Self::new_constant_u64(value)),
+            false => u32::try_from(value)
+                .map(Self::new_constant_u32)
+                .map_err(|_| Error::msg(format!("Address '{value}' exceeds 32-bit memory"))),
+        };
+    }
+
     pub fn new_constant_isize(value: i32, module: &ModuleBuilder) -> Self {
         return match module.wasm_memory64 {
             true => Self::new_constant_i64(value as i64),
@@ -166,26 +185,30 @@ impl Integer {
     }
 
     pub fn kind(&self, module: &ModuleBuilder) -> Result<IntegerKind> {
-        return Ok(match &self.source {
+        // Chains of operations can be arbitrarily long, so they are followed iteratively
+        let mut integer = self;
+        loop {
+            integer = match &integer.source {
+                IntegerSource::Select { true_value, .. } => true_value,
+                IntegerSource::Unary { op1, .. } | IntegerSource::Binary { op1, .. } => op1,
+                _ => break,
+            };
+        }
+
+        return Ok(match &integer.source {
             IntegerSource::Loaded { pointer, .. } => match &pointer.pointee {
                 Type::Scalar(ScalarType::I32) => IntegerKind::Short,
                 Type::Scalar(ScalarType::I64) => IntegerKind::Long,
                 _ => return Err(Error::unexpected()),
             },
-            IntegerSource::Select {
-                true_value,
-                false_value,
-                ..
-            } => {
-                debug_assert_eq!(true_value.kind(module)?, false_value.kind(module)?);
-                return true_value.kind(module);
-            }
             IntegerSource::Extracted { vector, .. } => match vector.element_type {
                 ScalarType::I32 => IntegerKind::Short,
                 ScalarType::I64 => IntegerKind::Long,
                 _ => return Err(Error::unexpected()),
             },
-            IntegerSource::ArrayLength { .. } => IntegerKind::Short,
+            IntegerSource::ArrayLength { .. }
+            | IntegerSource::AtomicIncrement { .. }
+            | IntegerSource::ConstantArrayElement { .. } => IntegerKind::Short,
             IntegerSource::FunctionParam(kind)
             | IntegerSource::FunctionCall { kind, .. }
             | IntegerSource::Conversion(ConversionSource::FromBool(_, kind)) => *kind,
@@ -210,12 +233,9 @@ impl Integer {
                     _ => return Err(Error::unexpected()),
                 }
             }
-            IntegerSource::Unary { op1, .. } => op1.kind(module)?,
-            IntegerSource::Binary { op1, op2, .. } => {
-                let res = op1.kind(module)?;
-                debug_assert_eq!(res, op2.kind(module)?);
-                res
-            }
+            IntegerSource::Select { .. }
+            | IntegerSource::Unary { .. }
+            | IntegerSource::Binary { .. } => return Err(Error::unexpected()),
         });
     }
 
@@ -296,10 +316,25 @@ impl Integer {
         match storage_class {
             StorageClass::Generic => module
                 .capabilities
-                .require_mut(Capability::GenericPointer)?,
+                .require_mut_by(Capability::GenericPointer, || {
+                    Some(String::from("a conversion from integer to generic pointer"))
+                })?,
             _ => {}
         }
 
+        let integer_bits = match self.kind(module)? {
+            IntegerKind::Short => 32,
+            IntegerKind::Long => 64,
+        };
+
+        if let Some(bits) = module.spirv_address_bits(storage_class) {
+            if bits != integer_bits {
+                return Err(Error::msg(format!(
+                    "Cannot convert a {integer_bits}-bit integer into a {bits}-bit pointer"
+                )));
+            }
+        }
+
         let ptr = Pointer::new(
             size.to_pointer_kind(),
             storage_class,
@@ -425,6 +460,17 @@ impl Integer {
 
             (Some(ConstantSource::Short(0) | ConstantSource::Long(0)), _) => return Ok(self),
 
+            // `INT_MIN / -1` overflows, which is undefined without division traps, so it isn't folded
+            (Some(ConstantSource::Short(0x8000_0000)), Some(ConstantSource::Short(u32::MAX)))
+            | (
+                Some(ConstantSource::Long(0x8000_0000_0000_0000)),
+                Some(ConstantSource::Long(u64::MAX)),
+            ) => IntegerSource::Binary {
+                source: BinarySource::SDiv,
+                op1: self,
+                op2: rhs,
+            },
+
             (Some(ConstantSource::Short(x)), Some(ConstantSource::Short(y))) => unsafe {
                 IntegerSource::Constant(ConstantSource::Short(transmute(
                     transmute::<_, i32>(x) / transmute::<_, i32>(y),
@@ -520,13 +566,13 @@ impl Integer {
 
             (Some(ConstantSource::Short(x)), Some(ConstantSource::Short(y))) => unsafe {
                 IntegerSource::Constant(ConstantSource::Short(transmute(
-                    transmute::<_, i32>(x) % transmute::<_, i32>(y),
+                    transmute::<_, i32>(x).wrapping_rem(transmute::<_, i32>(y)),
                 )))
             },
 
             (Some(ConstantSource::Long(x)), Some(ConstantSource::Long(y))) => unsafe {
                 IntegerSource::Constant(ConstantSource::Long(transmute(
-                    transmute::<_, i64>(x) % transmute::<_, i64>(y),
+                    transmute::<_, i64>(x).wrapping_rem(transmute::<_, i64>(y)),
                 )))
             },
 
@@ -674,17 +720,18 @@ impl Integer {
             (x, y) if x != y => return Err(Error::mismatch(x, y)),
             _ => {}
         }
+        let rhs = Self::shift_amount(rhs, module)?;
 
         let source = match (self.get_constant_value()?, rhs.get_constant_value()?) {
             (Some(ConstantSource::Short(0) | ConstantSource::Long(0)), _)
             | (_, Some(ConstantSource::Short(0) | ConstantSource::Long(0))) => return Ok(self),
 
             (Some(ConstantSource::Short(x)), Some(ConstantSource::Short(y))) => {
-                IntegerSource::Constant(ConstantSource::Short(x << y))
+                IntegerSource::Constant(ConstantSource::Short(x.wrapping_shl(y)))
             }
 
             (Some(ConstantSource::Long(x)), Some(ConstantSource::Long(y))) => {
-                IntegerSource::Constant(ConstantSource::Long(x << y))
+                IntegerSource::Constant(ConstantSource::Long(x.wrapping_shl(y as u32)))
             }
 
             _ => IntegerSource::Binary {
@@ -705,6 +752,7 @@ impl Integer {
             (x, y) if x != y => return Err(Error::mismatch(x, y)),
             _ => {}
         }
+        let rhs = Self::shift_amount(rhs, module)?;
 
         let source = match (self.get_constant_value()?, rhs.get_constant_value()?) {
             (Some(ConstantSource::Short(0) | ConstantSource::Long(0)), _)
@@ -712,13 +760,13 @@ impl Integer {
 
             (Some(ConstantSource::Short(x)), Some(ConstantSource::Short(y))) => unsafe {
                 IntegerSource::Constant(ConstantSource::Short(transmute(
-                    transmute::<_, i32>(x) >> transmute::<_, i32>(y),
+                    transmute::<_, i32>(x).wrapping_shr(y),
                 )))
             },
 
             (Some(ConstantSource::Long(x)), Some(ConstantSource::Long(y))) => unsafe {
                 IntegerSource::Constant(ConstantSource::Long(transmute(
-                    transmute::<_, i64>(x) >> transmute::<_, i64>(y),
+                    transmute::<_, i64>(x).wrapping_shr(y as u32),
                 )))
             },
 
@@ -745,17 +793,18 @@ impl Integer {
             (x, y) if x != y => return Err(Error::mismatch(x, y)),
             _ => {}
         }
+        let rhs = Self::shift_amount(rhs, module)?;
 
         let source = match (self.get_constant_value()?, rhs.get_constant_value()?) {
             (Some(ConstantSource::Short(0) | ConstantSource::Long(0)), _)
             | (_, Some(ConstantSource::Short(0) | ConstantSource::Long(0))) => return Ok(self),
 
             (Some(ConstantSource::Short(x)), Some(ConstantSource::Short(y))) => {
-                IntegerSource::Constant(ConstantSource::Short(x >> y))
+                IntegerSource::Constant(ConstantSource::Short(x.wrapping_shr(y)))
             }
 
             (Some(ConstantSource::Long(x)), Some(ConstantSource::Long(y))) => {
-                IntegerSource::Constant(ConstantSource::Long(x >> y))
+                IntegerSource::Constant(ConstantSource::Long(x.wrapping_shr(y as u32)))
             }
 
             (_, Some(x)) if optimize_away => match &self.source {
@@ -784,6 +833,19 @@ impl Integer {
         }));
     }
 
+    fn shift_amount(rhs: Rc<Integer>, module: &ModuleBuilder) -> Result<Rc<Integer>> {
+        return match rhs.get_constant_value()? {
+            Some(ConstantSource::Short(x)) if x < 32 => Ok(rhs),
+            Some(ConstantSource::Long(x)) if x < 64 => Ok(rhs),
+            Some(ConstantSource::Short(x)) => Ok(Rc::new(Integer::new_constant_u32(x & 31))),
+            Some(ConstantSource::Long(x)) => Ok(Rc::new(Integer::new_constant_u64(x & 63))),
+            None => match rhs.kind(module)? {
+                IntegerKind::Short => rhs.and(Rc::new(Integer::new_constant_u32(31)), module),
+                IntegerKind::Long => rhs.and(Rc::new(Integer::new_constant_u64(63)), module),
+            },
+        };
+    }
+
     pub fn clz(self: Rc<Self>) -> Result<Rc<Self>> {
         let source = match self.get_constant_value()? {
             Some(ConstantSource::Short(x)) => {
@@ -1,14 +1,14 @@
 use super::{
     bool::{Bool, BoolSource},
     float::{Float, FloatSource},
-    integer::{Integer, IntegerSource},
+    integer::{ConstantSource, Integer, IntegerSource},
     vector::{Vector, VectorSource},
     Value,
 };
 use crate::{
     decorator::VariableDecorator,
     error::{Error, Result},
-    fg::{block::BlockBuilder, module::ModuleBuilder, Operation},
+    fg::{block::BlockBuilder, function::BufferKind, module::ModuleBuilder, Operation},
     r#type::{CompositeType, PointerSize, ScalarType, Type},
 };
 use spirv::StorageClass;
@@ -63,12 +63,15 @@ impl Pointer {
     }
 
     pub fn is_structured(&self) -> bool {
-        return matches!(
-            self.storage_class,
-            StorageClass::Uniform
-                | StorageClass::StorageBuffer
-                | StorageClass::PhysicalStorageBuffer
-        );
+        return !matches!(self.source, PointerSource::Member { .. })
+            && matches!(
+                self.storage_class,
+                StorageClass::Uniform
+                    | StorageClass::StorageBuffer
+                    | StorageClass::PhysicalStorageBuffer
+                    | StorageClass::PushConstant
+                    | StorageClass::ShaderRecordBufferKHR
+            );
     }
 
     pub fn new_variable(
@@ -85,6 +88,24 @@ impl Pointer {
             PointerSource::Variable {
                 init,
                 decorators: decorators.into(),
+                buffer: BufferKind::Storage,
+            },
+        );
+    }
+
+    /// Creates a uniform buffer (`Uniform` storage class with a `Block` decorated structure)
+    pub fn new_uniform_buffer(
+        ty: impl Into<Type>,
+        decorators: impl Into<Box<[VariableDecorator]>>,
+    ) -> Self {
+        return Self::new(
+            PointerKind::skinny(),
+            StorageClass::Uniform,
+            ty,
+            PointerSource::Variable {
+                init: None,
+                decorators: decorators.into(),
+                buffer: BufferKind::Uniform,
             },
         );
     }
@@ -122,6 +143,24 @@ impl Pointer {
         }
     }
 
+    /// Whether the pointer points into a variable decorated as `Volatile`
+    pub fn is_volatile(&self) -> bool {
+        return match &self.source {
+            PointerSource::Casted { prev } | PointerSource::Member { parent: prev, .. } => {
+                prev.is_volatile()
+            }
+            PointerSource::Select {
+                true_value,
+                false_value,
+                ..
+            } => true_value.is_volatile() || false_value.is_volatile(),
+            PointerSource::Variable { decorators, .. } => decorators
+                .iter()
+                .any(|x| matches!(x, VariableDecorator::Volatile)),
+            _ => false,
+        };
+    }
+
     pub fn cast(self: Rc<Self>, new_pointee: impl Into<Type>) -> Rc<Pointer> {
         let new_pointee = new_pointee.into();
         if self.pointee == new_pointee {
@@ -224,6 +263,14 @@ impl Pointer {
                 },
             }
             .into(),
+
+            Type::Composite(CompositeType::Matrix(..)) => {
+                return Err(Error::msg("Matrices cannot be loaded as values"))
+            }
+
+            Type::Composite(CompositeType::Struct(_)) => {
+                return Err(Error::msg("Structs cannot be loaded as values"))
+            }
         };
 
         return Ok(result);
@@ -235,6 +282,14 @@ impl Pointer {
         module: &ModuleBuilder,
     ) -> Result<Self> {
         let byte_offset = byte_offset.into();
+        if let PointerSource::Casted { prev } = &self.source {
+            if matches!(prev.pointee, Type::Composite(CompositeType::Struct(_))) {
+                return prev
+                    .clone()
+                    .access_member(byte_offset, self.pointee.clone(), module);
+            }
+        }
+
         let kind = match &self.kind {
             PointerKind::Skinny => {
                 todo!()
@@ -257,6 +312,80 @@ impl Pointer {
         ));
     }
 
+    /// Returns a pointer to the structure member found at `byte_offset`, checking it against the declared layout
+    pub fn access_member(
+        self: Rc<Self>,
+        byte_offset: Rc<Integer>,
+        ty: Type,
+        module: &ModuleBuilder,
+    ) -> Result<Self> {
+        let composite = match &self.pointee {
+            Type::Composite(composite @ CompositeType::Struct(_)) => composite,
+            _ => return Err(Error::unexpected()),
+        };
+
+        let (parent, byte_offset) = match self.clone().take_byte_offset() {
+            (parent, Some(offset)) => (parent, offset.add(byte_offset, module)?),
+            (parent, None) => (parent, byte_offset),
+        };
+
+        let byte_offset = match byte_offset.get_constant_value()? {
+            Some(ConstantSource::Short(x)) => x as u64,
+            Some(ConstantSource::Long(x)) => x,
+            None => {
+                return Err(Error::msg(
+                    "Structure members can only be accessed through constant offsets",
+                ))
+            }
+        };
+
+        let (element_offset, member_offset) = match parent.is_fat() {
+            false => (0, byte_offset),
+            true => {
+                let size = composite.byte_size().ok_or_else(Error::unexpected)? as u64;
+                (byte_offset - byte_offset % size, byte_offset % size)
+            }
+        };
+
+        let (index, member) = u32::try_from(member_offset)
+            .ok()
+            .and_then(|offset| composite.member_at(offset))
+            .ok_or_else(|| {
+                Error::msg(format!(
+                    "No structure member found at offset {member_offset}"
+                ))
+            })?;
+
+        if member.ty != ty {
+            return Err(Error::mismatch(&member.ty, ty));
+        }
+
+        let parent = match parent.is_fat() {
+            false => parent,
+            true => {
+                let element_offset = u32::try_from(element_offset).map_err(Error::custom)?;
+                Rc::new(Pointer::new(
+                    PointerKind::Fat {
+                        byte_offset: Some(Rc::new(Integer::new_constant_usize(
+                            element_offset,
+                            module,
+                        ))),
+                    },
+                    parent.storage_class,
+                    parent.pointee.clone(),
+                    parent.source.clone(),
+                ))
+            }
+        };
+
+        return Ok(Pointer::new(
+            PointerKind::Skinny,
+            self.storage_class,
+            ty,
+            PointerSource::Member { parent, index },
+        ));
+    }
+
     pub fn physical_bytes(&self, module: &ModuleBuilder) -> Option<u32> {
         return module.spirv_address_bytes(self.storage_class);
     }
@@ -274,6 +403,10 @@ pub enum PointerSource {
     Casted {
         prev: Rc<Pointer>,
     },
+    Member {
+        parent: Rc<Pointer>,
+        index: u32,
+    },
     Loaded {
         pointer: Rc<Pointer>,
         log2_alignment: Option<u32>,
@@ -281,5 +414,7 @@ pub enum PointerSource {
     Variable {
         init: Option<Value>,
         decorators: Box<[VariableDecorator]>,
+        /// Kind of buffer the variable's structure represents, if it's structured
+        buffer: BufferKind,
     },
 }
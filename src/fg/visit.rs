@@ -0,0 +1,301 @@
+use super::{
+    function::FunctionBuilder,
+    module::ModuleBuilder,
+    values::{
+        bool::{Bool, BoolSource},
+        float::{ConversionSource as FloatConversionSource, Float, FloatSource},
+        integer::{ConversionSource as IntegerConversionSource, Integer, IntegerSource},
+        pointer::{Pointer, PointerKind, PointerSource},
+        vector::{Vector, VectorSource},
+        Value,
+    },
+    Operation,
+};
+use std::{collections::HashSet, rc::Rc};
+
+/// Traversal over the operations of a function and the values they depend on
+pub trait Visitor {
+    /// Addresses of the values visited so far
+    fn visited(&mut self) -> &mut HashSet<*const ()>;
+
+    fn visit_operation(&mut self, op: &Operation) {
+        walk_operation(self, op)
+    }
+
+    fn visit_value(&mut self, value: &Value) {
+        walk_value(self, value)
+    }
+
+    fn visit_integer(&mut self, integer: &Rc<Integer>) {
+        walk_integer(self, integer)
+    }
+
+    fn visit_float(&mut self, float: &Rc<Float>) {
+        walk_float(self, float)
+    }
+
+    fn visit_bool(&mut self, bool: &Rc<Bool>) {
+        walk_bool(self, bool)
+    }
+
+    fn visit_vector(&mut self, vector: &Rc<Vector>) {
+        walk_vector(self, vector)
+    }
+
+    fn visit_pointer(&mut self, pointer: &Rc<Pointer>) {
+        walk_pointer(self, pointer)
+    }
+}
+
+/// Transformation over the operations of a function
+pub trait Rewriter {
+    /// Returns the operations that replace `op`, or `None` to keep it unchanged. Returning no operations removes it
+    fn rewrite_operation(&mut self, op: &Operation) -> Option<Vec<Operation>>;
+}
+
+/// Visits every function of the module, in order
+pub fn walk_module<V: ?Sized + Visitor>(visitor: &mut V, module: &ModuleBuilder) {
+    for function in module.built_functions.iter() {
+        walk_function(visitor, function)
+    }
+}
+
+/// Visits the variable initializers and the body of the function, in order
+pub fn walk_function<V: ?Sized + Visitor>(visitor: &mut V, function: &FunctionBuilder) {
+    for op in function
+        .variable_initializers
+        .iter()
+        .chain(function.anchors.iter())
+    {
+        visitor.visit_operation(op)
+    }
+}
+
+pub fn walk_operation<V: ?Sized + Visitor>(visitor: &mut V, op: &Operation) {
+    match op {
+        Operation::Value(value) | Operation::Return { value: Some(value) } => {
+            visitor.visit_value(value)
+        }
+        Operation::BranchConditional { condition, .. } => once(visitor, condition, V::visit_bool),
+        Operation::Store { target, value, .. } => {
+            once(visitor, target, V::visit_pointer);
+            visitor.visit_value(value);
+        }
+        Operation::Copy { src, dst, .. } => {
+            once(visitor, src, V::visit_pointer);
+            once(visitor, dst, V::visit_pointer);
+        }
+        Operation::FunctionCall { args, .. }
+        | Operation::DebugPrintf { args, .. }
+        | Operation::TraceRay { args, .. } => {
+            for arg in args.iter() {
+                visitor.visit_value(arg)
+            }
+        }
+        Operation::Label(_)
+        | Operation::Branch { .. }
+        | Operation::Nop
+        | Operation::Unreachable { .. }
+        | Operation::Return { value: None } => {}
+    }
+}
+
+pub fn walk_value<V: ?Sized + Visitor>(visitor: &mut V, value: &Value) {
+    match value {
+        Value::Integer(x) => once(visitor, x, V::visit_integer),
+        Value::Float(x) => once(visitor, x, V::visit_float),
+        Value::Pointer(x) => once(visitor, x, V::visit_pointer),
+        Value::Vector(x) => once(visitor, x, V::visit_vector),
+        Value::Bool(x) => once(visitor, x, V::visit_bool),
+    }
+}
+
+pub fn walk_integer<V: ?Sized + Visitor>(visitor: &mut V, integer: &Integer) {
+    match &integer.source {
+        IntegerSource::FunctionParam(_) | IntegerSource::Constant(_) => {}
+        IntegerSource::Conversion(conversion) => match conversion {
+            IntegerConversionSource::Bitcast { value, .. } => visitor.visit_value(value),
+            IntegerConversionSource::FromShort { value, .. }
+            | IntegerConversionSource::FromLong(value) => once(visitor, value, V::visit_integer),
+            IntegerConversionSource::FromPointer(pointer) => {
+                once(visitor, pointer, V::visit_pointer)
+            }
+            IntegerConversionSource::FromBool(value, _) => once(visitor, value, V::visit_bool),
+            IntegerConversionSource::FromFloat { value, .. } => {
+                once(visitor, value, V::visit_float)
+            }
+        },
+        IntegerSource::ArrayLength { structured_array } => {
+            once(visitor, structured_array, V::visit_pointer)
+        }
+        IntegerSource::Loaded { pointer, .. } => once(visitor, pointer, V::visit_pointer),
+        IntegerSource::AtomicIncrement { counter } => once(visitor, counter, V::visit_pointer),
+        IntegerSource::Select {
+            selector,
+            true_value,
+            false_value,
+        } => {
+            once(visitor, selector, V::visit_bool);
+            once(visitor, true_value, V::visit_integer);
+            once(visitor, false_value, V::visit_integer);
+        }
+        IntegerSource::Extracted { vector, index } => {
+            once(visitor, vector, V::visit_vector);
+            once(visitor, index, V::visit_integer);
+        }
+        IntegerSource::ConstantArrayElement { index, .. } => once(visitor, index, V::visit_integer),
+        IntegerSource::FunctionCall { args, .. } => {
+            for arg in args.iter() {
+                visitor.visit_value(arg)
+            }
+        }
+        IntegerSource::Unary { op1, .. } => once(visitor, op1, V::visit_integer),
+        IntegerSource::Binary { op1, op2, .. } => {
+            once(visitor, op1, V::visit_integer);
+            once(visitor, op2, V::visit_integer);
+        }
+    }
+}
+
+pub fn walk_float<V: ?Sized + Visitor>(visitor: &mut V, float: &Float) {
+    match &float.source {
+        FloatSource::FunctionParam(_) | FloatSource::Constant(_) => {}
+        FloatSource::Conversion(conversion) => match conversion {
+            FloatConversionSource::Bitcast { value, .. } => visitor.visit_value(value),
+            FloatConversionSource::FromSingle(value) | FloatConversionSource::FromDouble(value) => {
+                once(visitor, value, V::visit_float)
+            }
+            FloatConversionSource::FromInteger { value, .. } => {
+                once(visitor, value, V::visit_integer)
+            }
+        },
+        FloatSource::Loaded { pointer, .. } => once(visitor, pointer, V::visit_pointer),
+        FloatSource::Extracted { vector, index } => {
+            once(visitor, vector, V::visit_vector);
+            once(visitor, index, V::visit_integer);
+        }
+        FloatSource::Select {
+            selector,
+            true_value,
+            false_value,
+        } => {
+            once(visitor, selector, V::visit_bool);
+            once(visitor, true_value, V::visit_float);
+            once(visitor, false_value, V::visit_float);
+        }
+        FloatSource::FunctionCall { args, .. } => {
+            for arg in args.iter() {
+                visitor.visit_value(arg)
+            }
+        }
+        FloatSource::Unary { op1, .. } => once(visitor, op1, V::visit_float),
+        FloatSource::Binary { op1, op2, .. } => {
+            once(visitor, op1, V::visit_float);
+            once(visitor, op2, V::visit_float);
+        }
+    }
+}
+
+pub fn walk_bool<V: ?Sized + Visitor>(visitor: &mut V, bool: &Bool) {
+    match &bool.source {
+        BoolSource::Constant(_) => {}
+        BoolSource::FromInteger(value) => once(visitor, value, V::visit_integer),
+        BoolSource::Negated(value) => once(visitor, value, V::visit_bool),
+        BoolSource::Select {
+            selector,
+            true_value,
+            false_value,
+        } => {
+            once(visitor, selector, V::visit_bool);
+            once(visitor, true_value, V::visit_bool);
+            once(visitor, false_value, V::visit_bool);
+        }
+        BoolSource::IntEquality { op1, op2, .. } | BoolSource::IntComparison { op1, op2, .. } => {
+            once(visitor, op1, V::visit_integer);
+            once(visitor, op2, V::visit_integer);
+        }
+        BoolSource::FloatEquality { op1, op2, .. }
+        | BoolSource::FloatComparison { op1, op2, .. } => {
+            once(visitor, op1, V::visit_float);
+            once(visitor, op2, V::visit_float);
+        }
+        BoolSource::Loaded { pointer, .. } => once(visitor, pointer, V::visit_pointer),
+    }
+}
+
+pub fn walk_vector<V: ?Sized + Visitor>(visitor: &mut V, vector: &Vector) {
+    match &vector.source {
+        VectorSource::Loaded { pointer, .. } => once(visitor, pointer, V::visit_pointer),
+        VectorSource::Select {
+            selector,
+            true_value,
+            false_value,
+        } => {
+            once(visitor, selector, V::visit_bool);
+            once(visitor, true_value, V::visit_vector);
+            once(visitor, false_value, V::visit_vector);
+        }
+    }
+}
+
+pub fn walk_pointer<V: ?Sized + Visitor>(visitor: &mut V, pointer: &Pointer) {
+    if let PointerKind::Fat {
+        byte_offset: Some(byte_offset),
+    } = &pointer.kind
+    {
+        once(visitor, byte_offset, V::visit_integer)
+    }
+
+    match &pointer.source {
+        PointerSource::FunctionParam => {}
+        PointerSource::FromInteger(value) => once(visitor, value, V::visit_integer),
+        PointerSource::Select {
+            selector,
+            true_value,
+            false_value,
+        } => {
+            once(visitor, selector, V::visit_bool);
+            once(visitor, true_value, V::visit_pointer);
+            once(visitor, false_value, V::visit_pointer);
+        }
+        PointerSource::Casted { prev: parent } | PointerSource::Member { parent, .. } => {
+            once(visitor, parent, V::visit_pointer)
+        }
+        PointerSource::Loaded { pointer, .. } => once(visitor, pointer, V::visit_pointer),
+        PointerSource::Variable { init, .. } => {
+            if let Some(init) = init {
+                visitor.visit_value(init)
+            }
+        }
+    }
+}
+
+fn once<V: ?Sized + Visitor, T>(visitor: &mut V, node: &Rc<T>, visit: fn(&mut V, &Rc<T>)) {
+    if visitor.visited().insert(Rc::as_ptr(node).cast()) {
+        visit(visitor, node)
+    }
+}
+
+/// Rewrites the body of every function of the module
+pub fn rewrite_module<R: ?Sized + Rewriter>(rewriter: &mut R, module: &mut ModuleBuilder) {
+    for function in module.built_functions.iter_mut() {
+        rewrite_function(rewriter, function)
+    }
+}
+
+/// Rewrites every operation of the function's body, in order
+pub fn rewrite_function<R: ?Sized + Rewriter>(rewriter: &mut R, function: &mut FunctionBuilder) {
+    let anchors = std::mem::take(&mut function.anchors);
+    let operators = std::mem::take(&mut function.operators);
+
+    for (op, i) in anchors.into_iter().zip(0..) {
+        let ops = rewriter.rewrite_operation(&op).unwrap_or_else(|| vec![op]);
+
+        for op in ops {
+            function.anchors.push(op);
+            if let Some(operator) = operators.get(i) {
+                function.operators.push(operator.clone());
+            }
+        }
+    }
+}
@@ -1,34 +1,59 @@
 #![cfg_attr(docsrs, feature(doc_cfg))]
 #![allow(clippy::needless_return)]
 
-use config::Config;
+use config::{Config, WasmFeatures};
+use diagnostics::{Origin, PanicMessage};
 use docfg::docfg;
 use error::{Error, Result};
 use fg::module::ModuleBuilder;
 use once_cell::unsync::OnceCell;
+use profile::ProfileCounter;
 use rspirv::{
     binary::{Assemble, Disassemble, ParseState},
     dr::Module,
 };
 use serde::{Deserialize, Serialize};
 use std::{
-    cell::UnsafeCell,
+    cell::{Ref, RefCell, UnsafeCell},
+    collections::{BTreeMap, HashMap},
+    fs::File,
+    io::{BufWriter, Write},
     mem::{size_of, ManuallyDrop},
     ops::Deref,
+    path::Path,
+    time::{Duration, Instant},
 };
 use version::TargetPlatform;
+use wasmparser::{types::Types, Chunk, Parser, ValidPayload, Validator};
 
-// pub mod binary;
+pub mod binary;
+#[cfg(feature = "cache")]
+pub mod cache;
 pub mod capabilities;
+mod compact;
 pub mod compilers;
 pub mod config;
 pub mod decorator;
+pub mod diagnostics;
 pub mod error;
+mod features;
+/// Intermediate representation the WebAssembly program is translated into, before being lowered into SPIR-V
 pub mod fg;
+pub mod link;
+mod phi;
+pub mod profile;
+pub mod reflection;
+mod split;
+pub mod stats;
+#[cfg(any(feature = "testing", feature = "wgpu-test"))]
+pub mod testing;
 pub mod translation;
 pub mod r#type;
 pub mod version;
 
+#[docfg(feature = "naga")]
+pub use naga;
+
 pub struct Compilation {
     pub platform: TargetPlatform,
     module: OnceCell<Result<Module, ParseState>>,
@@ -43,17 +68,158 @@ pub struct Compilation {
     words: OnceCell<Box<[u32]>>,
     #[cfg(feature = "spvt-validate")]
     validate: OnceCell<Option<spirv_tools::error::Error>>,
+    origins: HashMap<rspirv::spirv::Word, Origin>,
+    wasm_features: WasmFeatures,
+    profile_counters: Box<[ProfileCounter]>,
+    panics: Box<[PanicMessage]>,
+    timings: CompilationTimings,
+}
+
+/// Time spent in each phase of a [`Compilation`], in milliseconds. Only [`Compilation::new`] tells the phases
+/// before the emission apart
+#[derive(Debug, Clone, Copy, Default, PartialEq, Serialize, Deserialize)]
+pub struct CompilationTimings {
+    /// Parsing and validation of the WebAssembly binary
+    pub parse_ms: f64,
+    /// Building the flow graph of the module
+    pub fg_ms: f64,
+    /// Translation of the flow graph into SPIR-V
+    pub emit_ms: f64,
+}
+
+impl CompilationTimings {
+    pub fn total_ms(&self) -> f64 {
+        return self.parse_ms + self.fg_ms + self.emit_ms;
+    }
+}
+
+fn millis(duration: Duration) -> f64 {
+    return duration.as_secs_f64() * 1000.0;
+}
+
+/// Builds a [`Compilation`] from a WebAssembly binary received in pieces
+pub struct CompilationBuilder {
+    config: Config,
+    bytes: Vec<u8>,
+    consumed: usize,
+    parser: Parser,
+    validator: Validator,
+    types: Option<Types>,
+}
+
+/// Compilation that defers the translation into SPIR-V until its output is requested
+pub struct CompilationRef<'a> {
+    builder: RefCell<Option<ModuleBuilder<'a>>>,
+    compilation: OnceCell<Compilation>,
 }
 
 impl Compilation {
     pub fn new(config: Config, bytes: &[u8]) -> Result<Self> {
-        let platform = config.platform;
-        #[cfg(feature = "spirv-tools")]
-        let target_env = spirv_tools::TargetEnv::from(&config.platform);
-        let builder = ModuleBuilder::new(config, bytes)?;
-        let module = builder.translate()?.module();
+        let start = Instant::now();
+        let types = features::validate(config.features, bytes)?;
+        let parsed = Instant::now();
+        let builder = ModuleBuilder::from_validated(config, bytes, &types)?;
+        let built = Instant::now();
+
+        let mut result = Self::from_builder(builder)?;
+        result.timings.parse_ms = millis(parsed - start);
+        result.timings.fg_ms = millis(built - parsed);
+        return Ok(result);
+    }
+
+    /// Translates a module built with the [`fg`] API, like one created with [`ModuleBuilder::empty`]
+    pub fn from_builder(builder: ModuleBuilder<'_>) -> Result<Self> {
+        let platform = builder.platform;
+        let wasm_features = builder.wasm_features;
+        let panics = builder
+            .panics
+            .iter()
+            .map(|x| PanicMessage::clone(x))
+            .collect();
+        let start = Instant::now();
+        let builder = builder.translate()?;
+        let profile_counters = Box::from(builder.profile_counters());
+        let (module, origins) = builder.module_with_origins();
+
+        let mut result = Self::from_module(platform, module, origins);
+        result.wasm_features = wasm_features;
+        result.profile_counters = profile_counters;
+        result.panics = panics;
+        result.timings.emit_ms = millis(start.elapsed());
+        return Ok(result);
+    }
+
+    /// Compiles the WebAssembly module, resolving its imports with the exports of the library modules
+    pub fn with_libraries(
+        config: Config,
+        bytes: &[u8],
+        libraries: &[(&str, &[u8])],
+    ) -> Result<Self> {
+        return Self::from_builder(ModuleBuilder::with_libraries(config, bytes, libraries)?);
+    }
+
+    /// Compiles the WebAssembly module once for every [profile](Config::profiles) of the configuration, by name
+    pub fn new_all_profiles(
+        config: Config,
+        bytes: &[u8],
+    ) -> Result<BTreeMap<Box<str>, Compilation>> {
+        let types = features::validate(config.features, bytes)?;
+
+        let mut result = BTreeMap::new();
+        for name in config.profiles.keys() {
+            let profile = config.profile(name).ok_or_else(Error::unexpected)?;
+            let builder = ModuleBuilder::from_validated(profile, bytes, &types)?;
+            result.insert(name.clone(), Self::from_builder(builder)?);
+        }
+
+        return Ok(result);
+    }
+
+    /// Parses and validates the WebAssembly module, deferring its translation into SPIR-V
+    #[inline]
+    pub fn new_borrowed(config: Config, bytes: &[u8]) -> Result<CompilationRef<'_>> {
+        return CompilationRef::new(config, bytes);
+    }
+
+    /// Wraps an already compiled SPIR-V binary, without renumbering its ids
+    pub fn from_spirv(platform: TargetPlatform, words: impl Into<Box<[u32]>>) -> Result<Self> {
+        let words = words.into();
+        let mut loader = rspirv::dr::Loader::new();
+        rspirv::binary::parse_words(&words, &mut loader).map_err(|e| Error::msg(e.to_string()))?;
 
         return Ok(Self {
+            platform,
+            module: OnceCell::with_value(Ok(loader.module())),
+            #[cfg(feature = "naga")]
+            naga_module: OnceCell::new(),
+            #[cfg(feature = "spirvcross")]
+            spvc_context: OnceCell::new(),
+            #[cfg(feature = "spirv-tools")]
+            target_env: spirv_tools::TargetEnv::from(&platform),
+            assembly: OnceCell::new(),
+            words: OnceCell::with_value(words),
+            #[cfg(feature = "spirv-tools")]
+            validate: OnceCell::new(),
+            origins: HashMap::new(),
+            wasm_features: WasmFeatures::default(),
+            profile_counters: Box::default(),
+            panics: Box::default(),
+            timings: CompilationTimings::default(),
+        });
+    }
+
+    #[inline]
+    pub fn builder(config: Config) -> CompilationBuilder {
+        return CompilationBuilder::new(config);
+    }
+
+    fn from_module(
+        platform: TargetPlatform,
+        mut module: Module,
+        mut origins: HashMap<rspirv::spirv::Word, Origin>,
+    ) -> Self {
+        compact::compact_ids(&mut module, &mut origins);
+        return Self {
             platform,
             module: OnceCell::with_value(Ok(module)),
             #[cfg(feature = "naga")]
@@ -61,12 +227,17 @@ impl Compilation {
             #[cfg(feature = "spirvcross")]
             spvc_context: OnceCell::new(),
             #[cfg(feature = "spirv-tools")]
-            target_env,
+            target_env: spirv_tools::TargetEnv::from(&platform),
             assembly: OnceCell::new(),
             words: OnceCell::new(),
             #[cfg(feature = "spirv-tools")]
             validate: OnceCell::new(),
-        });
+            origins,
+            wasm_features: WasmFeatures::default(),
+            profile_counters: Box::default(),
+            panics: Box::default(),
+            timings: CompilationTimings::default(),
+        };
     }
 
     pub fn module(&self) -> Result<&Module> {
@@ -101,6 +272,61 @@ impl Compilation {
         });
     }
 
+    /// Writes the SPIR-V binary into the writer, in little-endian. Every word is written separately, so unbuffered
+    /// writers should be wrapped in a [`BufWriter`](std::io::BufWriter)
+    pub fn write_to<W: ?Sized + std::io::Write>(&self, writer: &mut W) -> Result<()> {
+        if let Some(words) = self.words.get() {
+            return write_words(words, writer);
+        }
+
+        let module = self.module()?;
+        if let Some(header) = &module.header {
+            write_words(&header.assemble(), writer)?;
+        }
+
+        let mut buffer = Vec::new();
+        for instruction in module.all_inst_iter() {
+            buffer.clear();
+            instruction.assemble_into(&mut buffer);
+            write_words(&buffer, writer)?;
+        }
+
+        return Ok(());
+    }
+
+    /// Writes the SPIR-V binary into the file, creating it if it doesn't exist
+    pub fn write_to_file(&self, path: impl AsRef<Path>) -> Result<()> {
+        let mut writer = BufWriter::new(File::create(path)?);
+        self.write_to(&mut writer)?;
+        writer.flush()?;
+        return Ok(());
+    }
+
+    /// WebAssembly proposals the compiled module couldn't be validated without
+    pub fn required_wasm_features(&self) -> WasmFeatures {
+        return self.wasm_features;
+    }
+
+    /// Layout of the [profiling counters](crate::config::Config::profiling) buffer. Empty if the module wasn't
+    /// instrumented
+    pub fn profile_counters(&self) -> &[ProfileCounter] {
+        return &self.profile_counters;
+    }
+
+    /// Time spent parsing, building the flow graph and emitting
+    pub fn timings(&self) -> CompilationTimings {
+        return self.timings;
+    }
+
+    /// Checks the capabilities and extensions declared by the emitted binary
+    pub fn verify_roundtrip(&self) -> Result<()> {
+        let mut loader = rspirv::dr::Loader::new();
+        rspirv::binary::parse_words(self.words()?, &mut loader)
+            .map_err(|e| Error::msg(e.to_string()))?;
+
+        return capabilities::verify_module(&loader.module());
+    }
+
     #[docfg(any(feature = "spvt-validate", feature = "naga-validate"))]
     #[inline]
     pub fn validate(&self) -> Result<()> {
@@ -125,6 +351,28 @@ impl Compilation {
         }
     }
 
+    #[docfg(any(feature = "spvc-glsl", feature = "naga-glsl"))]
+    #[inline]
+    pub fn glsl_with(&self, options: &compilers::GlslOptions) -> Result<String> {
+        cfg_if::cfg_if! {
+            if #[cfg(feature = "spvc-glsl")] {
+                return self.spvc_glsl_with(options)
+            } else {
+                return self.naga_glsl_with(options)
+            }
+        }
+    }
+
+    #[docfg(any(feature = "spvc-glsl", feature = "naga-glsl"))]
+    #[inline]
+    pub fn glsl_entry(&self, name: &str, stage: rspirv::spirv::ExecutionModel) -> Result<String> {
+        return self.glsl_with(&compilers::GlslOptions {
+            entry_point: Some(name.to_string()),
+            stage: Some(stage),
+            ..Default::default()
+        });
+    }
+
     #[docfg(any(feature = "spvc-hlsl", feature = "naga-hlsl"))]
     #[inline]
     pub fn hlsl(&self) -> Result<String> {
@@ -137,6 +385,28 @@ impl Compilation {
         }
     }
 
+    #[docfg(any(feature = "spvc-hlsl", feature = "naga-hlsl"))]
+    #[inline]
+    pub fn hlsl_with(&self, options: &compilers::HlslOptions) -> Result<String> {
+        cfg_if::cfg_if! {
+            if #[cfg(feature = "spvc-hlsl")] {
+                return self.spvc_hlsl_with(options)
+            } else {
+                return self.naga_hlsl_with(options)
+            }
+        }
+    }
+
+    #[docfg(any(feature = "spvc-hlsl", feature = "naga-hlsl"))]
+    #[inline]
+    pub fn hlsl_entry(&self, name: &str, stage: rspirv::spirv::ExecutionModel) -> Result<String> {
+        return self.hlsl_with(&compilers::HlslOptions {
+            entry_point: Some(name.to_string()),
+            stage: Some(stage),
+            ..Default::default()
+        });
+    }
+
     #[docfg(any(feature = "spvc-msl", feature = "naga-msl"))]
     #[inline]
     pub fn msl(&self) -> Result<String> {
@@ -149,12 +419,40 @@ impl Compilation {
         }
     }
 
+    #[docfg(any(feature = "spvc-msl", feature = "naga-msl"))]
+    #[inline]
+    pub fn msl_with(&self, options: &compilers::MslOptions) -> Result<String> {
+        cfg_if::cfg_if! {
+            if #[cfg(feature = "spvc-msl")] {
+                return self.spvc_msl_with(options)
+            } else {
+                return self.naga_msl_with(options)
+            }
+        }
+    }
+
+    #[docfg(any(feature = "spvc-msl", feature = "naga-msl"))]
+    #[inline]
+    pub fn msl_entry(&self, name: &str, stage: rspirv::spirv::ExecutionModel) -> Result<String> {
+        return self.msl_with(&compilers::MslOptions {
+            entry_point: Some(name.to_string()),
+            stage: Some(stage),
+            ..Default::default()
+        });
+    }
+
     #[docfg(feature = "naga-wgsl")]
     #[inline]
     pub fn wgsl(&self) -> Result<String> {
         return self.naga_wgsl();
     }
 
+    #[docfg(feature = "naga-wgsl")]
+    #[inline]
+    pub fn wgsl_with(&self, options: &compilers::WgslOptions) -> Result<String> {
+        return self.naga_wgsl_with(options);
+    }
+
     pub fn into_assembly(self) -> Result<String> {
         if self.assembly.get().is_some() {
             let str = unsafe { self.assembly.into_inner().unwrap_unchecked() };
@@ -185,6 +483,136 @@ impl Compilation {
     }
 }
 
+fn write_words<W: ?Sized + std::io::Write>(words: &[u32], writer: &mut W) -> Result<()> {
+    for word in words {
+        writer.write_all(&word.to_le_bytes())?;
+    }
+    return Ok(());
+}
+
+impl<'a> CompilationRef<'a> {
+    pub fn new(config: Config, bytes: &'a [u8]) -> Result<Self> {
+        return Ok(Self {
+            builder: RefCell::new(Some(ModuleBuilder::new(config, bytes)?)),
+            compilation: OnceCell::new(),
+        });
+    }
+
+    /// Returns the module's flow graph, if it hasn't been translated yet
+    pub fn module_builder(&self) -> Option<Ref<'_, ModuleBuilder<'a>>> {
+        return Ref::filter_map(self.builder.borrow(), Option::as_ref).ok();
+    }
+
+    /// Renders the control flow graph of the function as a Graphviz graph. Only available until the module is
+    /// translated
+    pub fn debug_cfg(&self, function: u32) -> Result<String> {
+        let builder = self
+            .module_builder()
+            .ok_or_else(|| Error::msg("The module has already been translated"))?;
+
+        return builder
+            .built_functions
+            .iter()
+            .find(|x| x.index == function)
+            .map(|x| x.dump_dot())
+            .ok_or_else(|| Error::msg(format!("Function {function} isn't defined by the module")));
+    }
+
+    /// Translates the module (if it wasn't already), returning the resulting compilation
+    pub fn compilation(&self) -> Result<&Compilation> {
+        return self.compilation.get_or_try_init(|| {
+            let builder = self
+                .builder
+                .try_borrow_mut()
+                .map_err(|_| Error::msg("The module's flow graph is currently borrowed"))?
+                .take()
+                .ok_or_else(|| Error::msg("A previous translation of the module failed"))?;
+
+            return Compilation::from_builder(builder);
+        });
+    }
+
+    pub fn into_compilation(self) -> Result<Compilation> {
+        self.compilation()?;
+        return self.compilation.into_inner().ok_or_else(Error::unexpected);
+    }
+
+    #[inline]
+    pub fn module(&self) -> Result<&Module> {
+        return self.compilation()?.module();
+    }
+
+    #[inline]
+    pub fn assembly(&self) -> Result<&str> {
+        return self.compilation()?.assembly();
+    }
+
+    #[inline]
+    pub fn words(&self) -> Result<&[u32]> {
+        return self.compilation()?.words();
+    }
+
+    #[inline]
+    pub fn bytes(&self) -> Result<&[u8]> {
+        return self.compilation()?.bytes();
+    }
+}
+
+impl CompilationBuilder {
+    pub fn new(config: Config) -> Self {
+        return Self {
+            validator: Validator::new_with_features(config.features.into()),
+            config,
+            bytes: Vec::new(),
+            consumed: 0,
+            parser: Parser::new(0),
+            types: None,
+        };
+    }
+
+    /// Appends the next piece of the WebAssembly binary
+    pub fn push_bytes(&mut self, bytes: &[u8]) -> Result<&mut Self> {
+        self.bytes.extend_from_slice(bytes);
+        self.parse(false)?;
+        return Ok(self);
+    }
+
+    pub fn finish(mut self) -> Result<Compilation> {
+        self.parse(true)?;
+        let types = self
+            .types
+            .ok_or_else(|| Error::msg("Incomplete WebAssembly module"))?;
+
+        let builder = ModuleBuilder::from_validated(self.config, &self.bytes, &types)?;
+        return Compilation::from_builder(builder);
+    }
+
+    fn parse(&mut self, eof: bool) -> Result<()> {
+        while self.types.is_none() {
+            let chunk = self.parser.parse(&self.bytes[self.consumed..], eof)?;
+            let (consumed, payload) = match chunk {
+                Chunk::NeedMoreData(_) => return Ok(()),
+                Chunk::Parsed { consumed, payload } => (consumed, payload),
+            };
+
+            let features = self.config.features;
+            let explain = |e| features::explain(e, features, &self.bytes, eof);
+            match self.validator.payload(&payload).map_err(explain)? {
+                ValidPayload::Ok | ValidPayload::Parser(_) => {}
+                ValidPayload::Func(func, body) => {
+                    let mut validator = func.into_validator(Default::default());
+                    validator.validate(&body).map_err(explain)?;
+                }
+                ValidPayload::End(types) => self.types = Some(types),
+            }
+
+            self.consumed += consumed;
+        }
+
+        return Ok(());
+    }
+}
+
 #[derive(Debug, Clone, PartialEq, Eq, Hash)]
 pub enum Str<'a> {
     Owned(Box<str>),
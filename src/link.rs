@@ -0,0 +1,383 @@
+use crate::{
+    config::WasmFeatures,
+    diagnostics::Origin,
+    error::{Error, Result},
+    version::TargetPlatform,
+    Compilation,
+};
+use rspirv::{
+    dr::{Instruction, Module, Operand},
+    spirv::{Capability, Decoration, LinkageType, Op, Word},
+};
+use std::collections::{HashMap, HashSet};
+
+/// Merges several SPIR-V modules into one
+pub struct Linker {
+    platform: TargetPlatform,
+    modules: Vec<(Module, HashMap<Word, Origin>)>,
+    wasm_features: WasmFeatures,
+}
+
+impl Linker {
+    pub fn new(platform: TargetPlatform) -> Self {
+        return Self {
+            platform,
+            modules: Vec::new(),
+            wasm_features: WasmFeatures::default(),
+        };
+    }
+
+    /// Adds a compilation to the linker. It must target the same platform as the linker
+    pub fn add(&mut self, compilation: &Compilation) -> Result<&mut Self> {
+        if compilation.platform != self.platform {
+            return Err(Error::msg(format!(
+                "Cannot link a module targeting {:?} into one targeting {:?}",
+                compilation.platform, self.platform
+            )));
+        }
+
+        self.modules
+            .push((compilation.module()?.clone(), compilation.origins.clone()));
+        self.wasm_features = self.wasm_features.union(compilation.wasm_features);
+        return Ok(self);
+    }
+
+    /// Adds an already built SPIR-V module to the linker
+    pub fn add_module(&mut self, module: Module) -> &mut Self {
+        self.modules.push((module, HashMap::new()));
+        return self;
+    }
+
+    pub fn link(self) -> Result<Compilation> {
+        let mut modules = self.modules.into_iter();
+        let (mut result, mut origins) = modules.next().ok_or_else(Error::element_not_found)?;
+
+        for (mut module, module_origins) in modules {
+            let offset = bound(&result);
+            for instr in module.all_inst_iter_mut() {
+                shift_ids(instr, offset);
+            }
+            origins.extend(
+                module_origins
+                    .into_iter()
+                    .map(|(id, origin)| (id + offset, origin)),
+            );
+            merge(&mut result, module)?;
+        }
+
+        let mut replacements = HashMap::new();
+        deduplicate(&mut result, &mut replacements);
+        resolve_linkage(&mut result, &mut replacements)?;
+
+        for instr in result.all_inst_iter_mut() {
+            replace_ids(instr, &replacements);
+        }
+        remove_duplicates(&mut result.debug_names);
+        remove_duplicates(&mut result.annotations);
+
+        if !result.annotations.iter().any(is_linkage) {
+            result.capabilities.retain(|x| {
+                !matches!(
+                    x.operands.first(),
+                    Some(Operand::Capability(Capability::Linkage))
+                )
+            });
+        }
+
+        let mut compilation = Compilation::from_module(self.platform, result, origins);
+        compilation.wasm_features = self.wasm_features;
+        return Ok(compilation);
+    }
+}
+
+fn merge(result: &mut Module, module: Module) -> Result<()> {
+    match (&result.header, &module.header) {
+        (Some(x), Some(y)) if y.version > x.version => result.header = module.header.clone(),
+        (None, _) => result.header = module.header.clone(),
+        _ => {}
+    }
+
+    match (&result.memory_model, &module.memory_model) {
+        (Some(x), Some(y)) if x.operands != y.operands => {
+            return Err(Error::msg(
+                "Cannot link modules with different memory models",
+            ))
+        }
+        (None, _) => result.memory_model = module.memory_model.clone(),
+        _ => {}
+    }
+
+    for entry_point in module.entry_points.iter() {
+        let (execution_model, name) = entry_point_key(entry_point)?;
+        for x in result.entry_points.iter() {
+            if entry_point_key(x)? == (execution_model, name) {
+                return Err(Error::msg(format!(
+                    "Entry point {name:?} is defined by more than one module"
+                )));
+            }
+        }
+    }
+
+    for instr in module.capabilities {
+        if !result
+            .capabilities
+            .iter()
+            .any(|x| x.operands == instr.operands)
+        {
+            result.capabilities.push(instr);
+        }
+    }
+    for instr in module.extensions {
+        if !result
+            .extensions
+            .iter()
+            .any(|x| x.operands == instr.operands)
+        {
+            result.extensions.push(instr);
+        }
+    }
+
+    result.ext_inst_imports.extend(module.ext_inst_imports);
+    result.entry_points.extend(module.entry_points);
+    result.execution_modes.extend(module.execution_modes);
+    result
+        .debug_string_source
+        .extend(module.debug_string_source);
+    result.debug_names.extend(module.debug_names);
+    result
+        .debug_module_processed
+        .extend(module.debug_module_processed);
+    result.annotations.extend(module.annotations);
+    result
+        .types_global_values
+        .extend(module.types_global_values);
+    result.functions.extend(module.functions);
+    return Ok(());
+}
+
+fn deduplicate(module: &mut Module, replacements: &mut HashMap<Word, Word>) {
+    let mut imports = Vec::<(Vec<Operand>, Word)>::new();
+    module.ext_inst_imports.retain(|x| {
+        let Some(id) = x.result_id else { return true };
+        match imports.iter().find(|(operands, _)| *operands == x.operands) {
+            Some((_, prev)) => {
+                replacements.insert(id, *prev);
+                false
+            }
+            None => {
+                imports.push((x.operands.clone(), id));
+                true
+            }
+        }
+    });
+
+    // Types only reference previously declared types, so they can be replaced in order
+    let mut seen = Vec::<(Instruction, Vec<Instruction>, Word)>::new();
+    let mut removed = HashSet::new();
+    for instr in module.types_global_values.iter_mut() {
+        replace_ids(instr, replacements);
+
+        let Some(id) = instr.result_id else { continue };
+        if !is_deduplicable(instr.class.opcode) {
+            continue;
+        }
+
+        let decorations = decorations(&module.annotations, id, replacements);
+        let mut key = instr.clone();
+        key.result_id = None;
+
+        match seen
+            .iter()
+            .find(|(instr, dec, _)| *instr == key && *dec == decorations)
+        {
+            Some((_, _, prev)) => {
+                replacements.insert(id, *prev);
+                removed.insert(id);
+            }
+            None => seen.push((key, decorations, id)),
+        }
+    }
+
+    module
+        .types_global_values
+        .retain(|x| !x.result_id.is_some_and(|id| removed.contains(&id)));
+}
+
+fn resolve_linkage(module: &mut Module, replacements: &mut HashMap<Word, Word>) -> Result<()> {
+    let mut exports = HashMap::new();
+    let mut imports = Vec::new();
+
+    for instr in module.annotations.iter().filter(|x| is_linkage(x)) {
+        match &instr.operands[..] {
+            [Operand::IdRef(target), _, Operand::LiteralString(name), Operand::LinkageType(LinkageType::Export)] => {
+                if exports.insert(name.clone(), *target).is_some() {
+                    return Err(Error::msg(format!(
+                        "Symbol `{name}` is exported by more than one module"
+                    )));
+                }
+            }
+            [Operand::IdRef(target), _, Operand::LiteralString(name), Operand::LinkageType(LinkageType::Import)] => {
+                imports.push((name.clone(), *target))
+            }
+            _ => return Err(Error::unexpected()),
+        }
+    }
+
+    let mut resolved = HashSet::new();
+    let mut linked = HashSet::new();
+    for (name, import) in imports {
+        let Some(&export) = exports.get(&name) else {
+            continue;
+        };
+
+        if type_of(module, import, replacements) != type_of(module, export, replacements) {
+            return Err(Error::msg(format!(
+                "Imported symbol `{name}` doesn't match the type of its export"
+            )));
+        }
+
+        replacements.insert(import, export);
+        linked.insert(import);
+        resolved.extend([import, export]);
+    }
+
+    module.annotations.retain(|x| {
+        !(is_linkage(x)
+            && matches!(x.operands.first(), Some(Operand::IdRef(id)) if resolved.contains(id)))
+    });
+
+    // Remove the declarations of the resolved imports
+    let imported = |id: Option<Word>| id.is_some_and(|id| linked.contains(&id));
+    module
+        .functions
+        .retain(|f| !imported(f.def.as_ref().and_then(|x| x.result_id)));
+    module
+        .types_global_values
+        .retain(|x| !imported(x.result_id));
+
+    let removed = |x: &Instruction| matches!(x.operands.first(), Some(Operand::IdRef(id)) if imported(Some(*id)));
+    module.debug_names.retain(|x| !removed(x));
+    module.annotations.retain(|x| !removed(x));
+    return Ok(());
+}
+
+fn type_of(module: &Module, id: Word, replacements: &HashMap<Word, Word>) -> Option<Word> {
+    let ty = module
+        .functions
+        .iter()
+        .filter_map(|f| f.def.as_ref())
+        .chain(module.types_global_values.iter())
+        .find(|x| x.result_id == Some(id))
+        .and_then(|x| match x.class.opcode {
+            Op::Function => x.operands.get(1).and_then(Operand::id_ref_any),
+            _ => x.result_type,
+        })?;
+
+    return Some(replacements.get(&ty).copied().unwrap_or(ty));
+}
+
+fn decorations(
+    annotations: &[Instruction],
+    id: Word,
+    replacements: &HashMap<Word, Word>,
+) -> Vec<Instruction> {
+    return annotations
+        .iter()
+        .filter(|x| matches!(x.operands.first(), Some(Operand::IdRef(target)) if *target == id))
+        .map(|x| {
+            let mut x = x.clone();
+            x.operands.remove(0);
+            replace_ids(&mut x, replacements);
+            x
+        })
+        .collect();
+}
+
+fn is_deduplicable(op: Op) -> bool {
+    return matches!(
+        op,
+        Op::TypeVoid
+            | Op::TypeBool
+            | Op::TypeInt
+            | Op::TypeFloat
+            | Op::TypeVector
+            | Op::TypeMatrix
+            | Op::TypeImage
+            | Op::TypeSampler
+            | Op::TypeSampledImage
+            | Op::TypeArray
+            | Op::TypeRuntimeArray
+            | Op::TypeStruct
+            | Op::TypePointer
+            | Op::TypeFunction
+            | Op::Constant
+            | Op::ConstantTrue
+            | Op::ConstantFalse
+            | Op::ConstantComposite
+            | Op::ConstantNull
+    );
+}
+
+fn entry_point_key(instr: &Instruction) -> Result<(&Operand, &Operand)> {
+    return match (instr.operands.first(), instr.operands.get(2)) {
+        (Some(execution_model), Some(name)) => Ok((execution_model, name)),
+        _ => Err(Error::msg(
+            "Entry point is missing its execution model or name",
+        )),
+    };
+}
+
+fn is_linkage(instr: &Instruction) -> bool {
+    return instr.class.opcode == Op::Decorate
+        && matches!(
+            instr.operands.get(1),
+            Some(Operand::Decoration(Decoration::LinkageAttributes))
+        );
+}
+
+fn remove_duplicates(instructions: &mut Vec<Instruction>) {
+    let mut result = Vec::<Instruction>::with_capacity(instructions.len());
+    for instr in instructions.drain(..) {
+        if !result.contains(&instr) {
+            result.push(instr);
+        }
+    }
+    *instructions = result;
+}
+
+fn bound(module: &Module) -> Word {
+    return module
+        .all_inst_iter()
+        .filter_map(|x| x.result_id)
+        .max()
+        .map_or(1, |x| x + 1);
+}
+
+fn shift_ids(instr: &mut Instruction, offset: Word) {
+    for id in instr
+        .result_id
+        .iter_mut()
+        .chain(instr.result_type.iter_mut())
+        .chain(
+            instr
+                .operands
+                .iter_mut()
+                .filter_map(Operand::id_ref_any_mut),
+        )
+    {
+        *id += offset;
+    }
+}
+
+fn replace_ids(instr: &mut Instruction, replacements: &HashMap<Word, Word>) {
+    for id in instr.result_type.iter_mut().chain(
+        instr
+            .operands
+            .iter_mut()
+            .filter_map(Operand::id_ref_any_mut),
+    ) {
+        if let Some(new) = replacements.get(id) {
+            *id = *new;
+        }
+    }
+}
@@ -0,0 +1,204 @@
+use rspirv::{
+    dr::{Block, Function, Instruction, Module, Operand},
+    spirv::{Op, StorageClass, Word},
+};
+use std::collections::{HashMap, HashSet};
+
+pub(crate) fn promote_temporaries(module: &mut Module) {
+    let mut removed = HashSet::new();
+    for function in module.functions.iter_mut() {
+        promote_function(function, &mut removed);
+    }
+
+    // Names and decorations of the removed variables
+    module.debug_names.retain(|x| !references(x, &removed));
+    module.annotations.retain(|x| !references(x, &removed));
+}
+
+fn promote_function(function: &mut Function, removed: &mut HashSet<Word>) {
+    split_blocks(function);
+    let Some(entry) = function.blocks.first() else {
+        return;
+    };
+
+    let candidates = entry
+        .instructions
+        .iter()
+        .filter(|x| x.class.opcode == Op::Variable && x.operands.len() == 1)
+        .filter(|x| x.operands[0] == Operand::StorageClass(StorageClass::Function))
+        .filter_map(|x| x.result_id)
+        .collect::<Vec<_>>();
+
+    let preds = predecessors(&function.blocks);
+    let mut replacements = HashMap::<Word, Word>::new();
+
+    for variable in candidates {
+        let Some((block, phi, loads)) = phi_for(function, &preds, variable) else {
+            continue;
+        };
+
+        // The declaration, loads and stores of the variable
+        for block in function.blocks.iter_mut() {
+            block.instructions.retain(|x| !uses(x, variable));
+        }
+
+        let result_id = phi.result_id.unwrap_or_default();
+        for load in loads {
+            replacements.insert(load, result_id);
+        }
+
+        let instructions = &mut function.blocks[block].instructions;
+        let at = instructions
+            .iter()
+            .take_while(|x| x.class.opcode == Op::Phi)
+            .count();
+        instructions.insert(at, phi);
+        removed.insert(variable);
+    }
+
+    for block in function.blocks.iter_mut() {
+        for id in block
+            .instructions
+            .iter_mut()
+            .flat_map(|x| x.operands.iter_mut())
+            .filter_map(Operand::id_ref_any_mut)
+        {
+            if let Some(replacement) = replacements.get(id) {
+                *id = *replacement;
+            }
+        }
+    }
+}
+
+fn phi_for(
+    function: &Function,
+    preds: &[Vec<usize>],
+    variable: Word,
+) -> Option<(usize, Instruction, Vec<Word>)> {
+    let mut loaded = None;
+    let mut loads = Vec::new();
+    let mut result_type = None;
+    let mut stores = HashMap::<usize, Word>::new();
+
+    for (i, block) in function.blocks.iter().enumerate() {
+        for instr in block.instructions.iter() {
+            if instr.result_id == Some(variable) {
+                continue;
+            }
+
+            match instr.class.opcode {
+                Op::Load if instr.operands.first() == Some(&Operand::IdRef(variable)) => {
+                    if *loaded.get_or_insert(i) != i {
+                        return None;
+                    }
+                    loads.push(instr.result_id?);
+                    result_type = instr.result_type;
+                }
+                Op::Store
+                    if instr.operands.first() == Some(&Operand::IdRef(variable))
+                        && instr.operands.get(1) != Some(&Operand::IdRef(variable)) =>
+                {
+                    // Later stores of the same block overwrite the earlier ones
+                    stores.insert(i, instr.operands.get(1)?.id_ref_any()?);
+                }
+                _ if uses(instr, variable) => return None,
+                _ => {}
+            }
+        }
+    }
+
+    let block = loaded?;
+    let mut incoming = preds[block].clone();
+    incoming.sort_unstable();
+    incoming.dedup();
+
+    let mut stored = stores.keys().copied().collect::<Vec<_>>();
+    stored.sort_unstable();
+    if incoming.is_empty() || incoming != stored || incoming.contains(&block) {
+        return None;
+    }
+
+    let operands = incoming
+        .iter()
+        .flat_map(|&pred| {
+            let label = function.blocks[pred].label_id().unwrap_or_default();
+            [Operand::IdRef(stores[&pred]), Operand::IdRef(label)]
+        })
+        .collect();
+
+    let phi = Instruction::new(Op::Phi, result_type, Some(loads[0]), operands);
+    return Some((block, phi, loads));
+}
+
+pub(crate) fn split_blocks(function: &mut Function) {
+    let mut blocks = Vec::with_capacity(function.blocks.len());
+    for block in std::mem::take(&mut function.blocks) {
+        let mut current = Block {
+            label: block.label,
+            instructions: Vec::new(),
+        };
+
+        for instr in block.instructions {
+            match instr.class.opcode {
+                Op::Label => blocks.push(std::mem::replace(
+                    &mut current,
+                    Block {
+                        label: Some(instr),
+                        instructions: Vec::new(),
+                    },
+                )),
+                _ => current.instructions.push(instr),
+            }
+        }
+        blocks.push(current);
+    }
+
+    function.blocks = blocks;
+}
+
+fn predecessors(blocks: &[Block]) -> Vec<Vec<usize>> {
+    let indices = blocks
+        .iter()
+        .enumerate()
+        .filter_map(|(i, x)| Some((x.label_id()?, i)))
+        .collect::<HashMap<_, _>>();
+
+    let mut result = vec![Vec::new(); blocks.len()];
+    for (i, block) in blocks.iter().enumerate() {
+        let Some(terminator) = block.instructions.last() else {
+            continue;
+        };
+
+        let targets = match terminator.class.opcode {
+            Op::Branch => &terminator.operands[..1],
+            Op::BranchConditional => &terminator.operands[1..3],
+            Op::Switch => &terminator.operands[1..],
+            _ => &[],
+        };
+
+        for target in targets.iter().filter_map(Operand::id_ref_any) {
+            if let Some(&j) = indices.get(&target) {
+                result[j].push(i);
+            }
+        }
+    }
+
+    return result;
+}
+
+fn uses(instr: &Instruction, id: Word) -> bool {
+    return instr.result_id == Some(id)
+        || instr
+            .operands
+            .iter()
+            .filter_map(Operand::id_ref_any)
+            .any(|x| x == id);
+}
+
+fn references(instr: &Instruction, ids: &HashSet<Word>) -> bool {
+    return instr
+        .operands
+        .iter()
+        .filter_map(Operand::id_ref_any)
+        .any(|x| ids.contains(&x));
+}
@@ -0,0 +1,289 @@
+use crate::{
+    config::ProfilingBuffer,
+    error::{Error, Result},
+    phi::split_blocks,
+    translation::Builder,
+    version::Version,
+};
+use rspirv::{
+    dr::{Instruction, Operand},
+    spirv::{Decoration, Op, Scope, StorageClass, Word},
+};
+use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
+
+/// A 32-bit counter of the [profiling buffer](crate::config::Config::profiling)
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Serialize, Deserialize)]
+pub struct ProfileCounter {
+    /// Byte offset of the counter from the start of the buffer
+    pub offset: u32,
+    /// Index of the WebAssembly function the counter belongs to
+    pub function: u32,
+    pub kind: CounterKind,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum CounterKind {
+    /// Incremented every time the function is called
+    FunctionEntry,
+    /// Incremented every time the loop branches back into its header. Loops are numbered in order of appearance
+    BackEdge { loop_index: u32 },
+}
+
+/// Where a counter is incremented
+struct Site {
+    function: usize,
+    block: usize,
+    kind: CounterKind,
+    /// Label of the loop header, for back-edges
+    header: Option<Word>,
+}
+
+pub(crate) fn instrument(
+    builder: &mut Builder,
+    functions: &[u32],
+    buffer: ProfilingBuffer,
+    version: Version,
+) -> Result<Vec<ProfileCounter>> {
+    if builder.module_ref().functions.len() != functions.len() {
+        return Err(Error::unexpected());
+    }
+
+    let mut sites = Vec::new();
+    for (i, function) in builder.module_mut().functions.iter_mut().enumerate() {
+        split_blocks(function);
+        if function.blocks.is_empty() {
+            continue;
+        }
+
+        sites.push(Site {
+            function: i,
+            block: 0,
+            kind: CounterKind::FunctionEntry,
+            header: None,
+        });
+
+        let headers = function
+            .blocks
+            .iter()
+            .enumerate()
+            .filter(|(_, x)| {
+                x.instructions
+                    .iter()
+                    .any(|x| x.class.opcode == Op::LoopMerge)
+            })
+            .filter_map(|(j, x)| Some((x.label_id()?, j)))
+            .collect::<HashMap<_, _>>();
+
+        let mut back_edges = function
+            .blocks
+            .iter()
+            .enumerate()
+            .filter_map(|(j, block)| {
+                // Structured loops have a single back-edge, from a block after their header
+                let (label, header) = branch_targets(block.instructions.last()?)
+                    .filter_map(|x| Some((x, *headers.get(&x)?)))
+                    .find(|(_, header)| *header <= j)?;
+                Some((header, j, label))
+            })
+            .collect::<Vec<_>>();
+
+        back_edges.sort_unstable();
+        for (loop_index, (_, block, label)) in (0..).zip(back_edges) {
+            sites.push(Site {
+                function: i,
+                block,
+                kind: CounterKind::BackEdge { loop_index },
+                header: Some(label),
+            });
+        }
+    }
+
+    if sites.is_empty() {
+        return Ok(Vec::new());
+    }
+
+    // Counters buffer
+    let uint = builder.type_int(32, 0);
+    let len = builder.constant_u32(uint, sites.len() as u32);
+    let zero = builder.constant_u32(uint, 0);
+    let one = builder.constant_u32(uint, 1);
+    let scope = builder.constant_u32(uint, Scope::Device as u32);
+    let indices = (0..sites.len() as u32)
+        .map(|i| builder.constant_u32(uint, i))
+        .collect::<Vec<_>>();
+
+    let (storage_class, block_decoration) = match version >= Version::V1_3 {
+        true => (StorageClass::StorageBuffer, Decoration::Block),
+        false => (StorageClass::Uniform, Decoration::BufferBlock),
+    };
+
+    // The types are never deduplicated, so that their decorations can't clash with the ones of other types
+    let array_type = builder.id();
+    let struct_type = builder.id();
+    builder.module_mut().types_global_values.extend([
+        Instruction::new(
+            Op::TypeArray,
+            None,
+            Some(array_type),
+            vec![Operand::IdRef(uint), Operand::IdRef(len)],
+        ),
+        Instruction::new(
+            Op::TypeStruct,
+            None,
+            Some(struct_type),
+            vec![Operand::IdRef(array_type)],
+        ),
+    ]);
+    builder.decorate(
+        array_type,
+        Decoration::ArrayStride,
+        Some(Operand::LiteralInt32(4)),
+    );
+    builder.member_decorate(
+        struct_type,
+        0,
+        Decoration::Offset,
+        Some(Operand::LiteralInt32(0)),
+    );
+    builder.decorate(struct_type, block_decoration, None);
+
+    let pointer_type = builder.type_pointer(None, storage_class, struct_type);
+    let counter_pointer_type = builder.type_pointer(None, storage_class, uint);
+    let variable = builder.id();
+    builder
+        .module_mut()
+        .types_global_values
+        .push(Instruction::new(
+            Op::Variable,
+            Some(pointer_type),
+            Some(variable),
+            vec![Operand::StorageClass(storage_class)],
+        ));
+    builder.decorate(
+        variable,
+        Decoration::DescriptorSet,
+        Some(Operand::LiteralInt32(buffer.set)),
+    );
+    builder.decorate(
+        variable,
+        Decoration::Binding,
+        Some(Operand::LiteralInt32(buffer.binding)),
+    );
+
+    // Increments
+    let mut result = Vec::with_capacity(sites.len());
+    for (site, index) in sites.into_iter().zip(indices) {
+        let mut instructions = Vec::with_capacity(3);
+
+        let pointer = builder.id();
+        instructions.push(Instruction::new(
+            Op::AccessChain,
+            Some(counter_pointer_type),
+            Some(pointer),
+            vec![
+                Operand::IdRef(variable),
+                Operand::IdRef(zero),
+                Operand::IdRef(index),
+            ],
+        ));
+
+        let block = &builder.module_ref().functions[site.function].blocks[site.block];
+        let terminator = block
+            .instructions
+            .last()
+            .cloned()
+            .ok_or_else(Error::unexpected)?;
+
+        let (position, value) = match site.header {
+            // Variables must come first in the entry block
+            None => {
+                let position = block
+                    .instructions
+                    .iter()
+                    .take_while(|x| x.class.opcode == Op::Variable)
+                    .count();
+                (position, one)
+            }
+
+            // Before the terminator and its merge instruction
+            Some(header) => {
+                let merges = block
+                    .instructions
+                    .iter()
+                    .rev()
+                    .skip(1)
+                    .take_while(|x| matches!(x.class.opcode, Op::LoopMerge | Op::SelectionMerge))
+                    .count();
+                let position = block.instructions.len() - 1 - merges;
+
+                // Conditional back-edges only count when they are taken
+                let value = match (terminator.class.opcode, &terminator.operands[..]) {
+                    (
+                        Op::BranchConditional,
+                        [Operand::IdRef(condition), Operand::IdRef(true_label), Operand::IdRef(false_label), ..],
+                    ) if true_label != false_label => {
+                        let (taken, not_taken) = match *true_label == header {
+                            true => (one, zero),
+                            false => (zero, one),
+                        };
+
+                        let value = builder.id();
+                        instructions.push(Instruction::new(
+                            Op::Select,
+                            Some(uint),
+                            Some(value),
+                            vec![
+                                Operand::IdRef(*condition),
+                                Operand::IdRef(taken),
+                                Operand::IdRef(not_taken),
+                            ],
+                        ));
+                        value
+                    }
+                    _ => one,
+                };
+
+                (position, value)
+            }
+        };
+
+        let semantics = zero;
+        let increment = builder.id();
+        instructions.push(Instruction::new(
+            Op::AtomicIAdd,
+            Some(uint),
+            Some(increment),
+            vec![
+                Operand::IdRef(pointer),
+                Operand::IdRef(scope),
+                Operand::IdRef(semantics),
+                Operand::IdRef(value),
+            ],
+        ));
+
+        builder.module_mut().functions[site.function].blocks[site.block]
+            .instructions
+            .splice(position..position, instructions);
+
+        result.push(ProfileCounter {
+            offset: 4 * result.len() as u32,
+            function: functions[site.function],
+            kind: site.kind,
+        });
+    }
+
+    return Ok(result);
+}
+
+fn branch_targets(terminator: &Instruction) -> impl '_ + Iterator<Item = Word> {
+    let targets = match terminator.class.opcode {
+        Op::Branch => &terminator.operands[..1],
+        Op::BranchConditional => &terminator.operands[1..3],
+        Op::Switch => &terminator.operands[1..],
+        _ => &[],
+    };
+
+    return targets.iter().filter_map(Operand::id_ref_any);
+}
@@ -0,0 +1,346 @@
+use crate::{error::Result, Compilation};
+use rspirv::{
+    dr::{Instruction, Module, Operand},
+    spirv::{
+        BuiltIn, Capability, Decoration, ExecutionMode, ExecutionModel, Op, StorageClass, Word,
+    },
+};
+use serde::{Deserialize, Serialize};
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct Reflection {
+    pub entry_points: Vec<EntryPointReflection>,
+    pub descriptor_bindings: Vec<DescriptorBinding>,
+    pub push_constants: Vec<PushConstantRange>,
+    pub inputs: Vec<InterfaceVariable>,
+    pub outputs: Vec<InterfaceVariable>,
+    pub capabilities: Vec<Capability>,
+    pub extensions: Vec<String>,
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct EntryPointReflection {
+    pub name: String,
+    pub execution_model: ExecutionModel,
+    pub execution_modes: Vec<ExecutionModeReflection>,
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct ExecutionModeReflection {
+    pub mode: ExecutionMode,
+    pub operands: Vec<u32>,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum DescriptorType {
+    UniformBuffer,
+    StorageBuffer,
+    /// Descriptor kind not (yet) recognized by the reflection
+    Other,
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct DescriptorBinding {
+    pub name: Option<String>,
+    pub set: u32,
+    pub binding: u32,
+    pub descriptor_type: DescriptorType,
+    pub storage_class: StorageClass,
+    /// Size (in bytes) of the sized part of the binding
+    pub size: Option<u32>,
+    /// Stride (in bytes) of the binding's trailing runtime array, if it has one
+    pub runtime_array_stride: Option<u32>,
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct PushConstantRange {
+    pub name: Option<String>,
+    pub offset: u32,
+    pub size: Option<u32>,
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct InterfaceVariable {
+    pub name: Option<String>,
+    pub location: Option<u32>,
+    pub builtin: Option<BuiltIn>,
+    pub size: Option<u32>,
+}
+
+impl Compilation {
+    /// Returns a structured description of the compiled module's interface
+    pub fn reflect(&self) -> Result<Reflection> {
+        let module = self.module()?;
+        let mut result = Reflection {
+            entry_points: Vec::with_capacity(module.entry_points.len()),
+            descriptor_bindings: Vec::new(),
+            push_constants: Vec::new(),
+            inputs: Vec::new(),
+            outputs: Vec::new(),
+            capabilities: Vec::with_capacity(module.capabilities.len()),
+            extensions: Vec::with_capacity(module.extensions.len()),
+        };
+
+        for capability in module.capabilities.iter() {
+            if let Some(Operand::Capability(capability)) = capability.operands.first() {
+                result.capabilities.push(*capability);
+            }
+        }
+
+        for extension in module.extensions.iter() {
+            if let Some(Operand::LiteralString(extension)) = extension.operands.first() {
+                result.extensions.push(extension.clone());
+            }
+        }
+
+        for entry_point in module.entry_points.iter() {
+            let (execution_model, function, name) = match &entry_point.operands[..] {
+                [Operand::ExecutionModel(model), Operand::IdRef(function), Operand::LiteralString(name), ..] => {
+                    (*model, *function, name.clone())
+                }
+                _ => continue,
+            };
+
+            let execution_modes = module
+                .execution_modes
+                .iter()
+                .filter_map(|instr| match &instr.operands[..] {
+                    [Operand::IdRef(target), Operand::ExecutionMode(mode), operands @ ..]
+                        if *target == function =>
+                    {
+                        Some(ExecutionModeReflection {
+                            mode: *mode,
+                            operands: operands.iter().filter_map(literal_u32).collect(),
+                        })
+                    }
+                    _ => None,
+                })
+                .collect();
+
+            result.entry_points.push(EntryPointReflection {
+                name,
+                execution_model,
+                execution_modes,
+            });
+        }
+
+        for variable in module
+            .types_global_values
+            .iter()
+            .filter(|x| x.class.opcode == Op::Variable)
+        {
+            let (id, storage_class) = match (variable.result_id, variable.operands.first()) {
+                (Some(id), Some(Operand::StorageClass(storage_class))) => (id, *storage_class),
+                _ => continue,
+            };
+
+            let pointee = variable
+                .result_type
+                .and_then(|ty| find_type(module, ty))
+                .and_then(|ty| match ty.operands.get(1) {
+                    Some(Operand::IdRef(pointee)) => Some(*pointee),
+                    _ => None,
+                });
+
+            let name = debug_name(module, id);
+            match storage_class {
+                StorageClass::Input | StorageClass::Output => {
+                    let interface = InterfaceVariable {
+                        name,
+                        location: decoration(module, id, Decoration::Location),
+                        builtin: module.annotations.iter().find_map(|instr| {
+                            match &instr.operands[..] {
+                                [Operand::IdRef(target), Operand::Decoration(Decoration::BuiltIn), Operand::BuiltIn(builtin)]
+                                    if *target == id =>
+                                {
+                                    Some(*builtin)
+                                }
+                                _ => None,
+                            }
+                        }),
+                        size: pointee.and_then(|ty| type_size(module, ty)),
+                    };
+
+                    match storage_class {
+                        StorageClass::Input => result.inputs.push(interface),
+                        _ => result.outputs.push(interface),
+                    }
+                }
+
+                StorageClass::PushConstant => result.push_constants.push(PushConstantRange {
+                    name,
+                    offset: pointee
+                        .and_then(|ty| min_member_offset(module, ty))
+                        .unwrap_or_default(),
+                    size: pointee.and_then(|ty| type_size(module, ty)),
+                }),
+
+                _ => {
+                    let (set, binding) = match (
+                        decoration(module, id, Decoration::DescriptorSet),
+                        decoration(module, id, Decoration::Binding),
+                    ) {
+                        (Some(set), Some(binding)) => (set, binding),
+                        _ => continue,
+                    };
+
+                    let descriptor_type = match storage_class {
+                        StorageClass::StorageBuffer => DescriptorType::StorageBuffer,
+                        StorageClass::Uniform
+                            if pointee.is_some_and(|ty| {
+                                has_decoration(module, ty, Decoration::BufferBlock)
+                            }) =>
+                        {
+                            DescriptorType::StorageBuffer
+                        }
+                        StorageClass::Uniform => DescriptorType::UniformBuffer,
+                        _ => DescriptorType::Other,
+                    };
+
+                    result.descriptor_bindings.push(DescriptorBinding {
+                        name,
+                        set,
+                        binding,
+                        descriptor_type,
+                        storage_class,
+                        size: pointee.and_then(|ty| type_size(module, ty)),
+                        runtime_array_stride: pointee
+                            .and_then(|ty| runtime_array_stride(module, ty)),
+                    })
+                }
+            }
+        }
+
+        result
+            .descriptor_bindings
+            .sort_by_key(|x| (x.set, x.binding));
+        // Variables aliasing the same binding are reported once
+        result
+            .descriptor_bindings
+            .dedup_by_key(|x| (x.set, x.binding));
+        return Ok(result);
+    }
+}
+
+fn literal_u32(operand: &Operand) -> Option<u32> {
+    match operand {
+        Operand::LiteralInt32(x) => Some(*x),
+        _ => None,
+    }
+}
+
+fn find_type(module: &Module, id: Word) -> Option<&Instruction> {
+    module
+        .types_global_values
+        .iter()
+        .find(|x| x.result_id == Some(id))
+}
+
+fn debug_name(module: &Module, id: Word) -> Option<String> {
+    module
+        .debug_names
+        .iter()
+        .find_map(|instr| match &instr.operands[..] {
+            [Operand::IdRef(target), Operand::LiteralString(name)] if *target == id => {
+                Some(name.clone())
+            }
+            _ => None,
+        })
+}
+
+fn decoration(module: &Module, id: Word, decoration: Decoration) -> Option<u32> {
+    module
+        .annotations
+        .iter()
+        .find_map(|instr| match &instr.operands[..] {
+            [Operand::IdRef(target), Operand::Decoration(x), Operand::LiteralInt32(value)]
+                if *target == id && *x == decoration =>
+            {
+                Some(*value)
+            }
+            _ => None,
+        })
+}
+
+fn has_decoration(module: &Module, id: Word, decoration: Decoration) -> bool {
+    module.annotations.iter().any(|instr| {
+        matches!(
+            &instr.operands[..],
+            [Operand::IdRef(target), Operand::Decoration(x), ..] if *target == id && *x == decoration
+        )
+    })
+}
+
+fn member_offset(module: &Module, id: Word, member: u32) -> Option<u32> {
+    module
+        .annotations
+        .iter()
+        .find_map(|instr| match &instr.operands[..] {
+            [Operand::IdRef(target), Operand::LiteralInt32(idx), Operand::Decoration(Decoration::Offset), Operand::LiteralInt32(offset)]
+                if *target == id && *idx == member =>
+            {
+                Some(*offset)
+            }
+            _ => None,
+        })
+}
+
+fn min_member_offset(module: &Module, id: Word) -> Option<u32> {
+    let ty = find_type(module, id)?;
+    if ty.class.opcode != Op::TypeStruct {
+        return Some(0);
+    }
+
+    return (0..ty.operands.len() as u32)
+        .filter_map(|i| member_offset(module, id, i))
+        .min();
+}
+
+fn type_size(module: &Module, id: Word) -> Option<u32> {
+    let ty = find_type(module, id)?;
+    return match (ty.class.opcode, &ty.operands[..]) {
+        (Op::TypeInt | Op::TypeFloat, [Operand::LiteralInt32(width), ..]) => Some(width / 8),
+        (Op::TypeVector, [Operand::IdRef(elem), Operand::LiteralInt32(count)]) => {
+            Some(type_size(module, *elem)? * count)
+        }
+        (Op::TypeMatrix, [Operand::IdRef(column), Operand::LiteralInt32(count)]) => {
+            Some(type_size(module, *column)? * count)
+        }
+        (Op::TypeArray, [Operand::IdRef(elem), Operand::IdRef(length)]) => {
+            let length = match find_type(module, *length)?.operands.first()? {
+                Operand::LiteralInt32(x) => *x,
+                Operand::LiteralInt64(x) => u32::try_from(*x).ok()?,
+                _ => return None,
+            };
+            let stride = decoration(module, id, Decoration::ArrayStride)
+                .or_else(|| type_size(module, *elem))?;
+            Some(stride * length)
+        }
+        (Op::TypeRuntimeArray, _) => Some(0),
+        (Op::TypeStruct, members) => {
+            let mut size = 0;
+            for (member, i) in members.iter().zip(0..) {
+                let member = match member {
+                    Operand::IdRef(x) => *x,
+                    _ => return None,
+                };
+                let offset = member_offset(module, id, i).unwrap_or(size);
+                size = u32::max(size, offset + type_size(module, member)?);
+            }
+            Some(size)
+        }
+        _ => None,
+    };
+}
+
+fn runtime_array_stride(module: &Module, id: Word) -> Option<u32> {
+    let ty = find_type(module, id)?;
+    return match (ty.class.opcode, &ty.operands[..]) {
+        (Op::TypeRuntimeArray, [Operand::IdRef(elem)]) => {
+            decoration(module, id, Decoration::ArrayStride).or_else(|| type_size(module, *elem))
+        }
+        (Op::TypeStruct, [.., Operand::IdRef(last)]) => runtime_array_stride(module, *last),
+        _ => None,
+    };
+}
@@ -0,0 +1,113 @@
+use crate::{
+    error::{Error, Result},
+    Compilation,
+};
+use rspirv::{
+    dr::{Function, Instruction, Module, Operand},
+    spirv::{Op, Word},
+};
+use std::collections::HashSet;
+
+impl Compilation {
+    /// Splits the module into one standalone module per entry point, named after it, without the items it doesn't use
+    pub fn split_entry_points(&self) -> Result<Vec<(String, Compilation)>> {
+        let module = self.module()?;
+        let mut result = Vec::with_capacity(module.entry_points.len());
+
+        for entry_point in module.entry_points.iter() {
+            let (function, name) = match &entry_point.operands[..] {
+                [_, Operand::IdRef(function), Operand::LiteralString(name), ..] => {
+                    (*function, name.clone())
+                }
+                _ => return Err(Error::unexpected()),
+            };
+
+            let module = prune(module, entry_point, function);
+            let mut compilation =
+                Compilation::from_module(self.platform, module, self.origins.clone());
+            compilation.wasm_features = self.wasm_features;
+            compilation.profile_counters = self.profile_counters.clone();
+            compilation.panics = self.panics.clone();
+            result.push((name, compilation));
+        }
+
+        return Ok(result);
+    }
+}
+
+fn prune(module: &Module, entry_point: &Instruction, function: Word) -> Module {
+    let mut result = module.clone();
+    result.entry_points = vec![entry_point.clone()];
+    result
+        .execution_modes
+        .retain(|x| matches!(x.operands.first(), Some(Operand::IdRef(id)) if *id == function));
+
+    // Functions reachable from the entry point
+    let mut reachable = HashSet::new();
+    let mut pending = vec![function];
+    while let Some(id) = pending.pop() {
+        if !reachable.insert(id) {
+            continue;
+        }
+
+        if let Some(f) = module.functions.iter().find(|x| function_id(x) == Some(id)) {
+            pending.extend(
+                function_instructions(f).filter_map(|x| match x.class.opcode {
+                    Op::FunctionCall => x.operands.first().and_then(Operand::id_ref_any),
+                    _ => None,
+                }),
+            );
+        }
+    }
+
+    let mut removed = HashSet::new();
+    result.functions.retain(|f| {
+        let keep = function_id(f).is_some_and(|id| reachable.contains(&id));
+        if !keep {
+            removed.extend(
+                f.def
+                    .iter()
+                    .chain(f.parameters.iter())
+                    .chain(function_instructions(f))
+                    .filter_map(|x| x.result_id),
+            );
+        }
+        keep
+    });
+
+    // Global variables used by the remaining functions (or the entry point's interface)
+    let used = result
+        .functions
+        .iter()
+        .flat_map(function_instructions)
+        .chain(result.entry_points.iter())
+        .flat_map(|x| x.operands.iter())
+        .filter_map(Operand::id_ref_any)
+        .collect::<HashSet<_>>();
+
+    result.types_global_values.retain(|x| {
+        let keep =
+            x.class.opcode != Op::Variable || x.result_id.is_some_and(|id| used.contains(&id));
+        if !keep {
+            removed.extend(x.result_id);
+        }
+        keep
+    });
+
+    let targets_removed = |x: &Instruction| matches!(x.operands.first(), Some(Operand::IdRef(id)) if removed.contains(id));
+    result.annotations.retain(|x| !targets_removed(x));
+    result.debug_names.retain(|x| !targets_removed(x));
+
+    return result;
+}
+
+fn function_id(function: &Function) -> Option<Word> {
+    return function.def.as_ref().and_then(|x| x.result_id);
+}
+
+fn function_instructions(function: &Function) -> impl Iterator<Item = &Instruction> {
+    return function
+        .blocks
+        .iter()
+        .flat_map(|x| x.label.iter().chain(x.instructions.iter()));
+}
@@ -0,0 +1,301 @@
+//! Size statistics of compiled modules, meant to track their growth over time
+
+use crate::{
+    error::Result,
+    reflection::{DescriptorBinding, DescriptorType},
+    Compilation,
+};
+use rspirv::{
+    dr::{Module, Operand},
+    spirv::{Capability, ExecutionModel, Op, Word},
+};
+use serde::{Deserialize, Serialize};
+use std::{
+    collections::{BTreeMap, HashMap, HashSet},
+    fmt::Display,
+};
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct Stats {
+    /// Total number of instructions of the module
+    pub instructions: usize,
+    /// Number of instructions of every class, for the classes used by the module
+    pub classes: BTreeMap<OpcodeClass, usize>,
+    /// Upper bound of the ids of the module
+    pub id_bound: u32,
+    /// Number of constants (including specialization constants) of the module
+    pub constants: usize,
+    pub capabilities: Vec<Capability>,
+    pub entry_points: Vec<EntryPointStats>,
+    /// Descriptor bindings of the module, as found by [`Compilation::reflect`]
+    pub bindings: Vec<DescriptorBinding>,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct EntryPointStats {
+    pub name: String,
+    pub execution_model: ExecutionModel,
+    /// Number of instructions of the entry point's function, and of every function it (transitively) calls
+    pub instructions: usize,
+}
+
+/// Class of a SPIR-V instruction, following the grouping of the specification
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum OpcodeClass {
+    ModeSetting,
+    Debug,
+    Annotation,
+    Type,
+    Constant,
+    Memory,
+    Function,
+    Composite,
+    Conversion,
+    Arithmetic,
+    Bit,
+    Relational,
+    ControlFlow,
+    Atomic,
+    Barrier,
+    Extended,
+    Other,
+}
+
+impl OpcodeClass {
+    pub fn of(opcode: Op) -> Self {
+        return match opcode {
+            Op::Capability
+            | Op::Extension
+            | Op::ExtInstImport
+            | Op::MemoryModel
+            | Op::EntryPoint
+            | Op::ExecutionMode
+            | Op::ExecutionModeId => Self::ModeSetting,
+            Op::SourceContinued
+            | Op::Source
+            | Op::SourceExtension
+            | Op::Name
+            | Op::MemberName
+            | Op::String
+            | Op::Line
+            | Op::NoLine
+            | Op::ModuleProcessed => Self::Debug,
+            Op::Decorate
+            | Op::MemberDecorate
+            | Op::DecorationGroup
+            | Op::GroupDecorate
+            | Op::GroupMemberDecorate
+            | Op::DecorateId => Self::Annotation,
+            Op::Undef => Self::Constant,
+            Op::Function | Op::FunctionParameter | Op::FunctionEnd | Op::FunctionCall => {
+                Self::Function
+            }
+            Op::Variable
+            | Op::ImageTexelPointer
+            | Op::Load
+            | Op::Store
+            | Op::CopyMemory
+            | Op::CopyMemorySized
+            | Op::AccessChain
+            | Op::InBoundsAccessChain
+            | Op::PtrAccessChain
+            | Op::ArrayLength
+            | Op::InBoundsPtrAccessChain => Self::Memory,
+            Op::Phi
+            | Op::LoopMerge
+            | Op::SelectionMerge
+            | Op::Label
+            | Op::Branch
+            | Op::BranchConditional
+            | Op::Switch
+            | Op::Kill
+            | Op::Return
+            | Op::ReturnValue
+            | Op::Unreachable
+            | Op::TerminateInvocation => Self::ControlFlow,
+            Op::ControlBarrier | Op::MemoryBarrier => Self::Barrier,
+            Op::ExtInst => Self::Extended,
+            // The rest of the classes are contiguous ranges of opcodes
+            _ => match opcode as u32 {
+                // OpTypeVoid to OpTypeForwardPointer
+                19..=39 => Self::Type,
+                // OpConstantTrue to OpSpecConstantOp
+                41..=52 => Self::Constant,
+                // OpVectorExtractDynamic to OpTranspose
+                77..=84 => Self::Composite,
+                // OpConvertFToU to OpBitcast
+                109..=124 => Self::Conversion,
+                // OpSNegate to OpSMulExtended
+                126..=152 => Self::Arithmetic,
+                // OpAny to OpFUnordGreaterThanEqual
+                154..=191 => Self::Relational,
+                // OpShiftRightLogical to OpBitCount
+                194..=205 => Self::Bit,
+                // OpAtomicLoad to OpAtomicXor
+                227..=242 => Self::Atomic,
+                _ => Self::Other,
+            },
+        };
+    }
+}
+
+impl Compilation {
+    /// Returns size statistics of the compiled module
+    pub fn stats(&self) -> Result<Stats> {
+        let module = self.module()?;
+        let reflection = self.reflect()?;
+
+        let mut instructions = 0;
+        let mut classes = BTreeMap::<OpcodeClass, usize>::new();
+        for instr in module.all_inst_iter() {
+            instructions += 1;
+            *classes
+                .entry(OpcodeClass::of(instr.class.opcode))
+                .or_default() += 1;
+        }
+
+        let constants = module
+            .types_global_values
+            .iter()
+            .filter(|x| OpcodeClass::of(x.class.opcode) == OpcodeClass::Constant)
+            .count();
+
+        let entry_points = module
+            .entry_points
+            .iter()
+            .filter_map(|instr| match &instr.operands[..] {
+                [Operand::ExecutionModel(model), Operand::IdRef(function), Operand::LiteralString(name), ..] => {
+                    Some(EntryPointStats {
+                        name: name.clone(),
+                        execution_model: *model,
+                        instructions: reachable_instructions(module, *function),
+                    })
+                }
+                _ => None,
+            })
+            .collect();
+
+        return Ok(Stats {
+            instructions,
+            classes,
+            id_bound: module.header.as_ref().map_or(0, |x| x.bound),
+            constants,
+            capabilities: reflection.capabilities,
+            entry_points,
+            bindings: reflection.descriptor_bindings,
+        });
+    }
+}
+
+fn reachable_instructions(module: &Module, function: Word) -> usize {
+    let functions = module
+        .functions
+        .iter()
+        .filter_map(|f| Some((f.def.as_ref()?.result_id?, f)))
+        .collect::<HashMap<_, _>>();
+
+    let mut result = 0;
+    let mut visited = HashSet::new();
+    let mut pending = vec![function];
+    while let Some(id) = pending.pop() {
+        if !visited.insert(id) {
+            continue;
+        }
+        let Some(f) = functions.get(&id) else {
+            continue;
+        };
+
+        for instr in f.all_inst_iter() {
+            result += 1;
+            if let (Op::FunctionCall, Some(Operand::IdRef(callee))) =
+                (instr.class.opcode, instr.operands.first())
+            {
+                pending.push(*callee);
+            }
+        }
+    }
+
+    return result;
+}
+
+impl Display for OpcodeClass {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        let name = match self {
+            Self::ModeSetting => "mode setting",
+            Self::Debug => "debug",
+            Self::Annotation => "annotation",
+            Self::Type => "type",
+            Self::Constant => "constant",
+            Self::Memory => "memory",
+            Self::Function => "function",
+            Self::Composite => "composite",
+            Self::Conversion => "conversion",
+            Self::Arithmetic => "arithmetic",
+            Self::Bit => "bit",
+            Self::Relational => "relational and logical",
+            Self::ControlFlow => "control flow",
+            Self::Atomic => "atomic",
+            Self::Barrier => "barrier",
+            Self::Extended => "extended instruction set",
+            Self::Other => "other",
+        };
+        return f.write_str(name);
+    }
+}
+
+impl Display for Stats {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        writeln!(
+            f,
+            "{} instructions, {} constants, id bound of {}",
+            self.instructions, self.constants, self.id_bound
+        )?;
+        for (class, count) in self.classes.iter() {
+            writeln!(f, "  {:<24} {count}", class.to_string())?;
+        }
+
+        let capabilities = self
+            .capabilities
+            .iter()
+            .map(|x| format!("{x:?}"))
+            .collect::<Vec<_>>();
+        writeln!(f, "Capabilities: {}", capabilities.join(", "))?;
+
+        writeln!(f, "Entry points:")?;
+        for entry_point in self.entry_points.iter() {
+            writeln!(
+                f,
+                "  {} ({:?}): {} instructions",
+                entry_point.name, entry_point.execution_model, entry_point.instructions
+            )?;
+        }
+
+        writeln!(f, "Bindings:")?;
+        for binding in self.bindings.iter() {
+            let kind = match binding.descriptor_type {
+                DescriptorType::UniformBuffer => "uniform buffer",
+                DescriptorType::StorageBuffer => "storage buffer",
+                DescriptorType::Other => "descriptor",
+            };
+
+            write!(
+                f,
+                "  set {}, binding {}: {kind}",
+                binding.set, binding.binding
+            )?;
+            if let Some(name) = &binding.name {
+                write!(f, " `{name}`")?;
+            }
+            match (binding.size, binding.runtime_array_stride) {
+                (Some(size), Some(stride)) => write!(f, " ({size} bytes + {stride} per element)")?,
+                (Some(size), None) => write!(f, " ({size} bytes)")?,
+                (None, Some(stride)) => write!(f, " ({stride} bytes per element)")?,
+                (None, None) => {}
+            }
+            writeln!(f)?;
+        }
+
+        return Ok(());
+    }
+}
@@ -0,0 +1,13 @@
+//! Helpers to run compiled modules on the GPU, for end-to-end tests
+
+use docfg::docfg;
+
+#[cfg(feature = "testing")]
+mod vulkan;
+#[cfg(feature = "wgpu-test")]
+mod webgpu;
+
+#[docfg(feature = "testing")]
+pub use vulkan::is_available;
+#[docfg(feature = "wgpu-test")]
+pub use webgpu::{is_wgpu_available, WgpuShader};
@@ -0,0 +1,486 @@
+use crate::{
+    error::{Error, Result},
+    reflection::{DescriptorBinding, DescriptorType},
+    version::TargetPlatform,
+    Compilation,
+};
+use ash::vk;
+use rspirv::spirv::{Capability, ExecutionModel};
+use std::ffi::CString;
+
+/// Returns `true` if a Vulkan driver with a compute-capable device is available to run modules on
+pub fn is_available() -> bool {
+    let Ok(entry) = (unsafe { ash::Entry::load() }) else {
+        return false;
+    };
+
+    let instance = match unsafe { entry.create_instance(&vk::InstanceCreateInfo::default(), None) }
+    {
+        Ok(instance) => instance,
+        Err(_) => return false,
+    };
+
+    let result = compute_device(&instance).is_ok();
+    unsafe { instance.destroy_instance(None) };
+    return result;
+}
+
+impl Compilation {
+    /// Runs the module's compute entry point on a single Vulkan workgroup
+    #[inline]
+    pub fn execute_compute(&self, input_buffers: &[&[u8]]) -> Result<Vec<Vec<u8>>> {
+        return self.execute_compute_with(input_buffers, [1, 1, 1]);
+    }
+
+    /// Runs the module's compute entry point on `group_count` Vulkan workgroups. Every buffer of the module is bound
+    /// in ascending order of set and binding, initialized with the matching entry of `input_buffers`, and returned
+    pub fn execute_compute_with(
+        &self,
+        input_buffers: &[&[u8]],
+        group_count: [u32; 3],
+    ) -> Result<Vec<Vec<u8>>> {
+        let api_version = match self.platform {
+            TargetPlatform::Vulkan(version) => {
+                vk::make_api_version(0, version.major as u32, version.minor as u32, 0)
+            }
+            other => {
+                return Err(Error::msg(format!(
+                    "Only Vulkan modules can be executed, found '{other:?}'"
+                )))
+            }
+        };
+
+        let reflection = self.reflect()?;
+        if !reflection.push_constants.is_empty() {
+            return Err(Error::msg("Push constants are not supported"));
+        }
+
+        let mut entry_points = reflection
+            .entry_points
+            .iter()
+            .filter(|x| x.execution_model == ExecutionModel::GLCompute);
+        let entry_point = match (entry_points.next(), entry_points.next()) {
+            (Some(entry_point), None) => CString::new(entry_point.name.as_str())
+                .map_err(|_| Error::msg("Entry point names can't contain nul bytes"))?,
+            _ => {
+                return Err(Error::msg(
+                    "Exactly one compute entry point must be specified",
+                ))
+            }
+        };
+
+        let mut bindings = reflection.descriptor_bindings.iter().collect::<Vec<_>>();
+        bindings.sort_by_key(|x| (x.set, x.binding));
+        if bindings.len() != input_buffers.len() {
+            return Err(Error::msg(format!(
+                "The module has {} buffers, but {} were provided",
+                bindings.len(),
+                input_buffers.len()
+            )));
+        }
+
+        let capabilities = &reflection.capabilities;
+        let entry = unsafe { ash::Entry::load() }.map_err(Error::custom)?;
+        let app_info = vk::ApplicationInfo::builder().api_version(api_version);
+        let instance = unsafe {
+            entry.create_instance(
+                &vk::InstanceCreateInfo::builder().application_info(&app_info),
+                None,
+            )?
+        };
+
+        let mut context = Context {
+            _entry: entry,
+            instance,
+            device: None,
+            buffers: Vec::with_capacity(bindings.len()),
+            set_layouts: Vec::new(),
+            pipeline_layout: vk::PipelineLayout::null(),
+            shader: vk::ShaderModule::null(),
+            pipeline: vk::Pipeline::null(),
+            descriptor_pool: vk::DescriptorPool::null(),
+            command_pool: vk::CommandPool::null(),
+            fence: vk::Fence::null(),
+        };
+
+        let (physical_device, queue_family) = compute_device(&context.instance)?;
+        let features = vk::PhysicalDeviceFeatures::builder()
+            .shader_int64(capabilities.contains(&Capability::Int64))
+            .shader_float64(capabilities.contains(&Capability::Float64))
+            .shader_int16(capabilities.contains(&Capability::Int16));
+        let mut variable_pointers = vk::PhysicalDeviceVariablePointersFeatures::builder()
+            .variable_pointers_storage_buffer(
+                capabilities.contains(&Capability::VariablePointersStorageBuffer)
+                    || capabilities.contains(&Capability::VariablePointers),
+            )
+            .variable_pointers(capabilities.contains(&Capability::VariablePointers));
+
+        let queue_info = [vk::DeviceQueueCreateInfo::builder()
+            .queue_family_index(queue_family)
+            .queue_priorities(&[1.0])
+            .build()];
+        let mut device_info = vk::DeviceCreateInfo::builder()
+            .queue_create_infos(&queue_info)
+            .enabled_features(&features);
+        // Variable pointers are core since Vulkan 1.1
+        if api_version >= vk::API_VERSION_1_1 {
+            device_info = device_info.push_next(&mut variable_pointers);
+        }
+
+        let device = unsafe {
+            context
+                .instance
+                .create_device(physical_device, &device_info, None)?
+        };
+        let device = &*context.device.insert(device);
+        let queue = unsafe { device.get_device_queue(queue_family, 0) };
+        let memory_properties = unsafe {
+            context
+                .instance
+                .get_physical_device_memory_properties(physical_device)
+        };
+
+        // Buffers
+        let mut sizes = Vec::with_capacity(bindings.len());
+        for (binding, input) in bindings.iter().zip(input_buffers) {
+            let size = input.len().max(binding.size.unwrap_or_default() as usize);
+            let (buffer, memory) =
+                create_buffer(device, &memory_properties, descriptor_type(binding)?, size)?;
+            context.buffers.push((buffer, memory));
+
+            unsafe {
+                let ptr =
+                    device.map_memory(memory, 0, vk::WHOLE_SIZE, vk::MemoryMapFlags::empty())?;
+                std::ptr::write_bytes(ptr.cast::<u8>(), 0, size);
+                std::ptr::copy_nonoverlapping(input.as_ptr(), ptr.cast::<u8>(), input.len());
+                device.unmap_memory(memory);
+            }
+            sizes.push(size);
+        }
+
+        // Descriptor set layouts
+        let set_count = bindings.last().map_or(0, |x| x.set + 1);
+        for set in 0..set_count {
+            let layout_bindings = bindings
+                .iter()
+                .filter(|x| x.set == set)
+                .map(|x| {
+                    Ok(vk::DescriptorSetLayoutBinding::builder()
+                        .binding(x.binding)
+                        .descriptor_type(descriptor_type(x)?)
+                        .descriptor_count(1)
+                        .stage_flags(vk::ShaderStageFlags::COMPUTE)
+                        .build())
+                })
+                .collect::<Result<Vec<_>>>()?;
+
+            let layout = unsafe {
+                device.create_descriptor_set_layout(
+                    &vk::DescriptorSetLayoutCreateInfo::builder().bindings(&layout_bindings),
+                    None,
+                )?
+            };
+            context.set_layouts.push(layout);
+        }
+
+        // Pipeline
+        context.pipeline_layout = unsafe {
+            device.create_pipeline_layout(
+                &vk::PipelineLayoutCreateInfo::builder().set_layouts(&context.set_layouts),
+                None,
+            )?
+        };
+        context.shader = unsafe {
+            device.create_shader_module(
+                &vk::ShaderModuleCreateInfo::builder().code(self.words()?),
+                None,
+            )?
+        };
+
+        let stage = vk::PipelineShaderStageCreateInfo::builder()
+            .stage(vk::ShaderStageFlags::COMPUTE)
+            .module(context.shader)
+            .name(&entry_point);
+        let pipeline_info = vk::ComputePipelineCreateInfo::builder()
+            .stage(stage.build())
+            .layout(context.pipeline_layout);
+        context.pipeline = unsafe {
+            device
+                .create_compute_pipelines(vk::PipelineCache::null(), &[pipeline_info.build()], None)
+                .map_err(|(_, e)| e)?[0]
+        };
+
+        // Descriptor sets
+        let mut descriptor_sets = Vec::new();
+        if !context.set_layouts.is_empty() {
+            let mut pool_sizes = Vec::<vk::DescriptorPoolSize>::new();
+            for binding in bindings.iter() {
+                let ty = descriptor_type(binding)?;
+                match pool_sizes.iter_mut().find(|x| x.ty == ty) {
+                    Some(pool_size) => pool_size.descriptor_count += 1,
+                    None => pool_sizes.push(vk::DescriptorPoolSize {
+                        ty,
+                        descriptor_count: 1,
+                    }),
+                }
+            }
+
+            // Sets without any binding still need to be allocated, so the pool can't be empty
+            if pool_sizes.is_empty() {
+                pool_sizes.push(vk::DescriptorPoolSize {
+                    ty: vk::DescriptorType::STORAGE_BUFFER,
+                    descriptor_count: 1,
+                });
+            }
+
+            context.descriptor_pool = unsafe {
+                device.create_descriptor_pool(
+                    &vk::DescriptorPoolCreateInfo::builder()
+                        .max_sets(set_count)
+                        .pool_sizes(&pool_sizes),
+                    None,
+                )?
+            };
+            descriptor_sets = unsafe {
+                device.allocate_descriptor_sets(
+                    &vk::DescriptorSetAllocateInfo::builder()
+                        .descriptor_pool(context.descriptor_pool)
+                        .set_layouts(&context.set_layouts),
+                )?
+            };
+
+            let buffer_infos = context
+                .buffers
+                .iter()
+                .map(|(buffer, _)| {
+                    [vk::DescriptorBufferInfo {
+                        buffer: *buffer,
+                        offset: 0,
+                        range: vk::WHOLE_SIZE,
+                    }]
+                })
+                .collect::<Vec<_>>();
+
+            let writes = bindings
+                .iter()
+                .zip(buffer_infos.iter())
+                .map(|(binding, info)| {
+                    Ok(vk::WriteDescriptorSet::builder()
+                        .dst_set(descriptor_sets[binding.set as usize])
+                        .dst_binding(binding.binding)
+                        .descriptor_type(descriptor_type(binding)?)
+                        .buffer_info(info)
+                        .build())
+                })
+                .collect::<Result<Vec<_>>>()?;
+            unsafe { device.update_descriptor_sets(&writes, &[]) };
+        }
+
+        // Dispatch
+        context.command_pool = unsafe {
+            device.create_command_pool(
+                &vk::CommandPoolCreateInfo::builder().queue_family_index(queue_family),
+                None,
+            )?
+        };
+        let command_buffer = unsafe {
+            device.allocate_command_buffers(
+                &vk::CommandBufferAllocateInfo::builder()
+                    .command_pool(context.command_pool)
+                    .level(vk::CommandBufferLevel::PRIMARY)
+                    .command_buffer_count(1),
+            )?[0]
+        };
+
+        unsafe {
+            device.begin_command_buffer(
+                command_buffer,
+                &vk::CommandBufferBeginInfo::builder()
+                    .flags(vk::CommandBufferUsageFlags::ONE_TIME_SUBMIT),
+            )?;
+            device.cmd_bind_pipeline(
+                command_buffer,
+                vk::PipelineBindPoint::COMPUTE,
+                context.pipeline,
+            );
+            if !descriptor_sets.is_empty() {
+                device.cmd_bind_descriptor_sets(
+                    command_buffer,
+                    vk::PipelineBindPoint::COMPUTE,
+                    context.pipeline_layout,
+                    0,
+                    &descriptor_sets,
+                    &[],
+                );
+            }
+
+            let [x, y, z] = group_count;
+            device.cmd_dispatch(command_buffer, x, y, z);
+
+            // Makes the shader's writes visible to the host
+            let barrier = vk::MemoryBarrier::builder()
+                .src_access_mask(vk::AccessFlags::SHADER_WRITE)
+                .dst_access_mask(vk::AccessFlags::HOST_READ);
+            device.cmd_pipeline_barrier(
+                command_buffer,
+                vk::PipelineStageFlags::COMPUTE_SHADER,
+                vk::PipelineStageFlags::HOST,
+                vk::DependencyFlags::empty(),
+                &[barrier.build()],
+                &[],
+                &[],
+            );
+            device.end_command_buffer(command_buffer)?;
+
+            context.fence = device.create_fence(&vk::FenceCreateInfo::default(), None)?;
+            let command_buffers = [command_buffer];
+            let submit = vk::SubmitInfo::builder().command_buffers(&command_buffers);
+            device.queue_submit(queue, &[submit.build()], context.fence)?;
+            device.wait_for_fences(&[context.fence], true, u64::MAX)?;
+        }
+
+        // Read back
+        let mut result = Vec::with_capacity(sizes.len());
+        for ((_, memory), size) in context.buffers.iter().zip(sizes) {
+            unsafe {
+                let ptr =
+                    device.map_memory(*memory, 0, vk::WHOLE_SIZE, vk::MemoryMapFlags::empty())?;
+                result.push(std::slice::from_raw_parts(ptr.cast::<u8>(), size).to_vec());
+                device.unmap_memory(*memory);
+            }
+        }
+
+        return Ok(result);
+    }
+}
+
+/// Owns every Vulkan object created for an execution, destroying them when dropped
+struct Context {
+    _entry: ash::Entry,
+    instance: ash::Instance,
+    device: Option<ash::Device>,
+    buffers: Vec<(vk::Buffer, vk::DeviceMemory)>,
+    set_layouts: Vec<vk::DescriptorSetLayout>,
+    pipeline_layout: vk::PipelineLayout,
+    shader: vk::ShaderModule,
+    pipeline: vk::Pipeline,
+    descriptor_pool: vk::DescriptorPool,
+    command_pool: vk::CommandPool,
+    fence: vk::Fence,
+}
+
+impl Drop for Context {
+    fn drop(&mut self) {
+        unsafe {
+            // Destroying null handles is a no-op, so partially initialized contexts are fine
+            if let Some(device) = self.device.take() {
+                let _ = device.device_wait_idle();
+                device.destroy_fence(self.fence, None);
+                device.destroy_command_pool(self.command_pool, None);
+                device.destroy_descriptor_pool(self.descriptor_pool, None);
+                device.destroy_pipeline(self.pipeline, None);
+                device.destroy_shader_module(self.shader, None);
+                device.destroy_pipeline_layout(self.pipeline_layout, None);
+                for layout in self.set_layouts.drain(..) {
+                    device.destroy_descriptor_set_layout(layout, None);
+                }
+                for (buffer, memory) in self.buffers.drain(..) {
+                    device.destroy_buffer(buffer, None);
+                    device.free_memory(memory, None);
+                }
+                device.destroy_device(None);
+            }
+            self.instance.destroy_instance(None);
+        }
+    }
+}
+
+fn compute_device(instance: &ash::Instance) -> Result<(vk::PhysicalDevice, u32)> {
+    for physical_device in unsafe { instance.enumerate_physical_devices()? } {
+        let families =
+            unsafe { instance.get_physical_device_queue_family_properties(physical_device) };
+        if let Some(family) = families
+            .iter()
+            .position(|x| x.queue_flags.contains(vk::QueueFlags::COMPUTE))
+        {
+            return Ok((physical_device, family as u32));
+        }
+    }
+
+    return Err(Error::msg(
+        "No Vulkan device with compute support was found",
+    ));
+}
+
+fn descriptor_type(binding: &DescriptorBinding) -> Result<vk::DescriptorType> {
+    return match binding.descriptor_type {
+        DescriptorType::StorageBuffer => Ok(vk::DescriptorType::STORAGE_BUFFER),
+        DescriptorType::UniformBuffer => Ok(vk::DescriptorType::UNIFORM_BUFFER),
+        DescriptorType::Other => Err(Error::msg(format!(
+            "Unsupported descriptor at set {} and binding {}",
+            binding.set, binding.binding
+        ))),
+    };
+}
+
+fn create_buffer(
+    device: &ash::Device,
+    memory_properties: &vk::PhysicalDeviceMemoryProperties,
+    ty: vk::DescriptorType,
+    size: usize,
+) -> Result<(vk::Buffer, vk::DeviceMemory)> {
+    let usage = match ty {
+        vk::DescriptorType::UNIFORM_BUFFER => vk::BufferUsageFlags::UNIFORM_BUFFER,
+        _ => vk::BufferUsageFlags::STORAGE_BUFFER,
+    };
+
+    let buffer = unsafe {
+        device.create_buffer(
+            &vk::BufferCreateInfo::builder()
+                // Zero-sized buffers aren't allowed
+                .size(size.max(4) as vk::DeviceSize)
+                .usage(usage)
+                .sharing_mode(vk::SharingMode::EXCLUSIVE),
+            None,
+        )?
+    };
+
+    let requirements = unsafe { device.get_buffer_memory_requirements(buffer) };
+    let flags = vk::MemoryPropertyFlags::HOST_VISIBLE | vk::MemoryPropertyFlags::HOST_COHERENT;
+    let memory_type = (0..memory_properties.memory_type_count).find(|&i| {
+        requirements.memory_type_bits & (1 << i) != 0
+            && memory_properties.memory_types[i as usize]
+                .property_flags
+                .contains(flags)
+    });
+
+    let Some(memory_type) = memory_type else {
+        unsafe { device.destroy_buffer(buffer, None) };
+        return Err(Error::msg("No host-visible memory type was found"));
+    };
+
+    let memory = match unsafe {
+        device.allocate_memory(
+            &vk::MemoryAllocateInfo::builder()
+                .allocation_size(requirements.size)
+                .memory_type_index(memory_type),
+            None,
+        )
+    } {
+        Ok(memory) => memory,
+        Err(e) => {
+            unsafe { device.destroy_buffer(buffer, None) };
+            return Err(e.into());
+        }
+    };
+
+    if let Err(e) = unsafe { device.bind_buffer_memory(buffer, memory, 0) } {
+        unsafe {
+            device.destroy_buffer(buffer, None);
+            device.free_memory(memory, None);
+        }
+        return Err(e.into());
+    }
+
+    return Ok((buffer, memory));
+}
@@ -0,0 +1,208 @@
+use crate::{
+    error::{Error, Result},
+    reflection::DescriptorType,
+    Compilation,
+};
+use std::{borrow::Cow, sync::mpsc};
+use wgpu::util::DeviceExt;
+
+/// Shader source handed to `wgpu`
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Default)]
+pub enum WgpuShader {
+    /// Translates the module into WGSL (see [`Compilation::wgsl`]), exercising the same path as WGSL consumers
+    #[default]
+    Wgsl,
+    /// Passes naga's IR of the module directly (see [`Compilation::naga_module`])
+    Naga,
+}
+
+/// Returns `true` if `wgpu` finds an adapter (on any backend) to run modules on
+pub fn is_wgpu_available() -> bool {
+    let instance = wgpu::Instance::default();
+    return pollster::block_on(instance.request_adapter(&wgpu::RequestAdapterOptions::default()))
+        .is_some();
+}
+
+impl Compilation {
+    /// Runs the module's compute entry point through `wgpu`, dispatching a single workgroup
+    #[inline]
+    pub fn execute_wgpu(
+        &self,
+        input_buffers: &[&[u8]],
+        shader: WgpuShader,
+    ) -> Result<Vec<Vec<u8>>> {
+        return self.execute_wgpu_with(input_buffers, [1, 1, 1], shader);
+    }
+
+    /// Runs the module's compute entry point through `wgpu`, dispatching `group_count` workgroups. Buffers are bound
+    /// like in [`Compilation::execute_compute_with`]
+    pub fn execute_wgpu_with(
+        &self,
+        input_buffers: &[&[u8]],
+        group_count: [u32; 3],
+        shader: WgpuShader,
+    ) -> Result<Vec<Vec<u8>>> {
+        let reflection = self.reflect()?;
+        if !reflection.push_constants.is_empty() {
+            return Err(Error::msg("Push constants are not supported"));
+        }
+
+        let (module, _) = self.naga_module()?;
+        let mut entry_points = module
+            .entry_points
+            .iter()
+            .filter(|x| x.stage == naga::ShaderStage::Compute);
+        let entry_point = match (entry_points.next(), entry_points.next()) {
+            (Some(entry_point), None) => entry_point.name.as_str(),
+            _ => {
+                return Err(Error::msg(
+                    "Exactly one compute entry point must be specified",
+                ))
+            }
+        };
+
+        let mut bindings = reflection.descriptor_bindings.iter().collect::<Vec<_>>();
+        bindings.sort_by_key(|x| (x.set, x.binding));
+        if bindings.len() != input_buffers.len() {
+            return Err(Error::msg(format!(
+                "The module has {} buffers, but {} were provided",
+                bindings.len(),
+                input_buffers.len()
+            )));
+        }
+
+        let instance = wgpu::Instance::default();
+        let adapter =
+            pollster::block_on(instance.request_adapter(&wgpu::RequestAdapterOptions::default()))
+                .ok_or_else(|| Error::msg("No wgpu adapter was found"))?;
+        let (device, queue) = pollster::block_on(adapter.request_device(
+            &wgpu::DeviceDescriptor {
+                label: None,
+                features: wgpu::Features::empty(),
+                limits: adapter.limits(),
+            },
+            None,
+        ))
+        .map_err(|e| Error::msg(e.to_string()))?;
+
+        // Validation errors would otherwise panic
+        device.push_error_scope(wgpu::ErrorFilter::Validation);
+        let source = match shader {
+            WgpuShader::Wgsl => wgpu::ShaderSource::Wgsl(Cow::Owned(self.naga_wgsl()?)),
+            WgpuShader::Naga => wgpu::ShaderSource::Naga(Cow::Owned(module.clone())),
+        };
+        let shader = device.create_shader_module(wgpu::ShaderModuleDescriptor {
+            label: None,
+            source,
+        });
+        let pipeline = device.create_compute_pipeline(&wgpu::ComputePipelineDescriptor {
+            label: None,
+            layout: None,
+            module: &shader,
+            entry_point,
+        });
+
+        // Buffers
+        let mut buffers = Vec::with_capacity(bindings.len());
+        for (binding, input) in bindings.iter().zip(input_buffers) {
+            let usage = match binding.descriptor_type {
+                DescriptorType::StorageBuffer => wgpu::BufferUsages::STORAGE,
+                DescriptorType::UniformBuffer => wgpu::BufferUsages::UNIFORM,
+                DescriptorType::Other => {
+                    return Err(Error::msg(format!(
+                        "Unsupported descriptor at set {} and binding {}",
+                        binding.set, binding.binding
+                    )))
+                }
+            };
+
+            // Copies must be a multiple of 4 bytes
+            let size = input.len().max(binding.size.unwrap_or_default() as usize);
+            let mut contents = vec![0u8; size.max(1).next_multiple_of(4)];
+            contents[..input.len()].copy_from_slice(input);
+
+            let buffer = device.create_buffer_init(&wgpu::util::BufferInitDescriptor {
+                label: None,
+                contents: &contents,
+                usage: usage | wgpu::BufferUsages::COPY_SRC,
+            });
+            let staging = device.create_buffer(&wgpu::BufferDescriptor {
+                label: None,
+                size: contents.len() as wgpu::BufferAddress,
+                usage: wgpu::BufferUsages::MAP_READ | wgpu::BufferUsages::COPY_DST,
+                mapped_at_creation: false,
+            });
+            buffers.push((buffer, staging, size));
+        }
+
+        // Bind groups
+        let set_count = bindings.last().map_or(0, |x| x.set + 1);
+        let bind_groups = (0..set_count)
+            .map(|set| {
+                let entries = bindings
+                    .iter()
+                    .zip(buffers.iter())
+                    .filter(|(binding, _)| binding.set == set)
+                    .map(|(binding, (buffer, _, _))| wgpu::BindGroupEntry {
+                        binding: binding.binding,
+                        resource: buffer.as_entire_binding(),
+                    })
+                    .collect::<Vec<_>>();
+
+                device.create_bind_group(&wgpu::BindGroupDescriptor {
+                    label: None,
+                    layout: &pipeline.get_bind_group_layout(set),
+                    entries: &entries,
+                })
+            })
+            .collect::<Vec<_>>();
+
+        // Dispatch
+        let mut encoder =
+            device.create_command_encoder(&wgpu::CommandEncoderDescriptor { label: None });
+        {
+            let mut pass = encoder.begin_compute_pass(&wgpu::ComputePassDescriptor { label: None });
+            pass.set_pipeline(&pipeline);
+            for (set, bind_group) in bind_groups.iter().enumerate() {
+                pass.set_bind_group(set as u32, bind_group, &[]);
+            }
+
+            let [x, y, z] = group_count;
+            pass.dispatch_workgroups(x, y, z);
+        }
+
+        for (buffer, staging, _) in buffers.iter() {
+            encoder.copy_buffer_to_buffer(buffer, 0, staging, 0, staging.size());
+        }
+        queue.submit(Some(encoder.finish()));
+
+        if let Some(e) = pollster::block_on(device.pop_error_scope()) {
+            return Err(Error::msg(e.to_string()));
+        }
+
+        // Read back
+        let (sender, receiver) = mpsc::channel();
+        for (_, staging, _) in buffers.iter() {
+            let sender = sender.clone();
+            staging
+                .slice(..)
+                .map_async(wgpu::MapMode::Read, move |result| {
+                    let _ = sender.send(result);
+                });
+        }
+
+        device.poll(wgpu::Maintain::Wait);
+        let results = receiver.try_iter().collect::<Vec<_>>();
+        if results.len() != buffers.len() {
+            return Err(Error::msg("Unable to read back the buffers"));
+        }
+        for result in results {
+            result.map_err(Error::custom)?;
+        }
+
+        return Ok(buffers
+            .iter()
+            .map(|(_, staging, size)| staging.slice(..).get_mapped_range()[..*size].to_vec())
+            .collect());
+    }
+}
@@ -1,10 +1,17 @@
 use crate::{
-    capabilities::instruction_capabilities,
+    capabilities::{
+        capability_extension, extension_core_version, instruction_capabilities,
+        instruction_extension,
+    },
+    compact::{check_duplicates, sort_globals},
+    config::{CapabilityModel, Float64Mode, SelectionHint, TrapMode, Verbosity},
+    diagnostics::Origin,
     error::{Error, Result},
     fg::{
-        extended_is::{ExtendedSet, GLSLInstr, OpenCLInstr},
-        function::{ExecutionMode, FunctionBuilder, Schrodinger},
+        extended_is::{DebugPrintfInstr, ExtendedSet, GLSLInstr, OpenCLInstr},
+        function::{BufferKind, ExecutionMode, FunctionBuilder, Schrodinger},
         module::{GlobalVariable, ModuleBuilder},
+        structurize::Construct,
         values::{
             bool::{Bool, BoolSource, Comparison, Equality},
             float::{
@@ -21,25 +28,33 @@ use crate::{
             vector::{Vector, VectorSource},
             Value,
         },
-        Label, Operation,
+        Handle, Label, Operation,
     },
-    r#type::{CompositeType, PointerSize, ScalarType, Type},
-    version::Version,
+    phi::promote_temporaries,
+    profile::{instrument, ProfileCounter},
+    r#type::{CompositeType, PointerSize, ScalarType, StructMember, Type},
+    version::{TargetPlatform, Version},
 };
 use rspirv::{
+    binary::Disassemble,
     dr::{Instruction, Module, Operand},
     spirv::{
-        Decoration, ExecutionMode as SpirvExecutionMode, FunctionControl, LoopControl,
-        MemoryAccess, Op, SelectionControl,
+        Decoration, ExecutionMode as SpirvExecutionMode, ExecutionModel, FunctionControl,
+        LinkageType, LoopControl, MemoryAccess, Op, Scope, SelectionControl,
     },
 };
 use spirv::{Capability, StorageClass};
 use std::{
     cmp::Ordering,
-    collections::HashMap,
+    collections::{BTreeSet, HashMap, HashSet},
     ops::{Deref, DerefMut},
     rc::Rc,
 };
+use tracing::span::EnteredSpan;
+use wasmparser::ExternalKind;
+
+/// Chains of values deeper than this are translated bottom-up with an explicit stack, instead of recursively
+const RECURSION_LIMIT: usize = 64;
 
 #[derive(Debug, Clone, PartialEq, Eq, Hash)]
 enum Constant {
@@ -53,6 +68,13 @@ enum Constant {
 pub struct Builder {
     inner: rspirv::dr::Builder,
     constants: HashMap<(rspirv::spirv::Word, Constant), rspirv::spirv::Word>,
+    /// Extended instruction sets, by name
+    ext_inst_imports: HashMap<String, rspirv::spirv::Word>,
+    origins: HashMap<rspirv::spirv::Word, Origin>,
+    profile_counters: Vec<ProfileCounter>,
+    acceleration_structures: HashMap<(u32, u32), rspirv::spirv::Word>,
+    /// Structure types, by their members
+    structures: HashMap<Box<[StructMember]>, rspirv::spirv::Word>,
 }
 
 impl Builder {
@@ -60,6 +82,11 @@ impl Builder {
         return Self {
             inner: rspirv::dr::Builder::new(),
             constants: HashMap::new(),
+            ext_inst_imports: HashMap::new(),
+            origins: HashMap::new(),
+            profile_counters: Vec::new(),
+            acceleration_structures: HashMap::new(),
+            structures: HashMap::new(),
         };
     }
 
@@ -67,6 +94,157 @@ impl Builder {
         self.inner.module()
     }
 
+    /// Returns the built module, alongside the origin of every function instruction with a result id
+    pub fn module_with_origins(self) -> (Module, HashMap<rspirv::spirv::Word, Origin>) {
+        (self.inner.module(), self.origins)
+    }
+
+    /// Layout of the profiling buffer, if the module was instrumented
+    pub fn profile_counters(&self) -> &[ProfileCounter] {
+        &self.profile_counters
+    }
+
+    fn acceleration_structure(&mut self, set: u32, binding: u32) -> rspirv::spirv::Word {
+        if let Some(variable) = self.acceleration_structures.get(&(set, binding)) {
+            return *variable;
+        }
+
+        let acceleration_structure_type = self.type_acceleration_structure_khr();
+        let pointer_type = self.type_pointer(
+            None,
+            StorageClass::UniformConstant,
+            acceleration_structure_type,
+        );
+        let variable = self.id();
+        self.module_mut().types_global_values.push(Instruction::new(
+            Op::Variable,
+            Some(pointer_type),
+            Some(variable),
+            vec![Operand::StorageClass(StorageClass::UniformConstant)],
+        ));
+        self.decorate(
+            variable,
+            Decoration::DescriptorSet,
+            Some(Operand::LiteralInt32(set)),
+        );
+        self.decorate(
+            variable,
+            Decoration::Binding,
+            Some(Operand::LiteralInt32(binding)),
+        );
+
+        self.acceleration_structures
+            .insert((set, binding), variable);
+        return variable;
+    }
+
+    fn explain_instruction(&self, instr: &Instruction, functions: &[FunctionBuilder]) -> String {
+        let origin = instr.result_id.and_then(|id| {
+            self.origins.get(&id).or_else(|| {
+                self.module_ref()
+                    .all_inst_iter()
+                    .filter(|x| {
+                        x.result_type == Some(id)
+                            || x.operands
+                                .iter()
+                                .any(|x| matches!(x, Operand::IdRef(x) if *x == id))
+                    })
+                    .find_map(|x| self.origins.get(&x.result_id?))
+            })
+        });
+
+        let mut result = format!("`{}`", instr.disassemble());
+        if let Some(origin) = origin {
+            let name = functions
+                .iter()
+                .find(|x| x.index == origin.function)
+                .and_then(|x| x.entry_point.as_ref())
+                .map_or_else(String::new, |x| format!(" `{}`", &*x.name));
+
+            result.push_str(&format!(", used by function {}{name}", origin.function));
+            if let Some(ref operator) = origin.operator {
+                result.push_str(&format!(" at operator {operator}"));
+            }
+            result.push_str(&format!(" ({})", origin.operation));
+        }
+
+        return result;
+    }
+
+    fn instruction_position(&self) -> (usize, usize) {
+        return match self.module_ref().functions.last() {
+            Some(function) => match function.blocks.last() {
+                Some(block) => (function.blocks.len() - 1, block.instructions.len()),
+                None => (0, 0),
+            },
+            None => (0, 0),
+        };
+    }
+
+    fn parameter_position(&self) -> usize {
+        return self
+            .module_ref()
+            .functions
+            .last()
+            .map_or(0, |function| function.parameters.len());
+    }
+
+    fn record_parameter_origin(&mut self, position: usize, origin: Origin) {
+        let function = match self.inner.module_ref().functions.last() {
+            Some(function) => function,
+            None => return,
+        };
+
+        for id in function
+            .parameters
+            .iter()
+            .skip(position)
+            .filter_map(|x| x.result_id)
+        {
+            self.origins.entry(id).or_insert_with(|| origin.clone());
+        }
+    }
+
+    fn record_origin(&mut self, (start_block, start_instruction): (usize, usize), origin: Origin) {
+        let function = match self.inner.module_ref().functions.last() {
+            Some(function) => function,
+            None => return,
+        };
+
+        for (block, i) in function.blocks.iter().zip(0..).skip(start_block) {
+            let skip = match i == start_block {
+                true => start_instruction,
+                false => {
+                    if let Some(id) = block.label.as_ref().and_then(|x| x.result_id) {
+                        self.origins.entry(id).or_insert_with(|| origin.clone());
+                    }
+                    0
+                }
+            };
+
+            for id in block
+                .instructions
+                .iter()
+                .skip(skip)
+                .filter_map(|x| x.result_id)
+            {
+                self.origins.entry(id).or_insert_with(|| origin.clone());
+            }
+        }
+    }
+
+    /// Imports the extended instruction set, unless it already was
+    pub fn ext_inst_import(&mut self, name: impl Into<String>) -> rspirv::spirv::Word {
+        let name = name.into();
+        if let Some(id) = self.ext_inst_imports.get(&name) {
+            return *id;
+        }
+
+        let id = self.inner.ext_inst_import(name.clone());
+        self.ext_inst_imports.insert(name, id);
+        return id;
+    }
+
     pub fn constant_true(&mut self, result_type: rspirv::spirv::Word) -> rspirv::spirv::Word {
         *self
             .constants
@@ -142,6 +320,12 @@ impl<'a> ModuleBuilder<'a> {
 
         // Globals
         for global in self.global_variables.iter() {
+            // Members of a block are accessed from within each function
+            if let GlobalVariable::Variable(var) = global {
+                if let PointerSource::Member { .. } = var.source {
+                    continue;
+                }
+            }
             let _ = global.translate(&self, None, &mut builder)?;
         }
 
@@ -150,23 +334,98 @@ impl<'a> ModuleBuilder<'a> {
             function.function_id.set(Some(builder.id()));
         }
 
+        // Linkage
+        for (name, function_id, ty) in self.imported_functions.iter() {
+            let return_type = match ty.results().first() {
+                Some(ty) => Type::from(*ty).translate(&self, None, &mut builder)?,
+                None => builder.type_void(),
+            };
+            let mut parameters = Vec::with_capacity(ty.params().len());
+            for ty in ty.params() {
+                parameters.push(Type::from(*ty).translate(&self, None, &mut builder)?);
+            }
+
+            let function_type = builder.type_function(return_type, parameters.iter().copied());
+            let id =
+                builder.begin_function(return_type, None, FunctionControl::NONE, function_type)?;
+            for parameter in parameters {
+                builder.function_parameter(parameter)?;
+            }
+            builder.end_function()?;
+
+            builder.decorate(
+                id,
+                Decoration::LinkageAttributes,
+                [
+                    Operand::LiteralString(name.to_string()),
+                    Operand::LinkageType(LinkageType::Import),
+                ],
+            );
+            function_id.set(Some(id));
+        }
+
+        if self.linkage {
+            for function in self.built_functions.iter() {
+                let export = self
+                    .exports
+                    .iter()
+                    .find(|x| x.kind == ExternalKind::Func && x.index == function.index);
+                let (Some(export), None) = (export, &function.entry_point) else {
+                    continue;
+                };
+
+                builder.decorate(
+                    function.function_id.get().ok_or_else(Error::unexpected)?,
+                    Decoration::LinkageAttributes,
+                    [
+                        Operand::LiteralString(export.name.to_string()),
+                        Operand::LinkageType(LinkageType::Export),
+                    ],
+                );
+            }
+        }
+
         // Hidden globals
         for global in self.hidden_global_variables.iter() {
             let _ = global.translate(&self, None, &mut builder)?;
         }
 
         // Function bodies
+        for function in self.built_functions.iter_mut() {
+            let _span = function.span("structurize");
+            function.structurize()?;
+        }
         for function in self.built_functions.iter() {
+            let _span = function.span("translate_function");
             function.translate(&self, &mut builder)?;
         }
 
+        // Profiling counters
+        if let Some(buffer) = self.profiling {
+            if let TargetPlatform::OpenCL(_) = self.platform {
+                return Err(Error::msg(
+                    "OpenCL kernels don't have descriptor sets to bind the profiling buffer to",
+                ));
+            }
+
+            let functions = self
+                .built_functions
+                .iter()
+                .map(|x| x.index)
+                .collect::<Vec<_>>();
+            builder.profile_counters = instrument(&mut builder, &functions, buffer, self.version)?;
+        }
+
+        // Entry point interfaces
+        collect_interfaces(builder.module_mut(), self.version);
+
         // Capabilities
-        for capability in builder
-            .module_ref()
-            .all_inst_iter()
-            .flat_map(instruction_capabilities)
-        {
-            self.capabilities.require_mut(capability)?;
+        for instr in builder.module_ref().all_inst_iter() {
+            for capability in instruction_capabilities(instr) {
+                self.capabilities.require_mut_by(capability, || {
+                    Some(builder.explain_instruction(instr, &self.built_functions))
+                })?;
+            }
         }
 
         for capability in self.capabilities.iter() {
@@ -174,15 +433,79 @@ impl<'a> ModuleBuilder<'a> {
         }
 
         // Extensions
-        for extension in self.extensions.iter() {
-            builder.extension(extension.to_string())
+        let mut extensions = self
+            .extensions
+            .iter()
+            .map(|x| x.to_string())
+            .collect::<Vec<_>>();
+
+        // Dynamic capabilities that require an extension before it became core
+        if let CapabilityModel::Dynamic(_) = self.capabilities {
+            for extension in self
+                .capabilities
+                .iter()
+                .filter_map(|x| capability_extension(*x))
+            {
+                if !extensions.iter().any(|x| x == extension)
+                    && !extension_core_version(extension).is_some_and(|since| self.version >= since)
+                {
+                    extensions.push(extension.to_string());
+                }
+            }
+        }
+
+        // Instructions that require an extension before it became core, like the `StorageBuffer` storage class
+        // before SPIR-V 1.3, or the non-semantic instruction set of `debug_printf` before SPIR-V 1.6
+        for instr in builder.module_ref().all_inst_iter() {
+            let Some(extension) = instruction_extension(instr) else {
+                continue;
+            };
+            if extensions.iter().any(|x| x == extension)
+                || extension_core_version(extension).is_some_and(|since| self.version >= since)
+            {
+                continue;
+            }
+
+            match self.capabilities {
+                CapabilityModel::Dynamic(_) => extensions.push(extension.to_string()),
+                CapabilityModel::Static(_) => {
+                    return Err(Error::msg(format!(
+                        "{} requires the `{extension}` extension, which isn't enabled by the configuration",
+                        builder.explain_instruction(instr, &self.built_functions)
+                    )))
+                }
+            }
+        }
+
+        for extension in extensions {
+            builder.extension(extension)
         }
 
+        if self.phi_merges {
+            promote_temporaries(builder.module_mut());
+        }
+        if self.sort_globals {
+            sort_globals(builder.module_mut());
+        }
+
+        check_duplicates(builder.module_ref())?;
         return Ok(builder);
     }
 }
 
 impl<'a> FunctionBuilder<'a> {
+    fn span(&self, name: &'static str) -> Option<EnteredSpan> {
+        if self.verbosity < Verbosity::Functions {
+            return None;
+        }
+
+        let entry_point = self.entry_point.as_ref().map(|x| &*x.name);
+        return Some(
+            tracing::debug_span!("function", pass = name, index = self.index, entry_point)
+                .entered(),
+        );
+    }
+
     pub fn translate(&self, module: &ModuleBuilder, builder: &mut Builder) -> Result<()> {
         let return_type = match &self.return_type {
             Some(ty) => ty.clone().translate(module, Some(self), builder)?,
@@ -203,17 +526,12 @@ impl<'a> FunctionBuilder<'a> {
         // Create entry point
         if let Some(ref entry_point) = self.entry_point {
             let function_id = self.function_id.get().ok_or_else(Error::unexpected)?;
-            let interface = entry_point
-                .interface
-                .iter()
-                .map(|x| x.translate(module, Some(self), builder))
-                .collect::<Result<Vec<_>>>()?;
-
+            // The interface is collected once every function body has been translated
             builder.entry_point(
                 entry_point.execution_model,
                 function_id,
-                entry_point.name,
-                interface,
+                &*entry_point.name,
+                [],
             );
 
             // Add execution mode
@@ -235,36 +553,113 @@ impl<'a> FunctionBuilder<'a> {
                     ExecutionMode::LocalSizeHint(x, y, z) => {
                         (SpirvExecutionMode::LocalSizeHint, vec![*x, *y, *z])
                     }
+                    // The operands of `OpExecutionModeId` are ids, instead of literals
+                    ExecutionMode::LocalSizeId(x, y, z) => {
+                        let integer_type = builder.type_int(32, 0);
+                        let params = [*x, *y, *z].map(|x| builder.constant_u32(integer_type, x));
+                        builder.execution_mode_id(
+                            function_id,
+                            SpirvExecutionMode::LocalSizeId,
+                            params,
+                        );
+                        continue;
+                    }
                     ExecutionMode::DepthReplacing => {
                         (SpirvExecutionMode::DepthReplacing, Vec::new())
                     }
+                    ExecutionMode::DepthGreater => (SpirvExecutionMode::DepthGreater, Vec::new()),
+                    ExecutionMode::DepthLess => (SpirvExecutionMode::DepthLess, Vec::new()),
+                    ExecutionMode::DepthUnchanged => {
+                        (SpirvExecutionMode::DepthUnchanged, Vec::new())
+                    }
+                    ExecutionMode::EarlyFragmentTests => {
+                        (SpirvExecutionMode::EarlyFragmentTests, Vec::new())
+                    }
+                    ExecutionMode::InputPoints => (SpirvExecutionMode::InputPoints, Vec::new()),
+                    ExecutionMode::InputLines => (SpirvExecutionMode::InputLines, Vec::new()),
+                    ExecutionMode::InputLinesAdjacency => {
+                        (SpirvExecutionMode::InputLinesAdjacency, Vec::new())
+                    }
+                    ExecutionMode::Triangles => (SpirvExecutionMode::Triangles, Vec::new()),
+                    ExecutionMode::InputTrianglesAdjacency => {
+                        (SpirvExecutionMode::InputTrianglesAdjacency, Vec::new())
+                    }
+                    ExecutionMode::Quads => (SpirvExecutionMode::Quads, Vec::new()),
+                    ExecutionMode::Isolines => (SpirvExecutionMode::Isolines, Vec::new()),
+                    ExecutionMode::OutputVertices(x) => {
+                        (SpirvExecutionMode::OutputVertices, vec![*x])
+                    }
+                    ExecutionMode::OutputPoints => (SpirvExecutionMode::OutputPoints, Vec::new()),
+                    ExecutionMode::OutputLineStrip => {
+                        (SpirvExecutionMode::OutputLineStrip, Vec::new())
+                    }
+                    ExecutionMode::OutputTriangleStrip => {
+                        (SpirvExecutionMode::OutputTriangleStrip, Vec::new())
+                    }
+                    ExecutionMode::SpacingEqual => (SpirvExecutionMode::SpacingEqual, Vec::new()),
+                    ExecutionMode::SpacingFractionalEven => {
+                        (SpirvExecutionMode::SpacingFractionalEven, Vec::new())
+                    }
+                    ExecutionMode::SpacingFractionalOdd => {
+                        (SpirvExecutionMode::SpacingFractionalOdd, Vec::new())
+                    }
+                    ExecutionMode::VertexOrderCw => (SpirvExecutionMode::VertexOrderCw, Vec::new()),
+                    ExecutionMode::VertexOrderCcw => {
+                        (SpirvExecutionMode::VertexOrderCcw, Vec::new())
+                    }
+                    ExecutionMode::PointMode => (SpirvExecutionMode::PointMode, Vec::new()),
+                    ExecutionMode::OutputPrimitives(x) => {
+                        (SpirvExecutionMode::OutputPrimitivesNV, vec![*x])
+                    }
+                    ExecutionMode::OutputLines => (SpirvExecutionMode::OutputLinesNV, Vec::new()),
+                    ExecutionMode::OutputTriangles => {
+                        (SpirvExecutionMode::OutputTrianglesNV, Vec::new())
+                    }
                 };
                 builder.execution_mode(function_id, execution_mode, params)
             }
         }
 
-        builder.begin_function(
+        let function_id = builder.begin_function(
             return_type,
             self.function_id.get(),
             FunctionControl::NONE,
             function_type,
         )?;
 
+        if self.relaxed_precision.return_value && self.return_type.is_some() {
+            builder.decorate(function_id, Decoration::RelaxedPrecision, None);
+        }
+
         // Initialize function parameters
-        for param in self.parameters.iter() {
+        for (param, i) in self.parameters.iter().zip(0..) {
+            let position = builder.parameter_position();
             let _ = param.translate(module, Some(self), builder)?;
+            builder.record_parameter_origin(
+                position,
+                Origin {
+                    function: self.index,
+                    operator: None,
+                    operation: format!("parameter {i}").into_boxed_str(),
+                },
+            );
         }
 
         builder.begin_block(None)?;
 
         // Initialize
         for init in self.variable_initializers.iter() {
+            let position = builder.instruction_position();
             let _ = init.translate(module, Some(self), builder)?;
+            builder.record_origin(position, self.origin(init, None));
         }
 
         // Translate anchors
-        for anchor in self.anchors.iter() {
+        for (anchor, i) in self.anchors.iter().zip(0..) {
+            let position = builder.instruction_position();
             let _ = anchor.translate(module, Some(self), builder)?;
+            let operator = self.operators.get(i).and_then(Option::as_ref);
+            builder.record_origin(position, self.origin(anchor, operator));
         }
 
         builder.end_function()?;
@@ -287,7 +682,7 @@ pub trait Translation {
 impl Translation for ScalarType {
     fn translate(
         self,
-        _: &ModuleBuilder,
+        module: &ModuleBuilder,
         _: Option<&FunctionBuilder>,
         builder: &mut Builder,
     ) -> Result<rspirv::spirv::Word> {
@@ -295,6 +690,7 @@ impl Translation for ScalarType {
             ScalarType::I32 => builder.type_int(32, 0),
             ScalarType::I64 => builder.type_int(64, 0),
             ScalarType::F32 => builder.type_float(32),
+            ScalarType::F64 if module.float64 == Float64Mode::Demote => builder.type_float(32),
             ScalarType::F64 => builder.type_float(64),
             ScalarType::Bool => builder.type_bool(),
         });
@@ -313,6 +709,44 @@ impl Translation for CompositeType {
                 let component_type = elem.translate(module, function, builder)?;
                 Ok(builder.type_vector(component_type, component_count))
             }
+            CompositeType::Matrix(
+                column_count,
+                row_count,
+                elem @ (ScalarType::F32 | ScalarType::F64),
+            ) => {
+                let column_type =
+                    CompositeType::Vector(elem, row_count).translate(module, function, builder)?;
+                Ok(builder.type_matrix(column_type, column_count))
+            }
+            CompositeType::Matrix(_, _, elem) => Err(Error::msg(format!(
+                "Matrix elements must be floating point, found '{elem:?}'"
+            ))),
+            CompositeType::Struct(members) => {
+                if let Some(structure_type) = builder.structures.get(&members) {
+                    return Ok(*structure_type);
+                }
+
+                let member_types = members
+                    .iter()
+                    .map(|member| member.ty.clone().translate(module, function, builder))
+                    .collect::<Result<Vec<_>>>()?;
+
+                let id = builder.id();
+                let structure_type = builder.type_struct_id(Some(id), member_types);
+                for (member, i) in members.iter().zip(0..) {
+                    builder.member_decorate(
+                        structure_type,
+                        i,
+                        Decoration::Offset,
+                        Some(Operand::LiteralInt32(member.offset)),
+                    );
+                    builder.member_name(structure_type, i, member.name.as_ref());
+                    decorate_matrix_member(structure_type, i, &member.ty, builder);
+                }
+
+                builder.structures.insert(members, structure_type);
+                Ok(structure_type)
+            }
         }
     }
 }
@@ -336,6 +770,8 @@ impl Translation for Type {
                     StorageClass::Uniform
                         | StorageClass::StorageBuffer
                         | StorageClass::PhysicalStorageBuffer
+                        | StorageClass::PushConstant
+                        | StorageClass::ShaderRecordBufferKHR
                 );
 
                 // Fat (RuntimeArray)
@@ -365,25 +801,15 @@ impl Translation for Type {
                 let pointee_type = match is_structured {
                     false => pointee_type,
                     true => {
-                        let n = builder.module_ref().types_global_values.len();
-                        let structure_type = builder.type_struct([pointee_type]);
-
-                        if n != builder.module_ref().types_global_values.len() {
-                            builder.member_decorate(
-                                structure_type,
-                                0,
-                                Decoration::Offset,
-                                Some(Operand::LiteralInt32(0)),
-                            );
-
-                            let block = match module.version.cmp(&Version::V1_3) {
-                                Ordering::Greater | Ordering::Equal => Decoration::Block,
-                                _ => Decoration::BufferBlock,
-                            };
-                            builder.decorate(structure_type, block, None);
-                        }
-
-                        structure_type
+                        let block = match (storage_class, module.version.cmp(&Version::V1_3)) {
+                            (
+                                StorageClass::PushConstant | StorageClass::ShaderRecordBufferKHR,
+                                _,
+                            )
+                            | (_, Ordering::Greater | Ordering::Equal) => Decoration::Block,
+                            _ => Decoration::BufferBlock,
+                        };
+                        structure_type(pointee_type, &pointee, block, builder)
                     }
                 };
 
@@ -434,6 +860,7 @@ impl Translation for &Bool {
         if let Some(res) = self.translation.get() {
             return Ok(res);
         }
+        translate_operands(Node::Bool(self), module, function, builder)?;
 
         let result_type = builder.type_bool();
         let res = match &self.source {
@@ -552,9 +979,11 @@ impl Translation for &Bool {
             } => {
                 let pointee = &pointer.pointee;
                 let storage_class = pointer.storage_class;
+                let volatile = pointer.is_volatile();
                 let pointer = translate_to_skinny(pointer, module, function, builder)?;
 
-                let (memory_access, additional_params) = additional_access_info(*log2_alignment);
+                let (memory_access, additional_params) =
+                    additional_access_info(*log2_alignment, volatile);
                 builder.load(result_type, None, pointer, memory_access, additional_params)
             }
         }?;
@@ -574,6 +1003,7 @@ impl Translation for &Integer {
         if let Some(res) = self.translation.get() {
             return Ok(res);
         }
+        translate_operands(Node::Integer(self), module, function, builder)?;
 
         let result_type = builder.type_int(
             match self.kind(module)? {
@@ -611,6 +1041,12 @@ impl Translation for &Integer {
             }
 
             IntegerSource::Conversion(IntConversionSource::Bitcast { value, .. }) => {
+                if let Value::Float(float) = value {
+                    if module.float64 == Float64Mode::Demote && float.kind()? == FloatKind::Double {
+                        return Err(demoted_bitcast());
+                    }
+                }
+
                 let value = value.translate(module, function, builder)?;
                 builder.bitcast(result_type, None, value)
             }
@@ -684,12 +1120,22 @@ impl Translation for &Integer {
             } => {
                 let pointee = &pointer.pointee;
                 let storage_class = pointer.storage_class;
+                let volatile = pointer.is_volatile();
                 let pointer = translate_to_skinny(pointer, module, function, builder)?;
 
-                let (memory_access, additional_params) = additional_access_info(*log2_alignment);
+                let (memory_access, additional_params) =
+                    additional_access_info(*log2_alignment, volatile);
                 builder.load(result_type, None, pointer, memory_access, additional_params)
             }
 
+            IntegerSource::AtomicIncrement { counter } => {
+                let pointer = translate_to_skinny(counter, module, function, builder)?;
+                let scope = builder.constant_u32(result_type, Scope::Device as u32);
+                let semantics = builder.constant_u32(result_type, 0);
+                let one = builder.constant_u32(result_type, 1);
+                builder.atomic_i_add(result_type, None, pointer, scope, semantics, one)
+            }
+
             IntegerSource::Extracted { vector, index } => {
                 let composite = vector.translate(module, function, builder)?;
                 match index.get_constant_value()? {
@@ -704,6 +1150,38 @@ impl Translation for &Integer {
                 }
             }
 
+            IntegerSource::ConstantArrayElement { words, index } => {
+                let u32_type = builder.type_int(32, 0);
+                let length = builder.constant_u32(u32_type, words.len() as u32);
+                let array_type = builder.type_array(result_type, length);
+                let elements = words
+                    .iter()
+                    .map(|x| builder.constant_u32(result_type, *x))
+                    .collect::<Vec<_>>();
+                let initializer = builder.constant_composite(array_type, elements);
+
+                // Constants can't be indexed dynamically, so the array is copied into a variable
+                let pointer_type = builder.type_pointer(None, StorageClass::Function, array_type);
+                let id = builder.id();
+                push_function_variable(
+                    Instruction::new(
+                        Op::Variable,
+                        Some(pointer_type),
+                        Some(id),
+                        vec![
+                            Operand::StorageClass(StorageClass::Function),
+                            Operand::IdRef(initializer),
+                        ],
+                    ),
+                    builder,
+                )?;
+
+                let index = index.translate(module, function, builder)?;
+                let element_type = builder.type_pointer(None, StorageClass::Function, result_type);
+                let element = builder.access_chain(element_type, None, id, [index])?;
+                builder.load(result_type, None, element, None, None)
+            }
+
             IntegerSource::FunctionCall {
                 function_id, args, ..
             } => {
@@ -739,9 +1217,10 @@ impl Translation for &Integer {
                             }
                         }
 
-                        module
-                            .capabilities
-                            .require(Capability::IntegerFunctions2INTEL)?;
+                        module.capabilities.require_by(
+                            Capability::IntegerFunctions2INTEL,
+                            || Some(String::from("counting leading zeros without the OpenCL extended instruction set")),
+                        )?;
                         builder.u_count_leading_zeros_intel(result_type, None, operand)
                     }
 
@@ -762,9 +1241,10 @@ impl Translation for &Integer {
                             }
                         }
 
-                        module
-                            .capabilities
-                            .require(Capability::IntegerFunctions2INTEL)?;
+                        module.capabilities.require_by(
+                            Capability::IntegerFunctions2INTEL,
+                            || Some(String::from("counting trailing zeros without the OpenCL extended instruction set")),
+                        )?;
                         builder.u_count_trailing_zeros_intel(result_type, None, operand)
                     }
                 }
@@ -815,10 +1295,32 @@ impl Translation for &Integer {
                                 _ => continue,
                             }
                         }
-                        todo!()
+
+                        let kind = self.kind(module)?;
+                        rotate(true, kind, result_type, operand_1, operand_2, builder)
                     }
                     IntBinarySource::Rotr => 'brk: {
-                        todo!()
+                        for is in module.extended_is.iter() {
+                            match is.kind {
+                                ExtendedSet::OpenCL => {
+                                    // OpenCL only rotates left, and does so modulo the bit width
+                                    let extension_set = is.translate(module, function, builder)?;
+                                    let operand_2 =
+                                        builder.s_negate(result_type, None, operand_2)?;
+                                    break 'brk builder.ext_inst(
+                                        result_type,
+                                        None,
+                                        extension_set,
+                                        OpenCLInstr::Rotate as u32,
+                                        [Operand::IdRef(operand_1), Operand::IdRef(operand_2)],
+                                    );
+                                }
+                                _ => continue,
+                            }
+                        }
+
+                        let kind = self.kind(module)?;
+                        rotate(false, kind, result_type, operand_1, operand_2, builder)
                     }
                 }
             }
@@ -839,8 +1341,12 @@ impl Translation for &Float {
         if let Some(res) = self.translation.get() {
             return Ok(res);
         }
+        translate_operands(Node::Float(self), module, function, builder)?;
 
-        let kind = self.kind()?;
+        let kind = match (self.kind()?, module.float64) {
+            (FloatKind::Double, Float64Mode::Demote) => FloatKind::Single,
+            (kind, _) => kind,
+        };
         let result_bits = match kind {
             FloatKind::Single => 32,
             FloatKind::Double => 64,
@@ -855,15 +1361,28 @@ impl Translation for &Float {
                 Ok(builder.constant_f32(result_type, *x))
             }
 
-            FloatSource::Constant(FloatConstantSource::Double(x)) => {
-                Ok(builder.constant_f64(result_type, *x))
-            }
+            FloatSource::Constant(FloatConstantSource::Double(x)) => match kind {
+                FloatKind::Single => Ok(builder.constant_f32(result_type, *x as f32)),
+                FloatKind::Double => Ok(builder.constant_f64(result_type, *x)),
+            },
+
+            FloatSource::Conversion(FloatConversionSource::Bitcast {
+                kind: FloatKind::Double,
+                ..
+            }) if kind == FloatKind::Single => return Err(demoted_bitcast()),
 
             FloatSource::Conversion(FloatConversionSource::Bitcast { value, .. }) => {
                 let value = value.translate(module, function, builder)?;
                 builder.bitcast(result_type, None, value)
             }
 
+            // Demoted doubles are already single precision
+            FloatSource::Conversion(
+                FloatConversionSource::FromDouble(value) | FloatConversionSource::FromSingle(value),
+            ) if module.float64 == Float64Mode::Demote => {
+                value.translate(module, function, builder)
+            }
+
             FloatSource::Conversion(
                 FloatConversionSource::FromDouble(value) | FloatConversionSource::FromSingle(value),
             ) => {
@@ -900,9 +1419,11 @@ impl Translation for &Float {
             } => {
                 let pointee = &pointer.pointee;
                 let storage_class = pointer.storage_class;
+                let volatile = pointer.is_volatile();
                 let pointer = translate_to_skinny(pointer, module, function, builder)?;
 
-                let (memory_access, additional_params) = additional_access_info(*log2_alignment);
+                let (memory_access, additional_params) =
+                    additional_access_info(*log2_alignment, volatile);
                 builder.load(result_type, None, pointer, memory_access, additional_params)
             }
 
@@ -1027,6 +1548,7 @@ impl Translation for &Float {
                                         Some(Operand::IdRef(operand)),
                                     );
                                 }
+                                _ => continue,
                             }
                         }
                         return Err(Error::msg(
@@ -1056,6 +1578,7 @@ impl Translation for &Float {
                                         Some(Operand::IdRef(operand)),
                                     );
                                 }
+                                _ => continue,
                             }
                         }
                         return Err(Error::msg(
@@ -1085,6 +1608,7 @@ impl Translation for &Float {
                                         Some(Operand::IdRef(operand)),
                                     );
                                 }
+                                _ => continue,
                             }
                         }
                         return Err(Error::msg(
@@ -1114,10 +1638,39 @@ impl Translation for &Float {
                                         Some(Operand::IdRef(operand)),
                                     );
                                 }
+                                _ => continue,
                             }
                         }
                         return Err(Error::msg("Square root is not supported on this platform"));
                     }
+                    source => {
+                        let (glsl, opencl) = match source {
+                            FloatUnarySource::Sin => (GLSLInstr::Sin, OpenCLInstr::Sin),
+                            FloatUnarySource::Cos => (GLSLInstr::Cos, OpenCLInstr::Cos),
+                            FloatUnarySource::Tan => (GLSLInstr::Tan, OpenCLInstr::Tan),
+                            FloatUnarySource::Asin => (GLSLInstr::Asin, OpenCLInstr::Asin),
+                            FloatUnarySource::Acos => (GLSLInstr::Acos, OpenCLInstr::Acos),
+                            FloatUnarySource::Atan => (GLSLInstr::Atan, OpenCLInstr::Atan),
+                            FloatUnarySource::Sinh => (GLSLInstr::Sinh, OpenCLInstr::Sinh),
+                            FloatUnarySource::Cosh => (GLSLInstr::Cosh, OpenCLInstr::Cosh),
+                            FloatUnarySource::Tanh => (GLSLInstr::Tanh, OpenCLInstr::Tanh),
+                            FloatUnarySource::Exp => (GLSLInstr::Exp, OpenCLInstr::Exp),
+                            FloatUnarySource::Exp2 => (GLSLInstr::Exp2, OpenCLInstr::Exp2),
+                            FloatUnarySource::Log => (GLSLInstr::Log, OpenCLInstr::Log),
+                            FloatUnarySource::Log2 => (GLSLInstr::Log2, OpenCLInstr::Log2),
+                            _ => return Err(Error::unexpected()),
+                        };
+                        Ok(transcendental(
+                            glsl,
+                            opencl,
+                            kind,
+                            result_type,
+                            [operand],
+                            module,
+                            function,
+                            builder,
+                        )?)
+                    }
                 }
             }
             FloatSource::Binary { source, op1, op2 } => {
@@ -1153,7 +1706,27 @@ impl Translation for &Float {
                             }
                         }
 
-                        todo!()
+                        // Magnitude bits from the first operand, sign bit from the second
+                        let integer_type = builder.type_int(result_bits, 0);
+                        let (sign_mask, magnitude_mask) = match result_bits {
+                            32 => (
+                                builder.constant_u32(integer_type, 1 << 31),
+                                builder.constant_u32(integer_type, !(1 << 31)),
+                            ),
+                            64 => (
+                                builder.constant_u64(integer_type, 1 << 63),
+                                builder.constant_u64(integer_type, !(1 << 63)),
+                            ),
+                            _ => return Err(Error::unexpected()),
+                        };
+
+                        let magnitude = builder.bitcast(integer_type, None, operand_1)?;
+                        let magnitude =
+                            builder.bitwise_and(integer_type, None, magnitude, magnitude_mask)?;
+                        let sign = builder.bitcast(integer_type, None, operand_2)?;
+                        let sign = builder.bitwise_and(integer_type, None, sign, sign_mask)?;
+                        let result = builder.bitwise_or(integer_type, None, magnitude, sign)?;
+                        builder.bitcast(result_type, None, result)
                     }
                     FloatBinarySource::Min => {
                         const F32_NAN_ODDS: u32 = (1u32 << f32::MANTISSA_DIGITS) - 2;
@@ -1196,7 +1769,13 @@ impl Translation for &Float {
                         .translate(module, function, builder)?;
 
                         let current_block = builder.selected_block();
-                        builder.selection_merge(merge_label, SelectionControl::FLATTEN)?;
+                        builder.selection_merge(
+                            merge_label,
+                            SelectionHint::selection_control(
+                                function.and_then(|x| x.selection_control),
+                                SelectionControl::FLATTEN,
+                            ),
+                        )?;
                         builder.select_block(current_block)?;
                         builder.branch_conditional(
                             is_nan,
@@ -1257,7 +1836,13 @@ impl Translation for &Float {
                         let merge_label = builder.id();
 
                         let current_block = builder.selected_block();
-                        builder.selection_merge(merge_label, SelectionControl::FLATTEN)?;
+                        builder.selection_merge(
+                            merge_label,
+                            SelectionHint::selection_control(
+                                function.and_then(|x| x.selection_control),
+                                SelectionControl::FLATTEN,
+                            ),
+                        )?;
                         builder.select_block(current_block)?;
                         builder.branch_conditional(
                             is_nan,
@@ -1295,10 +1880,65 @@ impl Translation for &Float {
                         builder.begin_block(Some(merge_label))?;
                         builder.load(result_type, None, result, None, None)
                     }
+                    FloatBinarySource::Pow | FloatBinarySource::Atan2 => {
+                        let (glsl, opencl) = match source {
+                            FloatBinarySource::Pow => (GLSLInstr::Pow, OpenCLInstr::Pow),
+                            _ => (GLSLInstr::Atan2, OpenCLInstr::Atan2),
+                        };
+                        Ok(transcendental(
+                            glsl,
+                            opencl,
+                            kind,
+                            result_type,
+                            [operand_1, operand_2],
+                            module,
+                            function,
+                            builder,
+                        )?)
+                    }
                 }
             }
         }?;
 
+        if kind == FloatKind::Single
+            && matches!(
+                self.source,
+                FloatSource::Unary { .. } | FloatSource::Binary { .. }
+            )
+            && function.is_some_and(|x| x.relaxed_precision.arithmetic)
+        {
+            builder.decorate(res, Decoration::RelaxedPrecision, None);
+        }
+
+        // Only decorate the operations that map to a single arithmetic instruction
+        let arithmetic = matches!(
+            self.source,
+            FloatSource::Unary {
+                source: FloatUnarySource::Neg,
+                ..
+            } | FloatSource::Binary {
+                source: FloatBinarySource::Add
+                    | FloatBinarySource::Sub
+                    | FloatBinarySource::Mul
+                    | FloatBinarySource::Div,
+                ..
+            }
+        );
+        if let (true, Some(function)) = (arithmetic, function) {
+            if function.fast_math.no_contraction {
+                builder.decorate(res, Decoration::NoContraction, None);
+            }
+
+            let mode = function.fast_math.mode();
+            if !mode.is_empty() {
+                builder.decorate(
+                    res,
+                    Decoration::FPFastMathMode,
+                    Some(Operand::FPFastMathMode(mode)),
+                );
+            }
+        }
+
         self.translation.set(Some(res));
         return Ok(res);
     }
@@ -1315,12 +1955,27 @@ impl Translation for &Rc<Pointer> {
             return Ok(res);
         }
 
-        let pointer_type = Type::pointer(
-            self.kind.to_pointer_size(),
-            self.storage_class,
-            self.pointee.clone(),
-        )
-        .translate(module, function, builder)?;
+        let pointer_type = match self.source {
+            PointerSource::Member { .. } => {
+                let pointee_type = self.pointee.clone().translate(module, function, builder)?;
+                builder.type_pointer(None, self.storage_class, pointee_type)
+            }
+            PointerSource::Variable {
+                buffer: BufferKind::Uniform,
+                ..
+            } => {
+                let pointee_type = self.pointee.clone().translate(module, function, builder)?;
+                let structure_type =
+                    structure_type(pointee_type, &self.pointee, Decoration::Block, builder);
+                builder.type_pointer(None, self.storage_class, structure_type)
+            }
+            _ => Type::pointer(
+                self.kind.to_pointer_size(),
+                self.storage_class,
+                self.pointee.clone(),
+            )
+            .translate(module, function, builder)?,
+        };
 
         let res = match &self.source {
             PointerSource::FunctionParam => builder.function_parameter(pointer_type),
@@ -1330,6 +1985,13 @@ impl Translation for &Rc<Pointer> {
                 builder.bitcast(pointer_type, None, prev)
             }
 
+            PointerSource::Member { parent, index } => {
+                let parent = translate_to_skinny(parent, module, function, builder)?;
+                let index_type = ScalarType::I32.translate(module, function, builder)?;
+                let index = builder.constant_u32(index_type, *index);
+                builder.access_chain(pointer_type, None, parent, [index])
+            }
+
             PointerSource::FromInteger(value) => {
                 let integer_value = value.translate(module, function, builder)?;
                 builder.convert_u_to_ptr(pointer_type, None, integer_value)
@@ -1350,8 +2012,10 @@ impl Translation for &Rc<Pointer> {
                 pointer,
                 log2_alignment,
             } => {
+                let volatile = pointer.is_volatile();
                 let pointer = pointer.translate(module, function, builder)?;
-                let (memory_access, additional_params) = additional_access_info(*log2_alignment);
+                let (memory_access, additional_params) =
+                    additional_access_info(*log2_alignment, volatile);
                 builder.load(
                     pointer_type,
                     None,
@@ -1361,7 +2025,9 @@ impl Translation for &Rc<Pointer> {
                 )
             }
 
-            PointerSource::Variable { init, decorators } => {
+            PointerSource::Variable {
+                init, decorators, ..
+            } => {
                 let initializer = init
                     .as_ref()
                     .map(|x| x.translate(module, function, builder))
@@ -1379,7 +2045,7 @@ impl Translation for &Rc<Pointer> {
                 match self.storage_class == StorageClass::Function
                     && builder.selected_block().is_some()
                 {
-                    true => builder.insert_into_block(rspirv::dr::InsertPoint::Begin, variable)?,
+                    true => push_function_variable(variable, builder)?,
                     false => builder.module_mut().types_global_values.push(variable),
                 }
 
@@ -1412,9 +2078,11 @@ impl Translation for &Vector {
             } => {
                 let pointee = &pointer.pointee;
                 let storage_class = pointer.storage_class;
+                let volatile = pointer.is_volatile();
                 let pointer = translate_to_skinny(pointer, module, function, builder)?;
 
-                let (memory_access, additional_params) = additional_access_info(*log2_alignment);
+                let (memory_access, additional_params) =
+                    additional_access_info(*log2_alignment, volatile);
                 builder.load(result_type, None, pointer, memory_access, additional_params)
             }
             VectorSource::Select {
@@ -1451,19 +2119,25 @@ impl Translation for &Value {
     }
 }
 
-impl Translation for &Label {
+impl Translation for Handle<Label> {
     fn translate(
         self,
         _: &ModuleBuilder,
-        _: Option<&FunctionBuilder>,
+        function: Option<&FunctionBuilder>,
         builder: &mut Builder,
     ) -> Result<rspirv::spirv::Word> {
-        if let Some(res) = self.translation.get() {
+        let function = function.ok_or_else(|| Error::msg("Labels must be inside a function"))?;
+        let label = function
+            .labels
+            .get(self)
+            .ok_or_else(Error::element_not_found)?;
+
+        if let Some(res) = label.translation.get() {
             return Ok(res);
         }
 
         let id = builder.id();
-        self.translation.set(Some(id));
+        label.translation.set(Some(id));
         return Ok(id);
     }
 }
@@ -1496,7 +2170,10 @@ impl Translation for &Operation {
                 let selected = builder.selected_block();
                 let target_label = label.translate(module, Some(function), builder)?;
 
-                // TODO control flow
+                // control flow
+                if let Some(construct) = function.construct_of(self) {
+                    translate_construct(construct, module, function, builder)?;
+                }
 
                 let res = builder.branch(target_label);
                 builder.select_block(selected)?;
@@ -1511,48 +2188,17 @@ impl Translation for &Operation {
                 let function =
                     function.ok_or_else(|| Error::msg("Branches must be inside a function"))?;
 
-                let current_block = function.block_of(self).ok_or_else(Error::unexpected)?;
-                let true_block = function.block(true_label).last();
-                let false_block = function.block(false_label).last();
-
-                // control flow
-                let (merge_block, continue_target) = match (true_block, false_block) {
-                    // Both blocks end up branching to the same block.
-                    // This is probably a structured if
-                    (
-                        Some(Operation::Branch { label }),
-                        Some(Operation::Branch { label: label1 }),
-                    ) if Rc::ptr_eq(label, label1) => (Some(label.clone()), None),
-
-                    // True block ends up branching to the current label.
-                    // True block is probably the continue target, leaving the false block as the "exit" branch
-                    (Some(Operation::Branch { label }), _) if label == current_block => {
-                        (Some(false_label.clone()), Some(true_label.clone()))
-                    }
-
-                    // True block ends up branching to the false label.
-                    (Some(Operation::Branch { label }), _) if label == false_label => {
-                        (Some(false_label.clone()), None)
-                    }
-
-                    (_, Some(Operation::Branch { label })) if label == true_label => {
-                        (Some(true_label.clone()), None)
-                    }
-
-                    // False block ends up branching to the current label.
-                    // False block is probably the continue target, leaving the true block as the "exit" branch
-                    (_, Some(Operation::Branch { label })) if label == current_block => {
-                        (Some(true_label.clone()), Some(false_label.clone()))
-                    }
-                    _ => (None, None),
-                };
-
+                let construct = function.construct_of(self);
                 let selected = builder.selected_block();
                 let true_label = true_label.translate(module, Some(function), builder)?;
                 let false_label = false_label.translate(module, Some(function), builder)?;
 
-                match (&condition.source, continue_target) {
-                    (BoolSource::FromInteger(int), None) => {
+                match (&condition.source, construct) {
+                    // Selections over an integer don't need to convert it into a boolean first
+                    (
+                        BoolSource::FromInteger(int),
+                        Some(construct @ Construct::Selection { .. }),
+                    ) => {
                         let selector = int.translate(module, Some(function), builder)?;
                         let zero = match int.kind(module)? {
                             IntegerKind::Short => Operand::LiteralInt32(0),
@@ -1560,49 +2206,17 @@ impl Translation for &Operation {
                         };
 
                         // control flow
-                        if let Some(merge_block) = merge_block {
-                            let merge_block =
-                                merge_block.translate(module, Some(function), builder)?;
-                            let block = builder.selected_block();
-                            builder.selection_merge(merge_block, SelectionControl::NONE)?;
-                            builder.select_block(block)?;
-                        }
-
+                        translate_construct(construct, module, function, builder)?;
                         builder.switch(selector, true_label, Some((zero, false_label)))
                     }
 
-                    (_, continue_target) => {
+                    (_, construct) => {
                         let condition = condition.translate(module, Some(function), builder)?;
 
                         // control flow
-                        let block = builder.selected_block();
-                        match (merge_block, continue_target) {
-                            (Some(merge_block), None) => {
-                                let merge_block =
-                                    merge_block.translate(module, Some(function), builder)?;
-                                builder.selection_merge(merge_block, SelectionControl::NONE)?;
-                            }
-
-                            (Some(merge_block), Some(continue_target)) => {
-                                let merge_block =
-                                    merge_block.translate(module, Some(function), builder)?;
-
-                                let continue_target =
-                                    continue_target.translate(module, Some(function), builder)?;
-
-                                builder.loop_merge(
-                                    merge_block,
-                                    continue_target,
-                                    LoopControl::NONE,
-                                    None,
-                                )?;
-                            }
-
-                            (None, None) => {}
-
-                            _ => return Err(Error::unexpected()),
+                        if let Some(construct) = construct {
+                            translate_construct(construct, module, function, builder)?;
                         }
-                        builder.select_block(block)?;
 
                         builder.branch_conditional(condition, true_label, false_label, None)
                     }
@@ -1616,9 +2230,11 @@ impl Translation for &Operation {
                 value,
                 log2_alignment,
             } => {
+                let volatile = pointer.is_volatile();
                 let pointer = translate_to_skinny(pointer, module, function, builder)?;
                 let object = value.translate(module, function, builder)?;
-                let (memory_access, additional_params) = additional_access_info(*log2_alignment);
+                let (memory_access, additional_params) =
+                    additional_access_info(*log2_alignment, volatile);
 
                 builder.store(pointer, object, memory_access, additional_params)
             }
@@ -1629,6 +2245,7 @@ impl Translation for &Operation {
                 dst,
                 dst_log2_alignment,
             } => {
+                let volatile = src.is_volatile() || dst.is_volatile();
                 let src = src.translate(module, function, builder)?;
                 let dst = dst.translate(module, function, builder)?;
                 let (
@@ -1665,6 +2282,12 @@ impl Translation for &Operation {
                         }
                 }
 
+                if volatile {
+                    memory_access_1 = Some(
+                        memory_access_1.unwrap_or(MemoryAccess::NONE) | MemoryAccess::VOLATILE,
+                    );
+                }
+
                 let additional_params = additional_params_2.into_iter().chain(additional_params_1);
                 builder.copy_memory(
                     dst,
@@ -1687,15 +2310,138 @@ impl Translation for &Operation {
                 Ok(())
             }
 
+            Operation::DebugPrintf { set, format, args } => {
+                let set = set.translate(module, function, builder)?;
+                let format = builder.string(format.to_string());
+                let args = args
+                    .iter()
+                    .map(|x| x.translate(module, function, builder).map(Operand::IdRef))
+                    .collect::<Result<Vec<_>, _>>()?;
+
+                let void = builder.type_void();
+                builder.ext_inst(
+                    void,
+                    None,
+                    set,
+                    DebugPrintfInstr::DebugPrintf as u32,
+                    std::iter::once(Operand::IdRef(format)).chain(args),
+                )?;
+                Ok(())
+            }
+
+            Operation::TraceRay {
+                set,
+                binding,
+                args,
+                payload,
+            } => {
+                let payload = module
+                    .ray_payloads
+                    .iter()
+                    .find(|(location, _)| location == payload)
+                    .map(|(_, x)| x)
+                    .ok_or_else(|| {
+                        Error::msg(format!(
+                            "`trace_ray` uses the ray payload at location {payload}, but no parameter is a ray payload with that location"
+                        ))
+                    })?
+                    .translate(module, function, builder)?;
+
+                let args = args
+                    .iter()
+                    .map(|x| x.translate(module, function, builder))
+                    .collect::<Result<Vec<_>, _>>()?;
+                let [flags, cull_mask, sbt_offset, sbt_stride, miss_index, ox, oy, oz, tmin, dx, dy, dz, tmax] =
+                    args[..]
+                else {
+                    return Err(Error::unexpected());
+                };
+
+                let float = ScalarType::F32.translate(module, function, builder)?;
+                let vector = builder.type_vector(float, 3);
+                let origin = builder.composite_construct(vector, None, [ox, oy, oz])?;
+                let direction = builder.composite_construct(vector, None, [dx, dy, dz])?;
+
+                let variable = builder.acceleration_structure(*set, *binding);
+                let acceleration_structure_type = builder.type_acceleration_structure_khr();
+                let acceleration_structure =
+                    builder.load(acceleration_structure_type, None, variable, None, [])?;
+
+                builder.trace_ray_khr(
+                    acceleration_structure,
+                    flags,
+                    cull_mask,
+                    sbt_offset,
+                    sbt_stride,
+                    miss_index,
+                    origin,
+                    tmin,
+                    direction,
+                    tmax,
+                    payload,
+                )?;
+                Ok(())
+            }
+
             Operation::Nop => {
                 let selected = builder.selected_block();
                 builder.nop()?;
                 builder.select_block(selected)
             }
 
-            Operation::Unreachable => {
+            Operation::Unreachable { panic } => {
                 let selected = builder.selected_block();
-                builder.unreachable()?;
+                match module.trap_mode {
+                    TrapMode::TerminateInvocation if fragment_only(module, function) => {
+                        builder.terminate_invocation()?
+                    }
+                    TrapMode::Unreachable | TrapMode::TerminateInvocation => {
+                        builder.unreachable()?
+                    }
+
+                    // The loop gets blocks of its own, after the ones of the function
+                    TrapMode::InfiniteLoop => {
+                        let header = builder.id();
+                        let merge = builder.id();
+
+                        builder.branch(header)?;
+                        builder.begin_block(Some(header))?;
+                        builder.loop_merge(merge, header, LoopControl::NONE, None)?;
+                        builder.branch(header)?;
+                        builder.begin_block(Some(merge))?;
+                        builder.unreachable()?
+                    }
+
+                    TrapMode::Flag { .. } => {
+                        let function = function
+                            .ok_or_else(|| Error::msg("Traps must be inside a function"))?;
+                        let flag = module.trap_flag.as_ref().ok_or_else(Error::unexpected)?;
+
+                        let pointer = translate_to_skinny(flag, module, Some(function), builder)?;
+                        // Known panics are told apart by their index
+                        let code = match panic {
+                            Some(panic) => module
+                                .panics
+                                .iter()
+                                .position(|x| Rc::ptr_eq(x, panic))
+                                .map_or(1, |i| 2 + i as u32),
+                            None => 1,
+                        };
+
+                        let integer_type = builder.type_int(32, 0);
+                        let code = builder.constant_u32(integer_type, code);
+                        builder.store(pointer, code, None, None)?;
+                        trap_return(function, module, builder)?
+                    }
+
+                    TrapMode::DemoteToHelperInvocation if fragment_only(module, function) => {
+                        let function = function
+                            .ok_or_else(|| Error::msg("Traps must be inside a function"))?;
+                        builder.demote_to_helper_invocation_ext()?;
+                        trap_return(function, module, builder)?
+                    }
+                    TrapMode::DemoteToHelperInvocation => builder.unreachable()?,
+                };
                 builder.select_block(selected)
             }
 
@@ -1718,6 +2464,50 @@ impl Translation for &Operation {
     }
 }
 
+fn translate_construct(
+    construct: &Construct,
+    module: &ModuleBuilder,
+    function: &FunctionBuilder,
+    builder: &mut Builder,
+) -> Result<()> {
+    let block = builder.selected_block();
+    if function.verbosity >= Verbosity::Operators {
+        tracing::debug!(function = function.index, "Declaring {construct:?}");
+    }
+
+    match construct {
+        Construct::Selection { merge } => {
+            let merge = merge.translate(module, Some(function), builder)?;
+            builder.selection_merge(
+                merge,
+                SelectionHint::selection_control(
+                    function.selection_control,
+                    SelectionControl::NONE,
+                ),
+            )?;
+        }
+
+        Construct::Loop {
+            merge,
+            continue_target,
+        } => {
+            let merge = merge.translate(module, Some(function), builder)?;
+            let continue_target = continue_target.translate(module, Some(function), builder)?;
+
+            let (loop_control, dependency_length) = function.loops.loop_control();
+            if dependency_length.is_some() && module.version < Version::V1_1 {
+                return Err(Error::msg(
+                    "Loop dependency lengths require SPIR-V 1.1 or later",
+                ));
+            }
+
+            builder.loop_merge(merge, continue_target, loop_control, dependency_length)?;
+        }
+    }
+
+    return builder.select_block(block).map_err(Into::into);
+}
+
 impl Deref for Builder {
     type Target = rspirv::dr::Builder;
 
@@ -1732,16 +2522,177 @@ impl DerefMut for Builder {
     }
 }
 
-fn additional_access_info(log2_alignment: Option<u32>) -> (Option<MemoryAccess>, Option<Operand>) {
+fn rotate(
+    left: bool,
+    kind: IntegerKind,
+    result_type: rspirv::spirv::Word,
+    value: rspirv::spirv::Word,
+    amount: rspirv::spirv::Word,
+    builder: &mut Builder,
+) -> Result<rspirv::spirv::Word, rspirv::dr::Error> {
+    let mask = match kind {
+        IntegerKind::Short => builder.constant_u32(result_type, 31),
+        IntegerKind::Long => builder.constant_u64(result_type, 63),
+    };
+
+    let amount = builder.bitwise_and(result_type, None, amount, mask)?;
+    let negated = builder.s_negate(result_type, None, amount)?;
+    let complement = builder.bitwise_and(result_type, None, negated, mask)?;
+
+    let (left_amount, right_amount) = match left {
+        true => (amount, complement),
+        false => (complement, amount),
+    };
+
+    let high = builder.shift_left_logical(result_type, None, value, left_amount)?;
+    let low = builder.shift_right_logical(result_type, None, value, right_amount)?;
+    return builder.bitwise_or(result_type, None, high, low);
+}
+
+fn decorate_matrix_member(
+    structure_type: rspirv::spirv::Word,
+    member: u32,
+    ty: &Type,
+    builder: &mut Builder,
+) {
+    if let Some(matrix_stride) = ty.get_composite().and_then(CompositeType::matrix_stride) {
+        builder.member_decorate(structure_type, member, Decoration::ColMajor, None);
+        builder.member_decorate(
+            structure_type,
+            member,
+            Decoration::MatrixStride,
+            Some(Operand::LiteralInt32(matrix_stride)),
+        );
+    }
+}
+
+fn collect_interfaces(module: &mut Module, version: Version) {
+    let globals = module
+        .types_global_values
+        .iter()
+        .filter(|x| x.class.opcode == Op::Variable)
+        .filter_map(|x| match (x.result_id, x.operands.first()) {
+            (Some(id), Some(Operand::StorageClass(storage_class))) => Some((id, *storage_class)),
+            _ => None,
+        })
+        .filter(|(_, storage_class)| {
+            version >= Version::V1_4
+                || matches!(storage_class, StorageClass::Input | StorageClass::Output)
+        })
+        .map(|(id, _)| id)
+        .collect::<HashSet<_>>();
+
+    let functions = module
+        .functions
+        .iter()
+        .filter_map(|f| Some((f.def.as_ref()?.result_id?, f)))
+        .collect::<HashMap<_, _>>();
+
+    for entry_point in module.entry_points.iter_mut() {
+        let Some(Operand::IdRef(function_id)) = entry_point.operands.get(1) else {
+            continue;
+        };
+
+        let mut interface = BTreeSet::new();
+        let mut visited = HashSet::new();
+        let mut pending = vec![*function_id];
+
+        while let Some(id) = pending.pop() {
+            if !visited.insert(id) {
+                continue;
+            }
+            let Some(function) = functions.get(&id) else {
+                continue;
+            };
+
+            for instr in function.blocks.iter().flat_map(|x| x.instructions.iter()) {
+                for id in instr.operands.iter().filter_map(Operand::id_ref_any) {
+                    if globals.contains(&id) {
+                        interface.insert(id);
+                    } else if instr.class.opcode == Op::FunctionCall && functions.contains_key(&id)
+                    {
+                        pending.push(id);
+                    }
+                }
+            }
+        }
+
+        entry_point.operands.truncate(3);
+        entry_point
+            .operands
+            .extend(interface.into_iter().map(Operand::IdRef));
+    }
+}
+
+fn push_function_variable(variable: Instruction, builder: &mut Builder) -> Result<()> {
+    let current_block = builder.selected_block();
+    builder.select_block(Some(0))?;
+    builder.insert_into_block(rspirv::dr::InsertPoint::Begin, variable)?;
+    builder.select_block(current_block)?;
+    return Ok(());
+}
+
+fn structure_type(
+    pointee_type: rspirv::spirv::Word,
+    pointee: &Type,
+    block: Decoration,
+    builder: &mut Builder,
+) -> rspirv::spirv::Word {
+    let module = builder.module_ref();
+    let existing = module
+        .types_global_values
+        .iter()
+        .filter(|x| {
+            x.class.opcode == Op::TypeStruct && x.operands == [Operand::IdRef(pointee_type)]
+        })
+        .filter_map(|x| x.result_id)
+        .find(|id| {
+            module.annotations.iter().any(|x| {
+                x.class.opcode == Op::Decorate
+                    && x.operands.first() == Some(&Operand::IdRef(*id))
+                    && x.operands.get(1) == Some(&Operand::Decoration(block))
+            })
+        });
+
+    if let Some(structure_type) = existing {
+        return structure_type;
+    }
+
+    let id = builder.id();
+    let structure_type = builder.type_struct_id(Some(id), [pointee_type]);
+    builder.member_decorate(
+        structure_type,
+        0,
+        Decoration::Offset,
+        Some(Operand::LiteralInt32(0)),
+    );
+    decorate_matrix_member(structure_type, 0, pointee, builder);
+    builder.decorate(structure_type, block, None);
+    return structure_type;
+}
+
+fn additional_access_info(
+    log2_alignment: Option<u32>,
+    volatile: bool,
+) -> (Option<MemoryAccess>, Option<Operand>) {
     cfg_if::cfg_if! {
         if #[cfg(feature = "naga")] {
-            (None, None)
+            let (memory_access, params) = (None, None);
+            let _ = log2_alignment;
         } else {
-            log2_alignment
+            let (memory_access, params) = log2_alignment
                 .map(|align| (MemoryAccess::ALIGNED, Operand::LiteralInt32(1 << align)))
-                .unzip()
+                .unzip();
         }
     }
+
+    return match volatile {
+        true => (
+            Some(memory_access.unwrap_or(MemoryAccess::NONE) | MemoryAccess::VOLATILE),
+            params,
+        ),
+        false => (memory_access, params),
+    };
 }
 
 fn translate_to_skinny(
@@ -1781,11 +2732,15 @@ fn translate_to_skinny(
             .ok_or_else(Error::unexpected)?;
 
         let stride = Rc::new(Integer::new_constant_usize(stride, module));
-        let offset = pointer
+        let index = pointer
             .byte_offset()
             .unwrap_or_else(|| Rc::new(Integer::new_constant_usize(0, module)))
-            .u_div(stride, true, module)?
-            .translate(module, function, builder)?;
+            .u_div(stride, true, module)?;
+
+        let mut offset = index.translate(module, function, builder)?;
+        if module.bounds_checks && pointer.is_structured() {
+            offset = clamp_index(offset, index.kind(module)?, pointer_word, builder)?;
+        }
 
         indexes.push(offset);
     }
@@ -1798,6 +2753,31 @@ fn translate_to_skinny(
     };
 }
 
+fn clamp_index(
+    index: spirv::Word,
+    kind: IntegerKind,
+    structure: spirv::Word,
+    builder: &mut Builder,
+) -> Result<spirv::Word> {
+    let u32_type = builder.type_int(32, 0);
+    let length = builder.array_length(u32_type, None, structure, 0)?;
+
+    let (index_type, length, one) = match kind {
+        IntegerKind::Short => (u32_type, length, builder.constant_u32(u32_type, 1)),
+        IntegerKind::Long => {
+            let u64_type = builder.type_int(64, 0);
+            let length = builder.u_convert(u64_type, None, length)?;
+            (u64_type, length, builder.constant_u64(u64_type, 1))
+        }
+    };
+
+    // Accesses into empty arrays trap before getting here, so there's always a last element
+    let last = builder.i_sub(index_type, None, length, one)?;
+    let bool_type = builder.type_bool();
+    let in_bounds = builder.u_less_than(bool_type, None, index, length)?;
+    return Ok(builder.select(index_type, None, in_bounds, index, last)?);
+}
+
 fn fast_fmin(
     boolean: spirv::Word,
     result_type: spirv::Word,
@@ -1829,6 +2809,7 @@ fn fast_fmin(
                     [Operand::IdRef(operand_1), Operand::IdRef(operand_2)],
                 )?);
             }
+            _ => continue,
         }
     }
 
@@ -1838,6 +2819,73 @@ fn fast_fmin(
         .map_err(Into::into)
 }
 
+#[allow(clippy::too_many_arguments)]
+fn transcendental<const N: usize>(
+    glsl: GLSLInstr,
+    opencl: OpenCLInstr,
+    kind: FloatKind,
+    result_type: spirv::Word,
+    operands: [spirv::Word; N],
+    module: &ModuleBuilder,
+    function: Option<&FunctionBuilder>,
+    builder: &mut Builder,
+) -> Result<spirv::Word> {
+    for is in module.extended_is.iter() {
+        let instruction = match is.kind {
+            ExtendedSet::GLSL450 if kind == FloatKind::Single => glsl as u32,
+            ExtendedSet::OpenCL => opencl as u32,
+            _ => continue,
+        };
+
+        let extension_set = is.translate(module, function, builder)?;
+        return Ok(builder.ext_inst(
+            result_type,
+            None,
+            extension_set,
+            instruction,
+            operands.map(Operand::IdRef),
+        )?);
+    }
+
+    return Err(Error::msg(format!(
+        "`{glsl:?}` of {kind:?} floats is not supported on this platform"
+    )));
+}
+
+fn fragment_only(module: &ModuleBuilder, function: Option<&FunctionBuilder>) -> bool {
+    if let Some(entry_point) = function.and_then(|x| x.entry_point.as_ref()) {
+        return entry_point.execution_model == ExecutionModel::Fragment;
+    }
+
+    return module
+        .built_functions
+        .iter()
+        .filter_map(|x| x.entry_point.as_ref())
+        .all(|x| x.execution_model == ExecutionModel::Fragment);
+}
+
+fn trap_return(
+    function: &FunctionBuilder,
+    module: &ModuleBuilder,
+    builder: &mut Builder,
+) -> Result<()> {
+    match &function.return_type {
+        Some(ty) => {
+            let return_type = ty.clone().translate(module, Some(function), builder)?;
+            let undef = builder.undef(return_type, None);
+            builder.ret_value(undef)?
+        }
+        None => builder.ret()?,
+    }
+    return Ok(());
+}
+
+fn demoted_bitcast() -> Error {
+    return Error::msg(
+        "64-bit floats demoted to 32-bit ones can't be reinterpreted from or into 64-bit integers",
+    );
+}
+
 fn fast_fmax(
     boolean: spirv::Word,
     result_type: spirv::Word,
@@ -1869,6 +2917,7 @@ fn fast_fmax(
                     [Operand::IdRef(operand_1), Operand::IdRef(operand_2)],
                 )?);
             }
+            _ => continue,
         }
     }
 
@@ -1877,3 +2926,217 @@ fn fast_fmax(
         .select(result_type, None, condition, operand_1, operand_2)
         .map_err(Into::into)
 }
+
+/// Scalar value, as seen by the iterative translation of deep chains
+#[derive(Clone, Copy)]
+enum Node<'a> {
+    Integer(&'a Integer),
+    Float(&'a Float),
+    Bool(&'a Bool),
+}
+
+impl<'a> Node<'a> {
+    fn from_value(value: &'a Value) -> Option<Self> {
+        return match value {
+            Value::Integer(x) => Some(Node::Integer(x)),
+            Value::Float(x) => Some(Node::Float(x)),
+            Value::Bool(x) => Some(Node::Bool(x)),
+            Value::Pointer(_) | Value::Vector(_) => None,
+        };
+    }
+
+    fn address(self) -> *const () {
+        return match self {
+            Node::Integer(x) => (x as *const Integer).cast(),
+            Node::Float(x) => (x as *const Float).cast(),
+            Node::Bool(x) => (x as *const Bool).cast(),
+        };
+    }
+
+    fn is_translated(self) -> bool {
+        return match self {
+            Node::Integer(x) => x.translation.get().is_some(),
+            Node::Float(x) => x.translation.get().is_some(),
+            Node::Bool(x) => x.translation.get().is_some(),
+        };
+    }
+
+    fn translate(
+        self,
+        module: &ModuleBuilder,
+        function: Option<&FunctionBuilder>,
+        builder: &mut Builder,
+    ) -> Result<rspirv::spirv::Word> {
+        return match self {
+            Node::Integer(x) => x.translate(module, function, builder),
+            Node::Float(x) => x.translate(module, function, builder),
+            Node::Bool(x) => x.translate(module, function, builder),
+        };
+    }
+
+    fn operands(self, operands: &mut Vec<Node<'a>>) {
+        match self {
+            Node::Integer(x) => match &x.source {
+                IntegerSource::Select {
+                    selector,
+                    true_value,
+                    false_value,
+                } => operands.extend([
+                    Node::Integer(true_value),
+                    Node::Integer(false_value),
+                    Node::Bool(selector),
+                ]),
+                IntegerSource::Unary { op1, .. } => operands.push(Node::Integer(op1)),
+                IntegerSource::Binary { op1, op2, .. } => {
+                    operands.extend([Node::Integer(op1), Node::Integer(op2)])
+                }
+                IntegerSource::ConstantArrayElement { index, .. } => {
+                    operands.push(Node::Integer(index))
+                }
+                IntegerSource::Conversion(IntConversionSource::Bitcast { value, .. }) => {
+                    operands.extend(Node::from_value(value))
+                }
+                IntegerSource::Conversion(
+                    IntConversionSource::FromLong(value)
+                    | IntConversionSource::FromShort { value, .. },
+                ) => operands.push(Node::Integer(value)),
+                IntegerSource::Conversion(IntConversionSource::FromFloat { value, .. }) => {
+                    operands.push(Node::Float(value))
+                }
+                IntegerSource::Conversion(IntConversionSource::FromBool(value, _)) => {
+                    operands.push(Node::Bool(value))
+                }
+                _ => {}
+            },
+
+            Node::Float(x) => match &x.source {
+                FloatSource::Select {
+                    selector,
+                    true_value,
+                    false_value,
+                } => operands.extend([
+                    Node::Float(true_value),
+                    Node::Float(false_value),
+                    Node::Bool(selector),
+                ]),
+                FloatSource::Unary { op1, .. } => operands.push(Node::Float(op1)),
+                FloatSource::Binary { op1, op2, .. } => {
+                    operands.extend([Node::Float(op1), Node::Float(op2)])
+                }
+                FloatSource::Conversion(FloatConversionSource::Bitcast { value, .. }) => {
+                    operands.extend(Node::from_value(value))
+                }
+                FloatSource::Conversion(
+                    FloatConversionSource::FromSingle(value)
+                    | FloatConversionSource::FromDouble(value),
+                ) => operands.push(Node::Float(value)),
+                FloatSource::Conversion(FloatConversionSource::FromInteger { value, .. }) => {
+                    operands.push(Node::Integer(value))
+                }
+                _ => {}
+            },
+
+            Node::Bool(x) => match &x.source {
+                BoolSource::FromInteger(value) => operands.push(Node::Integer(value)),
+                BoolSource::Negated(value) => operands.push(Node::Bool(value)),
+                BoolSource::Select {
+                    selector,
+                    true_value,
+                    false_value,
+                } => operands.extend([
+                    Node::Bool(true_value),
+                    Node::Bool(false_value),
+                    Node::Bool(selector),
+                ]),
+                BoolSource::IntEquality { op1, op2, .. }
+                | BoolSource::IntComparison { op1, op2, .. } => {
+                    operands.extend([Node::Integer(op1), Node::Integer(op2)])
+                }
+                BoolSource::FloatEquality { op1, op2, .. }
+                | BoolSource::FloatComparison { op1, op2, .. } => {
+                    operands.extend([Node::Float(op1), Node::Float(op2)])
+                }
+                _ => {}
+            },
+        }
+    }
+}
+
+fn pending_depth(root: Node) -> usize {
+    let mut depths = HashMap::<*const (), usize>::new();
+    let mut stack = vec![(root, false)];
+    let mut operands = Vec::new();
+
+    while let Some((node, expanded)) = stack.pop() {
+        if depths.contains_key(&node.address()) {
+            continue;
+        }
+
+        node.operands(&mut operands);
+        operands.retain(|x| !x.is_translated());
+
+        match expanded {
+            true => {
+                let depth = operands
+                    .drain(..)
+                    .filter_map(|x| depths.get(&x.address()).copied())
+                    .max()
+                    .unwrap_or_default();
+                depths.insert(node.address(), depth + 1);
+            }
+            false => {
+                stack.push((node, true));
+                stack.extend(operands.drain(..).map(|x| (x, false)));
+            }
+        }
+    }
+
+    return depths.get(&root.address()).copied().unwrap_or_default();
+}
+
+fn translate_operands(
+    node: Node,
+    module: &ModuleBuilder,
+    function: Option<&FunctionBuilder>,
+    builder: &mut Builder,
+) -> Result<()> {
+    let depth = pending_depth(node);
+    if let Some(max) = module.max_value_depth {
+        if depth > max as usize {
+            return Err(Error::msg(format!(
+                "Value depends on a chain of {depth} operations, deeper than the maximum of {max}"
+            )));
+        }
+    }
+
+    if depth <= RECURSION_LIMIT {
+        return Ok(());
+    }
+
+    let mut operands = Vec::new();
+    node.operands(&mut operands);
+    let mut stack = operands
+        .drain(..)
+        .rev()
+        .map(|x| (x, false))
+        .collect::<Vec<_>>();
+
+    while let Some((node, expanded)) = stack.pop() {
+        if node.is_translated() {
+            continue;
+        }
+
+        match expanded {
+            true => {
+                let _ = node.translate(module, function, builder)?;
+            }
+            false => {
+                stack.push((node, true));
+                node.operands(&mut operands);
+                stack.extend(operands.drain(..).rev().map(|x| (x, false)));
+            }
+        }
+    }
+
+    return Ok(());
+}
@@ -1,10 +1,14 @@
-use crate::fg::{
-    module::ModuleBuilder,
-    values::{float::FloatKind, integer::IntegerKind, pointer::PointerKind},
+use crate::{
+    error::{Error, Result},
+    fg::{
+        module::ModuleBuilder,
+        values::{float::FloatKind, integer::IntegerKind, pointer::PointerKind},
+    },
 };
 use num_enum::TryFromPrimitive;
 use rspirv::spirv::{Capability, StorageClass};
 use serde::{Deserialize, Serialize};
+use std::fmt::Display;
 use wasmparser::ValType;
 
 #[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Default, Serialize, Deserialize)]
@@ -24,7 +28,7 @@ impl PointerSize {
     }
 }
 
-#[derive(Debug, Clone, PartialEq, Hash, Serialize, Deserialize)]
+#[derive(Debug, Clone, PartialEq, Eq, Hash, Serialize, Deserialize)]
 #[serde(untagged)]
 pub enum Type {
     Pointer {
@@ -47,9 +51,22 @@ pub enum ScalarType {
     Bool,
 }
 
-#[derive(Debug, Clone, PartialEq, Hash, Serialize, Deserialize)]
+#[derive(Debug, Clone, PartialEq, Eq, Hash, Serialize, Deserialize)]
 pub enum CompositeType {
     Vector(ScalarType, u32),
+    /// Column-major matrix, with its column count, row count and element type
+    Matrix(u32, u32, ScalarType),
+    /// Structure with explicitly laid out members
+    Struct(Box<[StructMember]>),
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, Hash, Serialize, Deserialize)]
+pub struct StructMember {
+    pub name: Box<str>,
+    #[serde(rename = "type")]
+    pub ty: Type,
+    /// Byte offset of the member from the start of the structure
+    pub offset: u32,
 }
 
 impl Type {
@@ -65,7 +82,7 @@ impl Type {
         match self {
             Type::Pointer { storage_class, .. } => module.spirv_address_bytes(*storage_class),
             Type::Scalar(x) => x.byte_size(),
-            Type::Composite(CompositeType::Vector(elem, count)) => Some(elem.byte_size()? * count),
+            Type::Composite(x) => x.byte_size(),
         }
     }
 
@@ -126,6 +143,218 @@ impl CompositeType {
     pub fn vector(elem: impl Into<ScalarType>, count: u32) -> CompositeType {
         return CompositeType::Vector(elem.into(), count);
     }
+
+    pub fn matrix(columns: u32, rows: u32, elem: impl Into<ScalarType>) -> CompositeType {
+        return CompositeType::Matrix(columns, rows, elem.into());
+    }
+
+    /// Type of each of the matrix's columns
+    pub fn column_type(&self) -> Option<CompositeType> {
+        match self {
+            CompositeType::Matrix(_, rows, elem) => Some(CompositeType::Vector(*elem, *rows)),
+            _ => None,
+        }
+    }
+
+    /// Byte distance between two consecutive columns of a matrix (three-component columns are padded to four)
+    pub fn matrix_stride(&self) -> Option<u32> {
+        match self {
+            CompositeType::Matrix(_, 3, elem) => Some(elem.byte_size()? * 4),
+            CompositeType::Matrix(_, rows, elem) => Some(elem.byte_size()? * rows),
+            _ => None,
+        }
+    }
+
+    pub fn byte_size(&self) -> Option<u32> {
+        match self {
+            CompositeType::Vector(elem, count) => Some(elem.byte_size()? * count),
+            CompositeType::Matrix(columns, ..) => Some(self.matrix_stride()? * columns),
+            CompositeType::Struct(members) => {
+                let mut size = 0;
+                let mut alignment = 1;
+                for member in members.iter() {
+                    let member_size = match &member.ty {
+                        Type::Scalar(x) => x.byte_size()?,
+                        Type::Composite(x) => x.byte_size()?,
+                        Type::Pointer { .. } => return None,
+                    };
+                    size = u32::max(size, member.offset + member_size);
+                    alignment = u32::max(alignment, Layout::Std430.alignment(&member.ty).ok()?);
+                }
+                // Padded up to its alignment, so that it can be the element of an array
+                Some(size.next_multiple_of(alignment))
+            }
+        }
+    }
+
+    /// Returns the index of the member found at `offset`, along with said member
+    pub fn member_at(&self, offset: u32) -> Option<(u32, &StructMember)> {
+        match self {
+            CompositeType::Struct(members) => (0..)
+                .zip(members.iter())
+                .find(|(_, member)| member.offset == offset),
+            _ => None,
+        }
+    }
+}
+
+impl StructMember {
+    pub fn new(name: impl Into<Box<str>>, ty: impl Into<Type>, offset: u32) -> Self {
+        return Self {
+            name: name.into(),
+            ty: ty.into(),
+            offset,
+        };
+    }
+}
+
+/* LAYOUT */
+/// Rules used to lay out the contents of buffers
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum Layout {
+    /// Layout of uniform buffers. Structures are aligned to (and padded up to) 16 bytes
+    Std140,
+    /// Layout of storage buffers
+    Std430,
+}
+
+impl Layout {
+    /// Base alignment of `ty`
+    pub fn alignment(self, ty: &Type) -> Result<u32> {
+        match ty {
+            Type::Pointer { .. } => Err(Error::msg(format!("pointers don't have a {self} layout"))),
+            Type::Scalar(x) => x
+                .byte_size()
+                .ok_or_else(|| Error::msg(format!("{x:?} doesn't have a {self} layout"))),
+            Type::Composite(CompositeType::Vector(elem, count @ 2..=4)) => {
+                let size = self.alignment(&Type::Scalar(*elem))?;
+                Ok(match count {
+                    2 => 2 * size,
+                    _ => 4 * size,
+                })
+            }
+            Type::Composite(x @ CompositeType::Vector(..)) => {
+                Err(Error::msg(format!("{x:?} doesn't have a {self} layout")))
+            }
+            Type::Composite(x @ CompositeType::Matrix(..)) => self.column_stride(x),
+            Type::Composite(CompositeType::Struct(members)) => {
+                let mut alignment = match self {
+                    Layout::Std140 => 16,
+                    Layout::Std430 => 1,
+                };
+                for member in members.iter() {
+                    alignment = u32::max(alignment, self.alignment(&member.ty)?);
+                }
+                Ok(self.round(alignment))
+            }
+        }
+    }
+
+    /// Size of `ty`, including the padding at the end of structures
+    pub fn size(self, ty: &Type) -> Result<u32> {
+        match ty {
+            Type::Composite(x @ CompositeType::Matrix(columns, ..)) => {
+                Ok(self.column_stride(x)? * columns)
+            }
+            Type::Composite(CompositeType::Struct(members)) => {
+                let mut size = 0;
+                for member in members.iter() {
+                    size = u32::max(size, member.offset + self.size(&member.ty)?);
+                }
+                Ok(size.next_multiple_of(self.alignment(ty)?))
+            }
+            Type::Composite(CompositeType::Vector(elem, count)) => {
+                self.alignment(ty)?;
+                Ok(self.alignment(&Type::Scalar(*elem))? * count)
+            }
+            _ => self.alignment(ty),
+        }
+    }
+
+    /// Creates a structure with its members laid out one after the other, following these rules
+    pub fn struct_type<N: Into<Box<str>>>(
+        self,
+        members: impl IntoIterator<Item = (N, Type)>,
+    ) -> Result<CompositeType> {
+        let mut offset = 0u32;
+        let mut result = Vec::new();
+
+        for (name, ty) in members {
+            let member_offset = offset.next_multiple_of(self.alignment(&ty)?);
+            offset = member_offset + self.size(&ty)?;
+            result.push(StructMember::new(name, ty, member_offset));
+        }
+
+        return Ok(CompositeType::Struct(result.into_boxed_slice()));
+    }
+
+    /// Checks that the explicit offsets and strides of `ty` (and its members) follow these rules
+    pub fn validate(self, ty: &Type, name: &str) -> Result<()> {
+        match ty {
+            Type::Composite(x @ CompositeType::Matrix(..)) => {
+                let stride = x.matrix_stride().ok_or_else(Error::unexpected)?;
+                let required = self.column_stride(x)?;
+                if stride != required {
+                    return Err(Error::msg(format!(
+                        "matrix `{name}` violates {self} stride (stride {stride}, requires {required})"
+                    )));
+                }
+            }
+
+            Type::Composite(CompositeType::Struct(members)) => {
+                let mut end = 0;
+                for (member, i) in members.iter().zip(0..) {
+                    let alignment = self.alignment(&member.ty)?;
+                    if member.offset % alignment != 0 {
+                        return Err(Error::msg(format!(
+                            "member {i} of block `{name}` violates {self} alignment (offset {}, requires {alignment})",
+                            member.offset
+                        )));
+                    } else if member.offset < end {
+                        return Err(Error::msg(format!(
+                            "member {i} of block `{name}` overlaps the previous member (offset {}, previous member ends at {end})",
+                            member.offset
+                        )));
+                    }
+
+                    self.validate(&member.ty, &member.name)?;
+                    end = member.offset + self.size(&member.ty)?;
+                    // Members that follow a structure start after its padding
+                    if let Type::Composite(CompositeType::Struct(_)) = member.ty {
+                        end = end.next_multiple_of(alignment);
+                    }
+                }
+            }
+
+            _ => {
+                self.alignment(ty)?;
+            }
+        }
+
+        return Ok(());
+    }
+
+    fn column_stride(self, matrix: &CompositeType) -> Result<u32> {
+        let column = matrix.column_type().ok_or_else(Error::unexpected)?;
+        return Ok(self.round(self.alignment(&Type::Composite(column))?));
+    }
+
+    fn round(self, alignment: u32) -> u32 {
+        return match self {
+            Layout::Std140 => alignment.next_multiple_of(16),
+            Layout::Std430 => alignment,
+        };
+    }
+}
+
+impl Display for Layout {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Layout::Std140 => f.write_str("std140"),
+            Layout::Std430 => f.write_str("std430"),
+        }
+    }
 }
 
 /* CONVERSIONS */
@@ -171,6 +400,9 @@ impl From<CompositeType> for Type {
     }
 }
 
+/// Handle of null references. Function references are handled as their function index plus one
+pub const NULL_REFERENCE: u32 = 0;
+
 impl From<ValType> for Type {
     fn from(value: ValType) -> Self {
         match value {
@@ -179,7 +411,8 @@ impl From<ValType> for Type {
             ValType::F32 => Type::Scalar(ScalarType::F32),
             ValType::F64 => Type::Scalar(ScalarType::F64),
             ValType::V128 => todo!(),
-            ValType::Ref(_) => todo!(),
+            // References are opaque handles, only usable with null checks
+            ValType::Ref(_) => Type::Scalar(ScalarType::I32),
         }
     }
 }
@@ -1,8 +1,10 @@
 use crate::{
+    config::AddressingModel,
     error::Error,
     fg::extended_is::{ExtendedIs, ExtendedSet},
 };
 use docfg::docfg;
+use rspirv::spirv::{Capability, MemoryModel};
 use serde::{Deserialize, Serialize};
 use std::{fmt::Display, str::FromStr};
 
@@ -79,6 +81,8 @@ impl Default for Version {
 pub enum TargetPlatform {
     Universal(Version),
     Vulkan(Version),
+    OpenGL(Version),
+    OpenCL(Version),
 }
 
 impl TargetPlatform {
@@ -88,19 +92,79 @@ impl TargetPlatform {
     pub const SPV_1_3: TargetPlatform = Self::Universal(Version::V1_3);
     pub const SPV_1_4: TargetPlatform = Self::Universal(Version::V1_4);
     pub const SPV_1_5: TargetPlatform = Self::Universal(Version::V1_5);
+    pub const SPV_1_6: TargetPlatform = Self::Universal(Version::V1_6);
 
     pub const VK_1_0: TargetPlatform = Self::Vulkan(Version::V1_0);
     pub const VK_1_1: TargetPlatform = Self::Vulkan(Version::V1_1);
     pub const VK_1_2: TargetPlatform = Self::Vulkan(Version::V1_2);
+    pub const VK_1_3: TargetPlatform = Self::Vulkan(Version::V1_3);
+
+    pub const GL_4_5: TargetPlatform = Self::OpenGL(Version::new(4, 5));
+    pub const GL_4_6: TargetPlatform = Self::OpenGL(Version::new(4, 6));
+
+    pub const CL_2_1: TargetPlatform = Self::OpenCL(Version::new(2, 1));
+    pub const CL_2_2: TargetPlatform = Self::OpenCL(Version::new(2, 2));
 
     pub fn extended_is(&self) -> Option<ExtendedIs> {
         let kind = match self {
-            Self::Vulkan(_) => ExtendedSet::GLSL450,
+            Self::Vulkan(_) | Self::OpenGL(_) => ExtendedSet::GLSL450,
+            Self::OpenCL(_) => ExtendedSet::OpenCL,
             _ => return None,
         };
         return Some(ExtendedIs::new(kind));
     }
 
+    /// Version of the platform itself, like `1.3` for Vulkan 1.3
+    pub fn version(self) -> Version {
+        return match self {
+            TargetPlatform::Universal(version)
+            | TargetPlatform::Vulkan(version)
+            | TargetPlatform::OpenGL(version)
+            | TargetPlatform::OpenCL(version) => version,
+        };
+    }
+
+    /// Checks that the platform has the specified version, listing the ones it does have otherwise
+    pub fn check(self) -> Result<(), Error> {
+        let (name, versions): (_, &[Version]) = match self {
+            TargetPlatform::Universal(_) => (
+                "SPIR-V",
+                &[
+                    Version::V1_0,
+                    Version::V1_1,
+                    Version::V1_2,
+                    Version::V1_3,
+                    Version::V1_4,
+                    Version::V1_5,
+                    Version::V1_6,
+                ],
+            ),
+            TargetPlatform::Vulkan(_) => (
+                "Vulkan",
+                &[Version::V1_0, Version::V1_1, Version::V1_2, Version::V1_3],
+            ),
+            TargetPlatform::OpenGL(_) => ("OpenGL", &[Version::new(4, 5), Version::new(4, 6)]),
+            TargetPlatform::OpenCL(_) => (
+                "OpenCL",
+                &[Version::new(2, 1), Version::new(2, 2), Version::new(3, 0)],
+            ),
+        };
+
+        let version = self.version();
+        if versions.contains(&version) {
+            return Ok(());
+        }
+
+        let versions = versions
+            .iter()
+            .map(Version::to_string)
+            .collect::<Vec<_>>()
+            .join(", ");
+        return Err(Error::msg(format!(
+            "{name} {version} isn't a supported target platform (supported versions of {name} are {versions})"
+        )));
+    }
+
     pub fn is_vulkan(self) -> bool {
         return matches!(self, Self::Vulkan(_));
     }
@@ -119,6 +183,61 @@ impl TargetPlatform {
                     Version::V1_0
                 }
             }
+            // OpenGL 4.6 (with `GL_ARB_gl_spirv`) only guarantees SPIR-V 1.0
+            TargetPlatform::OpenGL(_) => Version::V1_0,
+            TargetPlatform::OpenCL(version) => {
+                if version >= Version::new(2, 2) {
+                    Version::V1_2
+                } else {
+                    Version::V1_0
+                }
+            }
+        };
+    }
+
+    /// Memory model used by default when targeting this platform
+    pub fn default_memory_model(self) -> MemoryModel {
+        return match self {
+            TargetPlatform::OpenCL(_) => MemoryModel::OpenCL,
+            _ => MemoryModel::GLSL450,
+        };
+    }
+
+    /// Addressing model used by default when targeting this platform
+    pub fn default_addressing_model(self) -> AddressingModel {
+        return match self {
+            TargetPlatform::OpenCL(_) => AddressingModel::Physical,
+            _ => AddressingModel::Logical,
+        };
+    }
+
+    /// Capabilities every implementation of the platform is required to support
+    pub fn core_capabilities(self) -> Vec<Capability> {
+        return match self {
+            TargetPlatform::Universal(_) => Vec::new(),
+            TargetPlatform::Vulkan(_) | TargetPlatform::OpenGL(_) => {
+                vec![Capability::Shader, Capability::Matrix]
+            }
+            TargetPlatform::OpenCL(_) => vec![
+                Capability::Addresses,
+                Capability::Kernel,
+                Capability::Int64,
+                Capability::Linkage,
+            ],
+        };
+    }
+
+    /// Extensions that are part of the platform's core, and don't need to be enabled by the user
+    pub fn core_extensions(self) -> &'static [&'static str] {
+        return match self {
+            TargetPlatform::Vulkan(version) if version >= Version::V1_1 => &[
+                "SPV_KHR_storage_buffer_storage_class",
+                "SPV_KHR_variable_pointers",
+                "SPV_KHR_16bit_storage",
+                "SPV_KHR_multiview",
+                "SPV_KHR_device_group",
+            ],
+            _ => &[],
         };
     }
 }
@@ -126,18 +245,25 @@ impl TargetPlatform {
 #[docfg(feature = "spirv-tools")]
 impl From<&TargetPlatform> for spirv_tools::TargetEnv {
     fn from(platform: &TargetPlatform) -> Self {
-        match platform {
-            &TargetPlatform::SPV_1_0 => Self::Universal_1_0,
-            &TargetPlatform::SPV_1_1 => Self::Universal_1_1,
-            &TargetPlatform::SPV_1_2 => Self::Universal_1_2,
-            &TargetPlatform::SPV_1_3 => Self::Universal_1_3,
-            &TargetPlatform::SPV_1_4 => Self::Universal_1_4,
-            &TargetPlatform::SPV_1_5 => Self::Universal_1_5,
-
-            &TargetPlatform::VK_1_0 => Self::Vulkan_1_0,
-            &TargetPlatform::VK_1_1 => Self::Vulkan_1_1,
-            &TargetPlatform::VK_1_2 => Self::Vulkan_1_2,
-            _ => todo!(),
+        match *platform {
+            TargetPlatform::Universal(version) => match version {
+                Version::V1_0 => Self::Universal_1_0,
+                Version::V1_1 => Self::Universal_1_1,
+                Version::V1_2 => Self::Universal_1_2,
+                Version::V1_3 => Self::Universal_1_3,
+                Version::V1_4 => Self::Universal_1_4,
+                Version::V1_5 => Self::Universal_1_5,
+                _ => Self::Universal_1_5,
+            },
+            TargetPlatform::Vulkan(version) => match version {
+                Version::V1_0 => Self::Vulkan_1_0,
+                Version::V1_1 => Self::Vulkan_1_1,
+                Version::V1_2 => Self::Vulkan_1_2,
+                _ => Self::Vulkan_1_2,
+            },
+            TargetPlatform::OpenGL(_) => Self::OpenGL_4_5,
+            TargetPlatform::OpenCL(version) if version >= Version::new(2, 2) => Self::OpenCL_2_2,
+            TargetPlatform::OpenCL(_) => Self::OpenCL_2_1,
         }
     }
 }
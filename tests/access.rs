@@ -0,0 +1,70 @@
+use wasm2spirv::{config::Config, Compilation};
+
+fn saxpy(param: &str, access: serde_json::Value) -> color_eyre::Result<Compilation> {
+    let mut config: serde_json::Value =
+        serde_json::from_str(include_str!("../examples/saxpy/saxpy.json"))?;
+    config["functions"]["2"]["params"][param]["kind"]["descriptor_set"]["access"] = access;
+    let config: Config = serde_json::from_value(config)?;
+
+    let wasm = wat::parse_bytes(include_bytes!("../examples/saxpy/saxpy.wat"))?;
+    return Ok(Compilation::new(config, &wasm)?);
+}
+
+#[test]
+fn buffer_access() -> color_eyre::Result<()> {
+    let compilation = saxpy(
+        "2",
+        serde_json::json!({ "read_only": true, "restrict": true }),
+    )?;
+    let assembly = compilation.assembly()?;
+    assert!(assembly.contains("NonWritable"));
+    assert!(assembly.contains("Restrict"));
+    assert!(!assembly.contains("NonReadable"));
+
+    let assembly = saxpy("2", serde_json::json!({}))?.into_assembly()?;
+    assert!(!assembly.contains("NonWritable"));
+    assert!(!assembly.contains("Restrict"));
+    return Ok(());
+}
+
+#[test]
+fn conflicting_buffer_access() -> color_eyre::Result<()> {
+    assert!(saxpy(
+        "2",
+        serde_json::json!({ "read_only": true, "write_only": true })
+    )
+    .is_err());
+    assert!(saxpy(
+        "2",
+        serde_json::json!({ "restrict": true, "aliased": true })
+    )
+    .is_err());
+    return Ok(());
+}
+
+#[test]
+fn volatile_buffer_access() -> color_eyre::Result<()> {
+    let compilation = saxpy(
+        "3",
+        serde_json::json!({ "volatile": true, "coherent": true }),
+    )?;
+    let assembly = compilation.assembly()?;
+    assert!(assembly
+        .lines()
+        .any(|x| x.contains("OpDecorate") && x.contains("Volatile")));
+    assert!(assembly
+        .lines()
+        .any(|x| x.contains("OpDecorate") && x.contains("Coherent")));
+    assert!(assembly
+        .lines()
+        .any(|x| x.contains("OpStore") && x.contains("Volatile")));
+
+    let assembly = saxpy("2", serde_json::json!({ "volatile": true }))?.into_assembly()?;
+    assert!(assembly
+        .lines()
+        .any(|x| x.contains("OpLoad") && x.contains("Volatile")));
+
+    let assembly = saxpy("3", serde_json::json!({}))?.into_assembly()?;
+    assert!(!assembly.contains("Volatile"));
+    return Ok(());
+}
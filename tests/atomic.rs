@@ -0,0 +1,93 @@
+mod common;
+
+use wasm2spirv::{
+    binary::{deserialize::BinaryDeserialize, serialize::BinarySerialize},
+    config::{AtomicCounter, Config},
+    Compilation,
+};
+
+const WAT: &str = r#"
+(module
+  (import "spir_global" "atomic_counter_increment" (func $increment (param i32) (result i32)))
+  (memory 1)
+  (func (export "main")
+    ;; Appends to the buffer
+    i32.const 0
+    call $increment
+    i32.const 4
+    i32.mul
+    i32.const 42
+    i32.store
+
+    ;; Only counts
+    i32.const 1
+    call $increment
+    drop))
+"#;
+
+fn config() -> color_eyre::Result<Config> {
+    let mut config = common::vulkan();
+    config["memories"] = serde_json::json!({ "0": { "set": 0, "binding": 0 } });
+    config["atomic_counters"] = serde_json::json!({
+        "0": { "set": 0, "binding": 1 },
+        "1": { "set": 0, "binding": 2 }
+    });
+    config["functions"]["1"] = serde_json::json!({
+        "execution_model": "GLCompute",
+        "execution_modes": [{ "local_size": [64, 1, 1] }]
+    });
+
+    return Ok(serde_json::from_value(config)?);
+}
+
+#[test]
+fn atomic_counter_increment() -> color_eyre::Result<()> {
+    let config = config()?;
+    let report = config.validate();
+    assert!(report.is_ok(), "{report:?}");
+
+    let compilation = Compilation::new(config, &wat::parse_str(WAT)?)?;
+    #[cfg(feature = "spvt-validate")]
+    compilation.validate()?;
+
+    // The dropped increment is still performed
+    let assembly = compilation.assembly()?;
+    assert_eq!(assembly.matches("OpAtomicIAdd").count(), 2);
+    assert!(assembly.contains("Binding 1"));
+    assert!(assembly.contains("Binding 2"));
+    return Ok(());
+}
+
+#[test]
+fn atomic_counter_config() -> color_eyre::Result<()> {
+    let mut config = config()?;
+    let mut bytes = Vec::new();
+    config.serialize_into(&mut bytes)?;
+    let decoded = Config::deserialize_from(&mut &bytes[..])?;
+    assert_eq!(decoded.atomic_counters, config.atomic_counters);
+
+    // The counter can't share the binding of the memory
+    config
+        .atomic_counters
+        .insert(1, AtomicCounter { set: 0, binding: 0 });
+    let report = config.validate();
+    assert!(
+        report
+            .warnings
+            .iter()
+            .any(|x| x.contains("atomic counter 1")),
+        "{report:?}"
+    );
+
+    // Counters must be bound to a buffer
+    let wasm = wat::parse_str(WAT)?;
+    config.atomic_counters.remove(&1);
+    let Err(error) = Compilation::new(config, &wasm) else {
+        panic!("an unbound counter should be rejected");
+    };
+    assert!(
+        format!("{error:?}").contains("Atomic counter 1"),
+        "{error:?}"
+    );
+    return Ok(());
+}
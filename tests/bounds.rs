@@ -0,0 +1,58 @@
+use wasm2spirv::{config::Config, Compilation};
+
+fn saxpy(bounds_checks: bool) -> color_eyre::Result<String> {
+    let mut config: Config = serde_json::from_str(include_str!("../examples/saxpy/saxpy.json"))?;
+    config.bounds_checks = bounds_checks;
+
+    let wasm = wat::parse_bytes(include_bytes!("../examples/saxpy/saxpy.wat"))?;
+    return Ok(Compilation::new(config, &wasm)?.into_assembly()?);
+}
+
+#[test]
+fn bounds_checks() -> color_eyre::Result<()> {
+    let checked = saxpy(true)?;
+    assert!(checked.contains("OpArrayLength"));
+    assert!(checked.contains("OpSelect"));
+
+    let unchecked = saxpy(false)?;
+    assert!(!unchecked.contains("OpArrayLength"));
+    assert!(!unchecked.contains("OpIEqual"));
+    return Ok(());
+}
+
+#[test]
+fn empty_buffers_trap() -> color_eyre::Result<()> {
+    let assembly = saxpy(true)?;
+    let lines = assembly.lines().map(str::trim).collect::<Vec<_>>();
+    let result = |line: &&str| line.split_once(" = ").map(|(id, _)| id);
+
+    // Comparisons of a buffer's length against zero
+    let lengths = lines
+        .iter()
+        .filter(|x| x.contains("= OpArrayLength "))
+        .filter_map(result)
+        .collect::<Vec<_>>();
+    let is_empty = lines
+        .iter()
+        .filter(|x| x.contains("= OpIEqual "))
+        .filter(|x| x.split_whitespace().any(|x| lengths.contains(&x)))
+        .filter_map(result)
+        .collect::<Vec<_>>();
+    assert!(!is_empty.is_empty(), "{assembly}");
+
+    // Each of them branches to a trap
+    for condition in is_empty {
+        let branch = lines
+            .iter()
+            .find(|x| x.starts_with(&format!("OpBranchConditional {condition} ")))
+            .unwrap_or_else(|| panic!("{condition} doesn't branch\n{assembly}"));
+
+        let trap = branch.split_whitespace().nth(2).unwrap();
+        let trap_label = lines
+            .iter()
+            .position(|x| *x == format!("{trap} = OpLabel"))
+            .unwrap();
+        assert_eq!(lines[trap_label + 1], "OpUnreachable", "{assembly}");
+    }
+    return Ok(());
+}
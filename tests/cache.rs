@@ -0,0 +1,62 @@
+#![cfg(feature = "cache")]
+
+use wasm2spirv::{cache::CompilationCache, config::Config, Compilation};
+
+#[test]
+fn memoizes() -> color_eyre::Result<()> {
+    let config: Config = serde_json::from_str(include_str!("../examples/saxpy/saxpy.json"))?;
+    let wasm = wat::parse_bytes(include_bytes!("../examples/saxpy/saxpy.wat"))?;
+    let cache = CompilationCache::new(1);
+
+    let first = cache.words(&config, &wasm)?;
+    let second = cache.words(&config, &wasm)?;
+    assert!(std::sync::Arc::ptr_eq(&first, &second));
+    assert_eq!(&*first, Compilation::new(config, &wasm)?.words()?);
+    assert_eq!(cache.len(), 1);
+    return Ok(());
+}
+
+#[test]
+fn on_disk() -> color_eyre::Result<()> {
+    let config: Config = serde_json::from_str(include_str!("../examples/saxpy/saxpy.json"))?;
+    let wasm = wat::parse_bytes(include_bytes!("../examples/saxpy/saxpy.wat"))?;
+    let dir = std::env::temp_dir().join("wasm2spirv-cache-test");
+
+    let first = CompilationCache::new(0)
+        .with_directory(&dir)?
+        .words(&config, &wasm)?;
+    let second = CompilationCache::new(0)
+        .with_directory(&dir)?
+        .words(&config, &wasm)?;
+
+    assert_eq!(first, second);
+    let _ = std::fs::remove_dir_all(dir);
+    return Ok(());
+}
+
+#[test]
+fn mismatched_file() -> color_eyre::Result<()> {
+    let config: Config = serde_json::from_str(include_str!("../examples/saxpy/saxpy.json"))?;
+    let wasm = wat::parse_bytes(include_bytes!("../examples/saxpy/saxpy.wat"))?;
+    let dir = std::env::temp_dir().join("wasm2spirv-cache-mismatch-test");
+
+    let words = CompilationCache::new(0)
+        .with_directory(&dir)?
+        .words(&config, &wasm)?;
+
+    // Files whose stored key doesn't match are ignored
+    for entry in std::fs::read_dir(&dir)? {
+        let path = entry?.path();
+        let mut bytes = std::fs::read(&path)?;
+        bytes[0] ^= 0xff;
+        bytes.truncate(36);
+        std::fs::write(path, bytes)?;
+    }
+
+    let recompiled = CompilationCache::new(0)
+        .with_directory(&dir)?
+        .words(&config, &wasm)?;
+    assert_eq!(words, recompiled);
+    let _ = std::fs::remove_dir_all(dir);
+    return Ok(());
+}
@@ -0,0 +1,22 @@
+use wasm2spirv::{config::Config, Compilation};
+
+#[test]
+fn debug_cfg() -> color_eyre::Result<()> {
+    let config: Config = serde_json::from_str(include_str!("../examples/saxpy/saxpy.json"))?;
+    let wasm = wat::parse_bytes(include_bytes!("../examples/saxpy/saxpy.wat"))?;
+    let compilation = Compilation::new_borrowed(config, &wasm)?;
+
+    let dot = compilation.debug_cfg(2)?;
+    assert!(dot.starts_with("digraph"), "{dot}");
+    assert!(dot.contains("<B>entry</B>"), "{dot}");
+    assert!(dot.contains("[label=\"true\"]"), "{dot}");
+    assert!(dot.contains("[label=\"false\"]"), "{dot}");
+    assert!(dot.contains("BranchConditional"), "{dot}");
+    assert!(compilation.debug_cfg(42).is_err());
+
+    // The graph is gone once the module is translated
+    compilation.compilation()?;
+    assert!(compilation.debug_cfg(2).is_err());
+
+    return Ok(());
+}
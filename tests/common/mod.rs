@@ -0,0 +1,121 @@
+//! Fixtures shared by the integration tests
+#![allow(dead_code)]
+
+use rspirv::spirv::{Capability, MemoryModel};
+use wasm2spirv::{
+    config::{AddressingModel, CapabilityModel, Config, ConfigBuilder, WasmFeatures},
+    version::TargetPlatform,
+    Compilation,
+};
+
+/// Parameter bound to the storage buffer at `binding` of descriptor set 0, accessed as an array of `pointee`
+pub fn storage_buffer(pointee: &str, binding: u32) -> serde_json::Value {
+    return buffer(pointee, "StorageBuffer", binding);
+}
+
+/// Parameter bound to the buffer of `storage_class` at `binding` of descriptor set 0, accessed as an array of
+/// `pointee`
+pub fn buffer(pointee: &str, storage_class: &str, binding: u32) -> serde_json::Value {
+    return serde_json::json!({
+        "type": {
+            "size": "fat",
+            "storage_class": storage_class,
+            "pointee": pointee
+        },
+        "kind": {
+            "descriptor_set": {
+                "storage_class": storage_class,
+                "set": 0,
+                "binding": binding
+            }
+        }
+    });
+}
+
+/// Compute shader entry point with a single invocation per workgroup
+pub fn compute_shader() -> serde_json::Value {
+    return serde_json::json!({
+        "execution_model": "GLCompute",
+        "execution_modes": [{ "local_size": [1, 1, 1] }]
+    });
+}
+
+/// Configuration targeting Vulkan 1.1, without any functions
+pub fn vulkan() -> serde_json::Value {
+    return serde_json::json!({
+        "platform": { "vulkan": "1.1" },
+        "addressing_model": "logical",
+        "memory_model": "GLSL450",
+        "capabilities": { "dynamic": [] },
+        "extensions": [],
+        "functions": {}
+    });
+}
+
+/// Configuration targeting Vulkan 1.1, whose function `main` is a compute shader taking a storage buffer of
+/// `pointee` as its first parameter
+pub fn config(main: u32, pointee: &str) -> serde_json::Value {
+    let mut entry_point = compute_shader();
+    entry_point["params"] = serde_json::json!({ "0": storage_buffer(pointee, 0) });
+
+    let mut config = vulkan();
+    config["functions"][main.to_string()] = entry_point;
+    return config;
+}
+
+/// Builder of a configuration targeting Vulkan 1.1, without any functions
+pub fn vulkan_builder() -> color_eyre::Result<ConfigBuilder> {
+    return Ok(Config::builder(
+        TargetPlatform::VK_1_1,
+        CapabilityModel::dynamic(Vec::new()),
+        None::<&'static str>,
+        AddressingModel::Logical,
+        MemoryModel::GLSL450,
+    )?);
+}
+
+/// Configuration targeting Vulkan 1.1, without any functions
+pub fn vulkan_config() -> color_eyre::Result<Config> {
+    return Ok(vulkan_builder()?.build()?);
+}
+
+/// Configuration targeting SPIR-V 1.3 with the OpenCL memory model and `addressing_model`
+pub fn physical_config(
+    addressing_model: AddressingModel,
+    memory64: bool,
+) -> color_eyre::Result<Config> {
+    let mut config = Config::builder(
+        TargetPlatform::SPV_1_3,
+        CapabilityModel::dynamic(vec![Capability::Int64, Capability::Addresses]),
+        None::<&'static str>,
+        AddressingModel::Logical,
+        MemoryModel::OpenCL,
+    )?;
+
+    config.set_addressing_model(addressing_model)?;
+    config.set_features(WasmFeatures {
+        memory64,
+        ..Default::default()
+    });
+
+    return Ok(config.build()?);
+}
+
+/// Compiles `wat` with the JSON `config`, validating the result
+pub fn compile(config: serde_json::Value, wat: &str) -> color_eyre::Result<Compilation> {
+    let compilation = try_compile(config, wat)??;
+    #[cfg(feature = "spvt-validate")]
+    compilation.validate()?;
+    return Ok(compilation);
+}
+
+/// Compiles `wat` with the JSON `config`, returning the error of the compilation instead of failing
+pub fn try_compile(
+    config: serde_json::Value,
+    wat: &str,
+) -> color_eyre::Result<wasm2spirv::error::Result<Compilation>> {
+    return Ok(Compilation::new(
+        serde_json::from_value(config)?,
+        &wat::parse_str(wat)?,
+    ));
+}
@@ -0,0 +1,68 @@
+mod common;
+
+use std::collections::HashSet;
+use wasm2spirv::Compilation;
+
+const WAT: &str = r#"
+(module
+  (func $square (param f32) (result f32)
+    local.get 0
+    local.get 0
+    f32.mul)
+  (func (export "main") (param i32)
+    local.get 0
+    local.get 0
+    f32.load
+    call $square
+    f32.const 2
+    f32.add
+    f32.store)
+  (memory 1))
+"#;
+
+fn compile(sort_globals: bool) -> color_eyre::Result<Compilation> {
+    let mut config = common::config(1, "f32");
+    config["sort_globals"] = sort_globals.into();
+    return common::compile(config, WAT);
+}
+
+#[test]
+fn compact_ids() -> color_eyre::Result<()> {
+    // Identical inputs result in identical outputs
+    assert_eq!(compile(false)?.bytes()?, compile(false)?.bytes()?);
+
+    let compilation = compile(false)?;
+    let module = compilation.module()?;
+    let ids = module
+        .all_inst_iter()
+        .filter_map(|x| x.result_id)
+        .collect::<Vec<_>>();
+
+    // Ids are numbered in order of definition, without gaps
+    assert_eq!(ids, (1..=ids.len() as u32).collect::<Vec<_>>());
+    assert_eq!(module.header.as_ref().unwrap().bound, ids.len() as u32 + 1);
+
+    return Ok(());
+}
+
+#[test]
+fn sort_globals() -> color_eyre::Result<()> {
+    let compilation = compile(true)?;
+    let module = compilation.module()?;
+    assert_eq!(compilation.bytes()?, compile(true)?.bytes()?);
+
+    // Every global is declared after its dependencies
+    let mut declared = HashSet::new();
+    for instr in module.types_global_values.iter() {
+        for id in instr
+            .result_type
+            .into_iter()
+            .chain(instr.operands.iter().filter_map(|x| x.id_ref_any()))
+        {
+            assert!(declared.contains(&id), "{instr:?}");
+        }
+        declared.extend(instr.result_id);
+    }
+
+    return Ok(());
+}
@@ -0,0 +1,308 @@
+use rspirv::spirv::{Capability, ExecutionModel, MemoryModel, StorageClass};
+use wasm2spirv::{
+    config::{AddressingModel, CapabilityModel, Config, WasmFeatures},
+    fg::function::{ExecutionMode, ParameterKind},
+    r#type::ScalarType,
+    version::{TargetPlatform, Version},
+};
+
+#[test]
+fn valid() -> color_eyre::Result<()> {
+    let mut config = Config::for_platform(TargetPlatform::VK_1_1)?;
+    config
+        .function(0)
+        .set_entry_point(ExecutionModel::GLCompute)?
+        .add_exec_mode(ExecutionMode::LocalSize(1, 1, 1))?
+        .build();
+
+    let report = config.build()?.validate();
+    assert!(report.errors.is_empty(), "{report:?}");
+    assert!(report.warnings.is_empty(), "{report:?}");
+    return Ok(());
+}
+
+#[test]
+fn memory64_without_int64() -> color_eyre::Result<()> {
+    let mut config = Config::builder(
+        TargetPlatform::VK_1_1,
+        CapabilityModel::Static(Box::new([Capability::Shader])),
+        None::<&'static str>,
+        AddressingModel::Logical,
+        MemoryModel::GLSL450,
+    )?;
+    config.set_features(WasmFeatures {
+        memory64: true,
+        ..Default::default()
+    });
+
+    let report = config.build()?.validate();
+    assert!(!report.is_ok());
+    assert!(
+        report.errors.iter().any(|x| x.contains("Int64")),
+        "{report:?}"
+    );
+    return Ok(());
+}
+
+#[test]
+fn physical_storage_buffer_without_extension() -> color_eyre::Result<()> {
+    let mut config = Config::builder(
+        TargetPlatform::VK_1_1,
+        CapabilityModel::Static(Box::new([
+            Capability::Shader,
+            Capability::Int64,
+            Capability::PhysicalStorageBufferAddresses,
+        ])),
+        None::<&'static str>,
+        AddressingModel::PhysicalStorageBuffer,
+        MemoryModel::GLSL450,
+    )?;
+    config.set_features(WasmFeatures {
+        memory64: true,
+        ..Default::default()
+    });
+
+    let report = config.build()?.validate();
+    assert!(
+        report
+            .errors
+            .iter()
+            .any(|x| x.contains("SPV_KHR_physical_storage_buffer")),
+        "{report:?}"
+    );
+    assert!(
+        report
+            .warnings
+            .iter()
+            .any(|x| x.contains("VK_KHR_buffer_device_address")),
+        "{report:?}"
+    );
+    return Ok(());
+}
+
+#[test]
+fn kernel_descriptor_sets() -> color_eyre::Result<()> {
+    let mut config = Config::for_platform(TargetPlatform::CL_2_1)?;
+    config
+        .function(0)
+        .set_entry_point(ExecutionModel::Kernel)?
+        .param(0)
+        .set_type(ScalarType::I32)?
+        .set_kind(ParameterKind::DescriptorSet {
+            storage_class: StorageClass::StorageBuffer,
+            set: 0,
+            binding: 0,
+            access: Default::default(),
+            buffer: Default::default(),
+            layout: None,
+        })?
+        .build()
+        .build();
+
+    let report = config.build()?.validate();
+    assert!(
+        report
+            .errors
+            .iter()
+            .any(|x| x.contains("descriptor sets") && x.contains("parameter 0 of function 0")),
+        "{report:?}"
+    );
+    assert!(report.into_result().is_err());
+    return Ok(());
+}
+
+#[test]
+fn warnings() -> color_eyre::Result<()> {
+    let mut config = Config::builder(
+        TargetPlatform::VK_1_1,
+        CapabilityModel::dynamic(vec![Capability::Shader]),
+        ["SPV_KHR_storage_buffer_storage_class"],
+        AddressingModel::Logical,
+        MemoryModel::GLSL450,
+    )?;
+    config
+        .set_memory_binding(0, 0, 0)
+        .set_data_segments(wasm2spirv::config::DataSegmentMode::Buffer { set: 0, binding: 0 })
+        .function(0)
+        .set_entry_point(ExecutionModel::GLCompute)?
+        .build();
+
+    let report = config.build()?.validate();
+    assert!(report.is_ok(), "{report:?}");
+
+    let warnings = report.into_result()?;
+    assert!(
+        warnings.iter().any(|x| x.contains("local size")),
+        "{warnings:?}"
+    );
+    assert!(
+        warnings.iter().any(|x| x.contains("already part of")),
+        "{warnings:?}"
+    );
+    assert!(
+        warnings
+            .iter()
+            .any(|x| x.contains("memory 0") && x.contains("data segments")),
+        "{warnings:?}"
+    );
+    return Ok(());
+}
+
+#[test]
+fn entry_point_keeps_params() -> color_eyre::Result<()> {
+    let mut config = Config::vulkan_compute(Version::V1_1)?;
+    config
+        .function(0)
+        .param(0)
+        .set_type(ScalarType::I32)?
+        .set_kind(ParameterKind::Input(0))?
+        .build()
+        .build();
+
+    let config = config
+        .compute_entry_point(0, [8, 8, 1])?
+        .compute_entry_point(0, [64, 1, 1])?
+        .build()?;
+
+    let function = config.functions.get(&0).unwrap();
+    assert_eq!(function.execution_model, Some(ExecutionModel::GLCompute));
+    assert!(function.params.get(&0).is_some());
+    assert!(matches!(
+        function.execution_modes[..],
+        [ExecutionMode::LocalSize(64, 1, 1)]
+    ));
+    return Ok(());
+}
+
+#[test]
+fn execution_model_parameters() -> color_eyre::Result<()> {
+    let mut config = Config::for_platform(TargetPlatform::VK_1_1)?;
+    config
+        .function(0)
+        .set_entry_point(ExecutionModel::GLCompute)?
+        .add_exec_mode(ExecutionMode::LocalSize(1, 1, 1))?
+        .param(0)
+        .set_type(ScalarType::I32)?
+        .set_kind(ParameterKind::Input(0))?
+        .build()
+        .build();
+    config
+        .function(1)
+        .set_entry_point(ExecutionModel::Vertex)?
+        .param(0)
+        .set_type(ScalarType::F32)?
+        .set_kind(ParameterKind::Input(1))?
+        .build()
+        .param(1)
+        .set_type(ScalarType::F32)?
+        .set_kind(ParameterKind::Input(1))?
+        .build()
+        .param(2)
+        .set_type(ScalarType::F32)?
+        .set_kind(ParameterKind::Output(1))?
+        .build()
+        .build();
+
+    let report = config.build()?.validate();
+    assert!(
+        report
+            .errors
+            .iter()
+            .any(|x| x.contains("Parameter 0 of function 0") && x.contains("descriptor set")),
+        "{report:?}"
+    );
+    assert!(
+        report
+            .errors
+            .iter()
+            .any(|x| x.contains("Parameters 0 and 1 of function 1")),
+        "{report:?}"
+    );
+    assert_eq!(report.errors.len(), 2, "{report:?}");
+    return Ok(());
+}
+
+#[test]
+fn execution_model_modes() -> color_eyre::Result<()> {
+    let mut config = Config::for_platform(TargetPlatform::VK_1_1)?;
+    config
+        .function(0)
+        .set_entry_point(ExecutionModel::Fragment)?
+        .add_exec_mode(ExecutionMode::LocalSize(1, 1, 1))?
+        .build();
+
+    let report = config.build()?.validate();
+    assert!(
+        report
+            .errors
+            .iter()
+            .any(|x| x.contains("LocalSize") && x.contains("not Fragment")),
+        "{report:?}"
+    );
+    assert!(
+        report.errors.iter().any(|x| x.contains("OriginUpperLeft")),
+        "{report:?}"
+    );
+    return Ok(());
+}
+
+#[test]
+fn fragment_depth_modes() -> color_eyre::Result<()> {
+    for (modes, valid) in [
+        (
+            [
+                ExecutionMode::EarlyFragmentTests,
+                ExecutionMode::DepthGreater,
+            ],
+            true,
+        ),
+        (
+            [ExecutionMode::DepthLess, ExecutionMode::DepthUnchanged],
+            false,
+        ),
+    ] {
+        let mut config = Config::for_platform(TargetPlatform::VK_1_1)?;
+        let mut function = config
+            .function(0)
+            .set_entry_point(ExecutionModel::Fragment)?
+            .add_exec_mode(ExecutionMode::OriginUpperLeft)?;
+        for mode in modes {
+            function = function.add_exec_mode(mode)?;
+        }
+        function.build();
+
+        let report = config.build()?.validate();
+        assert_eq!(report.is_ok(), valid, "{report:?}");
+        if !valid {
+            assert!(
+                report.errors.iter().any(|x| x.contains("only declare one")),
+                "{report:?}"
+            );
+        }
+    }
+    return Ok(());
+}
+
+#[test]
+fn geometry_required_modes() -> color_eyre::Result<()> {
+    let mut config = Config::for_platform(TargetPlatform::VK_1_1)?;
+    config
+        .function(0)
+        .set_entry_point(ExecutionModel::Geometry)?
+        .add_exec_mode(ExecutionMode::InputPoints)?
+        .add_exec_mode(ExecutionMode::Quads)?
+        .build();
+
+    let report = config.build()?.validate();
+    for expected in ["OutputVertices", "output primitive", "Quads"] {
+        assert!(
+            report.errors.iter().any(|x| x.contains(expected)),
+            "{report:?}"
+        );
+    }
+    assert!(
+        !report.errors.iter().any(|x| x.contains("input primitive")),
+        "{report:?}"
+    );
+    return Ok(());
+}
@@ -0,0 +1,161 @@
+mod common;
+
+use wasm2spirv::{config::Config, fg::module::ModuleBuilder, Compilation};
+
+const WAT: &str = r#"
+(module
+  (import "spir_global" "gl_GlobalInvocationID" (func $id (param i32) (result i32)))
+  (memory 1)
+  (data (i32.const 16) "\2a\00\00\00\00\00\80\3f")
+  (func (export "main")
+    i32.const 0
+    i32.const 16
+    i32.load
+    i32.store
+    i32.const 4
+    i32.const 0
+    call $id
+    f32.load
+    f32.store
+    i32.const 8
+    i32.const 20
+    i32.load8_u offset=3
+    i32.store))
+"#;
+
+fn config(data_segments: serde_json::Value) -> color_eyre::Result<Config> {
+    let mut config = common::vulkan();
+    config["memories"] = serde_json::json!({ "0": { "set": 0, "binding": 0 } });
+    config["data_segments"] = data_segments;
+    config["functions"]["1"] = common::compute_shader();
+
+    return Ok(serde_json::from_value(config)?);
+}
+
+fn compile(data_segments: serde_json::Value) -> color_eyre::Result<Compilation> {
+    let compilation = Compilation::new(config(data_segments)?, &wat::parse_str(WAT)?)?;
+    #[cfg(feature = "spvt-validate")]
+    compilation.validate()?;
+    return Ok(compilation);
+}
+
+#[test]
+fn constant_data_segments() -> color_eyre::Result<()> {
+    let assembly = compile("constant".into())?.into_assembly()?;
+
+    // Loads from constant addresses are replaced by the segment's contents
+    assert!(assembly
+        .lines()
+        .any(|x| x.contains("OpConstant") && x.ends_with(" 42")));
+    assert!(assembly
+        .lines()
+        .any(|x| x.contains("OpConstant") && x.ends_with(" 63")));
+
+    // Loads from runtime addresses still read the memory
+    assert!(!assembly.contains("OpSelect"));
+    return Ok(());
+}
+
+#[test]
+fn buffer_data_segments() -> color_eyre::Result<()> {
+    let data_segments = serde_json::json!({ "buffer": { "set": 0, "binding": 1 } });
+    let assembly = compile(data_segments.clone())?.into_assembly()?;
+
+    assert!(assembly.contains("Binding 1"));
+    assert!(assembly.contains("OpSelect"));
+
+    // Runtime addresses are checked against both ends of the segment, since the stack and heap may be below it
+    assert!(assembly.contains("OpUGreaterThanEqual"));
+    assert!(assembly.contains("OpULessThanEqual"));
+
+    // The host initializes the buffer with the memory's contents, followed by the scratch slot
+    let bytes = wat::parse_str(WAT)?;
+    let module = ModuleBuilder::new(config(data_segments)?, &bytes)?;
+    let mut image = vec![0; 32];
+    image[16..24].copy_from_slice(&[0x2a, 0, 0, 0, 0, 0, 0x80, 0x3f]);
+    assert_eq!(module.data_image(), image);
+    return Ok(());
+}
+
+#[test]
+fn stores_into_data_segments() -> color_eyre::Result<()> {
+    let wat = WAT.replace(
+        "    i32.const 8\n    i32.const 20",
+        "    i32.const 0\n    call $id\n    i32.const 1\n    i32.store\n    i32.const 8\n    i32.const 20",
+    );
+    assert_ne!(wat, WAT);
+
+    // Stores to runtime addresses are written into both the memory and the buffer
+    let data_segments = serde_json::json!({ "buffer": { "set": 0, "binding": 1 } });
+    let compilation = Compilation::new(config(data_segments)?, &wat::parse_str(&wat)?)?;
+    let assembly = compilation.into_assembly()?;
+    assert_eq!(assembly.matches("OpStore").count(), 5);
+
+    // Constant segments can't be written to
+    let wat = WAT.replace(
+        "    i32.const 0\n    i32.const 16",
+        "    i32.const 16\n    i32.const 16",
+    );
+    assert_ne!(wat, WAT);
+    assert!(Compilation::new(config("constant".into())?, &wat::parse_str(&wat)?).is_err());
+    return Ok(());
+}
+
+#[test]
+fn ignored_data_segments() -> color_eyre::Result<()> {
+    let assembly = compile("ignore".into())?.into_assembly()?;
+    assert!(!assembly
+        .lines()
+        .any(|x| x.contains("OpConstant") && x.ends_with(" 42")));
+    return Ok(());
+}
+
+#[test]
+fn passive_data_segments() -> color_eyre::Result<()> {
+    let wat = r#"
+    (module
+      (memory 1)
+      (data $words "\00\00\00\00\07\00\00\00\09\00\00\00")
+      (func (export "main")
+        i32.const 8
+        i32.const 4
+        i32.const 8
+        memory.init $words
+        data.drop $words))
+    "#;
+
+    let mut config = config("ignore".into())?;
+    config.functions =
+        serde_json::from_value(serde_json::json!({ "0": common::compute_shader() }))?;
+
+    let compilation = Compilation::new(config.clone(), &wat::parse_str(wat)?)?;
+    #[cfg(feature = "spvt-validate")]
+    compilation.validate()?;
+
+    // The segment is copied out of a constant array, one byte at a time
+    let assembly = compilation.into_assembly()?;
+    assert!(assembly.contains("OpConstantComposite"));
+    assert!(assembly.contains("OpLoopMerge"));
+    assert!(assembly
+        .lines()
+        .any(|x| x.contains("OpConstant") && x.ends_with(" 7")));
+    assert!(assembly
+        .lines()
+        .any(|x| x.contains("OpConstant") && x.ends_with(" 9")));
+
+    // Unaligned and runtime ranges are copied too, trapping if they're out of bounds
+    for range in [
+        "i32.const 5\n        i32.const 6\n",
+        "i32.const 0\n        i32.load\n        i32.const 8\n",
+    ] {
+        let wat = wat.replace("i32.const 4\n        i32.const 8\n", range);
+        let compilation = Compilation::new(config.clone(), &wat::parse_str(wat)?)?;
+        #[cfg(feature = "spvt-validate")]
+        compilation.validate()?;
+    }
+
+    // Constant ranges are checked at compile time
+    let wat = wat.replace("i32.const 4\n", "i32.const 8\n");
+    assert!(Compilation::new(config, &wat::parse_str(wat)?).is_err());
+    return Ok(());
+}
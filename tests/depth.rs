@@ -0,0 +1,53 @@
+mod common;
+
+use rspirv::spirv::Op;
+use wasm2spirv::Compilation;
+
+/// Compiles a function storing a chain of `depth` additions into its storage buffer parameter
+fn compile(
+    depth: usize,
+    max_value_depth: Option<u32>,
+) -> color_eyre::Result<wasm2spirv::error::Result<Compilation>> {
+    let chain = "i32.const 1 i32.add\n    ".repeat(depth);
+    let wat = format!(
+        r#"(module
+  (func (export "main") (param i32)
+    local.get 0
+    local.get 0
+    i32.load
+    {chain}
+    i32.store)
+  (memory 1))"#
+    );
+
+    let mut config = common::config(0, "i32");
+    config["max_value_depth"] = max_value_depth.into();
+    return common::try_compile(config, &wat);
+}
+
+#[test]
+fn deep_chain() -> color_eyre::Result<()> {
+    let compilation = compile(1024, None)??;
+    #[cfg(feature = "spvt-validate")]
+    compilation.validate()?;
+
+    let adds = compilation
+        .module()?
+        .all_inst_iter()
+        .filter(|instr| instr.class.opcode == Op::IAdd)
+        .count();
+    assert_eq!(adds, 1024);
+    return Ok(());
+}
+
+#[test]
+fn max_value_depth() -> color_eyre::Result<()> {
+    let Err(error) = compile(64, Some(32))? else {
+        panic!("the chain is deeper than the maximum");
+    };
+    assert!(error.to_string().contains("maximum of 32"), "{error}");
+
+    // Shallower chains are unaffected
+    compile(8, Some(32))??;
+    return Ok(());
+}
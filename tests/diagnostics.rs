@@ -0,0 +1,92 @@
+use rspirv::{
+    dr::Builder,
+    spirv::{AddressingModel, Capability, MemoryModel, Op},
+};
+use wasm2spirv::{link::Linker, version::TargetPlatform, Compilation};
+
+#[test]
+fn saxpy_origins() -> color_eyre::Result<()> {
+    let _ = color_eyre::install();
+
+    let config = serde_json::from_str(include_str!("../examples/saxpy/saxpy.json"))?;
+    let wasm = wat::parse_bytes(include_bytes!("../examples/saxpy/saxpy.wat"))?;
+    let compilation = Compilation::new(config, &wasm)?;
+
+    let mul = compilation
+        .module()?
+        .all_inst_iter()
+        .find(|x| x.class.opcode == Op::FMul)
+        .and_then(|x| x.result_id)
+        .expect("saxpy contains a float multiplication");
+
+    // The multiplication is only anchored by the store it flows into
+    let origin = compilation.origin(mul).expect("origin is tracked");
+    assert_eq!(origin.function, 2);
+    assert!(origin
+        .operator
+        .as_deref()
+        .is_some_and(|x| x.starts_with("F32Store")));
+    assert_eq!(&*origin.operation, "Store");
+    return Ok(());
+}
+
+#[test]
+fn annotated_assembly() -> color_eyre::Result<()> {
+    let config = serde_json::from_str(include_str!("../examples/saxpy/saxpy.json"))?;
+    let wasm = wat::parse_bytes(include_bytes!("../examples/saxpy/saxpy.wat"))?;
+    let compilation = Compilation::new(config, &wasm)?;
+
+    let assembly = compilation.annotated_assembly()?;
+    assert!(assembly.contains("%main = OpFunction"), "{assembly}");
+    assert!(
+        assembly.contains("OpEntryPoint GLCompute %main"),
+        "{assembly}"
+    );
+    assert!(
+        assembly.contains("; function 2, operator F32Store"),
+        "{assembly}"
+    );
+
+    // Apart from the comments and names, the output is the plain disassembly
+    let plain = compilation.assembly()?;
+    let is_instruction = |x: &&str| !x.starts_with("; ");
+    assert_eq!(
+        assembly.lines().filter(is_instruction).count(),
+        plain.lines().filter(is_instruction).count()
+    );
+    return Ok(());
+}
+
+#[test]
+fn annotated_assembly_names() -> color_eyre::Result<()> {
+    let mut builder = Builder::new();
+    builder.set_version(1, 0);
+    builder.capability(Capability::Shader);
+    builder.capability(Capability::Linkage);
+    builder.memory_model(AddressingModel::Logical, MemoryModel::GLSL450);
+
+    let int = builder.type_int(32, 1);
+    let first = builder.constant_u32(int, 1);
+    let second = builder.constant_u32(int, 2);
+    builder.name(int, "int");
+    builder.name(first, "one");
+    builder.name(second, "one");
+
+    let mut linker = Linker::new(TargetPlatform::SPV_1_0);
+    linker.add_module(builder.module());
+    let compilation = linker.link()?;
+
+    let assembly = compilation.annotated_assembly()?;
+    assert!(assembly.contains("%int = OpTypeInt 32 1"), "{assembly}");
+    assert!(assembly.contains(r#"OpName %int "int""#), "{assembly}");
+    // Ambiguous names keep their id
+    assert!(
+        assembly.contains(&format!("%one_{first} = OpConstant")),
+        "{assembly}"
+    );
+    assert!(
+        assembly.contains(&format!("%one_{second} = OpConstant")),
+        "{assembly}"
+    );
+    return Ok(());
+}
@@ -0,0 +1,85 @@
+mod common;
+
+use rspirv::spirv::Op;
+
+/// Compiles a function storing `body` into its storage buffer parameter, returning the opcodes of the result
+fn compile(
+    body: &str,
+    division_traps: bool,
+) -> color_eyre::Result<wasm2spirv::error::Result<Vec<Op>>> {
+    let wat = format!(
+        r#"(module
+  (func (export "main") (param i32)
+    local.get 0
+    {body}
+    i32.store)
+  (memory 1))"#
+    );
+
+    let mut config = common::config(0, "i32");
+    config["division_traps"] = division_traps.into();
+    return Ok(common::try_compile(config, &wat)?.and_then(|compilation| {
+        Ok(compilation
+            .module()?
+            .all_inst_iter()
+            .map(|instr| instr.class.opcode)
+            .collect())
+    }));
+}
+
+fn count(ops: &[Op], op: Op) -> usize {
+    return ops.iter().filter(|x| **x == op).count();
+}
+
+const LOADED: &str = "local.get 0 i32.load local.get 0 i32.load offset=4";
+
+#[test]
+fn unguarded() -> color_eyre::Result<()> {
+    let ops = compile(&format!("{LOADED} i32.div_s"), false)??;
+    assert_eq!(count(&ops, Op::SDiv), 1);
+    assert_eq!(count(&ops, Op::Unreachable), 0);
+    return Ok(());
+}
+
+#[test]
+fn guarded() -> color_eyre::Result<()> {
+    for op in ["i32.div_s", "i32.div_u", "i32.rem_u"] {
+        let ops = compile(&format!("{LOADED} {op}"), true)??;
+        assert_eq!(count(&ops, Op::Unreachable), 1, "{op}");
+        assert_eq!(count(&ops, Op::BranchConditional), 1, "{op}");
+    }
+
+    // Signed remainders only trap on zero, and never overflow
+    let ops = compile(&format!("{LOADED} i32.rem_s"), true)??;
+    assert_eq!(count(&ops, Op::Unreachable), 1);
+    assert_eq!(count(&ops, Op::Select), 1);
+    return Ok(());
+}
+
+#[test]
+fn constant_divisor() -> color_eyre::Result<()> {
+    let ops = compile("local.get 0 i32.load i32.const 3 i32.div_s", true)??;
+    assert_eq!(count(&ops, Op::Unreachable), 0);
+
+    let ops = compile("local.get 0 i32.load i32.const 0 i32.div_u", true)??;
+    assert_eq!(count(&ops, Op::Unreachable), 1);
+    assert_eq!(count(&ops, Op::UDiv), 0);
+    return Ok(());
+}
+
+#[test]
+fn constant_overflow() -> color_eyre::Result<()> {
+    // Without division traps, the overflowing division is left to the platform
+    let overflow = "i32.const -2147483648 i32.const -1 i32.div_s";
+    let ops = compile(overflow, false)??;
+    assert_eq!(count(&ops, Op::SDiv), 1);
+    assert_eq!(count(&ops, Op::Unreachable), 0);
+
+    let ops = compile(overflow, true)??;
+    assert_eq!(count(&ops, Op::Unreachable), 1);
+
+    // The remainder is defined, and folded
+    let ops = compile("i32.const -2147483648 i32.const -1 i32.rem_s", false)??;
+    assert_eq!(count(&ops, Op::SRem), 0);
+    return Ok(());
+}
@@ -0,0 +1,77 @@
+mod common;
+
+use wasm2spirv::{
+    binary::{deserialize::BinaryDeserialize, serialize::BinarySerialize},
+    config::Config,
+    Compilation,
+};
+
+const WAT: &str = r#"
+(module
+  (func $compute (export "cs_main") (export "cs_alt"))
+  (func $helper (export "helper")))
+"#;
+
+fn config(json: serde_json::Value) -> color_eyre::Result<Config> {
+    let mut config = common::vulkan();
+    config["functions"]["0"] = common::compute_shader();
+    config["functions"]["1"] = common::compute_shader();
+
+    for (key, value) in json.as_object().into_iter().flatten() {
+        config[key] = value.clone();
+    }
+    return Ok(serde_json::from_value(config)?);
+}
+
+fn compile(config: Config) -> color_eyre::Result<String> {
+    let compilation = Compilation::new(config, &wat::parse_str(WAT)?)?;
+    #[cfg(feature = "spvt-validate")]
+    compilation.validate()?;
+    return Ok(compilation.assembly()?.to_string());
+}
+
+#[test]
+fn renamed_entry_points() -> color_eyre::Result<()> {
+    let assembly = compile(config(serde_json::json!({
+        "entry_point_names": { "cs_main": "main" }
+    }))?)?;
+
+    assert!(assembly.contains("\"main\""), "{assembly}");
+    assert!(assembly.contains("\"helper\""), "{assembly}");
+    assert!(!assembly.contains("\"cs_main\""), "{assembly}");
+    return Ok(());
+}
+
+#[test]
+fn internal_exports() -> color_eyre::Result<()> {
+    let config = config(serde_json::json!({
+        "internal_exports": ["cs_main", "helper"]
+    }))?;
+
+    let mut bytes = Vec::new();
+    config.serialize_into(&mut bytes)?;
+    let decoded = Config::deserialize_from(&mut &bytes[..])?;
+    assert_eq!(decoded.internal_exports, config.internal_exports);
+
+    // The function is still exposed through its other export
+    let assembly = compile(config)?;
+    assert_eq!(assembly.matches("OpEntryPoint").count(), 1, "{assembly}");
+    assert!(assembly.contains("\"cs_alt\""), "{assembly}");
+    assert!(!assembly.contains("\"helper\""), "{assembly}");
+    return Ok(());
+}
+
+#[test]
+fn renamed_internal_export() -> color_eyre::Result<()> {
+    let report = config(serde_json::json!({
+        "entry_point_names": { "helper": "main" },
+        "internal_exports": ["helper"]
+    }))?
+    .validate();
+
+    assert!(
+        report.warnings.iter().any(|x| x.contains("`helper`")),
+        "{report:?}"
+    );
+    return Ok(());
+}
@@ -0,0 +1,64 @@
+mod common;
+
+const WAT: &str = r#"
+(module
+  (func (export "main") (param i32)
+    local.get 0
+    i32.const 7
+    i32.store)
+  (memory 1))
+"#;
+
+/// Compiles a compute shader writing into a `StorageBuffer` parameter
+fn compile(
+    vulkan: &str,
+    capabilities: serde_json::Value,
+) -> color_eyre::Result<wasm2spirv::error::Result<String>> {
+    let mut config = common::config(0, "i32");
+    config["platform"] = serde_json::json!({ "vulkan": vulkan });
+    config["capabilities"] = capabilities;
+    return Ok(
+        common::try_compile(config, WAT)?.and_then(|compilation| compilation.into_assembly())
+    );
+}
+
+const STORAGE_BUFFER: &str = r#"OpExtension "SPV_KHR_storage_buffer_storage_class""#;
+
+#[test]
+fn dynamic() -> color_eyre::Result<()> {
+    let assembly = compile("1.0", serde_json::json!({ "dynamic": [] }))??;
+    assert!(assembly.contains(STORAGE_BUFFER), "{assembly}");
+
+    // The storage class is core since SPIR-V 1.3
+    let assembly = compile("1.1", serde_json::json!({ "dynamic": [] }))??;
+    assert!(!assembly.contains(STORAGE_BUFFER), "{assembly}");
+    return Ok(());
+}
+
+#[test]
+fn static_model() -> color_eyre::Result<()> {
+    let Err(error) = compile("1.0", serde_json::json!({ "static": ["Shader"] }))? else {
+        panic!("the extension isn't enabled");
+    };
+    assert!(
+        error
+            .to_string()
+            .contains("SPV_KHR_storage_buffer_storage_class"),
+        "{error}"
+    );
+    return Ok(());
+}
+
+#[test]
+fn capability_extensions() -> color_eyre::Result<()> {
+    const VARIABLE_POINTERS: &str = r#"OpExtension "SPV_KHR_variable_pointers""#;
+
+    let capabilities = serde_json::json!({ "dynamic": ["VariablePointers"] });
+    let assembly = compile("1.0", capabilities.clone())??;
+    assert!(assembly.contains(VARIABLE_POINTERS), "{assembly}");
+
+    // Variable pointers are core since SPIR-V 1.3
+    let assembly = compile("1.1", capabilities)??;
+    assert!(!assembly.contains(VARIABLE_POINTERS), "{assembly}");
+    return Ok(());
+}
@@ -0,0 +1,81 @@
+mod common;
+
+use rspirv::spirv::FPFastMathMode;
+use wasm2spirv::{config::Config, fg::function::FastMath, Compilation};
+
+const WAT: &str = r#"
+(module
+  (func $fma (param f32) (result f32)
+    local.get 0
+    f32.const 2
+    f32.mul
+    f32.const 1
+    f32.add)
+  (func (export "main") (param i32)
+    local.get 0
+    local.get 0
+    f32.load
+    call $fma
+    f32.store)
+  (memory 1))
+"#;
+
+fn config(fast_math: serde_json::Value) -> color_eyre::Result<Config> {
+    let mut config = common::config(1, "f32");
+    config["functions"]["0"] = serde_json::json!({ "fast_math": fast_math });
+
+    return Ok(serde_json::from_value(config)?);
+}
+
+#[test]
+fn no_contraction() -> color_eyre::Result<()> {
+    let config = config(serde_json::json!({ "no_contraction": true }))?;
+    assert!(config.validate().is_ok());
+
+    let compilation = Compilation::new(config, &wat::parse_str(WAT)?)?;
+    #[cfg(feature = "spvt-validate")]
+    compilation.validate()?;
+
+    let assembly = compilation.assembly()?;
+    let decorated = assembly
+        .lines()
+        .filter(|x| x.contains("NoContraction"))
+        .count();
+
+    // Multiplication and addition
+    assert_eq!(decorated, 2);
+    return Ok(());
+}
+
+#[test]
+fn contraction() -> color_eyre::Result<()> {
+    let compilation = Compilation::new(config(serde_json::json!({}))?, &wat::parse_str(WAT)?)?;
+    assert!(!compilation.assembly()?.contains("NoContraction"));
+    return Ok(());
+}
+
+#[test]
+fn shader_fast_math() -> color_eyre::Result<()> {
+    let config = config(serde_json::json!({ "not_nan": true, "fast": true }))?;
+    let report = config.validate();
+    assert!(
+        report.errors.iter().any(|x| x.contains("fast math modes")),
+        "{report:?}"
+    );
+    return Ok(());
+}
+
+#[test]
+fn mode() {
+    assert_eq!(FastMath::default().mode(), FPFastMathMode::NONE);
+
+    let fast_math = FastMath {
+        not_inf: true,
+        allow_recip: true,
+        ..Default::default()
+    };
+    assert_eq!(
+        fast_math.mode(),
+        FPFastMathMode::NOT_INF | FPFastMathMode::ALLOW_RECIP
+    );
+}
@@ -0,0 +1,82 @@
+mod common;
+
+use wasm2spirv::{config::Config, Compilation};
+
+fn config(tail_call: bool) -> color_eyre::Result<Config> {
+    let mut config = common::config(1, "i32");
+    config["features"] = serde_json::json!({
+        "memory64": false,
+        "saturating_float_to_int": false,
+        "tail_call": tail_call
+    });
+
+    return Ok(serde_json::from_value(config)?);
+}
+
+fn wasm(call: &str) -> color_eyre::Result<Vec<u8>> {
+    let wat = format!(
+        r#"(module
+  (func $double (param $x i32) (result i32)
+    local.get $x
+    i32.const 2
+    i32.mul)
+  (func (export "main") (param i32)
+    local.get 0
+    local.get 0
+    i32.load
+    call $f
+    i32.store)
+  (func $f (param $x i32) (result i32)
+    local.get $x
+    {call} $double)
+  (memory 1))"#
+    );
+
+    return Ok(wat::parse_str(wat)?);
+}
+
+#[test]
+fn disabled_proposal() -> color_eyre::Result<()> {
+    let Err(error) = Compilation::new(config(false)?, &wasm("return_call")?) else {
+        panic!("tail calls should be rejected when the proposal is disabled");
+    };
+
+    assert!(error.to_string().contains("`tail_call`"), "{error:?}");
+    return Ok(());
+}
+
+#[test]
+fn disabled_proposal_streaming() -> color_eyre::Result<()> {
+    let bytes = wasm("return_call")?;
+    let mut builder = Compilation::builder(config(false)?);
+    let result = bytes
+        .chunks(16)
+        .try_for_each(|x| builder.push_bytes(x).map(drop))
+        .and_then(|_| builder.finish().map(drop));
+
+    let Err(error) = result else {
+        panic!("tail calls should be rejected when the proposal is disabled");
+    };
+
+    assert!(error.to_string().contains("`tail_call`"), "{error:?}");
+    return Ok(());
+}
+
+#[test]
+fn required_features() -> color_eyre::Result<()> {
+    let compilation = Compilation::new(config(true)?, &wasm("return_call")?)?;
+    #[cfg(feature = "spvt-validate")]
+    compilation.validate()?;
+
+    let features = compilation.required_wasm_features();
+    assert_eq!(features.enabled().collect::<Vec<_>>(), ["tail_call"]);
+    return Ok(());
+}
+
+#[test]
+fn unused_features() -> color_eyre::Result<()> {
+    let compilation = Compilation::new(config(true)?, &wasm("call")?)?;
+    let features = compilation.required_wasm_features();
+    assert_eq!(features.enabled().count(), 0);
+    return Ok(());
+}
@@ -0,0 +1,114 @@
+use rspirv::spirv::{ExecutionModel, StorageClass};
+use std::rc::Rc;
+use wasm2spirv::{
+    config::Config,
+    decorator::VariableDecorator,
+    fg::{
+        function::{ExecutionMode, FunctionBuilder},
+        module::ModuleBuilder,
+        values::{
+            integer::{Integer, IntegerKind, IntegerSource},
+            pointer::Pointer,
+        },
+        Operation,
+    },
+    r#type::{PointerSize, ScalarType},
+    Compilation,
+};
+
+fn empty_module() -> color_eyre::Result<ModuleBuilder<'static>> {
+    let config: Config = serde_json::from_value(serde_json::json!({
+        "platform": { "vulkan": "1.1" },
+        "addressing_model": "logical",
+        "memory_model": "GLSL450",
+        "capabilities": { "dynamic": [] },
+        "extensions": []
+    }))?;
+    return Ok(ModuleBuilder::empty(config)?);
+}
+
+fn output_buffer() -> Rc<Pointer> {
+    return Rc::new(Pointer::new_variable(
+        PointerSize::Skinny,
+        StorageClass::StorageBuffer,
+        ScalarType::I32,
+        None,
+        [
+            VariableDecorator::DesctiptorSet(0),
+            VariableDecorator::Binding(0),
+        ],
+    ));
+}
+
+#[test]
+fn custom_frontend() -> color_eyre::Result<()> {
+    let mut module = empty_module()?;
+    let output = output_buffer();
+
+    let mut answer = FunctionBuilder::with_signature([], Some(ScalarType::I32.into()));
+    answer.push(Operation::Return {
+        value: Some(Integer::new_constant_u32(42).into()),
+    });
+    let answer = module.add_function(answer);
+
+    let mut main = FunctionBuilder::with_signature([], None);
+    main.set_entry_point(
+        "main",
+        ExecutionModel::GLCompute,
+        [ExecutionMode::LocalSize(1, 1, 1)],
+    )
+    .push(Operation::Store {
+        target: output,
+        value: Integer::new(IntegerSource::FunctionCall {
+            function_id: answer,
+            args: Box::default(),
+            kind: IntegerKind::Short,
+        })
+        .into(),
+        log2_alignment: None,
+    })
+    .push(Operation::Return { value: None });
+    module.add_function(main);
+
+    let compilation = Compilation::from_builder(module)?;
+    #[cfg(feature = "spvt-validate")]
+    compilation.validate()?;
+
+    let assembly = compilation.into_assembly()?;
+    assert!(assembly.contains("OpEntryPoint GLCompute"));
+    assert!(assembly.contains("OpFunctionCall"));
+    return Ok(());
+}
+
+#[test]
+fn labels() -> color_eyre::Result<()> {
+    let mut module = empty_module()?;
+
+    let mut main = FunctionBuilder::with_signature([], None);
+    let next = main.new_label();
+    assert_ne!(next, main.new_label());
+
+    main.set_entry_point(
+        "main",
+        ExecutionModel::GLCompute,
+        [ExecutionMode::LocalSize(1, 1, 1)],
+    )
+    .push(Operation::Branch { label: next })
+    .push(Operation::Label(next))
+    .push(Operation::Store {
+        target: output_buffer(),
+        value: Integer::new_constant_u32(42).into(),
+        log2_alignment: None,
+    })
+    .push(Operation::Return { value: None });
+    assert_eq!(main.labels.len(), 2);
+    module.add_function(main);
+
+    let compilation = Compilation::from_builder(module)?;
+    #[cfg(feature = "spvt-validate")]
+    compilation.validate()?;
+
+    let assembly = compilation.into_assembly()?;
+    assert!(assembly.contains("OpBranch"), "{assembly}");
+    return Ok(());
+}
@@ -0,0 +1,23 @@
+mod common;
+
+use wasm2spirv::Compilation;
+
+#[test]
+fn copysign_without_opencl() -> color_eyre::Result<()> {
+    let wasm = wat::parse_str(
+        r#"
+        (module
+          (func (param f32 f32) (result f32)
+            local.get 0
+            local.get 1
+            f32.copysign))
+        "#,
+    )?;
+
+    let compile = Compilation::new(common::vulkan_config()?, &wasm)?;
+    let assembly = compile.assembly()?;
+    assert!(!assembly.contains(" OpExtInst "));
+    assert!(assembly.contains("OpBitcast"));
+    assert!(assembly.contains("OpBitwiseOr"));
+    return Ok(());
+}
@@ -0,0 +1,89 @@
+mod common;
+
+use rspirv::{
+    dr::Operand,
+    spirv::{Capability, Op},
+};
+use wasm2spirv::{config::Float64Mode, Compilation};
+
+/// Compiles a function storing `body` into its storage buffer parameter
+fn compile(
+    body: &str,
+    float64: Float64Mode,
+) -> color_eyre::Result<wasm2spirv::error::Result<Compilation>> {
+    let wat = format!(
+        r#"(module
+  (func (export "main") (param i32)
+    local.get 0
+    {body}
+    f32.store)
+  (memory 1))"#
+    );
+
+    let mut config = common::config(0, "f32");
+    config["float64"] = serde_json::to_value(float64)?;
+    return common::try_compile(config, &wat);
+}
+
+const SCALED: &str = "local.get 0 f32.load f64.promote_f32 f64.const 0.5 f64.mul f32.demote_f64";
+
+fn float_widths(compilation: &Compilation) -> color_eyre::Result<Vec<u32>> {
+    return Ok(compilation
+        .module()?
+        .types_global_values
+        .iter()
+        .filter(|instr| instr.class.opcode == Op::TypeFloat)
+        .filter_map(|instr| match instr.operands.first() {
+            Some(Operand::LiteralInt32(width)) => Some(*width),
+            _ => None,
+        })
+        .collect());
+}
+
+#[test]
+fn native() -> color_eyre::Result<()> {
+    let compilation = compile(SCALED, Float64Mode::Native)??;
+    assert!(float_widths(&compilation)?.contains(&64));
+    assert!(compilation
+        .module()?
+        .capabilities
+        .iter()
+        .any(|x| x.operands.first() == Some(&Operand::Capability(Capability::Float64))));
+    return Ok(());
+}
+
+#[test]
+fn error() -> color_eyre::Result<()> {
+    let Err(error) = compile(SCALED, Float64Mode::Error)? else {
+        panic!("the function uses 64-bit floats");
+    };
+    assert!(error.to_string().contains("functions 0"), "{error}");
+
+    // Modules without 64-bit floats are unaffected
+    compile("local.get 0 f32.load", Float64Mode::Error)??;
+    return Ok(());
+}
+
+#[test]
+fn demote() -> color_eyre::Result<()> {
+    let compilation = compile(SCALED, Float64Mode::Demote)??;
+    #[cfg(feature = "spvt-validate")]
+    compilation.validate()?;
+
+    assert_eq!(float_widths(&compilation)?, [32]);
+    assert!(!compilation
+        .module()?
+        .all_inst_iter()
+        .any(|instr| instr.class.opcode == Op::FConvert));
+    return Ok(());
+}
+
+#[test]
+fn demoted_bitcast() -> color_eyre::Result<()> {
+    let body = "local.get 0 f32.load f64.promote_f32 i64.reinterpret_f64 f64.reinterpret_i64 f32.demote_f64";
+    let Err(error) = compile(body, Float64Mode::Demote)? else {
+        panic!("demoted floats can't be reinterpreted");
+    };
+    assert!(error.to_string().contains("reinterpreted"), "{error}");
+    return Ok(());
+}
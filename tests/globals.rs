@@ -0,0 +1,108 @@
+mod common;
+
+use wasm2spirv::Compilation;
+
+const WAT: &str = r#"
+(module
+  (global $scale (export "scale") f32 (f32.const 1))
+  (global $bias (export "bias") f32 (f32.const 0))
+  (global $offset i32 (i32.const 0))
+  (func (export "main") (param i32)
+    local.get 0
+    local.get 0
+    f32.load
+    global.get $scale
+    f32.mul
+    global.get $bias
+    f32.add
+    f32.store)
+  (memory 1))
+"#;
+
+fn compile(wat: &str, globals: serde_json::Value) -> color_eyre::Result<Compilation> {
+    let mut config = common::config(0, "f32");
+    config["globals"] = globals;
+    return common::compile(config, wat);
+}
+
+#[test]
+fn push_constant_globals() -> color_eyre::Result<()> {
+    let globals = serde_json::json!({ "0": "push_constant", "1": "push_constant" });
+    let assembly = compile(WAT, globals)?.into_assembly()?;
+
+    let variables = assembly
+        .lines()
+        .filter(|x| x.contains("OpVariable") && x.ends_with(" PushConstant"))
+        .count();
+    assert_eq!(variables, 1);
+    assert!(assembly.contains("\"scale\""));
+    assert!(assembly.contains("\"bias\""));
+    assert!(assembly
+        .lines()
+        .any(|x| x.starts_with("OpMemberDecorate") && x.ends_with(" 1 Offset 4")));
+    return Ok(());
+}
+
+#[test]
+fn descriptor_set_globals() -> color_eyre::Result<()> {
+    let globals = serde_json::json!({
+        "0": { "descriptor_set": { "set": 1, "binding": 0, "buffer": "uniform" } },
+        "1": { "descriptor_set": { "set": 1, "binding": 1 } }
+    });
+    let assembly = compile(WAT, globals)?.into_assembly()?;
+
+    assert!(assembly
+        .lines()
+        .any(|x| x.contains("OpVariable") && x.ends_with(" Uniform")));
+    assert!(assembly
+        .lines()
+        .any(|x| x.contains("OpVariable") && x.ends_with(" StorageBuffer")));
+    assert!(assembly
+        .lines()
+        .any(|x| x.starts_with("OpDecorate") && x.ends_with(" DescriptorSet 1")));
+    return Ok(());
+}
+
+#[test]
+fn mutable_push_constant() -> color_eyre::Result<()> {
+    let wat = WAT.replace(
+        "$bias (export \"bias\") f32",
+        "$bias (export \"bias\") (mut f32)",
+    );
+    let globals = serde_json::json!({ "1": "push_constant" });
+    assert!(compile(&wat, globals).is_err());
+    return Ok(());
+}
+
+#[test]
+fn unwritten_mutable_global() -> color_eyre::Result<()> {
+    let wat = WAT.replace(
+        "(global $scale (export \"scale\") f32",
+        "(global $scale (mut f32)",
+    );
+    let assembly = compile(&wat, serde_json::json!({}))?.into_assembly()?;
+
+    assert!(!assembly
+        .lines()
+        .any(|x| x.contains("OpVariable") && x.ends_with(" Private")));
+    return Ok(());
+}
+
+#[test]
+fn written_mutable_global() -> color_eyre::Result<()> {
+    let wat = WAT
+        .replace(
+            "(global $scale (export \"scale\") f32",
+            "(global $scale (mut f32)",
+        )
+        .replace(
+            "f32.store)",
+            "f32.store\n    f32.const 2\n    global.set $scale)",
+        );
+    let assembly = compile(&wat, serde_json::json!({}))?.into_assembly()?;
+
+    assert!(assembly
+        .lines()
+        .any(|x| x.contains("OpVariable") && x.ends_with(" Private")));
+    return Ok(());
+}
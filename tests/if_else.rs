@@ -0,0 +1,110 @@
+mod common;
+
+use wasm2spirv::Compilation;
+
+fn compile(body: &str) -> color_eyre::Result<Compilation> {
+    let wat = format!(
+        r#"(module
+  (type $pair (func (param i32 i32) (result i32)))
+  (func (export "main") (param i32)
+    (local $i i32)
+    i32.const 0
+    local.set $i
+    {body})
+  (memory 1))"#
+    );
+
+    return common::compile(common::config(0, "i32"), &wat);
+}
+
+#[test]
+fn if_else_result() -> color_eyre::Result<()> {
+    let compilation = compile(
+        r#"
+    local.get 0
+    local.get $i
+    i32.eqz
+    if (result i32)
+      i32.const 1
+    else
+      i32.const 2
+    end
+    i32.store"#,
+    )?;
+
+    let assembly = compilation.assembly()?;
+    assert!(assembly.contains("OpSelectionMerge"), "{assembly}");
+    return Ok(());
+}
+
+#[test]
+fn if_without_else() -> color_eyre::Result<()> {
+    compile(
+        r#"
+    local.get $i
+    if
+      local.get 0
+      i32.const 3
+      i32.store
+    end"#,
+    )?;
+    return Ok(());
+}
+
+#[test]
+fn nested_if() -> color_eyre::Result<()> {
+    compile(
+        r#"
+    local.get 0
+    local.get $i
+    if (result i32)
+      local.get $i
+      i32.const 4
+      i32.gt_u
+      if (result i32)
+        i32.const 1
+      else
+        i32.const 2
+      end
+    else
+      i32.const 3
+    end
+    i32.store"#,
+    )?;
+    return Ok(());
+}
+
+#[test]
+fn block_params() -> color_eyre::Result<()> {
+    compile(
+        r#"
+    local.get 0
+    i32.const 6
+    i32.const 5
+    local.get $i
+    if (type $pair)
+      i32.add
+    else
+      i32.sub
+    end
+    i32.store"#,
+    )?;
+    return Ok(());
+}
+
+#[test]
+fn branch_with_result() -> color_eyre::Result<()> {
+    compile(
+        r#"
+    local.get 0
+    block (result i32)
+      i32.const 7
+      local.get $i
+      br_if 0
+      drop
+      i32.const 8
+    end
+    i32.store"#,
+    )?;
+    return Ok(());
+}
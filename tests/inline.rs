@@ -0,0 +1,56 @@
+mod common;
+
+use rspirv::{dr::Module, spirv::Op};
+use wasm2spirv::Compilation;
+
+const WAT: &str = r#"
+(module
+  (func $add_one (param i32) (result i32)
+    (local i32)
+    local.get 0
+    i32.const 1
+    i32.add
+    local.set 1
+    local.get 1)
+  (func $fail (param i32) (result i32)
+    unreachable)
+  (func (export "main") (param i32)
+    local.get 0
+    local.get 0
+    i32.load
+    call $add_one
+    call $add_one
+    call $fail
+    i32.store)
+  (memory 1))
+"#;
+
+fn calls(module: &Module) -> usize {
+    let entry_point = module.entry_points[0].operands[1].unwrap_id_ref();
+    return module
+        .functions
+        .iter()
+        .filter(|x| x.def.as_ref().and_then(|x| x.result_id) == Some(entry_point))
+        .flat_map(|x| x.blocks.iter())
+        .flat_map(|x| x.instructions.iter())
+        .filter(|x| x.class.opcode == Op::FunctionCall)
+        .count();
+}
+
+fn compile(inline_threshold: Option<u32>) -> color_eyre::Result<Compilation> {
+    let mut config = common::config(2, "i32");
+    config["inline_threshold"] = inline_threshold.into();
+    return common::compile(config, WAT);
+}
+
+#[test]
+fn inline_small_functions() -> color_eyre::Result<()> {
+    assert_eq!(calls(compile(None)?.module()?), 3);
+
+    // `fail` doesn't return, so it's still called
+    assert_eq!(calls(compile(Some(10))?.module()?), 1);
+
+    // `add_one` has too many operators
+    assert_eq!(calls(compile(Some(2))?.module()?), 3);
+    return Ok(());
+}
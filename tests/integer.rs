@@ -0,0 +1,127 @@
+mod common;
+
+use rspirv::spirv::MemoryModel;
+use wasm2spirv::{
+    config::{AddressingModel, CapabilityModel, Config},
+    version::TargetPlatform,
+    Compilation,
+};
+
+#[test]
+fn dynamic_shift_is_masked() -> color_eyre::Result<()> {
+    let wasm = wat::parse_str(
+        r#"
+        (module
+          (func (param i32 i32) (result i32)
+            local.get 0
+            local.get 1
+            i32.shl))
+        "#,
+    )?;
+
+    let compile = Compilation::new(common::vulkan_config()?, &wasm)?;
+    let assembly = compile.assembly()?;
+    assert!(assembly.contains("OpBitwiseAnd"));
+    assert!(assembly.contains("OpShiftLeftLogical"));
+    return Ok(());
+}
+
+#[test]
+fn constant_shift_is_masked() -> color_eyre::Result<()> {
+    let wasm = wat::parse_str(
+        r#"
+        (module
+          (func (param i64) (result i64)
+            local.get 0
+            i64.const 65
+            i64.shr_u))
+        "#,
+    )?;
+
+    let compile = Compilation::new(common::vulkan_config()?, &wasm)?;
+    let assembly = compile.assembly()?;
+    assert!(!assembly.contains("OpBitwiseAnd"));
+    assert!(assembly
+        .lines()
+        .any(|x| x.contains("OpConstant") && x.trim_end().ends_with(" 1")));
+    assert!(!assembly
+        .lines()
+        .any(|x| x.contains("OpConstant") && x.trim_end().ends_with(" 65")));
+    return Ok(());
+}
+
+#[test]
+fn divisor_is_not_masked() -> color_eyre::Result<()> {
+    let wasm = wat::parse_str(
+        r#"
+        (module
+          (func (param i32) (result i32)
+            local.get 0
+            i32.const 100
+            i32.div_u))
+        "#,
+    )?;
+
+    let compile = Compilation::new(common::vulkan_config()?, &wasm)?;
+    let assembly = compile.assembly()?;
+    assert!(assembly.contains("OpUDiv"));
+    assert!(assembly
+        .lines()
+        .any(|x| x.contains("OpConstant") && x.trim_end().ends_with(" 100")));
+    return Ok(());
+}
+
+#[test]
+fn rotate_without_opencl() -> color_eyre::Result<()> {
+    let wasm = wat::parse_str(
+        r#"
+        (module
+          (func (param i32 i32) (result i32)
+            local.get 0
+            local.get 1
+            i32.rotl)
+          (func (param i64 i64) (result i64)
+            local.get 0
+            local.get 1
+            i64.rotr))
+        "#,
+    )?;
+
+    let compile = Compilation::new(common::vulkan_config()?, &wasm)?;
+    let assembly = compile.assembly()?;
+    assert!(!assembly.contains(" OpExtInst "));
+    assert!(assembly.contains("OpShiftLeftLogical"));
+    assert!(assembly.contains("OpShiftRightLogical"));
+    assert!(assembly.contains("OpBitwiseOr"));
+    return Ok(());
+}
+
+#[test]
+fn missing_capability_is_explained() -> color_eyre::Result<()> {
+    let config = Config::builder(
+        TargetPlatform::VK_1_1,
+        CapabilityModel::Static(Box::new([rspirv::spirv::Capability::Shader])),
+        None::<&'static str>,
+        AddressingModel::Logical,
+        MemoryModel::GLSL450,
+    )?
+    .build()?;
+
+    let wasm = wat::parse_str(
+        r#"
+        (module
+          (func (param i64) (result i64)
+            local.get 0))
+        "#,
+    )?;
+
+    let err = match Compilation::new(config, &wasm) {
+        Ok(_) => panic!("Int64 shouldn't be available"),
+        Err(e) => e.to_string(),
+    };
+
+    assert!(err.contains("Int64"));
+    assert!(err.contains("OpTypeInt 64"));
+    assert!(err.contains("parameter 0"));
+    return Ok(());
+}
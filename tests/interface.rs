@@ -0,0 +1,100 @@
+use rspirv::{
+    dr::{Module, Operand},
+    spirv::{Op, StorageClass},
+};
+use wasm2spirv::Compilation;
+
+const WAT: &str = r#"
+(module
+  (import "spir_global" "gl_GlobalInvocationID" (func $id (param i32) (result i32)))
+  (func $index (result i32)
+    i32.const 0
+    call $id
+    i32.const 2
+    i32.shl)
+  (func (export "main") (param i32)
+    local.get 0
+    call $index
+    i32.add
+    i32.const 1
+    i32.store)
+  (memory 1))
+"#;
+
+fn compile(vulkan: &str) -> color_eyre::Result<Compilation> {
+    let storage_class = match vulkan {
+        "1.0" => "Uniform",
+        _ => "StorageBuffer",
+    };
+
+    let config = serde_json::json!({
+        "platform": { "vulkan": vulkan },
+        "addressing_model": "logical",
+        "memory_model": "GLSL450",
+        "capabilities": { "dynamic": [] },
+        "extensions": [],
+        "functions": {
+            "2": {
+                "execution_model": "GLCompute",
+                "execution_modes": [{ "local_size": [1, 1, 1] }],
+                "params": {
+                    "0": {
+                        "type": {
+                            "size": "fat",
+                            "storage_class": storage_class,
+                            "pointee": "i32"
+                        },
+                        "kind": {
+                            "descriptor_set": {
+                                "storage_class": storage_class,
+                                "set": 0,
+                                "binding": 0
+                            }
+                        }
+                    }
+                }
+            }
+        }
+    });
+
+    let compilation = Compilation::new(serde_json::from_value(config)?, &wat::parse_str(WAT)?)?;
+    #[cfg(feature = "spvt-validate")]
+    compilation.validate()?;
+    return Ok(compilation);
+}
+
+fn interface(module: &Module) -> Vec<StorageClass> {
+    return module.entry_points[0].operands[3..]
+        .iter()
+        .map(|x| {
+            let id = x.unwrap_id_ref();
+            let variable = module
+                .types_global_values
+                .iter()
+                .find(|x| x.result_id == Some(id) && x.class.opcode == Op::Variable)
+                .unwrap();
+            match variable.operands[0] {
+                Operand::StorageClass(storage_class) => storage_class,
+                _ => unreachable!(),
+            }
+        })
+        .collect();
+}
+
+#[test]
+fn entry_point_interface() -> color_eyre::Result<()> {
+    // Only inputs and outputs before SPIR-V 1.4, even if they're used by a callee
+    let compilation = compile("1.1")?;
+    assert_eq!(interface(compilation.module()?), [StorageClass::Input]);
+
+    // Every referenced global variable since SPIR-V 1.4
+    let compilation = compile("1.2")?;
+    let mut interface = interface(compilation.module()?);
+    interface.sort_by_key(|x| *x as u32);
+    assert_eq!(
+        interface,
+        [StorageClass::Input, StorageClass::StorageBuffer]
+    );
+
+    return Ok(());
+}
@@ -0,0 +1,244 @@
+mod common;
+
+use wasm2spirv::Compilation;
+
+fn compile(wat: &str) -> color_eyre::Result<wasm2spirv::error::Result<Compilation>> {
+    let mut config = common::config(2, "f32");
+    config["functions"]["2"]["params"]["1"] = common::storage_buffer("f32", 1);
+    return common::try_compile(config, wat);
+}
+
+#[test]
+fn imported() -> color_eyre::Result<()> {
+    let wat = r#"(module
+  (import "env" "memcpy" (func $memcpy (param i32 i32 i32) (result i32)))
+  (import "env" "memset" (func $memset (param i32 i32 i32) (result i32)))
+  (func (export "main") (param i32 i32)
+    local.get 1
+    local.get 0
+    i32.const 16
+    call $memcpy
+    drop
+    local.get 0
+    i32.const 0
+    i32.const 16
+    call $memset
+    drop)
+  (memory 1))"#;
+
+    let assembly = compile(wat)??.into_assembly()?;
+    assert!(assembly.contains("OpCopyMemory"), "{assembly}");
+    assert_eq!(assembly.matches("OpLoopMerge").count(), 2, "{assembly}");
+    return Ok(());
+}
+
+#[test]
+fn defined() -> color_eyre::Result<()> {
+    // Byte by byte, like compiler_builtins' fallback
+    let wat = r#"(module
+  (func $memset (export "memset") (param i32 i32 i32) (result i32)
+    (local i32)
+    block
+      loop
+        local.get 3
+        local.get 2
+        i32.ge_u
+        br_if 1
+        local.get 0
+        local.get 3
+        i32.add
+        local.get 1
+        i32.store8
+        local.get 3
+        i32.const 1
+        i32.add
+        local.set 3
+        br 0
+      end
+    end
+    local.get 0)
+  (func (param i32 i32) (result i32)
+    local.get 0)
+  (func (export "main") (param i32 i32)
+    local.get 1
+    i32.const 0
+    i32.const 8
+    call $memset
+    drop)
+  (memory 1))"#;
+
+    let assembly = compile(wat)??.into_assembly()?;
+    assert_eq!(assembly.matches("OpLoopMerge").count(), 1, "{assembly}");
+    return Ok(());
+}
+
+#[test]
+fn wrong_signature() -> color_eyre::Result<()> {
+    let wat = r#"(module
+  (import "env" "memcpy" (func $memcpy (param i32 i32)))
+  (func)
+  (func (export "main") (param i32 i32))
+  (memory 1))"#;
+
+    let Err(error) = compile(wat)? else {
+        panic!("memcpy takes three parameters");
+    };
+    assert!(error.to_string().contains("`memcpy` must take"), "{error}");
+    return Ok(());
+}
+
+#[test]
+fn unaligned_length() -> color_eyre::Result<()> {
+    let wat = r#"(module
+  (import "env" "memset" (func $memset (param i32 i32 i32) (result i32)))
+  (func)
+  (func (export "main") (param i32 i32)
+    local.get 0
+    i32.const 0
+    i32.const 6
+    call $memset
+    drop)
+  (memory 1))"#;
+
+    let error = match compile(wat)? {
+        Ok(compilation) => compilation.into_assembly().unwrap_err(),
+        Err(error) => error,
+    };
+    assert!(error.to_string().contains("multiple"), "{error}");
+    return Ok(());
+}
+
+#[test]
+fn runtime_length() -> color_eyre::Result<()> {
+    let wat = r#"(module
+  (import "env" "memset" (func $memset (param i32 i32 i32) (result i32)))
+  (func)
+  (func (export "main") (param i32 i32)
+    local.get 0
+    i32.const 0
+    local.get 1
+    f32.load
+    i32.trunc_f32_u
+    call $memset
+    drop)
+  (memory 1))"#;
+
+    // Lengths that aren't a multiple of the element size trap
+    let assembly = compile(wat)??.into_assembly()?;
+    assert!(assembly.contains("OpUMod"), "{assembly}");
+    assert!(assembly.contains("OpUnreachable"), "{assembly}");
+    assert_eq!(assembly.matches("OpLoopMerge").count(), 1, "{assembly}");
+    return Ok(());
+}
+
+#[test]
+fn overlapping_move() -> color_eyre::Result<()> {
+    let wat = |offset: &str| {
+        format!(
+            r#"(module
+  (import "env" "memmove" (func $memmove (param i32 i32 i32) (result i32)))
+  (func (export "main") (param i32)
+    local.get 0
+    {offset}
+    i32.add
+    local.get 0
+    i32.const 16
+    call $memmove
+    drop)
+  (memory 1))"#
+        )
+    };
+
+    // The direction of the copy is chosen at runtime
+    let compilation = common::compile(common::config(1, "i32"), &wat("local.get 0 i32.load"))?;
+    let assembly = compilation.into_assembly()?;
+    assert!(assembly.contains("OpUGreaterThan"), "{assembly}");
+    assert!(assembly.contains("OpSelect"), "{assembly}");
+
+    // Moves into higher constant offsets always copy backwards
+    let compilation = common::compile(common::config(1, "i32"), &wat("i32.const 4"))?;
+    let assembly = compilation.into_assembly()?;
+    assert!(!assembly.contains("OpUGreaterThan"), "{assembly}");
+    assert!(assembly.contains("OpISub"), "{assembly}");
+    return Ok(());
+}
+
+#[test]
+fn math() -> color_eyre::Result<()> {
+    let wat = r#"(module
+  (import "env" "__mulsf3" (func $mul (param f32 f32) (result f32)))
+  (import "env" "sinf" (func $sin (param f32) (result f32)))
+  (func (export "main") (param i32 i32)
+    local.get 0
+    local.get 1
+    f32.load
+    local.get 1
+    f32.load
+    call $mul
+    call $sin
+    f32.store)
+  (memory 1))"#;
+
+    let assembly = compile(wat)??.into_assembly()?;
+    assert!(assembly.contains("OpFMul"), "{assembly}");
+    assert!(assembly.contains("Sin"), "{assembly}");
+    assert!(!assembly.contains("OpFunctionCall"), "{assembly}");
+    return Ok(());
+}
+
+#[test]
+fn unsupported_math() -> color_eyre::Result<()> {
+    // GLSL's transcendental functions only take 32-bit floats
+    let wat = r#"(module
+  (import "env" "sin" (func $sin (param f64) (result f64)))
+  (func)
+  (func (export "main") (param i32 i32))
+  (memory 1))"#;
+
+    let Err(error) = compile(wat)? else {
+        panic!("Vulkan has no 64-bit sine");
+    };
+    assert!(error.to_string().contains("Unresolved import"), "{error}");
+    return Ok(());
+}
+
+#[test]
+fn defined_math_without_extended_set() -> color_eyre::Result<()> {
+    // Plain SPIR-V has no extended instruction set to lower `sinf` to, so the module's own is called
+    let wat = r#"(module
+  (func $sinf (export "sinf") (param f32) (result f32)
+    local.get 0)
+  (func)
+  (func (export "main") (param i32 i32)
+    local.get 0
+    local.get 0
+    f32.load
+    call $sinf
+    f32.store)
+  (memory 1))"#;
+
+    let mut config = common::config(2, "f32");
+    config["platform"] = serde_json::json!({ "universal": "1.3" });
+    config["functions"]["2"]["params"]["1"] = common::storage_buffer("f32", 1);
+
+    let assembly = common::try_compile(config, wat)??.into_assembly()?;
+    assert!(assembly.contains("OpFunctionCall"), "{assembly}");
+    assert!(!assembly.contains("OpExtInst "), "{assembly}");
+    return Ok(());
+}
+
+#[test]
+fn math_name_clash() -> color_eyre::Result<()> {
+    // Exports named like a math function, but with another signature, are kept
+    let wat = r#"(module
+  (func $log (export "log") (param i32))
+  (func)
+  (func (export "main") (param i32 i32)
+    i32.const 0
+    call $log)
+  (memory 1))"#;
+
+    let assembly = compile(wat)??.into_assembly()?;
+    assert!(assembly.contains("OpFunctionCall"), "{assembly}");
+    return Ok(());
+}
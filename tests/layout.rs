@@ -0,0 +1,131 @@
+mod common;
+
+use wasm2spirv::{
+    fg::module::ModuleBuilder,
+    r#type::{CompositeType, Layout, ScalarType, StructMember, Type},
+    translation::{Builder, Translation},
+};
+
+fn offsets(ty: &CompositeType) -> Vec<u32> {
+    return match ty {
+        CompositeType::Struct(members) => members.iter().map(|x| x.offset).collect(),
+        _ => unreachable!(),
+    };
+}
+
+#[test]
+fn struct_layout() -> color_eyre::Result<()> {
+    let inner = Type::Composite(CompositeType::Struct(Box::new([StructMember::new(
+        "x",
+        ScalarType::F32,
+        0,
+    )])));
+    let members = || {
+        [
+            ("a", Type::Scalar(ScalarType::F32)),
+            (
+                "b",
+                Type::Composite(CompositeType::vector(ScalarType::F32, 2)),
+            ),
+            (
+                "c",
+                Type::Composite(CompositeType::vector(ScalarType::F32, 3)),
+            ),
+            ("d", inner.clone()),
+            ("e", Type::Scalar(ScalarType::F32)),
+        ]
+    };
+
+    let std140 = Layout::Std140.struct_type(members())?;
+    assert_eq!(offsets(&std140), [0, 8, 16, 32, 48]);
+    assert_eq!(Layout::Std140.size(&Type::Composite(std140))?, 64);
+
+    let std430 = Layout::Std430.struct_type(members())?;
+    assert_eq!(offsets(&std430), [0, 8, 16, 28, 32]);
+    assert_eq!(Layout::Std430.size(&Type::Composite(std430.clone()))?, 48);
+
+    Layout::Std430.validate(&Type::Composite(std430), "Params")?;
+    return Ok(());
+}
+
+#[test]
+fn invalid_layout() -> color_eyre::Result<()> {
+    let params = Type::Composite(CompositeType::Struct(Box::new([
+        StructMember::new("a", ScalarType::F32, 0),
+        StructMember::new("b", ScalarType::F32, 4),
+        StructMember::new("c", CompositeType::vector(ScalarType::F32, 4), 12),
+    ])));
+    assert_eq!(
+        Layout::Std140
+            .validate(&params, "Params")
+            .unwrap_err()
+            .to_string(),
+        "Custom error: member 2 of block `Params` violates std140 alignment (offset 12, requires 16)"
+    );
+
+    let overlapping = Type::Composite(CompositeType::Struct(Box::new([
+        StructMember::new("a", CompositeType::vector(ScalarType::F32, 4), 0),
+        StructMember::new("b", ScalarType::F32, 8),
+    ])));
+    assert!(Layout::Std430.validate(&overlapping, "Params").is_err());
+
+    // Matrix columns are padded to 16 bytes by std140
+    let matrix = Type::Composite(CompositeType::matrix(2, 2, ScalarType::F32));
+    assert!(Layout::Std140.validate(&matrix, "m").is_err());
+    Layout::Std430.validate(&matrix, "m")?;
+
+    assert!(Layout::Std430
+        .alignment(&Type::Scalar(ScalarType::Bool))
+        .is_err());
+    return Ok(());
+}
+
+#[test]
+fn struct_types_keep_their_layout() -> color_eyre::Result<()> {
+    let config = common::vulkan_config()?;
+    let wasm = wat::parse_str("(module)")?;
+    let module = ModuleBuilder::new(config, &wasm)?;
+    let mut builder = Builder::new();
+
+    // Same member types, different offsets
+    let packed = CompositeType::Struct(Box::new([
+        StructMember::new("a", ScalarType::F32, 0),
+        StructMember::new("b", ScalarType::F32, 4),
+    ]));
+    let padded = CompositeType::Struct(Box::new([
+        StructMember::new("a", ScalarType::F32, 0),
+        StructMember::new("b", ScalarType::F32, 16),
+    ]));
+
+    let packed_type = packed.clone().translate(&module, None, &mut builder)?;
+    let padded_type = padded.translate(&module, None, &mut builder)?;
+    assert_ne!(packed_type, padded_type);
+    assert_eq!(packed.translate(&module, None, &mut builder)?, packed_type);
+    return Ok(());
+}
+
+#[test]
+fn struct_array_stride() -> color_eyre::Result<()> {
+    // 20 bytes of members, padded up to the 16-byte alignment of the vector
+    let pointee = serde_json::json!({
+        "Struct": [
+            { "name": "position", "type": { "Vector": ["f32", 4] }, "offset": 0 },
+            { "name": "weight", "type": "f32", "offset": 16 }
+        ]
+    });
+
+    let mut config = common::config(0, "f32");
+    config["functions"]["0"]["params"]["0"]["type"]["pointee"] = pointee;
+
+    // Weight of the second element
+    let wat = r#"(module
+  (func (export "main") (param i32)
+    local.get 0
+    f32.const 1
+    f32.store offset=48)
+  (memory 1))"#;
+
+    let assembly = common::compile(config, wat)?.into_assembly()?;
+    assert!(assembly.contains("ArrayStride 32"), "{assembly}");
+    return Ok(());
+}
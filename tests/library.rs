@@ -0,0 +1,69 @@
+mod common;
+
+use rspirv::spirv::Op;
+use wasm2spirv::Compilation;
+
+const MAIN: &str = r#"
+(module
+  (import "math" "add_one" (func $add_one (param i32) (result i32)))
+  (import "math" "offset" (global $offset i32))
+  (func (export "main") (param i32)
+    local.get 0
+    local.get 0
+    i32.load
+    call $add_one
+    global.get $offset
+    i32.add
+    i32.store)
+  (memory 1))
+"#;
+
+const LIBRARY: &str = r#"
+(module
+  (func (export "add_one") (param i32) (result i32)
+    local.get 0
+    i32.const 1
+    i32.add)
+  (global (export "offset") i32 (i32.const 2)))
+"#;
+
+#[test]
+fn library_imports() -> color_eyre::Result<()> {
+    let main = wat::parse_str(MAIN)?;
+    let library = wat::parse_str(LIBRARY)?;
+
+    let compilation = Compilation::with_libraries(
+        serde_json::from_value(common::config(1, "i32"))?,
+        &main,
+        &[("math", &library)],
+    )?;
+    #[cfg(feature = "spvt-validate")]
+    compilation.validate()?;
+
+    let module = compilation.module()?;
+    assert_eq!(module.entry_points.len(), 1);
+    assert_eq!(module.functions.len(), 2);
+    assert!(module
+        .functions
+        .iter()
+        .flat_map(|x| x.blocks.iter())
+        .flat_map(|x| x.instructions.iter())
+        .any(|x| x.class.opcode == Op::FunctionCall));
+
+    return Ok(());
+}
+
+#[test]
+fn library_unresolved_import() -> color_eyre::Result<()> {
+    let main = wat::parse_str(MAIN)?;
+    let library = wat::parse_str(LIBRARY)?;
+
+    let result = Compilation::with_libraries(
+        serde_json::from_value(common::config(1, "i32"))?,
+        &main,
+        &[("physics", &library)],
+    );
+    assert!(result.is_err());
+
+    return Ok(());
+}
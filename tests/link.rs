@@ -0,0 +1,225 @@
+mod common;
+
+use rspirv::{
+    dr::{Builder, Module, Operand},
+    spirv::{
+        AddressingModel, Capability, Decoration, ExecutionMode, ExecutionModel, FunctionControl,
+        LinkageType, MemoryModel, Op,
+    },
+};
+use wasm2spirv::{link::Linker, version::TargetPlatform};
+
+const FIRST: &str = r#"
+(module
+  (func (export "first") (param i32)
+    local.get 0
+    local.get 0
+    i32.load
+    i32.const 1
+    i32.add
+    i32.store)
+  (memory 1))
+"#;
+
+const SECOND: &str = r#"
+(module
+  (func (export "second") (param i32)
+    local.get 0
+    i32.const 2
+    i32.store)
+  (memory 1))
+"#;
+
+#[test]
+fn link_compilations() -> color_eyre::Result<()> {
+    let first = common::try_compile(common::config(0, "i32"), FIRST)??;
+    let second = common::try_compile(common::config(0, "i32"), SECOND)??;
+
+    let mut linker = Linker::new(first.platform);
+    linker.add(&first)?.add(&second)?;
+    let linked = linker.link()?;
+    #[cfg(feature = "spvt-validate")]
+    linked.validate()?;
+
+    let module = linked.module()?;
+    assert_eq!(module.entry_points.len(), 2);
+    assert_eq!(module.execution_modes.len(), 2);
+    assert_eq!(module.functions.len(), 2);
+    assert_eq!(
+        module
+            .types_global_values
+            .iter()
+            .filter(|x| x.class.opcode == Op::TypeInt)
+            .count(),
+        first
+            .module()?
+            .types_global_values
+            .iter()
+            .filter(|x| x.class.opcode == Op::TypeInt)
+            .count()
+    );
+
+    return Ok(());
+}
+
+#[test]
+fn link_mismatched_platforms() -> color_eyre::Result<()> {
+    let first = common::try_compile(common::config(0, "i32"), FIRST)??;
+    assert!(Linker::new(TargetPlatform::SPV_1_5).add(&first).is_err());
+    return Ok(());
+}
+
+fn library(linkage: LinkageType) -> Module {
+    let mut b = Builder::new();
+    b.set_version(1, 5);
+    b.capability(Capability::Shader);
+    b.capability(Capability::Linkage);
+    b.memory_model(AddressingModel::Logical, MemoryModel::GLSL450);
+
+    let int = b.type_int(32, 0);
+    let ty = b.type_function(int, [int]);
+    let function = b
+        .begin_function(int, None, FunctionControl::NONE, ty)
+        .unwrap();
+    let param = b.function_parameter(int).unwrap();
+    if linkage == LinkageType::Export {
+        let one = b.constant_u32(int, 1);
+        b.begin_block(None).unwrap();
+        let result = b.i_add(int, None, param, one).unwrap();
+        b.ret_value(result).unwrap();
+    }
+    b.end_function().unwrap();
+
+    b.decorate(
+        function,
+        Decoration::LinkageAttributes,
+        [
+            Operand::LiteralString(String::from("add_one")),
+            Operand::LinkageType(linkage),
+        ],
+    );
+
+    if linkage == LinkageType::Import {
+        let void = b.type_void();
+        let main_ty = b.type_function(void, []);
+        let main = b
+            .begin_function(void, None, FunctionControl::NONE, main_ty)
+            .unwrap();
+        let two = b.constant_u32(int, 2);
+        b.begin_block(None).unwrap();
+        b.function_call(int, None, function, [two]).unwrap();
+        b.ret().unwrap();
+        b.end_function().unwrap();
+
+        b.entry_point(ExecutionModel::GLCompute, main, "main", []);
+        b.execution_mode(main, ExecutionMode::LocalSize, [1, 1, 1]);
+    }
+
+    return b.module();
+}
+
+#[test]
+fn link_imports() -> color_eyre::Result<()> {
+    let mut linker = Linker::new(TargetPlatform::SPV_1_5);
+    linker
+        .add_module(library(LinkageType::Import))
+        .add_module(library(LinkageType::Export));
+    let linked = linker.link()?;
+    #[cfg(feature = "spvt-validate")]
+    linked.validate()?;
+
+    let module = linked.module()?;
+    assert_eq!(module.functions.len(), 2);
+    assert!(module.functions.iter().all(|x| !x.blocks.is_empty()));
+    assert!(module.annotations.is_empty());
+    assert!(module.capabilities.iter().all(|x| !matches!(
+        x.operands.first(),
+        Some(Operand::Capability(Capability::Linkage))
+    )));
+    assert_eq!(
+        module
+            .types_global_values
+            .iter()
+            .filter(|x| x.class.opcode == Op::TypeInt)
+            .count(),
+        1
+    );
+
+    return Ok(());
+}
+
+const CALLER: &str = r#"
+(module
+  (import "env" "add_one" (func $add_one (param i32) (result i32)))
+  (func (export "main") (param i32)
+    local.get 0
+    local.get 0
+    i32.load
+    call $add_one
+    i32.store)
+  (memory 1))
+"#;
+
+const CALLEE: &str = r#"
+(module
+  (func (export "add_one") (param i32) (result i32)
+    local.get 0
+    i32.const 1
+    i32.add))
+"#;
+
+fn linkage_attributes(module: &Module, linkage: LinkageType) -> usize {
+    return module
+        .annotations
+        .iter()
+        .filter(|x| x.operands.get(1) == Some(&Operand::Decoration(Decoration::LinkageAttributes)))
+        .filter(|x| x.operands.last() == Some(&Operand::LinkageType(linkage)))
+        .count();
+}
+
+#[test]
+fn link_wasm_imports() -> color_eyre::Result<()> {
+    let mut config = common::config(0, "i32");
+    let main = config["functions"]["0"].take();
+    config["linkage"] = true.into();
+
+    config["functions"] = serde_json::json!({ "1": main.clone() });
+    let caller = common::try_compile(config.clone(), CALLER)??;
+
+    config["functions"] = serde_json::json!({});
+    let callee = common::try_compile(config.clone(), CALLEE)??;
+
+    assert_eq!(linkage_attributes(caller.module()?, LinkageType::Import), 1);
+    assert_eq!(linkage_attributes(callee.module()?, LinkageType::Export), 1);
+
+    let mut linker = Linker::new(caller.platform);
+    linker.add(&caller)?.add(&callee)?;
+    let linked = linker.link()?;
+    #[cfg(feature = "spvt-validate")]
+    linked.validate()?;
+
+    let module = linked.module()?;
+    assert_eq!(module.functions.len(), 2);
+    assert!(module.functions.iter().all(|x| !x.blocks.is_empty()));
+    assert_eq!(linkage_attributes(module, LinkageType::Import), 0);
+    assert_eq!(linkage_attributes(module, LinkageType::Export), 0);
+
+    // Without linkage, imports must be resolved by the compilation itself
+    config["linkage"] = false.into();
+    config["functions"] = serde_json::json!({ "1": main });
+    assert!(common::try_compile(config, CALLER)?.is_err());
+    return Ok(());
+}
+
+#[test]
+fn link_malformed_entry_point() -> color_eyre::Result<()> {
+    let mut module = library(LinkageType::Import);
+    module.entry_points[0].operands.truncate(1);
+
+    let mut linker = Linker::new(TargetPlatform::SPV_1_5);
+    linker
+        .add_module(library(LinkageType::Import))
+        .add_module(module);
+    assert!(linker.link().is_err());
+    return Ok(());
+}
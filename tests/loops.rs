@@ -0,0 +1,33 @@
+use wasm2spirv::{config::Config, fg::function::LoopHints, Compilation};
+
+fn saxpy(loops: LoopHints) -> color_eyre::Result<String> {
+    let mut config: Config = serde_json::from_str(include_str!("../examples/saxpy/saxpy.json"))?;
+    config
+        .functions
+        .get_mut(&2)
+        .ok_or_else(|| color_eyre::eyre::eyre!("missing function config"))?
+        .loops = loops;
+
+    let wasm = wat::parse_bytes(include_bytes!("../examples/saxpy/saxpy.wat"))?;
+    return Ok(Compilation::new(config, &wasm)?.into_assembly()?);
+}
+
+#[test]
+fn loop_hints() -> color_eyre::Result<()> {
+    let default = saxpy(LoopHints::default())?;
+    assert!(default.contains("OpLoopMerge"));
+    assert!(!default.contains("Unroll"));
+
+    let unroll = saxpy(LoopHints {
+        unroll: Some(true),
+        dependency_length: Some(4),
+    })?;
+    assert!(unroll.contains("Unroll|DependencyLength 4"));
+
+    let dont_unroll = saxpy(LoopHints {
+        unroll: Some(false),
+        dependency_length: None,
+    })?;
+    assert!(dont_unroll.contains("DontUnroll"));
+    return Ok(());
+}
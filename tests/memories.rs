@@ -0,0 +1,63 @@
+use wasm2spirv::{config::Config, Compilation};
+
+const WAT: &str = r#"
+(module
+  (memory $a 1)
+  (memory $b 1)
+  (func (export "main")
+    i32.const 0
+    i32.const 4
+    i32.load $a
+    i32.store $b
+    i32.const 8
+    memory.size $b
+    i32.store $a))
+"#;
+
+fn compile(memories: serde_json::Value) -> color_eyre::Result<Compilation> {
+    let config = serde_json::json!({
+        "platform": { "vulkan": "1.1" },
+        "features": {
+            "memory64": false,
+            "saturating_float_to_int": false,
+            "multi_memory": true
+        },
+        "addressing_model": "logical",
+        "memory_model": "GLSL450",
+        "capabilities": { "dynamic": [] },
+        "extensions": [],
+        "memories": memories,
+        "functions": {
+            "0": {
+                "execution_model": "GLCompute",
+                "execution_modes": [{ "local_size": [1, 1, 1] }]
+            }
+        }
+    });
+
+    let config: Config = serde_json::from_value(config)?;
+    let compilation = Compilation::new(config, &wat::parse_str(WAT)?)?;
+    #[cfg(feature = "spvt-validate")]
+    compilation.validate()?;
+    return Ok(compilation);
+}
+
+#[test]
+fn multiple_memories() -> color_eyre::Result<()> {
+    let assembly = compile(serde_json::json!({
+        "0": { "set": 0, "binding": 0 },
+        "1": { "set": 0, "binding": 1 }
+    }))?
+    .into_assembly()?;
+
+    assert!(assembly.contains("Binding 0"));
+    assert!(assembly.contains("Binding 1"));
+    assert!(assembly.contains("OpArrayLength"));
+    return Ok(());
+}
+
+#[test]
+fn unbound_memory() -> color_eyre::Result<()> {
+    assert!(compile(serde_json::json!({ "0": { "set": 0, "binding": 0 } })).is_err());
+    return Ok(());
+}
@@ -0,0 +1,69 @@
+mod common;
+
+use wasm2spirv::{config::AddressingModel, Compilation};
+
+const ADD_OFFSET_64: &str = r#"
+(module
+  (func (param i64) (result i64)
+    local.get 0
+    i64.const 8
+    i64.add)
+  (memory i64 1))
+"#;
+
+const ADD_OFFSET_32: &str = r#"
+(module
+  (func (param i32) (result i32)
+    local.get 0
+    i32.const 8
+    i32.add)
+  (memory 1))
+"#;
+
+#[test]
+fn saxpy64() -> color_eyre::Result<()> {
+    let _ = color_eyre::install();
+
+    let config = serde_json::from_str(include_str!("../examples/saxpy64/saxpy64.json"))?;
+    let wasm = wat::parse_bytes(include_bytes!("../examples/saxpy64/saxpy64.wat"))?;
+    let compile = Compilation::new(config, &wasm)?;
+
+    assert!(compile.assembly()?.contains("OpTypeInt 64 0"));
+    return Ok(());
+}
+
+#[test]
+fn physical64() -> color_eyre::Result<()> {
+    let config = common::physical_config(AddressingModel::Physical, true)?;
+    let wasm = wat::parse_str(ADD_OFFSET_64)?;
+    let compile = Compilation::new(config, &wasm)?;
+
+    assert!(compile.assembly()?.contains("Physical64"));
+    return Ok(());
+}
+
+#[test]
+fn physical32() -> color_eyre::Result<()> {
+    let config = common::physical_config(AddressingModel::Physical, false)?;
+    let wasm = wat::parse_str(ADD_OFFSET_32)?;
+    let compile = Compilation::new(config, &wasm)?;
+
+    assert!(compile.assembly()?.contains("Physical32"));
+    return Ok(());
+}
+
+#[test]
+fn memory64_feature_required() -> color_eyre::Result<()> {
+    let config = common::physical_config(AddressingModel::Physical, false)?;
+    let wasm = wat::parse_str(ADD_OFFSET_64)?;
+    assert!(Compilation::new(config, &wasm).is_err());
+    return Ok(());
+}
+
+#[test]
+fn physical_storage_buffer32() -> color_eyre::Result<()> {
+    let config = common::physical_config(AddressingModel::PhysicalStorageBuffer, false)?;
+    let wasm = wat::parse_str(ADD_OFFSET_32)?;
+    assert!(Compilation::new(config, &wasm).is_err());
+    return Ok(());
+}
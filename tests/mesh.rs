@@ -0,0 +1,86 @@
+use rspirv::spirv::{Capability, ExecutionModel};
+use wasm2spirv::{config::Config, version::TargetPlatform, Compilation};
+
+const WAT: &str = r#"
+(module
+  (import "spir_global" "gl_PrimitiveCountNV" (func $primitive_count (param i32)))
+  (func (export "main")
+    i32.const 0
+    call $primitive_count))
+"#;
+
+fn compile(
+    execution_model: &str,
+    execution_modes: serde_json::Value,
+) -> color_eyre::Result<Compilation> {
+    let config = serde_json::json!({
+        "platform": { "vulkan": "1.1" },
+        "addressing_model": "logical",
+        "memory_model": "GLSL450",
+        "capabilities": { "dynamic": [] },
+        "extensions": [],
+        "functions": {
+            "1": {
+                "execution_model": execution_model,
+                "execution_modes": execution_modes
+            }
+        }
+    });
+
+    let config: Config = serde_json::from_value(config)?;
+    let report = config.validate();
+    assert!(report.is_ok(), "{report:?}");
+
+    let compilation = Compilation::new(config, &wat::parse_str(WAT)?)?;
+    #[cfg(feature = "spvt-validate")]
+    compilation.validate()?;
+    return Ok(compilation);
+}
+
+#[test]
+fn mesh_shader() -> color_eyre::Result<()> {
+    let compilation = compile(
+        "MeshNV",
+        serde_json::json!([
+            { "local_size": [32, 1, 1] },
+            { "output_vertices": 64 },
+            { "output_primitives": 126 },
+            "output_triangles"
+        ]),
+    )?;
+
+    let assembly = compilation.assembly()?;
+    assert!(assembly.contains("OpCapability MeshShadingNV"));
+    assert!(assembly.contains("OpExtension \"SPV_NV_mesh_shader\""));
+    assert!(assembly.contains("OutputPrimitivesNV 126"));
+    assert!(assembly.contains("OutputTrianglesNV"));
+    assert!(assembly.contains("BuiltIn PrimitiveCountNV"));
+    return Ok(());
+}
+
+#[test]
+fn missing_mesh_modes() -> color_eyre::Result<()> {
+    let mut config = Config::for_platform(TargetPlatform::VK_1_1)?;
+    config
+        .function(0)
+        .set_entry_point(ExecutionModel::MeshNV)?
+        .build();
+
+    let report = config.build()?.validate();
+    for expected in [
+        "OutputVertices",
+        "OutputPrimitives",
+        "output primitive",
+        "LocalSize",
+    ] {
+        assert!(
+            report.errors.iter().any(|x| x.contains(expected)),
+            "{report:?}"
+        );
+    }
+    assert!(config
+        .build()?
+        .capabilities
+        .can_enable(Capability::MeshShadingNV));
+    return Ok(());
+}
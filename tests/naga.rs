@@ -0,0 +1,193 @@
+#![cfg(feature = "naga")]
+
+use wasm2spirv::Compilation;
+
+#[test]
+fn naga_module() -> color_eyre::Result<()> {
+    let config = serde_json::from_str(include_str!("../examples/fragment/fragment.json"))?;
+    let wasm = wat::parse_bytes(include_bytes!("../examples/fragment/fragment.wat"))?;
+    let compile = Compilation::new(config, &wasm)?;
+
+    let (module, _) = compile.naga_module()?;
+    assert_eq!(module.entry_points.len(), 1);
+    assert_eq!(module.entry_points[0].name, "main");
+    return Ok(());
+}
+
+#[test]
+fn naga_from_spirv() -> color_eyre::Result<()> {
+    let config = serde_json::from_str(include_str!("../examples/fragment/fragment.json"))?;
+    let wasm = wat::parse_bytes(include_bytes!("../examples/fragment/fragment.wat"))?;
+    let compile = Compilation::new(config, &wasm)?;
+
+    let spirv = Compilation::from_spirv(compile.platform, compile.words()?)?;
+    let (module, _) = spirv.naga_module()?;
+    assert_eq!(module.entry_points.len(), 1);
+    assert_eq!(module.entry_points[0].name, "main");
+    return Ok(());
+}
+
+#[cfg(feature = "naga-glsl")]
+#[test]
+fn glsl_es() -> color_eyre::Result<()> {
+    use wasm2spirv::compilers::GlslOptions;
+
+    let config = serde_json::from_str(include_str!("../examples/fragment/fragment.json"))?;
+    let wasm = wat::parse_bytes(include_bytes!("../examples/fragment/fragment.wat"))?;
+    let compile = Compilation::new(config, &wasm)?;
+
+    let glsl = compile.naga_glsl_with(&GlslOptions {
+        version: 310,
+        es: true,
+        entry_point: Some(String::from("main")),
+        ..Default::default()
+    })?;
+
+    assert!(glsl.starts_with("#version 310 es"));
+    return Ok(());
+}
+
+#[cfg(feature = "naga-hlsl")]
+#[test]
+fn hlsl_register_spaces() -> color_eyre::Result<()> {
+    use wasm2spirv::compilers::{HlslOptions, ShaderModel};
+
+    let config = serde_json::from_str(include_str!("../examples/square/square.json"))?;
+    let wasm = wat::parse_bytes(include_bytes!("../examples/square/square.wat"))?;
+    let compile = Compilation::new(config, &wasm)?;
+
+    let mut options = HlslOptions {
+        shader_model: ShaderModel::V6_0,
+        entry_point: Some(String::from("Main")),
+        ..Default::default()
+    };
+    options.register_spaces.insert(0, 3);
+
+    let hlsl = compile.naga_hlsl_with(&options)?;
+    assert!(hlsl.contains("register(u0, space3)"));
+    return Ok(());
+}
+
+#[cfg(feature = "naga-msl")]
+#[test]
+fn msl_bindings() -> color_eyre::Result<()> {
+    use wasm2spirv::compilers::{MslBinding, MslOptions};
+
+    let config = serde_json::from_str(include_str!("../examples/square/square.json"))?;
+    let wasm = wat::parse_bytes(include_bytes!("../examples/square/square.wat"))?;
+    let compile = Compilation::new(config, &wasm)?;
+
+    let msl = compile.naga_msl_with(&MslOptions {
+        version: Some((2, 1)),
+        bindings: vec![MslBinding {
+            set: 0,
+            binding: 1,
+            buffer: Some(7),
+            texture: None,
+            sampler: None,
+        }],
+        ..Default::default()
+    })?;
+
+    assert!(msl.contains("[[buffer(7)]]"));
+    return Ok(());
+}
+
+#[cfg(feature = "naga-glsl")]
+#[test]
+fn glsl_entry() -> color_eyre::Result<()> {
+    use rspirv::spirv::ExecutionModel;
+
+    let config = serde_json::from_str(include_str!("../examples/fragment/fragment.json"))?;
+    let wasm = wat::parse_bytes(include_bytes!("../examples/fragment/fragment.wat"))?;
+    let compile = Compilation::new(config, &wasm)?;
+
+    assert!(compile.glsl_entry("main", ExecutionModel::Fragment).is_ok());
+    assert!(compile.glsl_entry("main", ExecutionModel::Vertex).is_err());
+    assert!(compile
+        .glsl_entry("other", ExecutionModel::Fragment)
+        .is_err());
+    return Ok(());
+}
+
+#[cfg(feature = "naga-glsl")]
+#[test]
+fn cross_compiler() -> color_eyre::Result<()> {
+    use wasm2spirv::compilers::CrossBackend;
+
+    let config = serde_json::from_str(include_str!("../examples/fragment/fragment.json"))?;
+    let wasm = wat::parse_bytes(include_bytes!("../examples/fragment/fragment.wat"))?;
+    let compile = Compilation::new(config, &wasm)?;
+
+    let naga = compile.cross_compiler(CrossBackend::Naga);
+    assert_eq!(naga.glsl()?, compile.naga_glsl()?);
+
+    // Without the feature, the backend is reported as unavailable instead of falling back
+    #[cfg(not(feature = "spvc-glsl"))]
+    {
+        let Err(error) = compile.cross_compiler(CrossBackend::SpirvCross).glsl() else {
+            panic!("SPIRV-Cross shouldn't be available");
+        };
+        assert!(error.to_string().contains("spvc-glsl"), "{error}");
+    }
+    return Ok(());
+}
+
+#[cfg(all(feature = "naga-glsl", not(feature = "spvc-glsl")))]
+#[test]
+fn fallback() -> color_eyre::Result<()> {
+    use wasm2spirv::{
+        compilers::{CrossBackend, GlslOptions},
+        error::Error,
+    };
+
+    let config = serde_json::from_str(include_str!("../examples/fragment/fragment.json"))?;
+    let wasm = wat::parse_bytes(include_bytes!("../examples/fragment/fragment.wat"))?;
+    let compile = Compilation::new(config, &wasm)?;
+
+    // SPIRV-Cross isn't available, so naga produces the output
+    let output = compile.glsl_fallback(&GlslOptions::default())?;
+    assert_eq!(output.backend, CrossBackend::Naga);
+    assert_eq!(output.source, compile.naga_glsl()?);
+
+    let Err(Error::Fallback(error)) = compile.glsl_fallback(&GlslOptions {
+        entry_point: Some(String::from("other")),
+        ..Default::default()
+    }) else {
+        panic!("every backend should fail");
+    };
+    assert_eq!(
+        error.errors.iter().map(|(x, _)| *x).collect::<Vec<_>>(),
+        CrossBackend::ALL
+    );
+    return Ok(());
+}
+
+#[cfg(feature = "naga-wgsl")]
+#[test]
+fn wgsl_options() -> color_eyre::Result<()> {
+    use wasm2spirv::compilers::WgslOptions;
+
+    let config = serde_json::from_str(include_str!("../examples/fragment/fragment.json"))?;
+    let wasm = wat::parse_bytes(include_bytes!("../examples/fragment/fragment.wat"))?;
+    let compile = Compilation::new(config, &wasm)?;
+
+    let wgsl = compile.wgsl()?;
+    assert_eq!(compile.wgsl_with(&WgslOptions::default())?, wgsl);
+
+    let inferred = compile.wgsl_with(&WgslOptions {
+        explicit_types: false,
+        ..Default::default()
+    })?;
+    assert!(inferred.len() < wgsl.len(), "{inferred}");
+
+    #[cfg(feature = "spirv-tools")]
+    {
+        let legalized = compile.wgsl_with(&WgslOptions {
+            legalize: true,
+            ..Default::default()
+        })?;
+        assert!(legalized.contains("fn main("), "{legalized}");
+    }
+    return Ok(());
+}
@@ -0,0 +1,69 @@
+use wasm2spirv::{config::Config, Compilation};
+
+fn saxpy(forbidden: &[&str]) -> color_eyre::Result<(Config, Vec<u8>)> {
+    let mut config: serde_json::Value =
+        serde_json::from_str(include_str!("../examples/saxpy/saxpy.json"))?;
+    config["forbidden_operators"] = serde_json::json!(forbidden);
+
+    let wasm = wat::parse_bytes(include_bytes!("../examples/saxpy/saxpy.wat"))?;
+    return Ok((serde_json::from_value(config)?, wasm.into_owned()));
+}
+
+fn compile(forbidden: &[&str]) -> color_eyre::Result<wasm2spirv::error::Result<Vec<u32>>> {
+    let (config, wasm) = saxpy(forbidden)?;
+    return Ok(Compilation::new(config, &wasm).and_then(|x| Ok(x.words()?.to_vec())));
+}
+
+#[test]
+fn allowed() -> color_eyre::Result<()> {
+    compile(&[])??;
+    compile(&["i64", "atomic", "simd", "F64Add"])??;
+    return Ok(());
+}
+
+#[test]
+fn forbidden_group() -> color_eyre::Result<()> {
+    let Err(error) = compile(&["i64", "float"])? else {
+        panic!("saxpy uses floats");
+    };
+
+    let error = error.to_string();
+    assert!(error.contains("Function 2 uses `F32Load`"), "{error}");
+    assert!(error.contains("at offset 0x"), "{error}");
+    assert!(error.contains("forbidden by `float`"), "{error}");
+    return Ok(());
+}
+
+#[test]
+fn forbidden_operator() -> color_eyre::Result<()> {
+    let Err(error) = compile(&["F32Mul"])? else {
+        panic!("saxpy multiplies floats");
+    };
+
+    let error = error.to_string();
+    assert!(error.contains("uses `F32Mul`"), "{error}");
+    assert!(error.contains("forbidden by `F32Mul`"), "{error}");
+    return Ok(());
+}
+
+#[test]
+fn forbidding() -> color_eyre::Result<()> {
+    let (config, _) = saxpy(&["f64", "simd", "I32Add"])?;
+    assert_eq!(config.forbidding("F64ConvertI32S"), Some("f64"));
+    assert_eq!(config.forbidding("I32x4Add"), Some("simd"));
+    assert_eq!(config.forbidding("I32Add"), Some("I32Add"));
+    assert_eq!(config.forbidding("I32Sub"), None);
+    assert_eq!(config.forbidding("F32Add"), None);
+    return Ok(());
+}
+
+#[test]
+fn unknown_group() -> color_eyre::Result<()> {
+    let (config, _) = saxpy(&["floats"])?;
+    let report = config.validate();
+    assert!(
+        report.errors.iter().any(|x| x.contains("`floats`")),
+        "{report:?}"
+    );
+    return Ok(());
+}
@@ -0,0 +1,47 @@
+mod common;
+
+use wasm2spirv::Compilation;
+
+const WAT: &str = r#"(module
+  (func (export "main") (param i32)
+    local.get 0
+    local.get 0
+    f32.load
+    local.get 0
+    f32.load offset=4
+    f32.min
+    f32.store offset=8
+    local.get 0
+    local.get 0
+    i32.load
+    if (result i32)
+      i32.const 1
+    else
+      i32.const 2
+    end
+    i32.store offset=12)
+  (memory 1))"#;
+
+fn compile(phi_merges: bool) -> color_eyre::Result<Compilation> {
+    let mut config = common::config(0, "i32");
+    config["phi_merges"] = phi_merges.into();
+    return common::compile(config, WAT);
+}
+
+#[test]
+fn temporaries() -> color_eyre::Result<()> {
+    let assembly = compile(false)?.assembly()?.to_string();
+    assert!(!assembly.contains("OpPhi"), "{assembly}");
+    return Ok(());
+}
+
+#[test]
+fn phi_merges() -> color_eyre::Result<()> {
+    let temporaries = compile(false)?;
+    let compilation = compile(true)?;
+
+    let assembly = compilation.assembly()?.to_string();
+    assert_eq!(assembly.matches("OpPhi").count(), 2, "{assembly}");
+    assert!(compilation.words()?.len() < temporaries.words()?.len());
+    return Ok(());
+}
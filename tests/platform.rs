@@ -0,0 +1,112 @@
+use wasm2spirv::{
+    config::Config,
+    version::{TargetPlatform, Version},
+    Compilation,
+};
+
+const ADD: &str = r#"
+(module
+  (func (param i32 i32) (result i32)
+    local.get 0
+    local.get 1
+    i32.add))
+"#;
+
+#[test]
+fn opengl_preset() -> color_eyre::Result<()> {
+    let config = Config::for_platform(TargetPlatform::GL_4_5)?.build()?;
+    let compile = Compilation::new(config, &wat::parse_str(ADD)?)?;
+
+    let assembly = compile.assembly()?;
+    assert!(assembly.contains("OpMemoryModel Logical GLSL450"));
+    assert!(assembly.contains("OpCapability Shader"));
+    return Ok(());
+}
+
+#[test]
+fn opencl_preset() -> color_eyre::Result<()> {
+    let config = Config::for_platform(TargetPlatform::CL_2_1)?.build()?;
+    let compile = Compilation::new(config, &wat::parse_str(ADD)?)?;
+
+    let assembly = compile.assembly()?;
+    assert!(assembly.contains("OpMemoryModel Physical32 OpenCL"));
+    assert!(assembly.contains("OpCapability Kernel"));
+    return Ok(());
+}
+
+#[test]
+fn spirv_versions() {
+    assert_eq!(
+        TargetPlatform::VK_1_3.spirv_version(),
+        TargetPlatform::SPV_1_6.spirv_version()
+    );
+    assert_eq!(
+        TargetPlatform::CL_2_2.spirv_version(),
+        TargetPlatform::SPV_1_2.spirv_version()
+    );
+}
+
+#[test]
+fn serde() -> color_eyre::Result<()> {
+    let platform: TargetPlatform = serde_json::from_str(r#"{ "opengl": "4.5" }"#)?;
+    assert_eq!(platform, TargetPlatform::GL_4_5);
+    return Ok(());
+}
+
+#[test]
+fn invalid_versions() -> color_eyre::Result<()> {
+    assert!(TargetPlatform::SPV_1_6.check().is_ok());
+    assert!(TargetPlatform::VK_1_3.check().is_ok());
+
+    let error = TargetPlatform::Vulkan(Version::new(1, 7))
+        .check()
+        .unwrap_err();
+    assert!(error.to_string().contains("1.0, 1.1, 1.2, 1.3"), "{error}");
+
+    let config = Config::for_platform(TargetPlatform::OpenGL(Version::new(3, 3)))?.build()?;
+    assert!(config
+        .validate()
+        .errors
+        .iter()
+        .any(|x| x.contains("OpenGL 3.3")));
+    assert!(Compilation::new(config, &wat::parse_str(ADD)?).is_err());
+    return Ok(());
+}
+
+/// Configuration of a compute shader that declares its local size with `OpExecutionModeId`
+fn local_size_id(version: &str) -> color_eyre::Result<Config> {
+    let config = serde_json::json!({
+        "platform": { "universal": version },
+        "addressing_model": "logical",
+        "memory_model": "GLSL450",
+        "capabilities": { "dynamic": [] },
+        "extensions": [],
+        "functions": {
+            "0": {
+                "execution_model": "GLCompute",
+                "execution_modes": [{ "local_size_id": [8, 4, 1] }]
+            }
+        }
+    });
+
+    return Ok(serde_json::from_value(config)?);
+}
+
+#[test]
+fn execution_mode_id() -> color_eyre::Result<()> {
+    let config = local_size_id("1.6")?;
+    assert!(config.validate().is_ok());
+
+    let wasm = wat::parse_str(r#"(module (func (export "main")))"#)?;
+    let assembly = Compilation::new(config, &wasm)?.into_assembly()?;
+    assert!(assembly.contains("OpExecutionModeId"));
+    assert!(assembly.contains("LocalSizeId"));
+
+    // `OpExecutionModeId` was introduced by SPIR-V 1.2
+    assert!(local_size_id("1.1")?
+        .validate()
+        .errors
+        .iter()
+        .any(|x| x.contains("SPIR-V 1.2")));
+    return Ok(());
+}
@@ -0,0 +1,108 @@
+use wasm2spirv::Compilation;
+
+const WAT: &str = r#"
+(module
+  (import "spir_global" "gl_GlobalInvocationID" (func $id (param i32) (result i32)))
+  (import "spir_global" "debug_printf" (func $printf (param i32 i32 f32)))
+  (memory 1)
+  (data (i32.const 16) "id = %u, x = %f\00")
+  (func (export "main")
+    i32.const 16
+    i32.const 0
+    call $id
+    f32.const 1.5
+    call $printf))
+"#;
+
+fn compile(wat: &str, capabilities: serde_json::Value) -> color_eyre::Result<Compilation> {
+    let config = serde_json::json!({
+        "platform": { "vulkan": "1.1" },
+        "addressing_model": "logical",
+        "memory_model": "GLSL450",
+        "capabilities": capabilities,
+        "extensions": [],
+        "memories": { "0": { "set": 0, "binding": 0 } },
+        "functions": {
+            "2": {
+                "execution_model": "GLCompute",
+                "execution_modes": [{ "local_size": [1, 1, 1] }]
+            }
+        }
+    });
+
+    return Ok(Compilation::new(
+        serde_json::from_value(config)?,
+        &wat::parse_str(wat)?,
+    )?);
+}
+
+#[test]
+fn debug_printf() -> color_eyre::Result<()> {
+    let compilation = compile(WAT, serde_json::json!({ "dynamic": [] }))?;
+    #[cfg(feature = "spvt-validate")]
+    compilation.validate()?;
+
+    let assembly = compilation.assembly()?;
+    assert!(assembly.contains(r#"OpExtension "SPV_KHR_non_semantic_info""#));
+    assert!(assembly.contains(r#"OpExtInstImport "NonSemantic.DebugPrintf""#));
+    assert!(assembly.contains(r#"OpString "id = %u, x = %f""#));
+    assert_eq!(assembly.matches("OpExtInst ").count(), 1);
+    return Ok(());
+}
+
+#[test]
+fn shared_import() -> color_eyre::Result<()> {
+    // Both imports use the same instruction set, which is only imported once
+    let wat = r#"
+(module
+  (import "spir_global" "debug_printf" (func $printf (param i32 i32 f32)))
+  (import "spir_global" "debug_printf" (func $printf_f32 (param i32 f32)))
+  (memory 1)
+  (data (i32.const 16) "id = %u, x = %f\00")
+  (data (i32.const 48) "x = %f\00")
+  (func (export "main")
+    i32.const 16
+    i32.const 0
+    f32.const 1.5
+    call $printf
+    i32.const 48
+    f32.const 2.5
+    call $printf_f32))
+"#;
+
+    let assembly = compile(wat, serde_json::json!({ "dynamic": [] }))?.into_assembly()?;
+    assert_eq!(
+        assembly
+            .matches(r#"OpExtInstImport "NonSemantic.DebugPrintf""#)
+            .count(),
+        1
+    );
+    assert_eq!(assembly.matches("OpExtInst ").count(), 2);
+    return Ok(());
+}
+
+#[test]
+fn debug_printf_errors() -> color_eyre::Result<()> {
+    // The extension can't be enabled with a static capability model
+    let err = compile(WAT, serde_json::json!({ "static": ["Shader"] }))
+        .err()
+        .unwrap()
+        .to_string();
+    assert!(err.contains("SPV_KHR_non_semantic_info"), "{err}");
+
+    // The format string must be known at compile time
+    let wat = WAT.replace("i32.const 16\n", "i32.const 0\n    call $id\n");
+    let err = compile(&wat, serde_json::json!({ "dynamic": [] }))
+        .err()
+        .unwrap()
+        .to_string();
+    assert!(err.contains("constant address"), "{err}");
+
+    let wat = WAT.replace("i32.const 16\n", "i32.const 8\n");
+    let err = compile(&wat, serde_json::json!({ "dynamic": [] }))
+        .err()
+        .unwrap()
+        .to_string();
+    assert!(err.contains("address 8"), "{err}");
+    return Ok(());
+}
@@ -0,0 +1,63 @@
+use wasm2spirv::{
+    binary::{deserialize::BinaryDeserialize, serialize::BinarySerialize},
+    config::{Config, ProfilingBuffer},
+    profile::CounterKind,
+    Compilation,
+};
+
+fn saxpy() -> color_eyre::Result<Config> {
+    return Ok(serde_json::from_str(include_str!(
+        "../examples/saxpy/saxpy.json"
+    ))?);
+}
+
+#[test]
+fn profile_counters() -> color_eyre::Result<()> {
+    let mut config = saxpy()?;
+    config.profiling = Some(ProfilingBuffer { set: 1, binding: 0 });
+
+    let wasm = wat::parse_bytes(include_bytes!("../examples/saxpy/saxpy.wat"))?;
+    let compilation = Compilation::new(config, &wasm)?;
+    #[cfg(feature = "spvt-validate")]
+    compilation.validate()?;
+
+    // One counter at the entry of the function, and another at the back-edge of its loop
+    let counters = compilation.profile_counters();
+    assert_eq!(counters.len(), 2);
+    assert_eq!(counters[0].kind, CounterKind::FunctionEntry);
+    assert_eq!(counters[1].kind, CounterKind::BackEdge { loop_index: 0 });
+    assert_eq!(counters[1].offset, 4);
+    assert!(counters.iter().all(|x| x.function == 2));
+
+    let assembly = compilation.assembly()?;
+    assert_eq!(assembly.matches("OpAtomicIAdd").count(), counters.len());
+    assert!(assembly.contains("DescriptorSet 1"));
+
+    // Without profiling, nothing is instrumented
+    let compilation = Compilation::new(saxpy()?, &wasm)?;
+    assert!(compilation.profile_counters().is_empty());
+    assert!(!compilation.assembly()?.contains("OpAtomicIAdd"));
+    return Ok(());
+}
+
+#[test]
+fn profiling_config() -> color_eyre::Result<()> {
+    let mut config = saxpy()?;
+    config.profiling = Some(ProfilingBuffer { set: 0, binding: 0 });
+
+    let mut bytes = Vec::new();
+    config.serialize_into(&mut bytes)?;
+    let decoded = Config::deserialize_from(&mut &bytes[..])?;
+    assert_eq!(decoded.profiling, config.profiling);
+
+    // The buffer can't share the binding of a parameter
+    let report = config.validate();
+    assert!(
+        report
+            .warnings
+            .iter()
+            .any(|x| x.contains("the profiling counters")),
+        "{report:?}"
+    );
+    return Ok(());
+}
@@ -0,0 +1,90 @@
+use wasm2spirv::{
+    binary::{deserialize::BinaryDeserialize, serialize::BinarySerialize},
+    config::{Config, Profile},
+    version::{TargetPlatform, Version},
+    Compilation,
+};
+
+fn saxpy() -> color_eyre::Result<Config> {
+    let mut config: serde_json::Value =
+        serde_json::from_str(include_str!("../examples/saxpy/saxpy.json"))?;
+    config["profiles"] = serde_json::json!({
+        "vulkan1.1-mobile": {},
+        "vulkan1.2-desktop": {
+            "platform": { "vulkan": "1.2" },
+            "capabilities": { "dynamic": ["VariablePointers", "Int64"] }
+        }
+    });
+
+    return Ok(serde_json::from_value(config)?);
+}
+
+#[test]
+fn all_profiles() -> color_eyre::Result<()> {
+    let config = saxpy()?;
+    let report = config.validate();
+    assert!(report.is_ok(), "{report:?}");
+
+    let wasm = wat::parse_bytes(include_bytes!("../examples/saxpy/saxpy.wat"))?;
+    let compilations = Compilation::new_all_profiles(config, &wasm)?;
+    assert_eq!(
+        compilations.keys().map(|x| &**x).collect::<Vec<_>>(),
+        ["vulkan1.1-mobile", "vulkan1.2-desktop"]
+    );
+
+    let mobile = &compilations["vulkan1.1-mobile"];
+    let desktop = &compilations["vulkan1.2-desktop"];
+    #[cfg(feature = "spvt-validate")]
+    {
+        mobile.validate()?;
+        desktop.validate()?;
+    }
+
+    assert_eq!(mobile.platform, TargetPlatform::Vulkan(Version::V1_1));
+    assert_eq!(desktop.platform, TargetPlatform::Vulkan(Version::V1_2));
+    assert_eq!(
+        mobile.module()?.header.as_ref().map(|x| x.version()),
+        Some((1, 3))
+    );
+    assert_eq!(
+        desktop.module()?.header.as_ref().map(|x| x.version()),
+        Some((1, 5))
+    );
+    assert!(!mobile.assembly()?.contains("OpCapability Int64"));
+    assert!(desktop.assembly()?.contains("OpCapability Int64"));
+    return Ok(());
+}
+
+#[test]
+fn profile_config() -> color_eyre::Result<()> {
+    let mut config = saxpy()?;
+    let mut bytes = Vec::new();
+    config.serialize_into(&mut bytes)?;
+    let decoded = Config::deserialize_from(&mut &bytes[..])?;
+    assert_eq!(decoded.profiles, config.profiles);
+
+    let profile = config.profile("vulkan1.2-desktop").unwrap();
+    assert_eq!(profile.platform, TargetPlatform::Vulkan(Version::V1_2));
+    assert_eq!(profile.extensions, config.extensions);
+    assert!(profile.profiles.is_empty());
+    assert!(config.profile("opencl").is_none());
+
+    // Only the problems introduced by the profile are reported for it
+    config.profiles.insert(
+        Box::from("opencl"),
+        Profile {
+            platform: Some(TargetPlatform::OpenCL(Version::V1_2)),
+            ..Default::default()
+        },
+    );
+    let report = config.validate();
+    assert!(
+        report
+            .errors
+            .iter()
+            .all(|x| x.starts_with("Profile `opencl`: ")),
+        "{report:?}"
+    );
+    assert!(!report.errors.is_empty());
+    return Ok(());
+}
@@ -0,0 +1,63 @@
+mod common;
+
+use rspirv::{
+    dr::Operand,
+    spirv::{Op, StorageClass},
+};
+
+/// Compiles a function with a storage buffer parameter, returning the number of function variables of the result
+fn function_variables(body: &str) -> color_eyre::Result<usize> {
+    let wat = format!(
+        r#"(module
+  (func (export "main") (param i32)
+    (local $ptr i32)
+    (local $int i32)
+    {body})
+  (memory 1))"#
+    );
+
+    let compilation = common::try_compile(common::config(0, "i32"), &wat)??;
+    let count = compilation
+        .module()?
+        .all_inst_iter()
+        .filter(|instr| {
+            instr.class.opcode == Op::Variable
+                && instr.operands.first() == Some(&Operand::StorageClass(StorageClass::Function))
+        })
+        .count();
+
+    return Ok(count);
+}
+
+#[test]
+fn pointer_local() -> color_eyre::Result<()> {
+    let direct = function_variables("local.get 0 i32.const 7 i32.store")?;
+    let copied =
+        function_variables("local.get 0 local.set $ptr local.get $ptr i32.const 7 i32.store")?;
+
+    // Only the pointer is kept, without an integer shadow
+    assert_eq!(copied, direct + 1);
+    return Ok(());
+}
+
+#[test]
+fn integer_local() -> color_eyre::Result<()> {
+    let direct = function_variables("local.get 0 i32.const 7 i32.store")?;
+    let copied =
+        function_variables("i32.const 7 local.set $int local.get 0 local.get $int i32.store")?;
+
+    assert_eq!(copied, direct + 1);
+    return Ok(());
+}
+
+#[test]
+fn mixed_local() -> color_eyre::Result<()> {
+    let direct = function_variables("local.get 0 i32.const 7 i32.store")?;
+    let mixed = function_variables(
+        "i32.const 0 local.set $ptr local.get 0 local.set $ptr local.get $ptr i32.const 7 i32.store",
+    )?;
+
+    // Locals that may hold both keep a variable for both
+    assert_eq!(mixed, direct + 2);
+    return Ok(());
+}
@@ -0,0 +1,139 @@
+use rspirv::spirv::{ExecutionModel, StorageClass};
+use wasm2spirv::{
+    config::Config,
+    fg::function::ParameterKind,
+    r#type::ScalarType,
+    version::{TargetPlatform, Version},
+    Compilation,
+};
+
+const WAT: &str = r#"
+(module
+  (import "spir_global" "trace_ray"
+    (func $trace_ray (param i32 i32 i32 i32 i32 i32 i32 f32 f32 f32 f32 f32 f32 f32 f32 i32)))
+
+  (func (export "raygen") (param f32)
+    ;; Acceleration structure
+    i32.const 0
+    i32.const 1
+    ;; Flags, cull mask, SBT offset, SBT stride and miss index
+    i32.const 0
+    i32.const 255
+    i32.const 0
+    i32.const 0
+    i32.const 0
+    ;; Origin and tmin
+    f32.const 0
+    f32.const 0
+    f32.const 0
+    f32.const 0.001
+    ;; Direction and tmax
+    f32.const 0
+    f32.const 0
+    f32.const 1
+    f32.const 1000
+    ;; Payload
+    i32.const 0
+    call $trace_ray)
+
+  (func (export "miss") (param f32)
+    f32.const 1
+    local.set 0)
+
+  (func (export "closest_hit") (param i32 f32)
+    local.get 0
+    f32.load
+    local.set 1)
+
+  (memory 1))
+"#;
+
+fn config(platform: TargetPlatform) -> color_eyre::Result<Config> {
+    let config = serde_json::json!({
+        "platform": platform,
+        "addressing_model": "logical",
+        "memory_model": "GLSL450",
+        "capabilities": { "dynamic": [] },
+        "extensions": [],
+        "functions": {
+            "1": {
+                "execution_model": "RayGenerationNV",
+                "params": { "0": { "type": "f32", "kind": { "ray_payload": 0 } } }
+            },
+            "2": {
+                "execution_model": "MissNV",
+                "params": { "0": { "type": "f32", "kind": "incoming_ray_payload" } }
+            },
+            "3": {
+                "execution_model": "ClosestHitNV",
+                "params": {
+                    "0": {
+                        "type": {
+                            "size": "skinny",
+                            "storage_class": "ShaderRecordBufferNV",
+                            "pointee": {
+                                "Struct": [{ "name": "color", "type": "f32", "offset": 0 }]
+                            }
+                        },
+                        "kind": "shader_record_buffer"
+                    },
+                    "1": { "type": "f32", "kind": "incoming_ray_payload" }
+                }
+            }
+        }
+    });
+
+    return Ok(serde_json::from_value(config)?);
+}
+
+#[test]
+fn trace_ray() -> color_eyre::Result<()> {
+    let config = config(TargetPlatform::Vulkan(Version::V1_2))?;
+    let report = config.validate();
+    assert!(report.is_ok(), "{report:?}");
+
+    let compilation = Compilation::new(config, &wat::parse_str(WAT)?)?;
+    #[cfg(feature = "spvt-validate")]
+    compilation.validate()?;
+
+    let assembly = compilation.assembly()?;
+    assert!(assembly.contains("OpCapability RayTracingKHR"));
+    assert!(assembly.contains("OpExtension \"SPV_KHR_ray_tracing\""));
+    assert!(assembly.contains("OpTraceRayKHR"));
+    assert!(assembly.contains("OpTypeAccelerationStructure"));
+    assert!(assembly.contains("Binding 1"));
+    return Ok(());
+}
+
+#[test]
+fn invalid_ray_tracing_config() -> color_eyre::Result<()> {
+    let report = config(TargetPlatform::VK_1_1)?.validate();
+    assert!(
+        report.errors.iter().any(|x| x.contains("SPIR-V 1.4")),
+        "{report:?}"
+    );
+
+    let mut config = Config::for_platform(TargetPlatform::Vulkan(Version::V1_2))?;
+    config
+        .function(0)
+        .set_entry_point(ExecutionModel::RayGenerationKHR)?
+        .param(0)
+        .set_type(ScalarType::F32)?
+        .set_kind(ParameterKind::IncomingRayPayload)?
+        .build()
+        .build();
+
+    let report = config.build()?.validate();
+    assert!(
+        report
+            .errors
+            .iter()
+            .any(|x| x.contains("Parameter 0 of function 0") && x.contains("IncomingRayPayload")),
+        "{report:?}"
+    );
+    assert_eq!(
+        ParameterKind::IncomingRayPayload.storage_class(),
+        StorageClass::IncomingRayPayloadKHR
+    );
+    return Ok(());
+}
@@ -0,0 +1,66 @@
+mod common;
+
+use wasm2spirv::Compilation;
+
+/// Compiles `wat`, whose function `main` is the entry point
+fn compile(wat: &str, main: u32) -> color_eyre::Result<Compilation> {
+    return common::compile(common::config(main, "i32"), wat);
+}
+
+#[test]
+fn reference_handles() -> color_eyre::Result<()> {
+    compile(
+        r#"(module
+  (func $g)
+  (elem declare func $g)
+  (func $is_null (param $r externref) (result i32)
+    (local $f funcref)
+    local.get $f
+    ref.is_null
+    if
+      ref.func $g
+      local.set $f
+    end
+    local.get $r
+    ref.is_null
+    if
+      ref.null func
+      local.set $f
+    end
+    local.get $f
+    ref.is_null)
+  (func (export "main") (param i32)
+    local.get 0
+    ref.null extern
+    call $is_null
+    i32.store)
+  (memory 1))"#,
+        2,
+    )?;
+    return Ok(());
+}
+
+#[test]
+fn typed_select() -> color_eyre::Result<()> {
+    compile(
+        r#"(module
+  (func $g)
+  (func $h)
+  (elem declare func $g $h)
+  (func $pick (param $x i32) (result i32)
+    ref.func $g
+    ref.func $h
+    local.get $x
+    select (result funcref)
+    ref.is_null)
+  (func (export "main") (param i32)
+    local.get 0
+    local.get 0
+    i32.load
+    call $pick
+    i32.store)
+  (memory 1))"#,
+        3,
+    )?;
+    return Ok(());
+}
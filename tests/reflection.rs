@@ -0,0 +1,167 @@
+use rspirv::spirv::{ExecutionMode, ExecutionModel};
+use wasm2spirv::{reflection::DescriptorType, Compilation};
+
+#[test]
+fn saxpy() -> color_eyre::Result<()> {
+    let _ = color_eyre::install();
+
+    let config = serde_json::from_str(include_str!("../examples/saxpy/saxpy.json"))?;
+    let wasm = wat::parse_bytes(include_bytes!("../examples/saxpy/saxpy.wat"))?;
+    let reflection = Compilation::new(config, &wasm)?.reflect()?;
+
+    assert_eq!(reflection.entry_points.len(), 1);
+    let entry_point = &reflection.entry_points[0];
+    assert_eq!(entry_point.execution_model, ExecutionModel::GLCompute);
+    assert!(entry_point
+        .execution_modes
+        .iter()
+        .any(|x| x.mode == ExecutionMode::LocalSize && x.operands == [1, 1, 1]));
+
+    assert_eq!(reflection.descriptor_bindings.len(), 4);
+    for (binding, i) in reflection.descriptor_bindings.iter().zip(0..) {
+        assert_eq!(binding.set, 0);
+        assert_eq!(binding.binding, i);
+        assert_eq!(binding.descriptor_type, DescriptorType::StorageBuffer);
+    }
+
+    assert_eq!(reflection.descriptor_bindings[0].size, Some(4));
+    assert_eq!(
+        reflection.descriptor_bindings[3].runtime_array_stride,
+        Some(4)
+    );
+    return Ok(());
+}
+
+#[test]
+fn fragment() -> color_eyre::Result<()> {
+    let config = serde_json::from_str(include_str!("../examples/fragment/fragment.json"))?;
+    let wasm = wat::parse_bytes(include_bytes!("../examples/fragment/fragment.wat"))?;
+    let reflection = Compilation::new(config, &wasm)?.reflect()?;
+
+    let mut locations = reflection
+        .inputs
+        .iter()
+        .filter_map(|x| x.location)
+        .collect::<Vec<_>>();
+    locations.sort();
+
+    assert_eq!(locations, [0, 1, 2]);
+    return Ok(());
+}
+
+#[test]
+fn fragment_depth_modes() -> color_eyre::Result<()> {
+    let mut config: serde_json::Value =
+        serde_json::from_str(include_str!("../examples/fragment/fragment.json"))?;
+    config["functions"]["1"]["execution_modes"] = serde_json::json!([
+        "origin_upper_left",
+        "depth_replacing",
+        "early_fragment_tests",
+        "depth_less"
+    ]);
+
+    let wasm = wat::parse_bytes(include_bytes!("../examples/fragment/fragment.wat"))?;
+    let compilation = Compilation::new(serde_json::from_value(config)?, &wasm)?;
+    #[cfg(feature = "spvt-validate")]
+    compilation.validate()?;
+
+    let modes = compilation.reflect()?.entry_points[0]
+        .execution_modes
+        .iter()
+        .map(|x| x.mode)
+        .collect::<Vec<_>>();
+    assert_eq!(
+        modes,
+        [
+            ExecutionMode::OriginUpperLeft,
+            ExecutionMode::DepthReplacing,
+            ExecutionMode::EarlyFragmentTests,
+            ExecutionMode::DepthLess
+        ]
+    );
+    return Ok(());
+}
+
+fn compile_stage(
+    execution_model: &str,
+    execution_modes: serde_json::Value,
+) -> color_eyre::Result<Compilation> {
+    let config = serde_json::json!({
+        "platform": { "vulkan": "1.1" },
+        "addressing_model": "logical",
+        "memory_model": "GLSL450",
+        "capabilities": { "dynamic": [] },
+        "extensions": [],
+        "functions": {
+            "0": {
+                "execution_model": execution_model,
+                "execution_modes": execution_modes
+            }
+        }
+    });
+
+    let wasm = wat::parse_str(r#"(module (func (export "main")))"#)?;
+    let compilation = Compilation::new(serde_json::from_value(config)?, &wasm)?;
+    #[cfg(feature = "spvt-validate")]
+    compilation.validate()?;
+    return Ok(compilation);
+}
+
+#[test]
+fn geometry_modes() -> color_eyre::Result<()> {
+    let compilation = compile_stage(
+        "Geometry",
+        serde_json::json!([
+            "triangles",
+            "output_triangle_strip",
+            { "output_vertices": 3 },
+            { "invocations": 2 }
+        ]),
+    )?;
+
+    let reflection = compilation.reflect()?;
+    let entry_point = &reflection.entry_points[0];
+    assert_eq!(entry_point.execution_model, ExecutionModel::Geometry);
+    assert!(entry_point
+        .execution_modes
+        .iter()
+        .any(|x| x.mode == ExecutionMode::OutputVertices && x.operands == [3]));
+    assert!(entry_point
+        .execution_modes
+        .iter()
+        .any(|x| x.mode == ExecutionMode::OutputTriangleStrip));
+    assert!(compilation.assembly()?.contains("OpCapability Geometry"));
+    return Ok(());
+}
+
+#[test]
+fn tessellation_modes() -> color_eyre::Result<()> {
+    let compilation = compile_stage(
+        "TessellationEvaluation",
+        serde_json::json!([
+            "quads",
+            "spacing_fractional_odd",
+            "vertex_order_cw",
+            "point_mode"
+        ]),
+    )?;
+
+    let modes = compilation.reflect()?.entry_points[0]
+        .execution_modes
+        .iter()
+        .map(|x| x.mode)
+        .collect::<Vec<_>>();
+    assert_eq!(
+        modes,
+        [
+            ExecutionMode::Quads,
+            ExecutionMode::SpacingFractionalOdd,
+            ExecutionMode::VertexOrderCw,
+            ExecutionMode::PointMode
+        ]
+    );
+    assert!(compilation
+        .assembly()?
+        .contains("OpCapability Tessellation"));
+    return Ok(());
+}
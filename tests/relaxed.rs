@@ -0,0 +1,52 @@
+mod common;
+
+const WAT: &str = r#"
+(module
+  (func $scale (param f32) (result f32)
+    local.get 0
+    f32.const 2
+    f32.mul)
+  (func (export "main") (param i32)
+    local.get 0
+    local.get 0
+    f32.load
+    call $scale
+    f32.store)
+  (memory 1))
+"#;
+
+fn config() -> serde_json::Value {
+    let mut config = common::config(1, "f32");
+    config["functions"]["0"] = serde_json::json!({
+        "params": {
+            "0": { "kind": "function_parameter", "relaxed_precision": true }
+        },
+        "relaxed_precision": { "return_value": true, "arithmetic": true }
+    });
+    return config;
+}
+
+#[test]
+fn relaxed_precision() -> color_eyre::Result<()> {
+    let compilation = common::compile(config(), WAT)?;
+
+    let assembly = compilation.assembly()?;
+    let decorated = assembly
+        .lines()
+        .filter(|x| x.contains("RelaxedPrecision"))
+        .count();
+
+    // Parameter variable, return value and multiplication
+    assert_eq!(decorated, 3);
+    return Ok(());
+}
+
+#[test]
+fn relaxed_descriptor_set() -> color_eyre::Result<()> {
+    let mut config = config();
+    config["functions"]["1"]["params"]["0"]["relaxed_precision"] = true.into();
+
+    let result = common::try_compile(config, WAT)?;
+    assert!(result.is_err());
+    return Ok(());
+}
@@ -0,0 +1,86 @@
+mod common;
+
+use wasm2spirv::Compilation;
+
+/// Compiles a module whose entry point stores the result of calling `$f` with its argument
+fn compile(f: &str) -> color_eyre::Result<Compilation> {
+    let wat = format!(
+        r#"(module
+  (func $f (param $x i32) (result i32)
+    {f})
+  (func (export "main") (param i32)
+    local.get 0
+    local.get 0
+    i32.load
+    call $f
+    i32.store)
+  (memory 1))"#
+    );
+
+    return common::compile(common::config(1, "i32"), &wat);
+}
+
+#[test]
+fn loop_result() -> color_eyre::Result<()> {
+    compile(
+        r#"
+    loop $continue (result i32)
+      local.get $x
+      i32.const 1
+      i32.add
+      local.tee $x
+      local.get $x
+      i32.const 10
+      i32.lt_u
+      br_if $continue
+    end"#,
+    )?;
+    return Ok(());
+}
+
+#[test]
+fn nested_branch_with_result() -> color_eyre::Result<()> {
+    compile(
+        r#"
+    block $outer (result i32)
+      block $inner
+        local.get $x
+        i32.eqz
+        br_if $inner
+        i32.const 1
+        br $outer
+      end
+      i32.const 2
+    end"#,
+    )?;
+    return Ok(());
+}
+
+#[test]
+fn return_from_block() -> color_eyre::Result<()> {
+    compile(
+        r#"
+    block
+      local.get $x
+      i32.eqz
+      br_if 0
+      i32.const 3
+      return
+    end
+    i32.const 4"#,
+    )?;
+    return Ok(());
+}
+
+#[test]
+fn branch_out_of_function() -> color_eyre::Result<()> {
+    compile(
+        r#"
+    i32.const 5
+    local.get $x
+    br_if 0
+    drop
+    i32.const 6"#,
+    )?;
+    return Ok(());
+}
@@ -0,0 +1,130 @@
+mod common;
+
+use wasm2spirv::Compilation;
+
+/// Compiles a module whose entry point stores the result of calling `$f` with its argument
+fn compile(f: &str) -> color_eyre::Result<Compilation> {
+    let wat = format!(
+        r#"(module
+  (func $f (param $x i32) (result i32)
+    {f})
+  (func (export "main") (param i32)
+    local.get 0
+    local.get 0
+    i32.load
+    call $f
+    i32.store)
+  (memory 1))"#
+    );
+
+    return common::compile(common::config(1, "i32"), &wat);
+}
+
+#[test]
+fn return_from_loop() -> color_eyre::Result<()> {
+    compile(
+        r#"
+    (local $i i32)
+    i32.const 0
+    local.set $i
+    loop $continue
+      local.get $i
+      local.get $x
+      i32.ge_u
+      if
+        local.get $i
+        return
+      end
+      local.get $i
+      i32.const 1
+      i32.add
+      local.set $i
+      br $continue
+    end
+    unreachable"#,
+    )?;
+    return Ok(());
+}
+
+#[test]
+fn return_from_nested_loops() -> color_eyre::Result<()> {
+    compile(
+        r#"
+    (local $i i32)
+    (local $j i32)
+    i32.const 0
+    local.set $i
+    block $done
+      loop $outer
+        i32.const 0
+        local.set $j
+        loop $inner
+          local.get $i
+          local.get $j
+          i32.mul
+          local.get $x
+          i32.eq
+          if
+            local.get $i
+            return
+          end
+          local.get $j
+          i32.const 1
+          i32.add
+          local.tee $j
+          i32.const 8
+          i32.lt_u
+          br_if $inner
+        end
+        local.get $i
+        i32.const 1
+        i32.add
+        local.tee $i
+        i32.const 8
+        i32.ge_u
+        br_if $done
+        br $outer
+      end
+    end
+    i32.const 0"#,
+    )?;
+    return Ok(());
+}
+
+#[test]
+fn return_from_both_branches() -> color_eyre::Result<()> {
+    compile(
+        r#"
+    local.get $x
+    if
+      i32.const 1
+      return
+    else
+      i32.const 2
+      return
+    end
+    unreachable"#,
+    )?;
+    return Ok(());
+}
+
+#[test]
+fn branch_out_of_loop() -> color_eyre::Result<()> {
+    compile(
+        r#"
+    loop $continue
+      local.get $x
+      i32.const 1
+      i32.add
+      local.tee $x
+      local.get $x
+      i32.const 16
+      i32.ge_u
+      br_if 1
+      drop
+      br $continue
+    end
+    unreachable"#,
+    )?;
+    return Ok(());
+}
@@ -0,0 +1,74 @@
+mod common;
+
+use rspirv::{
+    dr::{Instruction, Operand},
+    spirv::{Capability, Op},
+};
+use wasm2spirv::{capabilities::verify_module, Compilation};
+
+const WAT: &str = r#"
+(module
+  (func (export "main") (param i32)
+    local.get 0
+    local.get 0
+    i32.load8_u
+    i32.store)
+  (memory 1))
+"#;
+
+fn compile(wat: &str) -> color_eyre::Result<Compilation> {
+    return Ok(common::try_compile(common::config(0, "i32"), wat)??);
+}
+
+#[test]
+fn verify_roundtrip() -> color_eyre::Result<()> {
+    let compilation = compile(WAT)?;
+    compilation.verify_roundtrip()?;
+
+    // Dropping the declared capabilities is reported
+    let mut module = compilation.module()?.clone();
+    module.capabilities.clear();
+    let err = verify_module(&module).unwrap_err().to_string();
+    assert!(err.contains("Shader"), "{err}");
+
+    // So is declaring a capability without the extension it requires
+    let mut module = compilation.module()?.clone();
+    module.capabilities.push(Instruction::new(
+        Op::Capability,
+        None,
+        None,
+        vec![Operand::Capability(
+            Capability::PhysicalStorageBufferAddresses,
+        )],
+    ));
+    let err = verify_module(&module).unwrap_err().to_string();
+    assert!(err.contains("SPV_KHR_physical_storage_buffer"), "{err}");
+
+    // Unless the extension is already core in the module's version
+    let mut module = compilation.module()?.clone();
+    module.capabilities.push(Instruction::new(
+        Op::Capability,
+        None,
+        None,
+        vec![Operand::Capability(
+            Capability::VariablePointersStorageBuffer,
+        )],
+    ));
+    verify_module(&module)?;
+
+    return Ok(());
+}
+
+#[test]
+fn from_spirv() -> color_eyre::Result<()> {
+    let compilation = compile(WAT)?;
+    let words = compilation.words()?.to_vec();
+
+    let spirv = Compilation::from_spirv(compilation.platform, words.clone())?;
+    assert_eq!(spirv.words()?, &words[..]);
+    assert_eq!(spirv.assembly()?, compilation.assembly()?);
+    spirv.verify_roundtrip()?;
+
+    assert!(Compilation::from_spirv(compilation.platform, vec![0u32; 5]).is_err());
+    return Ok(());
+}
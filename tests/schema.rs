@@ -0,0 +1,178 @@
+//! Compatibility tests for the serde and binary representations of [`Config`].
+//!
+//! `tests/snapshots/config.json` uses every setting of the schema, and `tests/snapshots/config.bin` is its binary
+//! encoding. Run with `WASM2SPIRV_UPDATE_SNAPSHOTS=1` to regenerate the binary fixture after an intended change to
+//! the format, which must come with a new format version. Blobs written by previous versions of the format are kept
+//! as `tests/snapshots/config.v<version>.bin`, and must still be decoded.
+
+use std::{fs, path::Path};
+use wasm2spirv::{
+    binary::{deserialize::BinaryDeserialize, serialize::BinarySerialize, FORMAT_VERSION, MAGIC},
+    config::{Config, CONFIG_SCHEMA_VERSION},
+};
+
+const UPDATE_VAR: &str = "WASM2SPIRV_UPDATE_SNAPSHOTS";
+
+fn fixture(name: &str) -> std::path::PathBuf {
+    return Path::new(env!("CARGO_MANIFEST_DIR"))
+        .join("tests")
+        .join("snapshots")
+        .join(name);
+}
+
+fn to_binary(config: &Config) -> color_eyre::Result<Vec<u8>> {
+    let mut bytes = Vec::new();
+    config.serialize_into(&mut bytes)?;
+    return Ok(bytes);
+}
+
+#[test]
+fn json_roundtrip() -> color_eyre::Result<()> {
+    let json: serde_json::Value =
+        serde_json::from_str(&fs::read_to_string(fixture("config.json"))?)?;
+    let config: Config = serde_json::from_value(json.clone())?;
+
+    let serialized = serde_json::to_value(&config)?;
+    let config: Config = serde_json::from_value(serialized.clone())?;
+    assert_eq!(serialized, serde_json::to_value(&config)?);
+
+    // Every setting of the fixture is kept
+    assert_eq!(
+        serialized["functions"]["0"]["params"]["0"]["type"],
+        json["functions"]["0"]["params"]["0"]["type"]
+    );
+    assert_eq!(serialized["trap_mode"], json["trap_mode"]);
+    assert_eq!(serialized["globals"], json["globals"]);
+    return Ok(());
+}
+
+#[test]
+fn binary_roundtrip() -> color_eyre::Result<()> {
+    let config: Config = serde_json::from_str(&fs::read_to_string(fixture("config.json"))?)?;
+    let bytes = to_binary(&config)?;
+    let decoded = Config::deserialize_from(&mut &bytes[..])?;
+
+    assert_eq!(
+        serde_json::to_value(&config)?,
+        serde_json::to_value(&decoded)?
+    );
+    return Ok(());
+}
+
+#[test]
+fn binary_fixture() -> color_eyre::Result<()> {
+    let config: Config = serde_json::from_str(&fs::read_to_string(fixture("config.json"))?)?;
+    let actual = to_binary(&config)?;
+
+    let path = fixture("config.bin");
+    let update = std::env::var_os(UPDATE_VAR).is_some_and(|x| x != "0");
+    match fs::read(&path) {
+        Ok(expected) if expected == actual => {}
+        Ok(_) if update => fs::write(&path, &actual)?,
+        Ok(expected) => {
+            // The fixture must still be readable, even if the encoding drifted
+            let decoded = Config::deserialize_from(&mut &expected[..])?;
+            assert_eq!(
+                serde_json::to_value(&config)?,
+                serde_json::to_value(&decoded)?
+            );
+            panic!(
+                "the binary encoding of {} changed. Rerun with {UPDATE_VAR}=1 if this is intended",
+                path.display()
+            );
+        }
+        Err(e) if e.kind() == std::io::ErrorKind::NotFound => fs::write(&path, &actual)?,
+        Err(e) => return Err(e.into()),
+    }
+
+    return Ok(());
+}
+
+#[test]
+fn examples() -> color_eyre::Result<()> {
+    let root = Path::new(env!("CARGO_MANIFEST_DIR")).join("examples");
+    for entry in fs::read_dir(root)? {
+        let path = entry?.path();
+        let Some(name) = path.file_name().and_then(|x| x.to_str()) else {
+            continue;
+        };
+
+        let json = path.join(format!("{name}.json"));
+        if !json.is_file() {
+            continue;
+        }
+
+        // Configurations written before the schema was versioned
+        let config: Config = serde_json::from_str(&fs::read_to_string(&json)?)?;
+        assert_eq!(config.schema, 1, "{}", json.display());
+
+        let bytes = to_binary(&config)?;
+        let decoded = Config::deserialize_from(&mut &bytes[..])?;
+        assert_eq!(
+            serde_json::to_value(&config)?,
+            serde_json::to_value(&decoded)?,
+            "{}",
+            json.display()
+        );
+    }
+
+    return Ok(());
+}
+
+#[test]
+fn newer_schema() -> color_eyre::Result<()> {
+    let mut json: serde_json::Value =
+        serde_json::from_str(&fs::read_to_string(fixture("config.json"))?)?;
+    json["schema"] = (CONFIG_SCHEMA_VERSION + 1).into();
+    let error = serde_json::from_value::<Config>(json).unwrap_err();
+    assert!(error.to_string().contains("schema version"), "{error}");
+
+    let config: Config = serde_json::from_str(&fs::read_to_string(fixture("config.json"))?)?;
+    let mut bytes = to_binary(&config)?;
+    bytes[8..12].copy_from_slice(&(CONFIG_SCHEMA_VERSION + 1).to_le_bytes());
+
+    let Err(error) = Config::deserialize_from(&mut &bytes[..]) else {
+        panic!("a newer schema should be rejected");
+    };
+    assert!(error.to_string().contains("schema version"), "{error:?}");
+    return Ok(());
+}
+
+#[test]
+fn previous_formats() -> color_eyre::Result<()> {
+    let config: Config = serde_json::from_str(&fs::read_to_string(fixture("config.json"))?)?;
+    for version in 1..FORMAT_VERSION {
+        let bytes = fs::read(fixture(&format!("config.v{version}.bin")))?;
+        // The first version had no header
+        assert_eq!(bytes[..4] == MAGIC, version > 1);
+
+        let decoded = Config::deserialize_from(&mut &bytes[..])?;
+        assert_eq!(
+            serde_json::to_value(&config)?,
+            serde_json::to_value(&decoded)?,
+            "format version {version}"
+        );
+    }
+
+    return Ok(());
+}
+
+#[test]
+fn header() -> color_eyre::Result<()> {
+    let config: Config = serde_json::from_str(&fs::read_to_string(fixture("config.json"))?)?;
+    let mut bytes = to_binary(&config)?;
+    assert_eq!(bytes[..4], MAGIC);
+    assert_eq!(bytes[4..8], FORMAT_VERSION.to_le_bytes());
+
+    bytes[4..8].copy_from_slice(&(FORMAT_VERSION + 1).to_le_bytes());
+    let Err(error) = Config::deserialize_from(&mut &bytes[..]) else {
+        panic!("a newer format should be rejected");
+    };
+    assert!(error.to_string().contains("newer wasm2spirv"), "{error:?}");
+
+    let Err(error) = Config::deserialize_from(&mut &b"\0asm\x01\0\0\0"[..]) else {
+        panic!("other binaries should be rejected");
+    };
+    assert!(error.to_string().contains("Not a wasm2spirv"), "{error:?}");
+    return Ok(());
+}
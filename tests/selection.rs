@@ -0,0 +1,56 @@
+mod common;
+
+use wasm2spirv::{
+    config::{Config, SelectionHint},
+    Compilation,
+};
+
+const WAT: &str = r#"
+(module
+  (func (export "main") (param i32)
+    local.get 0
+    local.get 0
+    f32.load
+    f32.const 1
+    f32.min
+    f32.store)
+  (memory 1))
+"#;
+
+fn compile(
+    global: Option<SelectionHint>,
+    function: Option<SelectionHint>,
+) -> color_eyre::Result<String> {
+    let mut config: Config = serde_json::from_value(common::config(0, "f32"))?;
+    config.selection_control = global;
+    if let Some(f) = config.functions.get_mut(&0) {
+        f.selection_control = function;
+    }
+
+    let wasm = wat::parse_str(WAT)?;
+    return Ok(Compilation::new(config, &wasm)?.into_assembly()?);
+}
+
+fn selection_controls(assembly: &str) -> Vec<&str> {
+    return assembly
+        .lines()
+        .filter(|x| x.contains("OpSelectionMerge"))
+        .filter_map(|x| x.split_whitespace().last())
+        .collect();
+}
+
+#[test]
+fn selection_hints() -> color_eyre::Result<()> {
+    let assembly = compile(None, None)?;
+    assert_eq!(selection_controls(&assembly), ["Flatten"]);
+
+    let assembly = compile(Some(SelectionHint::DontFlatten), None)?;
+    assert_eq!(selection_controls(&assembly), ["DontFlatten"]);
+
+    let assembly = compile(
+        Some(SelectionHint::DontFlatten),
+        Some(SelectionHint::Flatten),
+    )?;
+    assert_eq!(selection_controls(&assembly), ["Flatten"]);
+    return Ok(());
+}
@@ -0,0 +1,57 @@
+mod common;
+
+use wasm2spirv::Compilation;
+
+/// Increments the first element of the buffer, spilling it into the shadow stack like LLVM's unoptimized code does
+const WAT: &str = r#"(module
+  (global $__stack_pointer (mut i32) (i32.const 65536))
+  (func (export "main") (param i32)
+    (local $frame i32)
+    global.get $__stack_pointer
+    i32.const 16
+    i32.sub
+    local.tee $frame
+    global.set $__stack_pointer
+    local.get $frame
+    local.get 0
+    i32.load
+    i32.store offset=12
+    local.get $frame
+    f32.const 1
+    f32.store offset=4
+    local.get 0
+    local.get $frame
+    i32.load offset=12
+    local.get $frame
+    f32.load offset=4
+    i32.trunc_f32_s
+    i32.add
+    i32.store
+    local.get $frame
+    i32.const 16
+    i32.add
+    global.set $__stack_pointer)
+  (memory 1))"#;
+
+fn compile(wat: &str) -> color_eyre::Result<Compilation> {
+    return common::compile(common::config(0, "i32"), wat);
+}
+
+#[test]
+fn frame_slots() -> color_eyre::Result<()> {
+    let assembly = compile(WAT)?.into_assembly()?;
+
+    // Only the parameter's buffer, the memory isn't accessed
+    let buffers = assembly
+        .lines()
+        .filter(|x| x.contains("OpVariable") && x.ends_with(" StorageBuffer"))
+        .count();
+    assert_eq!(buffers, 1, "{assembly}");
+
+    let slots = assembly
+        .lines()
+        .filter(|x| x.contains("OpVariable") && x.ends_with(" Function"))
+        .count();
+    assert!(slots >= 2, "{assembly}");
+    return Ok(());
+}
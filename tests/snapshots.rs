@@ -0,0 +1,108 @@
+//! Golden-file tests for the translator.
+//!
+//! Every `examples/<name>/<name>.wat` + `examples/<name>/<name>.json` pair is compiled, and its disassembly
+//! compared against `tests/snapshots/<name>.spvasm`. Run with `WASM2SPIRV_UPDATE_SNAPSHOTS=1` to regenerate
+//! the snapshots after an intended change to the output.
+
+use std::{fs, path::Path};
+use wasm2spirv::Compilation;
+
+const UPDATE_VAR: &str = "WASM2SPIRV_UPDATE_SNAPSHOTS";
+
+#[test]
+fn snapshots() -> color_eyre::Result<()> {
+    let _ = color_eyre::install();
+
+    let root = Path::new(env!("CARGO_MANIFEST_DIR"));
+    let snapshots = root.join("tests").join("snapshots");
+    let update = std::env::var_os(UPDATE_VAR).is_some_and(|x| x != "0");
+
+    let mut fixtures = fs::read_dir(root.join("examples"))?
+        .map(|entry| Ok(entry?.path()))
+        .collect::<std::io::Result<Vec<_>>>()?;
+    fixtures.sort();
+
+    let mut failures = Vec::new();
+    for fixture in fixtures {
+        let name = match fixture.file_name().and_then(|x| x.to_str()) {
+            Some(name) => name.to_string(),
+            None => continue,
+        };
+
+        let wat = fixture.join(format!("{name}.wat"));
+        let json = fixture.join(format!("{name}.json"));
+        if !wat.is_file() || !json.is_file() {
+            continue;
+        }
+
+        let config = serde_json::from_str(&fs::read_to_string(json)?)?;
+        let wasm = wat::parse_file(wat)?;
+        let actual = match Compilation::new(config, &wasm).and_then(|x| x.into_assembly()) {
+            Ok(assembly) => assembly,
+            Err(e) => {
+                failures.push(format!("{name}: compilation failed: {e}"));
+                continue;
+            }
+        };
+
+        let snapshot = snapshots.join(format!("{name}.spvasm"));
+        match fs::read_to_string(&snapshot) {
+            Ok(expected) if expected == actual => {}
+            Ok(_) if update => fs::write(&snapshot, actual)?,
+            Ok(expected) => failures.push(format!(
+                "{name}: output differs from {}\n{}",
+                snapshot.display(),
+                diff(&expected, &actual)
+            )),
+            Err(e) if e.kind() == std::io::ErrorKind::NotFound => {
+                // New fixtures get their snapshot recorded on the first run
+                fs::create_dir_all(&snapshots)?;
+                fs::write(&snapshot, actual)?;
+                println!("{name}: recorded new snapshot at {}", snapshot.display());
+            }
+            Err(e) => return Err(e.into()),
+        }
+    }
+
+    if !failures.is_empty() {
+        color_eyre::eyre::bail!(
+            "{} snapshot(s) failed (set {UPDATE_VAR}=1 to regenerate):\n\n{}",
+            failures.len(),
+            failures.join("\n\n")
+        );
+    }
+
+    return Ok(());
+}
+
+/// Line-by-line listing of the first differences between both outputs
+fn diff(expected: &str, actual: &str) -> String {
+    const MAX_LINES: usize = 20;
+
+    let mut expected_lines = expected.lines();
+    let mut actual_lines = actual.lines();
+    let mut result = Vec::new();
+
+    for line in 1.. {
+        match (expected_lines.next(), actual_lines.next()) {
+            (None, None) => break,
+            (x, y) if x == y => continue,
+            (x, y) => {
+                result.push(format!("  line {line}:"));
+                if let Some(x) = x {
+                    result.push(format!("  - {x}"));
+                }
+                if let Some(y) = y {
+                    result.push(format!("  + {y}"));
+                }
+            }
+        }
+
+        if result.len() >= MAX_LINES {
+            result.push(String::from("  ..."));
+            break;
+        }
+    }
+
+    return result.join("\n");
+}
@@ -0,0 +1,57 @@
+mod common;
+
+use rspirv::{
+    dr::Operand,
+    spirv::{Decoration, Op},
+};
+
+const WAT: &str = r#"
+(module
+  (func $helper (param i32) (result i32)
+    local.get 0
+    i32.const 1
+    i32.add)
+  (func (export "first") (param i32)
+    local.get 0
+    local.get 0
+    i32.load
+    call $helper
+    i32.store)
+  (memory 1)
+  (func (export "second")))
+"#;
+
+#[test]
+fn split_entry_points() -> color_eyre::Result<()> {
+    let mut config = common::config(1, "i32");
+    config["functions"]["2"] = common::compute_shader();
+    let compile = common::try_compile(config, WAT)??;
+
+    let split = compile.split_entry_points()?;
+    assert_eq!(split.len(), 2);
+
+    let (name, first) = &split[0];
+    assert_eq!(name, "first");
+    let first = first.module()?;
+    assert_eq!(first.entry_points.len(), 1);
+    assert_eq!(first.execution_modes.len(), 1);
+    assert_eq!(first.functions.len(), 2);
+    assert!(!first.annotations.is_empty());
+
+    let (name, second) = &split[1];
+    assert_eq!(name, "second");
+    let second = second.module()?;
+    assert_eq!(second.entry_points.len(), 1);
+    assert_eq!(second.execution_modes.len(), 1);
+    assert_eq!(second.functions.len(), 1);
+    assert!(second.annotations.iter().all(|x| !matches!(
+        x.operands.get(1),
+        Some(Operand::Decoration(Decoration::DescriptorSet))
+    )));
+    assert!(second
+        .types_global_values
+        .iter()
+        .all(|x| x.class.opcode != Op::Variable));
+
+    return Ok(());
+}
@@ -0,0 +1,41 @@
+#![cfg(feature = "spvc-glsl")]
+
+use wasm2spirv::{
+    compilers::{CrossBackend, GlslOptions},
+    Compilation,
+};
+
+fn saxpy() -> color_eyre::Result<Compilation> {
+    let config = serde_json::from_str(include_str!("../examples/saxpy/saxpy.json"))?;
+    let wasm = wat::parse_bytes(include_bytes!("../examples/saxpy/saxpy.wat"))?;
+    return Ok(Compilation::new(config, &wasm)?);
+}
+
+#[test]
+fn vulkan_semantics() -> color_eyre::Result<()> {
+    let compile = saxpy()?;
+    let spvc = compile.cross_compiler(CrossBackend::SpirvCross);
+
+    // Vulkan platforms default to Vulkan GLSL, which keeps the descriptor sets
+    let vulkan = spvc.glsl()?;
+    assert!(vulkan.contains("set = 0"), "{vulkan}");
+    assert_eq!(vulkan, compile.spvc_glsl()?);
+
+    let opengl = spvc.glsl_with(&GlslOptions {
+        vulkan_semantics: Some(false),
+        ..Default::default()
+    })?;
+    assert!(!opengl.contains("set = 0"), "{opengl}");
+    return Ok(());
+}
+
+#[cfg(feature = "naga-glsl")]
+#[test]
+fn both_backends() -> color_eyre::Result<()> {
+    let compile = saxpy()?;
+    for backend in [CrossBackend::Naga, CrossBackend::SpirvCross] {
+        let glsl = compile.cross_compiler(backend).glsl()?;
+        assert!(glsl.starts_with("#version 450"), "{backend:?}: {glsl}");
+    }
+    return Ok(());
+}
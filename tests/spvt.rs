@@ -0,0 +1,47 @@
+#![cfg(feature = "spirv-tools")]
+
+use wasm2spirv::{
+    compilers::spvt::{OptimizationPreset, OptimizationStats},
+    Compilation,
+};
+
+fn saxpy() -> color_eyre::Result<Compilation> {
+    let config = serde_json::from_str(include_str!("../examples/saxpy/saxpy.json"))?;
+    let wasm = wat::parse_bytes(include_bytes!("../examples/saxpy/saxpy.wat"))?;
+    return Ok(Compilation::new(config, &wasm)?);
+}
+
+fn check_stats(
+    input: &Compilation,
+    (result, stats): &(Compilation, OptimizationStats),
+) -> color_eyre::Result<()> {
+    assert_eq!(
+        stats.instructions_before,
+        input.module()?.all_inst_iter().count()
+    );
+    assert_eq!(stats.words_before, input.words()?.len());
+    assert_eq!(
+        stats.instructions_after,
+        result.module()?.all_inst_iter().count()
+    );
+    assert_eq!(stats.words_after, result.words()?.len());
+    return Ok(());
+}
+
+#[test]
+fn optimization_stats() -> color_eyre::Result<()> {
+    let compilation = saxpy()?;
+
+    let performance = compilation.optimized()?;
+    assert_eq!(performance.1.preset, OptimizationPreset::Performance);
+    check_stats(&compilation, &performance)?;
+
+    let size = compilation.optimized_for_size()?;
+    assert_eq!(size.1.preset, OptimizationPreset::Size);
+    check_stats(&compilation, &size)?;
+
+    // Same module as the performance preset, without the statistics
+    let optimized = saxpy()?.into_optimized()?;
+    assert_eq!(optimized.words()?, performance.0.words()?);
+    return Ok(());
+}
@@ -0,0 +1,62 @@
+mod common;
+
+use rspirv::spirv::Op;
+use wasm2spirv::Compilation;
+
+const WAT: &str = r#"
+(module
+  (global $scale (mut f32) (f32.const 1))
+  (func $init
+    f32.const 2
+    global.set $scale)
+  (func (export "main") (param i32)
+    local.get 0
+    global.get $scale
+    f32.store)
+  (memory 1)
+  (start $init))
+"#;
+
+fn compile(wat: &str) -> color_eyre::Result<Compilation> {
+    return common::compile(common::config(1, "f32"), wat);
+}
+
+#[test]
+fn start_function() -> color_eyre::Result<()> {
+    let compilation = compile(WAT)?;
+    let module = compilation.module()?;
+
+    let entry_point = module.entry_points[0].operands[1].unwrap_id_ref();
+    let main = module
+        .functions
+        .iter()
+        .find(|x| x.def_id() == Some(entry_point))
+        .unwrap();
+
+    // The start function is called before anything else
+    let first = main.blocks[0]
+        .instructions
+        .iter()
+        .find(|x| x.class.opcode != Op::Variable)
+        .unwrap();
+    assert_eq!(first.class.opcode, Op::FunctionCall);
+
+    // Globals set by the start function live in each invocation's private storage
+    assert!(compilation
+        .assembly()?
+        .lines()
+        .any(|x| x.contains("OpVariable") && x.contains("Private")));
+
+    // Without a start section, nothing gets called and the global stays a constant
+    let wat = WAT
+        .replace("(start $init)", "")
+        .replace("global.set $scale", "drop");
+    let compilation = compile(&wat)?;
+    assert!(!compilation
+        .module()?
+        .all_inst_iter()
+        .any(|x| x.class.opcode == Op::FunctionCall));
+    assert!(!compilation.assembly()?.contains("Private"));
+
+    return Ok(());
+}
@@ -0,0 +1,86 @@
+use rspirv::spirv::{Capability, ExecutionModel};
+use wasm2spirv::{stats::OpcodeClass, Compilation};
+
+fn saxpy() -> color_eyre::Result<Compilation> {
+    let config = serde_json::from_str(include_str!("../examples/saxpy/saxpy.json"))?;
+    let wasm = wat::parse_bytes(include_bytes!("../examples/saxpy/saxpy.wat"))?;
+    return Ok(Compilation::new(config, &wasm)?);
+}
+
+#[test]
+fn saxpy_stats() -> color_eyre::Result<()> {
+    let compilation = saxpy()?;
+    let stats = compilation.stats()?;
+    let module = compilation.module()?;
+
+    assert_eq!(stats.instructions, module.all_inst_iter().count());
+    assert_eq!(stats.classes.values().sum::<usize>(), stats.instructions);
+    assert!(stats.classes[&OpcodeClass::Type] > 0);
+    assert!(stats.classes[&OpcodeClass::ControlFlow] > 0);
+    assert!(stats.constants > 0);
+    assert!(stats.id_bound as usize > stats.constants);
+    assert!(stats.capabilities.contains(&Capability::Shader));
+
+    assert_eq!(stats.entry_points.len(), 1);
+    let entry_point = &stats.entry_points[0];
+    assert_eq!(entry_point.execution_model, ExecutionModel::GLCompute);
+    assert!(entry_point.instructions > 0);
+    assert!(entry_point.instructions < stats.instructions);
+
+    assert_eq!(stats.bindings.len(), 4);
+    return Ok(());
+}
+
+#[test]
+fn display() -> color_eyre::Result<()> {
+    let stats = saxpy()?.stats()?;
+    let display = stats.to_string();
+
+    assert!(display.starts_with(&format!("{} instructions", stats.instructions)));
+    assert!(display.contains("control flow"), "{display}");
+    assert!(display.contains("Shader"), "{display}");
+    assert!(
+        display.contains(&format!("{} (GLCompute)", stats.entry_points[0].name)),
+        "{display}"
+    );
+    assert!(
+        display.contains("set 0, binding 3: storage buffer"),
+        "{display}"
+    );
+    return Ok(());
+}
+
+#[test]
+fn opcode_classes() {
+    use rspirv::spirv::Op;
+
+    assert_eq!(OpcodeClass::of(Op::TypeInt), OpcodeClass::Type);
+    assert_eq!(OpcodeClass::of(Op::SpecConstantOp), OpcodeClass::Constant);
+    assert_eq!(OpcodeClass::of(Op::Bitcast), OpcodeClass::Conversion);
+    assert_eq!(OpcodeClass::of(Op::FMul), OpcodeClass::Arithmetic);
+    assert_eq!(OpcodeClass::of(Op::Select), OpcodeClass::Relational);
+    assert_eq!(OpcodeClass::of(Op::BitCount), OpcodeClass::Bit);
+    assert_eq!(OpcodeClass::of(Op::AtomicIAdd), OpcodeClass::Atomic);
+    assert_eq!(
+        OpcodeClass::of(Op::CompositeExtract),
+        OpcodeClass::Composite
+    );
+    assert_eq!(OpcodeClass::of(Op::Nop), OpcodeClass::Other);
+}
+
+#[test]
+fn timings() -> color_eyre::Result<()> {
+    let timings = saxpy()?.timings();
+    assert!(timings.parse_ms > 0.0, "{timings:?}");
+    assert!(timings.emit_ms > 0.0, "{timings:?}");
+    assert_eq!(
+        timings.total_ms(),
+        timings.parse_ms + timings.fg_ms + timings.emit_ms
+    );
+
+    // Compilations of existing SPIR-V don't go through any phase
+    let compilation = saxpy()?;
+    let compilation = Compilation::from_spirv(compilation.platform, compilation.into_words()?)?;
+    assert_eq!(compilation.timings().total_ms(), 0.0);
+    return Ok(());
+}
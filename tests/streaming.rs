@@ -0,0 +1,74 @@
+use wasm2spirv::Compilation;
+
+#[test]
+fn streaming() -> color_eyre::Result<()> {
+    let _ = color_eyre::install();
+
+    let config = include_str!("../examples/saxpy/saxpy.json");
+    let wasm = wat::parse_bytes(include_bytes!("../examples/saxpy/saxpy.wat"))?;
+    let compile = Compilation::new(serde_json::from_str(config)?, &wasm)?;
+
+    let mut builder = Compilation::builder(serde_json::from_str(config)?);
+    for chunk in wasm.chunks(7) {
+        builder.push_bytes(chunk)?;
+    }
+    let streamed = builder.finish()?;
+
+    assert_eq!(compile.words()?, streamed.words()?);
+    return Ok(());
+}
+
+#[test]
+fn incomplete() -> color_eyre::Result<()> {
+    let config = include_str!("../examples/saxpy/saxpy.json");
+    let wasm = wat::parse_bytes(include_bytes!("../examples/saxpy/saxpy.wat"))?;
+
+    let mut builder = Compilation::builder(serde_json::from_str(config)?);
+    builder.push_bytes(&wasm[..wasm.len() / 2])?;
+    assert!(builder.finish().is_err());
+    return Ok(());
+}
+
+#[test]
+fn borrowed() -> color_eyre::Result<()> {
+    let config: wasm2spirv::config::Config =
+        serde_json::from_str(include_str!("../examples/saxpy/saxpy.json"))?;
+    let wasm = wat::parse_bytes(include_bytes!("../examples/saxpy/saxpy.wat"))?;
+
+    let borrowed = Compilation::new_borrowed(config.clone(), &wasm)?;
+    assert!(borrowed.module_builder().is_some());
+
+    let owned = Compilation::new(config, &wasm)?;
+    assert_eq!(borrowed.words()?, owned.words()?);
+    assert!(borrowed.module_builder().is_none());
+    return Ok(());
+}
+
+#[test]
+fn write_to() -> color_eyre::Result<()> {
+    let config = include_str!("../examples/saxpy/saxpy.json");
+    let wasm = wat::parse_bytes(include_bytes!("../examples/saxpy/saxpy.wat"))?;
+    let compilation = Compilation::new(serde_json::from_str(config)?, &wasm)?;
+
+    // Written one instruction at a time
+    let mut streamed = Vec::new();
+    compilation.write_to(&mut streamed)?;
+
+    let expected = compilation
+        .words()?
+        .iter()
+        .flat_map(|x| x.to_le_bytes())
+        .collect::<Vec<_>>();
+    assert_eq!(streamed, expected);
+
+    // Written from the assembled words
+    let mut written = Vec::new();
+    compilation.write_to(&mut written)?;
+    assert_eq!(written, expected);
+
+    let path = std::env::temp_dir().join("wasm2spirv-write-test.spv");
+    compilation.write_to_file(&path)?;
+    assert_eq!(std::fs::read(&path)?, expected);
+    std::fs::remove_file(path)?;
+    return Ok(());
+}
@@ -0,0 +1,209 @@
+mod common;
+
+use wasm2spirv::Compilation;
+
+fn compile(body: &str) -> color_eyre::Result<Compilation> {
+    let wat = format!(
+        r#"(module
+  (func (export "main") (param i32)
+    (local $i i32)
+    (local $j i32)
+    i32.const 0
+    local.set $i
+    i32.const 0
+    local.set $j
+    {body})
+  (memory 1))"#
+    );
+
+    return common::compile(common::config(0, "i32"), &wat);
+}
+
+#[test]
+fn simple() -> color_eyre::Result<()> {
+    compile(
+        r#"
+    block $exit
+      loop $continue
+        local.get $i
+        i32.const 8
+        i32.ge_u
+        br_if $exit
+        local.get 0
+        local.get $i
+        i32.store
+        local.get $i
+        i32.const 1
+        i32.add
+        local.set $i
+        br $continue
+      end
+    end"#,
+    )?;
+    return Ok(());
+}
+
+#[test]
+fn multiple_back_edges() -> color_eyre::Result<()> {
+    compile(
+        r#"
+    block $exit
+      loop $continue
+        local.get $i
+        i32.const 1
+        i32.add
+        local.set $i
+        local.get $i
+        i32.const 3
+        i32.lt_u
+        br_if $continue
+        local.get $i
+        i32.const 8
+        i32.ge_u
+        br_if $exit
+        local.get 0
+        local.get $i
+        i32.store
+        br $continue
+      end
+    end"#,
+    )?;
+    return Ok(());
+}
+
+#[test]
+fn exits_and_fallthrough() -> color_eyre::Result<()> {
+    compile(
+        r#"
+    block $exit
+      loop $continue
+        local.get $i
+        i32.const 8
+        i32.ge_u
+        br_if $exit
+        local.get $i
+        i32.const 1
+        i32.add
+        local.set $i
+        local.get $i
+        i32.const 4
+        i32.ne
+        br_if $continue
+        local.get 0
+        local.get $i
+        i32.store
+      end
+    end"#,
+    )?;
+    return Ok(());
+}
+
+#[test]
+fn nested_loops() -> color_eyre::Result<()> {
+    compile(
+        r#"
+    block $outer_exit
+      loop $outer
+        block $inner_exit
+          loop $inner
+            local.get $j
+            i32.const 4
+            i32.ge_u
+            br_if $inner_exit
+            local.get $j
+            local.get $i
+            i32.mul
+            i32.const 10
+            i32.gt_u
+            br_if $outer_exit
+            local.get $j
+            i32.const 1
+            i32.add
+            local.set $j
+            br $inner
+          end
+        end
+        local.get $i
+        i32.const 1
+        i32.add
+        local.tee $i
+        i32.const 4
+        i32.lt_u
+        br_if $outer
+      end
+    end
+    local.get 0
+    local.get $i
+    i32.store"#,
+    )?;
+    return Ok(());
+}
+
+#[test]
+fn nested_blocks() -> color_eyre::Result<()> {
+    compile(
+        r#"
+    block $a
+      block $b
+        local.get 0
+        i32.load
+        br_if $a
+        local.get 0
+        i32.const 4
+        i32.add
+        i32.load
+        br_if $b
+        local.get 0
+        i32.const 1
+        i32.store
+      end
+      local.get 0
+      i32.const 2
+      i32.store
+    end"#,
+    )?;
+    return Ok(());
+}
+
+#[test]
+fn new_blocks_keep_operators() -> color_eyre::Result<()> {
+    let compilation = compile(
+        r#"
+    block $exit
+      loop $continue
+        local.get $i
+        i32.const 1
+        i32.add
+        local.set $i
+        local.get $i
+        i32.const 3
+        i32.lt_u
+        br_if $continue
+        local.get $i
+        i32.const 8
+        i32.ge_u
+        br_if $exit
+        br $continue
+      end
+    end
+    local.get 0
+    local.get $i
+    i32.const 2
+    i32.add
+    i32.store"#,
+    )?;
+
+    // Operations after the continue block added by the structurization are still attributed to their operators
+    let adds = compilation
+        .module()?
+        .all_inst_iter()
+        .filter(|x| x.class.opcode == rspirv::spirv::Op::IAdd)
+        .filter_map(|x| x.result_id)
+        .collect::<Vec<_>>();
+    assert_eq!(adds.len(), 2);
+    for add in adds {
+        let origin = compilation.origin(add).expect("origin is tracked");
+        assert!(origin.operator.is_some(), "{origin:?}");
+    }
+    return Ok(());
+}
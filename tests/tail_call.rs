@@ -0,0 +1,76 @@
+mod common;
+
+use wasm2spirv::Compilation;
+
+fn compile(functions: &str) -> color_eyre::Result<Compilation> {
+    let wat = format!(
+        r#"(module
+  (type $unary (func (param i32) (result i32)))
+  {functions}
+  (func (export "main") (param i32)
+    local.get 0
+    local.get 0
+    i32.load
+    call $f
+    i32.store)
+  (memory 1))"#
+    );
+
+    let mut config = common::config(2, "i32");
+    config["features"] = serde_json::json!({
+        "memory64": false,
+        "saturating_float_to_int": false,
+        "tail_call": true
+    });
+    return common::compile(config, &wat);
+}
+
+#[test]
+fn return_call() -> color_eyre::Result<()> {
+    let compilation = compile(
+        r#"
+  (func $double (param $x i32) (result i32)
+    local.get $x
+    i32.const 2
+    i32.mul)
+  (func $f (param $x i32) (result i32)
+    local.get $x
+    i32.eqz
+    if
+      i32.const 0
+      return
+    end
+    local.get $x
+    return_call $double)"#,
+    )?;
+
+    let assembly = compilation.assembly()?;
+    assert!(assembly.contains("OpFunctionCall"), "{assembly}");
+    return Ok(());
+}
+
+#[test]
+fn return_call_indirect() -> color_eyre::Result<()> {
+    let Err(error) = compile(
+        r#"
+  (table 1 funcref)
+  (func $double (param $x i32) (result i32)
+    local.get $x
+    i32.const 2
+    i32.mul)
+  (func $f (param $x i32) (result i32)
+    local.get $x
+    i32.const 0
+    return_call_indirect (type $unary))"#,
+    ) else {
+        panic!("indirect tail calls should be rejected");
+    };
+
+    assert!(
+        error
+            .to_string()
+            .contains("Indirect calls aren't supported"),
+        "{error:?}"
+    );
+    return Ok(());
+}
@@ -0,0 +1,50 @@
+#![cfg(feature = "testing")]
+
+use wasm2spirv::{testing, version::TargetPlatform, Compilation};
+
+fn bytes<T: Copy>(values: &[T]) -> Vec<u8> {
+    return values
+        .iter()
+        .flat_map(|x| unsafe {
+            std::slice::from_raw_parts((x as *const T).cast::<u8>(), std::mem::size_of::<T>())
+        })
+        .copied()
+        .collect();
+}
+
+fn saxpy() -> color_eyre::Result<Compilation> {
+    let config = serde_json::from_str(include_str!("../examples/saxpy/saxpy.json"))?;
+    let wasm = wat::parse_bytes(include_bytes!("../examples/saxpy/saxpy.wat"))?;
+    return Ok(Compilation::new(config, &wasm)?);
+}
+
+#[test]
+fn execute_saxpy() -> color_eyre::Result<()> {
+    if !testing::is_available() {
+        eprintln!("No Vulkan device available, skipping");
+        return Ok(());
+    }
+
+    let compilation = saxpy()?;
+    let x = bytes(&[1f32, 2.0, 3.0, 4.0]);
+    let y = bytes(&[1f32; 4]);
+    let result = compilation.execute_compute(&[&bytes(&[4i32]), &bytes(&[2f32]), &x, &y])?;
+
+    assert_eq!(result.len(), 4);
+    assert_eq!(result[2], x);
+    assert_eq!(result[3], bytes(&[3f32, 5.0, 7.0, 9.0]));
+    return Ok(());
+}
+
+#[test]
+fn execute_errors() -> color_eyre::Result<()> {
+    let compilation = saxpy()?;
+    let Err(error) = compilation.execute_compute(&[&[]]) else {
+        panic!("the buffer count must match");
+    };
+    assert!(format!("{error}").contains("4 buffers"), "{error}");
+
+    let spirv = Compilation::from_spirv(TargetPlatform::SPV_1_3, compilation.words()?)?;
+    assert!(spirv.execute_compute(&[]).is_err());
+    return Ok(());
+}
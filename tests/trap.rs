@@ -0,0 +1,190 @@
+mod common;
+
+use rspirv::spirv::ExecutionModel;
+use wasm2spirv::{
+    config::{TrapMode, WasmFeatures},
+    diagnostics::PanicMessage,
+    fg::function::ExecutionMode,
+    Compilation,
+};
+
+const TRAP: &str = r#"
+(module
+  (func (param i32) (result i32)
+    block
+      local.get 0
+      i32.eqz
+      br_if 0
+      unreachable
+    end
+    local.get 0))
+"#;
+
+// A Rust `assert!`, with its message and `core::panic::Location` in the data segments
+const PANIC: &str = r#"
+(module
+  (memory 1)
+  (data (i32.const 1024) "assertion failed: x < 10src/lib.rs")
+  (data (i32.const 2048) "\18\04\00\00\0a\00\00\00\03\00\00\00\05\00\00\00")
+  (func $panic (param i32 i32 i32)
+    unreachable)
+  (func (param i32) (result i32)
+    block
+      local.get 0
+      i32.const 10
+      i32.lt_u
+      br_if 0
+      i32.const 1024
+      i32.const 24
+      i32.const 2048
+      call $panic
+      unreachable
+    end
+    local.get 0))
+"#;
+
+// The `catch` handler can't be reached, since throwing traps
+const EXCEPTION: &str = r#"
+(module
+  (tag $error (param i32))
+  (func (param i32) (result i32)
+    try (result i32)
+      local.get 0
+      i32.eqz
+      if
+        local.get 0
+        throw $error
+      end
+      local.get 0
+    catch $error
+      i32.const 1
+      i32.add
+    end))
+"#;
+
+fn compile(trap_mode: TrapMode) -> color_eyre::Result<String> {
+    return Ok(compile_wat(TRAP, trap_mode)?.into_assembly()?);
+}
+
+fn compile_wat(wat: &str, trap_mode: TrapMode) -> color_eyre::Result<Compilation> {
+    return compile_with_features(wat, trap_mode, WasmFeatures::default());
+}
+
+fn compile_with_features(
+    wat: &str,
+    trap_mode: TrapMode,
+    features: WasmFeatures,
+) -> color_eyre::Result<Compilation> {
+    let mut config = common::vulkan_builder()?;
+    config.set_trap_mode(trap_mode).set_features(features);
+
+    let wasm = wat::parse_str(wat)?;
+    return Ok(Compilation::new(config.build()?, &wasm)?);
+}
+
+#[test]
+fn unreachable() -> color_eyre::Result<()> {
+    assert!(compile(TrapMode::Unreachable)?.contains("OpUnreachable"));
+    return Ok(());
+}
+
+#[test]
+fn infinite_loop() -> color_eyre::Result<()> {
+    assert!(compile(TrapMode::InfiniteLoop)?.contains("OpLoopMerge"));
+    return Ok(());
+}
+
+#[test]
+fn flag() -> color_eyre::Result<()> {
+    let assembly = compile(TrapMode::Flag { set: 1, binding: 2 })?;
+    assert!(assembly.contains("DescriptorSet 1"));
+    assert!(assembly.contains("Binding 2"));
+    assert!(assembly.contains("OpStore"));
+    assert!(!assembly.contains("OpUnreachable"));
+    return Ok(());
+}
+
+#[test]
+fn terminate_invocation() -> color_eyre::Result<()> {
+    let assembly = compile(TrapMode::TerminateInvocation)?;
+    assert!(assembly.contains("OpTerminateInvocation"));
+    assert!(assembly.contains("SPV_KHR_terminate_invocation"));
+    return Ok(());
+}
+
+#[test]
+fn terminate_compute_invocation() -> color_eyre::Result<()> {
+    let mut config = common::vulkan_builder()?;
+    config.set_trap_mode(TrapMode::TerminateInvocation);
+    config
+        .function(0)
+        .set_entry_point(ExecutionModel::GLCompute)?
+        .add_exec_mode(ExecutionMode::LocalSize(1, 1, 1))?
+        .build();
+
+    // Only fragment shaders can be terminated
+    let wat = TRAP.replacen(
+        "(module",
+        "(module\n  (func (export \"main\") i32.const 0 call 1 drop)",
+        1,
+    );
+    let assembly = Compilation::new(config.build()?, &wat::parse_str(wat)?)?.into_assembly()?;
+    assert!(!assembly.contains("OpTerminateInvocation"));
+    assert!(assembly.contains("OpUnreachable"));
+    return Ok(());
+}
+
+#[test]
+fn demote_to_helper_invocation() -> color_eyre::Result<()> {
+    let assembly = compile(TrapMode::DemoteToHelperInvocation)?;
+    assert!(assembly.contains("OpDemoteToHelperInvocation"));
+    assert!(assembly.contains("SPV_EXT_demote_to_helper_invocation"));
+    assert!(!assembly.contains("OpUnreachable"));
+    return Ok(());
+}
+
+#[test]
+fn panic_messages() -> color_eyre::Result<()> {
+    let compilation = compile_wat(PANIC, TrapMode::Flag { set: 0, binding: 0 })?;
+    assert_eq!(
+        compilation.panic_messages(),
+        [PanicMessage {
+            function: 1,
+            message: Some(String::from("assertion failed: x < 10")),
+            location: Some(String::from("src/lib.rs:3:5")),
+        }]
+    );
+
+    // The panicking function writes the generic code, and its caller the code of the panic
+    let assembly = compilation.assembly()?;
+    assert!(assembly
+        .lines()
+        .any(|x| x.contains("OpConstant") && x.ends_with(" 2")));
+
+    // Traps without a call before them aren't panics
+    assert!(compile_wat(TRAP, TrapMode::Unreachable)?
+        .panic_messages()
+        .is_empty());
+    return Ok(());
+}
+
+#[test]
+fn exceptions() -> color_eyre::Result<()> {
+    let error = match compile_wat(EXCEPTION, TrapMode::Unreachable) {
+        Ok(_) => panic!("the `exceptions` proposal isn't enabled"),
+        Err(e) => e,
+    };
+    assert!(error.to_string().contains("`exceptions`"), "{error:?}");
+
+    let features = WasmFeatures {
+        exceptions: true,
+        ..Default::default()
+    };
+    let compilation =
+        compile_with_features(EXCEPTION, TrapMode::Flag { set: 0, binding: 0 }, features)?;
+    let assembly = compilation.assembly()?;
+    assert!(assembly.contains("OpStore"));
+    assert!(!assembly.contains("OpUnreachable"));
+    assert!(!assembly.contains("OpIAdd"));
+    return Ok(());
+}
@@ -0,0 +1,91 @@
+mod common;
+
+use wasm2spirv::Compilation;
+
+const WAT: &str = r#"
+(module
+  (func (export "main") (param f32 i32)
+    local.get 1
+    local.get 0
+    f32.store)
+  (memory 1))
+"#;
+
+fn compile(vulkan: &str, uniform: serde_json::Value) -> color_eyre::Result<Compilation> {
+    let storage_class = match vulkan {
+        "1.0" => "Uniform",
+        _ => "StorageBuffer",
+    };
+
+    let mut entry_point = common::compute_shader();
+    entry_point["params"] = serde_json::json!({
+        "0": uniform,
+        "1": common::buffer("f32", storage_class, 1)
+    });
+
+    let mut config = common::vulkan();
+    config["platform"]["vulkan"] = vulkan.into();
+    config["functions"]["0"] = entry_point;
+    return common::compile(config, WAT);
+}
+
+fn uniform(ty: serde_json::Value, storage_class: &str) -> serde_json::Value {
+    return serde_json::json!({
+        "type": ty,
+        "kind": {
+            "descriptor_set": {
+                "storage_class": storage_class,
+                "set": 0,
+                "binding": 0,
+                "buffer": "uniform"
+            }
+        }
+    });
+}
+
+#[test]
+fn uniform_buffer() -> color_eyre::Result<()> {
+    for vulkan in ["1.0", "1.1"] {
+        let assembly = compile(vulkan, uniform("f32".into(), "Uniform"))?.into_assembly()?;
+        assert!(assembly
+            .lines()
+            .any(|x| x.starts_with("OpDecorate") && x.ends_with(" Block")));
+        assert!(assembly
+            .lines()
+            .any(|x| x.contains("OpVariable") && x.ends_with(" Uniform")));
+    }
+
+    // Storage buffers of the same type don't share the uniform buffer's structure
+    let assembly = compile("1.0", uniform("f32".into(), "Uniform"))?.into_assembly()?;
+    assert!(assembly
+        .lines()
+        .any(|x| x.starts_with("OpDecorate") && x.ends_with(" BufferBlock")));
+
+    return Ok(());
+}
+
+#[test]
+fn invalid_uniform_buffer() -> color_eyre::Result<()> {
+    // Wrong storage class
+    assert!(compile("1.1", uniform("f32".into(), "StorageBuffer")).is_err());
+
+    // vec2 members must be aligned to 8 bytes
+    let misaligned = serde_json::json!({
+        "Struct": [
+            { "name": "a", "type": "f32", "offset": 0 },
+            { "name": "b", "type": { "Vector": ["f32", 2] }, "offset": 4 }
+        ]
+    });
+    assert!(compile("1.1", uniform(misaligned, "Uniform")).is_err());
+
+    // Larger than the guaranteed uniform buffer range
+    let large = serde_json::json!({
+        "Struct": [
+            { "name": "a", "type": "f32", "offset": 0 },
+            { "name": "b", "type": "f32", "offset": 16384 }
+        ]
+    });
+    assert!(compile("1.1", uniform(large, "Uniform")).is_err());
+
+    return Ok(());
+}
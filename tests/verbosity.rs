@@ -0,0 +1,97 @@
+mod common;
+
+use std::{
+    io::Write,
+    sync::{Arc, Mutex},
+};
+use wasm2spirv::config::Verbosity;
+
+/// Log output of the subscriber
+#[derive(Clone, Default)]
+struct Logs(Arc<Mutex<Vec<u8>>>);
+
+impl Write for Logs {
+    fn write(&mut self, buf: &[u8]) -> std::io::Result<usize> {
+        self.0.lock().unwrap().extend_from_slice(buf);
+        return Ok(buf.len());
+    }
+
+    fn flush(&mut self) -> std::io::Result<()> {
+        return Ok(());
+    }
+}
+
+/// Compiles a loop with a nested selection, returning everything logged while doing so
+fn compile(verbosity: Verbosity) -> color_eyre::Result<String> {
+    let wat = r#"(module
+  (func (export "main") (param i32)
+    (local $i i32)
+    block $exit
+      loop $continue
+        local.get $i
+        i32.const 8
+        i32.ge_u
+        br_if $exit
+        local.get $i
+        i32.const 2
+        i32.rem_u
+        if
+          local.get 0
+          local.get $i
+          i32.store
+        end
+        local.get $i
+        i32.const 1
+        i32.add
+        local.set $i
+        br $continue
+      end
+    end)
+  (memory 1))"#;
+
+    let mut config = common::config(0, "i32");
+    config["verbosity"] = serde_json::to_value(verbosity)?;
+
+    let logs = Logs::default();
+    let writer = logs.clone();
+    let subscriber = tracing_subscriber::fmt()
+        .with_max_level(tracing::Level::DEBUG)
+        .with_ansi(false)
+        .with_writer(move || writer.clone())
+        .finish();
+
+    tracing::subscriber::with_default(subscriber, || -> color_eyre::Result<()> {
+        let compilation = common::try_compile(config, wat)??;
+        let _ = compilation.words()?;
+        return Ok(());
+    })?;
+
+    let logs = logs.0.lock().unwrap().clone();
+    return Ok(String::from_utf8(logs)?);
+}
+
+#[test]
+fn quiet() -> color_eyre::Result<()> {
+    let logs = compile(Verbosity::Quiet)?;
+    assert!(!logs.contains("function"), "{logs}");
+    assert!(!logs.contains("merges into"), "{logs}");
+    return Ok(());
+}
+
+#[test]
+fn functions() -> color_eyre::Result<()> {
+    let logs = compile(Verbosity::Functions)?;
+    assert!(!logs.contains("merges into"), "{logs}");
+    return Ok(());
+}
+
+#[test]
+fn operators() -> color_eyre::Result<()> {
+    let logs = compile(Verbosity::Operators)?;
+    assert!(logs.contains("build_function"), "{logs}");
+    assert!(logs.contains("block"), "{logs}");
+    assert!(logs.contains("Loop"), "{logs}");
+    assert!(logs.contains("Selection"), "{logs}");
+    assert!(logs.contains("merges into"), "{logs}");
+    return Ok(());
+}
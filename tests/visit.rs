@@ -0,0 +1,129 @@
+mod common;
+
+use rspirv::spirv::StorageClass;
+use std::{collections::HashSet, rc::Rc};
+use wasm2spirv::{
+    config::Config,
+    decorator::VariableDecorator,
+    fg::{
+        module::ModuleBuilder,
+        values::{
+            integer::{Integer, IntegerSource},
+            pointer::Pointer,
+        },
+        visit::{rewrite_module, walk_integer, walk_module, Rewriter, Visitor},
+        Operation,
+    },
+    r#type::{PointerSize, ScalarType},
+    Compilation,
+};
+
+const WAT: &str = r#"
+(module
+  (func (export "main") (param i32)
+    local.get 0
+    local.get 0
+    i32.load
+    local.get 0
+    i32.load offset=4
+    i32.add
+    i32.store)
+  (memory 1))
+"#;
+
+fn config() -> color_eyre::Result<Config> {
+    let config = common::config(0, "i32");
+
+    return Ok(serde_json::from_value(config)?);
+}
+
+#[derive(Default)]
+struct Loads {
+    count: usize,
+    visited: HashSet<*const ()>,
+}
+
+impl Visitor for Loads {
+    fn visited(&mut self) -> &mut HashSet<*const ()> {
+        return &mut self.visited;
+    }
+
+    fn visit_integer(&mut self, integer: &Rc<Integer>) {
+        if matches!(integer.source, IntegerSource::Loaded { .. }) {
+            self.count += 1;
+        }
+        walk_integer(self, integer)
+    }
+}
+
+/// Writes a flag before every return
+struct Instrument(Rc<Pointer>);
+
+impl Rewriter for Instrument {
+    fn rewrite_operation(&mut self, op: &Operation) -> Option<Vec<Operation>> {
+        if !matches!(op, Operation::Return { .. }) {
+            return None;
+        }
+
+        let flag = Operation::Store {
+            target: self.0.clone(),
+            value: Integer::new_constant_u32(1).into(),
+            log2_alignment: None,
+        };
+        return Some(vec![flag, op.clone()]);
+    }
+}
+
+#[test]
+fn visit_loads() -> color_eyre::Result<()> {
+    let bytes = wat::parse_str(WAT)?;
+    let module = ModuleBuilder::new(config()?, &bytes)?;
+
+    let mut loads = Loads::default();
+    walk_module(&mut loads, &module);
+    assert_eq!(loads.count, 2);
+    return Ok(());
+}
+
+#[test]
+fn visit_shared_values_once() -> color_eyre::Result<()> {
+    // The sum of both loads is stored into the local and the memory
+    let wat = WAT.replace("    i32.add\n", "    i32.add\n    local.tee 1\n");
+    assert_ne!(wat, WAT);
+    let wat = wat.replace("(param i32)", "(param i32) (local i32)");
+
+    let bytes = wat::parse_str(wat)?;
+    let module = ModuleBuilder::new(config()?, &bytes)?;
+
+    let mut loads = Loads::default();
+    walk_module(&mut loads, &module);
+    assert_eq!(loads.count, 2);
+    return Ok(());
+}
+
+#[test]
+fn rewrite_returns() -> color_eyre::Result<()> {
+    let bytes = wat::parse_str(WAT)?;
+    let mut module = ModuleBuilder::new(config()?, &bytes)?;
+
+    let flag = Rc::new(Pointer::new_variable(
+        PointerSize::Skinny,
+        StorageClass::StorageBuffer,
+        ScalarType::I32,
+        None,
+        [
+            VariableDecorator::DesctiptorSet(0),
+            VariableDecorator::Binding(1),
+        ],
+    ));
+    rewrite_module(&mut Instrument(flag), &mut module);
+
+    let compilation = Compilation::from_builder(module)?;
+    #[cfg(feature = "spvt-validate")]
+    compilation.validate()?;
+
+    let assembly = compilation.into_assembly()?;
+    assert!(assembly.contains("Binding 1"));
+    assert_eq!(assembly.matches("OpStore").count(), 2);
+    return Ok(());
+}